@@ -0,0 +1,150 @@
+// bitmap_font.rs - Small built-in font for text printed onto the page
+// A 5x7 pixel font covering digits, letters (shown as capitals) and common
+// punctuation, so footers can be rasterized without a font file. The canvas
+// preview draws the same cells, so it matches the printed output.
+
+use image::{Rgba, RgbaImage};
+
+/// Rows in a glyph
+pub const GLYPH_ROWS: u32 = 7;
+
+/// Columns taken by one character, including the one-column gap after it
+pub const ADVANCE: u32 = 6;
+
+/// Glyph rows, top first; bit 4 is the leftmost column
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '&' => [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        // Anything the font doesn't cover
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// Width of `text` in glyph columns, without a trailing gap
+pub fn text_columns(text: &str) -> u32 {
+    (text.chars().count() as u32 * ADVANCE).saturating_sub(1)
+}
+
+/// Width of `text` drawn `height` tall, in the same unit as `height`
+pub fn text_width(text: &str, height: f32) -> f32 {
+    text_columns(text) as f32 * height / GLYPH_ROWS as f32
+}
+
+/// Lit cells of `text` as (column, row), counted from its top-left corner
+pub fn cells(text: &str) -> impl Iterator<Item = (u32, u32)> + '_ {
+    text.chars().enumerate().flat_map(|(i, c)| {
+        let rows = glyph(c);
+        (0..GLYPH_ROWS).flat_map(move |row| {
+            (0..5u32)
+                .filter(move |col| rows[row as usize] & (0x10 >> col) != 0)
+                .map(move |col| (i as u32 * ADVANCE + col, row))
+        })
+    })
+}
+
+/// Draw `text` with its top-left corner at (x, y), `height` pixels tall.
+/// Cells outside the image are clipped.
+pub fn draw_text(img: &mut RgbaImage, text: &str, x: f32, y: f32, height: f32, color: Rgba<u8>) {
+    let cell = height / GLYPH_ROWS as f32;
+    let (width, image_height) = img.dimensions();
+    // Cell edges are rounded so neighbouring cells share edges without gaps
+    let span = |start: f32, index: u32, limit: u32| {
+        let from = (start + index as f32 * cell).round().max(0.0) as u32;
+        let to = (start + (index + 1) as f32 * cell).round().max(from as f32 + 1.0) as u32;
+        from.min(limit)..to.min(limit)
+    };
+    for (col, row) in cells(text) {
+        for py in span(y, row, image_height) {
+            for px in span(x, col, width) {
+                img.put_pixel(px, py, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths() {
+        assert_eq!(text_columns(""), 0);
+        assert_eq!(text_columns("1"), 5);
+        assert_eq!(text_columns("12"), 11);
+        assert!((text_width("12", 7.0) - 11.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lowercase_matches_capitals() {
+        assert_eq!(cells("page").collect::<Vec<_>>(), cells("PAGE").collect::<Vec<_>>());
+        assert_eq!(cells(" ").count(), 0);
+        // Unknown characters still print something
+        assert!(cells("é").count() > 0);
+    }
+
+    #[test]
+    fn test_draw_text_scales_and_clips() {
+        let mut img = RgbaImage::from_pixel(40, 20, Rgba([255, 255, 255, 255]));
+        draw_text(&mut img, "-", 2.0, 0.0, 14.0, Rgba([0, 0, 0, 255]));
+        // The dash is row 3 of 7, so two pixels tall at 14 px
+        let dark: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[0] == 0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(dark.len(), 10 * 2);
+        assert!(dark.iter().all(|&(x, y)| (2..12).contains(&x) && (6..8).contains(&y)));
+
+        // Drawing partly off the image doesn't panic
+        draw_text(&mut img, "88", 30.0, 15.0, 14.0, Rgba([0, 0, 0, 255]));
+    }
+}
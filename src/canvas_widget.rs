@@ -1,32 +1,112 @@
 // canvas_widget.rs - Canvas widget implementation with image rendering
 // Updated for Iced 0.13 with draw_image support
 
-use crate::layout::{Layout, PlacedImage};
+use crate::bitmap_font;
+use crate::config::{CanvasQuality, ViewOptions};
+use crate::document::Spread;
+use crate::layout::{Crop, Layout, LayoutElement, PlacedImage, PlacedRect, SequenceNumbers, Toning};
+use crate::printing::{apply_toning, page_toning, rotate_about_center};
+pub use crate::layout::resize::ResizeHandle;
+use iced::keyboard;
 use iced::mouse::{self, Cursor};
-use iced::widget::canvas::{self, Cache, Frame, Geometry, Image, Path, Program, Stroke, Text};
+use iced::widget::canvas::{self, Cache, Frame, Geometry, Image, LineDash, Path, Program, Stroke, Text};
 use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+/// Resize handle size (pixels) for mouse use
+pub const DEFAULT_HANDLE_SIZE_PX: f32 = 10.0;
+/// Resize handle size (pixels) for touch and pen input
+pub const TOUCH_HANDLE_SIZE_PX: f32 = 20.0;
+/// Distance (pixels) from the top edge of the selection to the rotation handle
+const ROTATE_HANDLE_OFFSET_PX: f32 = 24.0;
+const MIN_HANDLE_SIZE_PX: f32 = 4.0;
+const MAX_HANDLE_SIZE_PX: f32 = 48.0;
+/// Longest edge (pixels) of the copies of source images kept for drawing
+pub const CANVAS_PREVIEW_MAX_PX: u32 = 2400;
+/// Fill of images drawn without a preview: still loading, or in low GPU mode
+const PLACEHOLDER_FILL: Color = Color { r: 0.85, g: 0.90, b: 1.0, a: 0.8 };
+/// Page labels and the fold line of a two-page spread
+const SPREAD_INK: Color = Color { r: 0.45, g: 0.45, b: 0.5, a: 1.0 };
+/// Border of images an arrangement found no room for
+const UNPLACED_BORDER: Color = Color { r: 0.85, g: 0.15, b: 0.15, a: 1.0 };
+/// Screen pixels per millimeter at 100% zoom
+const PIXELS_PER_MM: f32 = 96.0 / 25.4;
+/// Closest the snap grid lines are drawn (pixels) before they are hidden
+const MIN_GRID_STEP_PX: f32 = 6.0;
+/// Space (pixels) the preview keeps around the page
+pub const PAGE_PADDING_PX: f32 = 40.0;
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 5.0;
+
+/// Which page dimension a fit zoom makes fill the preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoomFit {
+    /// The whole page
+    Page,
+    /// The page width; taller pages scroll vertically
+    Width,
+    /// The page height; wider pages such as panoramas scroll sideways
+    Height,
+}
+
+impl std::fmt::Display for ZoomFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZoomFit::Page => write!(f, "Fit"),
+            ZoomFit::Width => write!(f, "Fit Width"),
+            ZoomFit::Height => write!(f, "Fit Height"),
+        }
+    }
+}
+
+/// Zoom at which the page, with its padding, fills the viewport (pixels)
+/// along the dimension(s) chosen by `fit`
+pub fn fit_zoom(fit: ZoomFit, viewport: (f32, f32), page_mm: (f32, f32)) -> f32 {
+    let zoom_for = |available: f32, mm: f32| (available - PAGE_PADDING_PX).max(1.0) / (mm.max(1.0) * PIXELS_PER_MM);
+    let width = zoom_for(viewport.0, page_mm.0);
+    let height = zoom_for(viewport.1, page_mm.1);
+    let zoom = match fit {
+        ZoomFit::Page => width.min(height),
+        ZoomFit::Width => width,
+        ZoomFit::Height => height,
+    };
+    zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
 /// Cache key that includes transform parameters
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct TransformKey {
     path: PathBuf,
-    rotation_degrees: i32,  // Rounded to int for hash
+    rotation_centidegrees: i32, // Hundredths of a degree, so small tilts get their own entry
     flip_horizontal: bool,
     flip_vertical: bool,
     opacity_percent: u8,    // 0-100 for hash
+    crop: Option<[u32; 4]>, // Crop fractions in 1/10000ths for hash
+    tilt_aspect: u32,       // Frame width / height in 1/1000ths when tilted, else 0
+    toning: Option<Toning>, // Page toning in Black and White
+    draft: bool,            // Quarter-resolution draft preview
 }
 
 impl TransformKey {
-    fn from_placed_image(img: &PlacedImage) -> Self {
+    fn from_placed_image(img: &PlacedImage, toning: Option<Toning>, draft: bool) -> Self {
         Self {
             path: img.path.clone(),
-            rotation_degrees: (img.rotation_degrees as i32) % 360,
+            rotation_centidegrees: (img.rotation_degrees * 100.0).round() as i32 % 36000,
             flip_horizontal: img.flip_horizontal,
             flip_vertical: img.flip_vertical,
             opacity_percent: (img.opacity * 100.0) as u8,
+            crop: img.crop.map(|c| {
+                [c.x, c.y, c.width, c.height].map(|v| (v * 10000.0).round() as u32)
+            }),
+            tilt_aspect: if img.frame_angle_degrees() != 0.0 {
+                (img.width_mm / img.height_mm * 1000.0).round() as u32
+            } else {
+                0
+            },
+            toning,
+            draft,
         }
     }
 }
@@ -44,32 +124,43 @@ impl ImageCache {
         }
     }
 
-    /// Get or create a transformed image handle for the given placed image
-    /// Uses source_cache to avoid reloading images from disk
+    /// Get or create a transformed image handle for the given placed image,
+    /// toned like the printed page when `toning` is set (see `page_toning`).
+    /// Draft quality skips opacity and toning. Only already-decoded sources
+    /// are used; `None` until the source is ready.
     pub fn get_transformed_handle(
         &mut self, 
         img: &PlacedImage, 
-        source_cache: &mut SourceImageCache
+        source_cache: &SourceImageCache,
+        toning: Option<Toning>,
+        quality: CanvasQuality,
     ) -> Option<iced::widget::image::Handle> {
-        let key = TransformKey::from_placed_image(img);
+        let draft = quality == CanvasQuality::Draft;
+        let toning = toning.filter(|_| !draft);
+        let key = TransformKey::from_placed_image(img, toning, draft);
         
         if let Some(handle) = self.cache.get(&key) {
             return Some(handle.clone());
         }
 
-        // Get source image from cache (or load it)
-        let source = source_cache.get_or_load(&img.path)?;
-
-        // Apply rotation (90° increments)
-        let rotation_normalized = ((img.rotation_degrees % 360.0) + 360.0) % 360.0;
-        let rotated = if rotation_normalized >= 85.0 && rotation_normalized <= 95.0 {
-            source.rotate90()
-        } else if rotation_normalized >= 175.0 && rotation_normalized <= 185.0 {
-            source.rotate180()
-        } else if rotation_normalized >= 265.0 && rotation_normalized <= 275.0 {
-            source.rotate270()
+        // Get the decoded source; never read from disk here
+        let source = source_cache.get(&img.path)?;
+        // Draft: a quarter-resolution thumbnail, scaled the cheapest way
+        let thumbnail;
+        let source = if draft {
+            let (width, height) = ((source.width() / 4).max(1), (source.height() / 4).max(1));
+            thumbnail = source.resize_exact(width, height, image::imageops::FilterType::Nearest);
+            &thumbnail
         } else {
-            source.clone()
+            source
+        };
+
+        // Apply the quarter turns; any tilt comes last
+        let rotated = match img.quarter_turns() {
+            1 => source.rotate90(),
+            2 => source.rotate180(),
+            3 => source.rotate270(),
+            _ => source.clone(),
         };
 
         // Apply flips
@@ -83,15 +174,41 @@ impl ImageCache {
             rotated
         };
 
+        // Apply crop
+        let cropped = match img.crop {
+            Some(crop) => {
+                let (x, y, w, h) = crop.pixel_rect(flipped.width(), flipped.height());
+                flipped.crop_imm(x, y, w, h)
+            }
+            None => flipped,
+        };
+
         // Apply opacity
-        let mut rgba = flipped.to_rgba8();
-        if img.opacity < 1.0 {
+        let mut rgba = cropped.to_rgba8();
+        if img.opacity < 1.0 && !draft {
             let opacity_factor = img.opacity.clamp(0.0, 1.0);
             for pixel in rgba.pixels_mut() {
                 pixel[3] = (pixel[3] as f32 * opacity_factor) as u8;
             }
         }
 
+        // Tilt last, as printing does. The handle then covers the tilted
+        // frame's bounding box, so stretch to the frame's shape first.
+        let tilt = img.frame_angle_degrees();
+        if tilt != 0.0 {
+            let (width, height) = rgba.dimensions();
+            let frame_height = (width as f32 * img.height_mm / img.width_mm).round().max(1.0) as u32;
+            if frame_height != height {
+                rgba = image::imageops::resize(&rgba, width, frame_height, image::imageops::FilterType::Triangle);
+            }
+            rgba = rotate_about_center(&rgba, tilt);
+        }
+
+        // The page color mode comes after the image's own adjustments, as in print
+        if let Some(toning) = toning {
+            apply_toning(&mut rgba, toning);
+        }
+
         // Create handle from RGBA pixels
         let (width, height) = rgba.dimensions();
         let handle = iced::widget::image::Handle::from_rgba(
@@ -105,77 +222,335 @@ impl ImageCache {
     }
     
     /// Clear the cache (e.g., when images change)
-    #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.cache.clear();
     }
 }
 
 /// Messages that can be sent from the canvas
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CanvasMessage {
-    SelectImage(String),
-    DeselectAll,
     ImageMoved(String, f32, f32),
     ImageResized(String, f32, f32),
+    /// A press and release without dragging, at this point in mm; selects
+    /// the element there or clears the selection
     CanvasClicked(f32, f32),
+    /// A press on an element turned into a drag; the point (mm) is where
+    /// the button went down
+    StartMove(String, f32, f32),
     MouseMoved(f32, f32),
     MouseReleased,
     /// Start resizing from a specific handle
     StartResize(String, ResizeHandle),
+    /// Start turning an image with the handle above its selection box
+    StartRotate(String),
+    /// Enter crop mode for the selected image (Enter over the canvas)
+    EnterCropMode,
+    /// Start dragging a crop handle, or the whole crop rectangle when `None`
+    StartCropDrag(Option<ResizeHandle>),
+    /// Keep the edited crop and leave crop mode (Enter or a click outside)
+    CommitCrop,
+    /// Restore the previous crop and leave crop mode (Esc)
+    CancelCrop,
+    /// The pointer moved off the canvas
+    CursorLeft,
+    /// A drag on empty page ended; select what lies inside this rectangle
+    /// (x, y, width, height in mm)
+    SelectArea(f32, f32, f32, f32),
+}
+
+impl CanvasMessage {
+    /// Whether the message changes the layout rather than selecting or
+    /// tracking the pointer; view-only mode drops these
+    pub fn edits_layout(&self) -> bool {
+        matches!(
+            self,
+            CanvasMessage::ImageMoved(..)
+                | CanvasMessage::ImageResized(..)
+                | CanvasMessage::StartMove(..)
+                | CanvasMessage::StartResize(..)
+                | CanvasMessage::StartRotate(_)
+                | CanvasMessage::EnterCropMode
+                | CanvasMessage::StartCropDrag(_)
+        )
+    }
+}
+
+/// Pointer travel in pixels before a press on the page becomes a drag;
+/// anything less is a click
+pub const DRAG_THRESHOLD_PX: f32 = 3.0;
+
+/// Outline of selected elements other than the primary one
+fn secondary_selection_stroke() -> Stroke<'static> {
+    Stroke::default()
+        .with_width(2.0)
+        .with_color(Color::from_rgb(0.45, 0.72, 1.0))
 }
 
-/// Which resize handle is being dragged
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ResizeHandle {
-    TopLeft,
-    TopRight,
-    BottomLeft,
-    BottomRight,
-    Top,
-    Bottom,
-    Left,
-    Right,
+/// Per-widget state kept by iced between canvas events
+#[derive(Debug, Default)]
+pub struct CanvasState {
+    cursor_over: bool,
+    press: Option<Press>,
+}
+
+/// A left-button press on the page that is still a click or has become a drag
+#[derive(Debug, Clone, PartialEq)]
+struct Press {
+    /// Where the button went down, in canvas pixels
+    origin: Point,
+    /// Where the pointer is now, in canvas pixels
+    current: Point,
+    /// Element under the pointer at that moment
+    element: Option<String>,
+    dragging: bool,
+}
+
+impl Press {
+    fn new(origin: Point, element: Option<String>) -> Self {
+        Self { origin, current: origin, element, dragging: false }
+    }
+
+    /// The selection band (canvas pixels) of a drag that started on empty
+    /// page or a locked element
+    fn band(&self) -> Option<Rectangle> {
+        (self.dragging && self.element.is_none()).then(|| {
+            Rectangle::new(
+                Point::new(self.origin.x.min(self.current.x), self.origin.y.min(self.current.y)),
+                Size::new((self.current.x - self.origin.x).abs(), (self.current.y - self.origin.y).abs()),
+            )
+        })
+    }
+
+    /// Follow the pointer; true only for the move that first carries it
+    /// more than DRAG_THRESHOLD_PX from the origin
+    fn moved_to(&mut self, position: Point) -> bool {
+        self.current = position;
+        if self.dragging {
+            return false;
+        }
+        self.dragging = self.origin.distance(position) > DRAG_THRESHOLD_PX;
+        self.dragging
+    }
+}
+
+/// An interactive crop session. The whole image is drawn ghosted in `frame`
+/// and the crop rectangle is edited on top of it; the placed rect is untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropEdit {
+    pub image_id: String,
+    /// Where the uncropped image is drawn: x, y, width, height in mm
+    pub frame: (f32, f32, f32, f32),
+    pub crop: Crop,
+    /// Crop to restore on cancel
+    pub original: Option<Crop>,
+}
+
+impl CropEdit {
+    /// Start editing an image's crop. The frame is placed so that the
+    /// current crop lands exactly on the image's placed rect.
+    pub fn new(img: &PlacedImage) -> Self {
+        let crop = img.crop.unwrap_or(Crop::FULL);
+        let width = img.width_mm / crop.width;
+        let height = img.height_mm / crop.height;
+        Self {
+            image_id: img.id.clone(),
+            frame: (img.x_mm - crop.x * width, img.y_mm - crop.y * height, width, height),
+            crop,
+            original: img.crop,
+        }
+    }
+
+    pub fn frame_size(&self) -> (f32, f32) {
+        (self.frame.2, self.frame.3)
+    }
+
+    /// The crop rectangle on the page (x, y, width, height in mm)
+    pub fn crop_rect(&self) -> (f32, f32, f32, f32) {
+        let (x, y, width, height) = self.frame;
+        (
+            x + self.crop.x * width,
+            y + self.crop.y * height,
+            self.crop.width * width,
+            self.crop.height * height,
+        )
+    }
+}
+
+/// Handle positions around a rectangle (pixels), corners first so they win hit tests
+fn handle_points(x: f32, y: f32, width: f32, height: f32) -> [(f32, f32, ResizeHandle); 8] {
+    [
+        (x, y, ResizeHandle::TopLeft),
+        (x + width, y, ResizeHandle::TopRight),
+        (x, y + height, ResizeHandle::BottomLeft),
+        (x + width, y + height, ResizeHandle::BottomRight),
+        (x + width / 2.0, y, ResizeHandle::Top),
+        (x + width / 2.0, y + height, ResizeHandle::Bottom),
+        (x, y + height / 2.0, ResizeHandle::Left),
+        (x + width, y + height / 2.0, ResizeHandle::Right),
+    ]
 }
 
 /// Cache for source images loaded from disk (to avoid repeated disk I/O)
 #[derive(Debug, Default)]
 pub struct SourceImageCache {
     cache: HashMap<PathBuf, image::DynamicImage>,
+    // Paths being decoded in the background; drawn as placeholders until ready
+    pending: HashSet<PathBuf>,
+    // Size and format details recorded when each image was decoded
+    info: HashMap<PathBuf, SourceInfo>,
+    // Paths that failed to decode, with the reason; not retried until removed
+    failed: HashMap<PathBuf, String>,
+}
+
+/// Memory and file details of a decoded source image, at full size
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceInfo {
+    pub width_px: u32,
+    pub height_px: u32,
+    /// Bytes the decoded pixels take before the canvas shrinks them
+    pub decoded_bytes: u64,
+    /// Size of the file on disk, if it could be read
+    pub file_bytes: Option<u64>,
+    /// File format from the extension, e.g. "JPEG"
+    pub format: Option<String>,
+}
+
+impl SourceInfo {
+    fn new(path: &PathBuf, image: &image::DynamicImage) -> Self {
+        Self {
+            width_px: image.width(),
+            height_px: image.height(),
+            decoded_bytes: image.as_bytes().len() as u64,
+            file_bytes: std::fs::metadata(path).ok().map(|m| m.len()),
+            format: image::ImageFormat::from_path(path)
+                .ok()
+                .map(|f| format!("{:?}", f).to_uppercase()),
+        }
+    }
+}
+
+/// A source image decoded for the canvas: the preview it draws and the
+/// details of the full-size file it was shrunk from
+#[derive(Debug, Clone)]
+pub struct CanvasSource {
+    pub image: image::DynamicImage,
+    pub info: SourceInfo,
+}
+
+impl CanvasSource {
+    /// Record the details of the full-size `image`, then shrink it to at
+    /// most `CANVAS_PREVIEW_MAX_PX` on its longest edge
+    pub fn new(path: &PathBuf, image: image::DynamicImage) -> Self {
+        let info = SourceInfo::new(path, &image);
+        Self { image: downscale_for_canvas(image), info }
+    }
 }
 
 impl SourceImageCache {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            pending: HashSet::new(),
+            info: HashMap::new(),
+            failed: HashMap::new(),
         }
     }
 
-    /// Get or load a source image from disk
-    pub fn get_or_load(&mut self, path: &PathBuf) -> Option<&image::DynamicImage> {
-        if !self.cache.contains_key(path) {
-            if path.exists() {
-                if let Ok(img) = image::open(path) {
-                    self.cache.insert(path.clone(), img);
-                }
+    /// A decoded source image. Never touches the disk: images are decoded
+    /// in the background and handed over with `insert_decoded`.
+    pub fn get(&self, path: &PathBuf) -> Option<&image::DynamicImage> {
+        self.cache.get(path)
+    }
+
+    pub fn contains(&self, path: &PathBuf) -> bool {
+        self.cache.contains_key(path)
+    }
+
+    pub fn is_pending(&self, path: &PathBuf) -> bool {
+        self.pending.contains(path)
+    }
+
+    pub fn has_failed(&self, path: &PathBuf) -> bool {
+        self.failed.contains_key(path)
+    }
+
+    /// Why the image at `path` couldn't be decoded
+    pub fn failure(&self, path: &PathBuf) -> Option<&str> {
+        self.failed.get(path).map(String::as_str)
+    }
+
+    /// Mark a path as being decoded elsewhere
+    pub fn mark_pending(&mut self, path: PathBuf) {
+        self.pending.insert(path);
+    }
+
+    /// Store a decoded image, or remember why decoding failed
+    pub fn insert_decoded(&mut self, path: PathBuf, source: Result<CanvasSource, String>) {
+        self.pending.remove(&path);
+        match source {
+            Ok(source) => {
+                self.failed.remove(&path);
+                self.info.insert(path.clone(), source.info);
+                self.cache.insert(path, source.image);
+            }
+            Err(reason) => {
+                self.failed.insert(path, reason);
             }
         }
-        self.cache.get(path)
+    }
+
+    /// Details recorded when the image at `path` was decoded
+    pub fn info(&self, path: &PathBuf) -> Option<&SourceInfo> {
+        self.info.get(path)
     }
 
     /// Remove an image from cache
     #[allow(dead_code)]
     pub fn remove(&mut self, path: &PathBuf) {
         self.cache.remove(path);
+        self.info.remove(path);
+        self.failed.remove(path);
     }
 
     /// Clear the entire cache
     #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.info.clear();
+        self.failed.clear();
+    }
+}
+
+/// `text` cut to at most `max_chars` characters, ending in "…" when cut
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        short.push('…');
+        short
     }
 }
 
+/// Decode a source image for the canvas and shrink it to at most
+/// `CANVAS_PREVIEW_MAX_PX` on its longest edge. Runs off the UI thread.
+pub fn prepare_canvas_source(path: &PathBuf) -> Result<CanvasSource, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?;
+    Ok(CanvasSource::new(path, image))
+}
+
+/// Shrink an image (never enlarge it) to fit `CANVAS_PREVIEW_MAX_PX`, keeping its aspect ratio
+fn downscale_for_canvas(image: image::DynamicImage) -> image::DynamicImage {
+    if image.width().max(image.height()) <= CANVAS_PREVIEW_MAX_PX {
+        return image;
+    }
+    image.resize(CANVAS_PREVIEW_MAX_PX, CANVAS_PREVIEW_MAX_PX, image::imageops::FilterType::Triangle)
+}
+
+/// A page drawn on the canvas: its rectangle and its printable area (mm)
+type Sheet = ((f32, f32, f32, f32), (f32, f32, f32, f32));
+
 /// The canvas widget for displaying and interacting with the layout
 pub struct LayoutCanvas {
     layout: Layout,
@@ -183,26 +558,211 @@ pub struct LayoutCanvas {
     cache: Cache,
     // Use RefCell for interior mutability to allow caching in draw()
     image_cache: RefCell<ImageCache>,
-    // Cache for source images loaded from disk
+    // Decoded source images, filled by background decodes (never during draw)
     source_cache: RefCell<SourceImageCache>,
+    // Workspace color drawn around the page
+    background: Color,
+    // Previous image bounds (mm) outlined on top of the cached layer
+    ghosts: Vec<(f32, f32, f32, f32)>,
+    // Images Auto Arrange found no room for, with their bounds (mm) then;
+    // each is flagged until it is moved or resized
+    unplaced: Vec<(String, (f32, f32, f32, f32))>,
+    // Corner handle size in pixels; edge handles and hit radius scale from it
+    handle_size: f32,
+    // Crop mode session, if one is active
+    crop_edit: Option<CropEdit>,
+    // Show a busy cursor while background analysis runs
+    busy: bool,
+    // Review mode: no handles and no editing interactions
+    view_only: bool,
+    // Placeholders with file names instead of image previews, for weak GPUs
+    low_gpu: bool,
+    // Overlays chosen in the View menu, and the grid spacing in mm
+    view: ViewOptions,
+    grid_mm: f32,
+    // Automatic copies of images printed more than once, how many of them
+    // didn't fit on the page and how many more sheets those print on
+    copies: Vec<PlacedImage>,
+    copies_left_out: usize,
+    overflow_sheets: usize,
+    // Two facing pages shown side by side, when the layout joins them
+    spread: Option<Spread>,
+    // The selected image is being turned with its rotation handle
+    rotating: bool,
 }
 
 impl LayoutCanvas {
     pub fn new(layout: Layout) -> Self {
+        let (copies, copies_left_out) = layout.duplicate_copies();
+        let overflow_sheets = if copies_left_out > 0 { layout.overflow_pages().len() } else { 0 };
         Self {
             layout,
             zoom: 1.0,
             cache: Cache::new(),
             image_cache: RefCell::new(ImageCache::new()),
             source_cache: RefCell::new(SourceImageCache::new()),
+            background: Color::from_rgb(0.5, 0.5, 0.5),
+            ghosts: Vec::new(),
+            unplaced: Vec::new(),
+            handle_size: DEFAULT_HANDLE_SIZE_PX,
+            crop_edit: None,
+            busy: false,
+            view_only: false,
+            low_gpu: false,
+            view: ViewOptions::default(),
+            grid_mm: 10.0,
+            copies,
+            copies_left_out,
+            overflow_sheets,
+            spread: None,
+            rotating: false,
+        }
+    }
+
+    /// Hide the resize handles and ignore handle and crop interactions
+    pub fn set_view_only(&mut self, view_only: bool) {
+        if self.view_only != view_only {
+            self.view_only = view_only;
+            self.cache.clear();
+        }
+    }
+
+    /// Draw images as labelled placeholders instead of previews, so no image
+    /// data is uploaded to the GPU. Editing works the same either way.
+    pub fn set_low_gpu(&mut self, low_gpu: bool) {
+        if self.low_gpu != low_gpu {
+            self.low_gpu = low_gpu;
+            self.image_cache.borrow_mut().clear();
+            self.cache.clear();
+        }
+    }
+
+    /// Whether previews are drawn as placeholders
+    #[allow(dead_code)]
+    pub fn low_gpu(&self) -> bool {
+        self.low_gpu
+    }
+
+    /// Choose the overlays to draw; grid lines are `grid_mm` millimetres apart
+    pub fn set_view_options(&mut self, view: ViewOptions, grid_mm: f32) {
+        if self.view != view || self.grid_mm != grid_mm {
+            self.view = view;
+            self.grid_mm = grid_mm;
+            self.cache.clear();
+        }
+    }
+
+    /// Show a busy cursor over the canvas while a background job runs
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    /// Enter, update or (with `None`) leave crop mode. Only entering or
+    /// leaving redraws the cached layer; crop changes draw on the overlay.
+    pub fn set_crop_edit(&mut self, edit: Option<CropEdit>) {
+        let image_changed = self.crop_edit.as_ref().map(|e| &e.image_id) != edit.as_ref().map(|e| &e.image_id);
+        self.crop_edit = edit;
+        if image_changed {
+            self.cache.clear();
+        }
+    }
+
+    /// Set the resize handle size in pixels, used for drawing and hit testing
+    pub fn set_handle_size(&mut self, size: f32) {
+        self.handle_size = size.clamp(MIN_HANDLE_SIZE_PX, MAX_HANDLE_SIZE_PX);
+        self.cache.clear();
+    }
+
+    /// Edge handles are drawn slightly smaller than the corners
+    fn edge_handle_size(&self) -> f32 {
+        self.handle_size * 0.8
+    }
+
+    /// How close (in pixels, per axis) a click must be to grab a handle
+    fn handle_hit_radius(&self) -> f32 {
+        self.handle_size * 0.8
+    }
+
+    /// Set the workspace color drawn behind the page
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+        self.cache.clear();
+    }
+
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Outline old image positions (x, y, width, height in mm); empty clears them
+    pub fn set_ghosts(&mut self, ghosts: Vec<(f32, f32, f32, f32)>) {
+        self.ghosts = ghosts;
+    }
+
+    /// Flag images that were left out of an arrangement (id and bounds in
+    /// mm) with a warning border; empty clears them
+    pub fn set_unplaced(&mut self, unplaced: Vec<(String, (f32, f32, f32, f32))>) {
+        self.unplaced = unplaced;
+        self.cache.clear();
+    }
+
+    /// Show the selected image's angle next to its rotation handle while it
+    /// is being turned; drawn on the overlay, so the cached layer is kept
+    pub fn set_rotating(&mut self, rotating: bool) {
+        self.rotating = rotating;
+    }
+
+    /// Where (pixels, left middle) and what to draw as the angle readout
+    /// while the selected image is being turned
+    pub fn rotate_readout(&self) -> Option<(Point, String)> {
+        if !self.rotating {
+            return None;
+        }
+        let img = self.layout.selected_image()?;
+        let (rx, ry) = self.rotate_handle_point(img);
+        Some((Point::new(rx + self.handle_size, ry), format!("{}°", img.rotation_degrees)))
+    }
+
+    /// Draw the layout as the two pages of a spread (see
+    /// `Document::spread_layout`), or with `None` as a single page
+    pub fn set_spread(&mut self, spread: Option<Spread>) {
+        if self.spread != spread {
+            self.spread = spread;
+            self.cache.clear();
+        }
+    }
+
+    /// Each page drawn, as its rectangle and printable area (mm)
+    fn sheets(&self) -> Vec<Sheet> {
+        let page = &self.layout.page;
+        match &self.spread {
+            Some(spread) => (0..2)
+                .map(|side| {
+                    let (x, y) = spread.on_canvas(side, 0.0, 0.0);
+                    let (width, height) = spread.page_sizes[side];
+                    ((x, y, width, height), spread.printable_areas[side])
+                })
+                .collect(),
+            None => vec![((0.0, 0.0, page.width_mm, page.height_mm), page.printable_area())],
         }
     }
 
     pub fn set_layout(&mut self, layout: Layout) {
+        (self.copies, self.copies_left_out) = layout.duplicate_copies();
+        self.overflow_sheets = if self.copies_left_out > 0 { layout.overflow_pages().len() } else { 0 };
         self.layout = layout;
         self.cache.clear();
     }
 
+    /// How many requested copies didn't fit on the page
+    pub fn copies_left_out(&self) -> usize {
+        self.copies_left_out
+    }
+
+    /// Extra sheets the copies that didn't fit print on
+    pub fn overflow_sheets(&self) -> usize {
+        self.overflow_sheets
+    }
+
     /// Update layout without clearing the render cache - for position/size changes during drag
     /// This is more efficient for interactive operations where only positions change
     #[allow(dead_code)]
@@ -213,22 +773,46 @@ impl LayoutCanvas {
         self.cache.clear(); // Still need to clear for now since positions affect rendering
     }
 
-    /// Update just the selected image's position without full layout update
+    /// Update just the selected image's (or color block's) position without full layout update
+    /// Locked images are left where they are.
     pub fn update_image_position(&mut self, id: &str, x: f32, y: f32) {
         if let Some(img) = self.layout.images.iter_mut().find(|i| i.id == id) {
+            if img.locked {
+                return;
+            }
             img.x_mm = x;
             img.y_mm = y;
+        } else if let Some(rect) = self.layout.get_rect_mut(id) {
+            rect.x_mm = x;
+            rect.y_mm = y;
         }
         self.cache.clear();
     }
 
-    /// Update just the selected image's size without full layout update  
+    /// Update just the selected image's (or color block's) size without full layout update
+    /// Locked images keep their position and size.
     pub fn update_image_bounds(&mut self, id: &str, x: f32, y: f32, w: f32, h: f32) {
         if let Some(img) = self.layout.images.iter_mut().find(|i| i.id == id) {
+            if img.locked {
+                return;
+            }
             img.x_mm = x;
             img.y_mm = y;
             img.width_mm = w;
             img.height_mm = h;
+        } else if let Some(rect) = self.layout.get_rect_mut(id) {
+            rect.x_mm = x;
+            rect.y_mm = y;
+            rect.width_mm = w;
+            rect.height_mm = h;
+        }
+        self.cache.clear();
+    }
+
+    /// Update just an image's crop during an interactive resize
+    pub fn update_image_crop(&mut self, id: &str, crop: Option<Crop>) {
+        if let Some(img) = self.layout.images.iter_mut().find(|i| i.id == id) {
+            img.crop = crop;
         }
         self.cache.clear();
     }
@@ -238,13 +822,64 @@ impl LayoutCanvas {
         self.source_cache.borrow_mut().remove(path);
     }
 
+    /// Why the image at `path` couldn't be decoded, if it failed
+    pub fn source_failure(&self, path: &PathBuf) -> Option<String> {
+        self.source_cache.borrow().failure(path).map(str::to_string)
+    }
+
+    /// Mark the layout's images that aren't cached yet as loading and
+    /// return their paths, so the caller can decode them in the background.
+    /// Images that already failed to decode are not retried.
+    pub fn mark_uncached_pending(&mut self) -> Vec<PathBuf> {
+        let mut source_cache = self.source_cache.borrow_mut();
+        let mut paths = Vec::new();
+        for img in &self.layout.images {
+            if !source_cache.contains(&img.path)
+                && !source_cache.is_pending(&img.path)
+                && !source_cache.has_failed(&img.path)
+            {
+                source_cache.mark_pending(img.path.clone());
+                paths.push(img.path.clone());
+            }
+        }
+        paths
+    }
+
+    /// Hand over an image decoded in the background and redraw
+    pub fn source_decoded(&mut self, path: PathBuf, source: Result<CanvasSource, String>) {
+        self.source_cache.borrow_mut().insert_decoded(path, source);
+        self.cache.clear();
+    }
+
+    /// Decode details for a source image, once it has been loaded
+    pub fn source_info(&self, path: &PathBuf) -> Option<SourceInfo> {
+        self.source_cache.borrow().info(path).cloned()
+    }
+
+    /// Memory the layout's source images take decoded at full size, as
+    /// printing decodes them; the canvas keeps smaller previews. Files not
+    /// yet decoded are counted as 8-bit RGBA at their pixel size.
+    pub fn source_memory_bytes(&self) -> u64 {
+        let source_cache = self.source_cache.borrow();
+        let mut seen = HashSet::new();
+        self.layout
+            .images
+            .iter()
+            .filter(|img| seen.insert(&img.path))
+            .map(|img| match source_cache.info(&img.path) {
+                Some(info) => info.decoded_bytes,
+                None => img.original_width_px as u64 * img.original_height_px as u64 * 4,
+            })
+            .sum()
+    }
+
     #[allow(dead_code)]
     pub fn layout(&self) -> &Layout {
         &self.layout
     }
 
     pub fn set_zoom(&mut self, zoom: f32) {
-        self.zoom = zoom.clamp(0.1, 5.0);
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
         self.cache.clear();
     }
 
@@ -254,53 +889,126 @@ impl LayoutCanvas {
     }
 
     pub fn mm_to_pixels(&self, mm: f32) -> f32 {
-        let pixels_per_mm = 96.0 / 25.4;
-        mm * pixels_per_mm * self.zoom
+        mm * PIXELS_PER_MM * self.zoom
     }
 
     fn pixels_to_mm(&self, pixels: f32) -> f32 {
-        let pixels_per_mm = 96.0 / 25.4;
-        pixels / (pixels_per_mm * self.zoom)
+        pixels / (PIXELS_PER_MM * self.zoom)
     }
 
     fn draw_content(&self, frame: &mut Frame) {
         let page = &self.layout.page;
 
-        // Draw page background
+        // Fill the workspace around the page
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), self.background);
+
+        // Draw page background, both pages side by side for a spread
         let page_width = self.mm_to_pixels(page.width_mm);
         let page_height = self.mm_to_pixels(page.height_mm);
+        let sheets = self.sheets();
 
-        let page_bg = Path::rectangle(Point::ORIGIN, Size::new(page_width, page_height));
-        frame.fill(&page_bg, Color::WHITE);
-        frame.stroke(
-            &page_bg,
-            Stroke::default()
-                .with_width(2.0)
-                .with_color(Color::from_rgb(0.3, 0.3, 0.3)),
-        );
+        for ((x, y, width, height), _) in &sheets {
+            let page_bg = Path::rectangle(
+                Point::new(self.mm_to_pixels(*x), self.mm_to_pixels(*y)),
+                Size::new(self.mm_to_pixels(*width), self.mm_to_pixels(*height)),
+            );
+            frame.fill(&page_bg, Color::WHITE);
+            frame.stroke(
+                &page_bg,
+                Stroke::default()
+                    .with_width(2.0)
+                    .with_color(Color::from_rgb(0.3, 0.3, 0.3)),
+            );
+        }
+
+        // Grid, skipped when zoomed out so far the lines would merge
+        if self.view.show_grid {
+            let step = self.mm_to_pixels(self.grid_mm);
+            if step >= MIN_GRID_STEP_PX {
+                let lines = Path::new(|builder| {
+                    let mut x = step;
+                    while x < page_width {
+                        builder.move_to(Point::new(x, 0.0));
+                        builder.line_to(Point::new(x, page_height));
+                        x += step;
+                    }
+                    let mut y = step;
+                    while y < page_height {
+                        builder.move_to(Point::new(0.0, y));
+                        builder.line_to(Point::new(page_width, y));
+                        y += step;
+                    }
+                });
+                frame.stroke(
+                    &lines,
+                    Stroke::default()
+                        .with_width(1.0)
+                        .with_color(Color::from_rgba(0.2, 0.4, 0.8, 0.15)),
+                );
+            }
+        }
 
         // Draw margins
-        let (margin_x, margin_y, printable_width, printable_height) = page.printable_area();
-        let margin_rect = Path::rectangle(
-            Point::new(self.mm_to_pixels(margin_x), self.mm_to_pixels(margin_y)),
-            Size::new(
-                self.mm_to_pixels(printable_width),
-                self.mm_to_pixels(printable_height),
-            ),
-        );
-        frame.stroke(
-            &margin_rect,
-            Stroke::default()
-                .with_width(1.0)
-                .with_color(Color::from_rgb(0.7, 0.7, 0.7)),
-        );
+        if self.view.show_margins {
+            for (_, (margin_x, margin_y, printable_width, printable_height)) in &sheets {
+                let margin_rect = Path::rectangle(
+                    Point::new(self.mm_to_pixels(*margin_x), self.mm_to_pixels(*margin_y)),
+                    Size::new(
+                        self.mm_to_pixels(*printable_width),
+                        self.mm_to_pixels(*printable_height),
+                    ),
+                );
+                frame.stroke(
+                    &margin_rect,
+                    Stroke::default()
+                        .with_width(1.0)
+                        .with_color(Color::from_rgb(0.7, 0.7, 0.7)),
+                );
+            }
+        }
+        if let Some(spread) = &self.spread {
+            self.draw_spread_pages(frame, spread, page_height);
+        }
+        let overlapping: HashSet<String> = if self.view.highlight_overlaps {
+            self.layout.overlapping_images().into_iter().collect()
+        } else {
+            HashSet::new()
+        };
 
-        // Get mutable access to caches via RefCell
+        // Transformed handles are cached via RefCell; sources are only read
         let mut image_cache = self.image_cache.borrow_mut();
-        let mut source_cache = self.source_cache.borrow_mut();
+        let toning = page_toning(&self.layout.page);
+        let quality = self.view.render_quality;
+        let source_cache = self.source_cache.borrow();
+
+        // Draw images and color blocks, bottom first
+        for element in self.layout.elements() {
+            let img = match element {
+                LayoutElement::Rect(rect) => {
+                    self.draw_rect(frame, rect);
+                    continue;
+                }
+                LayoutElement::Image(img) => img,
+            };
+            // In crop mode the edited image is shown whole, in its frame
+            if let Some(edit) = self.crop_edit.as_ref().filter(|e| e.image_id == img.id) {
+                let (fx, fy, fw, fh) = edit.frame;
+                let mut uncropped = img.clone();
+                uncropped.crop = None;
+                // The crop frame is edited upright
+                uncropped.rotation_degrees -= uncropped.frame_angle_degrees();
+                let bounds = Rectangle::new(
+                    Point::new(self.mm_to_pixels(fx), self.mm_to_pixels(fy)),
+                    Size::new(self.mm_to_pixels(fw), self.mm_to_pixels(fh)),
+                );
+                if self.low_gpu {
+                    frame.fill_rectangle(bounds.position(), bounds.size(), PLACEHOLDER_FILL);
+                } else if let Some(handle) = image_cache.get_transformed_handle(&uncropped, &source_cache, toning, quality) {
+                    frame.draw_image(bounds, Image::new(handle));
+                }
+                continue;
+            }
 
-        // Draw images
-        for img in &self.layout.images {
             let x = self.mm_to_pixels(img.x_mm);
             let y = self.mm_to_pixels(img.y_mm);
             let width = self.mm_to_pixels(img.width_mm);
@@ -309,17 +1017,32 @@ impl LayoutCanvas {
             let bounds = Rectangle::new(Point::new(x, y), Size::new(width, height));
 
             // Try to draw transformed image using Iced 0.13's draw_image
-            if let Some(handle) = image_cache.get_transformed_handle(img, &mut source_cache) {
-                let image = Image::new(handle);
-                frame.draw_image(bounds, image);
+            let preview = if self.low_gpu {
+                None
+            } else {
+                image_cache.get_transformed_handle(img, &source_cache, toning, quality)
+            };
+            if let Some(handle) = preview {
+                frame.draw_image(self.image_target(img), Image::new(handle));
+            } else if let Some(reason) = source_cache.failure(&img.path) {
+                self.draw_broken_image(frame, img, bounds, reason);
             } else {
                 // Fallback: draw placeholder rectangle if image can't be loaded
                 let image_rect = Path::rectangle(Point::new(x, y), Size::new(width, height));
-                frame.fill(&image_rect, Color::from_rgba(0.85, 0.90, 1.0, 0.8));
+                frame.fill(&image_rect, PLACEHOLDER_FILL);
+                if source_cache.is_pending(&img.path) {
+                    frame.fill_text(Text {
+                        content: "Loading…".to_string(),
+                        position: Point::new(x + 5.0, y + 25.0),
+                        color: Color::from_rgb(0.3, 0.3, 0.4),
+                        size: 12.0.into(),
+                        ..Default::default()
+                    });
+                }
             }
 
-            // Draw border
-            let image_rect = Path::rectangle(Point::new(x, y), Size::new(width, height));
+            // Draw border, following the frame's tilt
+            let image_rect = self.frame_outline(img);
             frame.stroke(
                 &image_rect,
                 Stroke::default()
@@ -327,7 +1050,7 @@ impl LayoutCanvas {
                     .with_color(Color::from_rgb(0.5, 0.5, 0.5)),
             );
 
-            // Highlight selected image
+            // Highlight selected image; the rest of a multiple selection is lighter
             if self.layout.selected_image_id.as_ref() == Some(&img.id) {
                 frame.stroke(
                     &image_rect,
@@ -335,17 +1058,45 @@ impl LayoutCanvas {
                         .with_width(3.0)
                         .with_color(Color::from_rgb(0.0, 0.5, 1.0)),
                 );
+            } else if self.layout.is_selected(&img.id) {
+                frame.stroke(&image_rect, secondary_selection_stroke());
+            }
+            if overlapping.contains(&img.id) {
+                frame.stroke(
+                    &image_rect,
+                    Stroke {
+                        line_dash: LineDash {
+                            segments: &[6.0, 4.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default()
+                            .with_width(2.0)
+                            .with_color(Color::from_rgb(0.9, 0.45, 0.0))
+                    },
+                );
+            }
+            if self.unplaced.iter().any(|(id, bounds)| id == &img.id && *bounds == img.bounds()) {
+                frame.stroke(
+                    &image_rect,
+                    Stroke::default()
+                        .with_width(3.0)
+                        .with_color(UNPLACED_BORDER),
+                );
+            }
+            if self.view.show_thirds && self.layout.selected_image_id.as_ref() == Some(&img.id) {
+                self.draw_thirds(frame, bounds);
+            }
+
+            if img.locked {
+                self.draw_lock_badge(frame, img);
+            }
 
-                // Draw resize handles - corners (larger, square)
-                let corner_size = 10.0;
-                let corners = [
-                    (x, y),                           // TopLeft
-                    (x + width, y),                   // TopRight
-                    (x, y + height),                  // BottomLeft
-                    (x + width, y + height),          // BottomRight
-                ];
+            // Draw resize handles - corners (larger, square)
+            if !self.view_only && !img.locked && self.layout.selected_image_id.as_ref() == Some(&img.id) {
+                let corner_size = self.handle_size;
+                let handles = self.image_handle_points(img);
 
-                for (cx, cy) in corners.iter() {
+                for (cx, cy, _) in &handles[..4] {
                     let handle = Path::rectangle(
                         Point::new(cx - corner_size / 2.0, cy - corner_size / 2.0),
                         Size::new(corner_size, corner_size),
@@ -358,15 +1109,9 @@ impl LayoutCanvas {
                 }
 
                 // Draw edge handles (smaller, centered on edges)
-                let edge_size = 8.0;
-                let edges = [
-                    (x + width / 2.0, y),                  // Top
-                    (x + width / 2.0, y + height),         // Bottom
-                    (x, y + height / 2.0),                 // Left
-                    (x + width, y + height / 2.0),         // Right
-                ];
-
-                for (ex, ey) in edges.iter() {
+                let edge_size = self.edge_handle_size();
+
+                for (ex, ey, _) in &handles[4..] {
                     let handle = Path::rectangle(
                         Point::new(ex - edge_size / 2.0, ey - edge_size / 2.0),
                         Size::new(edge_size, edge_size),
@@ -377,24 +1122,443 @@ impl LayoutCanvas {
                         Stroke::default().with_width(1.0).with_color(Color::WHITE),
                     );
                 }
+
+                // Rotation handle: a round knob on a stem above the top edge
+                let (tx, ty, _) = handles[4];
+                let (rx, ry) = self.rotate_handle_point(img);
+                frame.stroke(
+                    &Path::line(Point::new(tx, ty), Point::new(rx, ry)),
+                    Stroke::default().with_width(1.0).with_color(Color::from_rgb(0.0, 0.5, 1.0)),
+                );
+                let knob = Path::circle(Point::new(rx, ry), corner_size / 2.0);
+                frame.fill(&knob, Color::from_rgb(0.0, 0.5, 1.0));
+                frame.stroke(&knob, Stroke::default().with_width(1.0).with_color(Color::WHITE));
             }
 
-            // Draw filename label
-            let filename = img
-                .path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
+            // Draw name label (user-assigned name or filename); none in draft,
+            // unless placeholders stand in for the previews
+            if quality == CanvasQuality::Full || self.low_gpu {
+                let label = img.display_label();
+
+                let text_bg_width = (label.chars().count() as f32 * 7.0).max(50.0);
+                let text_bg = Path::rectangle(Point::new(x, y), Size::new(text_bg_width, 20.0));
+                frame.fill(&text_bg, Color::from_rgba(0.0, 0.0, 0.0, 0.7));
 
-            let text_bg_width = (filename.len() as f32 * 7.0).max(50.0);
-            let text_bg = Path::rectangle(Point::new(x, y), Size::new(text_bg_width, 20.0));
-            frame.fill(&text_bg, Color::from_rgba(0.0, 0.0, 0.0, 0.7));
+                frame.fill_text(Text {
+                    content: label.to_string(),
+                    position: Point::new(x + 5.0, y + 5.0),
+                    color: Color::WHITE,
+                    size: 12.0.into(),
+                    ..Default::default()
+                });
+            }
 
+            if let Some(spread) = &self.spread {
+                self.draw_page_badge(frame, spread.page_number(spread.side_of(img.bounds())), Point::new(x, y + height));
+            }
+
+            // Flag images that can never fit on the paper
+            if img.exceeds_page(page) {
+                frame.stroke(
+                    &image_rect,
+                    Stroke::default()
+                        .with_width(2.0)
+                        .with_color(Color::from_rgb(0.85, 0.1, 0.1)),
+                );
+                let badge = Path::rectangle(Point::new(x, y + 20.0), Size::new(130.0, 20.0));
+                frame.fill(&badge, Color::from_rgb(0.85, 0.1, 0.1));
+                frame.fill_text(Text {
+                    content: "⚠ Larger than paper".to_string(),
+                    position: Point::new(x + 5.0, y + 25.0),
+                    color: Color::WHITE,
+                    size: 12.0.into(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        // Automatic copies print on top of everything, so they draw last.
+        // They are placed per printed sheet, so a spread leaves them out.
+        let copies: &[PlacedImage] = if self.spread.is_some() { &[] } else { &self.copies };
+        for copy in copies {
+            if self.low_gpu {
+                frame.fill(&self.frame_outline(copy), PLACEHOLDER_FILL);
+            } else if let Some(handle) = image_cache.get_transformed_handle(copy, &source_cache, toning, quality) {
+                frame.draw_image(self.image_target(copy), Image::new(handle));
+            }
+            frame.stroke(
+                &self.frame_outline(copy),
+                Stroke {
+                    line_dash: LineDash {
+                        segments: &[4.0, 3.0],
+                        offset: 0,
+                    },
+                    ..Stroke::default()
+                        .with_width(1.0)
+                        .with_color(Color::from_rgb(0.5, 0.5, 0.5))
+                },
+            );
+        }
+
+        if self.view.shade_margins {
+            for (sheet, area) in &sheets {
+                self.shade_margins(frame, *sheet, *area);
+            }
+        }
+        self.draw_caption_preview(frame);
+        // Marks, numbers and footers are laid out per printed page, so a
+        // spread leaves them out
+        if self.spread.is_none() {
+            self.draw_registration_marks(frame);
+            self.draw_sequence_preview(frame);
+            self.draw_footer_preview(frame);
+        }
+    }
+
+    /// Gray over the unprintable border of a page (both in mm), so anything
+    /// reaching into it stands out
+    fn shade_margins(&self, frame: &mut Frame, sheet: (f32, f32, f32, f32), area: (f32, f32, f32, f32)) {
+        let (page_x, page_y, page_width, page_height) = sheet;
+        let (x, y, width, height) = area;
+        let (page_left, page_top) = (self.mm_to_pixels(page_x), self.mm_to_pixels(page_y));
+        let (page_right, page_bottom) = (self.mm_to_pixels(page_x + page_width), self.mm_to_pixels(page_y + page_height));
+        let (left, top) = (self.mm_to_pixels(x), self.mm_to_pixels(y));
+        let (right, bottom) = (self.mm_to_pixels(x + width), self.mm_to_pixels(y + height));
+        let shade = Color::from_rgba(0.3, 0.3, 0.3, 0.25);
+        frame.fill_rectangle(Point::new(page_left, page_top), Size::new(page_right - page_left, top - page_top), shade);
+        frame.fill_rectangle(Point::new(page_left, bottom), Size::new(page_right - page_left, page_bottom - bottom), shade);
+        frame.fill_rectangle(Point::new(page_left, top), Size::new(left - page_left, bottom - top), shade);
+        frame.fill_rectangle(Point::new(right, top), Size::new(page_right - right, bottom - top), shade);
+    }
+
+    /// The fold between the pages of a spread as a dashed line, and each
+    /// page's number just inside its top edge
+    fn draw_spread_pages(&self, frame: &mut Frame, spread: &Spread, page_height: f32) {
+        let fold = self.mm_to_pixels(spread.fold_mm);
+        frame.stroke(
+            &Path::line(Point::new(fold, 0.0), Point::new(fold, page_height)),
+            Stroke {
+                line_dash: LineDash {
+                    segments: &[8.0, 4.0],
+                    offset: 0,
+                },
+                ..Stroke::default().with_width(1.0).with_color(SPREAD_INK)
+            },
+        );
+        for side in 0..2 {
+            let (x, _) = spread.on_canvas(side, 0.0, 0.0);
             frame.fill_text(Text {
-                content: filename.to_string(),
-                position: Point::new(x + 5.0, y + 5.0),
-                color: Color::WHITE,
-                size: 12.0.into(),
+                content: format!("Page {}", spread.page_number(side)),
+                position: Point::new(self.mm_to_pixels(x) + 6.0, 4.0),
+                color: SPREAD_INK,
+                size: 11.0.into(),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Small tag with the page an element of a spread prints on, at its
+    /// bottom-left corner (pixels)
+    fn draw_page_badge(&self, frame: &mut Frame, page_number: usize, corner: Point) {
+        let badge = Path::rectangle(Point::new(corner.x, corner.y - 16.0), Size::new(24.0, 16.0));
+        frame.fill(&badge, Color::from_rgba(0.2, 0.2, 0.3, 0.75));
+        frame.fill_text(Text {
+            content: format!("p{}", page_number),
+            position: Point::new(corner.x + 4.0, corner.y - 14.0),
+            color: Color::WHITE,
+            size: 11.0.into(),
+            ..Default::default()
+        });
+    }
+
+    /// Lines dividing an image into thirds each way, for composing a crop
+    fn draw_thirds(&self, frame: &mut Frame, bounds: Rectangle) {
+        let lines = Path::new(|builder| {
+            for i in 1..3 {
+                let x = bounds.x + bounds.width * i as f32 / 3.0;
+                let y = bounds.y + bounds.height * i as f32 / 3.0;
+                builder.move_to(Point::new(x, bounds.y));
+                builder.line_to(Point::new(x, bounds.y + bounds.height));
+                builder.move_to(Point::new(bounds.x, y));
+                builder.line_to(Point::new(bounds.x + bounds.width, y));
+            }
+        });
+        frame.stroke(
+            &lines,
+            Stroke::default()
+                .with_width(1.0)
+                .with_color(Color::from_rgba(1.0, 1.0, 1.0, 0.8)),
+        );
+    }
+
+    /// Small padlock in the top-right corner of a locked image
+    fn draw_lock_badge(&self, frame: &mut Frame, img: &PlacedImage) {
+        let right = self.mm_to_pixels(img.x_mm + img.width_mm);
+        let top = self.mm_to_pixels(img.y_mm);
+        let badge = Path::rounded_rectangle(Point::new(right - 24.0, top + 4.0), Size::new(20.0, 20.0), 4.0.into());
+        frame.fill(&badge, Color::from_rgba(0.0, 0.0, 0.0, 0.7));
+        // Shackle, then the body over its ends
+        let (cx, body_top) = (right - 14.0, top + 14.0);
+        let shackle = Path::new(|p| {
+            p.move_to(Point::new(cx - 3.5, body_top));
+            p.line_to(Point::new(cx - 3.5, body_top - 4.0));
+            p.arc(canvas::path::Arc {
+                center: Point::new(cx, body_top - 4.0),
+                radius: 3.5,
+                start_angle: iced::Radians(std::f32::consts::PI),
+                end_angle: iced::Radians(2.0 * std::f32::consts::PI),
+            });
+            p.line_to(Point::new(cx + 3.5, body_top));
+        });
+        frame.stroke(&shackle, Stroke::default().with_width(1.5).with_color(Color::WHITE));
+        frame.fill_rectangle(Point::new(cx - 5.5, body_top), Size::new(11.0, 7.0), Color::WHITE);
+    }
+
+    /// Cut registration marks as printed, their reserved corners tinted, and
+    /// a red outline on images that reach into those corners
+    fn draw_registration_marks(&self, frame: &mut Frame) {
+        let page = &self.layout.page;
+        let to_rect = |(x, y, w, h): (f32, f32, f32, f32)| {
+            (
+                Point::new(self.mm_to_pixels(x), self.mm_to_pixels(y)),
+                Size::new(self.mm_to_pixels(w), self.mm_to_pixels(h)),
+            )
+        };
+        for region in page.mark_reserved_regions() {
+            let (top_left, size) = to_rect(region);
+            frame.fill_rectangle(top_left, size, Color::from_rgba(0.85, 0.1, 0.1, 0.08));
+        }
+        for shape in page.registration_mark_shapes() {
+            let (top_left, size) = to_rect(shape);
+            // At least a pixel wide, so thin lines stay visible zoomed out
+            frame.fill_rectangle(top_left, Size::new(size.width.max(1.0), size.height.max(1.0)), Color::BLACK);
+        }
+        for id in self.layout.images_over_marks() {
+            if let Some(img) = self.layout.get_image(&id) {
+                frame.stroke(
+                    &self.frame_outline(img),
+                    Stroke::default()
+                        .with_width(2.0)
+                        .with_color(Color::from_rgb(0.85, 0.1, 0.1)),
+                );
+            }
+        }
+    }
+
+    /// Where an image's transformed pixels are drawn, in pixels: its frame,
+    /// or the tilted frame's bounding box when it's tilted
+    fn image_target(&self, img: &PlacedImage) -> Rectangle {
+        let (x, y, width, height) = if img.frame_angle_degrees() != 0.0 {
+            img.rotated_bounds()
+        } else {
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm)
+        };
+        Rectangle::new(
+            Point::new(self.mm_to_pixels(x), self.mm_to_pixels(y)),
+            Size::new(self.mm_to_pixels(width), self.mm_to_pixels(height)),
+        )
+    }
+
+    /// Faint preview of the printed sequence numbers, in the print render's
+    /// circles and glyph cells
+    fn draw_sequence_preview(&self, frame: &mut Frame) {
+        let numbers = &self.layout.page.sequence_numbers;
+        if !numbers.enabled {
+            return;
+        }
+        let ink = Color::from_rgba(0.15, 0.15, 0.15, 0.6);
+        for (img, number) in self.layout.numbered_images() {
+            let badge = numbers.badge(img.x_mm, img.y_mm, img.width_mm, img.height_mm);
+            let circle = Path::circle(
+                Point::new(self.mm_to_pixels(badge.0), self.mm_to_pixels(badge.1)),
+                self.mm_to_pixels(badge.2),
+            );
+            frame.fill(&circle, Color::from_rgba(1.0, 1.0, 1.0, 0.6));
+            frame.stroke(&circle, Stroke::default().with_width(1.0).with_color(ink));
+
+            let label = number.to_string();
+            let (x, y, _, height) = SequenceNumbers::label_rect(&label, badge);
+            let (x, y) = (self.mm_to_pixels(x), self.mm_to_pixels(y));
+            let cell = self.mm_to_pixels(height) / bitmap_font::GLYPH_ROWS as f32;
+            for (col, row) in bitmap_font::cells(&label) {
+                frame.fill_rectangle(
+                    Point::new(x + col as f32 * cell, y + row as f32 * cell),
+                    Size::new(cell, cell),
+                    ink,
+                );
+            }
+        }
+    }
+
+    /// Faint preview of the printed captions, in the print render's glyph cells
+    fn draw_caption_preview(&self, frame: &mut Frame) {
+        if !self.layout.page.captions {
+            return;
+        }
+        let ink = Color::from_rgba(0.15, 0.15, 0.15, 0.6);
+        for img in &self.layout.images {
+            let caption = img.display_label();
+            let (x, y, _, height) = img.caption_rect(caption);
+            let (x, y) = (self.mm_to_pixels(x), self.mm_to_pixels(y));
+            let cell = self.mm_to_pixels(height) / bitmap_font::GLYPH_ROWS as f32;
+            for (col, row) in bitmap_font::cells(caption) {
+                frame.fill_rectangle(
+                    Point::new(x + col as f32 * cell, y + row as f32 * cell),
+                    Size::new(cell, cell),
+                    ink,
+                );
+            }
+        }
+    }
+
+    /// Faint preview of the printed footer, using the same glyph cells as
+    /// the print render so its size and position match
+    fn draw_footer_preview(&self, frame: &mut Frame) {
+        let page = &self.layout.page;
+        let Some(text) = page.footer.text(1, 1, chrono::Local::now().date_naive()) else {
+            return;
+        };
+        let (x, y, _, height) = page.footer.rect(page, &text);
+        let (x, y) = (self.mm_to_pixels(x), self.mm_to_pixels(y));
+        let cell = self.mm_to_pixels(height) / bitmap_font::GLYPH_ROWS as f32;
+        let color = Color::from_rgba(0.35, 0.35, 0.35, 0.45);
+        for (col, row) in bitmap_font::cells(&text) {
+            frame.fill_rectangle(
+                Point::new(x + col as f32 * cell, y + row as f32 * cell),
+                Size::new(cell, cell),
+                color,
+            );
+        }
+    }
+
+    /// Fill a color block, with its selection outline and handles when selected
+    fn draw_rect(&self, frame: &mut Frame, rect: &PlacedRect) {
+        let (x, y, width, height) = (
+            self.mm_to_pixels(rect.x_mm),
+            self.mm_to_pixels(rect.y_mm),
+            self.mm_to_pixels(rect.width_mm),
+            self.mm_to_pixels(rect.height_mm),
+        );
+        // Toned like the printed page in Black and White
+        let mut color = image::RgbaImage::from_pixel(1, 1, image::Rgba(rect.color));
+        if let Some(toning) = page_toning(&self.layout.page) {
+            apply_toning(&mut color, toning);
+        }
+        let [r, g, b, a] = color.get_pixel(0, 0).0;
+        let shape = Path::rounded_rectangle(
+            Point::new(x, y),
+            Size::new(width, height),
+            self.mm_to_pixels(rect.effective_corner_radius()).into(),
+        );
+        frame.fill(&shape, Color::from_rgba8(r, g, b, a as f32 / 255.0));
+
+        if self.layout.selected_image_id.as_deref() == Some(rect.id.as_str()) {
+            frame.stroke(
+                &Path::rectangle(Point::new(x, y), Size::new(width, height)),
+                Stroke::default()
+                    .with_width(3.0)
+                    .with_color(Color::from_rgb(0.0, 0.5, 1.0)),
+            );
+        } else if self.layout.is_selected(&rect.id) {
+            frame.stroke(&Path::rectangle(Point::new(x, y), Size::new(width, height)), secondary_selection_stroke());
+        }
+        if !self.view_only && self.layout.selected_image_id.as_deref() == Some(rect.id.as_str()) {
+            for (i, (hx, hy, _)) in handle_points(x, y, width, height).into_iter().enumerate() {
+                // Corners first, drawn larger than the edge handles
+                let size = if i < 4 { self.handle_size } else { self.edge_handle_size() };
+                let handle = Path::rectangle(Point::new(hx - size / 2.0, hy - size / 2.0), Size::new(size, size));
+                frame.fill(&handle, Color::from_rgb(0.0, 0.5, 1.0));
+                frame.stroke(&handle, Stroke::default().with_width(1.0).with_color(Color::WHITE));
+            }
+        }
+    }
+
+    /// Dim the parts of the image outside the crop and draw the crop handles
+    fn draw_crop_overlay(&self, frame: &mut Frame, edit: &CropEdit) {
+        let px = |mm: f32| self.mm_to_pixels(mm);
+        let (fx, fy, fw, fh) = edit.frame;
+        let (cx, cy, cw, ch) = edit.crop_rect();
+        let shade = Color::from_rgba(0.0, 0.0, 0.0, 0.55);
+        // Bands above, below, left and right of the crop
+        let bands = [
+            (fx, fy, fw, cy - fy),
+            (fx, cy + ch, fw, fy + fh - cy - ch),
+            (fx, cy, cx - fx, ch),
+            (cx + cw, cy, fx + fw - cx - cw, ch),
+        ];
+        for (x, y, width, height) in bands {
+            if width > 0.0 && height > 0.0 {
+                frame.fill_rectangle(Point::new(px(x), px(y)), Size::new(px(width), px(height)), shade);
+            }
+        }
+
+        let (x, y, width, height) = (px(cx), px(cy), px(cw), px(ch));
+        frame.stroke(
+            &Path::rectangle(Point::new(x, y), Size::new(width, height)),
+            Stroke::default().with_width(2.0).with_color(Color::WHITE),
+        );
+        let size = self.handle_size;
+        for (hx, hy, _) in handle_points(x, y, width, height) {
+            let handle = Path::rectangle(Point::new(hx - size / 2.0, hy - size / 2.0), Size::new(size, size));
+            frame.fill(&handle, Color::WHITE);
+            frame.stroke(&handle, Stroke::default().with_width(1.0).with_color(Color::from_rgb(0.0, 0.5, 1.0)));
+        }
+    }
+
+    /// What a click at a point (in pixels) does in crop mode: grab a crop
+    /// handle, move the crop, or commit when outside it
+    fn crop_press_at_point(&self, edit: &CropEdit, px: f32, py: f32) -> CanvasMessage {
+        let (cx, cy, cw, ch) = edit.crop_rect();
+        let (x, y, width, height) = (
+            self.mm_to_pixels(cx),
+            self.mm_to_pixels(cy),
+            self.mm_to_pixels(cw),
+            self.mm_to_pixels(ch),
+        );
+        let radius = self.handle_hit_radius();
+        if let Some((_, _, handle)) = handle_points(x, y, width, height)
+            .into_iter()
+            .find(|(hx, hy, _)| (px - hx).abs() < radius && (py - hy).abs() < radius)
+        {
+            return CanvasMessage::StartCropDrag(Some(handle));
+        }
+        if px >= x && px <= x + width && py >= y && py <= y + height {
+            CanvasMessage::StartCropDrag(None)
+        } else {
+            CanvasMessage::CommitCrop
+        }
+    }
+
+    /// Placeholder for an image whose file couldn't be loaded: a crossed-out
+    /// box with the file name and a short reason
+    fn draw_broken_image(&self, frame: &mut Frame, img: &PlacedImage, bounds: Rectangle, reason: &str) {
+        let red = Color::from_rgb(0.75, 0.15, 0.15);
+        let rect = Path::rectangle(bounds.position(), bounds.size());
+        frame.fill(&rect, Color::from_rgba(0.95, 0.88, 0.88, 0.9));
+        let (left, top) = (bounds.x, bounds.y);
+        let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+        let cross = Path::new(|p| {
+            p.move_to(Point::new(left, top));
+            p.line_to(Point::new(right, bottom));
+            p.move_to(Point::new(right, top));
+            p.line_to(Point::new(left, bottom));
+        });
+        frame.stroke(&cross, Stroke::default().with_width(1.0).with_color(Color::from_rgba(0.75, 0.15, 0.15, 0.4)));
+
+        // Roughly 6.5 px per character at this size
+        let max_chars = ((bounds.width - 10.0) / 6.5).max(8.0) as usize;
+        let file_name = img.path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+        let lines = [
+            ("Can't load image".to_string(), red),
+            (shorten(file_name, max_chars), Color::from_rgb(0.2, 0.2, 0.2)),
+            (shorten(reason, max_chars), Color::from_rgb(0.4, 0.2, 0.2)),
+        ];
+        for (i, (content, color)) in lines.into_iter().enumerate() {
+            frame.fill_text(Text {
+                content,
+                position: Point::new(left + 5.0, top + 28.0 + i as f32 * 15.0),
+                color,
+                size: 11.0.into(),
                 ..Default::default()
             });
         }
@@ -403,54 +1567,85 @@ impl LayoutCanvas {
     /// Check if a point (in pixels) is over a resize handle of the selected image
     /// Returns the handle type if found
     fn get_resize_handle_at_point(&self, px: f32, py: f32) -> Option<(String, ResizeHandle)> {
-        if let Some(id) = &self.layout.selected_image_id {
-            if let Some(img) = self.layout.get_image(id) {
-                let x = self.mm_to_pixels(img.x_mm);
-                let y = self.mm_to_pixels(img.y_mm);
-                let width = self.mm_to_pixels(img.width_mm);
-                let height = self.mm_to_pixels(img.height_mm);
-                
-                let handle_radius = 8.0; // Detection radius
-                
-                // Check corners first (they have priority)
-                let corners = [
-                    (x, y, ResizeHandle::TopLeft),
-                    (x + width, y, ResizeHandle::TopRight),
-                    (x, y + height, ResizeHandle::BottomLeft),
-                    (x + width, y + height, ResizeHandle::BottomRight),
-                ];
-                
-                for (cx, cy, handle) in corners.iter() {
-                    if (px - cx).abs() < handle_radius && (py - cy).abs() < handle_radius {
-                        return Some((id.clone(), *handle));
-                    }
-                }
-                
-                // Check edges
-                let edges = [
-                    (x + width / 2.0, y, ResizeHandle::Top),
-                    (x + width / 2.0, y + height, ResizeHandle::Bottom),
-                    (x, y + height / 2.0, ResizeHandle::Left),
-                    (x + width, y + height / 2.0, ResizeHandle::Right),
-                ];
-                
-                for (ex, ey, handle) in edges.iter() {
-                    if (px - ex).abs() < handle_radius && (py - ey).abs() < handle_radius {
-                        return Some((id.clone(), *handle));
-                    }
-                }
+        if self.view_only {
+            return None;
+        }
+        let id = self.layout.selected_image_id.as_ref()?;
+        let handles = if let Some(img) = self.layout.get_image(id) {
+            if img.locked {
+                return None;
             }
+            self.image_handle_points(img)
+        } else {
+            let rect = self.layout.get_rect(id)?;
+            handle_points(
+                self.mm_to_pixels(rect.x_mm),
+                self.mm_to_pixels(rect.y_mm),
+                self.mm_to_pixels(rect.width_mm),
+                self.mm_to_pixels(rect.height_mm),
+            )
+        };
+        let handle_radius = self.handle_hit_radius();
+
+        // Corners come first, so they have priority over edges
+        handles
+            .into_iter()
+            .find(|(hx, hy, _)| (px - hx).abs() < handle_radius && (py - hy).abs() < handle_radius)
+            .map(|(_, _, handle)| (id.clone(), handle))
+    }
+
+    /// Position (pixels) of the rotation handle: above the middle of the
+    /// frame's top edge, following its tilt
+    fn rotate_handle_point(&self, img: &PlacedImage) -> (f32, f32) {
+        let (cx, cy) = img.frame_point(0.5, 0.5);
+        let (tx, ty) = img.frame_point(0.5, 0.0);
+        let (tx, ty) = (self.mm_to_pixels(tx), self.mm_to_pixels(ty));
+        let (dx, dy) = (tx - self.mm_to_pixels(cx), ty - self.mm_to_pixels(cy));
+        let length = dx.hypot(dy).max(f32::EPSILON);
+        (
+            tx + dx / length * ROTATE_HANDLE_OFFSET_PX,
+            ty + dy / length * ROTATE_HANDLE_OFFSET_PX,
+        )
+    }
+
+    /// The selected image, if the point (pixels) is on its rotation handle
+    fn get_rotate_handle_at_point(&self, px: f32, py: f32) -> Option<String> {
+        if self.view_only {
+            return None;
         }
-        None
+        let img = self.layout.selected_image().filter(|img| !img.locked)?;
+        let (rx, ry) = self.rotate_handle_point(img);
+        let radius = self.handle_hit_radius();
+        ((px - rx).abs() < radius && (py - ry).abs() < radius).then(|| img.id.clone())
+    }
+
+    /// Resize handle positions (pixels) of an image, on its tilted frame
+    fn image_handle_points(&self, img: &PlacedImage) -> [(f32, f32, ResizeHandle); 8] {
+        handle_points(0.0, 0.0, 1.0, 1.0).map(|(u, v, handle)| {
+            let (x, y) = img.frame_point(u, v);
+            (self.mm_to_pixels(x), self.mm_to_pixels(y), handle)
+        })
+    }
+
+    /// Outline of an image's frame (pixels), following its tilt
+    fn frame_outline(&self, img: &PlacedImage) -> Path {
+        let corners = img.corners().map(|(x, y)| Point::new(self.mm_to_pixels(x), self.mm_to_pixels(y)));
+        Path::new(|builder| {
+            builder.move_to(corners[0]);
+            for corner in &corners[1..] {
+                builder.line_to(*corner);
+            }
+            builder.close();
+        })
     }
 }
 
 impl Program<CanvasMessage> for LayoutCanvas {
-    type State = ();
+    type State = CanvasState;
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
@@ -460,19 +1655,115 @@ impl Program<CanvasMessage> for LayoutCanvas {
             self.draw_content(frame);
         });
 
-        vec![geometry]
+        let band = state.press.as_ref().and_then(Press::band);
+        let readout = self.rotate_readout();
+        if self.ghosts.is_empty() && self.crop_edit.is_none() && band.is_none() && readout.is_none() {
+            return vec![geometry];
+        }
+
+        // Ghost outlines, the crop rectangle and the angle being turned to
+        // change independently of the layout, so draw them uncached
+        let mut overlay = Frame::new(renderer, bounds.size());
+        if let Some((at, label)) = readout {
+            let width = label.chars().count() as f32 * 7.0 + 10.0;
+            overlay.fill(
+                &Path::rectangle(Point::new(at.x, at.y - 10.0), Size::new(width, 20.0)),
+                Color::from_rgba(0.0, 0.0, 0.0, 0.7),
+            );
+            overlay.fill_text(Text {
+                content: label,
+                position: Point::new(at.x + 5.0, at.y - 6.0),
+                color: Color::WHITE,
+                size: 12.0.into(),
+                ..Default::default()
+            });
+        }
+        if let Some(edit) = &self.crop_edit {
+            self.draw_crop_overlay(&mut overlay, edit);
+        }
+        if let Some(band) = band {
+            let outline = Path::rectangle(band.position(), band.size());
+            overlay.fill(&outline, Color::from_rgba(0.0, 0.5, 1.0, 0.1));
+            overlay.stroke(
+                &outline,
+                Stroke::default()
+                    .with_width(1.0)
+                    .with_color(Color::from_rgb(0.0, 0.5, 1.0)),
+            );
+        }
+        for &(x, y, width, height) in &self.ghosts {
+            let outline = Path::rectangle(
+                Point::new(self.mm_to_pixels(x), self.mm_to_pixels(y)),
+                Size::new(self.mm_to_pixels(width), self.mm_to_pixels(height)),
+            );
+            overlay.stroke(
+                &outline,
+                Stroke {
+                    line_dash: LineDash {
+                        segments: &[6.0, 4.0],
+                        offset: 0,
+                    },
+                    ..Stroke::default()
+                        .with_width(1.5)
+                        .with_color(Color::from_rgb(0.9, 0.3, 0.1))
+                },
+            );
+        }
+
+        vec![geometry, overlay.into_geometry()]
     }
 
     fn update(
         &self,
-        _state: &mut Self::State,
+        state: &mut Self::State,
         event: canvas::Event,
         bounds: Rectangle,
         cursor: Cursor,
     ) -> (iced::event::Status, Option<CanvasMessage>) {
+        // Enter and Esc drive crop mode. Enter only starts cropping while the
+        // pointer is over the canvas so pressing it in a text field doesn't.
+        if let canvas::Event::Keyboard(keyboard::Event::KeyPressed {
+            key: keyboard::Key::Named(key),
+            ..
+        }) = &event
+        {
+            let message = match (key, self.crop_edit.is_some()) {
+                (keyboard::key::Named::Escape, true) => Some(CanvasMessage::CancelCrop),
+                (keyboard::key::Named::Enter, true) => Some(CanvasMessage::CommitCrop),
+                (keyboard::key::Named::Enter, false)
+                    if cursor.is_over(bounds) && self.layout.selected_image_id.is_some() && !self.view_only =>
+                {
+                    Some(CanvasMessage::EnterCropMode)
+                }
+                _ => None,
+            };
+            if message.is_some() {
+                return (iced::event::Status::Captured, message);
+            }
+        }
+
+        // Report leaving once so the app forgets the last pointer position
+        let cursor_over = cursor.is_over(bounds);
+        if state.cursor_over && !cursor_over {
+            state.cursor_over = false;
+            return (iced::event::Status::Ignored, Some(CanvasMessage::CursorLeft));
+        }
+        state.cursor_over = cursor_over;
+
         if let Some(cursor_position) = cursor.position_in(bounds) {
             match event {
                 canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                    if let Some(edit) = &self.crop_edit {
+                        return (
+                            iced::event::Status::Captured,
+                            Some(self.crop_press_at_point(edit, cursor_position.x, cursor_position.y)),
+                        );
+                    }
+
+                    if let Some(id) = self.get_rotate_handle_at_point(cursor_position.x, cursor_position.y) {
+                        return (iced::event::Status::Captured, Some(CanvasMessage::StartRotate(id)));
+                    }
+
                     // First check if we're clicking on a resize handle
                     if let Some((id, handle)) = self.get_resize_handle_at_point(cursor_position.x, cursor_position.y) {
                         return (
@@ -481,23 +1772,33 @@ impl Program<CanvasMessage> for LayoutCanvas {
                         );
                     }
                     
-                    // Otherwise check for image selection/move
+                    // Otherwise wait to see whether this is a click or a move
                     let x_mm = self.pixels_to_mm(cursor_position.x);
                     let y_mm = self.pixels_to_mm(cursor_position.y);
-
-                    if let Some(image) = self.layout.find_image_at_point(x_mm, y_mm) {
-                        return (
-                            iced::event::Status::Captured,
-                            Some(CanvasMessage::SelectImage(image.id.clone())),
-                        );
-                    } else {
-                        return (
-                            iced::event::Status::Captured,
-                            Some(CanvasMessage::DeselectAll),
-                        );
-                    }
+                    // Locked elements are still clicked (to select them) but never dragged
+                    let element = self
+                        .layout
+                        .find_element_at_point(x_mm, y_mm)
+                        .filter(|e| !e.locked())
+                        .map(|e| e.id().to_string());
+                    state.press = Some(Press::new(cursor_position, element));
+                    return (iced::event::Status::Captured, None);
                 }
                 canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                    if let Some(press) = state.press.as_mut() {
+                        if press.moved_to(cursor_position) {
+                            if let Some(id) = press.element.clone() {
+                                return (
+                                    iced::event::Status::Captured,
+                                    Some(CanvasMessage::StartMove(
+                                        id,
+                                        self.pixels_to_mm(press.origin.x),
+                                        self.pixels_to_mm(press.origin.y),
+                                    )),
+                                );
+                            }
+                        }
+                    }
                     let x_mm = self.pixels_to_mm(cursor_position.x);
                     let y_mm = self.pixels_to_mm(cursor_position.y);
                     return (
@@ -506,10 +1807,23 @@ impl Program<CanvasMessage> for LayoutCanvas {
                     );
                 }
                 canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
-                    return (
-                        iced::event::Status::Captured,
-                        Some(CanvasMessage::MouseReleased),
-                    );
+                    let message = match state.press.take() {
+                        Some(press) if !press.dragging => CanvasMessage::CanvasClicked(
+                            self.pixels_to_mm(press.origin.x),
+                            self.pixels_to_mm(press.origin.y),
+                        ),
+                        Some(press) => match press.band() {
+                            Some(band) => CanvasMessage::SelectArea(
+                                self.pixels_to_mm(band.x),
+                                self.pixels_to_mm(band.y),
+                                self.pixels_to_mm(band.width),
+                                self.pixels_to_mm(band.height),
+                            ),
+                            None => CanvasMessage::MouseReleased,
+                        },
+                        None => CanvasMessage::MouseReleased,
+                    };
+                    return (iced::event::Status::Captured, Some(message));
                 }
                 _ => {}
             }
@@ -517,4 +1831,307 @@ impl Program<CanvasMessage> for LayoutCanvas {
 
         (iced::event::Status::Ignored, None)
     }
+
+    fn mouse_interaction(&self, _state: &Self::State, bounds: Rectangle, cursor: Cursor) -> mouse::Interaction {
+        if self.busy && cursor.is_over(bounds) {
+            mouse::Interaction::Working
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_press_becomes_a_drag_past_the_threshold() {
+        let mut press = Press::new(Point::new(100.0, 100.0), Some("a".to_string()));
+        // Jitter while clicking stays a click
+        assert!(!press.moved_to(Point::new(102.0, 101.0)));
+        assert!(!press.moved_to(Point::new(100.0, 97.0)));
+        assert!(!press.dragging);
+        // The move that crosses the threshold starts the drag, once
+        assert!(press.moved_to(Point::new(103.0, 102.0)));
+        assert!(press.dragging);
+        assert!(!press.moved_to(Point::new(100.0, 100.0)));
+        assert!(press.dragging);
+        // Dragging an element moves it rather than drawing a band
+        assert_eq!(press.band(), None);
+    }
+
+    #[test]
+    fn test_drag_on_empty_page_draws_a_band() {
+        let mut press = Press::new(Point::new(100.0, 100.0), None);
+        assert_eq!(press.band(), None);
+        press.moved_to(Point::new(60.0, 130.0));
+        assert_eq!(press.band(), Some(Rectangle::new(Point::new(60.0, 100.0), Size::new(40.0, 30.0))));
+    }
+
+    #[test]
+    fn test_fit_zoom_per_dimension() {
+        // A 1000 × 20 mm banner in a viewport 1000 × 500 px inside its padding
+        let viewport = (1000.0 + PAGE_PADDING_PX, 500.0 + PAGE_PADDING_PX);
+        let page = (1000.0, 20.0);
+        let width = fit_zoom(ZoomFit::Width, viewport, page);
+        assert!((width * 1000.0 * PIXELS_PER_MM - 1000.0).abs() < 0.01);
+        assert_eq!(fit_zoom(ZoomFit::Page, viewport, page), width);
+        // Fitting the height would zoom in past the limit
+        assert_eq!(fit_zoom(ZoomFit::Height, viewport, page), MAX_ZOOM);
+        // A tiny viewport still gives a usable zoom
+        assert_eq!(fit_zoom(ZoomFit::Page, (10.0, 10.0), page), MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_crop_edit_frame_puts_crop_on_placed_rect() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        img.x_mm = 40.0;
+        img.y_mm = 30.0;
+        img.width_mm = 50.0;
+        img.height_mm = 20.0;
+        img.crop = Some(Crop { x: 0.5, y: 0.25, width: 0.5, height: 0.5 });
+
+        let edit = CropEdit::new(&img);
+        assert_eq!(edit.frame, (-10.0, 20.0, 100.0, 40.0));
+        assert_eq!(edit.crop_rect(), (40.0, 30.0, 50.0, 20.0));
+        assert_eq!(edit.original, img.crop);
+    }
+
+    #[test]
+    fn test_crop_mode_clicks() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        img.x_mm = 50.0;
+        img.y_mm = 50.0;
+        img.width_mm = 50.0;
+        img.height_mm = 50.0;
+        let edit = CropEdit::new(&img);
+        let canvas = LayoutCanvas::new(Layout::new());
+        let px = |mm: f32| canvas.mm_to_pixels(mm);
+
+        assert_eq!(
+            canvas.crop_press_at_point(&edit, px(100.0), px(100.0)),
+            CanvasMessage::StartCropDrag(Some(ResizeHandle::BottomRight))
+        );
+        assert_eq!(canvas.crop_press_at_point(&edit, px(75.0), px(75.0)), CanvasMessage::StartCropDrag(None));
+        assert_eq!(canvas.crop_press_at_point(&edit, px(10.0), px(10.0)), CanvasMessage::CommitCrop);
+    }
+
+    #[test]
+    fn test_pending_sources_are_not_loaded_while_decoding() {
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/a.jpg"), 10, 10));
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/a.jpg"), 10, 10));
+        let mut canvas = LayoutCanvas::new(layout);
+
+        let path = PathBuf::from("/nonexistent/a.jpg");
+        assert_eq!(canvas.mark_uncached_pending(), vec![path.clone()]);
+        // Already pending: not queued twice
+        assert!(canvas.mark_uncached_pending().is_empty());
+        assert!(canvas.source_cache.borrow().get(&path).is_none());
+
+        canvas.source_decoded(path.clone(), Ok(CanvasSource::new(&path, image::DynamicImage::new_rgb8(4, 4))));
+        assert!(!canvas.source_cache.borrow().is_pending(&path));
+        assert!(canvas.source_cache.borrow().get(&path).is_some());
+    }
+
+    #[test]
+    fn test_failed_sources_are_not_retried() {
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/b.jpg"), 10, 10));
+        let mut canvas = LayoutCanvas::new(layout);
+        let path = PathBuf::from("/nonexistent/b.jpg");
+
+        assert_eq!(canvas.mark_uncached_pending(), vec![path.clone()]);
+        canvas.source_decoded(path.clone(), Err("No such file".to_string()));
+        assert!(canvas.mark_uncached_pending().is_empty());
+        assert_eq!(canvas.source_cache.borrow().failure(&path), Some("No such file"));
+
+        // Removing it from the cache allows another attempt
+        canvas.remove_from_source_cache(&path);
+        assert_eq!(canvas.mark_uncached_pending(), vec![path]);
+    }
+
+    #[test]
+    fn test_shorten_cuts_long_reasons() {
+        assert_eq!(shorten("Format error", 20), "Format error");
+        assert_eq!(shorten("The image format could not be determined", 10), "The image…");
+    }
+
+    #[test]
+    fn test_canvas_sources_are_downscaled() {
+        let big = downscale_for_canvas(image::DynamicImage::new_rgb8(CANVAS_PREVIEW_MAX_PX * 2, CANVAS_PREVIEW_MAX_PX));
+        assert_eq!((big.width(), big.height()), (CANVAS_PREVIEW_MAX_PX, CANVAS_PREVIEW_MAX_PX / 2));
+        let small = downscale_for_canvas(image::DynamicImage::new_rgb8(300, 200));
+        assert_eq!((small.width(), small.height()), (300, 200));
+    }
+
+    #[test]
+    fn test_source_memory_counts_each_file_once() {
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/a.jpg"), 100, 50));
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/a.jpg"), 100, 50));
+        layout.add_image(PlacedImage::new(PathBuf::from("/nonexistent/b.png"), 10, 10));
+        let mut canvas = LayoutCanvas::new(layout);
+        // Not decoded yet: estimated as RGBA
+        assert_eq!(canvas.source_memory_bytes(), 100 * 50 * 4 + 10 * 10 * 4);
+
+        // Decoded RGB uses the real buffer size
+        let path = PathBuf::from("/nonexistent/a.jpg");
+        canvas.source_decoded(path.clone(), Ok(CanvasSource::new(&path, image::DynamicImage::new_rgb8(100, 50))));
+        assert_eq!(canvas.source_memory_bytes(), 100 * 50 * 3 + 10 * 10 * 4);
+        let info = canvas.source_info(&path).unwrap();
+        assert_eq!(info.format.as_deref(), Some("JPEG"));
+        assert_eq!(info.file_bytes, None);
+    }
+
+    #[test]
+    fn test_source_info_is_recorded_before_the_preview_is_shrunk() {
+        let (width, height) = (CANVAS_PREVIEW_MAX_PX * 2, CANVAS_PREVIEW_MAX_PX);
+        let source = CanvasSource::new(&PathBuf::from("/nonexistent/big.png"), image::DynamicImage::new_rgb8(width, height));
+        assert_eq!(source.image.width(), CANVAS_PREVIEW_MAX_PX);
+        assert_eq!((source.info.width_px, source.info.height_px), (width, height));
+        assert_eq!(source.info.decoded_bytes, width as u64 * height as u64 * 3);
+    }
+
+    #[test]
+    fn test_spread_draws_each_page_with_its_own_printable_area() {
+        let mut layout = Layout::new();
+        let single = LayoutCanvas::new(layout.clone());
+        let page = (0.0, 0.0, layout.page.width_mm, layout.page.height_mm);
+        assert_eq!(single.sheets(), vec![(page, layout.page.printable_area())]);
+
+        let (width, height) = (layout.page.width_mm, layout.page.height_mm);
+        layout.page.width_mm *= 2.0;
+        let mut canvas = LayoutCanvas::new(layout);
+        let (x, y, w, h) = (5.0, 5.0, width - 10.0, height - 10.0);
+        canvas.set_spread(Some(Spread {
+            first_page: 2,
+            fold_mm: width,
+            page_sizes: [(width, height); 2],
+            printable_areas: [(x, y, w, h), (x + width, y, w, h)],
+        }));
+        assert_eq!(
+            canvas.sheets(),
+            vec![
+                ((0.0, 0.0, width, height), (x, y, w, h)),
+                ((width, 0.0, width, height), (x + width, y, w, h)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canvas_tones_images_like_the_printed_page() {
+        let path = PathBuf::from("/nonexistent/red.png");
+        let mut sources = SourceImageCache::new();
+        let red = image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        sources.insert_decoded(path.clone(), Ok(CanvasSource::new(&path, image::DynamicImage::ImageRgba8(red))));
+        let img = PlacedImage::new(path, 2, 2);
+
+        let first_pixel = |handle: iced::widget::image::Handle| match handle {
+            iced::widget::image::Handle::Rgba { pixels, .. } => [pixels[0], pixels[1], pixels[2], pixels[3]],
+            other => panic!("expected RGBA pixels, got {:?}", other),
+        };
+        let mut cache = ImageCache::new();
+        let color = cache.get_transformed_handle(&img, &sources, None, CanvasQuality::Full).unwrap();
+        assert_eq!(first_pixel(color), [255, 0, 0, 255]);
+        // Same value the print test expects for opaque red in sepia
+        let toned = cache.get_transformed_handle(&img, &sources, Some(Toning::Sepia), CanvasQuality::Full).unwrap();
+        assert_eq!(first_pixel(toned), [75, 56, 36, 255]);
+    }
+
+    #[test]
+    fn test_draft_quality_draws_small_plain_thumbnails() {
+        let path = PathBuf::from("/nonexistent/red.png");
+        let mut sources = SourceImageCache::new();
+        let red = image::RgbaImage::from_pixel(40, 20, image::Rgba([255, 0, 0, 255]));
+        sources.insert_decoded(path.clone(), Ok(CanvasSource::new(&path, image::DynamicImage::ImageRgba8(red))));
+        let mut img = PlacedImage::new(path, 40, 20);
+        img.opacity = 0.5;
+
+        let mut cache = ImageCache::new();
+        let full = cache.get_transformed_handle(&img, &sources, Some(Toning::Sepia), CanvasQuality::Full).unwrap();
+        let draft = cache.get_transformed_handle(&img, &sources, Some(Toning::Sepia), CanvasQuality::Draft).unwrap();
+        match (full, draft) {
+            (
+                iced::widget::image::Handle::Rgba { width: 40, height: 20, pixels: full, .. },
+                iced::widget::image::Handle::Rgba { width: 10, height: 5, pixels: draft, .. },
+            ) => {
+                assert_eq!(full[3], 127);
+                // No opacity and no toning in draft
+                assert_eq!(&draft[..4], &[255, 0, 0, 255]);
+            }
+            other => panic!("unexpected handles {:?}", other),
+        }
+        assert_eq!(cache.cache.len(), 2);
+    }
+
+    #[test]
+    fn test_locked_images_have_no_resize_handles() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (50.0, 50.0, 50.0, 50.0);
+        layout.selected_image_id = Some(img.id.clone());
+        layout.add_image(img);
+
+        let mut canvas = LayoutCanvas::new(layout.clone());
+        let corner = canvas.mm_to_pixels(100.0);
+        assert!(canvas.get_resize_handle_at_point(corner, corner).is_some());
+
+        layout.images[0].locked = true;
+        canvas.set_layout(layout);
+        assert!(canvas.get_resize_handle_at_point(corner, corner).is_none());
+
+        // Drag updates for a locked image are ignored
+        let id = canvas.layout.images[0].id.clone();
+        canvas.update_image_position(&id, 10.0, 10.0);
+        canvas.update_image_bounds(&id, 10.0, 10.0, 20.0, 20.0);
+        assert_eq!(canvas.layout.images[0].bounds(), (50.0, 50.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn test_rotate_handle_sits_above_the_selection_and_follows_tilt() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (50.0, 50.0, 50.0, 50.0);
+        layout.selected_image_id = Some(img.id.clone());
+        layout.add_image(img);
+        let mut canvas = LayoutCanvas::new(layout.clone());
+
+        let mid = canvas.mm_to_pixels(75.0);
+        let top = canvas.mm_to_pixels(50.0);
+        assert!(canvas.get_rotate_handle_at_point(mid, top - ROTATE_HANDLE_OFFSET_PX).is_some());
+        assert!(canvas.get_rotate_handle_at_point(mid, top).is_none());
+
+        // Tilted 30° clockwise, the handle leans to the right
+        layout.images[0].rotation_degrees = 30.0;
+        canvas.set_layout(layout.clone());
+        let (rx, ry) = canvas.rotate_handle_point(&canvas.layout.images[0]);
+        assert!(rx > mid && ry > top - ROTATE_HANDLE_OFFSET_PX);
+
+        layout.images[0].locked = true;
+        canvas.set_layout(layout);
+        assert!(canvas.get_rotate_handle_at_point(rx, ry).is_none());
+    }
+
+    #[test]
+    fn test_handle_hit_radius_follows_handle_size() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        img.x_mm = 50.0;
+        img.y_mm = 50.0;
+        img.width_mm = 50.0;
+        img.height_mm = 50.0;
+        layout.selected_image_id = Some(img.id.clone());
+        layout.add_image(img);
+
+        let mut canvas = LayoutCanvas::new(layout);
+        let corner = canvas.mm_to_pixels(50.0);
+        let near = corner - 12.0;
+        assert!(canvas.get_resize_handle_at_point(near, near).is_none());
+
+        canvas.set_handle_size(TOUCH_HANDLE_SIZE_PX);
+        let (_, handle) = canvas.get_resize_handle_at_point(near, near).unwrap();
+        assert_eq!(handle, ResizeHandle::TopLeft);
+    }
 }
@@ -0,0 +1,313 @@
+// cli.rs - Headless command-line mode
+// `print-layout --print file.pxl [--printer NAME]` or
+// `print-layout file.pxl --export-pdf out.pdf` runs without opening a window.
+// Bare file names, `--new` and `--low-gpu` start the window instead; main()
+// takes `--low-gpu` out before the rest are parsed.
+
+use crate::config::{ConfigManager, ProjectLayout};
+use crate::layout::{Layout, ResampleFilter};
+use crate::printing::{self, PrintJob};
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "\
+Usage:
+  print-layout [--low-gpu] [--new | FILE...]     Start the graphical interface
+  print-layout --print FILE.pxl [--printer NAME] [--copies N] [--dpi N]
+  print-layout FILE.pxl --export-pdf OUT.pdf [--dpi N]
+
+Options:
+  --print FILE      Print a saved layout and exit
+  --printer NAME    CUPS printer to use (default: the system default printer)
+  --copies N        Number of copies (default: 1)
+  --export-pdf OUT  Write the layout to a PDF file and exit
+  --dpi N           Render resolution (default: 300)
+  --new             Start with an empty layout
+  --low-gpu         Show placeholders instead of image previews on the canvas
+  -h, --help        Show this help";
+
+const DEFAULT_DPI: u32 = 300;
+
+/// Starts the window in low GPU mode, whatever the preference says
+pub const LOW_GPU_FLAG: &str = "--low-gpu";
+
+/// Remove `--low-gpu` from the arguments, saying whether it was given
+pub fn take_low_gpu_flag(args: &mut Vec<String>) -> bool {
+    let given = args.len();
+    args.retain(|arg| arg != LOW_GPU_FLAG);
+    args.len() != given
+}
+
+/// Start the window with an empty layout, as the desktop entry's action does
+const NEW_FLAG: &str = "--new";
+
+/// Whether to open the window or run headless
+#[derive(Debug, Clone, PartialEq)]
+pub enum Launch {
+    /// Start the window, opening these layouts or images (none for an empty layout)
+    Gui(Vec<PathBuf>),
+    Headless(CliCommand),
+}
+
+/// What the command line asked for
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Print {
+        project: PathBuf,
+        printer: Option<String>,
+        copies: u32,
+        dpi: u32,
+    },
+    ExportPdf {
+        project: PathBuf,
+        output: PathBuf,
+        dpi: u32,
+    },
+    Help,
+}
+
+/// Parse arguments (without the program name). File names without `--print`
+/// or `--export-pdf`, as the desktop entry passes them, open in the window.
+pub fn parse_args(args: &[String]) -> Result<Launch, String> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut new = false;
+    let mut print = false;
+    let mut printer = None;
+    let mut copies = 1;
+    let mut dpi = DEFAULT_DPI;
+    let mut output = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let flag = arg.as_str();
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "-h" | "--help" => return Ok(Launch::Headless(CliCommand::Help)),
+            NEW_FLAG => new = true,
+            "--print" => {
+                print = true;
+                files.push(PathBuf::from(value()?));
+            }
+            "--printer" => printer = Some(value()?),
+            "--copies" => copies = parse_positive(flag, &value()?)?,
+            "--dpi" => dpi = parse_positive(flag, &value()?)?,
+            "--export-pdf" => output = Some(PathBuf::from(value()?)),
+            other if other.starts_with('-') => return Err(format!("Unknown option: {}", other)),
+            other => files.push(PathBuf::from(other)),
+        }
+    }
+
+    let headless = print || output.is_some();
+    if new && (headless || !files.is_empty()) {
+        return Err(format!("{} starts an empty layout and takes no other arguments", NEW_FLAG));
+    }
+    if !headless {
+        return Ok(Launch::Gui(files));
+    }
+    let project = match files.as_slice() {
+        [] => return Err("No layout file given".to_string()),
+        [project] => project.clone(),
+        [_, extra, ..] => return Err(format!("Unexpected argument: {}", extra.display())),
+    };
+    match output {
+        Some(_) if print => Err("Use either --print or --export-pdf, not both".to_string()),
+        Some(output) => Ok(Launch::Headless(CliCommand::ExportPdf { project, output, dpi })),
+        None => Ok(Launch::Headless(CliCommand::Print { project, printer, copies, dpi })),
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} must be a positive number, got '{}'", name, value)),
+    }
+}
+
+/// Run a parsed command and return the process exit code
+pub fn run(command: CliCommand) -> i32 {
+    let result = match command {
+        CliCommand::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        CliCommand::Print { project, printer, copies, dpi } => print(project, printer, copies, dpi),
+        CliCommand::ExportPdf { project, output, dpi } => export_pdf(project, output, dpi),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+fn load(project: &Path) -> Result<ProjectLayout, String> {
+    // Loading only reads the project, so a read-only config directory is fine
+    let config_manager = ConfigManager::new_or_read_only();
+    config_manager
+        .load_layout(&project.to_path_buf())
+        .map_err(|e| format!("Failed to load {}: {}", project.display(), e))
+}
+
+/// The resampling filter chosen in the app's preferences. The config is only
+/// read: a broken file is left for the app to repair.
+fn resample_filter() -> ResampleFilter {
+    ConfigManager::new()
+        .ok()
+        .and_then(|manager| manager.read_config())
+        .map(|prefs| prefs.resample_filter)
+        .unwrap_or_default()
+}
+
+/// Job title from the project's name, or its file name when it has none
+fn project_title(project: &ProjectLayout, path: &Path, page_number: usize, layout: &Layout) -> String {
+    let name = Some(project.name.clone())
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()));
+    printing::job_title(name.as_deref(), page_number, &layout.page)
+}
+
+fn print(project: PathBuf, printer: Option<String>, copies: u32, dpi: u32) -> Result<(), String> {
+    let loaded = load(&project)?;
+    let printer_name = match printer {
+        Some(name) => name,
+        None => printing::get_default_printer()
+            .map_err(|e| e.to_string())?
+            .map(|p| p.name)
+            .ok_or("No default printer; pass --printer NAME")?,
+    };
+    let caps = printing::get_printer_capabilities(&printer_name).ok();
+    // One job per page with images, in page order
+    let document = loaded.document();
+    let pages: Vec<(usize, &Layout)> =
+        document.pages().iter().enumerate().filter(|(_, layout)| !layout.images.is_empty()).collect();
+    if pages.is_empty() {
+        return Err(format!("{} has no images to print", project.display()));
+    }
+    for (index, layout) in pages {
+        let job = PrintJob {
+            layout: layout.clone(),
+            printer_name: printer_name.clone(),
+            title: project_title(&loaded, &project, index + 1, layout),
+            resample_filter: resample_filter(),
+            copies,
+            dpi,
+            extra_options: printing::page_setting_options(&layout.page, caps.as_ref(), &[]),
+            cancel: None,
+        };
+        let job_id = printing::execute_print_job(job).map_err(|e| e.to_string())?;
+        println!("Sent {} page {} to {} (job {})", project.display(), index + 1, printer_name, job_id);
+    }
+    Ok(())
+}
+
+fn export_pdf(project: PathBuf, output: PathBuf, dpi: u32) -> Result<(), String> {
+    // One PDF page per page of the project, in order
+    let document = load(&project)?.document();
+    printing::export_pages_to_pdf(document.pages(), dpi, resample_filter(), &output, None)
+        .map_err(|e| e.to_string())?;
+    println!("Wrote {} ({} pages)", output.display(), document.page_count());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_starts_gui() {
+        assert_eq!(parse_args(&[]), Ok(Launch::Gui(Vec::new())));
+    }
+
+    #[test]
+    fn test_bare_files_open_in_the_gui() {
+        // The desktop entry runs `print-layout %F`
+        assert_eq!(parse_args(&args(&["a.pxl"])), Ok(Launch::Gui(vec![PathBuf::from("a.pxl")])));
+        assert_eq!(
+            parse_args(&args(&["a.png", "b.jpg"])),
+            Ok(Launch::Gui(vec![PathBuf::from("a.png"), PathBuf::from("b.jpg")]))
+        );
+    }
+
+    #[test]
+    fn test_new_starts_an_empty_gui() {
+        // The desktop entry's "New Layout" action
+        assert_eq!(parse_args(&args(&["--new"])), Ok(Launch::Gui(Vec::new())));
+        assert!(parse_args(&args(&["--new", "a.pxl"])).is_err());
+        assert!(parse_args(&args(&["--new", "--print", "a.pxl"])).is_err());
+    }
+
+    #[test]
+    fn test_low_gpu_flag_is_taken_out_before_parsing() {
+        let mut gui = args(&["--low-gpu"]);
+        assert!(take_low_gpu_flag(&mut gui));
+        assert_eq!(parse_args(&gui), Ok(Launch::Gui(Vec::new())));
+
+        let mut print = args(&["--print", "a.pxl"]);
+        assert!(!take_low_gpu_flag(&mut print));
+        assert_eq!(print.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_print() {
+        let cmd = parse_args(&args(&["--print", "a.pxl", "--printer", "Canon", "--copies", "2"]));
+        assert_eq!(
+            cmd,
+            Ok(Launch::Headless(CliCommand::Print {
+                project: PathBuf::from("a.pxl"),
+                printer: Some("Canon".to_string()),
+                copies: 2,
+                dpi: DEFAULT_DPI,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_export_pdf_with_positional_project() {
+        let cmd = parse_args(&args(&["a.pxl", "--export-pdf", "out.pdf", "--dpi", "150"]));
+        assert_eq!(
+            cmd,
+            Ok(Launch::Headless(CliCommand::ExportPdf {
+                project: PathBuf::from("a.pxl"),
+                output: PathBuf::from("out.pdf"),
+                dpi: 150,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_export_pdf_writes_every_page() {
+        let dir = std::env::temp_dir().join(format!("print_layout_cli_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let project_path = dir.join("two.pxl");
+        let mut project = ProjectLayout::new(Layout::new(), "Two pages".to_string());
+        project.more_pages.push(Layout::new());
+        std::fs::write(&project_path, serde_json::to_string(&project).unwrap()).unwrap();
+
+        let output = dir.join("two.pdf");
+        let command = parse_args(&args(&[project_path.to_str().unwrap(), "--export-pdf", output.to_str().unwrap(), "--dpi", "10"]));
+        let Ok(Launch::Headless(command)) = command else { panic!("expected a headless export, got {:?}", command) };
+        assert_eq!(run(command), 0);
+        let pdf = String::from_utf8_lossy(&std::fs::read(&output).unwrap()).into_owned();
+        assert!(pdf.contains("/Count 2"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&args(&["--print"])).is_err());
+        assert!(parse_args(&args(&["a.pxl", "b.pxl", "--export-pdf", "out.pdf"])).is_err());
+        assert!(parse_args(&args(&["--print", "a.pxl", "--export-pdf", "b.pdf"])).is_err());
+        assert!(parse_args(&args(&["--print", "a.pxl", "--copies", "0"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+        assert_eq!(parse_args(&args(&["--help"])), Ok(Launch::Headless(CliCommand::Help)));
+    }
+}
@@ -1,12 +1,51 @@
 // config.rs - Configuration and preferences management
 // Phase 5: Persistence & State Management
 
-use crate::layout::{Layout, PaperSize, PaperType, PrintQuality, ColorMode, Orientation};
-use chrono::{DateTime, Utc};
+use crate::canvas_widget::DEFAULT_HANDLE_SIZE_PX;
+use crate::document::Document;
+use crate::interchange::{self, InterchangeImport};
+use crate::trim::DEFAULT_WHITE_THRESHOLD;
+use crate::usage::UsageEntry;
+use crate::layout::{Crop, Layout, LayoutTemplate, Page, PlacedImage, PaperSize, PaperType, PrintQuality, ColorMode, Orientation, ResampleFilter};
+use chrono::format::{Fixed, Item, StrftimeItems};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use directories::ProjectDirs;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Print renders expected to need more memory than this ask first
+pub const DEFAULT_RENDER_MEMORY_BUDGET_MB: u32 = 2048;
+
+/// Smallest and largest snap grid spacing accepted in the settings
+pub const GRID_SIZE_RANGE_MM: (f32, f32) = (1.0, 100.0);
+
+/// File name suggested by Save As unless the user sets a template
+pub const DEFAULT_SAVE_NAME_TEMPLATE: &str = "layout.pxl";
+
+/// Resolutions offered for printing and exporting
+pub const DPI_CHOICES: [u32; 5] = [72, 150, 300, 600, 1200];
+
+/// Resolution used for printing until the user picks another
+pub const DEFAULT_PRINT_DPI: u32 = 300;
+
+/// Resolution used for exports until the user picks another
+pub const DEFAULT_EXPORT_DPI: u32 = 150;
+
+/// Step (degrees) a Shift-drag of the rotation handle snaps to by default
+pub const DEFAULT_ROTATION_SNAP_DEGREES: f32 = 15.0;
+
+/// Smallest and largest rotation snap step accepted in the settings
+pub const ROTATION_SNAP_RANGE_DEGREES: (f32, f32) = (1.0, 90.0);
+
+/// Smallest and largest gap between images accepted for Auto Arrange
+pub const AUTO_ARRANGE_GUTTER_RANGE_MM: (f32, f32) = (0.0, 50.0);
+
+/// How many recently used printers are listed first in the printer picker
+pub const MAX_RECENT_PRINTERS: usize = 3;
 
 /// Settings from the last successful print job
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -23,6 +62,348 @@ pub struct LastPrintSettings {
     pub last_success_time: Option<DateTime<Utc>>,
 }
 
+/// A named combination of print settings applied with one click
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrintPreset {
+    pub name: String,
+    pub printer_name: Option<String>,
+    pub paper_size: PaperSize,
+    pub paper_type: PaperType,
+    pub print_quality: PrintQuality,
+    pub color_mode: ColorMode,
+    pub borderless: bool,
+    pub margins: (f32, f32, f32, f32), // top, bottom, left, right
+    pub dpi: u32,
+}
+
+impl PrintPreset {
+    /// Capture the current page settings, printer and DPI under `name`
+    pub fn capture(name: String, printer_name: Option<String>, page: &Page, dpi: u32) -> Self {
+        Self {
+            name,
+            printer_name,
+            paper_size: page.paper_size,
+            paper_type: page.paper_type,
+            print_quality: page.print_quality,
+            color_mode: page.color_mode,
+            borderless: page.borderless,
+            margins: (page.margin_top_mm, page.margin_bottom_mm, page.margin_left_mm, page.margin_right_mm),
+            dpi,
+        }
+    }
+
+    /// Apply the page settings, keeping the page's orientation. Returns a
+    /// warning for anything that could not be applied as saved.
+    pub fn apply_to(&self, page: &mut Page) -> Vec<String> {
+        let mut warnings = Vec::new();
+        page.set_paper_size(self.paper_size, true);
+        page.paper_type = self.paper_type;
+        page.print_quality = self.print_quality;
+        page.color_mode = self.color_mode;
+        page.set_borderless(self.borderless, true);
+        if !self.borderless {
+            let (top, bottom, left, right) = self.margins;
+            if page.try_set_margins(top, bottom, left, right).is_err() {
+                page.apply_default_margins();
+                warnings.push(format!(
+                    "Saved margins don't fit {} in this orientation; using the paper defaults",
+                    self.paper_size
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+/// A named look for a placed image (rotation, flips, opacity and crop
+/// shape) without the image itself, so it can be applied to other photos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageStyle {
+    pub name: String,
+    pub rotation_degrees: f32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub opacity: f32,
+    /// Width / height of the cropped frame; None when the image was not cropped
+    pub crop_aspect: Option<f32>,
+}
+
+impl ImageStyle {
+    /// Capture the style of `image` under `name`
+    pub fn capture(name: String, image: &PlacedImage) -> Self {
+        Self {
+            name,
+            rotation_degrees: image.rotation_degrees,
+            flip_horizontal: image.flip_horizontal,
+            flip_vertical: image.flip_vertical,
+            opacity: image.opacity,
+            crop_aspect: image
+                .crop
+                .filter(|crop| !crop.is_full())
+                .map(|crop| image.content_aspect() * crop.width / crop.height),
+        }
+    }
+
+    /// Apply the style to `image`. The image keeps its width; the crop is
+    /// re-centered on the new photo and the height follows the frame shape.
+    pub fn apply_to(&self, image: &mut PlacedImage) {
+        image.rotation_degrees = self.rotation_degrees.rem_euclid(360.0);
+        image.flip_horizontal = self.flip_horizontal;
+        image.flip_vertical = self.flip_vertical;
+        image.opacity = self.opacity.clamp(0.0, 1.0);
+        let content_aspect = image.content_aspect();
+        let frame_aspect = match self.crop_aspect.filter(|a| a.is_finite() && *a > 0.0) {
+            Some(aspect) => {
+                image.crop = Some(Crop::cover(content_aspect, aspect, (0.5, 0.5)));
+                aspect
+            }
+            None => {
+                image.crop = None;
+                content_aspect
+            }
+        };
+        image.height_mm = image.width_mm / frame_aspect;
+    }
+}
+
+/// Unit that lengths are typed and shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MeasurementUnit {
+    #[default]
+    Millimeters,
+    Inches,
+}
+
+#[allow(clippy::wrong_self_convention)]
+impl MeasurementUnit {
+    pub const ALL: [MeasurementUnit; 2] = [MeasurementUnit::Millimeters, MeasurementUnit::Inches];
+
+    /// Convert a length in this unit to millimeters
+    pub fn to_mm(&self, value: f32) -> f32 {
+        match self {
+            MeasurementUnit::Millimeters => value,
+            MeasurementUnit::Inches => value * 25.4,
+        }
+    }
+
+    /// Convert millimeters to a length in this unit
+    pub fn from_mm(&self, mm: f32) -> f32 {
+        match self {
+            MeasurementUnit::Millimeters => mm,
+            MeasurementUnit::Inches => mm / 25.4,
+        }
+    }
+
+    /// A length for an editable field: 0.1 mm or 0.01 in
+    pub fn format(&self, mm: f32) -> String {
+        match self {
+            MeasurementUnit::Millimeters => format!("{:.1}", mm),
+            MeasurementUnit::Inches => format!("{:.2}", self.from_mm(mm)),
+        }
+    }
+
+    /// A length rounded to 0.01 of this unit, without trailing zeros
+    pub fn format_compact(&self, mm: f32) -> String {
+        ((self.from_mm(mm) * 100.0).round() / 100.0).to_string()
+    }
+
+    /// The unit written out, for messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeasurementUnit::Millimeters => "millimeters",
+            MeasurementUnit::Inches => "inches",
+        }
+    }
+
+    /// The other unit
+    pub fn toggled(&self) -> MeasurementUnit {
+        match self {
+            MeasurementUnit::Millimeters => MeasurementUnit::Inches,
+            MeasurementUnit::Inches => MeasurementUnit::Millimeters,
+        }
+    }
+
+    /// Parse a positive length typed in this unit, returned in millimeters
+    pub fn parse_mm(&self, text: &str) -> Option<f32> {
+        let value = text.trim().parse::<f32>().ok()?;
+        (value.is_finite() && value > 0.0).then(|| self.to_mm(value))
+    }
+}
+
+impl std::fmt::Display for MeasurementUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeasurementUnit::Millimeters => write!(f, "mm"),
+            MeasurementUnit::Inches => write!(f, "in"),
+        }
+    }
+}
+
+/// Settings panel tabs (mimicking Canon PPL)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SettingsTab {
+    #[default]
+    PrintSettings,
+    Layout,
+    ImageTools,
+}
+
+/// Usable range for the settings sidebar width in pixels
+pub const SETTINGS_PANEL_WIDTH_RANGE: (f32, f32) = (180.0, 520.0);
+const DEFAULT_SETTINGS_PANEL_WIDTH: f32 = 220.0;
+
+/// Usable range for the thumbnail strip height in pixels
+pub const THUMBNAIL_STRIP_HEIGHT_RANGE: (f32, f32) = (80.0, 320.0);
+const DEFAULT_THUMBNAIL_STRIP_HEIGHT: f32 = 120.0;
+
+/// Settings sidebar width kept within its usable range
+pub fn clamp_settings_panel_width(width: f32) -> f32 {
+    let (min, max) = SETTINGS_PANEL_WIDTH_RANGE;
+    if width.is_finite() { width.clamp(min, max) } else { DEFAULT_SETTINGS_PANEL_WIDTH }
+}
+
+/// Thumbnail strip height kept within its usable range
+pub fn clamp_thumbnail_strip_height(height: f32) -> f32 {
+    let (min, max) = THUMBNAIL_STRIP_HEIGHT_RANGE;
+    if height.is_finite() { height.clamp(min, max) } else { DEFAULT_THUMBNAIL_STRIP_HEIGHT }
+}
+
+/// Overlays drawn on the canvas, switched from the View menu. They are
+/// saved together, so a new overlay is a field here, an entry in
+/// `ViewOption` and a drawing step in the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewOptions {
+    /// Grid lines over the page; always shown while snapping to the grid
+    pub show_grid: bool,
+    /// Outline of the printable area
+    pub show_margins: bool,
+    /// Gray over the margins, where the printer can't print
+    pub shade_margins: bool,
+    /// Red outline on images that cover part of another image
+    pub highlight_overlaps: bool,
+    /// Rule-of-thirds lines over the selected image
+    pub show_thirds: bool,
+    /// How much detail the canvas draws images with
+    pub render_quality: CanvasQuality,
+}
+
+/// Image detail on the canvas. Printing and exports always use full quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum CanvasQuality {
+    #[default]
+    Full,
+    /// Quarter-resolution nearest-neighbor thumbnails without opacity,
+    /// toning or labels, so large layouts stay quick to arrange
+    Draft,
+}
+
+impl CanvasQuality {
+    pub fn toggled(self) -> Self {
+        match self {
+            CanvasQuality::Full => CanvasQuality::Draft,
+            CanvasQuality::Draft => CanvasQuality::Full,
+        }
+    }
+}
+
+impl Default for ViewOptions {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            show_margins: true,
+            shade_margins: false,
+            highlight_overlaps: false,
+            show_thirds: false,
+            render_quality: CanvasQuality::Full,
+        }
+    }
+}
+
+/// One entry of the View menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewOption {
+    Grid,
+    Margins,
+    MarginShading,
+    Overlaps,
+    Thirds,
+}
+
+impl ViewOption {
+    pub const ALL: [ViewOption; 5] = [
+        ViewOption::Grid,
+        ViewOption::Margins,
+        ViewOption::MarginShading,
+        ViewOption::Overlaps,
+        ViewOption::Thirds,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewOption::Grid => "Grid",
+            ViewOption::Margins => "Margin outline",
+            ViewOption::MarginShading => "Shade margins",
+            ViewOption::Overlaps => "Highlight overlaps",
+            ViewOption::Thirds => "Rule of thirds",
+        }
+    }
+}
+
+impl ViewOptions {
+    /// The field behind a View menu entry
+    pub fn get_mut(&mut self, option: ViewOption) -> &mut bool {
+        match option {
+            ViewOption::Grid => &mut self.show_grid,
+            ViewOption::Margins => &mut self.show_margins,
+            ViewOption::MarginShading => &mut self.shade_margins,
+            ViewOption::Overlaps => &mut self.highlight_overlaps,
+            ViewOption::Thirds => &mut self.show_thirds,
+        }
+    }
+
+    pub fn get(&self, option: ViewOption) -> bool {
+        let mut options = *self;
+        *options.get_mut(option)
+    }
+}
+
+/// Color of the canvas workspace around the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum WorkspaceBackground {
+    White,
+    LightGray,
+    #[default]
+    NeutralGray,
+    DarkGray,
+    Black,
+}
+
+impl WorkspaceBackground {
+    /// RGB components in the 0.0-1.0 range
+    pub fn to_rgb(self) -> (f32, f32, f32) {
+        match self {
+            WorkspaceBackground::White => (1.0, 1.0, 1.0),
+            WorkspaceBackground::LightGray => (0.75, 0.75, 0.75),
+            WorkspaceBackground::NeutralGray => (0.5, 0.5, 0.5),
+            WorkspaceBackground::DarkGray => (0.25, 0.25, 0.25),
+            WorkspaceBackground::Black => (0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl std::fmt::Display for WorkspaceBackground {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkspaceBackground::White => write!(f, "White"),
+            WorkspaceBackground::LightGray => write!(f, "Light Gray"),
+            WorkspaceBackground::NeutralGray => write!(f, "Neutral Gray"),
+            WorkspaceBackground::DarkGray => write!(f, "Dark Gray"),
+            WorkspaceBackground::Black => write!(f, "Black"),
+        }
+    }
+}
+
 /// User preferences that persist across sessions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPreferences {
@@ -43,6 +424,144 @@ pub struct UserPreferences {
     /// Settings from the last successful print
     #[serde(default)]
     pub last_print_settings: LastPrintSettings,
+    /// Canvas color drawn around the page
+    #[serde(default)]
+    pub workspace_background: WorkspaceBackground,
+    /// Printers that jobs were successfully sent to, most recent first
+    #[serde(default)]
+    pub recent_printers: Vec<String>,
+    /// Pick the photo paper matching the first image added to an empty layout
+    #[serde(default)]
+    pub auto_paper_from_first_image: bool,
+    /// One-click print setting presets shown in the top bar
+    #[serde(default)]
+    pub presets: Vec<PrintPreset>,
+    /// Size of the canvas resize handles; larger values suit touch and pen input
+    #[serde(default = "default_handle_size_px")]
+    pub handle_size_px: f32,
+    /// Saved image styles offered in the Image Tools tab
+    #[serde(default)]
+    pub image_styles: Vec<ImageStyle>,
+    /// Layout templates saved by the user, offered after the built-in ones
+    #[serde(default)]
+    pub layout_templates: Vec<LayoutTemplate>,
+    /// Space (mm) Auto Arrange leaves between images and between rows
+    #[serde(default = "default_auto_arrange_gutter_mm")]
+    pub auto_arrange_gutter_mm: f32,
+    /// Show the vertical image list next to the canvas
+    #[serde(default)]
+    pub show_image_list: bool,
+    /// Settings sidebar collapsed to a strip of tab buttons
+    #[serde(default)]
+    pub settings_collapsed: bool,
+    /// Re-apply the last Fit/Fit Width/Fit Height zoom when the window is resized
+    #[serde(default)]
+    pub fit_on_resize: bool,
+    /// Draw labelled placeholders instead of image previews on the canvas,
+    /// for graphics chips that stutter uploading them
+    #[serde(default)]
+    pub low_gpu: bool,
+    /// Settings tab shown at startup: the last one used
+    #[serde(default)]
+    pub settings_tab: SettingsTab,
+    /// Settings sidebar width in pixels, set by dragging its edge
+    #[serde(default = "default_settings_panel_width")]
+    pub settings_panel_width: f32,
+    /// Thumbnail strip height in pixels, set by dragging its edge
+    #[serde(default = "default_thumbnail_strip_height")]
+    pub thumbnail_strip_height: f32,
+    /// Ask for a print size every time images are added
+    #[serde(default)]
+    pub ask_size_when_adding: bool,
+    /// Move and shrink images back inside the printable area when the
+    /// borderless toggle changes the margins
+    #[serde(default)]
+    pub keep_images_in_printable_area: bool,
+    /// Suggested Save As file name; strftime codes such as %Y%m%d are filled in
+    #[serde(default = "default_save_name_template")]
+    pub save_name_template: String,
+    /// Print renders estimated above this many megabytes are confirmed first
+    #[serde(default = "default_render_memory_budget_mb")]
+    pub render_memory_budget_mb: u32,
+    /// Brightness (0-255) above which auto-trim treats scan borders as white
+    #[serde(default = "default_trim_threshold")]
+    pub trim_threshold: u8,
+    /// Crop the transparent padding off graphics as they are added
+    #[serde(default = "default_trim_transparent_on_add")]
+    pub trim_transparent_on_add: bool,
+    /// Resolution pages are rendered at for the printer
+    #[serde(default = "default_print_dpi")]
+    pub print_dpi: u32,
+    /// Resolution pages are rendered at for PNG, JPEG and PDF exports
+    #[serde(default = "default_export_dpi")]
+    pub export_dpi: u32,
+    /// Resampling filter for images that don't choose their own
+    #[serde(default)]
+    pub resample_filter: ResampleFilter,
+    /// Aspect-ratio lock given to newly added images
+    #[serde(default = "default_lock_aspect")]
+    pub lock_aspect_default: bool,
+    /// Turn images a quarter turn when that fills their cell better while
+    /// applying a template or arranging, or packs them tighter in Auto Arrange
+    #[serde(default)]
+    pub auto_rotate_to_fit: bool,
+    /// Step (degrees) the rotation handle snaps to while Shift is held
+    #[serde(default = "default_rotation_snap_degrees")]
+    pub rotation_snap_degrees: f32,
+    /// Unit margins and image sizes are shown and typed in
+    #[serde(default)]
+    pub measurement_unit: MeasurementUnit,
+    /// Canvas overlays chosen in the View menu
+    #[serde(default)]
+    pub view_options: ViewOptions,
+}
+
+fn default_save_name_template() -> String {
+    DEFAULT_SAVE_NAME_TEMPLATE.to_string()
+}
+
+fn default_render_memory_budget_mb() -> u32 {
+    DEFAULT_RENDER_MEMORY_BUDGET_MB
+}
+
+fn default_trim_threshold() -> u8 {
+    DEFAULT_WHITE_THRESHOLD
+}
+
+fn default_trim_transparent_on_add() -> bool {
+    true
+}
+
+fn default_settings_panel_width() -> f32 {
+    DEFAULT_SETTINGS_PANEL_WIDTH
+}
+
+fn default_thumbnail_strip_height() -> f32 {
+    DEFAULT_THUMBNAIL_STRIP_HEIGHT
+}
+
+fn default_print_dpi() -> u32 {
+    DEFAULT_PRINT_DPI
+}
+
+fn default_export_dpi() -> u32 {
+    DEFAULT_EXPORT_DPI
+}
+
+fn default_handle_size_px() -> f32 {
+    DEFAULT_HANDLE_SIZE_PX
+}
+
+fn default_auto_arrange_gutter_mm() -> f32 {
+    5.0
+}
+
+fn default_lock_aspect() -> bool {
+    true
+}
+
+fn default_rotation_snap_degrees() -> f32 {
+    DEFAULT_ROTATION_SNAP_DEGREES
 }
 
 impl Default for UserPreferences {
@@ -63,7 +582,77 @@ impl Default for UserPreferences {
             snap_to_grid: false,
             grid_size_mm: 10.0,
             last_print_settings: LastPrintSettings::default(),
+            workspace_background: WorkspaceBackground::default(),
+            recent_printers: Vec::new(),
+            auto_paper_from_first_image: false,
+            presets: Vec::new(),
+            handle_size_px: DEFAULT_HANDLE_SIZE_PX,
+            image_styles: Vec::new(),
+            layout_templates: Vec::new(),
+            auto_arrange_gutter_mm: default_auto_arrange_gutter_mm(),
+            show_image_list: false,
+            settings_collapsed: false,
+            fit_on_resize: false,
+            low_gpu: false,
+            settings_tab: SettingsTab::default(),
+            settings_panel_width: DEFAULT_SETTINGS_PANEL_WIDTH,
+            thumbnail_strip_height: DEFAULT_THUMBNAIL_STRIP_HEIGHT,
+            ask_size_when_adding: false,
+            keep_images_in_printable_area: false,
+            save_name_template: DEFAULT_SAVE_NAME_TEMPLATE.to_string(),
+            render_memory_budget_mb: DEFAULT_RENDER_MEMORY_BUDGET_MB,
+            trim_threshold: DEFAULT_WHITE_THRESHOLD,
+            trim_transparent_on_add: true,
+            print_dpi: DEFAULT_PRINT_DPI,
+            export_dpi: DEFAULT_EXPORT_DPI,
+            resample_filter: ResampleFilter::Auto,
+            lock_aspect_default: true,
+            auto_rotate_to_fit: false,
+            rotation_snap_degrees: DEFAULT_ROTATION_SNAP_DEGREES,
+            measurement_unit: MeasurementUnit::Millimeters,
+            view_options: ViewOptions::default(),
+        }
+    }
+}
+
+impl UserPreferences {
+    /// File name to suggest in the Save As dialog: the name template with
+    /// its date codes filled in for `now`, ending in `.pxl`. A template with
+    /// invalid codes, time zone codes (a local date and time has no zone) or
+    /// no usable name falls back to the default.
+    pub fn suggested_save_name(&self, now: NaiveDateTime) -> String {
+        let valid = |template: &str| {
+            !StrftimeItems::new(template).any(|item| item == Item::Error || needs_time_zone(&item))
+        };
+        let template = self.save_name_template.trim();
+        let template = if valid(template) { template } else { DEFAULT_SAVE_NAME_TEMPLATE };
+        let mut name = String::new();
+        if write!(&mut name, "{}", now.format(template)).is_err() {
+            return DEFAULT_SAVE_NAME_TEMPLATE.to_string();
+        }
+        let name = name.replace(['/', '\\'], "_");
+        let stem = name.strip_suffix(".pxl").unwrap_or(&name).trim();
+        if stem.is_empty() {
+            return DEFAULT_SAVE_NAME_TEMPLATE.to_string();
+        }
+        format!("{}.pxl", stem)
+    }
+
+    /// Order printer names for the picker: recently used printers that are
+    /// still available come first, followed by the rest in their original order
+    pub fn printers_recent_first(&self, names: Vec<String>) -> Vec<String> {
+        let mut ordered: Vec<String> = self
+            .recent_printers
+            .iter()
+            .filter(|recent| names.contains(recent))
+            .cloned()
+            .collect();
+        for name in names {
+            if !ordered.contains(&name) {
+                ordered.push(name);
+            }
         }
+        ordered
     }
 }
 
@@ -71,7 +660,14 @@ impl Default for UserPreferences {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectLayout {
     pub version: String,
+    /// First page; the only one in files from before multi-page projects
     pub layout: Layout,
+    /// Pages after the first, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub more_pages: Vec<Layout>,
+    /// Page shown when the project was saved
+    #[serde(default)]
+    pub current_page: usize,
     pub created_at: DateTime<Utc>,
     pub last_modified: DateTime<Utc>,
     pub name: String,
@@ -84,6 +680,8 @@ impl ProjectLayout {
         Self {
             version: env!("CARGO_PKG_VERSION").to_string(),
             layout,
+            more_pages: Vec::new(),
+            current_page: 0,
             created_at: now,
             last_modified: now,
             name,
@@ -91,94 +689,299 @@ impl ProjectLayout {
         }
     }
 
+    pub fn from_document(document: &Document, name: String) -> Self {
+        let mut project = Self::new(Layout::new(), name);
+        project.set_document(document);
+        project
+    }
+
+    /// Every page, with the one shown when it was saved as current
+    pub fn document(&self) -> Document {
+        let pages = std::iter::once(&self.layout).chain(&self.more_pages).cloned().collect();
+        Document::from_pages(pages, self.current_page)
+    }
+
+    pub fn set_document(&mut self, document: &Document) {
+        let (first, rest) = document.pages().split_first().expect("a document has pages");
+        self.layout = first.clone();
+        self.more_pages = rest.to_vec();
+        self.current_page = document.current_page();
+    }
+
     pub fn update_modified(&mut self) {
         self.last_modified = Utc::now();
     }
 }
 
+/// What happened when config.json could not be parsed at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigRepair {
+    /// Parse error from the broken file
+    pub error: String,
+    /// Where the broken file was moved, if moving it succeeded
+    pub backup_path: Option<PathBuf>,
+    /// Settings that were read from the broken file
+    pub recovered: Vec<String>,
+}
+
+/// Writes the bytes of a file being saved; tests swap it to corrupt output
+type FileWriter = fn(&mut fs::File, &[u8]) -> Result<(), std::io::Error>;
+
+fn write_all(file: &mut fs::File, bytes: &[u8]) -> Result<(), std::io::Error> {
+    file.write_all(bytes)
+}
+
 /// Configuration file management
 #[derive(Clone)]
 pub struct ConfigManager {
     config_dir: PathBuf,
     cache_dir: PathBuf,
+    /// Why the directories can't be written, when running read-only
+    read_only_reason: Option<String>,
+    write_file: FileWriter,
 }
 
 impl ConfigManager {
     pub fn new() -> Result<Self, std::io::Error> {
-        let proj_dirs = ProjectDirs::from("", "", "print_layout")
-            .ok_or_else(|| std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Could not determine config directory"
-            ))?;
-
-        let config_dir = proj_dirs.config_dir().to_path_buf();
-        let cache_dir = proj_dirs.cache_dir().to_path_buf();
+        let (config_dir, cache_dir) = default_dirs()?;
+        Self::with_dirs(config_dir, cache_dir)
+    }
 
+    /// Use the given config and cache directories, creating them if needed.
+    /// Fails if either can't be created or written to.
+    pub fn with_dirs(config_dir: PathBuf, cache_dir: PathBuf) -> Result<Self, std::io::Error> {
         // Ensure directories exist
         fs::create_dir_all(&config_dir)?;
         fs::create_dir_all(&cache_dir)?;
         fs::create_dir_all(config_dir.join("backups"))?;
 
+        // Directories that already exist can still be read-only
+        check_writable(&config_dir)?;
+        check_writable(&cache_dir)?;
+
         Ok(Self {
             config_dir,
             cache_dir,
+            read_only_reason: None,
+            write_file: write_all,
         })
     }
 
+    /// Like `new`, but if the directories can't be set up the manager runs
+    /// read-only instead of failing: preferences stay at their defaults and
+    /// settings, auto-saves, backups and usage are never written. Projects
+    /// still load from and save to the paths the user picks.
+    pub fn new_or_read_only() -> Self {
+        match default_dirs() {
+            Ok((config_dir, cache_dir)) => Self::with_dirs_or_read_only(config_dir, cache_dir),
+            Err(e) => Self::read_only(PathBuf::new(), PathBuf::new(), e),
+        }
+    }
+
+    /// `with_dirs`, falling back to read-only like `new_or_read_only`
+    pub fn with_dirs_or_read_only(config_dir: PathBuf, cache_dir: PathBuf) -> Self {
+        match Self::with_dirs(config_dir.clone(), cache_dir.clone()) {
+            Ok(manager) => manager,
+            Err(e) => Self::read_only(config_dir, cache_dir, e),
+        }
+    }
+
+    fn read_only(config_dir: PathBuf, cache_dir: PathBuf, error: std::io::Error) -> Self {
+        log::warn!("Config directory unavailable ({}), running without saved settings", error);
+        Self {
+            config_dir,
+            cache_dir,
+            read_only_reason: Some(error.to_string()),
+            write_file: write_all,
+        }
+    }
+
+    /// Why nothing can be saved to the config directory, if running read-only
+    pub fn read_only_reason(&self) -> Option<&str> {
+        self.read_only_reason.as_deref()
+    }
+
+    /// Error for writes attempted while running read-only
+    fn check_not_read_only(&self) -> Result<(), std::io::Error> {
+        match &self.read_only_reason {
+            Some(reason) => Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Config directory is read-only: {}", reason),
+            )),
+            None => Ok(()),
+        }
+    }
+
     /// Load user preferences from config file
-    pub fn load_config(&self) -> UserPreferences {
+    /// Save `contents` to `path` so that a crash or power cut never leaves a
+    /// truncated file: the text goes to a temp file that is fsynced and parsed
+    /// back as `T`, then `before_replace` runs, and only then is the temp file
+    /// renamed over `path` and the directory fsynced. On any failure the temp
+    /// file is removed and `path` keeps its old contents.
+    fn write_durably<T: DeserializeOwned>(
+        &self,
+        path: &Path,
+        contents: &str,
+        before_replace: impl FnOnce() -> Result<(), std::io::Error>,
+    ) -> Result<(), std::io::Error> {
+        let temp_path = path.with_extension("tmp");
+        let result = self
+            .write_verified::<T>(&temp_path, contents)
+            .and_then(|()| before_replace())
+            .and_then(|()| fs::rename(&temp_path, path));
+        if result.is_err() {
+            fs::remove_file(&temp_path).ok();
+        }
+        result?;
+        sync_parent_dir(path)
+    }
+
+    /// Write and fsync `path`, then read it back to check it parses as `T`
+    fn write_verified<T: DeserializeOwned>(&self, path: &Path, contents: &str) -> Result<(), std::io::Error> {
+        let mut file = fs::File::create(path)?;
+        (self.write_file)(&mut file, contents.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        let written = fs::read_to_string(path)?;
+        serde_json::from_str::<T>(&written).map(|_| ()).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} did not read back after writing: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Directory holding config.json, presets and backups
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    /// Load user preferences. A config file that fails to parse is moved to
+    /// `config.json.broken-<timestamp>` and whatever settings in it are still
+    /// valid are kept; the returned `ConfigRepair` describes what happened.
+    pub fn load_config(&self) -> (UserPreferences, Option<ConfigRepair>) {
         let config_path = self.config_dir.join("config.json");
         
-        if !config_path.exists() {
-            log::info!("Config file not found, using defaults");
-            return UserPreferences::default();
+        if !config_path.exists() || self.read_only_reason.is_some() {
+            // First run: pick the paper size once from the locale and record
+            // it so later launches never re-decide
+            let default_paper_size = PaperSize::for_locale(&system_locale());
+            log::info!("Config file not found, using defaults with {} paper", default_paper_size);
+            let prefs = UserPreferences {
+                default_paper_size,
+                ..Default::default()
+            };
+            if self.read_only_reason.is_none() {
+                if let Err(e) = self.save_config(&prefs) {
+                    log::warn!("Failed to save initial config: {}", e);
+                }
+            }
+            return (prefs, None);
         }
 
         match fs::read_to_string(&config_path) {
             Ok(contents) => match serde_json::from_str(&contents) {
                 Ok(config) => {
                     log::info!("Loaded config from {:?}", config_path);
-                    config
+                    (config, None)
                 }
                 Err(e) => {
-                    log::warn!("Failed to parse config: {}, using defaults", e);
-                    UserPreferences::default()
+                    log::warn!("Failed to parse config: {}, repairing", e);
+                    let (prefs, repair) = self.repair_config(&config_path, &contents, e.to_string());
+                    (prefs, Some(repair))
                 }
             },
             Err(e) => {
                 log::warn!("Failed to read config: {}, using defaults", e);
-                UserPreferences::default()
+                (UserPreferences::default(), None)
+            }
+        }
+    }
+
+    /// Move a broken config file aside and save the settings recovered from it
+    fn repair_config(&self, config_path: &Path, contents: &str, error: String) -> (UserPreferences, ConfigRepair) {
+        let base = UserPreferences {
+            default_paper_size: PaperSize::for_locale(&system_locale()),
+            ..Default::default()
+        };
+        let (prefs, recovered) = recover_preferences(contents, base);
+
+        let stamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let broken_path = self.config_dir.join(format!("config.json.broken-{}", stamp));
+        let backup_path = match fs::rename(config_path, &broken_path) {
+            Ok(()) => {
+                log::info!("Moved broken config to {:?}", broken_path);
+                Some(broken_path)
+            }
+            Err(e) => {
+                log::error!("Failed to move broken config aside: {}", e);
+                None
+            }
+        };
+        // Only overwrite the original once it is safely kept elsewhere
+        if backup_path.is_some() {
+            if let Err(e) = self.save_config(&prefs) {
+                log::error!("Failed to save repaired config: {}", e);
             }
         }
+
+        let repair = ConfigRepair { error, backup_path, recovered };
+        (prefs, repair)
+    }
+
+    /// Read the saved preferences as they are, for tools that only consult
+    /// them: a missing or unparsable file gives None, and nothing is
+    /// repaired, backed up or written
+    pub fn read_config(&self) -> Option<UserPreferences> {
+        let contents = fs::read_to_string(self.config_dir.join("config.json")).ok()?;
+        serde_json::from_str(&contents).ok()
     }
 
     /// Save user preferences to config file
     pub fn save_config(&self, prefs: &UserPreferences) -> Result<(), std::io::Error> {
+        self.check_not_read_only()?;
         let config_path = self.config_dir.join("config.json");
         let json = serde_json::to_string_pretty(prefs)?;
-        
-        // Atomic write: write to temp file, then rename
-        let temp_path = config_path.with_extension("tmp");
-        fs::write(&temp_path, json)?;
-        fs::rename(temp_path, &config_path)?;
+        self.write_durably::<UserPreferences>(&config_path, &json, || Ok(()))?;
         
         log::info!("Saved config to {:?}", config_path);
         Ok(())
     }
 
+    /// Load recorded print usage (empty if nothing has been printed yet)
+    pub fn load_usage(&self) -> Result<Vec<UsageEntry>, std::io::Error> {
+        let stats_path = self.config_dir.join("stats.json");
+        if !stats_path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&stats_path)?;
+        serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Append a completed job to stats.json
+    pub fn append_usage(&self, entry: UsageEntry) -> Result<(), std::io::Error> {
+        self.check_not_read_only()?;
+        let stats_path = self.config_dir.join("stats.json");
+        let mut entries = self.load_usage()?;
+        entries.push(entry);
+        let json = serde_json::to_string_pretty(&entries)?;
+        self.write_durably::<Vec<UsageEntry>>(&stats_path, &json, || Ok(()))
+    }
+
     /// Save a project layout to file
     pub fn save_layout(&self, project: &ProjectLayout, path: &PathBuf) -> Result<(), std::io::Error> {
         let json = serde_json::to_string_pretty(project)?;
         
-        // Create backup if file exists
-        if path.exists() {
-            self.create_backup(path)?;
-        }
-        
-        // Atomic write
-        let temp_path = path.with_extension("tmp");
-        fs::write(&temp_path, json)?;
-        fs::rename(temp_path, path)?;
+        // Back up the old file only once the new one is known to be good
+        // (there is nowhere to keep one when read-only)
+        self.write_durably::<ProjectLayout>(path, &json, || {
+            if path.exists() && self.read_only_reason.is_none() {
+                self.create_backup(path)?;
+            }
+            Ok(())
+        })?;
         
         log::info!("Saved layout to {:?}", path);
         Ok(())
@@ -187,8 +990,20 @@ impl ConfigManager {
     /// Load a project layout from file
     pub fn load_layout(&self, path: &PathBuf) -> Result<ProjectLayout, std::io::Error> {
         let contents = fs::read_to_string(path)?;
-        let project: ProjectLayout = serde_json::from_str(&contents)
+        let mut project: ProjectLayout = serde_json::from_str(&contents)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        for layout in std::iter::once(&mut project.layout).chain(&mut project.more_pages) {
+            // Hand-edited or older files can carry margins that collide
+            let page = &mut layout.page;
+            let (top, bottom, left, right) = (page.margin_top_mm, page.margin_bottom_mm, page.margin_left_mm, page.margin_right_mm);
+            if let Err(e) = page.try_set_margins(top, bottom, left, right) {
+                log::warn!("{:?}: {}; using the paper's default margins", path, e);
+                page.apply_default_margins();
+            }
+            // Stored z-indexes win over the array order
+            layout.normalize_z();
+        }
         
         log::info!("Loaded layout from {:?}", path);
         Ok(project)
@@ -245,18 +1060,19 @@ impl ConfigManager {
     }
 
     /// Save auto-save file
-    pub fn auto_save(&self, layout: &Layout) -> Result<(), std::io::Error> {
+    pub fn auto_save(&self, document: &Document) -> Result<(), std::io::Error> {
+        self.check_not_read_only()?;
         let auto_save_path = self.cache_dir.join("auto_save.pxl");
-        let project = ProjectLayout::new(layout.clone(), "Auto-save".to_string());
+        let project = ProjectLayout::from_document(document, "Auto-save".to_string());
         let json = serde_json::to_string_pretty(&project)?;
-        fs::write(&auto_save_path, json)?;
+        self.write_durably::<ProjectLayout>(&auto_save_path, &json, || Ok(()))?;
         log::debug!("Auto-saved layout");
         Ok(())
     }
 
     /// Check if auto-save file exists
     pub fn has_auto_save(&self) -> bool {
-        self.cache_dir.join("auto_save.pxl").exists()
+        self.read_only_reason.is_none() && self.cache_dir.join("auto_save.pxl").exists()
     }
 
     /// Load auto-save file
@@ -286,10 +1102,492 @@ impl ConfigManager {
         // Keep only 10 most recent
         prefs.recent_files.truncate(10);
     }
+
+    /// Add a printer to the recently used printers list
+    pub fn add_recent_printer(&self, prefs: &mut UserPreferences, name: String) {
+        prefs.recent_printers.retain(|p| p != &name);
+        prefs.recent_printers.insert(0, name);
+        prefs.recent_printers.truncate(MAX_RECENT_PRINTERS);
+    }
+
+    /// Add a preset, replacing any existing preset with the same name
+    pub fn save_preset(&self, prefs: &mut UserPreferences, preset: PrintPreset) {
+        match prefs.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => prefs.presets.push(preset),
+        }
+    }
+
+    /// Add a layout template, replacing any existing template with the same name
+    pub fn save_layout_template(&self, prefs: &mut UserPreferences, template: LayoutTemplate) {
+        match prefs.layout_templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => prefs.layout_templates.push(template),
+        }
+    }
+
+    /// Add an image style, replacing any existing style with the same name
+    pub fn save_image_style(&self, prefs: &mut UserPreferences, style: ImageStyle) {
+        match prefs.image_styles.iter_mut().find(|s| s.name == style.name) {
+            Some(existing) => *existing = style,
+            None => prefs.image_styles.push(style),
+        }
+    }
+}
+
+/// Write a project in the XML interchange format read by other layout
+/// programs. Only frame geometry, source files and the page carry over.
+pub fn export_interchange(project: &ProjectLayout, path: &Path) -> Result<(), std::io::Error> {
+    fs::write(path, interchange::to_xml(&project.layout, &project.name))?;
+    log::info!("Exported interchange layout to {:?}", path);
+    Ok(())
+}
+
+/// Read a layout from the XML interchange format, with a list of what was
+/// left behind
+pub fn import_interchange(path: &Path) -> Result<InterchangeImport, std::io::Error> {
+    let contents = fs::read_to_string(path)?;
+    let import = interchange::from_xml(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    log::info!(
+        "Imported interchange layout from {:?}: {} images, {} parts ignored",
+        path,
+        import.layout.images.len(),
+        import.ignored.len()
+    );
+    Ok(import)
+}
+
+/// Pull every setting that still deserializes out of a config file that
+/// failed to parse as a whole, filling the rest from `base`. Returns the
+/// preferences and the names of the settings that were recovered.
+fn recover_preferences(contents: &str, base: UserPreferences) -> (UserPreferences, Vec<String>) {
+    let Ok(serde_json::Value::Object(saved)) = serde_json::from_str::<serde_json::Value>(contents) else {
+        return (base, Vec::new());
+    };
+    let serde_json::Value::Object(mut merged) = serde_json::to_value(&base).unwrap_or_default() else {
+        return (base, Vec::new());
+    };
+
+    let mut recovered = Vec::new();
+    for (key, value) in saved {
+        let Some(previous) = merged.insert(key.clone(), value) else {
+            // Not a known setting
+            merged.remove(&key);
+            continue;
+        };
+        let candidate = serde_json::Value::Object(merged.clone());
+        if serde_json::from_value::<UserPreferences>(candidate).is_ok() {
+            recovered.push(key);
+        } else {
+            merged.insert(key, previous);
+        }
+    }
+    recovered.sort();
+
+    let prefs = serde_json::from_value(serde_json::Value::Object(merged)).unwrap_or(base);
+    (prefs, recovered)
+}
+
+/// The platform's config and cache directories for the app
+fn default_dirs() -> Result<(PathBuf, PathBuf), std::io::Error> {
+    let proj_dirs = ProjectDirs::from("", "", "print_layout")
+        .ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Could not determine config directory"
+        ))?;
+    Ok((proj_dirs.config_dir().to_path_buf(), proj_dirs.cache_dir().to_path_buf()))
+}
+
+/// Fail unless a file can be created in `dir`
+/// Sync the directory entry so a rename survives a power cut
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), std::io::Error> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => fs::File::open(dir)?.sync_all(),
+        None => fs::File::open(".")?.sync_all(),
+    }
+}
+
+/// Directories can't be opened for syncing here; the rename is all we can do
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+fn check_writable(dir: &Path) -> Result<(), std::io::Error> {
+    let probe = dir.join(".write_test");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// Whether a date code needs a time zone or offset, which formatting a
+/// `NaiveDateTime` can't supply
+fn needs_time_zone(item: &Item) -> bool {
+    matches!(
+        item,
+        Item::Fixed(
+            Fixed::TimezoneName
+                | Fixed::TimezoneOffset
+                | Fixed::TimezoneOffsetColon
+                | Fixed::TimezoneOffsetDoubleColon
+                | Fixed::TimezoneOffsetTripleColon
+                | Fixed::TimezoneOffsetColonZ
+                | Fixed::TimezoneOffsetZ
+                | Fixed::RFC2822
+                | Fixed::RFC3339
+                | Fixed::Internal(_)
+        )
+    )
+}
+
+/// The locale that governs paper size, following POSIX precedence:
+/// `LC_ALL`, then `LC_PAPER`, then `LANG`. Empty when none is set.
+fn system_locale() -> String {
+    ["LC_ALL", "LC_PAPER", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
 }
 
 impl Default for ConfigManager {
     fn default() -> Self {
-        Self::new().expect("Failed to create config manager")
+        Self::new_or_read_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_recent_printers_are_listed_first() {
+        let prefs = UserPreferences {
+            recent_printers: names(&["Office", "Gone", "Photo"]),
+            ..Default::default()
+        };
+        let ordered = prefs.printers_recent_first(names(&["Archive", "Lab", "Photo", "Office"]));
+        assert_eq!(ordered, names(&["Office", "Photo", "Archive", "Lab"]));
+    }
+
+    #[test]
+    fn test_preset_round_trip_applies_page_settings() {
+        let mut page = Page::new(PaperSize::Photo4x6);
+        page.paper_type = PaperType::Glossy;
+        page.print_quality = PrintQuality::Highest;
+        page.set_borderless(true, false);
+        let preset = PrintPreset::capture("4x6 glossy".to_string(), Some("Canon".to_string()), &page, 600);
+
+        let mut other = Page::new(PaperSize::A4);
+        other.orientation = Orientation::Landscape;
+        std::mem::swap(&mut other.width_mm, &mut other.height_mm);
+        assert!(preset.apply_to(&mut other).is_empty());
+        assert_eq!(other.paper_size, PaperSize::Photo4x6);
+        assert_eq!(other.paper_type, PaperType::Glossy);
+        assert_eq!(other.print_quality, PrintQuality::Highest);
+        assert!(other.borderless);
+        assert_eq!(other.margin_left_mm, 0.0);
+        // Orientation is kept
+        assert!(other.width_mm > other.height_mm);
+    }
+
+    #[test]
+    fn test_preset_with_impossible_margins_falls_back_with_warning() {
+        let mut preset = PrintPreset::capture("huge".to_string(), None, &Page::new(PaperSize::A4), 300);
+        preset.paper_size = PaperSize::Photo4x6;
+        preset.margins = (80.0, 80.0, 10.0, 10.0);
+        let mut page = Page::new(PaperSize::A4);
+        let warnings = preset.apply_to(&mut page);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(page.margin_top_mm, PaperSize::Photo4x6.default_margin_mm());
+    }
+
+    #[test]
+    fn test_save_preset_replaces_same_name() {
+        let manager = ConfigManager::default();
+        let mut prefs = UserPreferences::default();
+        let page = Page::new(PaperSize::A4);
+        manager.save_preset(&mut prefs, PrintPreset::capture("A4".to_string(), None, &page, 300));
+        manager.save_preset(&mut prefs, PrintPreset::capture("A4".to_string(), None, &page, 150));
+        assert_eq!(prefs.presets.len(), 1);
+        assert_eq!(prefs.presets[0].dpi, 150);
+    }
+
+    #[test]
+    fn test_image_style_transfers_to_another_photo() {
+        let mut source = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 2000);
+        source.rotation_degrees = 90.0;
+        source.flip_horizontal = true;
+        source.opacity = 0.5;
+        source.crop = Some(Crop::cover(source.content_aspect(), 1.0, (0.5, 0.5)));
+        let style = ImageStyle::capture("Square".to_string(), &source);
+        assert!((style.crop_aspect.unwrap() - 1.0).abs() < 1e-5);
+
+        // A portrait photo gets the same rotation and a centered square crop
+        let mut other = PlacedImage::new(PathBuf::from("b.jpg"), 1200, 1600);
+        other.width_mm = 100.0;
+        style.apply_to(&mut other);
+        assert_eq!(other.rotation_degrees, 90.0);
+        assert!(other.flip_horizontal && !other.flip_vertical);
+        assert_eq!(other.opacity, 0.5);
+        let crop = other.crop.unwrap();
+        assert!((crop.x - 0.125).abs() < 1e-5 && (crop.width - 0.75).abs() < 1e-5);
+        assert_eq!((crop.y, crop.height), (0.0, 1.0));
+        assert!((other.height_mm - 100.0).abs() < 1e-3);
+
+        // An uncropped style clears the crop and restores the photo's shape
+        let plain = ImageStyle::capture("Plain".to_string(), &PlacedImage::new(PathBuf::from("c.jpg"), 10, 10));
+        plain.apply_to(&mut other);
+        assert_eq!(other.crop, None);
+        assert!((other.height_mm - 133.333).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_recover_preferences_keeps_valid_fields() {
+        let contents = r#"{
+            "zoom_level": 1.5,
+            "grid_size_mm": 5.0,
+            "recent_files": 7,
+            "presets": [{"name": "broken"}],
+            "not_a_setting": true
+        }"#;
+        assert!(serde_json::from_str::<UserPreferences>(contents).is_err());
+        let (prefs, recovered) = recover_preferences(contents, UserPreferences::default());
+        assert_eq!(recovered, names(&["grid_size_mm", "zoom_level"]));
+        assert_eq!(prefs.zoom_level, 1.5);
+        assert_eq!(prefs.grid_size_mm, 5.0);
+        assert!(prefs.recent_files.is_empty());
+        assert!(prefs.presets.is_empty());
+    }
+
+    #[test]
+    fn test_recover_preferences_from_unreadable_json_uses_base() {
+        let base = UserPreferences { zoom_level: 2.0, ..Default::default() };
+        let (prefs, recovered) = recover_preferences("{\"zoom_level\": 1.5,,", base);
+        assert!(recovered.is_empty());
+        assert_eq!(prefs.zoom_level, 2.0);
+    }
+
+    #[test]
+    fn test_suggested_save_name_fills_in_date() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 3, 5).unwrap().and_hms_opt(14, 30, 0).unwrap();
+        let name = |template: &str| {
+            UserPreferences { save_name_template: template.to_string(), ..Default::default() }.suggested_save_name(now)
+        };
+        assert_eq!(name(DEFAULT_SAVE_NAME_TEMPLATE), "layout.pxl");
+        assert_eq!(name("layout_%Y%m%d.pxl"), "layout_20260305.pxl");
+        assert_eq!(name("prints %Y-%m-%d %H%M"), "prints 2026-03-05 1430.pxl");
+        assert_eq!(name("%Y/%m/shoot"), "2026_03_shoot.pxl");
+        // Invalid codes and empty names fall back to the default
+        assert_eq!(name("layout_%Q"), "layout.pxl");
+        // Time zone codes would fail to format a local time
+        assert_eq!(name("layout_%z"), "layout.pxl");
+        assert_eq!(name("layout_%Z_%Y"), "layout.pxl");
+        assert_eq!(name("  "), "layout.pxl");
+    }
+
+    #[test]
+    fn test_measurement_unit_parses_into_mm() {
+        assert_eq!(MeasurementUnit::Millimeters.parse_mm(" 150 "), Some(150.0));
+        assert_eq!(MeasurementUnit::Inches.parse_mm("6"), Some(152.4));
+        assert!((MeasurementUnit::Inches.from_mm(101.6) - 4.0).abs() < 1e-5);
+        assert_eq!(MeasurementUnit::Inches.parse_mm("0"), None);
+        assert_eq!(MeasurementUnit::Millimeters.parse_mm("-3"), None);
+        assert_eq!(MeasurementUnit::Millimeters.parse_mm("abc"), None);
+        assert_eq!(MeasurementUnit::Millimeters.format(25.4), "25.4");
+        assert_eq!(MeasurementUnit::Inches.format(25.4), "1.00");
+        assert_eq!(MeasurementUnit::Inches.format_compact(12.7), "0.5");
+        assert_eq!(MeasurementUnit::Millimeters.format_compact(10.0), "10");
+        assert_eq!(MeasurementUnit::Inches.toggled(), MeasurementUnit::Millimeters);
+    }
+
+    #[test]
+    fn test_view_options_saved_as_a_group() {
+        let mut options = ViewOptions::default();
+        for option in ViewOption::ALL {
+            let before = options.get(option);
+            *options.get_mut(option) = !before;
+            assert_eq!(options.get(option), !before, "{}", option.label());
+        }
+        // Options added later take their defaults in older settings files
+        let parsed: ViewOptions = serde_json::from_str(r#"{"show_grid":true}"#).unwrap();
+        assert_eq!(parsed, ViewOptions { show_grid: true, ..ViewOptions::default() });
+        assert_eq!(parsed.render_quality, CanvasQuality::Full);
+        assert_eq!(CanvasQuality::Full.toggled(), CanvasQuality::Draft);
+        assert_eq!(CanvasQuality::Draft.toggled(), CanvasQuality::Full);
+    }
+
+    #[test]
+    fn test_add_recent_printer_moves_to_front_and_caps_list() {
+        let manager = ConfigManager::default();
+        let mut prefs = UserPreferences::default();
+        for name in ["A", "B", "C", "D", "B"] {
+            manager.add_recent_printer(&mut prefs, name.to_string());
+        }
+        assert_eq!(prefs.recent_printers, names(&["B", "D", "C"]));
+    }
+
+    #[test]
+    fn test_read_config_leaves_a_broken_file_alone() {
+        let dir = std::env::temp_dir().join(format!("print_layout_read_config_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manager = ConfigManager::with_dirs(dir.clone(), dir.clone()).unwrap();
+        let config_path = dir.join("config.json");
+        assert!(manager.read_config().is_none());
+
+        fs::write(&config_path, "{\"zoom_level\": 1.5,,").unwrap();
+        assert!(manager.read_config().is_none());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "{\"zoom_level\": 1.5,,");
+        let moved_aside = fs::read_dir(&dir)
+            .unwrap()
+            .any(|entry| entry.unwrap().file_name().to_string_lossy().contains("broken"));
+        assert!(!moved_aside);
+
+        let prefs = UserPreferences { zoom_level: 2.0, ..Default::default() };
+        manager.save_config(&prefs).unwrap();
+        assert_eq!(manager.read_config().unwrap().zoom_level, 2.0);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_layout_orders_images_by_stored_z_index() {
+        let dir = std::env::temp_dir().join(format!("print_layout_z_order_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manager = ConfigManager::with_dirs(dir.clone(), dir.clone()).unwrap();
+
+        let mut layout = Layout::new();
+        for (name, z) in [("c", 2), ("a", 0), ("b", 1)] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 10, 10);
+            img.id = name.to_string();
+            img.z_index = z;
+            layout.images.push(img);
+        }
+        let path = dir.join("shuffled.pxl");
+        fs::write(&path, serde_json::to_string(&ProjectLayout::new(layout, "Shuffled".to_string())).unwrap()).unwrap();
+
+        let loaded = manager.load_layout(&path).unwrap().layout;
+        let order: Vec<(&str, usize)> = loaded.images.iter().map(|i| (i.id.as_str(), i.z_index)).collect();
+        assert_eq!(order, vec![("a", 0), ("b", 1), ("c", 2)]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_multi_page_projects_round_trip_and_single_page_files_still_load() {
+        let dir = std::env::temp_dir().join(format!("print_layout_pages_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manager = ConfigManager::with_dirs(dir.clone(), dir.clone()).unwrap();
+
+        let mut document = Document::new(Layout::new());
+        let mut second = Layout::new();
+        second.page.set_paper_size(PaperSize::A3, false);
+        document.add_page(second);
+        let path = dir.join("pages.pxl");
+        manager.save_layout(&ProjectLayout::from_document(&document, "Pages".to_string()), &path).unwrap();
+        let loaded = manager.load_layout(&path).unwrap().document();
+        assert_eq!(loaded.page_count(), 2);
+        assert_eq!(loaded.current_page(), 1);
+        assert_eq!(loaded.current().page.paper_size, PaperSize::A3);
+
+        // Files from before multi-page projects have no page fields at all
+        let mut old = serde_json::to_value(ProjectLayout::new(Layout::new(), "Old".to_string())).unwrap();
+        let fields = old.as_object_mut().unwrap();
+        assert!(!fields.contains_key("more_pages"));
+        fields.remove("current_page");
+        fs::write(&path, old.to_string()).unwrap();
+        let loaded = manager.load_layout(&path).unwrap().document();
+        assert_eq!((loaded.page_count(), loaded.current_page()), (1, 0));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_unwritable_dirs_run_read_only() {
+        let dir = std::env::temp_dir().join(format!("print_layout_read_only_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // A directory can't be created under a file, whoever runs the test
+        let blocker = dir.join("not_a_dir");
+        fs::write(&blocker, b"").unwrap();
+        let (config_dir, cache_dir) = (blocker.join("config"), blocker.join("cache"));
+        assert!(ConfigManager::with_dirs(config_dir.clone(), cache_dir.clone()).is_err());
+
+        let manager = ConfigManager::with_dirs_or_read_only(config_dir, cache_dir);
+        assert!(manager.read_only_reason().is_some());
+        let (prefs, repair) = manager.load_config();
+        assert!(repair.is_none());
+        assert_eq!(prefs.zoom_level, UserPreferences::default().zoom_level);
+        assert!(manager.save_config(&prefs).is_err());
+        assert!(manager.auto_save(&Document::new(Layout::new())).is_err());
+        assert!(!manager.has_auto_save());
+
+        // Projects still save to a path the user chose, even over an existing file
+        let path = dir.join("project.pxl");
+        let project = ProjectLayout::new(Layout::new(), "Project".to_string());
+        manager.save_layout(&project, &path).unwrap();
+        manager.save_layout(&project, &path).unwrap();
+        assert_eq!(manager.load_layout(&path).unwrap().name, "Project");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    fn truncating_writer(file: &mut fs::File, bytes: &[u8]) -> Result<(), std::io::Error> {
+        file.write_all(&bytes[..bytes.len() / 2])
+    }
+
+    #[test]
+    fn test_failed_verification_keeps_the_old_project_and_backups() {
+        let dir = std::env::temp_dir().join(format!("print_layout_durable_{}", std::process::id()));
+        let mut manager = ConfigManager::with_dirs(dir.join("config"), dir.join("cache")).unwrap();
+        let path = dir.join("durable.pxl");
+        manager.save_layout(&ProjectLayout::new(Layout::new(), "First".to_string()), &path).unwrap();
+
+        manager.write_file = truncating_writer;
+        let error = manager
+            .save_layout(&ProjectLayout::new(Layout::new(), "Second".to_string()), &path)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(manager.load_layout(&path).unwrap().name, "First");
+        assert!(!path.with_extension("tmp").exists());
+        // Backups only rotate once the new file is known to be good
+        assert_eq!(fs::read_dir(dir.join("config").join("backups")).unwrap().count(), 0);
+
+        let mut prefs = UserPreferences::default();
+        manager.write_file = write_all;
+        prefs.zoom_level = 2.0;
+        manager.save_config(&prefs).unwrap();
+        manager.write_file = truncating_writer;
+        prefs.zoom_level = 3.0;
+        assert!(manager.save_config(&prefs).is_err());
+        assert!(manager.auto_save(&Document::new(Layout::new())).is_err());
+        assert!(!manager.has_auto_save());
+        assert_eq!(manager.load_config().0.zoom_level, 2.0);
+
+        manager.write_file = write_all;
+        manager.save_layout(&ProjectLayout::new(Layout::new(), "Second".to_string()), &path).unwrap();
+        assert_eq!(manager.load_layout(&path).unwrap().name, "Second");
+        assert_eq!(fs::read_dir(dir.join("config").join("backups")).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_panel_sizes_are_clamped() {
+        assert_eq!(clamp_settings_panel_width(20.0), SETTINGS_PANEL_WIDTH_RANGE.0);
+        assert_eq!(clamp_settings_panel_width(300.0), 300.0);
+        assert_eq!(clamp_settings_panel_width(5000.0), SETTINGS_PANEL_WIDTH_RANGE.1);
+        assert_eq!(clamp_settings_panel_width(f32::NAN), DEFAULT_SETTINGS_PANEL_WIDTH);
+        assert_eq!(clamp_thumbnail_strip_height(-10.0), THUMBNAIL_STRIP_HEIGHT_RANGE.0);
+        assert_eq!(clamp_thumbnail_strip_height(150.0), 150.0);
+    }
+
+    #[test]
+    fn test_print_and_export_dpi_default_separately() {
+        let mut value = serde_json::to_value(UserPreferences::default()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("print_dpi");
+        object.remove("export_dpi");
+        let prefs: UserPreferences = serde_json::from_value(value).unwrap();
+        assert_eq!((prefs.print_dpi, prefs.export_dpi), (DEFAULT_PRINT_DPI, DEFAULT_EXPORT_DPI));
     }
 }
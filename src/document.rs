@@ -0,0 +1,525 @@
+// document.rs - Multi-page projects
+// A project holds one or more pages, each a complete Layout with its own
+// paper, margins and images. The app edits the page on screen directly and
+// writes it back here whenever the whole project is needed: saving,
+// auto-save, undo and printing. Two facing pages can also be edited side
+// by side as a spread; they are joined into one Layout for the canvas and
+// split back into their own pages when stored.
+
+use crate::layout::{Crop, Layout, Page, PlacedImage, PlacedRect};
+use uuid::Uuid;
+
+/// Pages of a spread must be this close in height (mm)
+const SPREAD_HEIGHT_TOLERANCE_MM: f32 = 0.01;
+
+/// An element has to reach at least this far (mm) past the fold to be split
+/// in two; smaller overhangs go with the page holding its center
+const SPREAD_MIN_OVERHANG_MM: f32 = 1.0;
+
+/// The pages of a project and the one being edited
+#[derive(Debug, Clone)]
+pub struct Document {
+    pages: Vec<Layout>,
+    current_page: usize,
+}
+
+impl Document {
+    /// A single-page document
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            pages: vec![layout],
+            current_page: 0,
+        }
+    }
+
+    /// A document from saved pages; an empty list gives one blank page and an
+    /// out-of-range current page falls back to the last one
+    pub fn from_pages(mut pages: Vec<Layout>, current_page: usize) -> Self {
+        if pages.is_empty() {
+            pages.push(Layout::new());
+        }
+        let current_page = current_page.min(pages.len() - 1);
+        Self { pages, current_page }
+    }
+
+    pub fn pages(&self) -> &[Layout] {
+        &self.pages
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Index of the page being edited
+    pub fn current_page(&self) -> usize {
+        self.current_page
+    }
+
+    /// The page being edited
+    pub fn current(&self) -> &Layout {
+        &self.pages[self.current_page]
+    }
+
+    /// Replace the contents of the page being edited
+    pub fn set_current(&mut self, layout: Layout) {
+        self.pages[self.current_page] = layout;
+    }
+
+    /// Make `index` the page being edited; false when there is no such page
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.pages.len() {
+            return false;
+        }
+        self.current_page = index;
+        true
+    }
+
+    /// Insert `layout` after the current page and edit it
+    pub fn add_page(&mut self, layout: Layout) {
+        self.current_page += 1;
+        self.pages.insert(self.current_page, layout);
+    }
+
+    /// Remove the current page and edit the one before it (or the new first
+    /// page). A document always keeps at least one page.
+    pub fn remove_current(&mut self) -> Result<Layout, String> {
+        if self.pages.len() == 1 {
+            return Err("A project needs at least one page".to_string());
+        }
+        let removed = self.pages.remove(self.current_page);
+        self.current_page = self.current_page.saturating_sub(1);
+        Ok(removed)
+    }
+
+    /// The spread holding the current page, or None when its facing page is
+    /// missing or a different height
+    pub fn current_spread(&self) -> Option<Spread> {
+        let first_page = self.current_page - self.current_page % 2;
+        let left = &self.pages[first_page].page;
+        let right = &self.pages.get(first_page + 1)?.page;
+        if (left.height_mm - right.height_mm).abs() > SPREAD_HEIGHT_TOLERANCE_MM {
+            return None;
+        }
+        let fold_mm = left.width_mm;
+        let (x, y, width, height) = right.printable_area();
+        Some(Spread {
+            first_page,
+            fold_mm,
+            page_sizes: [(left.width_mm, left.height_mm), (right.width_mm, right.height_mm)],
+            printable_areas: [left.printable_area(), (x + fold_mm, y, width, height)],
+        })
+    }
+
+    /// Both pages of the current spread as one layout: the left page's
+    /// settings on a page as wide as both, with the right page's images and
+    /// color blocks moved past the fold and stacked above the left page's
+    pub fn spread_layout(&self) -> Option<Layout> {
+        let spread = self.current_spread()?;
+        let left = &self.pages[spread.first_page];
+        let right = &self.pages[spread.first_page + 1];
+        let mut joined = left.clone();
+        joined.page.width_mm += right.page.width_mm;
+        joined.page.margin_right_mm = right.page.margin_right_mm;
+        let below = left.images.len();
+        joined.images.extend(right.images.iter().map(|image| {
+            let mut image = image.clone();
+            image.x_mm += spread.fold_mm;
+            image.z_index += below;
+            image
+        }));
+        joined.rects.extend(right.rects.iter().map(|rect| {
+            let mut rect = rect.clone();
+            rect.x_mm += spread.fold_mm;
+            rect.z_index += below;
+            rect
+        }));
+        if joined.selected_image_id.is_none() {
+            joined.selected_image_id = right.selected_image_id.clone();
+            joined.also_selected = right.also_selected.clone();
+        }
+        Some(joined)
+    }
+
+    /// Store an edited spread (see `spread_layout`) back into its two pages.
+    /// Elements left where they were stay on their page; anything else goes
+    /// to the page holding its center, except upright images and blocks
+    /// reaching well across the fold, which are split into one part per
+    /// page. Page settings changed on the spread apply to both pages. With
+    /// no spread the layout replaces the current page. Returns true when
+    /// something was split, so the caller can show the parts.
+    pub fn set_spread(&mut self, layout: &Layout) -> bool {
+        let (Some(spread), Some(before)) = (self.current_spread(), self.spread_layout()) else {
+            self.set_current(layout.clone());
+            return false;
+        };
+        let first = spread.first_page;
+        let mut pages = [self.pages[first].clone(), self.pages[first + 1].clone()];
+        let settings_changed = layout.page != before.page;
+        for (side, page) in pages.iter_mut().enumerate() {
+            if settings_changed {
+                page.page = page_from_spread(&layout.page, &page.page, side);
+            }
+            page.images.clear();
+            page.rects.clear();
+        }
+
+        // The side an element was on, if it hasn't moved or changed size
+        let right_page = &self.pages[first + 1];
+        let unmoved_side = |id: &str, bounds: (f32, f32, f32, f32), before_bounds: Option<(f32, f32, f32, f32)>| {
+            let on_right = right_page.get_image(id).is_some() || right_page.get_rect(id).is_some();
+            (before_bounds == Some(bounds)).then_some(usize::from(on_right))
+        };
+        let mut split = false;
+        for image in &layout.images {
+            let bounds = image.bounds();
+            let side = unmoved_side(&image.id, bounds, before.get_image(&image.id).map(|b| b.bounds()));
+            if side.is_none() && image.frame_angle_degrees() == 0.0 && spread.straddles(bounds) {
+                let (left, right) = split_image(image, spread.fold_mm);
+                pages[0].images.push(left);
+                pages[1].images.push(right);
+                split = true;
+            } else {
+                pages[side.unwrap_or_else(|| spread.side_of(bounds))].images.push(image.clone());
+            }
+        }
+        for rect in &layout.rects {
+            let bounds = rect.bounds();
+            let side = unmoved_side(&rect.id, bounds, before.get_rect(&rect.id).map(|b| b.bounds()));
+            if side.is_none() && spread.straddles(bounds) {
+                let (left, right) = split_rect(rect, spread.fold_mm);
+                pages[0].rects.push(left);
+                pages[1].rects.push(right);
+                split = true;
+            } else {
+                pages[side.unwrap_or_else(|| spread.side_of(bounds))].rects.push(rect.clone());
+            }
+        }
+
+        for image in &mut pages[1].images {
+            image.x_mm -= spread.fold_mm;
+        }
+        for rect in &mut pages[1].rects {
+            rect.x_mm -= spread.fold_mm;
+        }
+        for page in &mut pages {
+            // The joined z-indexes still order each page; renumber them
+            page.normalize_z();
+            let on_page = |id: &String| page.get_image(id).is_some() || page.get_rect(id).is_some();
+            let selected = layout.selected_image_id.clone().filter(|id| on_page(id));
+            let also_selected = layout.also_selected.iter().filter(|id| on_page(id)).cloned().collect();
+            page.selected_image_id = selected;
+            page.also_selected = also_selected;
+        }
+        let [left, right] = pages;
+        self.pages[first] = left;
+        self.pages[first + 1] = right;
+        split
+    }
+}
+
+/// Two facing pages shown side by side: pages 1-2, 3-4 and so on. On the
+/// joined canvas the left page starts at x = 0 and the right page at the
+/// fold, so distances across the fold are true to the printed pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spread {
+    /// Index of the left page; the right page follows it
+    pub first_page: usize,
+    /// Where the right page starts on the joined canvas (mm): the left page's width
+    pub fold_mm: f32,
+    /// Size of each page (mm)
+    pub page_sizes: [(f32, f32); 2],
+    /// Printable area of each page on the joined canvas (x, y, width, height in mm)
+    pub printable_areas: [(f32, f32, f32, f32); 2],
+}
+
+impl Spread {
+    /// Side (0 left, 1 right) a point on the joined canvas falls on
+    pub fn side_at(&self, x_mm: f32) -> usize {
+        usize::from(x_mm >= self.fold_mm)
+    }
+
+    /// Position on one side's page of a point on the joined canvas
+    pub fn to_page(&self, side: usize, x_mm: f32, y_mm: f32) -> (f32, f32) {
+        (x_mm - self.offset(side), y_mm)
+    }
+
+    /// Position on the joined canvas of a point on one side's page
+    pub fn on_canvas(&self, side: usize, x_mm: f32, y_mm: f32) -> (f32, f32) {
+        (x_mm + self.offset(side), y_mm)
+    }
+
+    /// Page number (from 1) shown for a side
+    pub fn page_number(&self, side: usize) -> usize {
+        self.first_page + side + 1
+    }
+
+    /// Side an element belongs to: the one holding its center
+    pub fn side_of(&self, (x, _, width, _): (f32, f32, f32, f32)) -> usize {
+        self.side_at(x + width / 2.0)
+    }
+
+    /// How far (mm) a side's page is shifted on the joined canvas
+    fn offset(&self, side: usize) -> f32 {
+        if side == 0 {
+            0.0
+        } else {
+            self.fold_mm
+        }
+    }
+
+    /// True when a box (x, y, width, height) reaches far enough past the
+    /// fold on both sides to be split there
+    fn straddles(&self, (x, _, width, _): (f32, f32, f32, f32)) -> bool {
+        self.fold_mm - x >= SPREAD_MIN_OVERHANG_MM && x + width - self.fold_mm >= SPREAD_MIN_OVERHANG_MM
+    }
+}
+
+/// Page settings for one side after editing the spread: the edited settings
+/// and outer margins, with the page's own size and inner margin
+fn page_from_spread(joined: &Page, own: &Page, side: usize) -> Page {
+    let mut page = joined.clone();
+    page.width_mm = own.width_mm;
+    page.height_mm = own.height_mm;
+    page.paper_size = own.paper_size;
+    page.orientation = own.orientation;
+    page.borderless = own.borderless;
+    if side == 0 {
+        page.margin_right_mm = own.margin_right_mm;
+    } else {
+        page.margin_left_mm = own.margin_left_mm;
+    }
+    page
+}
+
+/// The parts of an image on each side of the fold at `fold_mm`, cropping
+/// each to its share of the picture. The left part keeps the image's id.
+fn split_image(image: &PlacedImage, fold_mm: f32) -> (PlacedImage, PlacedImage) {
+    let fraction = (fold_mm - image.x_mm) / image.width_mm;
+    let crop = image.crop.unwrap_or(Crop::FULL);
+    let mut left = image.clone();
+    left.width_mm = fold_mm - image.x_mm;
+    left.crop = Some(Crop { width: crop.width * fraction, ..crop });
+    let mut right = image.clone();
+    right.id = Uuid::new_v4().to_string();
+    right.x_mm = fold_mm;
+    right.width_mm = image.x_mm + image.width_mm - fold_mm;
+    right.crop = Some(Crop {
+        x: crop.x + crop.width * fraction,
+        width: crop.width * (1.0 - fraction),
+        ..crop
+    });
+    (left, right)
+}
+
+/// The parts of a color block on each side of the fold at `fold_mm`
+fn split_rect(rect: &PlacedRect, fold_mm: f32) -> (PlacedRect, PlacedRect) {
+    let mut left = rect.clone();
+    left.width_mm = fold_mm - rect.x_mm;
+    let mut right = rect.clone();
+    right.id = Uuid::new_v4().to_string();
+    right.x_mm = fold_mm;
+    right.width_mm = rect.x_mm + rect.width_mm - fold_mm;
+    (left, right)
+}
+
+/// Page indexes (0-based, in order, without repeats) for a range typed as
+/// 1-based page numbers such as "1-3, 5"
+pub fn parse_page_range(input: &str, page_count: usize) -> Result<Vec<usize>, String> {
+    let page_number = |part: &str| -> Result<usize, String> {
+        match part.trim().parse::<usize>() {
+            Ok(n) if (1..=page_count).contains(&n) => Ok(n),
+            _ => Err(format!("Pages run from 1 to {}: \"{}\"", page_count, part.trim())),
+        }
+    };
+
+    let mut pages = Vec::new();
+    for part in input.split(',').filter(|part| !part.trim().is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (page_number(first)?, page_number(last)?),
+            None => {
+                let n = page_number(part)?;
+                (n, n)
+            }
+        };
+        if first > last {
+            return Err(format!("Page range runs backwards: \"{}\"", part.trim()));
+        }
+        for n in first..=last {
+            if !pages.contains(&(n - 1)) {
+                pages.push(n - 1);
+            }
+        }
+    }
+    if pages.is_empty() {
+        return Err("No pages given".to_string());
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{PaperSize, PaperType};
+    use std::path::{Path, PathBuf};
+
+    fn page(paper_size: PaperSize) -> Layout {
+        let mut layout = Layout::new();
+        layout.page.set_paper_size(paper_size, false);
+        layout
+    }
+
+    /// Two A4 pages with a 100 × 100 mm image each at (20, 30), showing the
+    /// second page
+    fn facing_pages() -> Document {
+        let mut document = Document::new(page(PaperSize::A4));
+        for name in ["left.jpg", "right.jpg"] {
+            if name == "right.jpg" {
+                document.add_page(page(PaperSize::A4));
+            }
+            let mut image = PlacedImage::new(PathBuf::from(name), 100, 100);
+            (image.x_mm, image.y_mm) = (20.0, 30.0);
+            let mut layout = document.current().clone();
+            layout.add_image(image);
+            document.set_current(layout);
+        }
+        document
+    }
+
+    #[test]
+    fn test_pages_are_added_after_and_removed_back_to_the_current_page() {
+        let mut document = Document::new(page(PaperSize::A4));
+        document.add_page(page(PaperSize::A3));
+        assert_eq!(document.current_page(), 1);
+        document.select(0);
+        document.add_page(page(PaperSize::Letter));
+
+        let papers: Vec<PaperSize> = document.pages().iter().map(|p| p.page.paper_size).collect();
+        assert_eq!(papers, vec![PaperSize::A4, PaperSize::Letter, PaperSize::A3]);
+        assert!(!document.select(3));
+
+        assert_eq!(document.remove_current().unwrap().page.paper_size, PaperSize::Letter);
+        assert_eq!(document.current().page.paper_size, PaperSize::A4);
+        document.remove_current().unwrap();
+        assert!(document.remove_current().is_err());
+        assert_eq!(document.current().page.paper_size, PaperSize::A3);
+    }
+
+    #[test]
+    fn test_saved_pages_are_clamped() {
+        let document = Document::from_pages(vec![page(PaperSize::A4), page(PaperSize::A3)], 7);
+        assert_eq!(document.current_page(), 1);
+        assert_eq!(Document::from_pages(Vec::new(), 0).page_count(), 1);
+    }
+
+    #[test]
+    fn test_spread_joins_facing_pages_and_stores_them_back() {
+        let mut document = facing_pages();
+        let mut right = document.current().clone();
+        right.page.paper_type = PaperType::Matte;
+        document.set_current(right);
+
+        let spread = document.current_spread().unwrap();
+        assert_eq!(spread.first_page, 0);
+        assert_eq!(spread.fold_mm, 210.0);
+        assert_eq!(spread.side_at(230.0), 1);
+        assert_eq!(spread.to_page(1, 230.0, 30.0), (20.0, 30.0));
+        assert_eq!(spread.on_canvas(1, 20.0, 30.0), (230.0, 30.0));
+        assert_eq!(spread.page_number(1), 2);
+
+        let joined = document.spread_layout().unwrap();
+        assert_eq!(joined.page.width_mm, 420.0);
+        assert_eq!(joined.page.paper_type, PaperType::Plain);
+        let xs: Vec<(f32, usize)> = joined.images.iter().map(|img| (img.x_mm, img.z_index)).collect();
+        assert_eq!(xs, vec![(20.0, 0), (230.0, 1)]);
+
+        // Storing it unchanged leaves both pages as they were
+        let before: Vec<Layout> = document.pages().to_vec();
+        assert!(!document.set_spread(&joined));
+        for (page, old) in document.pages().iter().zip(&before) {
+            assert_eq!(page.page, old.page);
+            assert_eq!(page.images[0].id, old.images[0].id);
+            assert_eq!(page.images[0].bounds(), old.images[0].bounds());
+        }
+
+        // A spread needs a facing page of the same height
+        document.add_page(page(PaperSize::A4));
+        assert!(document.current_spread().is_none());
+        document.add_page(page(PaperSize::A3));
+        assert!(document.current_spread().is_none());
+    }
+
+    #[test]
+    fn test_image_dragged_across_the_fold_is_split_with_its_crop() {
+        let mut document = facing_pages();
+        let mut joined = document.spread_layout().unwrap();
+        let id = joined.images[0].id.clone();
+        joined.images[0].x_mm = 160.0;
+        joined.select(Some(id.clone()));
+
+        assert!(document.set_spread(&joined));
+        let left = &document.pages()[0];
+        assert_eq!(left.images[0].id, id);
+        assert_eq!(left.images[0].bounds(), (160.0, 30.0, 50.0, 100.0));
+        assert_eq!(left.images[0].crop, Some(Crop { x: 0.0, y: 0.0, width: 0.5, height: 1.0 }));
+        assert_eq!(left.selected_image_id, Some(id.clone()));
+
+        let right = &document.pages()[1];
+        assert_eq!(right.images.len(), 2);
+        let part = right.images.iter().find(|img| img.path == Path::new("left.jpg")).unwrap();
+        assert_ne!(part.id, id);
+        assert_eq!(part.bounds(), (0.0, 30.0, 50.0, 100.0));
+        assert_eq!(part.crop, Some(Crop { x: 0.5, y: 0.0, width: 0.5, height: 1.0 }));
+        // Still under the right page's image, as on the spread
+        assert_eq!(part.z_index, 0);
+        assert_eq!(right.selected_image_id, None);
+    }
+
+    #[test]
+    fn test_spread_keeps_unmoved_overhangs_and_moves_tilted_images_whole() {
+        let mut document = facing_pages();
+        let mut left = document.pages()[0].clone();
+        left.images[0].x_mm = 150.0;
+        document.select(0);
+        document.set_current(left);
+
+        let mut joined = document.spread_layout().unwrap();
+        joined.images[1].rotation_degrees = 10.0;
+        joined.images[1].x_mm = 180.0;
+        assert!(!document.set_spread(&joined));
+
+        // The left image still reaches past its page edge, unsplit
+        assert_eq!(document.pages()[0].images.len(), 1);
+        assert_eq!(document.pages()[0].images[0].x_mm, 150.0);
+        // The tilted image went to the page holding its center
+        assert_eq!(document.pages()[1].images[0].x_mm, -30.0);
+    }
+
+    #[test]
+    fn test_settings_changed_on_a_spread_apply_to_both_pages() {
+        let mut document = facing_pages();
+        let mut joined = document.spread_layout().unwrap();
+        joined.page.paper_type = PaperType::Glossy;
+        joined.page.margin_left_mm = 12.0;
+        joined.page.margin_right_mm = 8.0;
+        joined.page.margin_top_mm = 15.0;
+        document.set_spread(&joined);
+
+        let inner = document.pages()[0].page.margin_right_mm;
+        let [left, right] = [&document.pages()[0].page, &document.pages()[1].page];
+        assert_eq!((left.paper_type, right.paper_type), (PaperType::Glossy, PaperType::Glossy));
+        assert_eq!((left.width_mm, right.width_mm), (210.0, 210.0));
+        assert_eq!((left.margin_left_mm, left.margin_right_mm), (12.0, inner));
+        assert_eq!((right.margin_left_mm, right.margin_right_mm), (inner, 8.0));
+        assert_eq!((left.margin_top_mm, right.margin_top_mm), (15.0, 15.0));
+    }
+
+    #[test]
+    fn test_parse_page_range() {
+        assert_eq!(parse_page_range("1-3, 5", 6), Ok(vec![0, 1, 2, 4]));
+        assert_eq!(parse_page_range("2,2,1-2", 3), Ok(vec![1, 0]));
+        assert!(parse_page_range("4", 3).is_err());
+        assert!(parse_page_range("3-1", 3).is_err());
+        assert!(parse_page_range("x", 3).is_err());
+        assert!(parse_page_range(" , ", 3).is_err());
+    }
+}
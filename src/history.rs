@@ -0,0 +1,106 @@
+// history.rs - Undo and redo
+// Every edit that marks the layout modified records a snapshot here; Ctrl+Z
+// and Ctrl+Y step back and forth between them. The app keeps snapshots of
+// the whole document, so adding or removing a page can be undone too.
+
+use crate::layout::Layout;
+
+/// Oldest snapshots are dropped beyond this many undo steps
+pub const MAX_UNDO_STEPS: usize = 100;
+
+/// Snapshots of the layout (or document) around the current state
+#[derive(Debug, Clone)]
+pub struct History<T: Clone = Layout> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    /// The layout as of the last recorded edit
+    current: T,
+}
+
+impl<T: Clone> History<T> {
+    pub fn new(layout: &T) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: layout.clone(),
+        }
+    }
+
+    /// Forget every step, e.g. after opening another layout
+    pub fn reset(&mut self, layout: &T) {
+        *self = Self::new(layout);
+    }
+
+    /// Record `layout` after an edit; the state before it can then be undone.
+    /// A new edit drops anything that was undone.
+    pub fn record(&mut self, layout: &T) {
+        let previous = std::mem::replace(&mut self.current, layout.clone());
+        self.undo.push(previous);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// The layout before the last recorded edit, if there is one
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.undo.pop()?;
+        let undone = std::mem::replace(&mut self.current, previous.clone());
+        self.redo.push(undone);
+        Some(previous)
+    }
+
+    /// The layout of the last undone edit, if there is one
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.redo.pop()?;
+        let previous = std::mem::replace(&mut self.current, next.clone());
+        self.undo.push(previous);
+        Some(next)
+    }
+
+    /// The layout as of the last recorded edit
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout_with_margin(margin_mm: f32) -> Layout {
+        let mut layout = Layout::new();
+        layout.page.margin_top_mm = margin_mm;
+        layout
+    }
+
+    #[test]
+    fn test_undo_and_redo_step_through_recorded_edits() {
+        let mut history = History::new(&layout_with_margin(1.0));
+        history.record(&layout_with_margin(2.0));
+        history.record(&layout_with_margin(3.0));
+
+        assert_eq!(history.undo().unwrap().page.margin_top_mm, 2.0);
+        assert_eq!(history.undo().unwrap().page.margin_top_mm, 1.0);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().page.margin_top_mm, 2.0);
+
+        // A new edit after undoing drops the undone step
+        history.record(&layout_with_margin(5.0));
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap().page.margin_top_mm, 2.0);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = History::new(&layout_with_margin(0.0));
+        for step in 1..=MAX_UNDO_STEPS + 5 {
+            history.record(&layout_with_margin(step as f32));
+        }
+        let mut steps = 0;
+        while history.undo().is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO_STEPS);
+    }
+}
@@ -0,0 +1,813 @@
+// interchange.rs - Layout exchange with other layout programs
+// Frame geometry, source files and the page size written to (and read from)
+// a small, stable XML format, so a layout can be rebuilt in tools such as
+// Canon's Professional Print & Layout by hand or by script. The format is
+// lossy: adjustments, styles, footers and print settings stay behind.
+//
+// Schema, version 1. Lengths are in `units` (mm, cm, in or pt; mm when
+// missing), measured from the page's top-left corner. Angles are degrees
+// clockwise. Every attribute not marked required may be left out.
+//
+//   <layout-interchange version="1" units="mm">
+//     <name>Holiday cards</name>
+//     <page width="210" height="297"          required, as oriented
+//           orientation="portrait"             portrait | landscape
+//           paper="A4"                         PrintLayout paper name
+//           borderless="false">
+//       <margins top="5" bottom="5" left="5" right="5"/>
+//     </page>
+//     <frame source="/photos/IMG_2041.jpg"     required, image file
+//            x="10" y="10" width="100" height="66.667"   required
+//            rotation="0" z="0"                z: stacking, 0 at the bottom
+//            pixel-width="1200" pixel-height="800"
+//            flip-horizontal="false" flip-vertical="false"
+//            opacity="1" name="Title" locked="false">
+//       <crop x="0" y="0" width="1" height="1"/>   fractions of the image
+//     </frame>
+//     <block x="0" y="250" width="210" height="47"   required
+//            color="#1E1E1E"                   #RRGGBB or #RRGGBBAA
+//            corner-radius="0" z="1" locked="false"/>  z: drawn below that frame
+//   </layout-interchange>
+//
+// Importing keeps what it understands; everything else is listed in the
+// import's `ignored` report instead of failing the whole file.
+
+use crate::layout::{format_hex_color, parse_hex_color, Crop, Layout, Orientation, Page, PaperSize, PlacedImage, PlacedRect};
+use std::path::PathBuf;
+
+/// Schema version written by [`to_xml`]
+pub const INTERCHANGE_VERSION: u32 = 1;
+
+const ROOT: &str = "layout-interchange";
+
+/// How close (mm) a named paper must be to the page size to be used
+const PAPER_TOLERANCE_MM: f32 = 0.1;
+
+/// A layout read from an interchange file
+#[derive(Debug, Clone)]
+pub struct InterchangeImport {
+    pub name: String,
+    pub layout: Layout,
+    /// Parts of the file that were not carried over, one line each
+    pub ignored: Vec<String>,
+}
+
+/// Write `layout` in the interchange format
+pub fn to_xml(layout: &Layout, name: &str) -> String {
+    let page = &layout.page;
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<{} version=\"{}\" units=\"mm\" generator=\"PrintLayout {}\">\n",
+        ROOT,
+        INTERCHANGE_VERSION,
+        env!("CARGO_PKG_VERSION")
+    ));
+    xml.push_str(&format!("  <name>{}</name>\n", escape(name)));
+
+    let paper = match page.paper_size {
+        PaperSize::Custom(..) => String::new(),
+        paper => paper_name(paper).map(|n| format!(" paper=\"{}\"", n)).unwrap_or_default(),
+    };
+    xml.push_str(&format!(
+        "  <page width=\"{}\" height=\"{}\" orientation=\"{}\"{} borderless=\"{}\">\n",
+        mm(page.width_mm),
+        mm(page.height_mm),
+        match page.orientation {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        },
+        paper,
+        page.borderless
+    ));
+    xml.push_str(&format!(
+        "    <margins top=\"{}\" bottom=\"{}\" left=\"{}\" right=\"{}\"/>\n",
+        mm(page.margin_top_mm),
+        mm(page.margin_bottom_mm),
+        mm(page.margin_left_mm),
+        mm(page.margin_right_mm)
+    ));
+    xml.push_str("  </page>\n");
+
+    let mut images: Vec<&PlacedImage> = layout.images.iter().collect();
+    images.sort_by_key(|img| img.z_index);
+    for img in images {
+        xml.push_str(&format!(
+            "  <frame source=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rotation=\"{}\" z=\"{}\" \
+             pixel-width=\"{}\" pixel-height=\"{}\" flip-horizontal=\"{}\" flip-vertical=\"{}\" opacity=\"{}\" locked=\"{}\"",
+            escape(&img.path.to_string_lossy()),
+            mm(img.x_mm),
+            mm(img.y_mm),
+            mm(img.width_mm),
+            mm(img.height_mm),
+            mm(img.rotation_degrees),
+            img.z_index,
+            img.original_width_px,
+            img.original_height_px,
+            img.flip_horizontal,
+            img.flip_vertical,
+            fraction(img.opacity),
+            img.locked
+        ));
+        if let Some(name) = &img.display_name {
+            xml.push_str(&format!(" name=\"{}\"", escape(name)));
+        }
+        match img.crop {
+            Some(crop) => xml.push_str(&format!(
+                ">\n    <crop x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n  </frame>\n",
+                fraction(crop.x),
+                fraction(crop.y),
+                fraction(crop.width),
+                fraction(crop.height)
+            )),
+            None => xml.push_str("/>\n"),
+        }
+    }
+
+    for rect in &layout.rects {
+        xml.push_str(&format!(
+            "  <block x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" color=\"{}\" \
+             corner-radius=\"{}\" z=\"{}\" locked=\"{}\"/>\n",
+            mm(rect.x_mm),
+            mm(rect.y_mm),
+            mm(rect.width_mm),
+            mm(rect.height_mm),
+            format_hex_color(rect.color),
+            mm(rect.corner_radius_mm),
+            rect.z_index,
+            rect.locked
+        ));
+    }
+    xml.push_str(&format!("</{}>\n", ROOT));
+    xml
+}
+
+/// Read an interchange file. Fails only when the text is not XML or not a
+/// layout at all; frames that can't be used are skipped and reported.
+pub fn from_xml(contents: &str) -> Result<InterchangeImport, String> {
+    let root = parse_document(contents)?;
+    if root.local_name() != ROOT {
+        return Err(format!("Not a layout interchange file: the root element is <{}>", root.name));
+    }
+    let mut report = Report::default();
+    report.check_attributes(&root, &["version", "units", "generator"]);
+    if let Some(version) = root.attribute("version") {
+        if !matches!(version.trim().parse::<u32>(), Ok(v) if v <= INTERCHANGE_VERSION) {
+            report.note(format!("Written for format version {}; only version {} parts were read", version, INTERCHANGE_VERSION));
+        }
+    }
+    let scale = match root.attribute("units").map(|u| u.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("mm") => 1.0,
+        Some("cm") => 10.0,
+        Some("in") | Some("inch") => 25.4,
+        Some("pt") => 25.4 / 72.0,
+        Some(other) => return Err(format!("Unknown units \"{}\"", other)),
+    };
+
+    let mut name = String::new();
+    let mut layout = Layout::new();
+    let mut frames = 0;
+    for child in &root.children {
+        match child.local_name() {
+            "name" => name = child.text.trim().to_string(),
+            "page" => match read_page(child, scale, &mut report) {
+                Ok(page) => layout.page = page,
+                Err(e) => report.note(format!("Page: {}; using the default paper", e)),
+            },
+            "frame" => {
+                frames += 1;
+                match read_frame(child, scale, frames - 1, &mut report) {
+                    Ok(image) => layout.images.push(image),
+                    Err(e) => report.note(format!("Frame {} skipped: {}", frames, e)),
+                }
+            }
+            "block" => match read_block(child, scale, layout.images.len(), &mut report) {
+                Ok(rect) => layout.rects.push(rect),
+                Err(e) => report.note(format!("Color block skipped: {}", e)),
+            },
+            _ => report.ignore_element(child),
+        }
+    }
+    // Stored z values win over the document order
+    layout.normalize_z();
+    Ok(InterchangeImport { name, layout, ignored: report.lines })
+}
+
+fn read_page(element: &Element, scale: f32, report: &mut Report) -> Result<Page, String> {
+    report.check_attributes(element, &["width", "height", "orientation", "paper", "borderless"]);
+    let width = required_length(element, "width", scale)?;
+    let height = required_length(element, "height", scale)?;
+    let orientation = match element.attribute("orientation").map(str::trim) {
+        Some("portrait") => Orientation::Portrait,
+        Some("landscape") => Orientation::Landscape,
+        other => {
+            if let Some(other) = other {
+                report.note(format!("Unknown page orientation \"{}\"", other));
+            }
+            if width > height { Orientation::Landscape } else { Orientation::Portrait }
+        }
+    };
+    // Paper sizes are stored upright; landscape turns them
+    let upright = match orientation {
+        Orientation::Portrait => (width, height),
+        Orientation::Landscape => (height, width),
+    };
+    let named = element.attribute("paper").map(|name| (name, paper_from_name(name)));
+    let paper = match named {
+        Some((_, Some(paper))) if same_size(paper.to_dimensions(), upright) => paper,
+        Some((name, _)) => {
+            report.note(format!("Paper \"{}\" doesn't match the page size; using a custom size", name));
+            PaperSize::Custom(upright.0, upright.1)
+        }
+        None => PaperSize::Custom(upright.0, upright.1),
+    };
+
+    // Named papers keep their exact size; the file's may be rounded
+    let mut page = Page::new(paper);
+    page.borderless = flag(element, "borderless", report).unwrap_or(false);
+    page.apply_default_margins();
+    page.set_orientation(orientation);
+
+    for child in &element.children {
+        if child.local_name() != "margins" {
+            report.ignore_element(child);
+            continue;
+        }
+        report.check_attributes(child, &["top", "bottom", "left", "right"]);
+        // A side left out keeps the paper's default
+        let side = |name, default| length(child, name, scale).map(|v| v.unwrap_or(default));
+        let margins = (|| {
+            Ok::<_, String>((
+                side("top", page.margin_top_mm)?,
+                side("bottom", page.margin_bottom_mm)?,
+                side("left", page.margin_left_mm)?,
+                side("right", page.margin_right_mm)?,
+            ))
+        })();
+        match margins {
+            Ok((top, bottom, left, right)) => {
+                if let Err(e) = page.try_set_margins(top, bottom, left, right) {
+                    report.note(format!("Margins: {}; using the paper's defaults", e));
+                }
+            }
+            Err(e) => report.note(format!("Margins: {}; using the paper's defaults", e)),
+        }
+    }
+    Ok(page)
+}
+
+fn read_frame(element: &Element, scale: f32, index: usize, report: &mut Report) -> Result<PlacedImage, String> {
+    report.check_attributes(
+        element,
+        &[
+            "source", "x", "y", "width", "height", "rotation", "z", "pixel-width", "pixel-height",
+            "flip-horizontal", "flip-vertical", "opacity", "name", "locked",
+        ],
+    );
+    let source = element
+        .attribute("source")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or("no source file")?;
+    let (x, y) = (required_length(element, "x", scale)?, required_length(element, "y", scale)?);
+    let (width, height) = (required_length(element, "width", scale)?, required_length(element, "height", scale)?);
+    if width <= 0.0 || height <= 0.0 {
+        return Err("width and height must be positive".to_string());
+    }
+    let pixels = |name| {
+        element
+            .attribute(name)
+            .map(|v| v.trim().parse::<u32>().map_err(|_| format!("{} is not a whole number: '{}'", name, v)))
+            .transpose()
+    };
+    // Without the image's pixel size its shape is taken from the frame
+    let (pixel_width, pixel_height) = match (pixels("pixel-width")?, pixels("pixel-height")?) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => (w, h),
+        _ => ((width * 100.0).round() as u32, (height * 100.0).round() as u32),
+    };
+
+    let mut image = PlacedImage::new(PathBuf::from(source), pixel_width, pixel_height);
+    image.x_mm = x;
+    image.y_mm = y;
+    image.width_mm = width;
+    image.height_mm = height;
+    image.rotation_degrees = number(element, "rotation")?.unwrap_or(0.0).rem_euclid(360.0);
+    image.z_index = z_index(element)?.unwrap_or(index);
+    image.flip_horizontal = flag(element, "flip-horizontal", report).unwrap_or(false);
+    image.flip_vertical = flag(element, "flip-vertical", report).unwrap_or(false);
+    image.opacity = number(element, "opacity")?.unwrap_or(1.0).clamp(0.0, 1.0);
+    image.locked = flag(element, "locked", report).unwrap_or(false);
+    image.display_name = element.attribute("name").map(str::to_string).filter(|n| !n.is_empty());
+
+    for child in &element.children {
+        if child.local_name() != "crop" {
+            report.ignore_element(child);
+            continue;
+        }
+        report.check_attributes(child, &["x", "y", "width", "height"]);
+        match read_crop(child) {
+            Ok(crop) => image.crop = Some(crop),
+            Err(e) => report.note(format!("Frame {}: crop ignored, {}", index + 1, e)),
+        }
+    }
+    Ok(image)
+}
+
+fn read_crop(element: &Element) -> Result<Crop, String> {
+    let value = |name| number(element, name)?.ok_or_else(|| format!("missing {}", name));
+    let crop = Crop {
+        x: value("x")?,
+        y: value("y")?,
+        width: value("width")?,
+        height: value("height")?,
+    };
+    let inside = crop.x >= 0.0 && crop.y >= 0.0 && crop.width > 0.0 && crop.height > 0.0;
+    if !inside || crop.x + crop.width > 1.0 + 1e-4 || crop.y + crop.height > 1.0 + 1e-4 {
+        return Err("it must lie within the image".to_string());
+    }
+    Ok(crop)
+}
+
+fn read_block(element: &Element, scale: f32, frames_below: usize, report: &mut Report) -> Result<PlacedRect, String> {
+    report.check_attributes(element, &["x", "y", "width", "height", "color", "corner-radius", "z", "locked"]);
+    let color = match element.attribute("color") {
+        Some(value) => parse_hex_color(value).ok_or_else(|| format!("color is not #RRGGBB or #RRGGBBAA: '{}'", value))?,
+        None => [0, 0, 0, 255],
+    };
+    let mut rect = PlacedRect::new(
+        required_length(element, "x", scale)?,
+        required_length(element, "y", scale)?,
+        required_length(element, "width", scale)?,
+        required_length(element, "height", scale)?,
+        color,
+    );
+    if rect.width_mm <= 0.0 || rect.height_mm <= 0.0 {
+        return Err("width and height must be positive".to_string());
+    }
+    rect.corner_radius_mm = length(element, "corner-radius", scale)?.unwrap_or(0.0).max(0.0);
+    rect.z_index = z_index(element)?.unwrap_or(frames_below);
+    rect.locked = flag(element, "locked", report).unwrap_or(false);
+    Ok(rect)
+}
+
+/// Lines describing what an import left behind, each listed once
+#[derive(Default)]
+struct Report {
+    lines: Vec<String>,
+}
+
+impl Report {
+    fn note(&mut self, line: String) {
+        if !self.lines.contains(&line) {
+            self.lines.push(line);
+        }
+    }
+
+    fn ignore_element(&mut self, element: &Element) {
+        self.note(format!("Ignored <{}>", element.name));
+    }
+
+    fn check_attributes(&mut self, element: &Element, known: &[&str]) {
+        for (name, _) in &element.attributes {
+            let local = local_name(name);
+            // Namespace declarations are not content
+            if !known.contains(&local) && name != "xmlns" && !name.starts_with("xmlns:") {
+                self.note(format!("Ignored attribute {} on <{}>", name, element.name));
+            }
+        }
+    }
+}
+
+/// Serde name of a built-in paper size, e.g. "A4" or "Photo4x6"
+fn paper_name(paper: PaperSize) -> Option<String> {
+    match serde_json::to_value(paper) {
+        Ok(serde_json::Value::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+fn paper_from_name(name: &str) -> Option<PaperSize> {
+    serde_json::from_value(serde_json::Value::String(name.trim().to_string()))
+        .ok()
+        .filter(|paper| !matches!(paper, PaperSize::Custom(..)))
+}
+
+fn same_size(a: (f32, f32), b: (f32, f32)) -> bool {
+    (a.0 - b.0).abs() <= PAPER_TOLERANCE_MM && (a.1 - b.1).abs() <= PAPER_TOLERANCE_MM
+}
+
+fn number(element: &Element, name: &str) -> Result<Option<f32>, String> {
+    element
+        .attribute(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("{} is not a number: '{}'", name, value))
+        })
+        .transpose()
+}
+
+fn length(element: &Element, name: &str, scale: f32) -> Result<Option<f32>, String> {
+    Ok(number(element, name)?.map(|v| v * scale))
+}
+
+fn required_length(element: &Element, name: &str, scale: f32) -> Result<f32, String> {
+    length(element, name, scale)?.ok_or_else(|| format!("missing {}", name))
+}
+
+fn z_index(element: &Element) -> Result<Option<usize>, String> {
+    element
+        .attribute("z")
+        .map(|v| v.trim().parse::<usize>().map_err(|_| format!("z is not a whole number: '{}'", v)))
+        .transpose()
+}
+
+fn flag(element: &Element, name: &str, report: &mut Report) -> Option<bool> {
+    match element.attribute(name)?.trim() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        other => {
+            report.note(format!("Ignored {}=\"{}\" on <{}>", name, other, element.name));
+            None
+        }
+    }
+}
+
+/// Lengths to 0.001 mm, without trailing zeros
+fn mm(value: f32) -> String {
+    trim_number(format!("{:.3}", value))
+}
+
+/// Fractions (crop, opacity) to five places, without trailing zeros
+fn fraction(value: f32) -> String {
+    trim_number(format!("{:.5}", value))
+}
+
+fn trim_number(formatted: String) -> String {
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "" | "-" | "-0" => "0".to_string(),
+        t => t.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity.strip_prefix('#').and_then(|d| d.parse().ok()).and_then(char::from_u32),
+                },
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            // A stray ampersand is kept as written
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The part of a possibly prefixed name after the namespace prefix
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// An XML element, enough of one for this format
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| local_name(n) == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse a whole document into its root element. Comments, processing
+/// instructions and a doctype without an internal subset are skipped.
+fn parse_document(src: &str) -> Result<Element, String> {
+    let mut reader = Reader { src: src.strip_prefix('\u{feff}').unwrap_or(src), pos: 0 };
+    reader.skip_misc()?;
+    let root = reader.element()?;
+    reader.skip_misc()?;
+    if !reader.rest().is_empty() {
+        return Err(format!("Unexpected content after </{}>", root.name));
+    }
+    Ok(root)
+}
+
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.src.len() - self.rest().trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), String> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(format!("Unterminated markup at byte {}", self.pos)),
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at byte {}", token, self.pos))
+        }
+    }
+
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(format!("Expected a name at byte {}", self.pos));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        self.expect("<")?;
+        let mut element = Element { name: self.name()?, ..Default::default() };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\''))
+                .ok_or_else(|| format!("Expected a quoted value for {} at byte {}", name, self.pos))?;
+            self.pos += 1;
+            let end = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| format!("Unterminated value for {} at byte {}", name, self.pos))?;
+            element.attributes.push((name, unescape(&self.rest()[..end])));
+            self.pos += end + 1;
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(format!("</{}> closes <{}>", name, element.name));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").ok_or_else(|| format!("Unterminated CDATA at byte {}", self.pos))?;
+                element.text.push_str(&cdata[..end]);
+                self.pos += "<![CDATA[".len() + end + "]]>".len();
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else if rest.is_empty() {
+                return Err(format!("<{}> is never closed", element.name));
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                element.text.push_str(&unescape(&rest[..end]));
+                self.pos += end;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE_MM: f32 = 0.1;
+
+    fn representative_layout() -> Layout {
+        let mut layout = Layout::new();
+        layout.page.set_paper_size(PaperSize::Letter, false);
+        layout.page.set_orientation(Orientation::Landscape);
+        layout.page.try_set_margins(6.35, 8.0, 12.7, 3.175).unwrap();
+
+        let mut first = PlacedImage::new(PathBuf::from("/photos/Beach & \"Sun\" <1>.jpg"), 6000, 4000);
+        (first.x_mm, first.y_mm, first.width_mm, first.height_mm) = (12.7, 6.35, 127.0, 84.666_67);
+        first.rotation_degrees = 90.0;
+        first.flip_horizontal = true;
+        first.opacity = 0.85;
+        first.display_name = Some("Day 1".to_string());
+        first.crop = Some(Crop { x: 0.1, y: 0.05, width: 0.8, height: 0.9 });
+        layout.add_image(first);
+
+        let mut second = PlacedImage::new(PathBuf::from("relative/IMG_2041.png"), 1200, 800);
+        (second.x_mm, second.y_mm, second.width_mm, second.height_mm) = (150.333, 40.123, 101.6, 67.733);
+        second.rotation_degrees = 12.5;
+        second.locked = true;
+        layout.add_image(second);
+
+        // A block under the second photo
+        let mut block = PlacedRect::new(140.0, 30.0, 120.0, 90.0, [30, 60, 90, 200]);
+        block.corner_radius_mm = 4.0;
+        layout.add_rect(block);
+        layout.rects[0].z_index = 1;
+        layout
+    }
+
+    fn assert_close(a: f32, b: f32, what: &str) {
+        assert!((a - b).abs() <= TOLERANCE_MM, "{}: {} vs {}", what, a, b);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_geometry() {
+        let layout = representative_layout();
+        let xml = to_xml(&layout, "Trip <2024>");
+        let import = from_xml(&xml).unwrap();
+        assert_eq!(import.name, "Trip <2024>");
+        assert!(import.ignored.is_empty(), "{:?}", import.ignored);
+
+        let (page, back) = (&layout.page, &import.layout.page);
+        assert_eq!(back.paper_size, PaperSize::Letter);
+        assert_eq!(back.orientation, Orientation::Landscape);
+        assert_close(back.width_mm, page.width_mm, "page width");
+        assert_close(back.height_mm, page.height_mm, "page height");
+        assert_close(back.margin_top_mm, page.margin_top_mm, "top margin");
+        assert_close(back.margin_bottom_mm, page.margin_bottom_mm, "bottom margin");
+        assert_close(back.margin_left_mm, page.margin_left_mm, "left margin");
+        assert_close(back.margin_right_mm, page.margin_right_mm, "right margin");
+
+        assert_eq!(import.layout.images.len(), 2);
+        for (a, b) in layout.images.iter().zip(&import.layout.images) {
+            assert_eq!(a.path, b.path);
+            assert_close(a.x_mm, b.x_mm, "x");
+            assert_close(a.y_mm, b.y_mm, "y");
+            assert_close(a.width_mm, b.width_mm, "width");
+            assert_close(a.height_mm, b.height_mm, "height");
+            assert_close(a.rotation_degrees, b.rotation_degrees, "rotation");
+            assert_eq!(a.z_index, b.z_index);
+            assert_eq!((a.original_width_px, a.original_height_px), (b.original_width_px, b.original_height_px));
+            assert_eq!((a.flip_horizontal, a.flip_vertical, a.locked), (b.flip_horizontal, b.flip_vertical, b.locked));
+            assert_eq!(a.display_name, b.display_name);
+            assert!((a.opacity - b.opacity).abs() < 1e-4);
+            assert_eq!(a.crop.is_some(), b.crop.is_some());
+            if let (Some(ca), Some(cb)) = (a.crop, b.crop) {
+                assert!((ca.x - cb.x).abs() < 1e-4 && (ca.width - cb.width).abs() < 1e-4);
+            }
+        }
+
+        let (a, b) = (&layout.rects[0], &import.layout.rects[0]);
+        assert_eq!((a.color, a.z_index), (b.color, b.z_index));
+        assert_close(a.x_mm, b.x_mm, "block x");
+        assert_close(a.height_mm, b.height_mm, "block height");
+        assert_close(a.corner_radius_mm, b.corner_radius_mm, "block radius");
+
+        // Exporting the import again gives the same file
+        assert_eq!(to_xml(&import.layout, &import.name), xml);
+    }
+
+    #[test]
+    fn test_custom_paper_round_trips() {
+        let mut layout = Layout::new();
+        layout.page.set_paper_size(PaperSize::Custom(150.0, 250.0), false);
+        let import = from_xml(&to_xml(&layout, "Custom")).unwrap();
+        assert_eq!(import.layout.page.paper_size, PaperSize::Custom(150.0, 250.0));
+        assert_eq!(import.layout.page.orientation, Orientation::Portrait);
+    }
+
+    #[test]
+    fn test_foreign_file_is_read_with_a_report() {
+        let xml = r#"<?xml version="1.0"?>
+<!-- written by another program -->
+<ppl:layout-interchange xmlns:ppl="urn:example" version="1" units="in" vendor="Other">
+  <name><![CDATA[Studio & Co]]></name>
+  <page width="11" height="8.5" paper="Letter">
+    <margins top="0.25" bottom="0.25" left="0.25" right="0.25"/>
+    <bleed size="3"/>
+  </page>
+  <frame source="/a.jpg" x="1" y="1" width="4" height="6" rotation="-90" effect="sepia" flip-vertical="maybe"/>
+  <frame x="0" y="0" width="1" height="1"/>
+  <frame source="/b.jpg" x="5" y="1" width="0" height="2"/>
+  <frame source="/c.jpg" x="5" y="1" width="2" height="2"><crop x="0.5" y="0" width="0.8" height="1"/></frame>
+  <template name="x"/>
+</ppl:layout-interchange>"#;
+        let import = from_xml(xml).unwrap();
+        assert_eq!(import.name, "Studio & Co");
+        let page = &import.layout.page;
+        assert_eq!((page.paper_size, page.orientation), (PaperSize::Letter, Orientation::Landscape));
+        assert_close(page.margin_left_mm, 6.35, "left margin");
+
+        assert_eq!(import.layout.images.len(), 2);
+        let first = &import.layout.images[0];
+        assert_close(first.width_mm, 101.6, "width in inches");
+        assert_eq!(first.rotation_degrees, 270.0);
+        assert!(!first.flip_vertical);
+        // Without pixel sizes the frame gives the shape
+        assert!((first.original_width_px as f32 / first.original_height_px as f32 - 4.0 / 6.0).abs() < 1e-3);
+        assert_eq!(import.layout.images[1].crop, None);
+
+        for expected in [
+            "Ignored attribute vendor on <ppl:layout-interchange>",
+            "Ignored <bleed>",
+            "Ignored attribute effect on <frame>",
+            "Ignored flip-vertical=\"maybe\" on <frame>",
+            "Frame 2 skipped: no source file",
+            "Frame 3 skipped: width and height must be positive",
+            "Frame 4: crop ignored, it must lie within the image",
+            "Ignored <template>",
+        ] {
+            assert!(import.ignored.iter().any(|line| line == expected), "missing {:?} in {:?}", expected, import.ignored);
+        }
+    }
+
+    #[test]
+    fn test_rejects_what_is_not_a_layout() {
+        assert!(from_xml("not xml at all").is_err());
+        assert!(from_xml("<svg></svg>").unwrap_err().contains("<svg>"));
+        assert!(from_xml("<layout-interchange><page></layout-interchange>").is_err());
+        assert!(from_xml("<layout-interchange units=\"furlong\"/>").is_err());
+    }
+}
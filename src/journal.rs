@@ -0,0 +1,118 @@
+// journal.rs - What changed in this session
+// Every edit that marks the layout modified is described here from the
+// difference to the previous state ("Resized IMG_2041.jpg …", "Paper size:
+// A4 → A3"). The journal lives only in memory and is cleared when the layout
+// is saved or replaced.
+
+use crate::layout::Layout;
+use chrono::NaiveTime;
+use std::collections::VecDeque;
+
+/// Oldest entries are dropped beyond this many
+pub const MAX_JOURNAL_ENTRIES: usize = 500;
+
+/// Described when an edit changed nothing `Layout::diff` reports, such as a
+/// color block or an image's name
+const UNDESCRIBED_EDIT: &str = "Edited the layout";
+
+/// One described change
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub time: NaiveTime,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    entries: VecDeque<JournalEntry>,
+}
+
+impl Journal {
+    /// Describe the edit that turned `previous` into `layout`
+    pub fn record(&mut self, previous: &Layout, layout: &Layout, time: NaiveTime) {
+        let changes = layout.diff(previous);
+        let texts: Vec<String> = if changes.is_empty() {
+            vec![UNDESCRIBED_EDIT.to_string()]
+        } else {
+            changes.iter().map(|change| change.to_string()).collect()
+        };
+        for text in texts {
+            self.note(text, time);
+        }
+    }
+
+    /// Add an entry for an edit a layout diff can't describe, such as
+    /// adding a page
+    pub fn note(&mut self, text: String, time: NaiveTime) {
+        self.entries.push_back(JournalEntry { time, text });
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries, the most recent first
+    pub fn newest_first(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{PaperSize, PlacedImage};
+    use std::path::PathBuf;
+
+    fn at(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_edits_are_described_newest_first() {
+        let mut journal = Journal::default();
+        let before = Layout::new();
+        let mut after = before.clone();
+        after.page.set_paper_size(PaperSize::A3, false);
+        journal.record(&before, &after, at(9));
+
+        let mut image = PlacedImage::new(PathBuf::from("IMG_2041.jpg"), 1200, 800);
+        image.width_mm = 100.0;
+        let mut resized = after.clone();
+        resized.add_image(image.clone());
+        let mut later = resized.clone();
+        later.get_image_mut(&image.id).unwrap().width_mm = 120.0;
+        journal.record(&resized, &later, at(10));
+
+        let texts: Vec<&str> = journal.newest_first().map(|e| e.text.as_str()).collect();
+        assert!(texts[0].starts_with("Resized IMG_2041.jpg"));
+        assert!(texts[1].starts_with("Paper size"));
+        assert_eq!(journal.newest_first().next().unwrap().time, at(10));
+
+        // Edits the diff doesn't cover still leave a trace
+        journal.record(&later, &later, at(11));
+        assert_eq!(journal.newest_first().next().unwrap().text, UNDESCRIBED_EDIT);
+        journal.clear();
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn test_journal_is_capped() {
+        let mut journal = Journal::default();
+        let layout = Layout::new();
+        for _ in 0..MAX_JOURNAL_ENTRIES + 10 {
+            journal.record(&layout, &layout, at(12));
+        }
+        assert_eq!(journal.len(), MAX_JOURNAL_ENTRIES);
+    }
+}
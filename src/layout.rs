@@ -1,10 +1,30 @@
 // layout.rs - Page and image data structures
 // Phase 2: Core Layout Engine
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+pub mod resize;
+
+use resize::ResizeHandle;
+
+/// Smallest width or height (mm) an image can be resized to
+pub const MIN_IMAGE_SIZE_MM: f32 = 10.0;
+
+/// Most copies of one image that can be asked for
+pub const MAX_COPIES: u32 = 99;
+
+/// Space (mm) kept between an image's automatic copies and other images
+const COPY_GAP_MM: f32 = 2.0;
+
+/// Text height (mm) of the names printed under images when captions are on
+pub const CAPTION_HEIGHT_MM: f32 = 2.5;
+
+/// Space (mm) between an image and its caption
+pub const CAPTION_GAP_MM: f32 = 1.0;
+
 /// Represents a paper size with physical dimensions in millimeters
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PaperSize {
@@ -56,6 +76,119 @@ pub enum PaperSize {
 
 #[allow(clippy::wrong_self_convention)]
 impl PaperSize {
+    /// Standard photo paper sizes
+    pub const PHOTO_SIZES: [PaperSize; 10] = [
+        PaperSize::Photo3_5x5,
+        PaperSize::Photo4x6,
+        PaperSize::Photo5x5,
+        PaperSize::Photo5x7,
+        PaperSize::Photo7x10,
+        PaperSize::Photo8x10,
+        PaperSize::Photo12x12,
+        PaperSize::Photo10x12,
+        PaperSize::Photo11x17,
+        PaperSize::Photo13x19,
+    ];
+
+    /// Smallest standard photo paper that holds an image of the given size in
+    /// either orientation. Falls back to the largest photo size when nothing fits.
+    pub fn nearest_photo_size(width_mm: f32, height_mm: f32) -> PaperSize {
+        const TOLERANCE_MM: f32 = 0.5;
+        let area = |p: &PaperSize| {
+            let (w, h) = p.to_dimensions();
+            w * h
+        };
+        let fits = |p: &PaperSize| {
+            let (w, h) = p.to_dimensions();
+            (width_mm <= w + TOLERANCE_MM && height_mm <= h + TOLERANCE_MM)
+                || (width_mm <= h + TOLERANCE_MM && height_mm <= w + TOLERANCE_MM)
+        };
+        Self::PHOTO_SIZES
+            .iter()
+            .filter(|p| fits(p))
+            .min_by(|a, b| area(a).total_cmp(&area(b)))
+            .or_else(|| Self::PHOTO_SIZES.iter().max_by(|a, b| area(a).total_cmp(&area(b))))
+            .copied()
+            .unwrap_or(PaperSize::Photo4x6)
+    }
+
+    /// Smallest paper from `candidates` (by area) that holds content of
+    /// `width` × `height` mm inside its margins, in either orientation;
+    /// portrait wins when both fit. `margin_mm` is the margin on each side of
+    /// a given paper. None when no candidate is large enough.
+    pub fn smallest_holding(
+        width: f32,
+        height: f32,
+        candidates: &[PaperSize],
+        margin_mm: impl Fn(PaperSize) -> f32,
+    ) -> Option<(PaperSize, Orientation)> {
+        const TOLERANCE_MM: f32 = 0.5;
+        let area = |p: &PaperSize| {
+            let (w, h) = p.to_dimensions();
+            w * h
+        };
+        candidates
+            .iter()
+            .filter(|p| !matches!(p, PaperSize::Custom(..)))
+            .filter_map(|&paper| {
+                let (w, h) = paper.to_dimensions();
+                let margins = 2.0 * margin_mm(paper);
+                let fits = |w: f32, h: f32| width + margins <= w + TOLERANCE_MM && height + margins <= h + TOLERANCE_MM;
+                if fits(w, h) {
+                    Some((paper, Orientation::Portrait))
+                } else if fits(h, w) {
+                    Some((paper, Orientation::Landscape))
+                } else {
+                    None
+                }
+            })
+            .min_by(|(a, _), (b, _)| area(a).total_cmp(&area(b)))
+    }
+
+    /// Standard photo paper whose aspect ratio is closest to `width:height`
+    /// in either orientation (e.g. 2:3 gives 4×6). Ties go to the smaller paper.
+    pub fn closest_photo_aspect(width: f32, height: f32) -> PaperSize {
+        const RATIO_TOLERANCE: f32 = 0.01;
+        let ratio = |w: f32, h: f32| w.max(h) / w.min(h).max(f32::EPSILON);
+        let target = ratio(width, height);
+        let distance = |p: &PaperSize| {
+            let (w, h) = p.to_dimensions();
+            (ratio(w, h) - target).abs()
+        };
+        let area = |p: &PaperSize| {
+            let (w, h) = p.to_dimensions();
+            w * h
+        };
+        Self::PHOTO_SIZES
+            .iter()
+            .copied()
+            .reduce(|best, p| {
+                let (d, best_d) = (distance(&p), distance(&best));
+                if d < best_d - RATIO_TOLERANCE || (d <= best_d + RATIO_TOLERANCE && area(&p) < area(&best)) {
+                    p
+                } else {
+                    best
+                }
+            })
+            .unwrap_or(PaperSize::Photo4x6)
+    }
+
+    /// Margin (mm, all sides) used for this paper until the user sets their own.
+    /// Photo papers get a narrow border instead of the 1 inch document margin.
+    pub fn default_margin_mm(&self) -> f32 {
+        match self {
+            PaperSize::Photo3_5x5 | PaperSize::Photo4x6 | PaperSize::Photo5x5 | PaperSize::Photo5x7 => 3.0,
+            PaperSize::Photo7x10
+            | PaperSize::Photo8x10
+            | PaperSize::Photo10x12
+            | PaperSize::Photo11x17
+            | PaperSize::Photo12x12
+            | PaperSize::Photo13x19
+            | PaperSize::Panorama => 5.0,
+            _ => 25.4, // 1 inch
+        }
+    }
+
     /// Convert paper size to dimensions in millimeters (width, height)
     pub fn to_dimensions(&self) -> (f32, f32) {
         match self {
@@ -155,12 +288,27 @@ impl std::fmt::Display for PaperSize {
 #[allow(clippy::derivable_impls)]
 impl Default for PaperSize {
     fn default() -> Self {
-        // Default to A4 (used in most of the world)
-        // TODO: Detect locale and return Letter for US/Canada
+        // Default to A4 (used in most of the world). First-run detection
+        // from the locale happens in ConfigManager::load_config.
         PaperSize::A4
     }
 }
 
+impl PaperSize {
+    /// Default paper for a POSIX locale string such as `en_US.UTF-8` or
+    /// `fr-CA`: Letter in the US and Canada, A4 everywhere else (including
+    /// the `C`/`POSIX` locales and unparseable input)
+    pub fn for_locale(locale: &str) -> PaperSize {
+        let name = locale.split(['.', '@']).next().unwrap_or("");
+        let territory = name.split(['_', '-']).nth(1).unwrap_or("");
+        if territory.eq_ignore_ascii_case("US") || territory.eq_ignore_ascii_case("CA") {
+            PaperSize::Letter
+        } else {
+            PaperSize::A4
+        }
+    }
+}
+
 /// Represents paper type for printing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PaperType {
@@ -187,6 +335,15 @@ impl std::fmt::Display for PaperType {
 
 #[allow(dead_code)]
 impl PaperType {
+    pub const ALL: [PaperType; 6] = [
+        PaperType::Plain,
+        PaperType::SuperHighGloss,
+        PaperType::Glossy,
+        PaperType::SemiGloss,
+        PaperType::Matte,
+        PaperType::FineArt,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             PaperType::Plain => "Plain Paper",
@@ -199,6 +356,7 @@ impl PaperType {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for PaperType {
     fn default() -> Self {
         PaperType::Plain
@@ -215,6 +373,10 @@ pub enum PrintQuality {
     Draft,
 }
 
+impl PrintQuality {
+    pub const ALL: [PrintQuality; 4] = [PrintQuality::Highest, PrintQuality::High, PrintQuality::Standard, PrintQuality::Draft];
+}
+
 impl std::fmt::Display for PrintQuality {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -247,6 +409,472 @@ impl std::fmt::Display for ColorMode {
     }
 }
 
+/// Monochrome toning applied when printing in Black and White
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum Toning {
+    #[default]
+    Neutral,
+    Sepia,
+    Selenium,
+    Cyanotype,
+}
+
+impl Toning {
+    /// Duotone colors as (shadow, midtone) RGB; highlights always map to white
+    pub fn duotone(&self) -> ([u8; 3], [u8; 3]) {
+        match self {
+            Toning::Neutral => ([0, 0, 0], [128, 128, 128]),
+            Toning::Sepia => ([20, 10, 0], [150, 118, 84]),
+            Toning::Selenium => ([18, 6, 22], [128, 116, 124]),
+            Toning::Cyanotype => ([0, 18, 58], [62, 108, 164]),
+        }
+    }
+}
+
+impl std::fmt::Display for Toning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Toning::Neutral => write!(f, "Neutral"),
+            Toning::Sepia => write!(f, "Sepia"),
+            Toning::Selenium => write!(f, "Selenium"),
+            Toning::Cyanotype => write!(f, "Cyanotype"),
+        }
+    }
+}
+
+/// How the rendered page is scaled onto the media at print time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum PrintScaling {
+    /// Print at exact physical size, no rescaling by CUPS
+    #[default]
+    ActualSize,
+    /// Shrink or grow to fit within the media's printable area
+    FitToPage,
+    /// Scale to cover the whole media, cropping any overflow
+    FillPage,
+}
+
+impl std::fmt::Display for PrintScaling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrintScaling::ActualSize => write!(f, "Actual Size"),
+            PrintScaling::FitToPage => write!(f, "Fit to Page"),
+            PrintScaling::FillPage => write!(f, "Fill Page"),
+        }
+    }
+}
+
+/// Filter used to scale images to their print size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ResampleFilter {
+    /// Chosen from how much the image is scaled
+    #[default]
+    Auto,
+    Triangle,
+    CatmullRom,
+    Lanczos3,
+}
+
+/// Scale factors (print pixels / source pixels) below this count as strong
+/// downscales, where Auto uses Lanczos3
+pub const STRONG_DOWNSCALE: f32 = 0.5;
+
+impl ResampleFilter {
+    pub const ALL: [ResampleFilter; 4] = [
+        ResampleFilter::Auto,
+        ResampleFilter::Triangle,
+        ResampleFilter::CatmullRom,
+        ResampleFilter::Lanczos3,
+    ];
+
+    /// The filter to use at `scale`. Auto picks CatmullRom for upscales,
+    /// where Lanczos3 rings, Triangle for modest downscales, where it is
+    /// much faster and looks the same, and Lanczos3 below STRONG_DOWNSCALE.
+    pub fn for_scale(self, scale: f32) -> ResampleFilter {
+        match self {
+            ResampleFilter::Auto if scale > 1.0 => ResampleFilter::CatmullRom,
+            ResampleFilter::Auto if scale >= STRONG_DOWNSCALE => ResampleFilter::Triangle,
+            ResampleFilter::Auto => ResampleFilter::Lanczos3,
+            chosen => chosen,
+        }
+    }
+}
+
+impl std::fmt::Display for ResampleFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResampleFilter::Auto => write!(f, "Auto"),
+            ResampleFilter::Triangle => write!(f, "Triangle (fastest)"),
+            ResampleFilter::CatmullRom => write!(f, "Catmull-Rom"),
+            ResampleFilter::Lanczos3 => write!(f, "Lanczos3 (sharpest)"),
+        }
+    }
+}
+
+/// Where the footer line sits along the bottom of the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FooterPosition {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl FooterPosition {
+    pub const ALL: [FooterPosition; 3] = [FooterPosition::Left, FooterPosition::Center, FooterPosition::Right];
+}
+
+impl std::fmt::Display for FooterPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FooterPosition::Left => write!(f, "Bottom Left"),
+            FooterPosition::Center => write!(f, "Bottom Center"),
+            FooterPosition::Right => write!(f, "Bottom Right"),
+        }
+    }
+}
+
+/// Closest (mm) the footer gets to the paper edge, so it still prints with
+/// borderless or very small margins
+const FOOTER_MIN_EDGE_MM: f32 = 3.0;
+
+/// Optional line of text printed at the bottom of the printable area
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Footer {
+    pub enabled: bool,
+    pub page_number: bool,
+    pub date: bool,
+    /// Printed first when not empty
+    pub project_name: String,
+    pub position: FooterPosition,
+    /// Text height in millimeters
+    pub size_mm: f32,
+}
+
+impl Default for Footer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            page_number: true,
+            date: true,
+            project_name: String::new(),
+            position: FooterPosition::Center,
+            size_mm: 3.0,
+        }
+    }
+}
+
+impl Footer {
+    /// Text heights (mm) offered in the print panel
+    pub const SIZE_RANGE_MM: (f32, f32) = (2.0, 6.0);
+
+    /// The footer line for one page, or None when the footer is off or has
+    /// nothing to show. "of N" is only added for multi-page output.
+    pub fn text(&self, page_number: usize, page_count: usize, date: NaiveDate) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let mut parts = Vec::new();
+        let name = self.project_name.trim();
+        if !name.is_empty() {
+            parts.push(name.to_string());
+        }
+        if self.page_number {
+            parts.push(if page_count > 1 {
+                format!("Page {} of {}", page_number, page_count)
+            } else {
+                format!("Page {}", page_number)
+            });
+        }
+        if self.date {
+            parts.push(date.format("%Y-%m-%d").to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(" - "))
+    }
+
+    /// Where `text` is drawn on `page` as (x, y, width, height) in mm. It
+    /// sits on the bottom edge of the printable area, never closer than
+    /// FOOTER_MIN_EDGE_MM to the paper edge, and shrinks to fit the width.
+    pub fn rect(&self, page: &Page, text: &str) -> (f32, f32, f32, f32) {
+        let left = page.margin_left_mm.max(FOOTER_MIN_EDGE_MM);
+        let right = page.width_mm - page.margin_right_mm.max(FOOTER_MIN_EDGE_MM);
+        let top = page.margin_top_mm.max(FOOTER_MIN_EDGE_MM);
+        let bottom = page.height_mm - page.margin_bottom_mm.max(FOOTER_MIN_EDGE_MM);
+        let available_width = (right - left).max(0.0);
+
+        let mut height = self.size_mm.min((bottom - top).max(0.0));
+        let mut width = crate::bitmap_font::text_width(text, height);
+        if width > available_width {
+            height *= available_width / width;
+            width = available_width;
+        }
+        let x = match self.position {
+            FooterPosition::Left => left,
+            FooterPosition::Center => left + (available_width - width) / 2.0,
+            FooterPosition::Right => right - width,
+        };
+        (x, bottom - height, width, height)
+    }
+}
+
+/// Order in which images are numbered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SequenceOrder {
+    /// Rows from top to bottom, left to right within a row
+    #[default]
+    Reading,
+    /// Bottom image first, as stacked on the page
+    Stacking,
+}
+
+impl SequenceOrder {
+    pub const ALL: [SequenceOrder; 2] = [SequenceOrder::Reading, SequenceOrder::Stacking];
+}
+
+impl std::fmt::Display for SequenceOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceOrder::Reading => write!(f, "Reading Order"),
+            SequenceOrder::Stacking => write!(f, "Stacking Order"),
+        }
+    }
+}
+
+/// Corner of each image that carries its number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SequenceCorner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl SequenceCorner {
+    pub const ALL: [SequenceCorner; 4] = [
+        SequenceCorner::TopLeft,
+        SequenceCorner::TopRight,
+        SequenceCorner::BottomLeft,
+        SequenceCorner::BottomRight,
+    ];
+}
+
+impl std::fmt::Display for SequenceCorner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceCorner::TopLeft => write!(f, "Top Left"),
+            SequenceCorner::TopRight => write!(f, "Top Right"),
+            SequenceCorner::BottomLeft => write!(f, "Bottom Left"),
+            SequenceCorner::BottomRight => write!(f, "Bottom Right"),
+        }
+    }
+}
+
+/// Circled number printed on each image, so small prints can be sorted
+/// after cutting them apart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SequenceNumbers {
+    pub enabled: bool,
+    pub order: SequenceOrder,
+    pub corner: SequenceCorner,
+    /// Circle diameter in millimeters
+    pub size_mm: f32,
+}
+
+impl Default for SequenceNumbers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            order: SequenceOrder::Reading,
+            corner: SequenceCorner::TopLeft,
+            size_mm: 5.0,
+        }
+    }
+}
+
+impl SequenceNumbers {
+    /// Circle diameters (mm) offered in the print panel
+    pub const SIZE_RANGE_MM: (f32, f32) = (3.0, 12.0);
+
+    /// Circle for an image at (x, y) sized (width, height), as (center x,
+    /// center y, radius) in mm. It is inset from the corner by half its
+    /// radius and shrinks to fit images smaller than the circle.
+    pub fn badge(&self, x: f32, y: f32, width: f32, height: f32) -> (f32, f32, f32) {
+        let shortest = width.min(height).max(0.0);
+        let radius = self.size_mm.min(shortest) / 2.0;
+        let inset = (radius / 2.0).min((shortest / 2.0 - radius).max(0.0));
+        let offset = inset + radius;
+        let cx = match self.corner {
+            SequenceCorner::TopLeft | SequenceCorner::BottomLeft => x + offset,
+            SequenceCorner::TopRight | SequenceCorner::BottomRight => x + width - offset,
+        };
+        let cy = match self.corner {
+            SequenceCorner::TopLeft | SequenceCorner::TopRight => y + offset,
+            SequenceCorner::BottomLeft | SequenceCorner::BottomRight => y + height - offset,
+        };
+        (cx, cy, radius)
+    }
+
+    /// Where `label` is drawn inside a circle from `badge`, as (x, y,
+    /// width, height) in mm: centered, narrowed to fit longer numbers
+    pub fn label_rect(label: &str, (cx, cy, radius): (f32, f32, f32)) -> (f32, f32, f32, f32) {
+        let mut height = radius;
+        let mut width = crate::bitmap_font::text_width(label, height);
+        let max_width = radius * 1.5;
+        if width > max_width {
+            height *= max_width / width;
+            width = max_width;
+        }
+        (cx - width / 2.0, cy - height / 2.0, width, height)
+    }
+}
+
+/// Print-and-cut registration marks printed in the corners of the paper, so
+/// a craft cutter can find the print before cutting around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RegistrationMarks {
+    #[default]
+    None,
+    Silhouette,
+    Cricut,
+    /// Corner brackets for trimming by hand or on machines without a preset
+    CropMarks,
+}
+
+impl RegistrationMarks {
+    pub const ALL: [RegistrationMarks; 4] = [
+        RegistrationMarks::None,
+        RegistrationMarks::Silhouette,
+        RegistrationMarks::Cricut,
+        RegistrationMarks::CropMarks,
+    ];
+}
+
+impl std::fmt::Display for RegistrationMarks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistrationMarks::None => write!(f, "No Cut Marks"),
+            RegistrationMarks::Silhouette => write!(f, "Silhouette"),
+            RegistrationMarks::Cricut => write!(f, "Cricut"),
+            RegistrationMarks::CropMarks => write!(f, "Crop Marks"),
+        }
+    }
+}
+
+/// Mark printed in one corner of the paper
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MarkShape {
+    /// Solid square
+    Square,
+    /// L of two lines along the paper edges
+    Bracket,
+}
+
+/// Geometry of one machine's marks, in mm
+struct MarkSpec {
+    /// Distance from the paper edges to the outer edges of each mark
+    inset_mm: f32,
+    square_mm: f32,
+    /// Length of each bracket arm
+    arm_mm: f32,
+    /// Thickness of bracket lines
+    line_mm: f32,
+    /// Marks in the top-left, top-right, bottom-left and bottom-right corners
+    corners: [Option<MarkShape>; 4],
+}
+
+/// Space (mm) kept clear around each mark's corner region
+const MARK_CLEARANCE_MM: f32 = 2.0;
+
+impl RegistrationMarks {
+    fn spec(self) -> Option<MarkSpec> {
+        use MarkShape::{Bracket, Square};
+        match self {
+            RegistrationMarks::None => None,
+            // Silhouette Studio's default type 1 marks
+            RegistrationMarks::Silhouette => Some(MarkSpec {
+                inset_mm: 10.0,
+                square_mm: 5.0,
+                arm_mm: 20.0,
+                line_mm: 0.5,
+                corners: [Some(Square), Some(Bracket), Some(Bracket), None],
+            }),
+            RegistrationMarks::Cricut => Some(MarkSpec {
+                inset_mm: 6.0,
+                square_mm: 6.0,
+                arm_mm: 0.0,
+                line_mm: 0.0,
+                corners: [Some(Square), Some(Square), Some(Square), None],
+            }),
+            RegistrationMarks::CropMarks => Some(MarkSpec {
+                inset_mm: 5.0,
+                square_mm: 0.0,
+                arm_mm: 10.0,
+                line_mm: 0.25,
+                corners: [Some(Bracket); 4],
+            }),
+        }
+    }
+
+    /// Filled rectangles (x, y, width, height in mm) that make up the marks
+    /// on a `page_width` × `page_height` mm sheet; empty when off
+    pub fn shapes(self, page_width: f32, page_height: f32) -> Vec<(f32, f32, f32, f32)> {
+        let Some(spec) = self.spec() else {
+            return Vec::new();
+        };
+        let mut shapes = Vec::new();
+        for (corner, shape) in spec.corners.iter().enumerate() {
+            let (right, bottom) = (corner % 2 == 1, corner >= 2);
+            // Top-left of a `size` box pushed into this corner, inset from both edges
+            let place = |size: f32| {
+                let x = if right { page_width - spec.inset_mm - size } else { spec.inset_mm };
+                let y = if bottom { page_height - spec.inset_mm - size } else { spec.inset_mm };
+                (x, y)
+            };
+            match shape {
+                Some(MarkShape::Square) => {
+                    let (x, y) = place(spec.square_mm);
+                    shapes.push((x, y, spec.square_mm, spec.square_mm));
+                }
+                Some(MarkShape::Bracket) => {
+                    // Both arms run along the outer edges of the box
+                    let (x, y) = place(spec.arm_mm);
+                    let line_x = if right { x + spec.arm_mm - spec.line_mm } else { x };
+                    let line_y = if bottom { y + spec.arm_mm - spec.line_mm } else { y };
+                    shapes.push((x, line_y, spec.arm_mm, spec.line_mm));
+                    shapes.push((line_x, y, spec.line_mm, spec.arm_mm));
+                }
+                None => {}
+            }
+        }
+        shapes
+    }
+
+    /// Corner regions (x, y, width, height in mm) that images must keep out
+    /// of: each mark, the paper between it and the edges, and a clearance
+    pub fn reserved_regions(self, page_width: f32, page_height: f32) -> Vec<(f32, f32, f32, f32)> {
+        let Some(spec) = self.spec() else {
+            return Vec::new();
+        };
+        spec.corners
+            .iter()
+            .enumerate()
+            .filter_map(|(corner, shape)| {
+                let size = match shape.as_ref()? {
+                    MarkShape::Square => spec.square_mm,
+                    MarkShape::Bracket => spec.arm_mm,
+                };
+                let extent = spec.inset_mm + size + MARK_CLEARANCE_MM;
+                let x = if corner % 2 == 1 { page_width - extent } else { 0.0 };
+                let y = if corner >= 2 { page_height - extent } else { 0.0 };
+                Some((x, y, extent, extent))
+            })
+            .collect()
+    }
+}
+
 /// Page orientation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum Orientation {
@@ -264,8 +892,64 @@ impl std::fmt::Display for Orientation {
     }
 }
 
+/// One of the four page margins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarginSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl MarginSide {
+    pub const ALL: [MarginSide; 4] = [MarginSide::Top, MarginSide::Bottom, MarginSide::Left, MarginSide::Right];
+}
+
+/// Why a set of margins was rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarginError {
+    /// The margin is negative or not a number
+    Invalid(MarginSide),
+    /// Top and bottom together leave no printable height
+    VerticalOverlap { total_mm: f32, height_mm: f32 },
+    /// Left and right together leave no printable width
+    HorizontalOverlap { total_mm: f32, width_mm: f32 },
+}
+
+impl MarginError {
+    /// The margins at fault; both of a pair when they collide
+    pub fn sides(&self) -> &'static [MarginSide] {
+        match self {
+            MarginError::Invalid(MarginSide::Top) => &[MarginSide::Top],
+            MarginError::Invalid(MarginSide::Bottom) => &[MarginSide::Bottom],
+            MarginError::Invalid(MarginSide::Left) => &[MarginSide::Left],
+            MarginError::Invalid(MarginSide::Right) => &[MarginSide::Right],
+            MarginError::VerticalOverlap { .. } => &[MarginSide::Top, MarginSide::Bottom],
+            MarginError::HorizontalOverlap { .. } => &[MarginSide::Left, MarginSide::Right],
+        }
+    }
+}
+
+impl std::fmt::Display for MarginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarginError::Invalid(_) => write!(f, "Margin can't be negative"),
+            MarginError::VerticalOverlap { height_mm, .. } => write!(
+                f,
+                "Top and bottom margins together must be less than the page height ({:.1} mm)",
+                height_mm
+            ),
+            MarginError::HorizontalOverlap { width_mm, .. } => write!(
+                f,
+                "Left and right margins together must be less than the page width ({:.1} mm)",
+                width_mm
+            ),
+        }
+    }
+}
+
 /// Represents the page configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Page {
     pub width_mm: f32,
     pub height_mm: f32,
@@ -279,6 +963,28 @@ pub struct Page {
     pub color_mode: ColorMode,
     pub orientation: Orientation,
     pub borderless: bool,
+    /// Print-time scaling onto the media
+    #[serde(default)]
+    pub print_scaling: PrintScaling,
+    /// Toning preset used when color_mode is BlackAndWhite
+    #[serde(default)]
+    pub toning: Toning,
+    /// Sharpening applied to every image when printing, from -1.0 (soften)
+    /// to 1.0 (sharpen); images can override it
+    #[serde(default)]
+    pub output_sharpen: f32,
+    /// Page number, date and project name line; off by default
+    #[serde(default)]
+    pub footer: Footer,
+    /// Numbers printed on each image for cut-and-sort; off by default
+    #[serde(default)]
+    pub sequence_numbers: SequenceNumbers,
+    /// Registration marks for a print-and-cut machine; off by default
+    #[serde(default)]
+    pub registration_marks: RegistrationMarks,
+    /// Each image's name printed under it, as on a contact sheet; off by default
+    #[serde(default)]
+    pub captions: bool,
 }
 
 #[allow(dead_code)]
@@ -286,19 +992,27 @@ impl Page {
     /// Create a new page with the given paper size and default margins
     pub fn new(paper_size: PaperSize) -> Self {
         let (width_mm, height_mm) = paper_size.to_dimensions();
+        let margin = paper_size.default_margin_mm();
         Self {
             width_mm,
             height_mm,
-            margin_top_mm: 25.4, // 1 inch
-            margin_bottom_mm: 25.4,
-            margin_left_mm: 25.4,
-            margin_right_mm: 25.4,
+            margin_top_mm: margin,
+            margin_bottom_mm: margin,
+            margin_left_mm: margin,
+            margin_right_mm: margin,
             paper_size,
             paper_type: PaperType::Plain,
             print_quality: PrintQuality::Standard,
             color_mode: ColorMode::UseICCProfile,
             orientation: Orientation::Portrait,
             borderless: false,
+            print_scaling: PrintScaling::ActualSize,
+            toning: Toning::Neutral,
+            output_sharpen: 0.0,
+            footer: Footer::default(),
+            sequence_numbers: SequenceNumbers::default(),
+            registration_marks: RegistrationMarks::None,
+            captions: false,
         }
     }
 
@@ -309,6 +1023,132 @@ impl Page {
         (width_px, height_px)
     }
 
+    pub fn margin(&self, side: MarginSide) -> f32 {
+        match side {
+            MarginSide::Top => self.margin_top_mm,
+            MarginSide::Bottom => self.margin_bottom_mm,
+            MarginSide::Left => self.margin_left_mm,
+            MarginSide::Right => self.margin_right_mm,
+        }
+    }
+
+    /// Set one margin. Rejects negative values and values that, together with
+    /// the opposite margin, would leave no printable width or height.
+    pub fn set_margin(&mut self, side: MarginSide, value: f32) -> Result<(), String> {
+        let mut margins = [self.margin_top_mm, self.margin_bottom_mm, self.margin_left_mm, self.margin_right_mm];
+        margins[side as usize] = value;
+        let [top, bottom, left, right] = margins;
+        self.try_set_margins(top, bottom, left, right).map_err(|e| e.to_string())
+    }
+
+    /// Set all four margins at once. Each must be a non-negative number and
+    /// each opposite pair must leave some printable height and width; when
+    /// any check fails the current margins are kept.
+    pub fn try_set_margins(&mut self, top: f32, bottom: f32, left: f32, right: f32) -> Result<(), MarginError> {
+        for (side, value) in MarginSide::ALL.into_iter().zip([top, bottom, left, right]) {
+            if !value.is_finite() || value < 0.0 {
+                return Err(MarginError::Invalid(side));
+            }
+        }
+        if top + bottom >= self.height_mm {
+            return Err(MarginError::VerticalOverlap { total_mm: top + bottom, height_mm: self.height_mm });
+        }
+        if left + right >= self.width_mm {
+            return Err(MarginError::HorizontalOverlap { total_mm: left + right, width_mm: self.width_mm });
+        }
+        self.margin_top_mm = top;
+        self.margin_bottom_mm = bottom;
+        self.margin_left_mm = left;
+        self.margin_right_mm = right;
+        Ok(())
+    }
+
+    /// Margin this page should use when the user hasn't set one:
+    /// none when borderless, otherwise the paper's default
+    pub fn default_margin_mm(&self) -> f32 {
+        if self.borderless {
+            0.0
+        } else {
+            self.paper_size.default_margin_mm()
+        }
+    }
+
+    /// Set all four margins to [`Page::default_margin_mm`]
+    pub fn apply_default_margins(&mut self) {
+        let margin = self.default_margin_mm();
+        self.margin_top_mm = margin;
+        self.margin_bottom_mm = margin;
+        self.margin_left_mm = margin;
+        self.margin_right_mm = margin;
+    }
+
+    /// Whether the margins leave at least 60% of the page width and height
+    /// printable. Margins carried over from a larger paper usually don't.
+    pub fn margins_reasonable(&self) -> bool {
+        let (_, _, width, height) = self.printable_area();
+        width >= self.width_mm * 0.6 && height >= self.height_mm * 0.6
+    }
+
+    /// Change the paper size, keeping the current orientation. Margins switch
+    /// to the new paper's defaults unless the user has edited them.
+    pub fn set_paper_size(&mut self, paper_size: PaperSize, margins_user_edited: bool) {
+        let (width, height) = paper_size.to_dimensions();
+        if self.orientation == Orientation::Landscape {
+            self.width_mm = height;
+            self.height_mm = width;
+        } else {
+            self.width_mm = width;
+            self.height_mm = height;
+        }
+        self.paper_size = paper_size;
+        if !margins_user_edited {
+            self.apply_default_margins();
+        }
+    }
+
+    /// Turn the page to `orientation`, swapping width and height. Margins
+    /// turn with the paper: going to landscape is a quarter turn
+    /// counter-clockwise (top becomes left, right becomes top), and back to
+    /// portrait turns it clockwise again. Returns false if nothing changed.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> bool {
+        if self.orientation == orientation {
+            return false;
+        }
+        std::mem::swap(&mut self.width_mm, &mut self.height_mm);
+        let (top, right, bottom, left) = (
+            self.margin_top_mm,
+            self.margin_right_mm,
+            self.margin_bottom_mm,
+            self.margin_left_mm,
+        );
+        (self.margin_top_mm, self.margin_right_mm, self.margin_bottom_mm, self.margin_left_mm) = match orientation {
+            Orientation::Landscape => (right, bottom, left, top),
+            Orientation::Portrait => (left, top, right, bottom),
+        };
+        self.orientation = orientation;
+        true
+    }
+
+    /// Turn borderless printing on or off. Turning it on always clears the
+    /// margins; turning it off restores the paper defaults unless the user
+    /// has edited margins since.
+    pub fn set_borderless(&mut self, enabled: bool, margins_user_edited: bool) {
+        self.borderless = enabled;
+        if enabled || !margins_user_edited {
+            self.apply_default_margins();
+        }
+    }
+
+    /// Filled rectangles (mm) of the cut registration marks on this page
+    pub fn registration_mark_shapes(&self) -> Vec<(f32, f32, f32, f32)> {
+        self.registration_marks.shapes(self.width_mm, self.height_mm)
+    }
+
+    /// Corner regions (mm) kept free of images for the registration marks
+    pub fn mark_reserved_regions(&self) -> Vec<(f32, f32, f32, f32)> {
+        self.registration_marks.reserved_regions(self.width_mm, self.height_mm)
+    }
+
     /// Get the printable area (excluding margins) in millimeters
     pub fn printable_area(&self) -> (f32, f32, f32, f32) {
         let x = self.margin_left_mm;
@@ -339,6 +1179,10 @@ pub struct PlacedImage {
     pub original_width_px: u32,
     pub original_height_px: u32,
     pub locked: bool,
+    /// Keep the width/height ratio when resizing; projects saved before the
+    /// lock was stored per image load as locked
+    #[serde(default = "default_lock_aspect")]
+    pub lock_aspect: bool,
     /// Horizontal flip (mirror)
     #[serde(default)]
     pub flip_horizontal: bool,
@@ -348,50 +1192,508 @@ pub struct PlacedImage {
     /// Opacity (0.0 = transparent, 1.0 = fully opaque)
     #[serde(default = "default_opacity")]
     pub opacity: f32,
+    /// User-assigned name; the file name is shown when unset
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Visible part of the rotated/flipped image; None shows all of it
+    #[serde(default)]
+    pub crop: Option<Crop>,
+    /// Print sharpening for this image (-1.0 soften to 1.0 sharpen);
+    /// None uses the page setting
+    #[serde(default)]
+    pub output_sharpen: Option<f32>,
+    /// Resampling filter for this image; None uses the global preference
+    #[serde(default)]
+    pub resample_filter: Option<ResampleFilter>,
+    /// How many times to print this image; copies after the first are
+    /// placed in free space on the page (see `Layout::expand_copies`)
+    #[serde(default = "default_copies")]
+    pub copies: u32,
+    /// Why the source file couldn't be loaded this session, if it couldn't
+    #[serde(skip)]
+    pub load_error: Option<String>,
 }
 
-fn default_opacity() -> f32 {
-    1.0
+/// Region of an image as fractions (0.0-1.0) of its width and height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Crop {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
 }
 
 #[allow(dead_code)]
-impl PlacedImage {
-    /// Create a new placed image with default positioning
-    pub fn new(path: PathBuf, original_width_px: u32, original_height_px: u32) -> Self {
-        let id = Uuid::new_v4().to_string();
-        // Default size: 100mm width, maintaining aspect ratio
-        let aspect_ratio = original_height_px as f32 / original_width_px as f32;
-        let width_mm = 100.0;
-        let height_mm = width_mm * aspect_ratio;
+impl Crop {
+    pub const FULL: Crop = Crop { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
 
-        Self {
-            id,
-            path,
-            x_mm: 50.0,
-            y_mm: 50.0,
-            width_mm,
-            height_mm,
-            rotation_degrees: 0.0,
-            z_index: 0,
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Largest crop of content with aspect `content_aspect` (width / height)
+    /// that fills a box of aspect `box_aspect`, centered as close to `center`
+    /// as the content edges allow.
+    pub fn cover(content_aspect: f32, box_aspect: f32, center: (f32, f32)) -> Crop {
+        let (width, height) = if box_aspect > content_aspect {
+            (1.0, content_aspect / box_aspect)
+        } else {
+            (box_aspect / content_aspect, 1.0)
+        };
+        let x = (center.0 - width / 2.0).clamp(0.0, 1.0 - width);
+        let y = (center.1 - height / 2.0).clamp(0.0, 1.0 - height);
+        Crop { x, y, width, height }
+    }
+
+    /// Pixel rectangle (x, y, width, height) of this crop in an image of the
+    /// given size; always at least 1×1 and inside the image
+    pub fn pixel_rect(&self, image_width: u32, image_height: u32) -> (u32, u32, u32, u32) {
+        let x = ((self.x * image_width as f32).round() as u32).min(image_width.saturating_sub(1));
+        let y = ((self.y * image_height as f32).round() as u32).min(image_height.saturating_sub(1));
+        let width = ((self.width * image_width as f32).round() as u32).clamp(1, image_width - x);
+        let height = ((self.height * image_height as f32).round() as u32).clamp(1, image_height - y);
+        (x, y, width, height)
+    }
+
+    /// True when the crop covers (almost) the whole image
+    pub fn is_full(&self) -> bool {
+        const TOLERANCE: f32 = 0.001;
+        self.x <= TOLERANCE
+            && self.y <= TOLERANCE
+            && self.width >= 1.0 - TOLERANCE
+            && self.height >= 1.0 - TOLERANCE
+    }
+
+    /// Crop after an interactive drag, with the uncropped image drawn in a
+    /// frame of `frame_size` (mm). `handle` resizes the crop keeping its
+    /// aspect ratio, `None` moves it. `delta` is in mm. Returns `None` when
+    /// the result would leave the image, so the caller keeps the last valid crop.
+    pub fn dragged(&self, handle: Option<ResizeHandle>, delta: (f32, f32), frame_size: (f32, f32)) -> Option<Crop> {
+        const MIN_CROP_MM: f32 = 5.0;
+        const TOLERANCE_MM: f32 = 0.01;
+        let (frame_w, frame_h) = frame_size;
+        let start = resize::Rect::new(
+            self.x * frame_w,
+            self.y * frame_h,
+            self.width * frame_w,
+            self.height * frame_h,
+        );
+        let rect = match handle {
+            Some(handle) => {
+                let rect = resize::compute(handle, start, delta, true, MIN_CROP_MM);
+                let inside = rect.x >= -TOLERANCE_MM
+                    && rect.y >= -TOLERANCE_MM
+                    && rect.x + rect.width <= frame_w + TOLERANCE_MM
+                    && rect.y + rect.height <= frame_h + TOLERANCE_MM;
+                if !inside {
+                    return None;
+                }
+                rect
+            }
+            None => resize::Rect::new(
+                (start.x + delta.0).clamp(0.0, frame_w - start.width),
+                (start.y + delta.1).clamp(0.0, frame_h - start.height),
+                start.width,
+                start.height,
+            ),
+        };
+        let width = (rect.width / frame_w).min(1.0);
+        let height = (rect.height / frame_h).min(1.0);
+        Some(Crop {
+            x: (rect.x / frame_w).clamp(0.0, 1.0 - width),
+            y: (rect.y / frame_h).clamp(0.0, 1.0 - height),
+            width,
+            height,
+        })
+    }
+
+    /// The same region as fractions of the source file. Crops are stored in
+    /// displayed space (after rotation and flips); this undoes the flips,
+    /// then the quarter-turn rotation.
+    pub fn to_source(self, rotation_degrees: f32, flip_horizontal: bool, flip_vertical: bool) -> Crop {
+        let mut c = self;
+        if flip_horizontal {
+            c.x = 1.0 - c.x - c.width;
+        }
+        if flip_vertical {
+            c.y = 1.0 - c.y - c.height;
+        }
+        match (whole_quarter_turns(rotation_degrees)).rem_euclid(4) {
+            // Rotated 90° clockwise: displayed (x, y) came from source (y, 1 - x)
+            1 => Crop { x: c.y, y: 1.0 - c.x - c.width, width: c.height, height: c.width },
+            2 => Crop { x: 1.0 - c.x - c.width, y: 1.0 - c.y - c.height, ..c },
+            // Rotated 270° clockwise: displayed (x, y) came from source (1 - y, x)
+            3 => Crop { x: 1.0 - c.y - c.height, y: c.x, width: c.height, height: c.width },
+            _ => c,
+        }
+    }
+
+    /// Inverse of [`Crop::to_source`]: a region of the source file as a
+    /// crop in displayed space
+    pub fn from_source(source: Crop, rotation_degrees: f32, flip_horizontal: bool, flip_vertical: bool) -> Crop {
+        let s = source;
+        let mut c = match (whole_quarter_turns(rotation_degrees)).rem_euclid(4) {
+            1 => Crop { x: 1.0 - s.y - s.height, y: s.x, width: s.height, height: s.width },
+            2 => Crop { x: 1.0 - s.x - s.width, y: 1.0 - s.y - s.height, ..s },
+            3 => Crop { x: s.y, y: 1.0 - s.x - s.width, width: s.height, height: s.width },
+            _ => s,
+        };
+        if flip_horizontal {
+            c.x = 1.0 - c.x - c.width;
+        }
+        if flip_vertical {
+            c.y = 1.0 - c.y - c.height;
+        }
+        c
+    }
+}
+
+/// Nearest whole number of quarter turns in a rotation. A rotation that
+/// lies exactly halfway (45°, 135°, ...) rounds toward zero, so it tilts
+/// the frame instead of turning the pixels.
+fn whole_quarter_turns(rotation_degrees: f32) -> i32 {
+    let quarters = rotation_degrees / 90.0;
+    (quarters.signum() * (quarters.abs() - 0.5).ceil()) as i32
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_lock_aspect() -> bool {
+    true
+}
+
+fn default_copies() -> u32 {
+    1
+}
+
+#[allow(dead_code)]
+impl PlacedImage {
+    /// Create a new placed image with default positioning
+    pub fn new(path: PathBuf, original_width_px: u32, original_height_px: u32) -> Self {
+        let id = Uuid::new_v4().to_string();
+        // Default size: 100mm width, maintaining aspect ratio
+        let aspect_ratio = original_height_px as f32 / original_width_px as f32;
+        let width_mm = 100.0;
+        let height_mm = width_mm * aspect_ratio;
+
+        Self {
+            id,
+            path,
+            x_mm: 50.0,
+            y_mm: 50.0,
+            width_mm,
+            height_mm,
+            rotation_degrees: 0.0,
+            z_index: 0,
             original_width_px,
             original_height_px,
             locked: false,
+            lock_aspect: true,
             flip_horizontal: false,
             flip_vertical: false,
             opacity: 1.0,
+            display_name: None,
+            crop: None,
+            output_sharpen: None,
+            resample_filter: None,
+            copies: 1,
+            load_error: None,
+        }
+    }
+
+    /// Scale the image to fit inside a cell (x, y, width, height in mm) and
+    /// center it there. With `auto_rotate` it is first turned a quarter turn
+    /// clockwise when that fills the cell noticeably better; it can still be
+    /// rotated back by hand. Returns whether it was turned.
+    pub fn fit_into_cell(&mut self, cell: (f32, f32, f32, f32), auto_rotate: bool) -> bool {
+        let (cell_x, cell_y, cell_w, cell_h) = cell;
+        let rotate = auto_rotate && should_rotate_to_fit(self.width_mm / self.height_mm, cell_w / cell_h);
+        if rotate {
+            std::mem::swap(&mut self.width_mm, &mut self.height_mm);
+            self.rotation_degrees = (self.rotation_degrees + 90.0) % 360.0;
+        }
+        let scale = (cell_w / self.width_mm).min(cell_h / self.height_mm);
+        self.width_mm *= scale;
+        self.height_mm *= scale;
+        self.x_mm = cell_x + (cell_w - self.width_mm) / 2.0;
+        self.y_mm = cell_y + (cell_h - self.height_mm) / 2.0;
+        rotate
+    }
+
+    /// Pixel aspect (width / height) of the image as displayed, after
+    /// rotation but before cropping
+    pub fn content_aspect(&self) -> f32 {
+        let quarter_turns = whole_quarter_turns(self.rotation_degrees);
+        if quarter_turns.rem_euclid(2) == 1 {
+            self.original_height_px as f32 / self.original_width_px as f32
+        } else {
+            self.original_width_px as f32 / self.original_height_px as f32
+        }
+    }
+
+    /// Width / height of the image as shown: its current frame, which the
+    /// 90° turns swap, or the rotated and cropped pixels while it has no
+    /// size yet. Typed sizes and the resize handles keep this ratio when the
+    /// aspect is locked.
+    pub fn display_aspect_ratio(&self) -> f32 {
+        if self.width_mm > 0.0 && self.height_mm > 0.0 {
+            self.width_mm / self.height_mm
+        } else {
+            let crop = self.crop.unwrap_or(Crop::FULL);
+            self.content_aspect() * crop.width / crop.height
+        }
+    }
+
+    /// True when the image is wider or taller than the paper itself, so part
+    /// of it is clipped wherever it is placed
+    pub fn exceeds_page(&self, page: &Page) -> bool {
+        const TOLERANCE_MM: f32 = 0.01;
+        self.width_mm > page.width_mm + TOLERANCE_MM || self.height_mm > page.height_mm + TOLERANCE_MM
+    }
+
+    /// Crop to a pixel rectangle of the source file and change the height so
+    /// the cropped content keeps its proportions at the current width
+    pub fn crop_to_source_pixels(&mut self, rect: (u32, u32, u32, u32)) {
+        let crop = self.crop_for_source_pixels(rect);
+        let aspect = self.content_aspect() * crop.width / crop.height;
+        self.height_mm = self.width_mm / aspect;
+        self.crop = Some(crop);
+    }
+
+    /// Crop to a pixel rectangle of the source file and shrink the frame
+    /// around what is left, so the remaining content keeps its size and
+    /// place on the page (used to drop transparent padding from graphics)
+    pub fn trim_to_source_pixels(&mut self, rect: (u32, u32, u32, u32)) {
+        let old = self.crop.unwrap_or(Crop::FULL);
+        let crop = self.crop_for_source_pixels(rect);
+        let (mm_per_x, mm_per_y) = (self.width_mm / old.width, self.height_mm / old.height);
+        self.x_mm += (crop.x - old.x) * mm_per_x;
+        self.y_mm += (crop.y - old.y) * mm_per_y;
+        self.width_mm = crop.width * mm_per_x;
+        self.height_mm = crop.height * mm_per_y;
+        self.crop = Some(crop);
+    }
+
+    /// Display crop showing a pixel rectangle (x, y, width, height) of the source file
+    fn crop_for_source_pixels(&self, rect: (u32, u32, u32, u32)) -> Crop {
+        let (w, h) = (self.original_width_px as f32, self.original_height_px as f32);
+        let source = Crop {
+            x: rect.0 as f32 / w,
+            y: rect.1 as f32 / h,
+            width: rect.2 as f32 / w,
+            height: rect.3 as f32 / h,
+        };
+        Crop::from_source(source, self.rotation_degrees, self.flip_horizontal, self.flip_vertical)
+    }
+
+    /// Pixel rectangle (x, y, width, height) of the source file that the
+    /// crop shows, or `None` when the image is not cropped
+    pub fn crop_source_pixels(&self) -> Option<(u32, u32, u32, u32)> {
+        self.crop.map(|crop| {
+            crop.to_source(self.rotation_degrees, self.flip_horizontal, self.flip_vertical)
+                .pixel_rect(self.original_width_px, self.original_height_px)
+        })
+    }
+
+    /// Name to show in the canvas label, thumbnails and reports
+    pub fn display_label(&self) -> &str {
+        match self.display_name.as_deref() {
+            Some(name) if !name.trim().is_empty() => name,
+            _ => self
+                .path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown"),
         }
     }
 
-    /// Calculate the effective DPI when this image is printed
+    /// Where `caption` is printed, as (x, y, width, height) in mm: centered
+    /// just below the image, narrowed to fit its width
+    pub fn caption_rect(&self, caption: &str) -> (f32, f32, f32, f32) {
+        let (x, y, width, height) = self.rotated_bounds();
+        let mut text_height = CAPTION_HEIGHT_MM;
+        let mut text_width = crate::bitmap_font::text_width(caption, text_height);
+        if text_width > width {
+            text_height *= width / text_width;
+            text_width = width;
+        }
+        (x + (width - text_width) / 2.0, y + height + CAPTION_GAP_MM, text_width, text_height)
+    }
+
+    /// Print sharpening to use: this image's override, else the page's setting
+    pub fn effective_output_sharpen(&self, page: &Page) -> f32 {
+        self.output_sharpen.unwrap_or(page.output_sharpen).clamp(-1.0, 1.0)
+    }
+
+    /// True when the image prints below `MIN_RECOMMENDED_DPI` in either direction
+    pub fn is_low_dpi(&self) -> bool {
+        let (dpi_x, dpi_y) = self.effective_dpi();
+        dpi_x.min(dpi_y) < MIN_RECOMMENDED_DPI
+    }
+
+    /// Calculate the effective DPI when this image is printed, from the
+    /// source pixels the crop keeps, turned with the image
     pub fn effective_dpi(&self) -> (f32, f32) {
-        let width_inches = self.width_mm / 25.4;
-        let height_inches = self.height_mm / 25.4;
-        let dpi_x = self.original_width_px as f32 / width_inches;
-        let dpi_y = self.original_height_px as f32 / height_inches;
+        let (pixels_x, pixels_y) = if self.quarter_turns() % 2 == 1 {
+            (self.original_height_px as f32, self.original_width_px as f32)
+        } else {
+            (self.original_width_px as f32, self.original_height_px as f32)
+        };
+        let crop = self.crop.unwrap_or(Crop::FULL);
+        let dpi_x = pixels_x * crop.width / (self.width_mm / 25.4);
+        let dpi_y = pixels_y * crop.height / (self.height_mm / 25.4);
         (dpi_x, dpi_y)
     }
 
-    /// Check if a point (in mm) is within this image's bounds
+    /// How the photo sits in its frame: Fill when it is cropped to cover
+    /// the frame, Fit when all of it shows
+    pub fn frame_fit(&self) -> SizeFit {
+        if self.crop.is_some() {
+            SizeFit::Fill
+        } else {
+            SizeFit::Fit
+        }
+    }
+
+    /// Fill: keep the frame and crop the photo to cover it, centered on
+    /// the current crop. Fit: show the whole photo, shrinking the frame
+    /// about its center to the photo's proportions.
+    pub fn set_frame_fit(&mut self, fit: SizeFit) {
+        let aspect = self.content_aspect();
+        match fit {
+            SizeFit::Fill => {
+                let frame_aspect = self.width_mm / self.height_mm;
+                let center = self.crop.unwrap_or(Crop::FULL).center();
+                self.crop = if (frame_aspect - aspect).abs() < 1e-3 {
+                    None
+                } else {
+                    Some(Crop::cover(aspect, frame_aspect, center))
+                };
+            }
+            SizeFit::Fit => {
+                let width = self.width_mm.min(self.height_mm * aspect);
+                let height = width / aspect;
+                self.x_mm += (self.width_mm - width) / 2.0;
+                self.y_mm += (self.height_mm - height) / 2.0;
+                (self.width_mm, self.height_mm) = (width, height);
+                self.crop = None;
+            }
+        }
+    }
+
+    /// Whole quarter turns (0-3) in the rotation, applied to the pixels;
+    /// whatever is left over tilts the frame (see `frame_angle_degrees`)
+    pub fn quarter_turns(&self) -> i32 {
+        (whole_quarter_turns(self.rotation_degrees)).rem_euclid(4)
+    }
+
+    /// Tilt of the frame about its center, in degrees clockwise: the part
+    /// of the rotation left over after the quarter turns, which are applied
+    /// by swapping the frame's width and height instead
+    pub fn frame_angle_degrees(&self) -> f32 {
+        self.rotation_degrees - whole_quarter_turns(self.rotation_degrees) as f32 * 90.0
+    }
+
+    /// Page position (mm) of a point given as fractions (0.0-1.0) of the
+    /// untilted frame, after tilting the frame about its center
+    pub fn frame_point(&self, u: f32, v: f32) -> (f32, f32) {
+        let (cx, cy) = (self.x_mm + self.width_mm / 2.0, self.y_mm + self.height_mm / 2.0);
+        let (dx, dy) = ((u - 0.5) * self.width_mm, (v - 0.5) * self.height_mm);
+        let (sin, cos) = self.frame_angle_degrees().to_radians().sin_cos();
+        (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+    }
+
+    /// Frame corners on the page (mm): top-left, top-right, bottom-right,
+    /// bottom-left of the untilted frame
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(u, v)| self.frame_point(u, v))
+    }
+
+    /// Check if a point (in mm) is within this image's frame, following its tilt
+    pub fn contains_point(&self, x_mm: f32, y_mm: f32) -> bool {
+        // Undo the tilt about the center, then test the upright frame
+        let (cx, cy) = (self.x_mm + self.width_mm / 2.0, self.y_mm + self.height_mm / 2.0);
+        let (dx, dy) = (x_mm - cx, y_mm - cy);
+        let (sin, cos) = self.frame_angle_degrees().to_radians().sin_cos();
+        let (local_x, local_y) = (dx * cos + dy * sin, -dx * sin + dy * cos);
+        local_x.abs() <= self.width_mm / 2.0 && local_y.abs() <= self.height_mm / 2.0
+    }
+
+    /// Get the bounding box in millimeters (x, y, width, height)
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        (self.x_mm, self.y_mm, self.width_mm, self.height_mm)
+    }
+
+    /// Axis-aligned box (x, y, width, height) in mm around the tilted frame
+    pub fn rotated_bounds(&self) -> (f32, f32, f32, f32) {
+        // Untilted frames are their own bounds, without rounding from the tilt math
+        if self.frame_angle_degrees() == 0.0 {
+            return self.bounds();
+        }
+        let corners = self.corners();
+        let (mut min_x, mut min_y) = corners[0];
+        let (mut max_x, mut max_y) = corners[0];
+        for (x, y) in &corners[1..] {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+        (min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Shift the frame so its rotated bounds start at (x, y)
+    pub fn move_bounds_to(&mut self, x: f32, y: f32) {
+        let (bx, by, _, _) = self.rotated_bounds();
+        self.x_mm += x - bx;
+        self.y_mm += y - by;
+    }
+}
+
+/// A solid color rectangle placed on the layout, e.g. a color bar under a
+/// photo grid or a backdrop behind a cut-out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlacedRect {
+    pub id: String,
+    pub x_mm: f32,
+    pub y_mm: f32,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    /// Fill color as RGBA
+    pub color: [u8; 4],
+    #[serde(default)]
+    pub corner_radius_mm: f32,
+    /// Drawn just before the image with the same z-index
+    pub z_index: usize,
+    #[serde(default)]
+    pub locked: bool,
+}
+
+#[allow(dead_code)]
+impl PlacedRect {
+    pub fn new(x_mm: f32, y_mm: f32, width_mm: f32, height_mm: f32, color: [u8; 4]) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            x_mm,
+            y_mm,
+            width_mm,
+            height_mm,
+            color,
+            corner_radius_mm: 0.0,
+            z_index: 0,
+            locked: false,
+        }
+    }
+
+    /// Corner radius actually drawn: never more than half the shorter side
+    pub fn effective_corner_radius(&self) -> f32 {
+        self.corner_radius_mm
+            .clamp(0.0, self.width_mm.min(self.height_mm) / 2.0)
+    }
+
+    /// Check if a point (in mm) is within this rectangle's bounds
     pub fn contains_point(&self, x_mm: f32, y_mm: f32) -> bool {
         x_mm >= self.x_mm
             && x_mm <= self.x_mm + self.width_mm
@@ -399,18 +1701,115 @@ impl PlacedImage {
             && y_mm <= self.y_mm + self.height_mm
     }
 
+    /// True when a point (in mm) is inside the rectangle with its rounded
+    /// corners cut off; used when filling it
+    pub fn covers_point(&self, x_mm: f32, y_mm: f32) -> bool {
+        if !self.contains_point(x_mm, y_mm) {
+            return false;
+        }
+        let r = self.effective_corner_radius();
+        // Distance into the corner square, measured from the nearest corner circle center
+        let dx = (self.x_mm + r - x_mm).max(x_mm - (self.x_mm + self.width_mm - r)).max(0.0);
+        let dy = (self.y_mm + r - y_mm).max(y_mm - (self.y_mm + self.height_mm - r)).max(0.0);
+        dx * dx + dy * dy <= r * r
+    }
+
     /// Get the bounding box in millimeters (x, y, width, height)
     pub fn bounds(&self) -> (f32, f32, f32, f32) {
         (self.x_mm, self.y_mm, self.width_mm, self.height_mm)
     }
 }
 
+/// Color as "#RRGGBB", or "#RRGGBBAA" when not fully opaque
+pub fn format_hex_color(color: [u8; 4]) -> String {
+    let [r, g, b, a] = color;
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
+
+/// Parse "#RRGGBB" or "#RRGGBBAA" (the "#" is optional)
+pub fn parse_hex_color(text: &str) -> Option<[u8; 4]> {
+    let hex = text.trim().trim_start_matches('#');
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Some([channel(0)?, channel(1)?, channel(2)?, alpha])
+}
+
+/// Anything placed on the page, so images and color blocks can be ordered,
+/// hit-tested and selected together
+#[derive(Debug, Clone, Copy)]
+pub enum LayoutElement<'a> {
+    Image(&'a PlacedImage),
+    Rect(&'a PlacedRect),
+}
+
+#[allow(dead_code)]
+impl<'a> LayoutElement<'a> {
+    pub fn id(&self) -> &'a str {
+        match *self {
+            LayoutElement::Image(img) => &img.id,
+            LayoutElement::Rect(rect) => &rect.id,
+        }
+    }
+
+    pub fn z_index(&self) -> usize {
+        match self {
+            LayoutElement::Image(img) => img.z_index,
+            LayoutElement::Rect(rect) => rect.z_index,
+        }
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match self {
+            LayoutElement::Image(img) => img.bounds(),
+            LayoutElement::Rect(rect) => rect.bounds(),
+        }
+    }
+
+    pub fn contains_point(&self, x_mm: f32, y_mm: f32) -> bool {
+        match self {
+            LayoutElement::Image(img) => img.contains_point(x_mm, y_mm),
+            LayoutElement::Rect(rect) => rect.contains_point(x_mm, y_mm),
+        }
+    }
+
+    /// Locked elements can be selected but not moved or resized
+    pub fn locked(&self) -> bool {
+        match self {
+            LayoutElement::Image(img) => img.locked,
+            LayoutElement::Rect(rect) => rect.locked,
+        }
+    }
+
+    /// Drawing order key: by z-index, with a rect before the image it shares a z-index with
+    fn draw_order(&self) -> (usize, u8) {
+        match self {
+            LayoutElement::Rect(rect) => (rect.z_index, 0),
+            LayoutElement::Image(img) => (img.z_index, 1),
+        }
+    }
+}
+
 /// Represents the complete layout
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layout {
     pub page: Page,
     pub images: Vec<PlacedImage>,
+    /// Solid color blocks; older files have none
+    #[serde(default)]
+    pub rects: Vec<PlacedRect>,
+    /// Selected image or color block; with several selected, the one the
+    /// tool tabs edit
     pub selected_image_id: Option<String>,
+    /// The rest of a multiple selection, not saved with the project
+    #[serde(skip)]
+    pub also_selected: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -420,71 +1819,3067 @@ impl Layout {
         Self {
             page: Page::default(),
             images: Vec::new(),
+            rects: Vec::new(),
             selected_image_id: None,
+            also_selected: Vec::new(),
+        }
+    }
+
+    /// Select just `id`, or nothing
+    pub fn select(&mut self, id: Option<String>) {
+        self.selected_image_id = id;
+        self.also_selected.clear();
+    }
+
+    /// Make `id` the primary selection. A member of the current selection
+    /// keeps the rest selected with it, so a group can be dragged by any of
+    /// its members; anything else replaces the selection.
+    pub fn select_keeping_group(&mut self, id: String) {
+        if self.selected_image_id.as_deref() == Some(id.as_str()) {
+            return;
+        }
+        match self.also_selected.iter().position(|other| *other == id) {
+            Some(index) => {
+                self.also_selected.remove(index);
+                if let Some(previous) = self.selected_image_id.replace(id) {
+                    self.also_selected.push(previous);
+                }
+            }
+            None => self.select(Some(id)),
+        }
+    }
+
+    /// Add `id` to the selection as its new primary, or take it out if it
+    /// was selected (Ctrl+click)
+    pub fn toggle_selected(&mut self, id: String) {
+        if self.is_selected(&id) {
+            self.deselect(&id);
+        } else if let Some(previous) = self.selected_image_id.replace(id) {
+            self.also_selected.push(previous);
+        }
+    }
+
+    /// Select every image and color block lying wholly inside the rectangle
+    /// (x, y, width, height in mm), adding to the selection when `extend`.
+    /// The topmost one becomes the primary selection.
+    pub fn select_in_rect(&mut self, (x, y, width, height): (f32, f32, f32, f32), extend: bool) {
+        if !extend {
+            self.select(None);
+        }
+        let inside: Vec<String> = self
+            .elements()
+            .iter()
+            .filter(|e| {
+                let (ex, ey, ew, eh) = e.bounds();
+                ex >= x && ey >= y && ex + ew <= x + width && ey + eh <= y + height
+            })
+            .map(|e| e.id().to_string())
+            .collect();
+        for id in inside {
+            if !self.is_selected(&id) {
+                self.toggle_selected(id);
+            }
+        }
+    }
+
+    /// Whether `id` is the primary or one of the other selected elements
+    pub fn is_selected(&self, id: &str) -> bool {
+        self.selected_image_id.as_deref() == Some(id) || self.also_selected.iter().any(|other| other == id)
+    }
+
+    /// Every selected id, the primary first
+    pub fn selected_ids(&self) -> Vec<String> {
+        self.selected_image_id.iter().chain(&self.also_selected).cloned().collect()
+    }
+
+    /// Drop `id` from the selection; if it was the primary, the most
+    /// recently added of the others takes its place
+    fn deselect(&mut self, id: &str) {
+        self.also_selected.retain(|other| other != id);
+        if self.selected_image_id.as_deref() == Some(id) {
+            self.selected_image_id = self.also_selected.pop();
         }
     }
 
-    /// Add an image to the layout
+    /// Add an image to the layout, on top of the others
     pub fn add_image(&mut self, image: PlacedImage) {
-        let z_index = self.images.len();
+        self.normalize_z();
         let mut image = image;
-        image.z_index = z_index;
+        image.z_index = self.images.len();
         self.images.push(image);
     }
 
-    /// Remove an image by ID
-    pub fn remove_image(&mut self, id: &str) -> Option<PlacedImage> {
-        if let Some(index) = self.images.iter().position(|img| img.id == id) {
-            let removed = self.images.remove(index);
-            // Reindex remaining images
-            for (i, img) in self.images.iter_mut().enumerate() {
-                img.z_index = i;
-            }
-            // Clear selection if removed image was selected
-            if self.selected_image_id.as_deref() == Some(id) {
-                self.selected_image_id = None;
-            }
-            Some(removed)
-        } else {
-            None
+    /// Put `images` in z-index order and renumber them 0, 1, 2… Images that
+    /// share a z-index keep their array order. Color blocks stay between the
+    /// same images. Files can disagree with the array order when edited by
+    /// hand or merged, so this runs on load.
+    pub fn normalize_z(&mut self) {
+        // A block sits under the images with a z-index at or above its own
+        for rect in &mut self.rects {
+            rect.z_index = self.images.iter().filter(|img| img.z_index < rect.z_index).count();
         }
+        self.images.sort_by_key(|img| img.z_index);
+        self.renumber_z();
     }
 
-    /// Get a mutable reference to an image by ID
-    pub fn get_image_mut(&mut self, id: &str) -> Option<&mut PlacedImage> {
-        self.images.iter_mut().find(|img| img.id == id)
+    /// Set each image's z-index to its position in `images`
+    fn renumber_z(&mut self) {
+        for (i, img) in self.images.iter_mut().enumerate() {
+            img.z_index = i;
+        }
     }
 
-    /// Get an immutable reference to an image by ID
-    pub fn get_image(&self, id: &str) -> Option<&PlacedImage> {
-        self.images.iter().find(|img| img.id == id)
+    /// Switch the page to the photo paper that best matches an image's aspect
+    /// ratio, turn it to the image's orientation, and scale the image to fill
+    /// the printable area, centered
+    pub fn fit_paper_to_image(&mut self, id: &str, margins_user_edited: bool) {
+        let Some((width, height)) = self.get_image(id).map(|img| (img.width_mm, img.height_mm)) else {
+            return;
+        };
+        let page = &mut self.page;
+        page.set_paper_size(PaperSize::closest_photo_aspect(width, height), margins_user_edited);
+        let image_landscape = width > height;
+        if image_landscape != (page.width_mm > page.height_mm) && page.width_mm != page.height_mm {
+            std::mem::swap(&mut page.width_mm, &mut page.height_mm);
+            page.orientation = if image_landscape {
+                Orientation::Landscape
+            } else {
+                Orientation::Portrait
+            };
+        }
+
+        let (area_x, area_y, area_w, area_h) = self.page.printable_area();
+        let scale = (area_w / width).min(area_h / height);
+        if let Some(image) = self.get_image_mut(id) {
+            image.width_mm = width * scale;
+            image.height_mm = height * scale;
+            image.x_mm = area_x + (area_w - image.width_mm) / 2.0;
+            image.y_mm = area_y + (area_h - image.height_mm) / 2.0;
+        }
     }
 
-    /// Find the topmost image at the given point (in mm)
-    pub fn find_image_at_point(&self, x_mm: f32, y_mm: f32) -> Option<&PlacedImage> {
-        // Iterate in reverse z-order (topmost first)
+    /// IDs of images larger than the paper, in stacking order
+    pub fn oversized_image_ids(&self) -> Vec<String> {
         self.images
             .iter()
-            .rev()
-            .find(|img| img.contains_point(x_mm, y_mm))
+            .filter(|img| img.exceeds_page(&self.page))
+            .map(|img| img.id.clone())
+            .collect()
     }
 
-    /// Get the currently selected image
-    pub fn selected_image(&self) -> Option<&PlacedImage> {
-        self.selected_image_id
-            .as_ref()
-            .and_then(|id| self.get_image(id))
+    /// IDs of images that reach into the corners kept free for the cut
+    /// registration marks, in stacking order
+    pub fn images_over_marks(&self) -> Vec<String> {
+        let reserved = self.page.mark_reserved_regions();
+        self.images
+            .iter()
+            .filter(|img| {
+                let (x, y, w, h) = img.rotated_bounds();
+                reserved
+                    .iter()
+                    .any(|&(rx, ry, rw, rh)| x < rx + rw && rx < x + w && y < ry + rh && ry < y + h)
+            })
+            .map(|img| img.id.clone())
+            .collect()
     }
 
-    /// Get a mutable reference to the currently selected image
-    pub fn selected_image_mut(&mut self) -> Option<&mut PlacedImage> {
-        let id = self.selected_image_id.clone()?;
-        self.get_image_mut(&id)
+    /// IDs of images that cover part of another image, in stacking order
+    pub fn overlapping_images(&self) -> Vec<String> {
+        let bounds: Vec<(f32, f32, f32, f32)> = self.images.iter().map(|img| img.rotated_bounds()).collect();
+        self.images
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| {
+                let (x, y, w, h) = bounds[i];
+                bounds
+                    .iter()
+                    .enumerate()
+                    .any(|(j, &(ox, oy, ow, oh))| i != j && x < ox + ow && ox < x + w && y < oy + oh && oy < y + h)
+            })
+            .map(|(_, img)| img.id.clone())
+            .collect()
     }
-}
 
-impl Default for Layout {
-    fn default() -> Self {
-        Self::new()
+    /// Shrink an image, keeping its aspect ratio, until it fits the printable
+    /// area, then move it inside. Images that already fit are only moved;
+    /// nothing changes when the margins leave no printable area.
+    pub fn scale_image_to_fit(&mut self, id: &str) {
+        let (area_x, area_y, area_w, area_h) = self.page.printable_area();
+        if area_w <= 0.0 || area_h <= 0.0 {
+            return;
+        }
+        if let Some(image) = self.get_image_mut(id) {
+            let scale = (area_w / image.width_mm).min(area_h / image.height_mm).min(1.0);
+            image.width_mm *= scale;
+            image.height_mm *= scale;
+            // Not clamp: rounding can leave the image a hair wider than the area
+            image.x_mm = image.x_mm.min(area_x + area_w - image.width_mm).max(area_x);
+            image.y_mm = image.y_mm.min(area_y + area_h - image.height_mm).max(area_y);
+        }
+    }
+
+    /// Box (x, y, width, height in mm) around every image, by its rotated
+    /// bounds, and every color block; None on an empty page
+    pub fn content_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let boxes = self
+            .images
+            .iter()
+            .map(|img| img.rotated_bounds())
+            .chain(self.rects.iter().map(|rect| rect.bounds()));
+        boxes
+            .map(|(x, y, w, h)| (x, y, x + w, y + h))
+            .reduce(|(ax, ay, ar, ab), (x, y, r, b)| (ax.min(x), ay.min(y), ar.max(r), ab.max(b)))
+            .map(|(x, y, right, bottom)| (x, y, right - x, bottom - y))
+    }
+
+    /// Switch to another paper and orientation the way picking them in the
+    /// settings does, then move all content together (locked images too, so
+    /// the arrangement is kept) to the middle of the new printable area
+    pub fn switch_paper_centered(&mut self, paper_size: PaperSize, orientation: Orientation, margins_user_edited: bool) {
+        self.page.set_orientation(orientation);
+        self.page.set_paper_size(paper_size, margins_user_edited);
+        let Some((x, y, width, height)) = self.content_bounds() else {
+            return;
+        };
+        let (area_x, area_y, area_w, area_h) = self.page.printable_area();
+        let dx = area_x + (area_w - width) / 2.0 - x;
+        let dy = area_y + (area_h - height) / 2.0 - y;
+        for image in &mut self.images {
+            image.x_mm += dx;
+            image.y_mm += dy;
+        }
+        for rect in &mut self.rects {
+            rect.x_mm += dx;
+            rect.y_mm += dy;
+        }
+    }
+
+    /// Scale an image, keeping its aspect ratio, to the largest size that fits
+    /// the printable area and center it there. Tilted images are fitted by
+    /// their rotated bounds. Locked images are left alone. Returns whether the
+    /// image changed.
+    pub fn fit_image_to_page(&mut self, id: &str) -> bool {
+        let area = self.page.printable_area();
+        let Some(image) = self.get_image_mut(id).filter(|img| !img.locked) else {
+            return false;
+        };
+        let before = image.bounds();
+        let (_, _, bw, bh) = image.rotated_bounds();
+        let scale = (area.2 / bw).min(area.3 / bh);
+        image.width_mm *= scale;
+        image.height_mm *= scale;
+        center_in_area(image, area);
+        image.bounds() != before
+    }
+
+    /// Move an image to the middle of the printable area without resizing it.
+    /// Locked images stay put. Returns whether the image moved.
+    pub fn center_image_on_page(&mut self, id: &str) -> bool {
+        let area = self.page.printable_area();
+        let Some(image) = self.get_image_mut(id).filter(|img| !img.locked) else {
+            return false;
+        };
+        let before = image.bounds();
+        center_in_area(image, area);
+        image.bounds() != before
+    }
+
+    /// Set the page up for `template` (its margins or borderless printing)
+    /// and snap the unlocked images into its cells in reading order, each
+    /// cropped to fill its cell or fitted inside it, turned first when
+    /// `auto_rotate` and that fits better. A lone image is tiled into every
+    /// cell, as in `arrange_grid`. Nothing changes when the cells don't fit;
+    /// otherwise returns how many images were left out.
+    pub fn apply_template(&mut self, template: &LayoutTemplate, auto_rotate: bool) -> Result<usize, String> {
+        let mut page = self.page.clone();
+        if template.borderless {
+            page.set_borderless(true, true);
+        } else if let Some((top, bottom, left, right)) = template.margins {
+            page.borderless = false;
+            page.try_set_margins(top, bottom, left, right).map_err(|e| e.to_string())?;
+        }
+        let previous = std::mem::replace(&mut self.page, page);
+        let placed = template
+            .cells(self.area_clear_of_marks())
+            .and_then(|cells| images_for_cells(self, cells.len()).map(|ids| (cells, ids)));
+        let (cells, ids) = match placed {
+            Ok(placed) => placed,
+            Err(e) => {
+                self.page = previous;
+                return Err(e);
+            }
+        };
+
+        for (&cell, id) in cells.iter().zip(&ids) {
+            let Some(image) = self.get_image_mut(id) else {
+                continue;
+            };
+            match template.fit {
+                SizeFit::Fill => {
+                    (image.x_mm, image.y_mm, image.width_mm, image.height_mm) = cell;
+                    image.set_frame_fit(SizeFit::Fill);
+                }
+                SizeFit::Fit => {
+                    image.set_frame_fit(SizeFit::Fit);
+                    image.fit_into_cell(cell, auto_rotate);
+                }
+            }
+        }
+        Ok(ids.len().saturating_sub(cells.len()))
+    }
+
+    /// Pack the unlocked images into the printable area (clear of any
+    /// registration marks) on shelves, keeping their sizes: tallest first,
+    /// each goes on the first row with room left, and a new row opens below
+    /// when none has. Images and rows are `gutter_mm` apart. With
+    /// `auto_rotate` an image is turned a quarter turn only when that packs
+    /// it better: to join a row it only fits lying the other way, or to open
+    /// a row when it doesn't fit as it is or `should_rotate_to_fit` says it
+    /// fills the space left below better turned. Locked images stay put and
+    /// the rest are packed around them, a gutter away; images there is no
+    /// room for stay put as well, and their ids are returned.
+    pub fn auto_arrange(&mut self, gutter_mm: f32, auto_rotate: bool) -> Vec<String> {
+        const TOLERANCE_MM: f32 = 0.01;
+        let gutter = if gutter_mm.is_finite() { gutter_mm.max(0.0) } else { 0.0 };
+        let (area_x, area_y, area_w, area_h) = self.area_clear_of_marks();
+        let fits = |width: f32, height: f32| width <= area_w + TOLERANCE_MM && height <= area_h + TOLERANCE_MM;
+
+        // Index and packed width and height (of the rotated bounds), tallest
+        // first and otherwise in reading order; an image that only fits
+        // turned counts by its height turned
+        let mut items: Vec<(usize, f32, f32)> = reading_order(&self.images)
+            .into_iter()
+            .filter(|&i| !self.images[i].locked)
+            .map(|i| {
+                let (_, _, width, height) = self.images[i].rotated_bounds();
+                (i, width, height)
+            })
+            .collect();
+        let packed_height = |&(_, width, height): &(usize, f32, f32)| {
+            if auto_rotate && !fits(width, height) && fits(height, width) {
+                width
+            } else {
+                height
+            }
+        };
+        items.sort_by(|a, b| packed_height(b).total_cmp(&packed_height(a)));
+
+        // Locked images are obstacles: whatever is packed keeps a gutter
+        // from them. `clear_x` is the first left edge from `x` on where a
+        // box at `y` misses them all and still ends inside the area.
+        let obstacles: Vec<(f32, f32, f32, f32)> =
+            self.images.iter().filter(|img| img.locked).map(|img| img.rotated_bounds()).collect();
+        let in_band = |&(_, oy, _, oh): &(f32, f32, f32, f32), y: f32, height: f32| {
+            oy < y + height + gutter - TOLERANCE_MM && y < oy + oh + gutter - TOLERANCE_MM
+        };
+        let clear_x = |mut x: f32, y: f32, width: f32, height: f32| loop {
+            if x + width > area_x + area_w + TOLERANCE_MM {
+                return None;
+            }
+            let in_the_way = obstacles
+                .iter()
+                .filter(|o| in_band(o, y, height))
+                .filter(|&&(ox, _, ow, _)| ox < x + width + gutter - TOLERANCE_MM && x < ox + ow + gutter - TOLERANCE_MM)
+                .map(|&(ox, _, ow, _)| ox + ow + gutter)
+                .reduce(f32::max);
+            match in_the_way {
+                Some(past) => x = past,
+                None => return Some(x),
+            }
+        };
+
+        // Rows as (top, height, left edge of the free space)
+        let mut shelves: Vec<(f32, f32, f32)> = Vec::new();
+        let row_with_room = |shelves: &[(f32, f32, f32)], width: f32, height: f32| {
+            shelves.iter().enumerate().find_map(|(row, &(top, shelf_height, free_x))| {
+                if height > shelf_height + TOLERANCE_MM {
+                    return None;
+                }
+                clear_x(free_x, top, width, height).map(|x| (row, x))
+            })
+        };
+        let mut left_out = Vec::new();
+        'images: for (index, width, height) in items {
+            let existing = match row_with_room(&shelves, width, height) {
+                Some((row, x)) => Some((row, x, false)),
+                None if auto_rotate => row_with_room(&shelves, height, width).map(|(row, x)| (row, x, true)),
+                None => None,
+            };
+            let (x, y, turn) = match existing {
+                Some((row, x, turn)) => {
+                    let (top, _, free_x) = &mut shelves[row];
+                    *free_x = x + if turn { height } else { width } + gutter;
+                    (x, *top, turn)
+                }
+                None => {
+                    let mut top = shelves.last().map_or(area_y, |(top, height, _)| top + height + gutter);
+                    loop {
+                        let space = (area_w, area_y + area_h - top);
+                        let fits_space = |width: f32, height: f32| {
+                            width <= space.0 + TOLERANCE_MM && height <= space.1 + TOLERANCE_MM
+                        };
+                        let upright = fits_space(width, height);
+                        let turned = auto_rotate && fits_space(height, width);
+                        if !upright && !turned {
+                            left_out.push(self.images[index].id.clone());
+                            continue 'images;
+                        }
+                        let turn = turned && (!upright || should_rotate_to_fit(width / height, space.0 / space.1));
+                        let (width, height) = if turn { (height, width) } else { (width, height) };
+                        if let Some(x) = clear_x(area_x, top, width, height) {
+                            shelves.push((top, height, x + width + gutter));
+                            break (x, top, turn);
+                        }
+                        // No gap in this row between the locked images:
+                        // open it below the first of them to end
+                        let below = obstacles
+                            .iter()
+                            .filter(|o| in_band(o, top, height))
+                            .map(|&(_, oy, _, oh)| oy + oh + gutter)
+                            .reduce(f32::min);
+                        match below {
+                            Some(below) => top = below,
+                            None => {
+                                left_out.push(self.images[index].id.clone());
+                                continue 'images;
+                            }
+                        }
+                    }
+                }
+            };
+
+            let image = &mut self.images[index];
+            if turn {
+                std::mem::swap(&mut image.width_mm, &mut image.height_mm);
+                image.rotation_degrees = (image.rotation_degrees + 90.0) % 360.0;
+            }
+            let (bounds_x, bounds_y, _, _) = image.rotated_bounds();
+            image.x_mm += x - bounds_x;
+            image.y_mm += y - bounds_y;
+        }
+        left_out
+    }
+
+    /// Bring every unlocked image inside the printable area, shrinking (never
+    /// growing) any that are too big and moving the rest by as little as
+    /// possible. Tilted images are fitted by their rotated bounds. Returns how
+    /// many images changed.
+    pub fn keep_images_in_printable_area(&mut self) -> usize {
+        let (area_x, area_y, area_w, area_h) = self.page.printable_area();
+        let mut changed = 0;
+        for image in self.images.iter_mut().filter(|img| !img.locked) {
+            let before = image.bounds();
+            let (_, _, bw, bh) = image.rotated_bounds();
+            let scale = (area_w / bw).min(area_h / bh).min(1.0);
+            if scale < 1.0 {
+                // Scale about the center so the rotated bounds shrink in place
+                let (cx, cy) = (image.x_mm + image.width_mm / 2.0, image.y_mm + image.height_mm / 2.0);
+                image.width_mm *= scale;
+                image.height_mm *= scale;
+                image.x_mm = cx - image.width_mm / 2.0;
+                image.y_mm = cy - image.height_mm / 2.0;
+            }
+            let (bx, by, bw, bh) = image.rotated_bounds();
+            image.x_mm += bx.clamp(area_x, (area_x + area_w - bw).max(area_x)) - bx;
+            image.y_mm += by.clamp(area_y, (area_y + area_h - bh).max(area_y)) - by;
+            if image.bounds() != before {
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Turn the page (see `Page::set_orientation`), keeping each image at
+    /// the same offset from the page's top-left corner but pulled back onto
+    /// the new page if it would now hang off it. Returns false if the
+    /// orientation didn't change.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> bool {
+        if !self.page.set_orientation(orientation) {
+            return false;
+        }
+        let (page_width, page_height) = (self.page.width_mm, self.page.height_mm);
+        for image in &mut self.images {
+            let (bx, by, bw, bh) = image.rotated_bounds();
+            image.x_mm += bx.clamp(0.0, (page_width - bw).max(0.0)) - bx;
+            image.y_mm += by.clamp(0.0, (page_height - bh).max(0.0)) - by;
+        }
+        true
+    }
+
+    /// Remove an image by ID
+    pub fn remove_image(&mut self, id: &str) -> Option<PlacedImage> {
+        if let Some(index) = self.images.iter().position(|img| img.id == id) {
+            let removed = self.images.remove(index);
+            // Color blocks above the removed image keep their place in the stack
+            self.normalize_z();
+            self.deselect(id);
+            Some(removed)
+        } else {
+            None
+        }
+    }
+
+    /// Copy every selected image and color block `offset_mm` right and down,
+    /// on top of the others, and select the copies. Copies start unlocked.
+    /// Returns the new ids, the copy of the primary selection first.
+    pub fn duplicate_selection(&mut self, offset_mm: f32) -> Vec<String> {
+        let mut copies = Vec::new();
+        for id in self.selected_ids() {
+            let new_id = Uuid::new_v4().to_string();
+            if let Some(image) = self.get_image(&id) {
+                let mut copy = image.clone();
+                copy.id = new_id.clone();
+                copy.x_mm += offset_mm;
+                copy.y_mm += offset_mm;
+                copy.locked = false;
+                self.add_image(copy);
+            } else if let Some(rect) = self.get_rect(&id) {
+                let mut copy = rect.clone();
+                copy.id = new_id.clone();
+                copy.x_mm += offset_mm;
+                copy.y_mm += offset_mm;
+                copy.locked = false;
+                self.add_rect(copy);
+            } else {
+                continue;
+            }
+            copies.push(new_id);
+        }
+        if let Some((primary, others)) = copies.split_first() {
+            self.select(Some(primary.clone()));
+            self.also_selected = others.to_vec();
+        }
+        copies
+    }
+
+    /// Move the image at position `from` to position `to` in the stacking
+    /// order (0 is the bottom) and renumber z-indexes. Returns false if
+    /// either position is out of range.
+    pub fn move_image(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.images.len() || to >= self.images.len() {
+            return false;
+        }
+        let image = self.images.remove(from);
+        self.images.insert(to, image);
+        self.renumber_z();
+        true
+    }
+
+    /// Raise an image one step in the stacking order. Returns false if it
+    /// is already on top or doesn't exist.
+    pub fn bring_forward(&mut self, id: &str) -> bool {
+        match self.images.iter().position(|img| img.id == id) {
+            Some(index) => self.move_image(index, index + 1),
+            None => false,
+        }
+    }
+
+    /// Lower an image one step in the stacking order. Returns false if it
+    /// is already at the bottom or doesn't exist.
+    pub fn send_backward(&mut self, id: &str) -> bool {
+        match self.images.iter().position(|img| img.id == id) {
+            Some(index) if index > 0 => self.move_image(index, index - 1),
+            _ => false,
+        }
+    }
+
+    /// Put an image above all others. Returns false if it is already on top
+    /// or doesn't exist.
+    pub fn bring_to_front(&mut self, id: &str) -> bool {
+        let top = self.images.len().saturating_sub(1);
+        match self.images.iter().position(|img| img.id == id) {
+            Some(index) if index < top => self.move_image(index, top),
+            _ => false,
+        }
+    }
+
+    /// Put an image below all others. Returns false if it is already at the
+    /// bottom or doesn't exist.
+    pub fn send_to_back(&mut self, id: &str) -> bool {
+        match self.images.iter().position(|img| img.id == id) {
+            Some(index) if index > 0 => self.move_image(index, 0),
+            _ => false,
+        }
+    }
+
+    /// Get a mutable reference to an image by ID
+    pub fn get_image_mut(&mut self, id: &str) -> Option<&mut PlacedImage> {
+        self.images.iter_mut().find(|img| img.id == id)
+    }
+
+    /// Get an immutable reference to an image by ID
+    pub fn get_image(&self, id: &str) -> Option<&PlacedImage> {
+        self.images.iter().find(|img| img.id == id)
+    }
+
+    /// Find the topmost image at the given point (in mm)
+    pub fn find_image_at_point(&self, x_mm: f32, y_mm: f32) -> Option<&PlacedImage> {
+        // Highest z-index wins; among equals the later image, as it is drawn last
+        self.images
+            .iter()
+            .filter(|img| img.contains_point(x_mm, y_mm))
+            .max_by_key(|img| img.z_index)
+    }
+
+    /// Images and color blocks in drawing order, bottom first
+    pub fn elements(&self) -> Vec<LayoutElement<'_>> {
+        let mut elements: Vec<LayoutElement> = self
+            .rects
+            .iter()
+            .map(LayoutElement::Rect)
+            .chain(self.images.iter().map(LayoutElement::Image))
+            .collect();
+        elements.sort_by_key(|e| e.draw_order());
+        elements
+    }
+
+    /// Duplicates for every image printed more than once, `copies - 1` of
+    /// each, placed in free spots of the printable area: the highest, then
+    /// the leftmost spot that keeps clear of every other image. Returns the
+    /// duplicates and how many didn't fit anywhere and were left out.
+    pub fn duplicate_copies(&self) -> (Vec<PlacedImage>, usize) {
+        let (placed, left_out) = self.place_copies();
+        (placed, left_out.len())
+    }
+
+    /// The duplicates that fit on the page, placed, and those that don't,
+    /// still at their original's position
+    fn place_copies(&self) -> (Vec<PlacedImage>, Vec<PlacedImage>) {
+        let area = self.page.printable_area();
+        // The registration mark corners are kept free like any other image
+        let mut occupied = self.page.mark_reserved_regions();
+        occupied.extend(self.images.iter().map(|img| img.rotated_bounds()));
+        let mut duplicates = Vec::new();
+        let mut left_out = Vec::new();
+        for image in self.images.iter().filter(|img| img.copies > 1) {
+            let (_, _, bw, bh) = image.rotated_bounds();
+            for copy in 1..image.copies {
+                let mut duplicate = image.clone();
+                duplicate.id = format!("{}-copy{}", image.id, copy);
+                duplicate.copies = 1;
+                match free_spot(&occupied, area, (bw, bh)) {
+                    Some((x, y)) => {
+                        // Shift the frame so its rotated bounds land on the spot
+                        duplicate.move_bounds_to(x, y);
+                        occupied.push((x, y, bw, bh));
+                        duplicates.push(duplicate);
+                    }
+                    None => left_out.push(duplicate),
+                }
+            }
+        }
+        (duplicates, left_out)
+    }
+
+    /// Extra sheets for the copies that don't fit on this page, laid out
+    /// row by row with the same page settings. Empty when everything fits.
+    pub fn overflow_pages(&self) -> Vec<Layout> {
+        let (_, left_out) = self.place_copies();
+        let sizes: Vec<(f32, f32)> = left_out
+            .iter()
+            .map(|copy| {
+                let (_, _, width, height) = copy.rotated_bounds();
+                (width, height)
+            })
+            .collect();
+        paginate(&sizes, self.area_clear_of_marks(), COPY_GAP_MM)
+            .into_iter()
+            .map(|placements| {
+                let mut sheet = Layout::new();
+                sheet.page = self.page.clone();
+                for (index, x, y) in placements {
+                    let mut copy = left_out[index].clone();
+                    copy.move_bounds_to(x, y);
+                    sheet.add_image(copy);
+                }
+                sheet
+            })
+            .collect()
+    }
+
+    /// Every sheet this layout prints on: the page with its copies added,
+    /// then the overflow pages
+    pub fn sheets(&self) -> Vec<Layout> {
+        let (first, _) = self.expand_copies();
+        let mut sheets = vec![first];
+        sheets.extend(self.overflow_pages());
+        sheets
+    }
+
+    /// The printable area less the bands at the top and bottom that the
+    /// registration mark corners reach into
+    fn area_clear_of_marks(&self) -> (f32, f32, f32, f32) {
+        let (x, mut top, width, height) = self.page.printable_area();
+        let mut bottom = top + height;
+        let middle = self.page.height_mm / 2.0;
+        for (_, ry, _, rh) in self.page.mark_reserved_regions() {
+            if ry < middle {
+                top = top.max(ry + rh + COPY_GAP_MM);
+            } else {
+                bottom = bottom.min(ry - COPY_GAP_MM);
+            }
+        }
+        (x, top, width, (bottom - top).max(0.0))
+    }
+
+    /// This layout with every image's extra copies added as images of their
+    /// own on top, as printing needs it. Also returns how many copies didn't
+    /// fit on the page.
+    pub fn expand_copies(&self) -> (Layout, usize) {
+        let (duplicates, left_out) = self.duplicate_copies();
+        let mut expanded = self.clone();
+        for image in &mut expanded.images {
+            image.copies = 1;
+        }
+        expanded.images.extend(duplicates);
+        expanded.renumber_z();
+        (expanded, left_out)
+    }
+
+    /// Images paired with their sequence numbers (from 1), in the order the
+    /// page's sequence settings ask for
+    pub fn numbered_images(&self) -> Vec<(&PlacedImage, usize)> {
+        let order: Vec<usize> = match self.page.sequence_numbers.order {
+            SequenceOrder::Reading => reading_order(&self.images),
+            SequenceOrder::Stacking => {
+                let mut order: Vec<usize> = (0..self.images.len()).collect();
+                order.sort_by_key(|&i| self.images[i].z_index);
+                order
+            }
+        };
+        order.into_iter().enumerate().map(|(n, i)| (&self.images[i], n + 1)).collect()
+    }
+
+    /// Find the topmost image or color block at the given point (in mm)
+    pub fn find_element_at_point(&self, x_mm: f32, y_mm: f32) -> Option<LayoutElement<'_>> {
+        self.elements()
+            .into_iter()
+            .rev()
+            .find(|e| e.contains_point(x_mm, y_mm))
+    }
+
+    /// Add a color block on top of every image currently in the layout
+    pub fn add_rect(&mut self, rect: PlacedRect) {
+        let mut rect = rect;
+        rect.z_index = self.images.len();
+        self.rects.push(rect);
+    }
+
+    pub fn get_rect(&self, id: &str) -> Option<&PlacedRect> {
+        self.rects.iter().find(|r| r.id == id)
+    }
+
+    pub fn get_rect_mut(&mut self, id: &str) -> Option<&mut PlacedRect> {
+        self.rects.iter_mut().find(|r| r.id == id)
+    }
+
+    /// Remove a color block by ID, clearing the selection if it was selected
+    pub fn remove_rect(&mut self, id: &str) -> Option<PlacedRect> {
+        let index = self.rects.iter().position(|r| r.id == id)?;
+        self.deselect(id);
+        Some(self.rects.remove(index))
+    }
+
+    /// Get the currently selected color block
+    pub fn selected_rect(&self) -> Option<&PlacedRect> {
+        self.selected_image_id
+            .as_ref()
+            .and_then(|id| self.get_rect(id))
+    }
+
+    /// Get the currently selected image
+    pub fn selected_image(&self) -> Option<&PlacedImage> {
+        self.selected_image_id
+            .as_ref()
+            .and_then(|id| self.get_image(id))
+    }
+
+    /// Get a mutable reference to the currently selected image
+    pub fn selected_image_mut(&mut self) -> Option<&mut PlacedImage> {
+        let id = self.selected_image_id.clone()?;
+        self.get_image_mut(&id)
+    }
+
+    /// List what changed going from `previous` (e.g. the saved file) to this
+    /// layout. Images are matched by id; page settings are compared field by field.
+    pub fn diff(&self, previous: &Layout) -> Vec<LayoutChange> {
+        let mut changes = diff_page(&previous.page, &self.page);
+
+        for old in &previous.images {
+            let label = old.display_label().to_string();
+            let Some(new) = self.get_image(&old.id) else {
+                changes.push(LayoutChange::ImageRemoved {
+                    id: old.id.clone(),
+                    label,
+                    bounds: old.bounds(),
+                });
+                continue;
+            };
+
+            let moved = !approx_eq(old.x_mm, new.x_mm) || !approx_eq(old.y_mm, new.y_mm);
+            let resized =
+                !approx_eq(old.width_mm, new.width_mm) || !approx_eq(old.height_mm, new.height_mm);
+            if resized {
+                changes.push(LayoutChange::ImageResized {
+                    id: old.id.clone(),
+                    label: label.clone(),
+                    from: old.bounds(),
+                    to: new.bounds(),
+                });
+            } else if moved {
+                changes.push(LayoutChange::ImageMoved {
+                    id: old.id.clone(),
+                    label: label.clone(),
+                    from: old.bounds(),
+                    to: new.bounds(),
+                });
+            }
+
+            let mut details = Vec::new();
+            if !approx_eq(old.rotation_degrees, new.rotation_degrees) {
+                details.push(format!("rotation {}° → {}°", old.rotation_degrees, new.rotation_degrees));
+            }
+            if old.flip_horizontal != new.flip_horizontal {
+                details.push("horizontal flip".to_string());
+            }
+            if old.flip_vertical != new.flip_vertical {
+                details.push("vertical flip".to_string());
+            }
+            if old.crop != new.crop {
+                details.push("crop".to_string());
+            }
+            if !approx_eq(old.opacity, new.opacity) {
+                details.push(format!(
+                    "opacity {:.0}% → {:.0}%",
+                    old.opacity * 100.0,
+                    new.opacity * 100.0
+                ));
+            }
+            if !details.is_empty() {
+                changes.push(LayoutChange::ImageTransformed {
+                    id: old.id.clone(),
+                    label,
+                    details,
+                });
+            }
+        }
+
+        for new in &self.images {
+            if previous.get_image(&new.id).is_none() {
+                changes.push(LayoutChange::ImageAdded {
+                    id: new.id.clone(),
+                    label: new.display_label().to_string(),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+/// Positions and sizes closer than this (mm) count as unchanged
+const DIFF_TOLERANCE_MM: f32 = 0.01;
+
+fn approx_eq(a: f32, b: f32) -> bool {
+    (a - b).abs() < DIFF_TOLERANCE_MM
+}
+
+fn diff_page(old: &Page, new: &Page) -> Vec<LayoutChange> {
+    let mut changes = Vec::new();
+    let mut compare = |setting: &'static str, from: String, to: String| {
+        if from != to {
+            changes.push(LayoutChange::PageChanged { setting, from, to });
+        }
+    };
+    compare("Paper size", old.paper_size.to_string(), new.paper_size.to_string());
+    compare("Paper type", old.paper_type.to_string(), new.paper_type.to_string());
+    compare("Orientation", old.orientation.to_string(), new.orientation.to_string());
+    compare("Print quality", old.print_quality.to_string(), new.print_quality.to_string());
+    compare("Color mode", old.color_mode.to_string(), new.color_mode.to_string());
+    compare("Toning", old.toning.to_string(), new.toning.to_string());
+    compare("Scaling", old.print_scaling.to_string(), new.print_scaling.to_string());
+    compare("Borderless", old.borderless.to_string(), new.borderless.to_string());
+    let margins = [
+        ("Top margin", old.margin_top_mm, new.margin_top_mm),
+        ("Bottom margin", old.margin_bottom_mm, new.margin_bottom_mm),
+        ("Left margin", old.margin_left_mm, new.margin_left_mm),
+        ("Right margin", old.margin_right_mm, new.margin_right_mm),
+    ];
+    for (setting, from, to) in margins {
+        if !approx_eq(from, to) {
+            compare(setting, format!("{:.1}mm", from), format!("{:.1}mm", to));
+        }
+    }
+    changes
+}
+
+/// A single difference reported by [`Layout::diff`]
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutChange {
+    ImageAdded {
+        id: String,
+        label: String,
+    },
+    ImageRemoved {
+        id: String,
+        label: String,
+        bounds: (f32, f32, f32, f32),
+    },
+    /// Position changed, size unchanged
+    ImageMoved {
+        id: String,
+        label: String,
+        from: (f32, f32, f32, f32),
+        to: (f32, f32, f32, f32),
+    },
+    /// Size changed (the position may have changed too)
+    ImageResized {
+        id: String,
+        label: String,
+        from: (f32, f32, f32, f32),
+        to: (f32, f32, f32, f32),
+    },
+    /// Rotation, flip or opacity changed
+    ImageTransformed {
+        id: String,
+        label: String,
+        details: Vec<String>,
+    },
+    PageChanged {
+        setting: &'static str,
+        from: String,
+        to: String,
+    },
+}
+
+impl LayoutChange {
+    /// Where the image used to be, for outlining old positions on the canvas
+    pub fn previous_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        match self {
+            LayoutChange::ImageRemoved { bounds, .. } => Some(*bounds),
+            LayoutChange::ImageMoved { from, .. } | LayoutChange::ImageResized { from, .. } => {
+                Some(*from)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LayoutChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutChange::ImageAdded { label, .. } => write!(f, "Added {}", label),
+            LayoutChange::ImageRemoved { label, .. } => write!(f, "Removed {}", label),
+            LayoutChange::ImageMoved { label, from, to, .. } => write!(
+                f,
+                "Moved {} from ({:.1}, {:.1}) to ({:.1}, {:.1}) mm",
+                label, from.0, from.1, to.0, to.1
+            ),
+            LayoutChange::ImageResized { label, from, to, .. } => write!(
+                f,
+                "Resized {} from {:.1} × {:.1} to {:.1} × {:.1} mm",
+                label, from.2, from.3, to.2, to.3
+            ),
+            LayoutChange::ImageTransformed { label, details, .. } => {
+                write!(f, "Changed {}: {}", label, details.join(", "))
+            }
+            LayoutChange::PageChanged { setting, from, to } => {
+                write!(f, "{}: {} → {}", setting, from, to)
+            }
+        }
+    }
+}
+
+/// Build a standalone one-image layout on the given paper.
+/// The page is oriented to match the image, the image is centered and only
+/// scaled down (never up) if it doesn't fit. Non-borderless pages get a small
+/// 3mm margin instead of the usual 1 inch so photo papers stay usable.
+pub fn single_image_layout(image: &PlacedImage, paper_size: PaperSize, borderless: bool) -> Layout {
+    let mut page = Page::new(paper_size);
+    let image_landscape = image.width_mm > image.height_mm;
+    let page_landscape = page.width_mm > page.height_mm;
+    if image_landscape != page_landscape && page.width_mm != page.height_mm {
+        std::mem::swap(&mut page.width_mm, &mut page.height_mm);
+        page.orientation = if image_landscape {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        };
+    }
+
+    let margin = if borderless { 0.0 } else { 3.0 };
+    page.borderless = borderless;
+    page.margin_top_mm = margin;
+    page.margin_bottom_mm = margin;
+    page.margin_left_mm = margin;
+    page.margin_right_mm = margin;
+
+    let (area_x, area_y, area_w, area_h) = page.printable_area();
+    let scale = (area_w / image.width_mm).min(area_h / image.height_mm).min(1.0);
+    let mut placed = image.clone();
+    placed.width_mm = image.width_mm * scale;
+    placed.height_mm = image.height_mm * scale;
+    placed.x_mm = area_x + (area_w - placed.width_mm) / 2.0;
+    placed.y_mm = area_y + (area_h - placed.height_mm) / 2.0;
+
+    let mut layout = Layout::new();
+    layout.page = page;
+    layout.add_image(placed);
+    layout
+}
+
+/// Print resolution below which an image is flagged as likely to look soft
+pub const MIN_RECOMMENDED_DPI: f32 = 150.0;
+
+/// Offset between successive images dropped at the same spot
+pub const DROP_CASCADE_MM: f32 = 5.0;
+
+/// Top-left corner for the `index`th image (from 0) dropped at `point_mm`.
+/// Each later image is shifted down and right so they don't stack exactly,
+/// and the corner is pulled back so an image that fits the page stays on it.
+pub fn drop_position(page: &Page, point_mm: (f32, f32), index: usize, size_mm: (f32, f32)) -> (f32, f32) {
+    let offset = index as f32 * DROP_CASCADE_MM;
+    let max_x = (page.width_mm - size_mm.0).max(0.0);
+    let max_y = (page.height_mm - size_mm.1).max(0.0);
+    (
+        (point_mm.0 + offset).clamp(0.0, max_x),
+        (point_mm.1 + offset).clamp(0.0, max_y),
+    )
+}
+
+/// Common print sizes offered when adding images: label, short and long side in mm
+pub const COMMON_PRINT_SIZES: [(&str, f32, f32); 7] = [
+    ("9×13 cm", 90.0, 130.0),
+    ("10×15 cm", 100.0, 150.0),
+    ("13×18 cm", 130.0, 180.0),
+    ("4×6\"", 101.6, 152.4),
+    ("5×7\"", 127.0, 177.8),
+    ("8×10\"", 203.2, 254.0),
+    ("5×5\"", 127.0, 127.0),
+];
+
+/// How a new image is sized into a requested print size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SizeFit {
+    /// Crop the photo so it covers the size exactly
+    #[default]
+    Fill,
+    /// Scale the whole photo to fit inside the size
+    Fit,
+}
+
+/// Physical size requested for newly added images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintSize {
+    pub width_mm: f32,
+    pub height_mm: f32,
+    pub fit: SizeFit,
+}
+
+/// Size and position a newly added image. With a `size`, the box is turned
+/// to match the photo's orientation: Fill crops the photo to exactly that
+/// box, Fit scales it to fit inside. The top-left corner goes to `anchor_mm`
+/// (or the image's default spot), cascaded by `index` as in `drop_position`.
+pub fn place_new_image(
+    image: &mut PlacedImage,
+    page: &Page,
+    size: Option<PrintSize>,
+    anchor_mm: Option<(f32, f32)>,
+    index: usize,
+) {
+    if let Some(size) = size {
+        let aspect = image.content_aspect();
+        let (short, long) = (size.width_mm.min(size.height_mm), size.width_mm.max(size.height_mm));
+        let (box_w, box_h) = if aspect >= 1.0 { (long, short) } else { (short, long) };
+        match size.fit {
+            SizeFit::Fill => {
+                image.width_mm = box_w;
+                image.height_mm = box_h;
+                let box_aspect = box_w / box_h;
+                image.crop = if (box_aspect - aspect).abs() < 1e-3 {
+                    None
+                } else {
+                    Some(Crop::cover(aspect, box_aspect, (0.5, 0.5)))
+                };
+            }
+            SizeFit::Fit => {
+                image.width_mm = box_w.min(box_h * aspect);
+                image.height_mm = image.width_mm / aspect;
+                image.crop = None;
+            }
+        }
+    }
+    let anchor = anchor_mm.unwrap_or((image.x_mm, image.y_mm));
+    (image.x_mm, image.y_mm) = drop_position(page, anchor, index, (image.width_mm, image.height_mm));
+}
+
+/// Least gain in cell coverage (as a fraction of the cell) for which
+/// auto-rotate turns an image
+pub const AUTO_ROTATE_MIN_GAIN: f32 = 0.1;
+
+/// Center an image's frame (and so its rotated bounds) in an area (x, y,
+/// width, height in mm)
+fn center_in_area(image: &mut PlacedImage, (x, y, width, height): (f32, f32, f32, f32)) {
+    image.x_mm = x + (width - image.width_mm) / 2.0;
+    image.y_mm = y + (height - image.height_mm) / 2.0;
+}
+
+/// Fraction of a cell with aspect `cell_aspect` (width / height) covered by
+/// content with aspect `content_aspect` scaled to fit inside it
+pub fn fit_fill_ratio(content_aspect: f32, cell_aspect: f32) -> f32 {
+    content_aspect.min(cell_aspect) / content_aspect.max(cell_aspect)
+}
+
+/// Whether turning content a quarter turn fills its cell better by more
+/// than AUTO_ROTATE_MIN_GAIN, e.g. a portrait photo in a landscape cell
+pub fn should_rotate_to_fit(content_aspect: f32, cell_aspect: f32) -> bool {
+    if !(content_aspect > 0.0 && cell_aspect > 0.0) {
+        return false;
+    }
+    fit_fill_ratio(1.0 / content_aspect, cell_aspect) - fit_fill_ratio(content_aspect, cell_aspect) > AUTO_ROTATE_MIN_GAIN
+}
+
+/// `value` rounded to the nearest multiple of `grid`; a zero or negative
+/// grid leaves it unchanged
+pub fn snap_to_grid(value: f32, grid: f32) -> f32 {
+    if grid > 0.0 {
+        (value / grid).round() * grid
+    } else {
+        value
+    }
+}
+
+/// `degrees` rounded to the nearest multiple of `increment`, within
+/// 0..360 so that angles just below a full turn snap to 0 rather than 360.
+/// A zero or negative increment only normalizes the angle.
+pub fn snap_angle(degrees: f32, increment: f32) -> f32 {
+    let snapped = if increment > 0.0 {
+        (degrees / increment).round() * increment
+    } else {
+        degrees
+    };
+    let normalized = snapped.rem_euclid(360.0);
+    // rem_euclid can round up to exactly 360 for tiny negative inputs
+    if normalized >= 360.0 {
+        0.0
+    } else {
+        normalized
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Spread boxes of the given sizes (mm) over as many pages as they need:
+/// left to right in rows starting at the top-left of `area`, `gutter` apart,
+/// starting a new row when one is full and a new page when a row would run
+/// off the bottom. Returns each page's (index into `sizes`, x, y). A box
+/// larger than the area gets a page of its own, at the corner.
+pub fn paginate(sizes: &[(f32, f32)], area: (f32, f32, f32, f32), gutter: f32) -> Vec<Vec<(usize, f32, f32)>> {
+    const TOLERANCE_MM: f32 = 0.01;
+    let (area_x, area_y, area_w, area_h) = area;
+    let mut pages = Vec::new();
+    let mut page = Vec::new();
+    let (mut x, mut y, mut row_height) = (area_x, area_y, 0.0_f32);
+    for (index, &(width, height)) in sizes.iter().enumerate() {
+        if width > area_w + TOLERANCE_MM || height > area_h + TOLERANCE_MM {
+            if !page.is_empty() {
+                pages.push(std::mem::take(&mut page));
+            }
+            pages.push(vec![(index, area_x, area_y)]);
+            (x, y, row_height) = (area_x, area_y, 0.0);
+            continue;
+        }
+        if x + width > area_x + area_w + TOLERANCE_MM {
+            (x, y, row_height) = (area_x, y + row_height + gutter, 0.0);
+        }
+        if y + height > area_y + area_h + TOLERANCE_MM {
+            pages.push(std::mem::take(&mut page));
+            (x, y, row_height) = (area_x, area_y, 0.0);
+        }
+        page.push((index, x, y));
+        x += width + gutter;
+        row_height = row_height.max(height);
+    }
+    if !page.is_empty() {
+        pages.push(page);
+    }
+    pages
+}
+
+/// Highest, then leftmost position for a box of `size` (mm) inside `area`
+/// that stays `COPY_GAP_MM` clear of every `occupied` box. The candidates
+/// are the area's corner and the spots just right of and just below each
+/// occupied box.
+fn free_spot(
+    occupied: &[(f32, f32, f32, f32)],
+    area: (f32, f32, f32, f32),
+    size: (f32, f32),
+) -> Option<(f32, f32)> {
+    const TOLERANCE_MM: f32 = 0.01;
+    let (area_x, area_y, area_w, area_h) = area;
+    let (width, height) = size;
+    let mut candidates = vec![(area_x, area_y)];
+    for &(x, y, w, h) in occupied {
+        candidates.push((x + w + COPY_GAP_MM, y));
+        candidates.push((x, y + h + COPY_GAP_MM));
+        candidates.push((area_x, y + h + COPY_GAP_MM));
+        candidates.push((x + w + COPY_GAP_MM, area_y));
+    }
+    let inside = |&(x, y): &(f32, f32)| {
+        x >= area_x - TOLERANCE_MM
+            && y >= area_y - TOLERANCE_MM
+            && x + width <= area_x + area_w + TOLERANCE_MM
+            && y + height <= area_y + area_h + TOLERANCE_MM
+    };
+    let clear = |&(x, y): &(f32, f32)| {
+        occupied.iter().all(|&(ox, oy, ow, oh)| {
+            x + width + COPY_GAP_MM <= ox + TOLERANCE_MM
+                || ox + ow + COPY_GAP_MM <= x + TOLERANCE_MM
+                || y + height + COPY_GAP_MM <= oy + TOLERANCE_MM
+                || oy + oh + COPY_GAP_MM <= y + TOLERANCE_MM
+        })
+    };
+    candidates
+        .into_iter()
+        .filter(inside)
+        .filter(clear)
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.total_cmp(&b.0)))
+}
+
+/// Indices of `images` in reading order: rows from top to bottom, left to
+/// right within a row. An image joins the current row when its top edge is
+/// above the middle of the row's first image, so hand-placed grids that are
+/// a little uneven still number row by row.
+pub fn reading_order(images: &[PlacedImage]) -> Vec<usize> {
+    let mut by_top: Vec<usize> = (0..images.len()).collect();
+    by_top.sort_by(|&a, &b| {
+        let (a, b) = (&images[a], &images[b]);
+        a.y_mm.total_cmp(&b.y_mm).then(a.x_mm.total_cmp(&b.x_mm))
+    });
+
+    let mut order = Vec::with_capacity(images.len());
+    let mut row: Vec<usize> = Vec::new();
+    let mut row_middle = f32::NEG_INFINITY;
+    for index in by_top {
+        let img = &images[index];
+        if img.y_mm >= row_middle {
+            row.sort_by(|&a, &b| images[a].x_mm.total_cmp(&images[b].x_mm));
+            order.append(&mut row);
+            row_middle = img.y_mm + img.height_mm / 2.0;
+        }
+        row.push(index);
+    }
+    row.sort_by(|&a, &b| images[a].x_mm.total_cmp(&images[b].x_mm));
+    order.append(&mut row);
+    order
+}
+
+/// Arrange the images in `rows` × `cols` equal cells filling the printable
+/// area (clear of any registration marks), `spacing_mm` apart. Images go into
+/// the cells in reading order, each scaled to fit its cell and centered
+/// (turned first when `auto_rotate` and that fits better). A lone image is
+/// tiled instead: copies of it fill every cell, replacing its automatic
+/// copies. Locked images, and any beyond `rows * cols`, stay where they
+/// are; returns how many unlocked images were left out.
+pub fn arrange_grid(layout: &mut Layout, rows: usize, cols: usize, spacing_mm: f32, auto_rotate: bool) -> Result<usize, String> {
+    let cells = grid_cells(layout.area_clear_of_marks(), rows, cols, spacing_mm)?;
+    let ids = images_for_cells(layout, cells.len())?;
+    for (cell, id) in cells.iter().zip(&ids) {
+        if let Some(image) = layout.get_image_mut(id) {
+            image.fit_into_cell(*cell, auto_rotate);
+        }
+    }
+    Ok(ids.len().saturating_sub(cells.len()))
+}
+
+/// `rows` × `cols` equal cells (x, y, width, height in mm) filling `area`,
+/// `spacing_mm` apart, row by row
+fn grid_cells(
+    (area_x, area_y, area_w, area_h): (f32, f32, f32, f32),
+    rows: usize,
+    cols: usize,
+    spacing_mm: f32,
+) -> Result<Vec<(f32, f32, f32, f32)>, String> {
+    if rows == 0 || cols == 0 {
+        return Err("The grid needs at least one row and one column".to_string());
+    }
+    if !spacing_mm.is_finite() || spacing_mm < 0.0 {
+        return Err("Grid spacing can't be negative".to_string());
+    }
+    let cell_w = (area_w - spacing_mm * (cols - 1) as f32) / cols as f32;
+    let cell_h = (area_h - spacing_mm * (rows - 1) as f32) / rows as f32;
+    if cell_w < MIN_IMAGE_SIZE_MM || cell_h < MIN_IMAGE_SIZE_MM {
+        return Err(format!(
+            "{} × {} cells {} mm apart don't fit the printable area",
+            rows, cols, spacing_mm
+        ));
+    }
+    Ok((0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            (
+                area_x + col as f32 * (cell_w + spacing_mm),
+                area_y + row as f32 * (cell_h + spacing_mm),
+                cell_w,
+                cell_h,
+            )
+        })
+        .collect())
+}
+
+/// Ids of the unlocked images in reading order, to be placed into
+/// `cell_count` cells. A lone image is tiled: copies of it are added until
+/// there is one per cell, replacing its automatic copies.
+fn images_for_cells(layout: &mut Layout, cell_count: usize) -> Result<Vec<String>, String> {
+    let mut ids: Vec<String> = reading_order(&layout.images)
+        .into_iter()
+        .map(|i| &layout.images[i])
+        .filter(|img| !img.locked)
+        .map(|img| img.id.clone())
+        .collect();
+    if ids.is_empty() {
+        return Err("There are no unlocked images to arrange".to_string());
+    }
+    if let [id] = ids.as_slice() {
+        let mut source = layout.get_image(id).cloned().expect("id from layout");
+        source.copies = 1;
+        layout.get_image_mut(id).expect("id from layout").copies = 1;
+        for _ in 1..cell_count {
+            let mut copy = source.clone();
+            copy.id = Uuid::new_v4().to_string();
+            ids.push(copy.id.clone());
+            layout.add_image(copy);
+        }
+    }
+    Ok(ids)
+}
+
+/// How a layout template divides the page into cells
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TemplateGrid {
+    /// `rows` × `cols` equal cells filling the printable area
+    Divide { rows: usize, cols: usize },
+    /// As many cells of a fixed size as fit, centered in the printable area
+    FixedCells { width_mm: f32, height_mm: f32 },
+}
+
+/// A reusable page layout: a grid of cells, the space between them and the
+/// margins around them. Applying one snaps the images into the cells.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutTemplate {
+    pub name: String,
+    pub grid: TemplateGrid,
+    /// Space (mm) between neighbouring cells
+    pub gutter_mm: f32,
+    /// Margins (mm) the page gets, top, bottom, left, right; None keeps the
+    /// page's own
+    pub margins: Option<(f32, f32, f32, f32)>,
+    /// Print edge to edge; the page turns borderless and `margins` is unused
+    pub borderless: bool,
+    /// Whether images are cropped to fill their cells or fitted inside them
+    pub fit: SizeFit,
+}
+
+impl LayoutTemplate {
+    /// Templates that ship with the app
+    pub fn built_ins() -> Vec<LayoutTemplate> {
+        let divide = |name: &str, rows, cols, fit| LayoutTemplate {
+            name: name.to_string(),
+            grid: TemplateGrid::Divide { rows, cols },
+            gutter_mm: 5.0,
+            margins: None,
+            borderless: false,
+            fit,
+        };
+        vec![
+            LayoutTemplate {
+                gutter_mm: 0.0,
+                borderless: true,
+                ..divide("1-up full bleed", 1, 1, SizeFit::Fill)
+            },
+            divide("2-up", 2, 1, SizeFit::Fill),
+            divide("4-up", 2, 2, SizeFit::Fill),
+            LayoutTemplate {
+                gutter_mm: 3.0,
+                ..divide("3×3 contact sheet", 3, 3, SizeFit::Fit)
+            },
+            LayoutTemplate {
+                name: "Passport 35×45 mm".to_string(),
+                grid: TemplateGrid::FixedCells { width_mm: 35.0, height_mm: 45.0 },
+                gutter_mm: 3.0,
+                margins: None,
+                borderless: false,
+                fit: SizeFit::Fill,
+            },
+        ]
+    }
+
+    /// A `rows` × `cols` template with `page`'s margins, for saving the
+    /// current setup under `name`
+    pub fn capture(name: String, page: &Page, rows: usize, cols: usize, gutter_mm: f32, fit: SizeFit) -> Self {
+        Self {
+            name,
+            grid: TemplateGrid::Divide { rows, cols },
+            gutter_mm,
+            margins: Some((
+                page.margin_top_mm,
+                page.margin_bottom_mm,
+                page.margin_left_mm,
+                page.margin_right_mm,
+            )),
+            borderless: page.borderless,
+            fit,
+        }
+    }
+
+    /// The cells (x, y, width, height in mm) in `area`, row by row
+    pub fn cells(&self, area: (f32, f32, f32, f32)) -> Result<Vec<(f32, f32, f32, f32)>, String> {
+        let (width_mm, height_mm) = match self.grid {
+            TemplateGrid::Divide { rows, cols } => return grid_cells(area, rows, cols, self.gutter_mm),
+            TemplateGrid::FixedCells { width_mm, height_mm } => (width_mm, height_mm),
+        };
+        if !(width_mm >= MIN_IMAGE_SIZE_MM && height_mm >= MIN_IMAGE_SIZE_MM) {
+            return Err(format!("Cells must be at least {} mm on each side", MIN_IMAGE_SIZE_MM));
+        }
+        let (area_x, area_y, area_w, area_h) = area;
+        let gutter = self.gutter_mm.max(0.0);
+        let cols = ((area_w + gutter) / (width_mm + gutter)).floor().max(0.0) as usize;
+        let rows = ((area_h + gutter) / (height_mm + gutter)).floor().max(0.0) as usize;
+        if rows == 0 || cols == 0 {
+            return Err(format!(
+                "{} × {} mm cells don't fit the printable area",
+                width_mm, height_mm
+            ));
+        }
+        let left = area_x + (area_w - (cols as f32 * (width_mm + gutter) - gutter)) / 2.0;
+        let top = area_y + (area_h - (rows as f32 * (height_mm + gutter) - gutter)) / 2.0;
+        Ok((0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                (
+                    left + col as f32 * (width_mm + gutter),
+                    top + row as f32 * (height_mm + gutter),
+                    width_mm,
+                    height_mm,
+                )
+            })
+            .collect())
+    }
+}
+
+impl std::fmt::Display for LayoutTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Most images placed on one contact sheet
+pub const CONTACT_SHEET_MAX_IMAGES: usize = 48;
+
+/// Space (mm) between the cells of a contact sheet
+const CONTACT_SHEET_GAP_MM: f32 = 3.0;
+
+/// A contact sheet built from a list of image files
+#[derive(Debug, Clone)]
+pub struct ContactSheet {
+    pub layout: Layout,
+    /// Files that couldn't be read as images, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Readable images that didn't fit on the sheet
+    pub left_out: usize,
+}
+
+/// Lay `paths` out on `page` in `columns` columns of equal cells, in order,
+/// each image fitted into its cell (turned first when `auto_rotate` and that
+/// fits better) with its file name printed underneath. Rows fill the
+/// printable area, up to [`CONTACT_SHEET_MAX_IMAGES`] images; files whose
+/// image size can't be read are skipped.
+pub fn build_contact_sheet(paths: &[PathBuf], page: &Page, columns: usize, auto_rotate: bool) -> ContactSheet {
+    contact_sheet_with(paths, page, columns, auto_rotate, |path| {
+        ::image::image_dimensions(path).map_err(|e| e.to_string())
+    })
+}
+
+/// [`build_contact_sheet`] with `probe` returning an image's pixel size
+fn contact_sheet_with<F>(paths: &[PathBuf], page: &Page, columns: usize, auto_rotate: bool, probe: F) -> ContactSheet
+where
+    F: Fn(&Path) -> Result<(u32, u32), String>,
+{
+    let columns = columns.max(1);
+    let mut layout = Layout::new();
+    layout.page = page.clone();
+    layout.page.captions = true;
+
+    let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+    let caption_band = CAPTION_GAP_MM + CAPTION_HEIGHT_MM;
+    let cell_w = ((area_w - CONTACT_SHEET_GAP_MM * (columns - 1) as f32) / columns as f32).max(0.0);
+    // As many rows of square picture boxes as fit, then stretched to fill the height
+    let rows = (((area_h + CONTACT_SHEET_GAP_MM) / (cell_w + caption_band + CONTACT_SHEET_GAP_MM)) as usize).max(1);
+    let row_h = (area_h - CONTACT_SHEET_GAP_MM * (rows - 1) as f32) / rows as f32;
+    let picture_h = (row_h - caption_band).max(0.0);
+    let capacity = (rows * columns).min(CONTACT_SHEET_MAX_IMAGES);
+
+    let mut skipped = Vec::new();
+    let mut readable = 0;
+    for path in paths {
+        let (width_px, height_px) = match probe(path) {
+            Ok(size) => size,
+            Err(e) => {
+                skipped.push((path.clone(), e));
+                continue;
+            }
+        };
+        readable += 1;
+        let index = layout.images.len();
+        if index >= capacity {
+            continue;
+        }
+        let (row, col) = (index / columns, index % columns);
+        let cell = (
+            area_x + col as f32 * (cell_w + CONTACT_SHEET_GAP_MM),
+            area_y + row as f32 * (row_h + CONTACT_SHEET_GAP_MM),
+            cell_w,
+            picture_h,
+        );
+        let mut image = PlacedImage::new(path.clone(), width_px, height_px);
+        image.fit_into_cell(cell, auto_rotate);
+        layout.add_image(image);
+    }
+    let left_out = readable - layout.images.len();
+    ContactSheet { layout, skipped, left_out }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_position_cascades_and_stays_on_page() {
+        let page = Page::new(PaperSize::A4); // 210 x 297
+        assert_eq!(drop_position(&page, (40.0, 60.0), 0, (50.0, 30.0)), (40.0, 60.0));
+        assert_eq!(drop_position(&page, (40.0, 60.0), 2, (50.0, 30.0)), (50.0, 70.0));
+        // Near the right and bottom edges the image is pulled back onto the page
+        assert_eq!(drop_position(&page, (200.0, 290.0), 0, (50.0, 30.0)), (160.0, 267.0));
+        // An image bigger than the page starts at the page corner
+        assert_eq!(drop_position(&page, (10.0, 10.0), 0, (300.0, 30.0)), (0.0, 10.0));
+    }
+
+    #[test]
+    fn test_move_image_reorders_and_renumbers() {
+        let mut layout = Layout::new();
+        for name in ["a", "b", "c", "d"] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 10, 10);
+            img.id = name.to_string();
+            layout.add_image(img);
+        }
+        let order = |layout: &Layout| layout.images.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+
+        assert!(layout.move_image(0, 2));
+        assert_eq!(order(&layout), vec!["b", "c", "a", "d"]);
+        assert!(layout.move_image(3, 0));
+        assert_eq!(order(&layout), vec!["d", "b", "c", "a"]);
+        assert!(layout.images.iter().enumerate().all(|(i, img)| img.z_index == i));
+        assert!(!layout.move_image(1, 4));
+    }
+
+    #[test]
+    fn test_stacking_order_commands_reindex() {
+        let mut layout = Layout::new();
+        for name in ["a", "b", "c", "d"] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 10, 10);
+            img.id = name.to_string();
+            layout.add_image(img);
+        }
+        let order = |layout: &Layout| layout.images.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+
+        assert!(layout.bring_forward("a"));
+        assert_eq!(order(&layout), vec!["b", "a", "c", "d"]);
+        assert!(layout.send_backward("c"));
+        assert_eq!(order(&layout), vec!["b", "c", "a", "d"]);
+        assert!(layout.bring_to_front("b"));
+        assert_eq!(order(&layout), vec!["c", "a", "d", "b"]);
+        assert!(layout.send_to_back("d"));
+        assert_eq!(order(&layout), vec!["d", "c", "a", "b"]);
+        assert!(layout.images.iter().enumerate().all(|(i, img)| img.z_index == i));
+
+        // Already at the end, or unknown
+        assert!(!layout.bring_forward("b"));
+        assert!(!layout.bring_to_front("b"));
+        assert!(!layout.send_backward("d"));
+        assert!(!layout.send_to_back("d"));
+        assert!(!layout.bring_forward("missing"));
+        assert_eq!(order(&layout), vec!["d", "c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_multiple_selection() {
+        let mut layout = Layout::new();
+        let mut ids = Vec::new();
+        for x in [10.0, 70.0, 130.0] {
+            let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, 20.0, 50.0, 50.0);
+            ids.push(img.id.clone());
+            layout.add_image(img);
+        }
+        let (a, b, c) = (ids[0].clone(), ids[1].clone(), ids[2].clone());
+
+        layout.select(Some(a.clone()));
+        layout.toggle_selected(b.clone());
+        assert_eq!(layout.selected_ids(), vec![b.clone(), a.clone()]);
+        // Grabbing a member keeps the group; grabbing anything else doesn't
+        layout.select_keeping_group(a.clone());
+        assert_eq!(layout.selected_ids(), vec![a.clone(), b.clone()]);
+        layout.select_keeping_group(c.clone());
+        assert_eq!(layout.selected_ids(), vec![c.clone()]);
+
+        // Ctrl+click on the primary hands over to another member
+        layout.toggle_selected(a.clone());
+        layout.toggle_selected(a.clone());
+        assert_eq!(layout.selected_ids(), vec![c.clone()]);
+
+        // A band around the first two only, then added to with the third
+        layout.select_in_rect((5.0, 15.0, 120.0, 60.0), false);
+        assert_eq!(layout.selected_ids(), vec![b.clone(), a.clone()]);
+        layout.select_in_rect((125.0, 15.0, 60.0, 60.0), true);
+        assert_eq!(layout.selected_ids(), vec![c.clone(), a.clone(), b.clone()]);
+
+        // Removing the primary promotes another member
+        layout.remove_image(&c);
+        assert_eq!(layout.selected_ids(), vec![b.clone(), a.clone()]);
+
+        // The selection isn't saved
+        let json = serde_json::to_string(&layout).unwrap();
+        assert!(!json.contains("also_selected"));
+        let restored: Layout = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.selected_ids(), vec![b]);
+    }
+
+    #[test]
+    fn test_duplicate_selection_copies_and_selects() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+        (img.x_mm, img.y_mm) = (10.0, 20.0);
+        img.locked = true;
+        let image_id = img.id.clone();
+        layout.add_image(img);
+        let rect = PlacedRect::new(50.0, 50.0, 20.0, 20.0, [255, 0, 0, 255]);
+        let rect_id = rect.id.clone();
+        layout.add_rect(rect);
+
+        layout.select(Some(image_id.clone()));
+        layout.toggle_selected(rect_id.clone());
+        let copies = layout.duplicate_selection(5.0);
+        assert_eq!(copies.len(), 2);
+        assert_eq!(layout.selected_ids(), copies);
+        assert_eq!((layout.images.len(), layout.rects.len()), (2, 2));
+
+        let rect_copy = layout.get_rect(&copies[0]).unwrap();
+        assert_eq!((rect_copy.x_mm, rect_copy.y_mm), (55.0, 55.0));
+        let image_copy = layout.get_image(&copies[1]).unwrap();
+        assert_eq!((image_copy.x_mm, image_copy.y_mm), (15.0, 25.0));
+        assert!(!image_copy.locked);
+        assert!(layout.get_image(&image_id).unwrap().locked);
+    }
+
+    #[test]
+    fn test_find_image_at_point_picks_highest_z_index() {
+        let mut layout = Layout::new();
+        for (name, z) in [("over", 1), ("under", 0)] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 10, 10);
+            img.id = name.to_string();
+            img.z_index = z;
+            layout.images.push(img);
+        }
+        // Array order disagrees with z-index, as in a file not yet normalized
+        assert_eq!(layout.find_image_at_point(60.0, 60.0).unwrap().id, "over");
+        layout.normalize_z();
+        assert!(layout.send_to_back("over"));
+        assert_eq!(layout.find_image_at_point(60.0, 60.0).unwrap().id, "under");
+    }
+
+    #[test]
+    fn test_normalize_z_sorts_shuffled_images_and_keeps_blocks_in_place() {
+        let mut layout = Layout::new();
+        for (name, z) in [("top", 9), ("bottom", 2), ("middle_a", 5), ("middle_b", 5)] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 10, 10);
+            img.id = name.to_string();
+            img.z_index = z;
+            layout.images.push(img);
+        }
+        // Under "top", above both middles
+        let mut rect = PlacedRect::new(0.0, 0.0, 10.0, 10.0, [0, 0, 0, 255]);
+        rect.z_index = 7;
+        layout.rects.push(rect);
+
+        // Round-trip through JSON, as a hand-edited file would arrive
+        let json = serde_json::to_string(&layout).unwrap();
+        let mut layout: Layout = serde_json::from_str(&json).unwrap();
+        layout.normalize_z();
+
+        let order: Vec<&str> = layout.images.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(order, vec!["bottom", "middle_a", "middle_b", "top"]);
+        assert!(layout.images.iter().enumerate().all(|(i, img)| img.z_index == i));
+        assert_eq!(layout.rects[0].z_index, 3);
+        let drawn: Vec<&str> = layout.elements().iter().map(|e| e.id()).collect();
+        assert_eq!(drawn[3], layout.rects[0].id.as_str());
+
+        // Adding and removing keep indices dense
+        let mut extra = PlacedImage::new(PathBuf::from("extra"), 10, 10);
+        extra.id = "extra".to_string();
+        layout.add_image(extra);
+        assert_eq!(layout.get_image("extra").unwrap().z_index, 4);
+        layout.remove_image("bottom");
+        assert!(layout.images.iter().enumerate().all(|(i, img)| img.z_index == i));
+        assert_eq!(layout.rects[0].z_index, 2);
+    }
+
+    #[test]
+    fn test_low_dpi_flag() {
+        // 1600 px over 10 inches is 160 DPI: fine; over 11 inches it's 145
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1600, 1600);
+        img.width_mm = 254.0;
+        img.height_mm = 254.0;
+        assert!(!img.is_low_dpi());
+        img.width_mm = 279.4;
+        assert!(img.is_low_dpi());
+    }
+
+    #[test]
+    fn test_effective_dpi_counts_cropped_and_turned_pixels() {
+        // 3000 x 1500 px printed 10 x 5 inches is 300 DPI
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 1500);
+        (img.width_mm, img.height_mm) = (254.0, 127.0);
+        let (dpi_x, dpi_y) = img.effective_dpi();
+        assert!((dpi_x - 300.0).abs() < 0.1 && (dpi_y - 300.0).abs() < 0.1);
+
+        // Enlarging the left half to the same frame halves the DPI across
+        img.crop = Some(Crop { x: 0.0, y: 0.0, width: 0.5, height: 1.0 });
+        assert!((img.effective_dpi().0 - 150.0).abs() < 0.1);
+
+        // A quarter turn prints the 1500 px side across: over the same
+        // 10 x 5 inch frame that is 150 DPI across and 600 DPI down
+        img.crop = None;
+        img.rotation_degrees = 90.0;
+        let (dpi_x, dpi_y) = img.effective_dpi();
+        assert!((dpi_x - 150.0).abs() < 0.1 && (dpi_y - 600.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_fill_and_fit_frame() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 2000);
+        // A square frame stretches the 3:2 photo until it is filled
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (10.0, 10.0, 100.0, 100.0);
+        assert_eq!(img.frame_fit(), SizeFit::Fit);
+        img.set_frame_fit(SizeFit::Fill);
+        assert_eq!(img.frame_fit(), SizeFit::Fill);
+        let crop = img.crop.unwrap();
+        assert!((crop.width - 2.0 / 3.0).abs() < 1e-4 && (crop.x - 1.0 / 6.0).abs() < 1e-4);
+        assert_eq!(img.bounds(), (10.0, 10.0, 100.0, 100.0));
+
+        // Fit shows the whole photo inside the old frame, centered
+        img.set_frame_fit(SizeFit::Fit);
+        assert_eq!(img.crop, None);
+        let (x, y, width, height) = img.bounds();
+        assert_eq!((x, width), (10.0, 100.0));
+        assert!((height - 66.667).abs() < 0.01 && (y - 26.667).abs() < 0.01);
+
+        // Filling a frame that already matches the photo needs no crop
+        img.set_frame_fit(SizeFit::Fill);
+        assert_eq!(img.crop, None);
+    }
+
+    #[test]
+    fn test_place_new_image_fill_crops_to_exact_size() {
+        let page = Page::new(PaperSize::A4);
+        // 4:3 landscape photo into a 10×15 cm print: the box turns landscape
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 4000, 3000);
+        let size = PrintSize { width_mm: 100.0, height_mm: 150.0, fit: SizeFit::Fill };
+        place_new_image(&mut img, &page, Some(size), Some((20.0, 30.0)), 0);
+        assert_eq!((img.x_mm, img.y_mm, img.width_mm, img.height_mm), (20.0, 30.0, 150.0, 100.0));
+        let crop = img.crop.unwrap();
+        assert_eq!(crop.width, 1.0);
+        assert!((crop.height - 0.8889).abs() < 1e-3);
+
+        // A photo already at the box's shape isn't cropped
+        let mut img = PlacedImage::new(PathBuf::from("b.jpg"), 2000, 3000);
+        place_new_image(&mut img, &page, Some(size), None, 1);
+        assert_eq!((img.width_mm, img.height_mm, img.crop), (100.0, 150.0, None));
+        assert_eq!((img.x_mm, img.y_mm), (50.0 + DROP_CASCADE_MM, 50.0 + DROP_CASCADE_MM));
+    }
+
+    #[test]
+    fn test_place_new_image_fit_keeps_whole_photo() {
+        let page = Page::new(PaperSize::A4);
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 4000, 3000);
+        let size = PrintSize { width_mm: 100.0, height_mm: 150.0, fit: SizeFit::Fit };
+        place_new_image(&mut img, &page, Some(size), None, 0);
+        assert!((img.width_mm - 133.333).abs() < 1e-2);
+        assert!((img.height_mm - 100.0).abs() < 1e-3);
+        assert_eq!(img.crop, None);
+
+        // Without a size the default size is kept
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 4000, 3000);
+        place_new_image(&mut img, &page, None, Some((10.0, 10.0)), 0);
+        assert_eq!((img.x_mm, img.y_mm, img.width_mm, img.height_mm), (10.0, 10.0, 100.0, 75.0));
+    }
+
+    #[test]
+    fn test_nearest_photo_size() {
+        // 100×150mm fits on 4×6 either way round
+        assert_eq!(PaperSize::nearest_photo_size(100.0, 150.0), PaperSize::Photo4x6);
+        assert_eq!(PaperSize::nearest_photo_size(150.0, 100.0), PaperSize::Photo4x6);
+        // Slightly larger than 4×6 moves up to 5×7
+        assert_eq!(PaperSize::nearest_photo_size(110.0, 160.0), PaperSize::Photo5x7);
+        // Nothing fits: largest photo size
+        assert_eq!(PaperSize::nearest_photo_size(500.0, 500.0), PaperSize::Photo13x19);
+    }
+
+    #[test]
+    fn test_smallest_paper_holding_content() {
+        let photo_margins = |paper: PaperSize| paper.default_margin_mm();
+        // 150×100 plus 3 mm margins misses 4×6 and fits 5×7 turned sideways
+        assert_eq!(
+            PaperSize::smallest_holding(150.0, 100.0, &PaperSize::PHOTO_SIZES, photo_margins),
+            Some((PaperSize::Photo5x7, Orientation::Landscape))
+        );
+        assert_eq!(
+            PaperSize::smallest_holding(90.0, 140.0, &PaperSize::PHOTO_SIZES, |_| 3.0),
+            Some((PaperSize::Photo4x6, Orientation::Portrait))
+        );
+        // Custom sizes are never suggested, and too much content finds nothing
+        assert_eq!(PaperSize::smallest_holding(10.0, 10.0, &[PaperSize::Custom(50.0, 50.0)], |_| 0.0), None);
+        assert_eq!(PaperSize::smallest_holding(400.0, 600.0, &PaperSize::PHOTO_SIZES, |_| 0.0), None);
+    }
+
+    #[test]
+    fn test_switch_paper_centers_the_content() {
+        let mut layout = Layout::new();
+        layout.page = Page::new(PaperSize::A4);
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1500, 1000);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (30.0, 40.0, 90.0, 60.0);
+        layout.add_image(img);
+        layout.add_rect(PlacedRect::new(120.0, 40.0, 30.0, 20.0, [0, 0, 0, 255]));
+        assert_eq!(layout.content_bounds(), Some((30.0, 40.0, 120.0, 60.0)));
+
+        layout.switch_paper_centered(PaperSize::Photo5x7, Orientation::Landscape, false);
+        assert_eq!((layout.page.width_mm, layout.page.height_mm), (177.8, 127.0));
+        let (x, y, w, h) = layout.content_bounds().unwrap();
+        assert!((x + w / 2.0 - 88.9).abs() < 1e-3 && (y + h / 2.0 - 63.5).abs() < 1e-3);
+        // The arrangement itself is unchanged
+        assert!((layout.rects[0].x_mm - layout.images[0].x_mm - 90.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_paper_size_for_locale() {
+        let cases = [
+            ("en_US.UTF-8", PaperSize::Letter),
+            ("en_CA", PaperSize::Letter),
+            ("fr_CA.UTF-8@euro", PaperSize::Letter),
+            ("en-us", PaperSize::Letter),
+            ("en_GB.UTF-8", PaperSize::A4),
+            ("de_DE@euro", PaperSize::A4),
+            ("ja_JP.eucJP", PaperSize::A4),
+            ("C", PaperSize::A4),
+            ("POSIX", PaperSize::A4),
+            ("", PaperSize::A4),
+        ];
+        for (locale, expected) in cases {
+            assert_eq!(PaperSize::for_locale(locale), expected, "{}", locale);
+        }
+    }
+
+    #[test]
+    fn test_closest_photo_aspect() {
+        assert_eq!(PaperSize::closest_photo_aspect(4000.0, 6000.0), PaperSize::Photo4x6);
+        assert_eq!(PaperSize::closest_photo_aspect(6000.0, 4000.0), PaperSize::Photo4x6);
+        assert_eq!(PaperSize::closest_photo_aspect(3000.0, 3000.0), PaperSize::Photo5x5);
+        assert_eq!(PaperSize::closest_photo_aspect(4000.0, 5000.0), PaperSize::Photo8x10);
+    }
+
+    #[test]
+    fn test_fit_paper_to_image_orients_and_fills() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("wide.jpg"), 6000, 4000);
+        img.width_mm = 300.0;
+        img.height_mm = 200.0;
+        let id = img.id.clone();
+        layout.add_image(img);
+        layout.fit_paper_to_image(&id, false);
+
+        assert_eq!(layout.page.paper_size, PaperSize::Photo4x6);
+        assert_eq!(layout.page.orientation, Orientation::Landscape);
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let placed = layout.get_image(&id).unwrap();
+        // 3:2 on a 3:2 sheet with equal margins is height-limited
+        assert!((placed.height_mm - area_h).abs() < 0.01);
+        assert!(placed.width_mm <= area_w + 0.01);
+        assert!((placed.y_mm - area_y).abs() < 0.01);
+        assert!((placed.x_mm + placed.width_mm / 2.0 - (area_x + area_w / 2.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_oversized_images_are_flagged_and_scaled_to_fit() {
+        let mut layout = Layout::new();
+        layout.page = Page::new(PaperSize::Photo4x6);
+        let mut big = PlacedImage::new(PathBuf::from("big.jpg"), 4000, 2000);
+        big.x_mm = 20.0;
+        big.width_mm = 200.0;
+        big.height_mm = 100.0;
+        let big_id = big.id.clone();
+        let small = PlacedImage::new(PathBuf::from("small.jpg"), 100, 100);
+        layout.add_image(big);
+        layout.add_image(small);
+
+        assert_eq!(layout.oversized_image_ids(), vec![big_id.clone()]);
+
+        layout.scale_image_to_fit(&big_id);
+        assert!(layout.oversized_image_ids().is_empty());
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let img = layout.get_image(&big_id).unwrap();
+        assert!((img.width_mm - area_w).abs() < 0.01);
+        assert!((img.width_mm / img.height_mm - 2.0).abs() < 0.001);
+        assert!(img.x_mm >= area_x && img.x_mm + img.width_mm <= area_x + area_w + 0.01);
+        assert!(img.y_mm >= area_y && img.y_mm + img.height_mm <= area_y + area_h + 0.01);
+
+        // Margins that swallow the page leave the image as it was
+        layout.page.margin_left_mm = layout.page.width_mm;
+        let before = layout.get_image(&big_id).unwrap().bounds();
+        layout.scale_image_to_fit(&big_id);
+        assert_eq!(layout.get_image(&big_id).unwrap().bounds(), before);
+    }
+
+    #[test]
+    fn test_crop_to_source_undoes_rotation_and_flips() {
+        let left_half = Crop { x: 0.0, y: 0.0, width: 0.5, height: 1.0 };
+        assert_eq!(left_half.to_source(0.0, false, false), left_half);
+        // The displayed left half of a 90° turn is the source's bottom half
+        assert_eq!(
+            left_half.to_source(90.0, false, false),
+            Crop { x: 0.0, y: 0.5, width: 1.0, height: 0.5 }
+        );
+        assert_eq!(
+            left_half.to_source(270.0, false, false),
+            Crop { x: 0.0, y: 0.0, width: 1.0, height: 0.5 }
+        );
+        assert_eq!(
+            left_half.to_source(180.0, false, false),
+            Crop { x: 0.5, y: 0.0, width: 0.5, height: 1.0 }
+        );
+        let corner = Crop { x: 0.0, y: 0.0, width: 0.25, height: 0.5 };
+        assert_eq!(
+            corner.to_source(0.0, true, true),
+            Crop { x: 0.75, y: 0.5, width: 0.25, height: 0.5 }
+        );
+
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 400, 200);
+        img.rotation_degrees = 90.0;
+        img.crop = Some(left_half);
+        assert_eq!(img.crop_source_pixels(), Some((0, 100, 400, 100)));
+    }
+
+    #[test]
+    fn test_crop_from_source_round_trips() {
+        let source = Crop { x: 0.1, y: 0.2, width: 0.3, height: 0.4 };
+        for rotation in [0.0, 90.0, 180.0, 270.0] {
+            for (flip_h, flip_v) in [(false, false), (true, false), (false, true), (true, true)] {
+                let back = Crop::from_source(source, rotation, flip_h, flip_v).to_source(rotation, flip_h, flip_v);
+                assert!((back.x - source.x).abs() < 1e-6, "{} {} {}", rotation, flip_h, flip_v);
+                assert!((back.y - source.y).abs() < 1e-6);
+                assert!((back.width - source.width).abs() < 1e-6);
+                assert!((back.height - source.height).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crop_to_source_pixels_keeps_proportions() {
+        let mut img = PlacedImage::new(PathBuf::from("scan.jpg"), 2000, 1000);
+        img.width_mm = 100.0;
+        img.height_mm = 50.0;
+        // Square photo in the middle of a wide scan
+        img.crop_to_source_pixels((500, 0, 1000, 1000));
+        assert_eq!(img.crop, Some(Crop { x: 0.25, y: 0.0, width: 0.5, height: 1.0 }));
+        assert!((img.height_mm - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_display_aspect_ratio_follows_frame_then_rotated_pixels() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 2000);
+        assert!((img.display_aspect_ratio() - 1.5).abs() < 1e-4);
+        // A stretched frame is what is shown
+        img.width_mm = 200.0;
+        assert!((img.display_aspect_ratio() - 200.0 / img.height_mm).abs() < 1e-4);
+
+        // Without a size yet, the turned and cropped pixels decide
+        (img.width_mm, img.height_mm) = (0.0, 0.0);
+        img.rotation_degrees = 90.0;
+        assert!((img.display_aspect_ratio() - 2.0 / 3.0).abs() < 1e-4);
+        img.crop = Some(Crop { x: 0.0, y: 0.0, width: 1.0, height: 0.5 });
+        assert!((img.display_aspect_ratio() - 4.0 / 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_trim_to_source_pixels_keeps_content_in_place() {
+        let mut img = PlacedImage::new(PathBuf::from("logo.png"), 1000, 500);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (10.0, 10.0, 100.0, 50.0);
+        // Logo in the middle of a padded canvas
+        img.trim_to_source_pixels((250, 100, 500, 250));
+        assert_eq!(img.crop, Some(Crop { x: 0.25, y: 0.2, width: 0.5, height: 0.5 }));
+        let (x, y, width, height) = img.bounds();
+        assert!((x - 35.0).abs() < 0.01 && (y - 20.0).abs() < 0.01);
+        assert!((width - 50.0).abs() < 0.01 && (height - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_crop_dragged_moves_resizes_and_stays_inside() {
+        let crop = Crop { x: 0.25, y: 0.25, width: 0.5, height: 0.5 };
+        let frame = (200.0, 100.0);
+
+        // Moving is clamped to the image edges
+        let moved = crop.dragged(None, (500.0, -10.0), frame).unwrap();
+        assert_eq!((moved.x, moved.y), (0.5, 0.15));
+        assert_eq!((moved.width, moved.height), (0.5, 0.5));
+
+        // Corner drag keeps the crop's on-page aspect (100 × 50 mm)
+        let grown = crop.dragged(Some(ResizeHandle::BottomRight), (20.0, 0.0), frame).unwrap();
+        assert!((grown.width * frame.0 / (grown.height * frame.1) - 2.0).abs() < 0.001);
+        assert!((grown.width - 0.6).abs() < 0.001);
+
+        // Growing past the image is refused
+        assert!(crop.dragged(Some(ResizeHandle::BottomRight), (90.0, 0.0), frame).is_none());
+        assert!(Crop::FULL.is_full() && !crop.is_full());
+    }
+
+    #[test]
+    fn test_single_image_layout_centers_and_orients() {
+        let mut img = PlacedImage::new(PathBuf::from("wide.jpg"), 1500, 1000);
+        img.width_mm = 150.0;
+        img.height_mm = 100.0;
+        let layout = single_image_layout(&img, PaperSize::Photo4x6, true);
+
+        assert_eq!(layout.page.orientation, Orientation::Landscape);
+        assert!(layout.page.width_mm > layout.page.height_mm);
+        assert_eq!(layout.images.len(), 1);
+        let placed = &layout.images[0];
+        let cx = placed.x_mm + placed.width_mm / 2.0;
+        let cy = placed.y_mm + placed.height_mm / 2.0;
+        assert!((cx - layout.page.width_mm / 2.0).abs() < 0.01);
+        assert!((cy - layout.page.height_mm / 2.0).abs() < 0.01);
+        assert!(placed.width_mm <= layout.page.width_mm + 0.01);
+    }
+
+    #[test]
+    fn test_diff_identical_layouts_is_empty() {
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000));
+        assert!(layout.diff(&layout.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_image_changes() {
+        let mut saved = Layout::new();
+        saved.add_image(PlacedImage::new(PathBuf::from("moved.jpg"), 1000, 1000));
+        saved.add_image(PlacedImage::new(PathBuf::from("resized.jpg"), 1000, 1000));
+        saved.add_image(PlacedImage::new(PathBuf::from("rotated.jpg"), 1000, 1000));
+        saved.add_image(PlacedImage::new(PathBuf::from("removed.jpg"), 1000, 1000));
+        let ids: Vec<String> = saved.images.iter().map(|i| i.id.clone()).collect();
+
+        let mut current = saved.clone();
+        current.get_image_mut(&ids[0]).unwrap().x_mm += 20.0;
+        current.get_image_mut(&ids[1]).unwrap().width_mm = 40.0;
+        let rotated = current.get_image_mut(&ids[2]).unwrap();
+        rotated.rotation_degrees = 90.0;
+        rotated.flip_horizontal = true;
+        current.remove_image(&ids[3]);
+        current.add_image(PlacedImage::new(PathBuf::from("added.jpg"), 1000, 1000));
+
+        let changes = current.diff(&saved);
+        assert_eq!(changes.len(), 5);
+        assert!(matches!(&changes[0], LayoutChange::ImageMoved { from, to, .. }
+            if from.0 == 50.0 && to.0 == 70.0));
+        assert!(matches!(&changes[1], LayoutChange::ImageResized { to, .. } if to.2 == 40.0));
+        assert!(matches!(&changes[2], LayoutChange::ImageTransformed { details, .. }
+            if details.len() == 2));
+        assert!(matches!(&changes[3], LayoutChange::ImageRemoved { label, .. }
+            if label == "removed.jpg"));
+        assert!(matches!(&changes[4], LayoutChange::ImageAdded { label, .. }
+            if label == "added.jpg"));
+
+        let ghosts: Vec<_> = changes.iter().filter_map(|c| c.previous_bounds()).collect();
+        assert_eq!(ghosts.len(), 3);
+    }
+
+    #[test]
+    fn test_diff_reports_page_changes_and_ignores_tiny_moves() {
+        let mut saved = Layout::new();
+        saved.add_image(PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000));
+        let mut current = saved.clone();
+        current.images[0].x_mm += 0.001;
+        current.page.paper_size = PaperSize::A3;
+        current.page.margin_left_mm = 10.0;
+
+        let changes = current.diff(&saved);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], LayoutChange::PageChanged { setting: "Paper size", .. }));
+        assert_eq!(changes[1].to_string(), "Left margin: 25.4mm → 10.0mm");
+    }
+
+    #[test]
+    fn test_crop_cover_fills_box_without_distortion() {
+        // 3:2 content into a square box: full height, 2/3 of the width, centered
+        let crop = Crop::cover(1.5, 1.0, (0.5, 0.5));
+        assert!((crop.width - 2.0 / 3.0).abs() < 1e-5);
+        assert_eq!(crop.height, 1.0);
+        assert!((crop.center().0 - 0.5).abs() < 1e-5);
+        // Cropped content aspect matches the box
+        assert!((1.5 * crop.width / crop.height - 1.0).abs() < 1e-5);
+
+        // Center near an edge is clamped so the crop stays inside the image
+        let crop = Crop::cover(1.5, 3.0, (0.5, 0.95));
+        assert_eq!(crop.width, 1.0);
+        assert!((crop.height - 0.5).abs() < 1e-5);
+        assert!((crop.y + crop.height - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_crop_pixel_rect_stays_inside_image() {
+        let crop = Crop { x: 0.25, y: 0.5, width: 0.5, height: 0.5 };
+        assert_eq!(crop.pixel_rect(400, 200), (100, 100, 200, 100));
+        let tiny = Crop { x: 0.999, y: 0.0, width: 0.0001, height: 1.0 };
+        let (x, _, w, _) = tiny.pixel_rect(10, 10);
+        assert!(w >= 1 && x + w <= 10);
+    }
+
+    #[test]
+    fn test_content_aspect_follows_rotation() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 2000);
+        assert!((img.content_aspect() - 1.5).abs() < 1e-5);
+        img.rotation_degrees = 90.0;
+        assert!((img.content_aspect() - 2.0 / 3.0).abs() < 1e-5);
+        img.rotation_degrees = 180.0;
+        assert!((img.content_aspect() - 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_rotation_splits_into_quarter_turns_and_tilt() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 3000, 2000);
+        for (degrees, quarters, tilt) in [(15.0, 0, 15.0), (100.0, 1, 10.0), (314.0, 3, 44.0), (350.0, 0, -10.0)] {
+            img.rotation_degrees = degrees;
+            assert_eq!(img.quarter_turns(), quarters, "{}°", degrees);
+            assert!((img.frame_angle_degrees() - tilt).abs() < 1e-4, "{}°", degrees);
+        }
+    }
+
+    fn margins(page: &Page) -> [f32; 4] {
+        [page.margin_top_mm, page.margin_bottom_mm, page.margin_left_mm, page.margin_right_mm]
+    }
+
+    #[test]
+    fn test_paper_change_applies_default_margins_until_user_edits() {
+        let mut page = Page::new(PaperSize::A4);
+        let mut user_edited = false;
+        assert_eq!(margins(&page), [25.4; 4]);
+
+        page.set_paper_size(PaperSize::Photo4x6, user_edited);
+        assert_eq!(margins(&page), [3.0; 4]);
+        assert!(page.margins_reasonable());
+
+        page.set_paper_size(PaperSize::Photo8x10, user_edited);
+        assert_eq!(margins(&page), [5.0; 4]);
+
+        // Manual edit: later paper changes keep the user's margins
+        page.margin_left_mm = 12.0;
+        user_edited = true;
+        page.set_paper_size(PaperSize::Letter, user_edited);
+        assert_eq!(margins(&page), [5.0, 5.0, 12.0, 5.0]);
+        assert_eq!(page.paper_size, PaperSize::Letter);
+    }
+
+    #[test]
+    fn test_set_orientation_turns_margins_with_the_page() {
+        let mut page = Page::new(PaperSize::A4);
+        (page.margin_top_mm, page.margin_right_mm, page.margin_bottom_mm, page.margin_left_mm) = (30.0, 5.0, 10.0, 15.0);
+        assert!(!page.set_orientation(Orientation::Portrait));
+
+        assert!(page.set_orientation(Orientation::Landscape));
+        assert_eq!((page.width_mm, page.height_mm), (297.0, 210.0));
+        assert_eq!(page.orientation, Orientation::Landscape);
+        // Top → left, right → top, bottom → right, left → bottom
+        assert_eq!(
+            (page.margin_top_mm, page.margin_right_mm, page.margin_bottom_mm, page.margin_left_mm),
+            (5.0, 10.0, 15.0, 30.0)
+        );
+
+        // Turning back restores the original page
+        assert!(page.set_orientation(Orientation::Portrait));
+        assert_eq!((page.width_mm, page.height_mm), (210.0, 297.0));
+        assert_eq!(
+            (page.margin_top_mm, page.margin_right_mm, page.margin_bottom_mm, page.margin_left_mm),
+            (30.0, 5.0, 10.0, 15.0)
+        );
+    }
+
+    #[test]
+    fn test_set_orientation_keeps_images_on_the_page() {
+        let mut layout = Layout::new();
+        let mut near = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+        (near.x_mm, near.y_mm, near.width_mm, near.height_mm) = (20.0, 30.0, 50.0, 40.0);
+        let mut low = PlacedImage::new(PathBuf::from("b.jpg"), 100, 100);
+        (low.x_mm, low.y_mm, low.width_mm, low.height_mm) = (100.0, 240.0, 60.0, 40.0);
+        layout.add_image(near);
+        layout.add_image(low);
+
+        assert!(layout.set_orientation(Orientation::Landscape));
+        // Images that still fit stay put relative to the top-left corner
+        assert_eq!((layout.images[0].x_mm, layout.images[0].y_mm), (20.0, 30.0));
+        // Images below the new bottom edge are pulled up onto the page
+        assert_eq!((layout.images[1].x_mm, layout.images[1].y_mm), (100.0, 170.0));
+        assert!(!layout.set_orientation(Orientation::Landscape));
+    }
+
+    #[test]
+    fn test_combined_margins_must_leave_printable_area() {
+        let mut page = Page::new(PaperSize::A4); // 210 × 297
+        assert!(page.set_margin(MarginSide::Left, 100.0).is_ok());
+        // 100 + 100 < 210 on its own, but the pair still fits
+        assert!(page.set_margin(MarginSide::Right, 100.0).is_ok());
+        // 100 + 110 = 210 leaves no printable width
+        assert!(page.set_margin(MarginSide::Right, 110.0).is_err());
+        assert_eq!(page.margin_right_mm, 100.0);
+        let (_, _, width, _) = page.printable_area();
+        assert!(width > 0.0);
+
+        // Either side of the pair is checked against the other
+        assert!(page.set_margin(MarginSide::Left, 150.0).is_err());
+        assert_eq!(page.margin_left_mm, 100.0);
+
+        // Vertical pair uses the page height
+        assert!(page.set_margin(MarginSide::Top, 200.0).is_ok());
+        let err = page.set_margin(MarginSide::Bottom, 97.0).unwrap_err();
+        assert!(err.contains("height"));
+        assert!(page.set_margin(MarginSide::Bottom, 96.0).is_ok());
+    }
+
+    #[test]
+    fn test_try_set_margins_rejects_colliding_pairs() {
+        let mut page = Page::new(PaperSize::A4); // 210 × 297
+        // 150 mm is less than half the height on its own, but not as a pair
+        let err = page.try_set_margins(150.0, 150.0, 10.0, 10.0).unwrap_err();
+        assert_eq!(err, MarginError::VerticalOverlap { total_mm: 300.0, height_mm: 297.0 });
+        assert_eq!(err.sides(), &[MarginSide::Top, MarginSide::Bottom]);
+        assert_eq!(margins(&page), [25.4; 4]);
+
+        let err = page.try_set_margins(10.0, 10.0, 105.0, 105.0).unwrap_err();
+        assert_eq!(err.sides(), &[MarginSide::Left, MarginSide::Right]);
+        assert!(err.to_string().contains("width"));
+
+        assert_eq!(page.try_set_margins(10.0, -2.0, 10.0, 10.0), Err(MarginError::Invalid(MarginSide::Bottom)));
+        assert_eq!(page.try_set_margins(140.0, 140.0, 5.0, 5.0), Ok(()));
+        assert_eq!(margins(&page), [140.0, 140.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_auto_resample_filter_follows_scale() {
+        assert_eq!(ResampleFilter::Auto.for_scale(2.0), ResampleFilter::CatmullRom);
+        assert_eq!(ResampleFilter::Auto.for_scale(1.0), ResampleFilter::Triangle);
+        assert_eq!(ResampleFilter::Auto.for_scale(STRONG_DOWNSCALE), ResampleFilter::Triangle);
+        assert_eq!(ResampleFilter::Auto.for_scale(0.1), ResampleFilter::Lanczos3);
+        // A chosen filter is used at any scale
+        assert_eq!(ResampleFilter::Lanczos3.for_scale(3.0), ResampleFilter::Lanczos3);
+        assert_eq!(ResampleFilter::Triangle.for_scale(0.1), ResampleFilter::Triangle);
+    }
+
+    #[test]
+    fn test_footer_text_and_placement() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let mut footer = Footer::default();
+        assert_eq!(footer.text(1, 1, date), None);
+
+        footer.enabled = true;
+        footer.project_name = " Wedding ".to_string();
+        assert_eq!(footer.text(1, 1, date).as_deref(), Some("Wedding - Page 1 - 2026-03-09"));
+        assert_eq!(footer.text(2, 3, date).as_deref(), Some("Wedding - Page 2 of 3 - 2026-03-09"));
+        footer.page_number = false;
+        footer.date = false;
+        footer.project_name.clear();
+        assert_eq!(footer.text(1, 1, date), None);
+
+        // Borderless: kept off the paper edge, on the bottom of the page
+        let mut page = Page::new(PaperSize::Photo4x6);
+        page.set_borderless(true, false);
+        footer.position = FooterPosition::Right;
+        let (x, y, width, height) = footer.rect(&page, "Page 1");
+        assert!((x + width - (page.width_mm - FOOTER_MIN_EDGE_MM)).abs() < 1e-4);
+        assert!((y + height - (page.height_mm - FOOTER_MIN_EDGE_MM)).abs() < 1e-4);
+        assert_eq!(height, footer.size_mm);
+
+        // Wide margins: inside the printable area, shrunk to its width
+        page.set_borderless(false, false);
+        page.try_set_margins(10.0, 20.0, 40.0, 40.0).unwrap();
+        footer.position = FooterPosition::Center;
+        let long = "X".repeat(60);
+        let (x, y, width, height) = footer.rect(&page, &long);
+        let (area_x, area_y, area_width, area_height) = page.printable_area();
+        assert!((x - area_x).abs() < 1e-4 && (width - area_width).abs() < 1e-3);
+        assert!(height < footer.size_mm);
+        assert!((y + height - (area_y + area_height)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_footer_defaults_off_for_older_files() {
+        let mut value = serde_json::to_value(Page::default()).unwrap();
+        value.as_object_mut().unwrap().remove("footer");
+        let page: Page = serde_json::from_value(value).unwrap();
+        assert_eq!(page.footer, Footer::default());
+        assert!(!page.footer.enabled);
+
+        let partial: Footer = serde_json::from_str(r#"{"enabled": true}"#).unwrap();
+        assert!(partial.enabled && partial.page_number && partial.date);
+    }
+
+    #[test]
+    fn test_aspect_lock_defaults_on_for_older_files() {
+        let mut value = serde_json::to_value(PlacedImage::new(PathBuf::from("a.jpg"), 10, 10)).unwrap();
+        value.as_object_mut().unwrap().remove("lock_aspect");
+        let img: PlacedImage = serde_json::from_value(value).unwrap();
+        assert!(img.lock_aspect);
+    }
+
+    #[test]
+    fn test_reading_order_numbers_uneven_rows() {
+        let place = |x: f32, y: f32| {
+            let mut img = PlacedImage::new(PathBuf::from(format!("{}-{}", x, y)), 10, 10);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, y, 40.0, 30.0);
+            img
+        };
+        // Two hand-placed rows of three, slightly out of line, added shuffled
+        let images = vec![
+            place(100.0, 52.0),
+            place(10.0, 12.0),
+            place(55.0, 50.0),
+            place(100.0, 10.0),
+            place(10.0, 48.0),
+            place(55.0, 14.0),
+        ];
+        assert_eq!(reading_order(&images), vec![1, 5, 3, 4, 2, 0]);
+        assert!(reading_order(&[]).is_empty());
+
+        let mut layout = Layout::new();
+        for img in images {
+            layout.add_image(img);
+        }
+        layout.page.sequence_numbers.order = SequenceOrder::Reading;
+        let numbers: Vec<(f32, f32, usize)> =
+            layout.numbered_images().iter().map(|(img, n)| (img.x_mm, img.y_mm, *n)).collect();
+        assert_eq!(numbers[0], (10.0, 12.0, 1));
+        assert_eq!(numbers[5], (100.0, 52.0, 6));
+
+        layout.page.sequence_numbers.order = SequenceOrder::Stacking;
+        let first = layout.numbered_images()[0];
+        assert_eq!((first.0.x_mm, first.0.y_mm, first.1), (100.0, 52.0, 1));
+    }
+
+    #[test]
+    fn test_arrange_grid_fills_cells_in_reading_order() {
+        let mut layout = Layout::new();
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        for (x, y) in [(120.0, 10.0), (10.0, 10.0), (10.0, 150.0), (120.0, 150.0), (60.0, 250.0)] {
+            let mut img = PlacedImage::new(PathBuf::from(format!("{}-{}", x, y)), 1200, 800);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, y, 60.0, 40.0);
+            layout.add_image(img);
+        }
+        let mut locked = PlacedImage::new(PathBuf::from("locked.jpg"), 100, 100);
+        (locked.x_mm, locked.y_mm, locked.width_mm, locked.height_mm, locked.locked) = (0.0, 0.0, 20.0, 20.0, true);
+        layout.add_image(locked.clone());
+
+        assert_eq!(arrange_grid(&mut layout, 2, 2, 4.0, false), Ok(1));
+        let cell_w = (area_w - 4.0) / 2.0;
+        let cell_h = (area_h - 4.0) / 2.0;
+        // The top-left image takes the first cell, the top-right one the second
+        let first = layout.images.iter().find(|img| img.path == Path::new("10-10")).unwrap();
+        assert!((first.width_mm - cell_w).abs() < 0.01);
+        assert!((first.x_mm - area_x).abs() < 0.01);
+        assert!(first.y_mm >= area_y && first.y_mm + first.height_mm <= area_y + cell_h + 0.01);
+        let second = layout.images.iter().find(|img| img.path == Path::new("120-10")).unwrap();
+        assert!((second.x_mm - (area_x + cell_w + 4.0)).abs() < 0.01);
+        // The fifth image didn't fit and the locked one wasn't touched
+        let fifth = layout.images.iter().find(|img| img.path == Path::new("60-250")).unwrap();
+        assert_eq!((fifth.x_mm, fifth.y_mm), (60.0, 250.0));
+        assert_eq!(layout.images.iter().find(|img| img.locked).unwrap().bounds(), locked.bounds());
+    }
+
+    #[test]
+    fn test_arrange_grid_tiles_a_single_image() {
+        let mut layout = Layout::new();
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let mut img = PlacedImage::new(PathBuf::from("wallet.jpg"), 800, 1200);
+        img.copies = 3;
+        layout.add_image(img);
+
+        assert_eq!(arrange_grid(&mut layout, 3, 2, 2.0, false), Ok(0));
+        assert_eq!(layout.images.len(), 6);
+        let mut ids: Vec<&str> = layout.images.iter().map(|img| img.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 6);
+        for img in &layout.images {
+            assert_eq!(img.copies, 1);
+            assert!(img.x_mm >= area_x - 0.01 && img.x_mm + img.width_mm <= area_x + area_w + 0.01);
+            assert!(img.y_mm >= area_y - 0.01 && img.y_mm + img.height_mm <= area_y + area_h + 0.01);
+        }
+        assert_eq!(reading_order(&layout.images).len(), 6);
+    }
+
+    #[test]
+    fn test_arrange_grid_turns_images_only_when_asked() {
+        let mut layout = Layout::new();
+        let mut portrait = PlacedImage::new(PathBuf::from("portrait.jpg"), 800, 1200);
+        (portrait.width_mm, portrait.height_mm) = (40.0, 60.0);
+        let mut landscape = PlacedImage::new(PathBuf::from("landscape.jpg"), 1200, 800);
+        (landscape.x_mm, landscape.width_mm, landscape.height_mm) = (100.0, 60.0, 40.0);
+        layout.add_image(portrait);
+        layout.add_image(landscape);
+        let mut turned = layout.clone();
+
+        // Two rows of wide cells: only the portrait image gains from a turn
+        assert_eq!(arrange_grid(&mut layout, 2, 1, 2.0, false), Ok(0));
+        assert!(layout.images.iter().all(|img| img.rotation_degrees == 0.0));
+        assert_eq!(arrange_grid(&mut turned, 2, 1, 2.0, true), Ok(0));
+        let rotation = |path: &str| turned.images.iter().find(|img| img.path == Path::new(path)).unwrap().rotation_degrees;
+        assert_eq!(rotation("portrait.jpg"), 90.0);
+        assert_eq!(rotation("landscape.jpg"), 0.0);
+    }
+
+    #[test]
+    fn test_arrange_grid_rejects_impossible_grids() {
+        let mut layout = Layout::new();
+        assert!(arrange_grid(&mut layout, 2, 2, 2.0, false).is_err());
+        layout.add_image(PlacedImage::new(PathBuf::from("a.jpg"), 100, 100));
+        assert!(arrange_grid(&mut layout, 0, 3, 2.0, false).is_err());
+        assert!(arrange_grid(&mut layout, 2, 2, -1.0, false).is_err());
+        assert!(arrange_grid(&mut layout, 40, 1, 2.0, false).is_err());
+        assert_eq!(layout.images.len(), 1);
+    }
+
+    #[test]
+    fn test_layout_templates_snap_images_into_cells() {
+        let templates = LayoutTemplate::built_ins();
+        let template = |name: &str| templates.iter().find(|t| t.name.starts_with(name)).unwrap();
+
+        // One passport photo is tiled into 35 × 45 mm cells, cropped to fill them
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("me.jpg"), 1200, 800));
+        let cells = template("Passport").cells(layout.page.printable_area()).unwrap();
+        assert!(cells.len() > 1);
+        assert_eq!(layout.apply_template(template("Passport"), false), Ok(0));
+        assert_eq!(layout.images.len(), cells.len());
+        for (img, &(x, y, _, _)) in layout.images.iter().zip(&cells) {
+            assert_eq!((img.x_mm, img.y_mm, img.width_mm, img.height_mm), (x, y, 35.0, 45.0));
+            assert!(img.crop.is_some());
+        }
+
+        // Three photos on a 2-up page: the top two take the halves in order
+        let mut layout = Layout::new();
+        for (name, y) in [("c", 200.0), ("a", 10.0), ("b", 100.0)] {
+            let mut img = PlacedImage::new(PathBuf::from(name), 1200, 800);
+            img.y_mm = y;
+            layout.add_image(img);
+        }
+        assert_eq!(layout.apply_template(template("2-up"), false), Ok(1));
+        let a = layout.images.iter().find(|img| img.path == Path::new("a")).unwrap();
+        let b = layout.images.iter().find(|img| img.path == Path::new("b")).unwrap();
+        assert_eq!(a.width_mm, b.width_mm);
+        assert!(a.y_mm + a.height_mm < b.y_mm);
+        let c = layout.images.iter().find(|img| img.path == Path::new("c")).unwrap();
+        assert_eq!(c.y_mm, 200.0);
+
+        // Full bleed turns the page borderless and covers it
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("me.jpg"), 1200, 800));
+        assert_eq!(layout.apply_template(template("1-up"), false), Ok(0));
+        assert!(layout.page.borderless);
+        let img = &layout.images[0];
+        assert_eq!(img.bounds(), (0.0, 0.0, layout.page.width_mm, layout.page.height_mm));
+
+        // Cells that don't fit leave the page as it was
+        let mut layout = Layout::new();
+        layout.add_image(PlacedImage::new(PathBuf::from("me.jpg"), 1200, 800));
+        let mut huge = template("Passport").clone();
+        huge.grid = TemplateGrid::FixedCells { width_mm: 500.0, height_mm: 45.0 };
+        huge.margins = Some((1.0, 1.0, 1.0, 1.0));
+        assert!(layout.apply_template(&huge, false).is_err());
+        assert_eq!(layout.page.margin_top_mm, Layout::new().page.margin_top_mm);
+    }
+
+    /// Check that the images with `ids` lie inside the printable area and
+    /// don't overlap one another
+    fn assert_packed(layout: &Layout, ids: &[String]) {
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let boxes: Vec<(f32, f32, f32, f32)> =
+            ids.iter().map(|id| layout.get_image(id).unwrap().rotated_bounds()).collect();
+        for (i, &(x, y, w, h)) in boxes.iter().enumerate() {
+            assert!(x >= area_x - 0.01 && x + w <= area_x + area_w + 0.01, "{:?} sticks out sideways", (x, w));
+            assert!(y >= area_y - 0.01 && y + h <= area_y + area_h + 0.01, "{:?} sticks out vertically", (y, h));
+            for &(ox, oy, ow, oh) in &boxes[i + 1..] {
+                let apart = x + w <= ox + 0.01 || ox + ow <= x + 0.01 || y + h <= oy + 0.01 || oy + oh <= y + 0.01;
+                assert!(apart, "{:?} overlaps {:?}", (x, y, w, h), (ox, oy, ow, oh));
+            }
+        }
+    }
+
+    fn layout_with_boxes(sizes: &[(f32, f32)]) -> Layout {
+        let mut layout = Layout::new();
+        layout.page.try_set_margins(5.0, 5.0, 5.0, 5.0).unwrap();
+        for (n, &(width, height)) in sizes.iter().enumerate() {
+            let mut img = PlacedImage::new(PathBuf::from(format!("{}.jpg", n)), width as u32 * 10, height as u32 * 10);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (0.0, 0.0, width, height);
+            layout.add_image(img);
+        }
+        layout
+    }
+
+    #[test]
+    fn test_auto_arrange_packs_a_dozen_images_on_shelves() {
+        let mut sizes = vec![(60.0, 40.0); 4];
+        sizes.extend([(40.0, 60.0); 4]);
+        sizes.extend([(100.0, 70.0); 2]);
+        sizes.extend([(90.0, 90.0); 2]);
+        let mut layout = layout_with_boxes(&sizes);
+        let ids: Vec<String> = layout.images.iter().map(|img| img.id.clone()).collect();
+
+        // The tallest images start the first row; one landscape print finds
+        // no room in the 200 × 287 mm area and stays where it was
+        let left_out = layout.auto_arrange(5.0, false);
+        assert_eq!(left_out, vec![ids[3].clone()]);
+        assert_eq!(layout.get_image(&ids[3]).unwrap().bounds(), (0.0, 0.0, 60.0, 40.0));
+        assert_eq!(layout.get_image(&ids[10]).unwrap().bounds(), (5.0, 5.0, 90.0, 90.0));
+        assert_eq!(layout.get_image(&ids[11]).unwrap().bounds(), (100.0, 5.0, 90.0, 90.0));
+        let placed: Vec<String> = ids.iter().filter(|id| !left_out.contains(id)).cloned().collect();
+        assert_packed(&layout, &placed);
+        assert!(layout.images.iter().all(|img| img.rotation_degrees == 0.0));
+
+        // On A3 everything fits
+        let mut layout = layout_with_boxes(&sizes);
+        layout.page.set_paper_size(PaperSize::A3, true);
+        assert!(layout.auto_arrange(5.0, false).is_empty());
+        let ids: Vec<String> = layout.images.iter().map(|img| img.id.clone()).collect();
+        assert_packed(&layout, &ids);
+    }
+
+    #[test]
+    fn test_auto_arrange_turns_images_only_when_that_packs_them() {
+        let sizes = [(60.0, 120.0), (220.0, 60.0), (150.0, 40.0), (300.0, 300.0)];
+        let mut layout = layout_with_boxes(&sizes);
+        let mut locked = PlacedImage::new(PathBuf::from("locked.jpg"), 100, 100);
+        (locked.x_mm, locked.y_mm, locked.width_mm, locked.height_mm, locked.locked) = (170.0, 255.0, 30.0, 30.0, true);
+        layout.add_image(locked.clone());
+        let ids: Vec<String> = layout.images.iter().map(|img| img.id.clone()).collect();
+
+        // The wide image only fits turned and opens the row; the tall one
+        // fits beside it as it is, the strip only on its side, and the huge
+        // one doesn't fit either way
+        let left_out = layout.auto_arrange(5.0, true);
+        assert_eq!(left_out, vec![ids[3].clone()]);
+        let wide = layout.get_image(&ids[1]).unwrap();
+        assert_eq!((wide.width_mm, wide.height_mm, wide.rotation_degrees), (60.0, 220.0, 90.0));
+        let tall = layout.get_image(&ids[0]).unwrap();
+        assert_eq!((tall.width_mm, tall.height_mm, tall.rotation_degrees), (60.0, 120.0, 0.0));
+        let strip = layout.get_image(&ids[2]).unwrap();
+        assert_eq!((strip.width_mm, strip.height_mm, strip.rotation_degrees), (40.0, 150.0, 90.0));
+        assert_packed(&layout, &ids[..3]);
+        assert_eq!(layout.get_image(&ids[4]).unwrap().bounds(), locked.bounds());
+
+        // A portrait print opening a row in the shallow strip left below a
+        // big one lies flat there
+        let mut layout = layout_with_boxes(&[(190.0, 200.0), (40.0, 60.0)]);
+        assert!(layout.auto_arrange(5.0, true).is_empty());
+        let small = &layout.images[1];
+        assert_eq!((small.width_mm, small.height_mm, small.rotation_degrees), (60.0, 40.0, 90.0));
+
+        // Without turning, the wide image is left out as well
+        let mut layout = layout_with_boxes(&sizes);
+        let ids: Vec<String> = layout.images.iter().map(|img| img.id.clone()).collect();
+        assert_eq!(layout.auto_arrange(5.0, false), vec![ids[3].clone(), ids[1].clone()]);
+        assert!(layout.images.iter().all(|img| img.rotation_degrees == 0.0));
+        assert_packed(&layout, &[ids[0].clone(), ids[2].clone()]);
+    }
+
+    #[test]
+    fn test_auto_arrange_packs_around_locked_images() {
+        let mut layout = layout_with_boxes(&[(60.0, 40.0); 8]);
+        let mut locked = PlacedImage::new(PathBuf::from("locked.jpg"), 100, 100);
+        (locked.x_mm, locked.y_mm, locked.width_mm, locked.height_mm, locked.locked) = (20.0, 20.0, 30.0, 30.0, true);
+        layout.add_image(locked.clone());
+        // A banner across the whole width pushes the next row below it
+        let mut banner = PlacedImage::new(PathBuf::from("banner.jpg"), 100, 100);
+        (banner.x_mm, banner.y_mm, banner.width_mm, banner.height_mm, banner.locked) = (5.0, 60.0, 200.0, 20.0, true);
+        layout.add_image(banner.clone());
+        let ids: Vec<String> = layout.images.iter().map(|img| img.id.clone()).collect();
+
+        assert!(layout.auto_arrange(5.0, false).is_empty());
+        assert_eq!(layout.get_image(&ids[8]).unwrap().bounds(), locked.bounds());
+        assert_eq!(layout.get_image(&ids[9]).unwrap().bounds(), banner.bounds());
+        // Packed images miss the locked ones, and leave a gutter to them
+        assert_packed(&layout, &ids);
+        for img in &layout.images[..8] {
+            let (x, y, w, h) = img.rotated_bounds();
+            for (ox, oy, ow, oh) in [locked.bounds(), banner.bounds()] {
+                let apart = x + w + 5.0 <= ox + 0.01 || ox + ow + 5.0 <= x + 0.01 || y + h + 5.0 <= oy + 0.01 || oy + oh + 5.0 <= y + 0.01;
+                assert!(apart, "{:?} is within a gutter of {:?}", (x, y, w, h), (ox, oy, ow, oh));
+            }
+        }
+        // The first row starts right of the small locked image
+        assert_eq!((layout.images[0].x_mm, layout.images[0].y_mm), (55.0, 5.0));
+        assert!(layout.images[..8].iter().any(|img| img.y_mm >= 85.0 - 0.01));
+    }
+
+    #[test]
+    fn test_contact_sheet_places_readable_images_with_captions() {
+        let page = Page::new(PaperSize::A4);
+        let (area_x, area_y, area_w, area_h) = page.printable_area();
+        let paths: Vec<PathBuf> = (0..60).map(|i| PathBuf::from(format!("/shoot/IMG_{:04}.jpg", i))).collect();
+        let sheet = contact_sheet_with(&paths, &page, 5, false, |path| {
+            if path.ends_with("IMG_0003.jpg") {
+                Err("not an image".to_string())
+            } else {
+                Ok((1200, 800))
+            }
+        });
+
+        assert!(sheet.layout.page.captions);
+        assert_eq!(sheet.skipped.len(), 1);
+        let placed = sheet.layout.images.len();
+        assert!(placed <= CONTACT_SHEET_MAX_IMAGES);
+        assert_eq!(placed + sheet.left_out + sheet.skipped.len(), paths.len());
+        // In file order, left to right, skipping the unreadable file
+        assert_eq!(sheet.layout.images[3].path, PathBuf::from("/shoot/IMG_0004.jpg"));
+        assert!(sheet.layout.images[0].x_mm < sheet.layout.images[1].x_mm);
+        assert!(sheet.layout.images[5].y_mm > sheet.layout.images[0].y_mm);
+        for img in &sheet.layout.images {
+            let (x, y, width, height) = img.caption_rect(img.display_label());
+            assert!(img.x_mm >= area_x - 0.01 && img.x_mm + img.width_mm <= area_x + area_w + 0.01);
+            assert!(y >= img.y_mm + img.height_mm && y + height <= area_y + area_h + 0.01);
+            assert!(x >= img.x_mm - 0.01 && width <= img.width_mm + 0.01);
+        }
+    }
+
+    #[test]
+    fn test_caption_narrows_to_the_image() {
+        let mut img = PlacedImage::new(PathBuf::from("a-very-long-file-name-from-the-camera.jpg"), 100, 100);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (10.0, 10.0, 20.0, 20.0);
+        let (x, y, width, height) = img.caption_rect(img.display_label());
+        assert_eq!((x, width), (10.0, 20.0));
+        assert!(height < CAPTION_HEIGHT_MM);
+        assert_eq!(y, 30.0 + CAPTION_GAP_MM);
+
+        let (x, _, width, height) = img.caption_rect("A");
+        assert_eq!(height, CAPTION_HEIGHT_MM);
+        assert!((x + width / 2.0 - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_expand_copies_fills_free_space_row_by_row() {
+        let mut layout = Layout::new();
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 800, 500);
+        img.id = "a".to_string();
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (area_x, area_y, 70.0, 50.0);
+        img.copies = 3;
+        layout.add_image(img);
+
+        let (expanded, left_out) = layout.expand_copies();
+        assert_eq!(left_out, 0);
+        let spots: Vec<(&str, f32, f32)> = expanded.images.iter().map(|i| (i.id.as_str(), i.x_mm, i.y_mm)).collect();
+        assert_eq!(
+            spots,
+            vec![("a", area_x, area_y), ("a-copy1", area_x + 72.0, area_y), ("a-copy2", area_x, area_y + 52.0)]
+        );
+        assert!(expanded.images.iter().all(|i| i.copies == 1 && i.width_mm == 70.0));
+        assert!(expanded.images.iter().enumerate().all(|(i, img)| img.z_index == i));
+        // The layout itself is untouched
+        assert_eq!(layout.images.len(), 1);
+
+        // Copies that don't fit are counted, not stacked
+        layout.images[0].copies = MAX_COPIES;
+        let per_row = ((area_w + COPY_GAP_MM) / 72.0).floor() as usize;
+        let rows = ((area_h + COPY_GAP_MM) / 52.0).floor() as usize;
+        let (expanded, left_out) = layout.expand_copies();
+        assert_eq!(expanded.images.len(), per_row * rows);
+        assert_eq!(left_out, MAX_COPIES as usize - per_row * rows);
+
+        // Projects saved before copies existed print one of each
+        let mut json = serde_json::to_value(&layout.images[0]).unwrap();
+        json.as_object_mut().unwrap().remove("copies");
+        assert_eq!(serde_json::from_value::<PlacedImage>(json).unwrap().copies, 1);
+    }
+
+    #[test]
+    fn test_paginate_exact_fit_and_overflow() {
+        let area = (10.0, 20.0, 100.0, 100.0);
+        // Two 49 mm boxes and a 2 mm gutter fill a row exactly
+        let pages = paginate(&[(49.0, 49.0); 5], area, 2.0);
+        assert_eq!(
+            pages,
+            vec![
+                vec![(0, 10.0, 20.0), (1, 61.0, 20.0), (2, 10.0, 71.0), (3, 61.0, 71.0)],
+                vec![(4, 10.0, 20.0)],
+            ]
+        );
+        // Boxes the size of the area take a page each
+        assert_eq!(paginate(&[(100.0, 100.0); 3], area, 2.0).len(), 3);
+        assert!(paginate(&[], area, 2.0).is_empty());
+    }
+
+    #[test]
+    fn test_paginate_gives_oversized_boxes_their_own_page() {
+        let area = (0.0, 0.0, 100.0, 100.0);
+        let pages = paginate(&[(40.0, 40.0), (150.0, 30.0), (40.0, 40.0), (40.0, 40.0)], area, 0.0);
+        assert_eq!(
+            pages,
+            vec![vec![(0, 0.0, 0.0)], vec![(1, 0.0, 0.0)], vec![(2, 0.0, 0.0), (3, 40.0, 0.0)]]
+        );
+    }
+
+    #[test]
+    fn test_copies_that_dont_fit_go_on_more_sheets() {
+        let mut layout = Layout::new();
+        let (area_x, area_y, area_w, area_h) = layout.page.printable_area();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 800, 500);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (area_x, area_y, 80.0, 50.0);
+        img.copies = MAX_COPIES;
+        layout.add_image(img);
+        assert_eq!(layout.sheets().len(), 1 + layout.overflow_pages().len());
+
+        let per_page = (((area_w + COPY_GAP_MM) / 82.0).floor() * ((area_h + COPY_GAP_MM) / 52.0).floor()) as usize;
+        let overflow = layout.overflow_pages();
+        assert_eq!(overflow.len(), (MAX_COPIES as usize - per_page).div_ceil(per_page));
+        let printed: usize = layout.sheets().iter().map(|sheet| sheet.images.len()).sum();
+        assert_eq!(printed, MAX_COPIES as usize);
+        assert!(overflow.iter().all(|sheet| sheet.page.printable_area() == layout.page.printable_area()));
+        assert_eq!((overflow[0].images[0].x_mm, overflow[0].images[0].y_mm), (area_x, area_y));
+
+        layout.images[0].copies = 2;
+        assert!(layout.overflow_pages().is_empty());
+    }
+
+    #[test]
+    fn test_registration_mark_geometry() {
+        let (width, height) = PaperSize::A4.to_dimensions();
+        assert_eq!((width, height), (210.0, 297.0));
+        assert!(RegistrationMarks::None.shapes(width, height).is_empty());
+        assert!(RegistrationMarks::None.reserved_regions(width, height).is_empty());
+
+        // Square top left, brackets top right and bottom left, all 10 mm in
+        assert_eq!(
+            RegistrationMarks::Silhouette.shapes(width, height),
+            vec![
+                (10.0, 10.0, 5.0, 5.0),
+                (180.0, 10.0, 20.0, 0.5),
+                (199.5, 10.0, 0.5, 20.0),
+                (10.0, 286.5, 20.0, 0.5),
+                (10.0, 267.0, 0.5, 20.0),
+            ]
+        );
+        assert_eq!(
+            RegistrationMarks::Silhouette.reserved_regions(width, height),
+            vec![(0.0, 0.0, 17.0, 17.0), (178.0, 0.0, 32.0, 32.0), (0.0, 265.0, 32.0, 32.0)]
+        );
+        assert_eq!(
+            RegistrationMarks::Cricut.shapes(width, height),
+            vec![(6.0, 6.0, 6.0, 6.0), (198.0, 6.0, 6.0, 6.0), (6.0, 285.0, 6.0, 6.0)]
+        );
+        // Crop marks go in all four corners
+        let crop = RegistrationMarks::CropMarks.shapes(width, height);
+        assert_eq!(crop.len(), 8);
+        assert_eq!(crop[6], (195.0, 291.75, 10.0, 0.25));
+        assert_eq!(crop[7], (204.75, 282.0, 0.25, 10.0));
+    }
+
+    #[test]
+    fn test_images_over_marks() {
+        let mut layout = Layout::new();
+        let mut corner = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+        (corner.x_mm, corner.y_mm, corner.width_mm, corner.height_mm) = (15.0, 15.0, 50.0, 50.0);
+        let corner_id = corner.id.clone();
+        let mut middle = PlacedImage::new(PathBuf::from("b.jpg"), 100, 100);
+        (middle.x_mm, middle.y_mm, middle.width_mm, middle.height_mm) = (80.0, 120.0, 50.0, 50.0);
+        layout.add_image(corner);
+        layout.add_image(middle);
+        assert!(layout.images_over_marks().is_empty());
+
+        layout.page.registration_marks = RegistrationMarks::Silhouette;
+        assert_eq!(layout.images_over_marks(), vec![corner_id]);
+    }
+
+    #[test]
+    fn test_overlapping_images() {
+        let mut layout = Layout::new();
+        let mut ids = Vec::new();
+        for (x, y) in [(10.0, 10.0), (40.0, 40.0), (100.0, 10.0), (150.0, 10.0)] {
+            let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, y, 50.0, 50.0);
+            ids.push(img.id.clone());
+            layout.add_image(img);
+        }
+        // Edges that only touch don't count
+        assert_eq!(layout.overlapping_images(), vec![ids[0].clone(), ids[1].clone()]);
+    }
+
+    #[test]
+    fn test_sequence_badge_stays_inside_small_images() {
+        let mut numbers = SequenceNumbers { size_mm: 6.0, ..Default::default() };
+        assert_eq!(numbers.badge(10.0, 20.0, 50.0, 40.0), (14.5, 24.5, 3.0));
+        numbers.corner = SequenceCorner::BottomRight;
+        assert_eq!(numbers.badge(10.0, 20.0, 50.0, 40.0), (55.5, 55.5, 3.0));
+        // A 4 mm image gets a 4 mm circle filling it
+        assert_eq!(numbers.badge(0.0, 0.0, 4.0, 10.0), (2.0, 8.0, 2.0));
+
+        let (x, _, width, height) = SequenceNumbers::label_rect("7", (10.0, 10.0, 3.0));
+        assert_eq!(height, 3.0);
+        assert!((x + width / 2.0 - 10.0).abs() < 1e-5);
+        let (_, _, width, _) = SequenceNumbers::label_rect("123", (10.0, 10.0, 3.0));
+        assert!(width <= 4.5 + 1e-5);
+    }
+
+    #[test]
+    fn test_auto_rotate_only_when_it_clearly_helps() {
+        // Portrait 2:3 in a landscape 3:2 cell: 44% filled upright, 100% turned
+        assert!((fit_fill_ratio(2.0 / 3.0, 1.5) - 4.0 / 9.0).abs() < 1e-5);
+        assert!(should_rotate_to_fit(2.0 / 3.0, 1.5));
+        // Already matching, square or nearly square: leave it
+        assert!(!should_rotate_to_fit(1.5, 1.5));
+        assert!(!should_rotate_to_fit(1.0, 1.5));
+        assert!(!should_rotate_to_fit(1.05, 0.98));
+        assert!(!should_rotate_to_fit(0.0, 1.5));
+
+        let mut img = PlacedImage::new(PathBuf::from("portrait.jpg"), 2000, 3000);
+        let cell = (10.0, 20.0, 150.0, 100.0);
+        assert!(img.fit_into_cell(cell, true));
+        assert_eq!(img.rotation_degrees, 90.0);
+        assert!((img.width_mm - 150.0).abs() < 1e-3 && (img.height_mm - 100.0).abs() < 1e-3);
+        assert!((img.x_mm - 10.0).abs() < 1e-3 && (img.y_mm - 20.0).abs() < 1e-3);
+
+        let mut upright = PlacedImage::new(PathBuf::from("portrait.jpg"), 2000, 3000);
+        assert!(!upright.fit_into_cell(cell, false));
+        assert_eq!(upright.rotation_degrees, 0.0);
+        assert!((upright.height_mm - 100.0).abs() < 1e-3);
+        assert!((upright.x_mm - (10.0 + (150.0 - upright.width_mm) / 2.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_snap_angle_wraps_around_a_full_turn() {
+        assert_eq!(snap_angle(359.0, 15.0), 0.0);
+        assert_eq!(snap_angle(352.0, 15.0), 345.0);
+        assert_eq!(snap_angle(7.4, 15.0), 0.0);
+        assert_eq!(snap_angle(7.6, 15.0), 15.0);
+        assert_eq!(snap_angle(-10.0, 15.0), 345.0);
+        assert_eq!(snap_angle(-1.0, 45.0), 0.0);
+        assert_eq!(snap_angle(460.0, 90.0), 90.0);
+        assert_eq!(snap_angle(-1e-7, 0.0), 0.0);
+        assert!((snap_angle(-12.5, 0.0) - 347.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_snap_to_grid_rounds_to_nearest_line() {
+        assert_eq!(snap_to_grid(14.9, 10.0), 10.0);
+        assert_eq!(snap_to_grid(15.1, 10.0), 20.0);
+        assert_eq!(snap_to_grid(-4.0, 5.0), -5.0);
+        assert_eq!(snap_to_grid(12.5, 0.0), 12.5);
+    }
+
+    #[test]
+    fn test_negative_margin_rejected() {
+        let mut page = Page::new(PaperSize::A4);
+        assert!(page.set_margin(MarginSide::Top, -1.0).is_err());
+        assert!(page.set_margin(MarginSide::Top, f32::NAN).is_err());
+        assert_eq!(page.margin(MarginSide::Top), 25.4);
+    }
+
+    #[test]
+    fn test_paper_change_keeps_landscape_orientation() {
+        let mut page = Page::new(PaperSize::A4);
+        page.orientation = Orientation::Landscape;
+        page.set_paper_size(PaperSize::Photo4x6, false);
+        assert!(page.width_mm > page.height_mm);
+    }
+
+    #[test]
+    fn test_borderless_toggle_interacts_with_defaults_and_edits() {
+        let mut page = Page::new(PaperSize::Photo4x6);
+
+        page.set_borderless(true, false);
+        assert_eq!(margins(&page), [0.0; 4]);
+        // Changing paper while borderless keeps zero margins
+        page.set_paper_size(PaperSize::Photo5x7, false);
+        assert_eq!(margins(&page), [0.0; 4]);
+        // Turning borderless off restores the paper default
+        page.set_borderless(false, false);
+        assert_eq!(margins(&page), [3.0; 4]);
+
+        // Borderless always clears margins, even after manual edits...
+        page.margin_top_mm = 8.0;
+        page.set_borderless(true, true);
+        assert_eq!(margins(&page), [0.0; 4]);
+        // ...but switching it off afterwards leaves the user's margins alone
+        page.margin_top_mm = 6.0;
+        page.set_borderless(false, true);
+        assert_eq!(margins(&page), [6.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_keep_images_in_printable_area() {
+        let mut layout = Layout::new();
+        layout.page = Page::new(PaperSize::A4);
+        for side in [MarginSide::Top, MarginSide::Bottom, MarginSide::Left, MarginSide::Right] {
+            layout.page.set_margin(side, 25.4).unwrap();
+        }
+        let place = |x: f32, y: f32, w: f32, h: f32| {
+            let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, y, w, h);
+            img
+        };
+        layout.add_image(place(0.0, 0.0, 50.0, 50.0));
+        layout.add_image(place(0.0, 100.0, 200.0, 100.0));
+        layout.add_image(place(60.0, 60.0, 50.0, 50.0));
+        let mut locked = place(0.0, 0.0, 50.0, 50.0);
+        locked.locked = true;
+        layout.add_image(locked);
+
+        assert_eq!(layout.keep_images_in_printable_area(), 2);
+        assert_eq!(layout.images[0].bounds(), (25.4, 25.4, 50.0, 50.0));
+        let (x, _, w, h) = layout.images[1].bounds();
+        assert!((x - 25.4).abs() < 1e-3 && (w - 159.2).abs() < 1e-3 && (h - 79.6).abs() < 1e-3);
+        assert_eq!(layout.images[2].bounds(), (60.0, 60.0, 50.0, 50.0));
+        assert_eq!(layout.images[3].bounds(), (0.0, 0.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn test_fit_and_center_on_the_printable_area() {
+        let mut layout = Layout::new();
+        layout.page = Page::new(PaperSize::A4);
+        layout.page.try_set_margins(10.0, 10.0, 10.0, 10.0).unwrap();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1500, 1000);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (5.0, 5.0, 60.0, 40.0);
+        let id = img.id.clone();
+        layout.add_image(img);
+
+        // Letterboxed: full width, centered top to bottom
+        assert!(layout.fit_image_to_page(&id));
+        let (x, y, w, h) = layout.get_image(&id).unwrap().bounds();
+        assert!((x - 10.0).abs() < 1e-3 && (w - 190.0).abs() < 1e-3);
+        assert!((h - 190.0 / 1.5).abs() < 1e-3);
+        assert!((y - (10.0 + (277.0 - h) / 2.0)).abs() < 1e-3);
+        assert!(!layout.fit_image_to_page(&id));
+
+        let image = layout.get_image_mut(&id).unwrap();
+        (image.x_mm, image.y_mm, image.width_mm, image.height_mm) = (0.0, 0.0, 50.0, 30.0);
+        assert!(layout.center_image_on_page(&id));
+        assert_eq!(layout.get_image(&id).unwrap().bounds(), (80.0, 133.5, 50.0, 30.0));
+
+        layout.get_image_mut(&id).unwrap().locked = true;
+        assert!(!layout.fit_image_to_page(&id));
+    }
+
+    #[test]
+    fn test_document_margins_are_unreasonable_on_small_photo_paper() {
+        let mut page = Page::new(PaperSize::Photo4x6);
+        page.margin_top_mm = 25.4;
+        page.margin_bottom_mm = 25.4;
+        page.margin_left_mm = 25.4;
+        page.margin_right_mm = 25.4;
+        assert!(!page.margins_reasonable());
+        assert!(Page::new(PaperSize::A4).margins_reasonable());
+    }
+
+    #[test]
+    fn test_display_label_falls_back_to_filename() {
+        let mut img = PlacedImage::new(PathBuf::from("/photos/IMG_20240312_143022.jpg"), 4000, 3000);
+        assert_eq!(img.display_label(), "IMG_20240312_143022.jpg");
+
+        img.display_name = Some("Beach".to_string());
+        assert_eq!(img.display_label(), "Beach");
+
+        img.display_name = Some("   ".to_string());
+        assert_eq!(img.display_label(), "IMG_20240312_143022.jpg");
+    }
+
+    #[test]
+    fn test_hit_testing_follows_frame_tilt() {
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 1000, 1000);
+        img.x_mm = 0.0;
+        img.y_mm = 0.0;
+        img.width_mm = 100.0;
+        img.height_mm = 20.0;
+
+        // Quarter turns swap the frame instead of tilting it
+        img.rotation_degrees = 90.0;
+        assert_eq!(img.frame_angle_degrees(), 0.0);
+        assert!(img.contains_point(95.0, 2.0));
+
+        // Tilted 45° about its center (50, 10)
+        img.rotation_degrees = 45.0;
+        assert!((img.frame_angle_degrees() - 45.0).abs() < 1e-4);
+        assert!(img.contains_point(50.0, 10.0));
+        assert!(!img.contains_point(95.0, 2.0), "corner of the untilted box is now empty");
+        assert!(img.contains_point(80.0, 40.0), "along the tilted long axis");
+
+        let (tx, ty) = img.corners()[0];
+        assert!((tx - 21.716).abs() < 1e-2 && (ty + 32.426).abs() < 1e-2);
+
+        let (x, y, w, h) = img.rotated_bounds();
+        let expected = 120.0 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((w - expected).abs() < 1e-3 && (h - expected).abs() < 1e-3);
+        assert!((x + w / 2.0 - 50.0).abs() < 1e-3 && (y + h / 2.0 - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_rects_share_the_stack_with_images() {
+        let mut layout = Layout::new();
+        let a = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+        let b = PlacedImage::new(PathBuf::from("b.jpg"), 100, 100);
+        let (a_id, b_id) = (a.id.clone(), b.id.clone());
+        layout.add_image(a);
+        let backdrop = PlacedRect::new(40.0, 40.0, 50.0, 50.0, [200, 30, 30, 255]);
+        let rect_id = backdrop.id.clone();
+        layout.add_rect(backdrop);
+        layout.add_image(b);
+
+        // The rect was added above "a" and shares z 1 with "b", which draws over it
+        let order: Vec<&str> = layout.elements().iter().map(|e| e.id()).collect();
+        assert_eq!(order, vec![a_id.as_str(), rect_id.as_str(), b_id.as_str()]);
+        assert!(matches!(layout.find_element_at_point(60.0, 60.0), Some(LayoutElement::Image(img)) if img.id == b_id));
+
+        layout.remove_image(&b_id);
+        assert!(matches!(layout.find_element_at_point(60.0, 60.0), Some(LayoutElement::Rect(r)) if r.id == rect_id));
+        layout.remove_image(&a_id);
+        assert_eq!(layout.get_rect(&rect_id).unwrap().z_index, 0);
+
+        layout.selected_image_id = Some(rect_id.clone());
+        assert!(layout.selected_rect().is_some());
+        assert!(layout.remove_rect(&rect_id).is_some());
+        assert_eq!(layout.selected_image_id, None);
+    }
+
+    #[test]
+    fn test_rect_rounded_corners_and_hex_colors() {
+        let mut rect = PlacedRect::new(0.0, 0.0, 40.0, 20.0, [0, 0, 0, 255]);
+        rect.corner_radius_mm = 50.0;
+        assert_eq!(rect.effective_corner_radius(), 10.0);
+        assert!(rect.contains_point(0.5, 0.5));
+        assert!(!rect.covers_point(0.5, 0.5), "cut off by the rounded corner");
+        assert!(rect.covers_point(10.0, 0.5));
+        assert!(rect.covers_point(20.0, 10.0));
+
+        assert_eq!(parse_hex_color("#1A2b3C"), Some([0x1A, 0x2B, 0x3C, 255]));
+        assert_eq!(parse_hex_color("1a2b3c80"), Some([0x1A, 0x2B, 0x3C, 0x80]));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+        assert_eq!(format_hex_color([255, 0, 16, 255]), "#FF0010");
+        assert_eq!(format_hex_color([255, 0, 16, 128]), "#FF001080");
     }
 }
@@ -0,0 +1,224 @@
+// resize.rs - Resize math for drag handles
+// Keeps the anchor edge fixed and enforces a minimum size on both dimensions
+
+/// Which resize handle is being dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResizeHandle {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl ResizeHandle {
+    #[allow(dead_code)]
+    pub const ALL: [ResizeHandle; 8] = [
+        ResizeHandle::TopLeft,
+        ResizeHandle::TopRight,
+        ResizeHandle::BottomLeft,
+        ResizeHandle::BottomRight,
+        ResizeHandle::Top,
+        ResizeHandle::Bottom,
+        ResizeHandle::Left,
+        ResizeHandle::Right,
+    ];
+
+    /// Horizontal direction the handle moves: -1 = left edge, 1 = right edge, 0 = neither
+    fn horizontal(&self) -> i8 {
+        match self {
+            ResizeHandle::TopLeft | ResizeHandle::BottomLeft | ResizeHandle::Left => -1,
+            ResizeHandle::TopRight | ResizeHandle::BottomRight | ResizeHandle::Right => 1,
+            ResizeHandle::Top | ResizeHandle::Bottom => 0,
+        }
+    }
+
+    /// Vertical direction the handle moves: -1 = top edge, 1 = bottom edge, 0 = neither
+    fn vertical(&self) -> i8 {
+        match self {
+            ResizeHandle::TopLeft | ResizeHandle::TopRight | ResizeHandle::Top => -1,
+            ResizeHandle::BottomLeft | ResizeHandle::BottomRight | ResizeHandle::Bottom => 1,
+            ResizeHandle::Left | ResizeHandle::Right => 0,
+        }
+    }
+}
+
+/// Axis-aligned rectangle in millimeters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+}
+
+/// Results are rounded to 0.01mm so repeated resizes don't drift
+const STEPS_PER_MM: f32 = 100.0;
+
+fn round_mm(value: f32) -> f32 {
+    (value * STEPS_PER_MM).round() / STEPS_PER_MM
+}
+
+/// Compute the rect after dragging `handle` by `delta` (mm) from `init`.
+///
+/// The edge or corner opposite the handle stays exactly where it was, both
+/// dimensions are at least `min_size`, and with `aspect_lock` the initial
+/// aspect ratio is kept (the minimum is raised so the derived side also
+/// respects it). Corner and left/right handles drive the width; top/bottom
+/// handles drive the height.
+pub fn compute(
+    handle: ResizeHandle,
+    init: Rect,
+    delta: (f32, f32),
+    aspect_lock: bool,
+    min_size: f32,
+) -> Rect {
+    let (dx, dy) = delta;
+    let h_dir = handle.horizontal();
+    let v_dir = handle.vertical();
+
+    let raw_w = init.width + dx * h_dir as f32;
+    let raw_h = init.height + dy * v_dir as f32;
+
+    let (width, height) = if aspect_lock && init.width > 0.0 && init.height > 0.0 {
+        let aspect = init.width / init.height;
+        if h_dir != 0 {
+            let min_w = min_size.max(min_size * aspect);
+            let w = round_mm(raw_w.max(min_w));
+            (w, round_mm(w / aspect).max(min_size))
+        } else {
+            let min_h = min_size.max(min_size / aspect);
+            let h = round_mm(raw_h.max(min_h));
+            (round_mm(h * aspect).max(min_size), h)
+        }
+    } else {
+        (
+            round_mm(raw_w.max(min_size)),
+            round_mm(raw_h.max(min_size)),
+        )
+    };
+
+    // Anchor the opposite edges; left/top handles move the origin
+    let x = if h_dir < 0 {
+        round_mm(init.x + init.width - width)
+    } else {
+        init.x
+    };
+    let y = if v_dir < 0 {
+        round_mm(init.y + init.height - height)
+    } else {
+        init.y
+    };
+
+    Rect::new(x, y, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: f32 = 10.0;
+    const EPS: f32 = 0.02;
+
+    fn sample_rects() -> Vec<Rect> {
+        vec![
+            Rect::new(50.0, 50.0, 100.0, 75.0),
+            Rect::new(12.3, 45.6, 200.0, 20.0), // very wide
+            Rect::new(0.0, 0.0, 15.0, 180.0),   // very tall
+            Rect::new(33.33, 66.67, 10.0, 10.0),
+        ]
+    }
+
+    fn sample_deltas() -> Vec<(f32, f32)> {
+        vec![
+            (0.0, 0.0),
+            (25.0, 10.0),
+            (-25.0, -10.0),
+            (-500.0, -500.0),
+            (500.0, -500.0),
+            (-500.0, 500.0),
+            (0.333, -0.777),
+        ]
+    }
+
+    #[test]
+    fn test_invariants_for_all_handles() {
+        for init in sample_rects() {
+            for handle in ResizeHandle::ALL {
+                for delta in sample_deltas() {
+                    for aspect_lock in [false, true] {
+                        let r = compute(handle, init, delta, aspect_lock, MIN);
+                        let ctx = format!("{:?} {:?} {:?} lock={}", handle, init, delta, aspect_lock);
+
+                        assert!(r.width >= MIN - 1e-4, "width below min: {} {:?}", ctx, r);
+                        assert!(r.height >= MIN - 1e-4, "height below min: {} {:?}", ctx, r);
+
+                        match handle.horizontal() {
+                            -1 => assert!(
+                                (r.x + r.width - (init.x + init.width)).abs() < EPS,
+                                "right edge moved: {} {:?}", ctx, r
+                            ),
+                            _ => assert_eq!(r.x, init.x, "left edge moved: {}", ctx),
+                        }
+                        match handle.vertical() {
+                            -1 => assert!(
+                                (r.y + r.height - (init.y + init.height)).abs() < EPS,
+                                "bottom edge moved: {} {:?}", ctx, r
+                            ),
+                            _ => assert_eq!(r.y, init.y, "top edge moved: {}", ctx),
+                        }
+
+                        if aspect_lock {
+                            let aspect = init.width / init.height;
+                            assert!(
+                                (r.width / r.height - aspect).abs() / aspect < 0.01,
+                                "aspect changed: {} {:?}", ctx, r
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_image_locked_keeps_height_above_min() {
+        // 200×20 shrunk hard: width can't go below 100 or height would drop under 10
+        let init = Rect::new(0.0, 0.0, 200.0, 20.0);
+        let r = compute(ResizeHandle::BottomRight, init, (-195.0, 0.0), true, MIN);
+        assert!((r.width - 100.0).abs() < EPS);
+        assert!((r.height - 10.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_repeated_resizes_do_not_drift() {
+        let mut rect = Rect::new(12.345, 67.891, 100.0, 66.667);
+        let right = rect.x + rect.width;
+        for i in 0..200 {
+            let d = if i % 2 == 0 { 0.37 } else { -0.37 };
+            rect = compute(ResizeHandle::Left, rect, (d, 0.0), false, MIN);
+        }
+        assert!((rect.x + rect.width - right).abs() < EPS);
+    }
+
+    #[test]
+    fn test_edge_handles_unlocked_keep_other_dimension() {
+        let init = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let r = compute(ResizeHandle::Right, init, (20.0, 30.0), false, MIN);
+        assert!((r.height - 50.0).abs() < EPS);
+        assert!((r.width - 120.0).abs() < EPS);
+        let r = compute(ResizeHandle::Top, init, (20.0, -30.0), false, MIN);
+        assert!((r.width - 100.0).abs() < EPS);
+        assert!((r.height - 80.0).abs() < EPS);
+        assert!((r.y - -20.0).abs() < EPS);
+    }
+}
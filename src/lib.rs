@@ -1,6 +1,17 @@
 // Module organization for Print Layout application
 
+pub mod bitmap_font;
 pub mod canvas_widget;
+pub mod cli;
 pub mod config;
+pub mod document;
+pub mod history;
+pub mod interchange;
+pub mod journal;
 pub mod layout;
+pub mod manifest;
 pub mod printing;
+pub mod quick_open;
+pub mod shortcuts;
+pub mod trim;
+pub mod usage;
@@ -1,43 +1,129 @@
 use iced::widget::{
     button, canvas, column, container, pick_list, row, scrollable, text, text_input,
     horizontal_rule, vertical_rule, checkbox, Space, image as iced_image, center,
-    progress_bar, opaque, mouse_area,
+    progress_bar, opaque, mouse_area, tooltip, slider,
 };
-use iced::{Alignment, Color, Element, Length, Padding, Size, Task, Theme};
+use iced::{Alignment, Color, Element, Length, Padding, Size, Subscription, Task, Theme};
 use ::image::GenericImageView;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::Arc;
 
+mod bitmap_font;
 mod canvas_widget;
+mod cli;
 mod config;
+mod document;
+mod history;
+mod interchange;
+mod journal;
 mod layout;
+mod manifest;
 mod printing;
+mod quick_open;
+mod shortcuts;
+mod trim;
+mod usage;
 
-use canvas_widget::{CanvasMessage, LayoutCanvas, ResizeHandle};
-use config::{ConfigManager, ProjectLayout, UserPreferences};
-use layout::{Layout, PaperSize, PaperType, PlacedImage, PrintQuality, Orientation as LayoutOrientation};
-use printing::{discover_printers, execute_print_job, get_printer_capabilities, PrintJob, PrinterInfo, PrinterCapabilities};
+use canvas_widget::{fit_zoom, prepare_canvas_source, CanvasMessage, CanvasSource, CropEdit, LayoutCanvas, ResizeHandle, ZoomFit, DEFAULT_HANDLE_SIZE_PX, MAX_ZOOM, MIN_ZOOM, TOUCH_HANDLE_SIZE_PX};
+use document::{parse_page_range, Document, Spread};
+use config::{CanvasQuality, clamp_settings_panel_width, clamp_thumbnail_strip_height, ConfigManager, ConfigRepair, DPI_CHOICES, ImageStyle, MeasurementUnit, PrintPreset, ProjectLayout, SettingsTab, UserPreferences, ViewOption, ViewOptions, WorkspaceBackground, AUTO_ARRANGE_GUTTER_RANGE_MM, GRID_SIZE_RANGE_MM, ROTATION_SNAP_RANGE_DEGREES};
+use layout::{
+    arrange_grid, build_contact_sheet, format_hex_color, parse_hex_color, place_new_image, resize, single_image_layout, snap_angle, snap_to_grid, ColorMode, ContactSheet, PrintSize, SizeFit, COMMON_PRINT_SIZES, Crop, Footer, FooterPosition, MarginSide, SequenceCorner, SequenceNumbers, SequenceOrder, Layout, LayoutTemplate, ResampleFilter, LayoutChange, PaperSize, PaperType, PlacedImage, PlacedRect, PrintQuality, PrintScaling, RegistrationMarks, Toning, MAX_COPIES, MIN_IMAGE_SIZE_MM,
+    Orientation as LayoutOrientation,
+};
+use manifest::ManifestImport;
+use quick_open::{PaletteEntry, PaletteItem};
+use history::History;
+use interchange::InterchangeImport;
+use journal::Journal;
+use shortcuts::Shortcut;
+use usage::{UsageEntry, UsageRange};
+use printing::{
+    build_extra_options, discover_printers, estimate_ink_coverage, estimate_render_bytes, get_printer_capabilities, job_title, largest_paper_mm, page_setting_options, printer_supports_paper_size, render_print_files, send_to_printer, supported_paper_types, supported_print_qualities,
+    diagnose, diagnosis_failed, CancelToken, DiagnosticResult, DiagnosticStatus,
+    ExportFormat, InkCoverage, OptionChoice, PrintJob, PrinterInfo, PrinterCapabilities, PrinterOption,
+};
 
 pub fn main() -> iced::Result {
     env_logger::init();
+    
+    // Headless mode: --print and --export-pdf run without a window and exit
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let low_gpu = cli::take_low_gpu_flag(&mut args);
+    let files = match cli::parse_args(&args) {
+        Ok(cli::Launch::Headless(command)) => std::process::exit(cli::run(command)),
+        Ok(cli::Launch::Gui(files)) => files,
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    
     log::info!("Initializing Print Layout v{}", VERSION);
     
     iced::application(PrintLayout::title, PrintLayout::update, PrintLayout::view)
         .theme(PrintLayout::theme)
-        .window_size(Size::new(1400.0, 900.0))
-        .run_with(PrintLayout::new)
+        .subscription(PrintLayout::subscription)
+        .window_size(INITIAL_WINDOW_SIZE)
+        // A config directory that can't be written leaves the app read-only, not broken
+        .run_with(move || PrintLayout::launch(ConfigManager::new_or_read_only(), low_gpu, files))
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Settings panel tabs (mimicking Canon PPL)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum SettingsTab {
-    #[default]
-    PrintSettings,
-    Layout,
-    ImageTools,
-}
+/// Window size at startup, before the first resize event
+const INITIAL_WINDOW_SIZE: Size = Size::new(1400.0, 900.0);
+
+/// Widget id of the quick-open palette's search field, focused on open
+const PALETTE_INPUT_ID: &str = "quick-open";
+
+/// Longest startup waits for `lpstat` to list the printers; a hung network
+/// queue then shows "No printers found" with a Retry button. Loading a
+/// printer's options gives up after as long.
+const PRINTER_DISCOVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Longest the startup printing check and auto-save check may take
+const STARTUP_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long a note next to the toolbar stays visible
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How far Ctrl+D places copies from the originals, right and down, in mm
+const DUPLICATE_OFFSET_MM: f32 = 5.0;
+
+/// Gap between the cells of "Arrange in grid", in mm, enough to cut between
+const ARRANGE_SPACING_MM: f32 = 3.0;
+
+/// Largest number of rows or columns offered for "Arrange in grid"
+const ARRANGE_MAX_CELLS: usize = 8;
+
+/// Columns of a contact sheet made from a folder
+const CONTACT_SHEET_COLUMNS: usize = 5;
+
+/// File extensions taken as images when pasting paths or reading a folder
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Widget id of the settings sidebar's scrollable, so its position can be
+/// restored after a selection change and reset on a tab switch
+const SETTINGS_SCROLL_ID: &str = "settings-scroll";
+
+/// Height (pixels) of the bars above the preview, used with the thumbnail
+/// strip's height to estimate the preview size for the fit zooms
+const EDITOR_BARS_HEIGHT: f32 = 110.0;
+const VIEW_ONLY_CHROME_HEIGHT: f32 = 45.0;
+
+/// Quick picks offered in the color block tools (RGBA)
+const COLOR_BLOCK_SWATCHES: [[u8; 4]; 8] = [
+    [0, 0, 0, 255],
+    [255, 255, 255, 255],
+    [128, 128, 128, 255],
+    [200, 40, 40, 255],
+    [240, 160, 30, 255],
+    [40, 140, 70, 255],
+    [30, 90, 180, 255],
+    [245, 235, 215, 255],
+];
 
 /// Print job status for progress dialog
 #[derive(Debug, Clone, PartialEq)]
@@ -54,45 +140,210 @@ pub enum Message {
     CanvasMessage(CanvasMessage),
     AddImageClicked,
     ImageFilesSelected(Vec<PathBuf>),
+    FileDropped(PathBuf),
+    PasteRequested,
+    ClipboardRead(Option<String>),
+    // "Ask size when adding" dialog
+    AskSizeWhenAddingToggled(bool),
+    PlacementSizeChosen(f32, f32),
+    PlacementWidthChanged(String),
+    PlacementHeightChanged(String),
+    PlacementUnitSelected(MeasurementUnit),
+    PlacementFitSelected(SizeFit),
+    ConfirmPlacement,
+    PlaceAtOriginalSize,
+    CancelPlacement,
     DeleteImageClicked,
+    // Color blocks
+    AddColorBlock,
+    ColorBlockColorPicked([u8; 4]),
+    ColorBlockHexChanged(String),
+    ColorBlockCornerRadiusChanged(f32),
     PaperSizeSelected(PaperSize),
     PaperTypeSelected(PaperType),
     MarginTopChanged(String),
     MarginBottomChanged(String),
     MarginLeftChanged(String),
     MarginRightChanged(String),
+    // Custom paper size, in the measurement unit
+    CustomWidthChanged(String),
+    CustomHeightChanged(String),
     ZoomIn,
     ZoomOut,
     ZoomReset,
     ZoomToFit,
+    ZoomToFitWidth,
+    ZoomToFitHeight,
+    WindowResized(Size),
+    FitOnResizeToggled(bool),
+    SplitterPressed(Splitter),
+    SplitterDragged(iced::Point),
+    SplitterReleased,
     // New settings messages
     SettingsTabChanged(SettingsTab),
+    SettingsScrolled(scrollable::Viewport),
     PrintQualitySelected(PrintQuality),
     OrientationToggled,
     BorderlessToggled(bool),
+    PrintScalingSelected(PrintScaling),
+    ColorModeSelected(ColorMode),
+    ToningSelected(Toning),
+    PageOutputSharpenChanged(f32),
+    PrintDpiSelected(u32),
+    ExportDpiSelected(u32),
+    ResampleFilterSelected(ResampleFilter),
+    ImageResampleFilterSelected(ResampleFilter),
+    ResetImageResampleFilter,
+    FooterToggled(bool),
+    FooterPageNumberToggled(bool),
+    FooterDateToggled(bool),
+    FooterProjectNameChanged(String),
+    FooterPositionSelected(FooterPosition),
+    FooterSizeChanged(f32),
+    SequenceNumbersToggled(bool),
+    CaptionsToggled(bool),        // Print each image's name under it
+    SequenceOrderSelected(SequenceOrder),
+    SequenceCornerSelected(SequenceCorner),
+    RegistrationMarksSelected(RegistrationMarks),
+    SequenceSizeChanged(f32),
+    WorkspaceBackgroundSelected(WorkspaceBackground),
+    AutoPaperFromFirstImageToggled(bool),
+    KeepImagesInPrintableAreaToggled(bool),
+    TouchModeToggled(bool),
+    LowGpuToggled(bool),
+    MemoryBudgetChanged(String),
+    SaveNameTemplateChanged(String),
     CopiesChanged(String),
+    /// Pages to print, such as "1-3, 5"; empty prints every page
+    PrintPagesChanged(String),
+    // Page navigation
+    PreviousPage,
+    NextPage,
+    AddPage,
+    RemovePage,
+    ToggleSpreadView,             // Show the current page next to its facing page
     // Thumbnail operations
     ThumbnailClicked(String),
+    // Image list panel
+    ToggleImageList,
+    ToggleSettingsPanel,
+    ToggleViewOnly,
+    EstimateInkClicked,
+    InkEstimated(Result<InkCoverage, String>),
+    ImageListPressed(usize),
+    ImageListHovered(usize),
+    ImageListReleased,
+    ImageListDragCancelled,
     ImageCopiesChanged(String, String),
     // Image manipulation tools
     RotateImageCW,           // Rotate 90° clockwise
     RotateImageCCW,          // Rotate 90° counter-clockwise
+    ImageRotationChanged(String), // Any angle in degrees, clockwise
     FlipImageHorizontal,     // Mirror horizontally
     FlipImageVertical,       // Flip vertically
+    BringImageForward,       // Stacking order: one step up
+    SendImageBackward,       // Stacking order: one step down
+    BringImageToFront,
+    SendImageToBack,
+    ToggleImageLock,              // Lock or unlock position and size
     ImageOpacityChanged(String),  // Change opacity (0-100%)
+    ImageOutputSharpenChanged(f32),
+    ResetImageOutputSharpen,
+    ImageNameChanged(String),     // Rename (empty = use filename)
     ImageWidthChanged(String),    // Resize width in mm
     ImageHeightChanged(String),   // Resize height in mm
-    MaintainAspectRatio(bool),    // Toggle aspect ratio lock
+    ImageXChanged(String),        // Move to this distance from the page's left edge
+    ImageYChanged(String),        // Move to this distance from the page's top edge
+    MaintainAspectRatio(bool),    // Toggle the selected image's aspect ratio lock
+    LockAspectDefaultToggled(bool),
+    AutoRotateToFitToggled(bool), // Turn images a quarter turn when that fills their cell better
+    SnapToGridToggled,            // Round dragged positions and sizes to the grid
+    UnitToggled,                  // Show lengths in inches instead of millimeters, or back
+    GridSizeChanged(String),
+    RotationSnapChanged(String),  // Step the rotation handle snaps to with Shift
+    ArrangeRowsSelected(usize),
+    ArrangeColsSelected(usize),
+    ArrangeGrid(usize, usize),    // Lay the images out in rows × columns over the printable area
+    LayoutTemplateSelected(LayoutTemplate),
+    ApplyLayoutTemplate,          // Set the page up for the chosen template and snap the images into its cells
+    LayoutTemplateNameChanged(String),
+    SaveLayoutTemplate,           // Save the arrange grid and margins as a named template
+    DeleteLayoutTemplate,
+    AutoArrange,                  // Pack the images into the printable area without overlaps
+    AutoArrangeGutterChanged(String),
+    SuggestPaperSize,             // Find the smallest paper that holds everything placed
+    ApplyPaperSuggestion,
+    DismissPaperSuggestion,
+    ModifiersChanged(iced::keyboard::Modifiers),
+    ShortcutPressed(Shortcut),    // Delete, R or an arrow key meant for the canvas
+    ShortcutFocusChecked(Shortcut, bool), // ... and whether a text field has focus
+    ToastExpired,
+    CropWhileResizing(bool),      // Resize handles reframe instead of stretching
+    ResetImageCrop,               // Show the whole image again
+    ToggleCropMode,               // Enter crop mode, or commit the crop being edited
+    AutoTrimClicked,              // Detect and crop away white scan borders
+    AutoTrimDetected(String, Result<Option<(u32, u32, u32, u32)>, String>),
+    TrimThresholdChanged(u8),
+    ImageFrameFitSelected(SizeFit), // Crop to cover the frame, or show the whole photo
+    TrimTransparencyClicked,      // Crop away transparent padding around a graphic
+    TransparencyTrimDetected(String, Result<Option<(u32, u32, u32, u32)>, String>),
+    TrimTransparentOnAddToggled(bool),
+    TrimThresholdReleased,
+    ScaleImageToFitMedia,         // Shrink the selected image onto the paper
+    FitImageToPage,               // Fill the printable area, keeping the aspect ratio
+    CenterImage,                  // Center in the printable area without resizing
     // Printing messages
     PrintersDiscovered(Vec<PrinterInfo>),
+    RetryPrinterDiscovery,
     PrinterSelected(String),
     PrinterCapabilitiesLoaded(PrinterCapabilities),
-    InputSlotSelected(String),
-    CupsMediaTypeSelected(String),
-    CupsColorModelSelected(String),
-    CupsPrintQualitySelected(String),
+    InputSlotSelected(OptionChoice),
+    CupsMediaTypeSelected(OptionChoice),
+    CupsColorModelSelected(OptionChoice),
+    CupsPrintQualitySelected(OptionChoice),
+    CupsResolutionSelected(OptionChoice),
+    CupsDuplexSelected(OptionChoice),
     PrintClicked,
+    PrintOversizedAnyway,
+    ScaleOversizedAndPrint,
+    CancelOversizedPrint,
+    PrintAllSheets,          // Confirm printing copies spread over several sheets
+    CancelSheetsPrint,
+    PrintOverBudgetAnyway,
+    CancelOverBudgetPrint,
+    PrintSelectedClicked,
+    PrintSelectedPaperChosen(PaperSize),
+    ConfirmPrintSelected,
+    CancelPrintSelected,
+    /// Sheets rendered to temporary files; the job is sent to the printer next
+    PrintRenderingDone(Result<(PrintJob, Vec<PathBuf>), String>),
     PrintJobCompleted(Result<String, String>),
+    /// Poll the running print or export render for progress
+    RenderProgressTick,
+    CancelRender,
+    UsageRecorded(Result<(), String>),
+    // Usage statistics
+    OpenUsageDialog,
+    UsageLoaded(Result<Vec<UsageEntry>, String>),
+    UsageRangeSelected(UsageRange),
+    ExportUsageCsv,
+    UsageCsvPathSelected(Option<PathBuf>),
+    CloseUsageDialog,
+    // Quick-open palette (Ctrl+K)
+    OpenPalette,
+    PaletteQueryChanged(String),
+    /// Move the highlight up (-1) or down (1)
+    PaletteMove(i32),
+    /// Choose the highlighted entry
+    PaletteActivate,
+    /// Choose the entry at this position in the filtered list
+    PalettePick(usize),
+    ClosePalette,
+    // Printing health check
+    DiagnosePrinting,
+    /// Results, and whether this was the startup check (shown only on failure)
+    PrintingDiagnosed(Vec<DiagnosticResult>, bool),
+    CloseDiagnostics,
     DismissPrintStatus,
     // File operations
     NewLayout,
@@ -101,14 +352,113 @@ pub enum Message {
     LayoutSavePathSelected(Option<PathBuf>),
     OpenLayoutClicked,
     LayoutOpenPathSelected(Option<PathBuf>),
-    LayoutLoaded(Result<ProjectLayout, String>),
+    LayoutLoaded(PathBuf, Result<ProjectLayout, String>),
+    CanvasAssetReady(PathBuf, Result<Arc<CanvasSource>, String>),
+    ImportManifestClicked,
+    ManifestPathSelected(Option<PathBuf>),
+    ManifestImported(Result<ManifestImport, String>),
+    DismissManifestErrors,
+    ExportInterchangeClicked,
+    InterchangeExportPathSelected(Option<PathBuf>),
+    ImportInterchangeClicked,
+    InterchangeImportPathSelected(Option<PathBuf>),
+    InterchangeImported(Result<InterchangeImport, String>),
+    DismissInterchangeReport,
+    CreateContactSheet,           // Lay out every image in a folder with captions
+    ContactSheetFolderSelected(Option<PathBuf>),
+    ContactSheetBuilt(Result<ContactSheet, String>),
+    ConfirmContactSheet,
+    CancelContactSheet,
+    DismissImportErrors,
+    ReplaceImageClicked,
+    ReplacementImageSelected(Option<PathBuf>),
+    // Startup notice about a config file that had to be repaired
+    OpenConfigDirectory,
+    DismissConfigRepair,
+    // Print setting presets
+    ApplyPreset(usize),
+    OpenPresetsDialog,
+    PresetNameChanged(String),
+    SaveCurrentAsPreset,
+    DeletePreset(usize),
+    ClosePresetsDialog,
+    DismissPresetWarnings,
+    // Reusable image styles
+    ImageStyleNameChanged(String),
+    SaveImageStyle,
+    ApplyImageStyle(usize),
+    DeleteImageStyle(usize),
+    ShowChangesClicked,
+    SavedLayoutLoadedForDiff(Result<ProjectLayout, String>),
+    OutlineChangesOnCanvas,
+    HideChangeOutlines,
+    CloseChanges,
+    ExportClicked,
+    ExportFormatSelected(ExportFormat),
+    ExportIncludeIndexToggled(bool),
+    ConfirmExport,
+    CancelExport,
+    ExportPathSelected(Option<PathBuf>),
+    ExportCompleted(Result<PathBuf, String>),
+    DismissExportResult,
+    ExportProcessedClicked,
+    ProcessedFormatSelected(ExportFormat),
+    ProcessedSelectedOnlyToggled(bool),
+    ConfirmExportProcessed,
+    CancelExportProcessed,
+    ProcessedFolderSelected(Option<PathBuf>),
+    ProcessedImageExported(Result<PathBuf, String>),
+    DismissProcessedExport,
+    FirstFrameShown,
     CheckAutoSave,
+    AutoSaveChecked(bool),
+    MissingRecentFilesFound(Vec<PathBuf>),
     RecoverAutoSave,
     DiscardAutoSave,
     AutoSaveTick,
     // Recent files
     OpenRecentFile(PathBuf),
     ToggleRecentFilesMenu,
+    ToggleViewMenu,
+    ViewOptionToggled(ViewOption),
+    /// Switch the canvas between draft and full quality
+    DraftPreviewToggled,
+    // Editing shortcuts
+    Undo,
+    Redo,
+    DuplicateSelection,
+    /// Show or hide the list of keyboard shortcuts (F1)
+    ToggleShortcutHelp,
+    /// Show or hide the list of this session's changes
+    ToggleJournal,
+}
+
+impl Message {
+    /// Changes the paper or margins, or places images against the page's
+    /// edges, which can't be done to the two pages of a spread at once
+    fn reshapes_page(&self) -> bool {
+        matches!(
+            self,
+            Message::PaperSizeSelected(_)
+                | Message::CustomWidthChanged(_)
+                | Message::CustomHeightChanged(_)
+                | Message::MarginTopChanged(_)
+                | Message::MarginBottomChanged(_)
+                | Message::MarginLeftChanged(_)
+                | Message::MarginRightChanged(_)
+                | Message::OrientationToggled
+                | Message::BorderlessToggled(_)
+                | Message::ApplyPreset(_)
+                | Message::SuggestPaperSize
+                | Message::ApplyPaperSuggestion
+                | Message::ArrangeGrid(..)
+                | Message::AutoArrange
+                | Message::ApplyLayoutTemplate
+                | Message::ScaleImageToFitMedia
+                | Message::FitImageToPage
+                | Message::CenterImage
+        )
+    }
 }
 
 /// Tracks what kind of drag operation is in progress
@@ -117,16 +467,34 @@ enum DragMode {
     None,
     Move,
     Resize(ResizeHandle),
+    /// Turning the selected image with its rotation handle
+    Rotate,
+    /// Crop mode: a crop handle, or the whole crop rectangle when `None`
+    Crop(Option<ResizeHandle>),
 }
 
 struct PrintLayout {
+    /// The page being edited, or both pages of the spread on screen joined
+    /// into one; written back to `document` by mark_modified
     layout: Layout,
+    /// Every page of the project
+    document: Document,
+    /// The two facing pages on screen, while the spread view is on
+    spread: Option<Spread>,
     canvas: LayoutCanvas,
     zoom: f32,
     margin_top_input: String,
     margin_bottom_input: String,
     margin_left_input: String,
     margin_right_input: String,
+    // Typed size of a custom paper, and why it can't be used
+    custom_width_input: String,
+    custom_height_input: String,
+    custom_size_error: Option<String>,
+    // Set once the user types a margin; paper changes then keep their margins
+    margins_user_edited: bool,
+    // Why the last typed value was rejected, per side (top, bottom, left, right)
+    margin_errors: [Option<String>; 4],
     // Drag state
     drag_mode: DragMode,
     drag_start_pos: (f32, f32),
@@ -134,57 +502,211 @@ struct PrintLayout {
     drag_image_initial_size: (f32, f32),
     // Printing state
     printers: Vec<PrinterInfo>,
+    discovering_printers: bool,
     selected_printer: Option<String>,
     printer_capabilities: Option<PrinterCapabilities>,
+    // CUPS option selections; None means the printer's own default applies
     selected_input_slot: Option<String>,
     selected_cups_media_type: Option<String>,
     selected_cups_color_model: Option<String>,
     selected_cups_print_quality: Option<String>,
+    selected_cups_resolution: Option<String>,
+    selected_cups_duplex: Option<String>,
     print_copies: u32,
     print_dpi: u32,
+    export_dpi: u32,
     copies_input: String,
+    print_pages_input: String,
+    /// Pages (index and layout) still to be sent after the current print job
+    print_queue: VecDeque<(usize, Layout)>,
     // UI state
     settings_tab: SettingsTab,
+    // Where the settings sidebar is scrolled to; kept across selection changes
+    settings_scroll: scrollable::AbsoluteOffset,
     print_status: PrintStatus,
+    // Print or export render in progress: its cancel flag and progress (0-1)
+    render_cancel: Option<CancelToken>,
+    render_progress: f32,
+    exporting: bool,
+    // Paper chosen in the "Print selected image" dialog (Some while the dialog is open)
+    print_selected_paper: Option<PaperSize>,
+    confirm_oversized_print: bool,
+    // Estimated render memory in bytes while asking whether to exceed the budget
+    confirm_memory_print: Option<u64>,
+    memory_budget_input: String,
+    grid_size_input: String,
+    rotation_snap_input: String,
+    // Grid chosen for "Arrange in grid" (rows, columns)
+    arrange_grid: (usize, usize),
+    // Template chosen in the Layout tab, and the name typed for saving one
+    layout_template: Option<LayoutTemplate>,
+    layout_template_name_input: String,
+    auto_arrange_gutter_input: String,
+    // Smaller paper offered by "Suggest Paper Size", until switched to or dismissed
+    paper_suggestion: Option<(PaperSize, LayoutOrientation)>,
+    // Keyboard modifiers currently held; Ctrl skips grid snapping
+    modifiers: iced::keyboard::Modifiers,
+    // Short note next to the toolbar, e.g. when a key had nothing to act on
+    toast: Option<String>,
+    // Pre-flight: the layout prints on this many sheets; ask first
+    confirm_sheet_count: Option<usize>,
+    // A contact sheet waiting for the go-ahead to replace the layout
+    pending_contact_sheet: Option<ContactSheet>,
+    // Ink coverage of the layout as last estimated; cleared when it changes
+    ink_estimate: Option<InkCoverage>,
+    estimating_ink: bool,
     // Image manipulation state
     image_width_input: String,
     image_height_input: String,
+    image_x_input: String,
+    image_y_input: String,
+    /// Why the typed position was not applied
+    image_position_error: Option<String>,
     image_opacity_input: String,
-    maintain_aspect_ratio: bool,
+    image_rotation_input: String,
+    // Copies fields being typed into, by image id, until they hold a valid count
+    copies_inputs: HashMap<String, String>,
+    color_block_hex_input: String,
+    image_name_input: String,
+    crop_while_resizing: bool,
+    // Crop center at the start of a crop-while-resizing drag
+    drag_initial_crop_center: (f32, f32),
+    /// Rotation of the image when a rotation-handle drag started
+    drag_initial_rotation: f32,
+    /// Other selected elements moving with the dragged one, at their
+    /// positions when the drag began
+    drag_group_initial: Vec<(String, (f32, f32))>,
+    // Active crop mode session and the crop when the current crop drag began
+    crop_edit: Option<CropEdit>,
+    /// Review mode: the page can be zoomed and images selected, nothing
+    /// else. Not saved with the project or preferences.
+    view_only: bool,
+    /// `--low-gpu` was given: placeholders on the canvas whatever the preference says
+    low_gpu_flag: bool,
+    /// Current window size, for the fit zooms and splitter drags
+    window_size: Size,
+    /// Splitter being dragged, if any
+    splitter_drag: Option<Splitter>,
+    drag_initial_crop: Crop,
+    // Auto-trim analysis running in the background
+    trimming: bool,
     // Config and file state
     config_manager: ConfigManager,
     preferences: UserPreferences,
     current_file: Option<PathBuf>,
     project: Option<ProjectLayout>,
     is_modified: bool,
+    /// Snapshots for undo and redo, recorded by mark_modified
+    history: History<Document>,
+    /// What changed this session, cleared on save or a new layout
+    journal: Journal,
+    show_journal: bool,
     auto_save_counter: u32,
     // UI dialogs/menus state
     show_recent_files_menu: bool,
+    show_view_menu: bool,
     show_recovery_dialog: bool,
+    show_shortcut_help: bool,
+    // Problems from the last manifest import (dialog shown while non-empty)
+    manifest_errors: Vec<String>,
+    // What the last XML interchange import left behind (dialog shown while non-empty)
+    interchange_report: Vec<String>,
+    // Files that couldn't be added as images (dialog shown while non-empty)
+    import_errors: Vec<String>,
+    // Graphics added with an alpha channel, waiting for their transparent
+    // padding to be measured in the background
+    pending_transparency_trims: Vec<(String, PathBuf)>,
+    // Presets dialog state, and what the last applied preset couldn't restore
+    presets_dialog_open: bool,
+    preset_name_input: String,
+    preset_warnings: Vec<String>,
+    image_style_name_input: String,
+    // Usage dialog state (entries are loaded when the dialog opens)
+    usage_dialog_open: bool,
+    usage_entries: Vec<UsageEntry>,
+    usage_error: Option<String>,
+    usage_range: UsageRange,
+    // Usage of the job being printed, recorded once it completes
+    pending_usage: Option<UsageEntry>,
+    // Quick-open palette: query and highlighted row of the filtered list
+    palette_open: bool,
+    palette_query: String,
+    palette_selected: usize,
+    // Printing health check results (dialog shown while Some)
+    diagnostics: Option<Vec<DiagnosticResult>>,
+    diagnosing: bool,
+    // Changes since the last save (dialog shown while Some)
+    layout_changes: Option<Vec<LayoutChange>>,
+    showing_change_outlines: bool,
+    // Export dialog state
+    export_dialog_open: bool,
+    export_format: ExportFormat,
+    export_include_index: bool,
+    export_result: Option<Result<PathBuf, String>>,
+    // "Export processed images" dialog and the run in progress
+    processed_dialog_open: bool,
+    processed_format: ExportFormat,
+    processed_selected_only: bool,
+    processed_export: Option<ProcessedExport>,
     // Thumbnail cache for performance
     thumbnail_cache: HashMap<PathBuf, iced::widget::image::Handle>,
+    // Background image loading after an open: (decoded, total)
+    image_load_progress: Option<(usize, usize)>,
     // Cached string for zoom percentage display
     zoom_text: String,
+    /// Fit mode of the current zoom; cleared by zooming in, out or to 100%
+    zoom_fit: Option<ZoomFit>,
+    // Last pointer position over the page in mm, and how many files have been
+    // dropped there since it last moved (for cascading)
+    pointer_mm: Option<(f32, f32)>,
+    drop_count: usize,
+    // Images waiting for a print size from the placement dialog
+    pending_placement: Option<PendingPlacement>,
+    // Image list row being dragged and the row it is over
+    list_drag: Option<(usize, usize)>,
+    placement_width_input: String,
+    placement_height_input: String,
+    placement_unit: MeasurementUnit,
+    placement_fit: SizeFit,
+    // Set when config.json was broken at startup and had to be repaired
+    config_repair: Option<ConfigRepair>,
+    // When startup began; cleared once the first frame is shown
+    startup: Option<std::time::Instant>,
+    // Recent files that weren't found when checked after startup
+    missing_recent_files: Vec<PathBuf>,
 }
 
 impl PrintLayout {
-    fn new() -> (Self, Task<Message>) {
-        // Initialize config manager
-        let config_manager = ConfigManager::new().expect("Failed to create config manager");
-        let preferences = config_manager.load_config();
+    /// Start the window; `low_gpu_flag` is `--low-gpu` on the command line
+    fn launch(config_manager: ConfigManager, low_gpu_flag: bool, files: Vec<PathBuf>) -> (Self, Task<Message>) {
+        let (mut app, task) = Self::with_config_manager(config_manager);
+        app.low_gpu_flag = low_gpu_flag;
+        app.sync_low_gpu();
+        match open_at_launch(files) {
+            Some(open) => (app, Task::batch([task, Task::done(open)])),
+            None => (app, task),
+        }
+    }
+
+    fn with_config_manager(config_manager: ConfigManager) -> (Self, Task<Message>) {
+        let started = std::time::Instant::now();
+        let (mut preferences, config_repair) = config_manager.load_config();
+        log::info!("Startup: preferences loaded in {:?}", started.elapsed());
+        // Hand-edited or stale panel sizes could hide a panel entirely
+        preferences.settings_panel_width = clamp_settings_panel_width(preferences.settings_panel_width);
+        preferences.thumbnail_strip_height = clamp_thumbnail_strip_height(preferences.thumbnail_strip_height);
         
         // Create layout with preferences, applying last successful print settings if available
         let mut layout = Layout::new();
         
         // Apply last print settings if they exist
         let last_print = &preferences.last_print_settings;
-        if let Some(paper_size) = last_print.paper_size {
-            layout.page.paper_size = paper_size;
-            // Get the paper dimensions
-            let (width, height) = paper_size.to_dimensions();
-            layout.page.width_mm = width;
-            layout.page.height_mm = height;
-        }
+        let paper_size = last_print.paper_size.unwrap_or(preferences.default_paper_size);
+        layout.page.paper_size = paper_size;
+        // Get the paper dimensions
+        let (width, height) = paper_size.to_dimensions();
+        layout.page.width_mm = width;
+        layout.page.height_mm = height;
         if let Some(paper_type) = last_print.paper_type {
             layout.page.paper_type = paper_type;
         }
@@ -204,82 +726,191 @@ impl PrintLayout {
         if let Some(borderless) = last_print.borderless {
             layout.page.borderless = borderless;
         }
-        if let Some(margins) = last_print.margins {
-            layout.page.margin_top_mm = margins.0;
-            layout.page.margin_bottom_mm = margins.1;
-            layout.page.margin_left_mm = margins.2;
-            layout.page.margin_right_mm = margins.3;
+        // Use margins from last print settings if available, otherwise use defaults
+        let (top, bottom, left, right) = last_print.margins.unwrap_or(preferences.default_margins);
+        
+        // Margins restored from a different paper can collide or swallow a small one
+        if layout.page.try_set_margins(top, bottom, left, right).is_err() || !layout.page.margins_reasonable() {
+            layout.page.apply_default_margins();
         }
         
-        let canvas = LayoutCanvas::new(layout.clone());
+        let page = &layout.page;
+        let (margin_top, margin_bottom, margin_left, margin_right) =
+            (page.margin_top_mm, page.margin_bottom_mm, page.margin_left_mm, page.margin_right_mm);
         
-        // Use margins from last print settings if available, otherwise use defaults
-        let (margin_top, margin_bottom, margin_left, margin_right) = 
-            last_print.margins.unwrap_or(preferences.default_margins);
+        let mut canvas = LayoutCanvas::new(layout.clone());
+        let (r, g, b) = preferences.workspace_background.to_rgb();
+        canvas.set_background(Color::from_rgb(r, g, b));
+        canvas.set_handle_size(preferences.handle_size_px);
+        canvas.set_low_gpu(preferences.low_gpu);
+        canvas.set_view_options(canvas_view_options(&preferences), preferences.grid_size_mm);
         
         // Get copies from last print, default to 1
         let print_copies = last_print.copies.unwrap_or(1);
         
         // Pre-compute zoom text for display
-        let zoom_text = format!("{:.0}%", preferences.zoom_level * 100.0);
+        let zoom_text = zoom_label(preferences.zoom_level, None);
+        let document = Document::new(layout.clone());
+        let history = History::new(&document);
+        let (custom_width, custom_height) = layout.page.paper_size.to_dimensions();
+        log::info!("Startup: layout and canvas ready after {:?}", started.elapsed());
 
         let instance = PrintLayout {
             layout,
+            document,
+            spread: None,
             canvas,
             zoom: preferences.zoom_level,
-            margin_top_input: margin_top.to_string(),
-            margin_bottom_input: margin_bottom.to_string(),
-            margin_left_input: margin_left.to_string(),
-            margin_right_input: margin_right.to_string(),
+            margins_user_edited: false,
+            margin_errors: Default::default(),
+            margin_top_input: preferences.measurement_unit.format_compact(margin_top),
+            margin_bottom_input: preferences.measurement_unit.format_compact(margin_bottom),
+            margin_left_input: preferences.measurement_unit.format_compact(margin_left),
+            margin_right_input: preferences.measurement_unit.format_compact(margin_right),
+            custom_width_input: preferences.measurement_unit.format_compact(custom_width),
+            custom_height_input: preferences.measurement_unit.format_compact(custom_height),
+            custom_size_error: None,
             drag_mode: DragMode::None,
             drag_start_pos: (0.0, 0.0),
             drag_image_initial_pos: (0.0, 0.0),
             drag_image_initial_size: (0.0, 0.0),
             printers: Vec::new(),
             // Use printer from last print settings if available
-            selected_printer: last_print.printer_name.clone().or(preferences.last_printer.clone()),
+            discovering_printers: true,
+            // An empty name could have been saved by older versions
+            selected_printer: last_print
+                .printer_name
+                .clone()
+                .or(preferences.last_printer.clone())
+                .filter(|name| !name.is_empty()),
             printer_capabilities: None,
             selected_input_slot: None,
             selected_cups_media_type: None,
             selected_cups_color_model: None,
             selected_cups_print_quality: None,
+            selected_cups_resolution: None,
+            selected_cups_duplex: None,
             print_copies,
-            print_dpi: 300,
+            print_dpi: preferences.print_dpi,
+            export_dpi: preferences.export_dpi,
             copies_input: print_copies.to_string(),
-            settings_tab: SettingsTab::PrintSettings,
+            print_pages_input: String::new(),
+            print_queue: VecDeque::new(),
+            settings_tab: preferences.settings_tab,
+            settings_scroll: scrollable::AbsoluteOffset { x: 0.0, y: 0.0 },
             print_status: PrintStatus::Idle,
+            render_cancel: None,
+            render_progress: 0.0,
+            exporting: false,
+            print_selected_paper: None,
+            confirm_oversized_print: false,
+            confirm_memory_print: None,
+            memory_budget_input: preferences.render_memory_budget_mb.to_string(),
+            grid_size_input: format!("{}", preferences.grid_size_mm),
+            rotation_snap_input: format!("{}", preferences.rotation_snap_degrees),
+            arrange_grid: (2, 2),
+            layout_template: None,
+            layout_template_name_input: String::new(),
+            auto_arrange_gutter_input: format!("{}", preferences.auto_arrange_gutter_mm),
+            paper_suggestion: None,
+            modifiers: iced::keyboard::Modifiers::default(),
+            toast: None,
+            confirm_sheet_count: None,
+            pending_contact_sheet: None,
+            ink_estimate: None,
+            estimating_ink: false,
             // Image manipulation defaults
             image_width_input: String::new(),
             image_height_input: String::new(),
+            image_x_input: String::new(),
+            image_y_input: String::new(),
+            image_position_error: None,
+            image_rotation_input: "0".to_string(),
+            copies_inputs: HashMap::new(),
             image_opacity_input: "100".to_string(),
-            maintain_aspect_ratio: true,
+            color_block_hex_input: String::new(),
+            image_name_input: String::new(),
+            crop_while_resizing: false,
+            drag_initial_crop_center: (0.5, 0.5),
+            drag_initial_rotation: 0.0,
+            drag_group_initial: Vec::new(),
+            crop_edit: None,
+            view_only: false,
+            low_gpu_flag: false,
+            window_size: INITIAL_WINDOW_SIZE,
+            splitter_drag: None,
+            drag_initial_crop: Crop::FULL,
+            trimming: false,
             config_manager,
             preferences,
             current_file: None,
             project: None,
             is_modified: false,
+            history,
+            journal: Journal::default(),
+            show_journal: false,
             auto_save_counter: 0,
             show_recent_files_menu: false,
+            show_view_menu: false,
             show_recovery_dialog: false,
+            show_shortcut_help: false,
+            manifest_errors: Vec::new(),
+            interchange_report: Vec::new(),
+            import_errors: Vec::new(),
+            pending_transparency_trims: Vec::new(),
+            presets_dialog_open: false,
+            preset_name_input: String::new(),
+            preset_warnings: Vec::new(),
+            image_style_name_input: String::new(),
+            usage_dialog_open: false,
+            usage_entries: Vec::new(),
+            usage_error: None,
+            palette_open: false,
+            palette_query: String::new(),
+            palette_selected: 0,
+            diagnostics: None,
+            diagnosing: false,
+            usage_range: UsageRange::default(),
+            pending_usage: None,
+            layout_changes: None,
+            showing_change_outlines: false,
+            export_dialog_open: false,
+            export_format: ExportFormat::default(),
+            export_include_index: false,
+            export_result: None,
+            processed_dialog_open: false,
+            processed_format: ExportFormat::Png,
+            processed_selected_only: false,
+            processed_export: None,
             thumbnail_cache: HashMap::new(),
+            image_load_progress: None,
             zoom_text,
+            zoom_fit: None,
+            pointer_mm: None,
+            drop_count: 0,
+            pending_placement: None,
+            list_drag: None,
+            placement_width_input: "100".to_string(),
+            placement_height_input: "150".to_string(),
+            placement_unit: MeasurementUnit::Millimeters,
+            placement_fit: SizeFit::default(),
+            config_repair,
+            startup: Some(started),
+            missing_recent_files: Vec::new(),
         };
         
+        // Printer discovery and the printing check talk to CUPS, which can
+        // hang; both run off the UI thread while the window shows
+        // "Looking for printers…". Disk checks wait for the first frame.
         let mut tasks = vec![
-            Task::perform(
-                async {
-                    discover_printers().unwrap_or_else(|e| {
-                        log::error!("Failed to discover printers: {}", e);
-                        Vec::new()
-                    })
-                },
-                Message::PrintersDiscovered,
-            ),
-            Task::done(Message::CheckAutoSave),
+            discover_printers_task(),
+            Task::perform(blocking_with_timeout("Startup printing check", STARTUP_CHECK_TIMEOUT, diagnose), |results| {
+                Message::PrintingDiagnosed(results.unwrap_or_default(), true)
+            }),
         ];
         
-        // Set up auto-save timer if enabled
-        if instance.preferences.auto_save_enabled {
+        // Set up auto-save timer if enabled and there is somewhere to save to
+        if instance.preferences.auto_save_enabled && instance.config_manager.read_only_reason().is_none() {
             tasks.push(Task::done(Message::AutoSaveTick));
         }
         
@@ -287,40 +918,106 @@ impl PrintLayout {
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        let selected = self.layout.selected_image_id.clone();
+        let task = self.handle_message(message);
+        self.canvas.set_rotating(self.drag_mode == DragMode::Rotate);
+        // A new selection refills the sidebar, which can lose its scroll
+        // position; put it back where the user left it
+        let keep_scroll = if self.layout.selected_image_id != selected {
+            scrollable::scroll_to(scrollable::Id::new(SETTINGS_SCROLL_ID), self.settings_scroll)
+        } else {
+            Task::none()
+        };
+        // Whatever changed the layout, decode its new images off the UI
+        // thread so the canvas never loads them while drawing
+        let trims = self.trim_new_transparent_images();
+        Task::batch([task, keep_scroll, self.load_images_in_background(), trims])
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         match message {
+            Message::CanvasMessage(canvas_msg) if self.view_only && canvas_msg.edits_layout() => {}
+            Message::FileDropped(_) | Message::PasteRequested if self.view_only => {}
+            Message::ShortcutPressed(shortcut) if self.view_only && shortcut.edits_layout() => {}
+            Message::Undo | Message::Redo | Message::DuplicateSelection if self.view_only => {}
+            message if self.spread.is_some() && message.reshapes_page() => {
+                return self.show_toast("Leave the spread view to change the paper or margins or place against a page's edges".to_string());
+            }
             Message::CanvasMessage(canvas_msg) => match canvas_msg {
-                CanvasMessage::SelectImage(id) => {
-                    log::info!("Selected image: {}", id);
-                    self.layout.selected_image_id = Some(id.clone());
-                    if let Some(image) = self.layout.get_image(&id) {
+                CanvasMessage::CanvasClicked(x, y) => {
+                    match self.layout.find_element_at_point(x, y).map(|e| e.id().to_string()) {
+                        // Ctrl+click adds to or takes out of the selection
+                        Some(id) if self.modifiers.control() => {
+                            self.layout.toggle_selected(id);
+                            self.sync_tool_fields();
+                        }
+                        Some(id) => self.select_element(id),
+                        None if self.modifiers.control() => {}
+                        None => self.layout.select(None),
+                    }
+                    self.drag_mode = DragMode::None;
+                    self.canvas.set_layout(self.layout.clone());
+                }
+                CanvasMessage::StartMove(id, x, y) => {
+                    // Dragging one of several selected elements moves them all
+                    self.layout.select_keeping_group(id.clone());
+                    self.sync_tool_fields();
+                    self.drag_group_initial = self
+                        .layout
+                        .elements()
+                        .into_iter()
+                        .filter(|e| e.id() != id && !e.locked() && self.layout.is_selected(e.id()))
+                        .map(|e| {
+                            let (x_mm, y_mm, _, _) = e.bounds();
+                            (e.id().to_string(), (x_mm, y_mm))
+                        })
+                        .collect();
+                    // A locked image is selected, so it can be unlocked, but not dragged
+                    if let Some((x_mm, y_mm, width, height)) = self
+                        .layout
+                        .selected_image()
+                        .filter(|image| !image.locked)
+                        .map(|image| image.bounds())
+                        .or_else(|| self.layout.selected_rect().map(|rect| rect.bounds()))
+                    {
                         self.drag_mode = DragMode::Move;
-                        self.drag_image_initial_pos = (image.x_mm, image.y_mm);
-                        self.drag_image_initial_size = (image.width_mm, image.height_mm);
-                        self.drag_start_pos = (0.0, 0.0);
-                        // Update input fields for the selected image
-                        self.image_width_input = format!("{:.1}", image.width_mm);
-                        self.image_height_input = format!("{:.1}", image.height_mm);
-                        self.image_opacity_input = format!("{:.0}", image.opacity * 100.0);
+                        self.drag_image_initial_pos = (x_mm, y_mm);
+                        self.drag_image_initial_size = (width, height);
+                        // Measure from the press so the image doesn't jump by the threshold
+                        self.drag_start_pos = (x, y);
                     }
                     self.canvas.set_layout(self.layout.clone());
                 }
                 CanvasMessage::StartResize(id, handle) => {
                     log::info!("Start resize: {} with handle {:?}", id, handle);
                     self.layout.selected_image_id = Some(id.clone());
-                    if let Some(image) = self.layout.get_image(&id) {
+                    if let Some(image) = self.layout.get_image(&id).filter(|image| !image.locked) {
                         self.drag_mode = DragMode::Resize(handle);
                         self.drag_image_initial_pos = (image.x_mm, image.y_mm);
                         self.drag_image_initial_size = (image.width_mm, image.height_mm);
+                        self.drag_initial_crop_center = image.crop.unwrap_or(Crop::FULL).center();
+                        self.drag_start_pos = (0.0, 0.0);
+                    } else if let Some(rect) = self.layout.get_rect(&id) {
+                        self.drag_mode = DragMode::Resize(handle);
+                        self.drag_image_initial_pos = (rect.x_mm, rect.y_mm);
+                        self.drag_image_initial_size = (rect.width_mm, rect.height_mm);
                         self.drag_start_pos = (0.0, 0.0);
                     }
                     self.canvas.set_layout(self.layout.clone());
                 }
-                CanvasMessage::DeselectAll => {
-                    self.layout.selected_image_id = None;
-                    self.drag_mode = DragMode::None;
+                CanvasMessage::StartRotate(id) => {
+                    self.layout.select(Some(id.clone()));
+                    if let Some(image) = self.layout.get_image(&id).filter(|image| !image.locked) {
+                        self.drag_mode = DragMode::Rotate;
+                        self.drag_image_initial_pos = (image.x_mm, image.y_mm);
+                        self.drag_image_initial_size = (image.width_mm, image.height_mm);
+                        self.drag_initial_rotation = image.rotation_degrees;
+                    }
                     self.canvas.set_layout(self.layout.clone());
                 }
                 CanvasMessage::MouseMoved(x, y) => {
+                    self.pointer_mm = Some((x, y));
+                    self.drop_count = 0;
                     match self.drag_mode {
                         DragMode::Move => {
                             if let Some(id) = self.layout.selected_image_id.clone() {
@@ -335,9 +1032,15 @@ impl PrintLayout {
                                 if let Some(image) = self.layout.get_image_mut(&id) {
                                     image.x_mm = new_x;
                                     image.y_mm = new_y;
+                                } else if let Some(rect) = self.layout.get_rect_mut(&id) {
+                                    rect.x_mm = new_x;
+                                    rect.y_mm = new_y;
                                 }
                                 // Use optimized method that updates canvas position directly
                                 self.canvas.update_image_position(&id, new_x, new_y);
+                                for (other, (start_x, start_y)) in self.drag_group_initial.clone() {
+                                    self.move_element_to(&other, start_x + dx, start_y + dy);
+                                }
                             }
                         }
                         DragMode::Resize(handle) => {
@@ -350,117 +1053,118 @@ impl PrintLayout {
                                 
                                 let (init_x, init_y) = self.drag_image_initial_pos;
                                 let (init_w, init_h) = self.drag_image_initial_size;
-                                let aspect_ratio = init_w / init_h;
-                                
-                                let (new_x, new_y, new_w, new_h) = match handle {
-                                    ResizeHandle::BottomRight => {
-                                        let new_w = (init_w + dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            (init_h + dy).max(10.0)
-                                        };
-                                        (init_x, init_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::BottomLeft => {
-                                        let new_w = (init_w - dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            (init_h + dy).max(10.0)
-                                        };
-                                        let new_x = init_x + init_w - new_w;
-                                        (new_x, init_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::TopRight => {
-                                        let new_w = (init_w + dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            (init_h - dy).max(10.0)
-                                        };
-                                        let new_y = init_y + init_h - new_h;
-                                        (init_x, new_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::TopLeft => {
-                                        let new_w = (init_w - dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            (init_h - dy).max(10.0)
-                                        };
-                                        let new_x = init_x + init_w - new_w;
-                                        let new_y = init_y + init_h - new_h;
-                                        (new_x, new_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::Right => {
-                                        let new_w = (init_w + dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            init_h
-                                        };
-                                        (init_x, init_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::Left => {
-                                        let new_w = (init_w - dx).max(10.0);
-                                        let new_h = if self.maintain_aspect_ratio {
-                                            new_w / aspect_ratio
-                                        } else {
-                                            init_h
-                                        };
-                                        let new_x = init_x + init_w - new_w;
-                                        (new_x, init_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::Bottom => {
-                                        let new_h = (init_h + dy).max(10.0);
-                                        let new_w = if self.maintain_aspect_ratio {
-                                            new_h * aspect_ratio
-                                        } else {
-                                            init_w
-                                        };
-                                        (init_x, init_y, new_w, new_h)
-                                    }
-                                    ResizeHandle::Top => {
-                                        let new_h = (init_h - dy).max(10.0);
-                                        let new_w = if self.maintain_aspect_ratio {
-                                            new_h * aspect_ratio
-                                        } else {
-                                            init_w
-                                        };
-                                        let new_y = init_y + init_h - new_h;
-                                        (init_x, new_y, new_w, new_h)
-                                    }
-                                };
+                                // Shapes have no lock of their own and follow the default
+                                let lock_aspect = self
+                                    .layout
+                                    .get_image(&id)
+                                    .map_or(self.preferences.lock_aspect_default, |img| img.lock_aspect);
+                                // A locked aspect keeps the shape the frame had at the
+                                // press: the image's display_aspect_ratio()
+                                let rect = resize::compute(
+                                    handle,
+                                    resize::Rect::new(init_x, init_y, init_w, init_h),
+                                    (dx, dy),
+                                    lock_aspect,
+                                    MIN_IMAGE_SIZE_MM,
+                                );
+                                let (new_x, new_y, new_w, new_h) = (rect.x, rect.y, rect.width, rect.height);
+                                // Reframe instead of stretching: crop the content to cover the new box
+                                let reframe = self.crop_while_resizing && !lock_aspect;
+                                let center = self.drag_initial_crop_center;
                                 
                                 if let Some(image) = self.layout.get_image_mut(&id) {
                                     image.x_mm = new_x;
                                     image.y_mm = new_y;
                                     image.width_mm = new_w;
                                     image.height_mm = new_h;
+                                    if reframe {
+                                        image.crop = Some(Crop::cover(image.content_aspect(), new_w / new_h, center));
+                                        self.canvas.update_image_crop(&id, image.crop);
+                                    }
                                     // Update input fields live
-                                    self.image_width_input = format!("{:.1}", new_w);
-                                    self.image_height_input = format!("{:.1}", new_h);
+                                    self.image_width_input = self.preferences.measurement_unit.format(new_w);
+                                    self.image_height_input = self.preferences.measurement_unit.format(new_h);
+                                } else if let Some(rect) = self.layout.get_rect_mut(&id) {
+                                    rect.x_mm = new_x;
+                                    rect.y_mm = new_y;
+                                    rect.width_mm = new_w;
+                                    rect.height_mm = new_h;
                                 }
                                 // Use optimized method that updates canvas bounds directly
                                 self.canvas.update_image_bounds(&id, new_x, new_y, new_w, new_h);
                             }
                         }
+                        DragMode::Rotate => {
+                            if let Some(center) = self.layout.selected_image().map(|img| img.frame_point(0.5, 0.5)) {
+                                // Shift snaps to the preferred step
+                                let snap = self.modifiers.shift().then_some(self.preferences.rotation_snap_degrees);
+                                let degrees = rotation_from_drag(center, (x, y), snap);
+                                if self.set_selected_rotation(degrees) {
+                                    self.image_rotation_input = format!("{}", degrees);
+                                }
+                            }
+                        }
+                        DragMode::Crop(handle) => {
+                            if self.drag_start_pos == (0.0, 0.0) {
+                                self.drag_start_pos = (x, y);
+                            }
+                            let delta = (x - self.drag_start_pos.0, y - self.drag_start_pos.1);
+                            if let Some(edit) = self.crop_edit.as_mut() {
+                                // Past the image edge the last valid crop is kept
+                                if let Some(crop) = self.drag_initial_crop.dragged(handle, delta, edit.frame_size()) {
+                                    edit.crop = crop;
+                                    self.canvas.set_crop_edit(Some(edit.clone()));
+                                }
+                            }
+                        }
                         DragMode::None => {}
                     }
                 }
                 CanvasMessage::MouseReleased => {
                     if self.drag_mode != DragMode::None {
+                        let mode = self.drag_mode;
+                        // A click that selects without dragging leaves the document unchanged
+                        let bounds = match (self.layout.selected_image(), self.layout.selected_rect()) {
+                            (Some(image), _) => Some(image.bounds()),
+                            (None, Some(rect)) => Some(rect.bounds()),
+                            (None, None) => None,
+                        };
+                        let changed = bounds.is_some_and(|(x, y, width, height)| {
+                            (x, y) != self.drag_image_initial_pos
+                                || (width, height) != self.drag_image_initial_size
+                        }) || (mode == DragMode::Rotate
+                            && self.layout.selected_image().is_some_and(|img| {
+                                img.rotation_degrees != self.drag_initial_rotation
+                            }));
                         self.drag_mode = DragMode::None;
                         self.drag_start_pos = (0.0, 0.0);
-                        self.is_modified = true;
+                        if changed {
+                            // Holding Ctrl while letting go places it freely
+                            if self.preferences.snap_to_grid && !self.modifiers.control() && mode != DragMode::Rotate {
+                                self.snap_selection_to_grid(mode);
+                            } else {
+                                // Lay the automatic copies out around the new position
+                                self.canvas.set_layout(self.layout.clone());
+                            }
+                            self.mark_modified();
+                        }
+                        self.drag_group_initial.clear();
+                        self.sync_tool_fields();
                     }
                 }
+                CanvasMessage::SelectArea(x, y, width, height) => {
+                    // Ctrl adds the enclosed elements to the current selection
+                    self.layout.select_in_rect((x, y, width, height), self.modifiers.control());
+                    self.sync_tool_fields();
+                    self.drag_mode = DragMode::None;
+                    self.canvas.set_layout(self.layout.clone());
+                }
                 CanvasMessage::ImageMoved(id, x, y) => {
                     if let Some(image) = self.layout.get_image_mut(&id) {
                         image.x_mm = x;
                         image.y_mm = y;
                         self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
                 CanvasMessage::ImageResized(id, width, height) => {
@@ -468,9 +1172,20 @@ impl PrintLayout {
                         image.width_mm = width;
                         image.height_mm = height;
                         self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                    }
+                }
+                CanvasMessage::EnterCropMode => self.enter_crop_mode(),
+                CanvasMessage::StartCropDrag(handle) => {
+                    if let Some(edit) = &self.crop_edit {
+                        self.drag_mode = DragMode::Crop(handle);
+                        self.drag_initial_crop = edit.crop;
+                        self.drag_start_pos = (0.0, 0.0);
                     }
                 }
-                CanvasMessage::CanvasClicked(_, _) => {}
+                CanvasMessage::CommitCrop => self.finish_crop_mode(true),
+                CanvasMessage::CancelCrop => self.finish_crop_mode(false),
+                CanvasMessage::CursorLeft => self.pointer_mm = None,
             },
             Message::AddImageClicked => {
                 return Task::perform(
@@ -487,212 +1202,657 @@ impl PrintLayout {
                 );
             }
             Message::ImageFilesSelected(paths) => {
-                for path in paths {
-                    match ::image::open(&path) {
-                        Ok(img) => {
-                            let (width, height) = img.dimensions();
-                            let placed_image = PlacedImage::new(path.clone(), width, height);
-                            self.layout.add_image(placed_image);
-                            // Cache the thumbnail handle
-                            let handle = iced::widget::image::Handle::from_path(&path);
-                            self.thumbnail_cache.insert(path.clone(), handle);
-                            log::info!("Added image: {} ({}x{})", path.display(), width, height);
-                        }
-                        Err(e) => log::error!("Failed to load image {}: {}", path.display(), e),
+                self.add_images(PendingPlacement { paths, anchor: None, first_index: 0 });
+            }
+            Message::FileDropped(path) => {
+                // Off the canvas, drops cascade from the default position instead
+                let first_index = self.drop_count;
+                self.drop_count += 1;
+                self.add_images(PendingPlacement {
+                    paths: vec![path],
+                    anchor: self.pointer_mm,
+                    first_index,
+                });
+            }
+            Message::PasteRequested => {
+                return iced::clipboard::read().map(Message::ClipboardRead);
+            }
+            Message::ClipboardRead(contents) => {
+                let paths = contents.as_deref().map(paths_from_clipboard).unwrap_or_default();
+                if paths.is_empty() {
+                    log::info!("Clipboard holds no image files to paste");
+                    return Task::none();
+                }
+                self.add_images(PendingPlacement { paths, anchor: self.pointer_mm, first_index: 0 });
+            }
+            Message::AskSizeWhenAddingToggled(enabled) => {
+                self.preferences.ask_size_when_adding = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save placement preference: {}", e);
+                }
+            }
+            Message::PlacementSizeChosen(short_mm, long_mm) => {
+                let unit = self.placement_unit;
+                self.placement_width_input = format_length(unit.from_mm(short_mm));
+                self.placement_height_input = format_length(unit.from_mm(long_mm));
+            }
+            Message::PlacementWidthChanged(value) => {
+                self.placement_width_input = value;
+            }
+            Message::PlacementHeightChanged(value) => {
+                self.placement_height_input = value;
+            }
+            Message::PlacementUnitSelected(unit) => {
+                // Convert what was typed so the size stays the same
+                let (old, new) = (self.placement_unit, unit);
+                for input in [&mut self.placement_width_input, &mut self.placement_height_input] {
+                    if let Some(mm) = old.parse_mm(input) {
+                        *input = format_length(new.from_mm(mm));
                     }
                 }
-                self.canvas.set_layout(self.layout.clone());
-                self.is_modified = true;
+                self.placement_unit = unit;
+            }
+            Message::PlacementFitSelected(fit) => {
+                self.placement_fit = fit;
+            }
+            Message::ConfirmPlacement => {
+                if let Some(size) = self.placement_size() {
+                    if let Some(pending) = self.pending_placement.take() {
+                        self.place_images(pending, Some(size));
+                    }
+                }
+            }
+            Message::PlaceAtOriginalSize => {
+                if let Some(pending) = self.pending_placement.take() {
+                    self.place_images(pending, None);
+                }
+            }
+            Message::CancelPlacement => {
+                self.pending_placement = None;
             }
             Message::DeleteImageClicked => {
-                if let Some(id) = &self.layout.selected_image_id.clone() {
+                self.finish_crop_mode(false);
+                // Locked images stay until they are unlocked
+                let (locked, selected): (Vec<String>, Vec<String>) = self
+                    .layout
+                    .selected_ids()
+                    .into_iter()
+                    .partition(|id| self.layout.get_image(id).is_some_and(|img| img.locked));
+                for id in &selected {
                     // Remove from thumbnail cache and source cache
                     if let Some(img) = self.layout.get_image(id) {
                         self.thumbnail_cache.remove(&img.path);
                         self.canvas.remove_from_source_cache(&img.path);
                     }
-                    self.layout.remove_image(id);
+                    if self.layout.remove_image(id).is_none() {
+                        self.layout.remove_rect(id);
+                    }
+                }
+                if !selected.is_empty() {
                     self.canvas.set_layout(self.layout.clone());
-                    self.is_modified = true;
+                    self.mark_modified();
                 }
-            }
-            Message::PaperSizeSelected(paper_size) => {
-                let (width, height) = paper_size.to_dimensions();
-                // Preserve current orientation when changing paper size
-                if self.layout.page.orientation == LayoutOrientation::Landscape {
-                    // For landscape, swap width and height
-                    self.layout.page.width_mm = height;
-                    self.layout.page.height_mm = width;
-                } else {
-                    self.layout.page.width_mm = width;
-                    self.layout.page.height_mm = height;
+                if !locked.is_empty() {
+                    return self.show_toast(format!(
+                        "{} locked image{} kept - unlock to delete",
+                        locked.len(),
+                        if locked.len() == 1 { "" } else { "s" }
+                    ));
                 }
-                self.layout.page.paper_size = paper_size;
-                self.canvas.set_layout(self.layout.clone());
-                self.is_modified = true;
             }
-            Message::PaperTypeSelected(paper_type) => {
-                self.layout.page.paper_type = paper_type;
-                self.is_modified = true;
+            Message::AddColorBlock => {
+                self.finish_crop_mode(false);
+                // A 100 × 20 mm bar centered in the printable area
+                let (area_x, area_y, area_w, area_h) = self.layout.page.printable_area();
+                let (width, height) = (100.0_f32.min(area_w), 20.0_f32.min(area_h));
+                let rect = PlacedRect::new(
+                    area_x + (area_w - width) / 2.0,
+                    area_y + (area_h - height) / 2.0,
+                    width,
+                    height,
+                    COLOR_BLOCK_SWATCHES[2],
+                );
+                self.color_block_hex_input = format_hex_color(rect.color);
+                self.layout.select(Some(rect.id.clone()));
+                self.layout.add_rect(rect);
+                self.settings_tab = SettingsTab::ImageTools;
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
             }
-            Message::MarginTopChanged(value) => {
-                self.margin_top_input = value.clone();
-                if let Ok(margin) = value.parse::<f32>() {
-                    if margin >= 0.0 && margin < self.layout.page.height_mm / 2.0 {
-                        self.layout.page.margin_top_mm = margin;
+            Message::ColorBlockColorPicked(color) => {
+                if let Some(id) = self.layout.selected_image_id.clone() {
+                    if let Some(rect) = self.layout.get_rect_mut(&id) {
+                        rect.color = color;
+                        self.color_block_hex_input = format_hex_color(color);
                         self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
             }
-            Message::MarginBottomChanged(value) => {
-                self.margin_bottom_input = value.clone();
-                if let Ok(margin) = value.parse::<f32>() {
-                    if margin >= 0.0 && margin < self.layout.page.height_mm / 2.0 {
-                        self.layout.page.margin_bottom_mm = margin;
+            Message::ColorBlockHexChanged(value) => {
+                self.color_block_hex_input = value.clone();
+                if let (Some(color), Some(id)) = (parse_hex_color(&value), self.layout.selected_image_id.clone()) {
+                    if let Some(rect) = self.layout.get_rect_mut(&id) {
+                        rect.color = color;
                         self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
             }
-            Message::MarginLeftChanged(value) => {
-                self.margin_left_input = value.clone();
-                if let Ok(margin) = value.parse::<f32>() {
-                    if margin >= 0.0 && margin < self.layout.page.width_mm / 2.0 {
-                        self.layout.page.margin_left_mm = margin;
+            Message::ColorBlockCornerRadiusChanged(radius) => {
+                if let Some(id) = self.layout.selected_image_id.clone() {
+                    if let Some(rect) = self.layout.get_rect_mut(&id) {
+                        rect.corner_radius_mm = radius;
                         self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
             }
-            Message::MarginRightChanged(value) => {
-                self.margin_right_input = value.clone();
-                if let Ok(margin) = value.parse::<f32>() {
-                    if margin >= 0.0 && margin < self.layout.page.width_mm / 2.0 {
-                        self.layout.page.margin_right_mm = margin;
-                        self.canvas.set_layout(self.layout.clone());
+            Message::PaperSizeSelected(paper_size) => {
+                self.layout.page.set_paper_size(paper_size, self.margins_user_edited);
+                self.sync_margin_inputs();
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+            }
+            Message::PaperTypeSelected(paper_type) => {
+                self.layout.page.paper_type = paper_type;
+                self.mark_modified();
+            }
+            Message::MarginTopChanged(value) => self.margin_input_changed(MarginSide::Top, value),
+            Message::MarginBottomChanged(value) => self.margin_input_changed(MarginSide::Bottom, value),
+            Message::MarginLeftChanged(value) => self.margin_input_changed(MarginSide::Left, value),
+            Message::MarginRightChanged(value) => self.margin_input_changed(MarginSide::Right, value),
+            Message::CustomWidthChanged(value) => {
+                self.custom_width_input = value;
+                self.custom_size_changed();
+            }
+            Message::CustomHeightChanged(value) => {
+                self.custom_height_input = value;
+                self.custom_size_changed();
+            }
+            Message::ZoomIn => self.set_zoom(self.zoom * 1.2, None),
+            Message::ZoomOut => self.set_zoom(self.zoom / 1.2, None),
+            Message::ZoomReset => self.set_zoom(1.0, None),
+            Message::ZoomToFit => self.zoom_to_fit(ZoomFit::Page),
+            Message::ZoomToFitWidth => self.zoom_to_fit(ZoomFit::Width),
+            Message::ZoomToFitHeight => self.zoom_to_fit(ZoomFit::Height),
+            Message::WindowResized(size) => {
+                self.window_size = size;
+                if self.preferences.fit_on_resize {
+                    if let Some(fit) = self.zoom_fit {
+                        self.zoom_to_fit(fit);
                     }
                 }
             }
-            Message::ZoomIn => {
-                self.zoom = (self.zoom * 1.2).min(5.0);
-                self.zoom_text = format!("{:.0}%", self.zoom * 100.0);
-                self.canvas.set_zoom(self.zoom);
-            }
-            Message::ZoomOut => {
-                self.zoom = (self.zoom / 1.2).max(0.1);
-                self.zoom_text = format!("{:.0}%", self.zoom * 100.0);
-                self.canvas.set_zoom(self.zoom);
+            Message::FitOnResizeToggled(enabled) => {
+                self.preferences.fit_on_resize = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
             }
-            Message::ZoomReset => {
-                self.zoom = 1.0;
-                self.zoom_text = "100%".to_string();
-                self.canvas.set_zoom(self.zoom);
+            Message::SplitterPressed(splitter) => {
+                self.splitter_drag = Some(splitter);
             }
-            Message::ZoomToFit => {
-                // Fit the page to the canvas (simplified implementation)
-                self.zoom = 0.5;
-                self.zoom_text = "50%".to_string();
-                self.canvas.set_zoom(self.zoom);
+            Message::SplitterDragged(position) => match self.splitter_drag {
+                // The settings panel is at the right edge, the thumbnails at the bottom
+                Some(Splitter::Settings) => {
+                    self.preferences.settings_panel_width =
+                        clamp_settings_panel_width(self.window_size.width - position.x);
+                }
+                Some(Splitter::Thumbnails) => {
+                    self.preferences.thumbnail_strip_height =
+                        clamp_thumbnail_strip_height(self.window_size.height - position.y);
+                }
+                None => {}
+            },
+            Message::SplitterReleased => {
+                if self.splitter_drag.take().is_some() {
+                    if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                        log::error!("Failed to save panel sizes: {}", e);
+                    }
+                }
             }
             // New settings handlers
             Message::SettingsTabChanged(tab) => {
+                // Only a different tab starts back at the top
+                let scroll = if self.settings_tab != tab {
+                    self.settings_scroll = scrollable::AbsoluteOffset { x: 0.0, y: 0.0 };
+                    scrollable::snap_to(scrollable::Id::new(SETTINGS_SCROLL_ID), scrollable::RelativeOffset::START)
+                } else {
+                    Task::none()
+                };
                 self.settings_tab = tab;
+                if self.preferences.settings_tab != tab {
+                    self.preferences.settings_tab = tab;
+                    if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                        log::error!("Failed to save settings tab preference: {}", e);
+                    }
+                }
+                // The collapsed strip's tab buttons reopen the sidebar
+                if self.preferences.settings_collapsed {
+                    return Task::batch([scroll, self.update(Message::ToggleSettingsPanel)]);
+                }
+                return scroll;
+            }
+            Message::SettingsScrolled(viewport) => {
+                self.settings_scroll = viewport.absolute_offset();
             }
             Message::PrintQualitySelected(quality) => {
                 self.layout.page.print_quality = quality;
-                self.is_modified = true;
+                self.mark_modified();
             }
             Message::OrientationToggled => {
-                // Swap dimensions and toggle orientation
+                // Turn the page, its margins and the images with it
                 let new_orientation = match self.layout.page.orientation {
                     LayoutOrientation::Portrait => LayoutOrientation::Landscape,
                     LayoutOrientation::Landscape => LayoutOrientation::Portrait,
                 };
-                std::mem::swap(&mut self.layout.page.width_mm, &mut self.layout.page.height_mm);
-                self.layout.page.orientation = new_orientation;
+                self.layout.set_orientation(new_orientation);
+                self.sync_margin_inputs();
                 self.canvas.set_layout(self.layout.clone());
-                self.is_modified = true;
+                self.mark_modified();
             }
             Message::BorderlessToggled(enabled) => {
-                self.layout.page.borderless = enabled;
-                if enabled {
-                    self.layout.page.margin_top_mm = 0.0;
-                    self.layout.page.margin_bottom_mm = 0.0;
-                    self.layout.page.margin_left_mm = 0.0;
-                    self.layout.page.margin_right_mm = 0.0;
-                    self.margin_top_input = "0".to_string();
-                    self.margin_bottom_input = "0".to_string();
-                    self.margin_left_input = "0".to_string();
-                    self.margin_right_input = "0".to_string();
-                } else {
-                    self.layout.page.margin_top_mm = 25.4;
-                    self.layout.page.margin_bottom_mm = 25.4;
-                    self.layout.page.margin_left_mm = 25.4;
-                    self.layout.page.margin_right_mm = 25.4;
-                    self.margin_top_input = "25.4".to_string();
-                    self.margin_bottom_input = "25.4".to_string();
-                    self.margin_left_input = "25.4".to_string();
-                    self.margin_right_input = "25.4".to_string();
+                self.layout.page.set_borderless(enabled, self.margins_user_edited);
+                self.sync_margin_inputs();
+                if self.preferences.keep_images_in_printable_area {
+                    let moved = self.layout.keep_images_in_printable_area();
+                    log::info!("Moved {} image(s) into the new printable area", moved);
                 }
                 self.canvas.set_layout(self.layout.clone());
-                self.is_modified = true;
+                self.mark_modified();
+            }
+            Message::PrintScalingSelected(scaling) => {
+                self.layout.page.print_scaling = scaling;
+                self.mark_modified();
+            }
+            Message::ColorModeSelected(mode) => {
+                self.layout.page.color_mode = mode;
+                self.mark_modified();
+            }
+            Message::PageOutputSharpenChanged(amount) => {
+                // Snap to the slider's steps so "off" is exactly 0.0
+                self.layout.page.output_sharpen = (amount * 10.0).round() / 10.0;
+                self.mark_modified();
+            }
+            Message::FooterToggled(enabled) => {
+                self.layout.page.footer.enabled = enabled;
+                self.footer_changed();
+            }
+            Message::FooterPageNumberToggled(enabled) => {
+                self.layout.page.footer.page_number = enabled;
+                self.footer_changed();
+            }
+            Message::FooterDateToggled(enabled) => {
+                self.layout.page.footer.date = enabled;
+                self.footer_changed();
+            }
+            Message::FooterProjectNameChanged(name) => {
+                self.layout.page.footer.project_name = name;
+                self.footer_changed();
+            }
+            Message::FooterPositionSelected(position) => {
+                self.layout.page.footer.position = position;
+                self.footer_changed();
+            }
+            Message::FooterSizeChanged(size_mm) => {
+                self.layout.page.footer.size_mm = (size_mm * 2.0).round() / 2.0;
+                self.footer_changed();
+            }
+            Message::SequenceNumbersToggled(enabled) => {
+                self.layout.page.sequence_numbers.enabled = enabled;
+                self.footer_changed();
+            }
+            Message::CaptionsToggled(enabled) => {
+                self.layout.page.captions = enabled;
+                self.footer_changed();
+            }
+            Message::SequenceOrderSelected(order) => {
+                self.layout.page.sequence_numbers.order = order;
+                self.footer_changed();
+            }
+            Message::SequenceCornerSelected(corner) => {
+                self.layout.page.sequence_numbers.corner = corner;
+                self.footer_changed();
+            }
+            Message::SequenceSizeChanged(size_mm) => {
+                self.layout.page.sequence_numbers.size_mm = (size_mm * 2.0).round() / 2.0;
+                self.footer_changed();
+            }
+            Message::RegistrationMarksSelected(marks) => {
+                self.layout.page.registration_marks = marks;
+                self.footer_changed();
+            }
+            Message::PrintDpiSelected(dpi) => {
+                self.print_dpi = dpi;
+                self.preferences.print_dpi = dpi;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::ExportDpiSelected(dpi) => {
+                self.export_dpi = dpi;
+                self.preferences.export_dpi = dpi;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::ResampleFilterSelected(filter) => {
+                self.preferences.resample_filter = filter;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::ImageResampleFilterSelected(filter) => {
+                if let Some(img) = self.layout.selected_image_mut() {
+                    img.resample_filter = Some(filter);
+                    self.mark_modified();
+                }
+            }
+            Message::ResetImageResampleFilter => {
+                if let Some(img) = self.layout.selected_image_mut() {
+                    img.resample_filter = None;
+                    self.mark_modified();
+                }
+            }
+            Message::ImageOutputSharpenChanged(amount) => {
+                if let Some(img) = self.layout.selected_image_mut() {
+                    img.output_sharpen = Some((amount * 10.0).round() / 10.0);
+                    self.mark_modified();
+                }
+            }
+            Message::ResetImageOutputSharpen => {
+                if let Some(img) = self.layout.selected_image_mut() {
+                    img.output_sharpen = None;
+                    self.mark_modified();
+                }
+            }
+            Message::ToningSelected(toning) => {
+                self.layout.page.toning = toning;
+                self.mark_modified();
+            }
+            Message::WorkspaceBackgroundSelected(background) => {
+                self.preferences.workspace_background = background;
+                let (r, g, b) = background.to_rgb();
+                self.canvas.set_background(Color::from_rgb(r, g, b));
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save workspace background: {}", e);
+                }
+            }
+            Message::KeepImagesInPrintableAreaToggled(enabled) => {
+                self.preferences.keep_images_in_printable_area = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save borderless preference: {}", e);
+                }
+            }
+            Message::AutoPaperFromFirstImageToggled(enabled) => {
+                self.preferences.auto_paper_from_first_image = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save paper preference: {}", e);
+                }
+            }
+            Message::LowGpuToggled(enabled) => {
+                self.preferences.low_gpu = enabled;
+                self.sync_low_gpu();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save low GPU mode: {}", e);
+                }
+            }
+            Message::TouchModeToggled(enabled) => {
+                self.preferences.handle_size_px = if enabled {
+                    TOUCH_HANDLE_SIZE_PX
+                } else {
+                    DEFAULT_HANDLE_SIZE_PX
+                };
+                self.canvas.set_handle_size(self.preferences.handle_size_px);
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save handle size: {}", e);
+                }
+            }
+            Message::SaveNameTemplateChanged(template) => {
+                self.preferences.save_name_template = template;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save file name template: {}", e);
+                }
+            }
+            Message::MemoryBudgetChanged(value) => {
+                if let Ok(mb) = value.trim().parse::<u32>() {
+                    if mb > 0 {
+                        self.preferences.render_memory_budget_mb = mb;
+                        if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                            log::error!("Failed to save memory budget: {}", e);
+                        }
+                    }
+                }
+                self.memory_budget_input = value;
             }
             Message::CopiesChanged(value) => {
                 self.copies_input = value.clone();
                 if let Ok(copies) = value.parse::<u32>() {
-                    if copies >= 1 && copies <= 99 {
+                    if (1..=99).contains(&copies) {
                         self.print_copies = copies;
                     }
                 }
             }
+            Message::PrintPagesChanged(value) => {
+                self.print_pages_input = value;
+            }
+            Message::PreviousPage => {
+                let page = self.document.current_page();
+                if page > 0 {
+                    return self.show_page(page.saturating_sub(self.pages_on_screen()));
+                }
+            }
+            Message::NextPage => {
+                return self.show_page(self.document.current_page() + self.pages_on_screen());
+            }
+            Message::AddPage | Message::RemovePage if self.spread.is_some() => {
+                return self.show_toast("Leave the spread view to add or remove pages".to_string());
+            }
+            Message::AddPage => {
+                // A new page keeps this page's paper and margins
+                let mut page = Layout::new();
+                page.page = self.layout.page.clone();
+                self.store_layout();
+                self.document.add_page(page);
+                self.journal.note(
+                    format!("Added page {}", self.document.current_page() + 1),
+                    chrono::Local::now().time(),
+                );
+                let show = self.show_current_page();
+                self.mark_modified();
+                return show;
+            }
+            Message::RemovePage => {
+                let removed = self.document.current_page() + 1;
+                if let Err(e) = self.document.remove_current() {
+                    return self.show_toast(e);
+                }
+                self.journal.note(format!("Removed page {}", removed), chrono::Local::now().time());
+                let show = self.show_current_page();
+                self.mark_modified();
+                return show;
+            }
+            Message::ToggleSpreadView => {
+                if self.spread.is_some() {
+                    return self.leave_spread_view();
+                }
+                self.store_layout();
+                let Some(spread) = self.document.current_spread() else {
+                    return self.show_toast(format!(
+                        "Page {} has no facing page of the same height to show it with",
+                        self.document.current_page() + 1
+                    ));
+                };
+                self.document.select(spread.first_page);
+                self.set_spread(Some(spread));
+                return self.show_current_page();
+            }
             Message::ThumbnailClicked(id) => {
-                self.layout.selected_image_id = Some(id.clone());
+                self.layout.select(Some(id.clone()));
                 // Update the image input fields to reflect selected image
                 if let Some(img) = self.layout.get_image(&id) {
-                    self.image_width_input = format!("{:.1}", img.width_mm);
-                    self.image_height_input = format!("{:.1}", img.height_mm);
+                    self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+                    self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
                     self.image_opacity_input = format!("{:.0}", img.opacity * 100.0);
+                    self.image_rotation_input = format!("{}", img.rotation_degrees);
+                    self.image_name_input = img.display_name.clone().unwrap_or_default();
                 }
                 self.canvas.set_layout(self.layout.clone());
             }
-            Message::ImageCopiesChanged(_id, _value) => {
-                // Per-image copies (future implementation)
+            Message::EstimateInkClicked => {
+                self.estimating_ink = true;
+                let layout = self.layout.clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || estimate_ink_coverage(&layout).map_err(|e| e.to_string()))
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|result| result)
+                    },
+                    Message::InkEstimated,
+                );
+            }
+            Message::InkEstimated(result) => {
+                // A change since the estimate started makes it stale
+                if !self.estimating_ink {
+                    return Task::none();
+                }
+                self.estimating_ink = false;
+                match result {
+                    Ok(coverage) => self.ink_estimate = Some(coverage),
+                    Err(e) => log::error!("Ink estimate failed: {}", e),
+                }
+            }
+            Message::ToggleImageList => {
+                self.preferences.show_image_list = !self.preferences.show_image_list;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save image list preference: {}", e);
+                }
+            }
+            Message::ToggleViewOnly => {
+                self.view_only = !self.view_only;
+                if self.view_only {
+                    // Keep a crop in progress rather than leaving it half done
+                    self.finish_crop_mode(true);
+                    self.drag_mode = DragMode::None;
+                }
+                self.canvas.set_view_only(self.view_only);
+            }
+            Message::ToggleSettingsPanel => {
+                self.preferences.settings_collapsed = !self.preferences.settings_collapsed;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save settings panel preference: {}", e);
+                }
+            }
+            Message::ImageListPressed(index) => {
+                // Pressing a row selects it and may start a drag to reorder
+                self.list_drag = Some((index, index));
+                if let Some(id) = self.layout.images.get(index).map(|img| img.id.clone()) {
+                    return self.update(Message::ThumbnailClicked(id));
+                }
+            }
+            Message::ImageListHovered(index) => {
+                if let Some((from, _)) = self.list_drag {
+                    self.list_drag = Some((from, index));
+                }
+            }
+            Message::ImageListReleased => {
+                if let Some((from, to)) = self.list_drag.take() {
+                    if from != to && self.layout.move_image(from, to) {
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                    }
+                }
+            }
+            Message::ImageListDragCancelled => {
+                self.list_drag = None;
+            }
+            Message::ImageCopiesChanged(id, value) => {
+                let copies = value.trim().parse::<u32>().ok().filter(|n| (1..=MAX_COPIES).contains(n));
+                match (copies, self.layout.get_image_mut(&id)) {
+                    (Some(copies), Some(image)) => {
+                        self.copies_inputs.remove(&id);
+                        if image.copies != copies {
+                            image.copies = copies;
+                            let sheets_before = self.canvas.overflow_sheets();
+                            self.canvas.set_layout(self.layout.clone());
+                            self.mark_modified();
+                            let sheets = self.canvas.overflow_sheets();
+                            if sheets > sheets_before {
+                                let added = sheets - sheets_before;
+                                return self.show_toast(format!(
+                                    "Created {} more page{} for copies that don't fit",
+                                    added,
+                                    if added == 1 { "" } else { "s" }
+                                ));
+                            }
+                        }
+                    }
+                    // Keep partial or invalid text in the field without applying it
+                    _ => {
+                        self.copies_inputs.insert(id, value);
+                    }
+                }
             }
             // Image manipulation tools
-            Message::RotateImageCW => {
+            Message::BringImageForward => self.restack_selected(Layout::bring_forward),
+            Message::SendImageBackward => self.restack_selected(Layout::send_backward),
+            Message::BringImageToFront => self.restack_selected(Layout::bring_to_front),
+            Message::SendImageToBack => self.restack_selected(Layout::send_to_back),
+            Message::ToggleImageLock => {
                 if let Some(img) = self.layout.selected_image_mut() {
+                    img.locked = !img.locked;
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::RotateImageCW => {
+                if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
                     // Rotate 90° clockwise - swap width and height
                     std::mem::swap(&mut img.width_mm, &mut img.height_mm);
                     img.rotation_degrees = (img.rotation_degrees + 90.0) % 360.0;
                     // Update input fields
-                    self.image_width_input = format!("{:.1}", img.width_mm);
-                    self.image_height_input = format!("{:.1}", img.height_mm);
+                    self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+                    self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+                    self.image_rotation_input = format!("{}", img.rotation_degrees);
                     self.canvas.set_layout(self.layout.clone());
-                    self.is_modified = true;
+                    self.mark_modified();
                 }
             }
             Message::RotateImageCCW => {
-                if let Some(img) = self.layout.selected_image_mut() {
+                if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
                     // Rotate 90° counter-clockwise - swap width and height
                     std::mem::swap(&mut img.width_mm, &mut img.height_mm);
                     img.rotation_degrees = (img.rotation_degrees + 270.0) % 360.0;
                     // Update input fields
-                    self.image_width_input = format!("{:.1}", img.width_mm);
-                    self.image_height_input = format!("{:.1}", img.height_mm);
+                    self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+                    self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+                    self.image_rotation_input = format!("{}", img.rotation_degrees);
                     self.canvas.set_layout(self.layout.clone());
-                    self.is_modified = true;
+                    self.mark_modified();
+                }
+            }
+            Message::ImageRotationChanged(value) => {
+                if let Ok(degrees) = value.trim().parse::<f32>() {
+                    if degrees.is_finite() && self.set_selected_rotation(degrees) {
+                        self.mark_modified();
+                    }
                 }
+                self.image_rotation_input = value;
             }
             Message::FlipImageHorizontal => {
-                if let Some(img) = self.layout.selected_image_mut() {
+                if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
                     img.flip_horizontal = !img.flip_horizontal;
                     self.canvas.set_layout(self.layout.clone());
-                    self.is_modified = true;
+                    self.mark_modified();
                 }
             }
             Message::FlipImageVertical => {
-                if let Some(img) = self.layout.selected_image_mut() {
+                if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
                     img.flip_vertical = !img.flip_vertical;
                     self.canvas.set_layout(self.layout.clone());
-                    self.is_modified = true;
+                    self.mark_modified();
                 }
             }
             Message::ImageOpacityChanged(value) => {
@@ -702,170 +1862,608 @@ impl PrintLayout {
                     if let Some(img) = self.layout.selected_image_mut() {
                         img.opacity = clamped;
                         self.canvas.set_layout(self.layout.clone());
-                        self.is_modified = true;
+                        self.mark_modified();
                     }
                 }
             }
+            Message::ImageNameChanged(value) => {
+                self.image_name_input = value.clone();
+                let name = if value.trim().is_empty() { None } else { Some(value) };
+                if let Some(img) = self.layout.selected_image_mut() {
+                    // Only the label changes; path and caches are untouched
+                    img.display_name = name;
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
             Message::ImageWidthChanged(value) => {
                 self.image_width_input = value.clone();
-                if let Ok(new_width) = value.parse::<f32>() {
-                    if new_width > 0.0 {
-                        if let Some(img) = self.layout.selected_image_mut() {
-                            if self.maintain_aspect_ratio {
-                                let aspect = img.original_height_px as f32 / img.original_width_px as f32;
-                                img.height_mm = new_width * aspect;
-                                self.image_height_input = format!("{:.1}", img.height_mm);
-                            }
-                            img.width_mm = new_width;
-                            self.canvas.set_layout(self.layout.clone());
-                            self.is_modified = true;
+                if let Some(new_width) = self.preferences.measurement_unit.parse_mm(&value) {
+                    if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
+                        if img.lock_aspect {
+                            img.height_mm = new_width / img.display_aspect_ratio();
+                            self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
                         }
+                        img.width_mm = new_width;
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
             }
             Message::ImageHeightChanged(value) => {
                 self.image_height_input = value.clone();
-                if let Ok(new_height) = value.parse::<f32>() {
-                    if new_height > 0.0 {
-                        if let Some(img) = self.layout.selected_image_mut() {
-                            if self.maintain_aspect_ratio {
-                                let aspect = img.original_width_px as f32 / img.original_height_px as f32;
-                                img.width_mm = new_height * aspect;
-                                self.image_width_input = format!("{:.1}", img.width_mm);
-                            }
-                            img.height_mm = new_height;
-                            self.canvas.set_layout(self.layout.clone());
-                            self.is_modified = true;
+                if let Some(new_height) = self.preferences.measurement_unit.parse_mm(&value) {
+                    if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
+                        if img.lock_aspect {
+                            img.width_mm = new_height * img.display_aspect_ratio();
+                            self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
                         }
+                        img.height_mm = new_height;
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
                     }
                 }
             }
+            Message::ImageXChanged(value) => {
+                self.image_x_input = value;
+                self.image_position_changed();
+            }
+            Message::ImageYChanged(value) => {
+                self.image_y_input = value;
+                self.image_position_changed();
+            }
             Message::MaintainAspectRatio(maintain) => {
-                self.maintain_aspect_ratio = maintain;
+                if let Some(img) = self.layout.selected_image_mut() {
+                    if img.lock_aspect != maintain {
+                        img.lock_aspect = maintain;
+                        self.mark_modified();
+                    }
+                }
+            }
+            Message::LockAspectDefaultToggled(enabled) => {
+                self.preferences.lock_aspect_default = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::AutoRotateToFitToggled(enabled) => {
+                self.preferences.auto_rotate_to_fit = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::SnapToGridToggled => {
+                self.preferences.snap_to_grid = !self.preferences.snap_to_grid;
+                self.sync_canvas_view();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save snap preference: {}", e);
+                }
+            }
+            Message::ArrangeRowsSelected(rows) => {
+                self.arrange_grid.0 = rows;
+            }
+            Message::ArrangeColsSelected(cols) => {
+                self.arrange_grid.1 = cols;
+            }
+            Message::ArrangeGrid(rows, cols) => {
+                match arrange_grid(&mut self.layout, rows, cols, ARRANGE_SPACING_MM, self.preferences.auto_rotate_to_fit) {
+                    Ok(left_out) => {
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                        self.sync_tool_fields();
+                        if left_out > 0 {
+                            return self.show_toast(format!(
+                                "{} image(s) didn't fit the {} × {} grid and stayed where they were",
+                                left_out, rows, cols
+                            ));
+                        }
+                    }
+                    Err(e) => return self.show_toast(e),
+                }
+            }
+            Message::AutoArrange => {
+                if self.layout.images.iter().all(|img| img.locked) {
+                    return self.show_toast("There are no unlocked images to arrange".to_string());
+                }
+                let left_out = self
+                    .layout
+                    .auto_arrange(self.preferences.auto_arrange_gutter_mm, self.preferences.auto_rotate_to_fit);
+                let unplaced = left_out
+                    .iter()
+                    .filter_map(|id| self.layout.get_image(id))
+                    .map(|img| (img.id.clone(), img.bounds()))
+                    .collect();
+                self.canvas.set_layout(self.layout.clone());
+                self.canvas.set_unplaced(unplaced);
+                self.mark_modified();
+                self.sync_tool_fields();
+                if !left_out.is_empty() {
+                    return self.show_toast(format!(
+                        "{} image(s) didn't fit and stayed where they were, outlined in red",
+                        left_out.len()
+                    ));
+                }
+            }
+            Message::AutoArrangeGutterChanged(value) => {
+                if let Ok(gutter) = value.trim().parse::<f32>() {
+                    let (min, max) = AUTO_ARRANGE_GUTTER_RANGE_MM;
+                    if (min..=max).contains(&gutter) {
+                        self.preferences.auto_arrange_gutter_mm = gutter;
+                        if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                            log::error!("Failed to save auto arrange gutter: {}", e);
+                        }
+                    }
+                }
+                self.auto_arrange_gutter_input = value;
+            }
+            Message::LayoutTemplateSelected(template) => {
+                self.layout_template = Some(template);
+            }
+            Message::ApplyLayoutTemplate => {
+                let Some(template) = self.layout_template.clone() else {
+                    return Task::none();
+                };
+                match self.layout.apply_template(&template, self.preferences.auto_rotate_to_fit) {
+                    Ok(left_out) => {
+                        if !template.borderless && template.margins.is_some() {
+                            self.margins_user_edited = true;
+                        }
+                        self.sync_margin_inputs();
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                        self.sync_tool_fields();
+                        log::info!("Applied layout template '{}'", template.name);
+                        if left_out > 0 {
+                            return self.show_toast(format!(
+                                "{} image(s) didn't fit the \"{}\" template and stayed where they were",
+                                left_out, template.name
+                            ));
+                        }
+                    }
+                    Err(e) => return self.show_toast(e),
+                }
+            }
+            Message::LayoutTemplateNameChanged(name) => {
+                self.layout_template_name_input = name;
+            }
+            Message::SaveLayoutTemplate => {
+                let name = self.layout_template_name_input.trim().to_string();
+                if name.is_empty() {
+                    return Task::none();
+                }
+                let (rows, cols) = self.arrange_grid;
+                let template = LayoutTemplate::capture(name, &self.layout.page, rows, cols, ARRANGE_SPACING_MM, SizeFit::Fit);
+                self.config_manager.save_layout_template(&mut self.preferences, template.clone());
+                self.layout_template = Some(template);
+                self.layout_template_name_input.clear();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save layout templates: {}", e);
+                }
+            }
+            Message::DeleteLayoutTemplate => {
+                let Some(template) = self.layout_template.take() else {
+                    return Task::none();
+                };
+                self.preferences.layout_templates.retain(|t| t.name != template.name);
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save layout templates: {}", e);
+                }
+            }
+            Message::SuggestPaperSize => {
+                let Some((_, _, width, height)) = self.layout.content_bounds() else {
+                    return self.show_toast("Place something on the page first".to_string());
+                };
+                let current = self.layout.page.paper_size;
+                let candidates = paper_sizes_for_printer(self.printer_capabilities.as_ref(), current);
+                let page = &self.layout.page;
+                // The margins the new paper will get, as in set_paper_size
+                let margin_mm = |paper: PaperSize| {
+                    if page.borderless {
+                        0.0
+                    } else if self.margins_user_edited {
+                        page.margin_top_mm.max(page.margin_bottom_mm).max(page.margin_left_mm).max(page.margin_right_mm)
+                    } else {
+                        paper.default_margin_mm()
+                    }
+                };
+                match PaperSize::smallest_holding(width, height, &candidates, margin_mm) {
+                    Some(suggestion) if suggestion == (current, page.orientation) => {
+                        return self.show_toast(format!("{} is already the smallest paper that holds everything", current));
+                    }
+                    Some(suggestion) => self.paper_suggestion = Some(suggestion),
+                    None => return self.show_toast("No listed paper is large enough for everything placed".to_string()),
+                }
+            }
+            Message::ApplyPaperSuggestion => {
+                if let Some((paper_size, orientation)) = self.paper_suggestion.take() {
+                    self.layout.switch_paper_centered(paper_size, orientation, self.margins_user_edited);
+                    self.sync_margin_inputs();
+                    self.sync_tool_fields();
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::DismissPaperSuggestion => {
+                self.paper_suggestion = None;
+            }
+            Message::GridSizeChanged(value) => {
+                if let Ok(size) = value.trim().parse::<f32>() {
+                    let (min, max) = GRID_SIZE_RANGE_MM;
+                    if (min..=max).contains(&size) {
+                        self.preferences.grid_size_mm = size;
+                        self.sync_canvas_view();
+                        if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                            log::error!("Failed to save grid size: {}", e);
+                        }
+                    }
+                }
+                self.grid_size_input = value;
+            }
+            Message::RotationSnapChanged(value) => {
+                if let Ok(step) = value.trim().parse::<f32>() {
+                    let (min, max) = ROTATION_SNAP_RANGE_DEGREES;
+                    if (min..=max).contains(&step) {
+                        self.preferences.rotation_snap_degrees = step;
+                        if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                            log::error!("Failed to save rotation snap: {}", e);
+                        }
+                    }
+                }
+                self.rotation_snap_input = value;
+            }
+            Message::UnitToggled => {
+                self.preferences.measurement_unit = self.preferences.measurement_unit.toggled();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save measurement unit: {}", e);
+                }
+                self.sync_margin_inputs();
+                self.sync_tool_fields();
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+            }
+            Message::ShortcutPressed(shortcut) => {
+                // Up and Down reach us even while a text field has focus
+                return iced::advanced::widget::operate(iced::advanced::widget::operation::focusable::count())
+                    .map(move |count| Message::ShortcutFocusChecked(shortcut, count.focused.is_some()));
+            }
+            Message::ShortcutFocusChecked(shortcut, field_focused) => {
+                if !field_focused {
+                    return self.run_shortcut(shortcut);
+                }
+            }
+            Message::ToastExpired => {
+                self.toast = None;
+            }
+            Message::CropWhileResizing(enabled) => {
+                self.crop_while_resizing = enabled;
+            }
+            Message::ResetImageCrop => {
+                if let Some(img) = self.layout.selected_image_mut() {
+                    img.crop = None;
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::ImageFrameFitSelected(fit) => {
+                self.finish_crop_mode(false);
+                if let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) {
+                    img.set_frame_fit(fit);
+                    self.sync_tool_fields();
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::ToggleCropMode => {
+                if self.crop_edit.is_some() {
+                    self.finish_crop_mode(true);
+                } else {
+                    self.enter_crop_mode();
+                }
+            }
+            Message::AutoTrimClicked => {
+                let Some(img) = self.layout.selected_image() else {
+                    return Task::none();
+                };
+                let (id, path) = (img.id.clone(), img.path.clone());
+                let threshold = self.preferences.trim_threshold;
+                self.trimming = true;
+                self.canvas.set_busy(true);
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let image = ::image::open(&path).map_err(|e| e.to_string())?;
+                            Ok(trim::detect_white_border(&image.to_rgba8(), threshold))
+                        })
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|result| result)
+                    },
+                    move |result| Message::AutoTrimDetected(id.clone(), result),
+                );
+            }
+            Message::AutoTrimDetected(id, result) => {
+                self.trimming = false;
+                self.canvas.set_busy(false);
+                match result {
+                    Ok(Some(rect)) => {
+                        let selected = self.layout.selected_image_id.as_deref() == Some(id.as_str());
+                        if let Some(img) = self.layout.get_image_mut(&id) {
+                            img.crop_to_source_pixels(rect);
+                            if selected {
+                                self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+                            }
+                            self.canvas.set_layout(self.layout.clone());
+                            self.mark_modified();
+                        }
+                    }
+                    Ok(None) => log::info!("Auto-trim found no white border"),
+                    Err(e) => log::error!("Auto-trim failed: {}", e),
+                }
+            }
+            Message::TrimTransparencyClicked => {
+                let Some(img) = self.layout.selected_image() else {
+                    return Task::none();
+                };
+                let (id, path) = (img.id.clone(), img.path.clone());
+                self.trimming = true;
+                self.canvas.set_busy(true);
+                return detect_transparency_task(id, path);
+            }
+            Message::TransparencyTrimDetected(id, result) => {
+                if self.trimming {
+                    self.trimming = false;
+                    self.canvas.set_busy(false);
+                }
+                match result {
+                    Ok(Some(rect)) => {
+                        if let Some(img) = self.layout.get_image_mut(&id).filter(|img| !img.locked) {
+                            img.trim_to_source_pixels(rect);
+                            if self.layout.selected_image_id.as_deref() == Some(id.as_str()) {
+                                self.sync_tool_fields();
+                            }
+                            self.canvas.set_layout(self.layout.clone());
+                            self.mark_modified();
+                        }
+                    }
+                    Ok(None) => log::info!("No transparent padding to trim"),
+                    Err(e) => log::error!("Transparency trim failed: {}", e),
+                }
+            }
+            Message::TrimTransparentOnAddToggled(enabled) => {
+                self.preferences.trim_transparent_on_add = enabled;
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save preferences: {}", e);
+                }
+            }
+            Message::TrimThresholdChanged(threshold) => {
+                self.preferences.trim_threshold = threshold;
+            }
+            Message::TrimThresholdReleased => {
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save trim threshold: {}", e);
+                }
+            }
+            Message::ScaleImageToFitMedia => {
+                let unlocked = self.layout.selected_image().filter(|img| !img.locked).map(|img| img.id.clone());
+                if let Some(id) = unlocked {
+                    self.layout.scale_image_to_fit(&id);
+                    if let Some(img) = self.layout.get_image(&id) {
+                        self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+                        self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+                    }
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::FitImageToPage => {
+                if let Some(id) = self.layout.selected_image_id.clone() {
+                    if self.layout.fit_image_to_page(&id) {
+                        self.sync_tool_fields();
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                    }
+                }
+            }
+            Message::CenterImage => {
+                if let Some(id) = self.layout.selected_image_id.clone() {
+                    if self.layout.center_image_on_page(&id) {
+                        self.sync_tool_fields();
+                        self.canvas.set_layout(self.layout.clone());
+                        self.mark_modified();
+                    }
+                }
             }
             Message::NewLayout => {
                 self.layout = Layout::new();
+                self.document = Document::new(self.layout.clone());
+                self.set_spread(None);
+                self.margins_user_edited = false;
                 self.canvas.set_layout(self.layout.clone());
+                self.canvas.set_ghosts(Vec::new());
+                self.showing_change_outlines = false;
                 self.current_file = None;
                 self.project = None;
                 self.is_modified = false;
-                self.margin_top_input = "25.4".to_string();
-                self.margin_bottom_input = "25.4".to_string();
-                self.margin_left_input = "25.4".to_string();
-                self.margin_right_input = "25.4".to_string();
+                self.history.reset(&self.document);
+                self.journal.clear();
+                self.sync_margin_inputs();
             }
             Message::PrintersDiscovered(printers) => {
                 self.printers = printers;
+                self.discovering_printers = false;
+                if self.printers.is_empty() {
+                    self.selected_printer = None;
+                    self.printer_capabilities = None;
+                }
                 let printer_to_select = if let Some(default_printer) = self.printers.iter().find(|p| p.is_default) {
                     Some(default_printer.name.clone())
-                } else if let Some(first_printer) = self.printers.first() {
-                    Some(first_printer.name.clone())
                 } else {
-                    None
+                    self.printers.first().map(|first_printer| first_printer.name.clone())
                 };
                 
                 if let Some(printer_name) = printer_to_select {
                     self.selected_printer = Some(printer_name.clone());
                     // Load capabilities for the selected printer
-                    return Task::perform(
-                        async move {
-                            get_printer_capabilities(&printer_name).unwrap_or_default()
-                        },
-                        Message::PrinterCapabilitiesLoaded,
-                    );
+                    return printer_capabilities_task(printer_name);
                 }
             }
+            Message::RetryPrinterDiscovery => {
+                self.discovering_printers = true;
+                return discover_printers_task();
+            }
             Message::PrinterSelected(printer_name) => {
+                if printer_name.is_empty() {
+                    return Task::none();
+                }
                 self.selected_printer = Some(printer_name.clone());
                 // Reset selections when printer changes
                 self.selected_input_slot = None;
                 self.selected_cups_media_type = None;
                 self.selected_cups_color_model = None;
                 self.selected_cups_print_quality = None;
+                self.selected_cups_resolution = None;
+                self.selected_cups_duplex = None;
                 // Load capabilities for the new printer
-                return Task::perform(
-                    async move {
-                        get_printer_capabilities(&printer_name).unwrap_or_default()
-                    },
-                    Message::PrinterCapabilitiesLoaded,
-                );
+                return printer_capabilities_task(printer_name);
             }
             Message::PrinterCapabilitiesLoaded(caps) => {
                 log::info!("Loaded {} options for printer '{}'", caps.options.len(), caps.printer_name);
-                // Set defaults from CUPS
-                if let Some(input_slot) = caps.input_slot() {
-                    self.selected_input_slot = input_slot.current_value().map(String::from);
-                }
-                if let Some(media_type) = caps.media_type() {
-                    self.selected_cups_media_type = media_type.current_value().map(String::from);
-                }
-                if let Some(color_model) = caps.color_model() {
-                    self.selected_cups_color_model = color_model.current_value().map(String::from);
-                }
-                if let Some(print_quality) = caps.print_quality() {
-                    self.selected_cups_print_quality = print_quality.current_value().map(String::from);
-                }
+                // Start every option at the printer default so nothing is sent to lp
+                // until the user explicitly picks a value
+                self.selected_input_slot = None;
+                self.selected_cups_media_type = None;
+                self.selected_cups_color_model = None;
+                self.selected_cups_print_quality = None;
+                self.selected_cups_resolution = None;
+                self.selected_cups_duplex = None;
                 self.printer_capabilities = Some(caps);
             }
-            Message::InputSlotSelected(value) => {
-                self.selected_input_slot = Some(value);
+            Message::InputSlotSelected(choice) => {
+                self.selected_input_slot = choice.value().map(String::from);
+            }
+            Message::CupsMediaTypeSelected(choice) => {
+                self.selected_cups_media_type = choice.value().map(String::from);
+            }
+            Message::CupsColorModelSelected(choice) => {
+                self.selected_cups_color_model = choice.value().map(String::from);
             }
-            Message::CupsMediaTypeSelected(value) => {
-                self.selected_cups_media_type = Some(value);
+            Message::CupsPrintQualitySelected(choice) => {
+                self.selected_cups_print_quality = choice.value().map(String::from);
             }
-            Message::CupsColorModelSelected(value) => {
-                self.selected_cups_color_model = Some(value);
+            Message::CupsResolutionSelected(choice) => {
+                self.selected_cups_resolution = choice.value().map(String::from);
             }
-            Message::CupsPrintQualitySelected(value) => {
-                self.selected_cups_print_quality = Some(value);
+            Message::CupsDuplexSelected(choice) => {
+                self.selected_cups_duplex = choice.value().map(String::from);
             }
             Message::PrintClicked => {
-                if self.layout.images.is_empty() {
+                match self.pages_to_print() {
+                    Ok(pages) if pages.is_empty() => return Task::none(),
+                    Ok(_) => {}
+                    Err(e) => return self.show_toast(e),
+                }
+                // Images larger than the paper would be clipped; ask first
+                if !self.layout.oversized_image_ids().is_empty() {
+                    self.confirm_oversized_print = true;
                     return Task::none();
                 }
-                let printer_name = match &self.selected_printer {
-                    Some(name) => name.clone(),
-                    None => return Task::none(),
-                };
-                
-                // Set status to rendering
-                self.print_status = PrintStatus::Rendering;
-                
-                // Build extra options from CUPS selections
-                let mut extra_options = Vec::new();
-                if let Some(ref slot) = self.selected_input_slot {
-                    extra_options.push(("InputSlot".to_string(), slot.clone()));
+                return self.check_sheet_count();
+            }
+            Message::PrintOversizedAnyway => {
+                self.confirm_oversized_print = false;
+                return self.check_sheet_count();
+            }
+            Message::ScaleOversizedAndPrint => {
+                self.confirm_oversized_print = false;
+                for id in self.layout.oversized_image_ids() {
+                    self.layout.scale_image_to_fit(&id);
                 }
-                if let Some(ref media_type) = self.selected_cups_media_type {
-                    extra_options.push(("MediaType".to_string(), media_type.clone()));
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+                return self.check_sheet_count();
+            }
+            Message::PrintAllSheets => {
+                self.confirm_sheet_count = None;
+                return self.check_render_memory();
+            }
+            Message::CancelSheetsPrint => {
+                self.confirm_sheet_count = None;
+            }
+            Message::CancelOversizedPrint => {
+                self.confirm_oversized_print = false;
+            }
+            Message::PrintOverBudgetAnyway => {
+                self.confirm_memory_print = None;
+                return self.print_document();
+            }
+            Message::CancelOverBudgetPrint => {
+                self.confirm_memory_print = None;
+            }
+            Message::PrintSelectedClicked => {
+                if let Some(img) = self.layout.selected_image() {
+                    self.print_selected_paper =
+                        Some(PaperSize::nearest_photo_size(img.width_mm, img.height_mm));
                 }
-                if let Some(ref color_model) = self.selected_cups_color_model {
-                    extra_options.push(("ColorModel".to_string(), color_model.clone()));
+            }
+            Message::PrintSelectedPaperChosen(paper_size) => {
+                self.print_selected_paper = Some(paper_size);
+            }
+            Message::ConfirmPrintSelected => {
+                let Some(paper_size) = self.print_selected_paper.take() else {
+                    return Task::none();
+                };
+                let Some(img) = self.layout.selected_image() else {
+                    return Task::none();
+                };
+                // Print a temporary one-image layout; the current document is untouched
+                let borderless = self.printer_capabilities
+                    .as_ref()
+                    .map(|caps| caps.supports_borderless())
+                    .unwrap_or(false);
+                let mut single = single_image_layout(img, paper_size, borderless);
+                single.page.paper_type = self.layout.page.paper_type;
+                single.page.print_quality = self.layout.page.print_quality;
+                single.page.color_mode = self.layout.page.color_mode;
+                single.page.toning = self.layout.page.toning;
+                return self.start_print_job(single, self.document.current_page() + 1);
+            }
+            Message::CancelPrintSelected => {
+                self.print_selected_paper = None;
+            }
+            Message::PrintRenderingDone(result) => {
+                // The render is over either way, which also stops the progress ticks
+                if self.render_cancel.take().is_some_and(|token| token.is_cancelled()) {
+                    log::info!("Print job cancelled");
+                    self.pending_usage = None;
+                    self.print_queue.clear();
+                    self.print_status = PrintStatus::Idle;
+                    return Task::none();
                 }
-                if let Some(ref quality) = self.selected_cups_print_quality {
-                    extra_options.push(("cupsPrintQuality".to_string(), quality.clone()));
+                match result {
+                    Ok((job, files)) => {
+                        self.print_status = PrintStatus::Sending;
+                        return Task::perform(
+                            async move { send_to_printer(&job, &files).map_err(|e| e.to_string()) },
+                            Message::PrintJobCompleted,
+                        );
+                    }
+                    Err(error) => {
+                        log::error!("Print job failed: {}", error);
+                        self.pending_usage = None;
+                        self.print_queue.clear();
+                        self.print_status = PrintStatus::Failed(error);
+                    }
                 }
-                
-                let job = PrintJob {
-                    layout: self.layout.clone(),
-                    printer_name,
-                    copies: self.print_copies,
-                    dpi: self.print_dpi,
-                    extra_options,
-                };
-                return Task::perform(
-                    async move {
-                        // Simulate brief delay to show the status
-                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                        match execute_print_job(job) {
-                            Ok(job_id) => Ok(job_id),
-                            Err(e) => Err(e.to_string()),
-                        }
-                    },
-                    Message::PrintJobCompleted,
-                );
             }
             Message::PrintJobCompleted(result) => {
+                if self.render_cancel.take().is_some_and(|token| token.is_cancelled()) {
+                    log::info!("Print job cancelled");
+                    self.pending_usage = None;
+                    self.print_queue.clear();
+                    self.print_status = PrintStatus::Idle;
+                    return Task::none();
+                }
                 match result {
                     Ok(job_id) => {
                         log::info!("Print job submitted: {}", job_id);
@@ -889,6 +2487,9 @@ impl PrintLayout {
                             )),
                             last_success_time: Some(chrono::Utc::now()),
                         };
+                        if let Some(printer) = self.selected_printer.clone() {
+                            self.config_manager.add_recent_printer(&mut self.preferences, printer);
+                        }
                         
                         // Save preferences to disk
                         if let Err(e) = self.config_manager.save_config(&self.preferences) {
@@ -896,13 +2497,167 @@ impl PrintLayout {
                         } else {
                             log::info!("Saved successful print settings");
                         }
+
+                        // Record usage off the UI thread, then go on to the next page
+                        let usage = self.pending_usage.take();
+                        let next_page = self.print_next_page();
+                        if let Some(entry) = usage {
+                            let config_manager = self.config_manager.clone();
+                            let record = Task::perform(
+                                async move {
+                                    tokio::task::spawn_blocking(move || {
+                                        config_manager.append_usage(entry).map_err(|e| e.to_string())
+                                    })
+                                    .await
+                                    .map_err(|e| e.to_string())
+                                    .and_then(|result| result)
+                                },
+                                Message::UsageRecorded,
+                            );
+                            return Task::batch([record, next_page]);
+                        }
+                        return next_page;
                     }
                     Err(error) => {
                         log::error!("Print job failed: {}", error);
+                        self.pending_usage = None;
+                        self.print_queue.clear();
                         self.print_status = PrintStatus::Failed(error);
                     }
                 }
             }
+            Message::RenderProgressTick => {
+                if let Some(token) = &self.render_cancel {
+                    self.render_progress = token.progress();
+                    return render_progress_tick();
+                }
+            }
+            Message::CancelRender => {
+                if let Some(token) = &self.render_cancel {
+                    token.cancel();
+                }
+            }
+            Message::UsageRecorded(result) => {
+                if let Err(e) = result {
+                    log::error!("Failed to record print usage: {}", e);
+                }
+            }
+            Message::OpenPalette => {
+                self.palette_open = true;
+                self.palette_query.clear();
+                self.palette_selected = 0;
+                return text_input::focus(text_input::Id::new(PALETTE_INPUT_ID));
+            }
+            Message::PaletteQueryChanged(query) => {
+                self.palette_query = query;
+                self.palette_selected = 0;
+            }
+            Message::PaletteMove(step) => {
+                let count = self.palette_matches().len();
+                if count > 0 {
+                    self.palette_selected = (self.palette_selected as i32 + step).rem_euclid(count as i32) as usize;
+                }
+            }
+            Message::PaletteActivate => {
+                return self.handle_message(Message::PalettePick(self.palette_selected));
+            }
+            Message::PalettePick(index) => {
+                let Some(item) = self.palette_matches().get(index).map(|entry| entry.item.clone()) else {
+                    return Task::none();
+                };
+                self.palette_open = false;
+                // Route through the same messages as the menus and buttons
+                let message = match item {
+                    PaletteItem::RecentFile(path) => Message::OpenRecentFile(path),
+                    PaletteItem::Preset(index) => Message::ApplyPreset(index),
+                    PaletteItem::ImageStyle(index) => Message::ApplyImageStyle(index),
+                };
+                return self.handle_message(message);
+            }
+            Message::ClosePalette => {
+                self.palette_open = false;
+            }
+            Message::DiagnosePrinting => {
+                self.diagnosing = true;
+                return Task::perform(
+                    async {
+                        tokio::task::spawn_blocking(diagnose).await.unwrap_or_else(|e| {
+                            log::error!("Printing check failed to run: {}", e);
+                            Vec::new()
+                        })
+                    },
+                    |results| Message::PrintingDiagnosed(results, false),
+                );
+            }
+            Message::PrintingDiagnosed(results, startup) => {
+                self.diagnosing = false;
+                for result in &results {
+                    log::info!("Printing check: {} - {:?}: {}", result.check, result.status, result.detail);
+                }
+                if !startup || diagnosis_failed(&results) {
+                    self.diagnostics = Some(results);
+                }
+            }
+            Message::CloseDiagnostics => {
+                self.diagnostics = None;
+            }
+            Message::OpenUsageDialog => {
+                self.usage_dialog_open = true;
+                let config_manager = self.config_manager.clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || config_manager.load_usage().map_err(|e| e.to_string()))
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|result| result)
+                    },
+                    Message::UsageLoaded,
+                );
+            }
+            Message::UsageLoaded(result) => match result {
+                Ok(entries) => {
+                    self.usage_entries = entries;
+                    self.usage_error = None;
+                }
+                Err(e) => {
+                    log::error!("Failed to load usage statistics: {}", e);
+                    self.usage_entries.clear();
+                    self.usage_error = Some(e);
+                }
+            },
+            Message::UsageRangeSelected(range) => {
+                self.usage_range = range;
+            }
+            Message::ExportUsageCsv => {
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_title("Export Usage")
+                            .set_file_name("print-usage.csv")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::UsageCsvPathSelected,
+                );
+            }
+            Message::UsageCsvPathSelected(path) => {
+                if let Some(path) = path {
+                    let start = self.usage_range.start(chrono::Utc::now());
+                    let csv = usage::to_csv(&usage::entries_in_range(&self.usage_entries, start));
+                    if let Err(e) = std::fs::write(&path, csv) {
+                        log::error!("Failed to export usage to {:?}: {}", path, e);
+                        self.usage_error = Some(format!("Could not write {}: {}", path.display(), e));
+                    }
+                }
+            }
+            Message::CloseUsageDialog => {
+                self.usage_dialog_open = false;
+                self.usage_error = None;
+            }
             Message::DismissPrintStatus => {
                 self.print_status = PrintStatus::Idle;
             }
@@ -918,13 +2673,14 @@ impl PrintLayout {
             }
             Message::SaveLayoutAs => {
                 let default_dir = self.preferences.last_open_directory.clone();
+                let file_name = self.preferences.suggested_save_name(chrono::Local::now().naive_local());
                 return Task::perform(
                     async move {
                         rfd::AsyncFileDialog::new()
                             .add_filter("Print Layout", &["pxl"])
                             .set_title("Save Layout As")
                             .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
-                            .set_file_name("layout.pxl")
+                            .set_file_name(file_name)
                             .save_file()
                             .await
                             .map(|f| f.path().to_path_buf())
@@ -954,25 +2710,23 @@ impl PrintLayout {
             }
             Message::LayoutOpenPathSelected(path) => {
                 if let Some(path) = path {
-                    let config_manager = self.config_manager.clone();
-                    return Task::perform(
-                        async move {
-                            match config_manager.load_layout(&path) {
-                                Ok(project) => Ok(project),
-                                Err(e) => Err(e.to_string()),
-                            }
-                        },
-                        Message::LayoutLoaded,
-                    );
+                    return self.load_layout_file(path);
                 }
             }
-            Message::LayoutLoaded(result) => {
+            Message::LayoutLoaded(path, result) => {
                 match result {
                     Ok(project) => {
-                        self.layout = project.layout.clone();
+                        self.document = project.document();
+                        self.set_spread(None);
+                        self.layout = self.document.current().clone();
                         self.canvas.set_layout(self.layout.clone());
+                        self.canvas.set_ghosts(Vec::new());
+                        self.showing_change_outlines = false;
+                        self.sync_margin_inputs();
                         self.project = Some(project);
                         self.is_modified = false;
+                        self.history.reset(&self.document);
+                        self.journal.clear();
                         
                         // Pre-populate thumbnail cache for loaded images
                         for item in &self.layout.images {
@@ -981,33 +2735,587 @@ impl PrintLayout {
                         }
                         
                         // Update recent files
-                        if let Some(path) = &self.current_file {
-                            self.config_manager.add_recent_file(&mut self.preferences, path.clone());
-                            let _ = self.config_manager.save_config(&self.preferences);
-                        }
+                        self.config_manager.add_recent_file(&mut self.preferences, path.clone());
+                        let _ = self.config_manager.save_config(&self.preferences);
+                        self.current_file = Some(path);
                         
                         log::info!("Layout loaded successfully");
+                        return self.load_images_in_background();
                     }
                     Err(error) => {
-                        log::error!("Failed to load layout: {}", error);
+                        // The file that was open stays the one Save writes to
+                        log::error!("Failed to load layout {}: {}", path.display(), error);
                     }
                 }
             }
-            Message::CheckAutoSave => {
-                if self.config_manager.has_auto_save() {
-                    log::info!("Auto-save file detected");
-                    // Show recovery dialog to user
-                    self.show_recovery_dialog = true;
+            Message::CanvasAssetReady(path, result) => {
+                let source = result
+                    .map(|source| Arc::try_unwrap(source).unwrap_or_else(|shared| (*shared).clone()))
+                    .map_err(|e| {
+                        log::error!("Failed to load image {}: {}", path.display(), e);
+                        e
+                    });
+                // Image Tools explains the failure for every image using this file
+                let load_error = source.as_ref().err().cloned();
+                for img in self.layout.images.iter_mut().filter(|img| img.path == path) {
+                    img.load_error = load_error.clone();
+                }
+                self.canvas.source_decoded(path, source);
+                if let Some((done, total)) = self.image_load_progress {
+                    self.image_load_progress = (done + 1 < total).then_some((done + 1, total));
                 }
             }
-            Message::RecoverAutoSave => {
-                self.show_recovery_dialog = false;
-                match self.config_manager.load_auto_save() {
-                    Ok(project) => {
-                        self.layout = project.layout.clone();
-                        self.canvas.set_layout(self.layout.clone());
+            Message::ImportManifestClicked => {
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Placement Manifest", &["csv", "json"])
+                            .set_title("Import Manifest")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ManifestPathSelected,
+                );
+            }
+            Message::ManifestPathSelected(path) => {
+                if let Some(path) = path {
+                    let page = self.layout.page.clone();
+                    return Task::perform(
+                        async move { manifest::import_manifest(&path, page) },
+                        Message::ManifestImported,
+                    );
+                }
+            }
+            Message::ManifestImported(result) => {
+                match result {
+                    Ok(import) => {
+                        log::info!(
+                            "Imported manifest: {} images placed, {} rows skipped",
+                            import.layout.images.len(),
+                            import.errors.len()
+                        );
+                        self.layout = import.layout;
+                        self.document = Document::new(self.layout.clone());
+                        self.set_spread(None);
+                        self.canvas.set_layout(self.layout.clone());
+                        // A manifest is not a project file; saving asks for a new path
+                        self.current_file = None;
+                        self.project = None;
+                        self.mark_modified();
+                        for item in &self.layout.images {
+                            self.thumbnail_cache.entry(item.path.clone())
+                                .or_insert_with(|| iced::widget::image::Handle::from_path(&item.path));
+                        }
+                        self.manifest_errors = import.errors.iter().map(|e| e.to_string()).collect();
+                        return self.load_images_in_background();
+                    }
+                    Err(error) => {
+                        log::error!("Failed to import manifest: {}", error);
+                        self.manifest_errors = vec![error];
+                    }
+                }
+            }
+            Message::OpenConfigDirectory => {
+                let dir = self.config_manager.config_dir().to_path_buf();
+                if let Err(e) = std::process::Command::new("xdg-open").arg(&dir).spawn() {
+                    log::error!("Failed to open {:?}: {}", dir, e);
+                }
+            }
+            Message::DismissConfigRepair => {
+                self.config_repair = None;
+            }
+            Message::DismissImportErrors => {
+                self.import_errors.clear();
+            }
+            Message::ReplaceImageClicked => {
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Images", &["png", "jpg", "jpeg", "gif", "bmp", "webp"])
+                            .set_title("Replace Image")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ReplacementImageSelected,
+                );
+            }
+            Message::ReplacementImageSelected(path) => {
+                let Some(path) = path else {
+                    return Task::none();
+                };
+                // Keep the placement, crop and adjustments; only the file changes
+                match ::image::image_dimensions(&path) {
+                    Ok((width, height)) => {
+                        if let Some(img) = self.layout.selected_image_mut() {
+                            log::info!("Replaced {} with {}", img.path.display(), path.display());
+                            img.path = path.clone();
+                            img.original_width_px = width;
+                            img.original_height_px = height;
+                            img.load_error = None;
+                            // A fixed file at the same path gets decoded again
+                            self.canvas.remove_from_source_cache(&path);
+                            self.thumbnail_cache.insert(path.clone(), iced::widget::image::Handle::from_path(&path));
+                            self.canvas.set_layout(self.layout.clone());
+                            self.mark_modified();
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Failed to read replacement {}: {}", path.display(), e);
+                        self.import_errors = vec![format!("{}: {}", path.display(), e)];
+                    }
+                }
+            }
+            Message::DismissManifestErrors => {
+                self.manifest_errors.clear();
+            }
+            Message::ExportInterchangeClicked => {
+                // The interchange file holds one page
+                let single_page = self.leave_spread_view();
+                let default_dir = self.preferences.last_open_directory.clone();
+                let file_name = format!("{}.xml", self.project_name().unwrap_or_else(|| "layout".to_string()));
+                let dialog = Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Layout Interchange XML", &["xml"])
+                            .set_title("Export Layout as XML")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::InterchangeExportPathSelected,
+                );
+                return Task::batch([single_page, dialog]);
+            }
+            Message::InterchangeExportPathSelected(path) => {
+                if let Some(path) = path {
+                    let project = ProjectLayout::new(self.document().current().clone(), self.project_name().unwrap_or_default());
+                    let message = match config::export_interchange(&project, &path) {
+                        Ok(()) => format!("Exported {}", path.display()),
+                        Err(e) => {
+                            log::error!("Failed to export {:?}: {}", path, e);
+                            format!("Could not write {}: {}", path.display(), e)
+                        }
+                    };
+                    return self.show_toast(message);
+                }
+            }
+            Message::ImportInterchangeClicked => {
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Layout Interchange XML", &["xml"])
+                            .set_title("Import Layout from XML")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::InterchangeImportPathSelected,
+                );
+            }
+            Message::InterchangeImportPathSelected(path) => {
+                if let Some(path) = path {
+                    return Task::perform(
+                        async move {
+                            config::import_interchange(&path).map_err(|e| format!("{}: {}", path.display(), e))
+                        },
+                        Message::InterchangeImported,
+                    );
+                }
+            }
+            Message::InterchangeImported(result) => match result {
+                Ok(import) => {
+                    self.layout = import.layout;
+                    self.document = Document::new(self.layout.clone());
+                    self.set_spread(None);
+                    self.canvas.set_layout(self.layout.clone());
+                    // Not a project file; saving asks for a new path
+                    self.current_file = None;
+                    self.project = (!import.name.is_empty())
+                        .then(|| ProjectLayout::new(self.layout.clone(), import.name));
+                    self.mark_modified();
+                    for item in &self.layout.images {
+                        self.thumbnail_cache.entry(item.path.clone())
+                            .or_insert_with(|| iced::widget::image::Handle::from_path(&item.path));
+                    }
+                    self.interchange_report = import.ignored;
+                    return self.load_images_in_background();
+                }
+                Err(error) => {
+                    log::error!("Failed to import interchange layout: {}", error);
+                    self.interchange_report = vec![error];
+                }
+            },
+            Message::DismissInterchangeReport => {
+                self.interchange_report.clear();
+            }
+            Message::CreateContactSheet => {
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("Contact Sheet from Folder")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .pick_folder()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ContactSheetFolderSelected,
+                );
+            }
+            Message::ContactSheetFolderSelected(dir) => {
+                if let Some(dir) = dir {
+                    let page = self.layout.page.clone();
+                    let auto_rotate = self.preferences.auto_rotate_to_fit;
+                    return Task::perform(
+                        async move {
+                            let paths = image_files_in(&dir)?;
+                            if paths.is_empty() {
+                                return Err(format!("No images found in {}", dir.display()));
+                            }
+                            Ok(build_contact_sheet(&paths, &page, CONTACT_SHEET_COLUMNS, auto_rotate))
+                        },
+                        Message::ContactSheetBuilt,
+                    );
+                }
+            }
+            Message::ContactSheetBuilt(result) => match result {
+                Ok(sheet) => {
+                    log::info!(
+                        "Contact sheet: {} images placed, {} skipped as unreadable, {} over the limit",
+                        sheet.layout.images.len(),
+                        sheet.skipped.len(),
+                        sheet.left_out
+                    );
+                    if sheet.layout.images.is_empty() {
+                        self.import_errors = sheet.skipped.iter().map(|(path, e)| format!("{}: {}", path.display(), e)).collect();
+                    } else if self.layout.images.is_empty() && self.layout.rects.is_empty() {
+                        return self.apply_contact_sheet(sheet);
+                    } else {
+                        // Ask before replacing work on the page
+                        self.pending_contact_sheet = Some(sheet);
+                    }
+                }
+                Err(error) => {
+                    log::error!("Failed to make a contact sheet: {}", error);
+                    return self.show_toast(error);
+                }
+            },
+            Message::ConfirmContactSheet => {
+                if let Some(sheet) = self.pending_contact_sheet.take() {
+                    return self.apply_contact_sheet(sheet);
+                }
+            }
+            Message::CancelContactSheet => {
+                self.pending_contact_sheet = None;
+            }
+            Message::ApplyPreset(index) => {
+                let Some(preset) = self.preferences.presets.get(index).cloned() else {
+                    return Task::none();
+                };
+                let mut warnings = preset.apply_to(&mut self.layout.page);
+                self.print_dpi = preset.dpi;
+                self.margins_user_edited = true;
+                self.sync_margin_inputs();
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+                log::info!("Applied preset '{}'", preset.name);
+
+                // Select the printer only if it is still installed
+                let mut task = Task::none();
+                if let Some(name) = preset.printer_name {
+                    if self.printers.iter().any(|p| p.name == name) {
+                        task = self.update(Message::PrinterSelected(name));
+                    } else {
+                        warnings.push(format!("Printer \"{}\" is not available; the printer was not changed", name));
+                    }
+                }
+                self.preset_warnings = warnings;
+                return task;
+            }
+            Message::OpenPresetsDialog => {
+                self.presets_dialog_open = true;
+            }
+            Message::PresetNameChanged(name) => {
+                self.preset_name_input = name;
+            }
+            Message::SaveCurrentAsPreset => {
+                let name = self.preset_name_input.trim().to_string();
+                if name.is_empty() {
+                    return Task::none();
+                }
+                let preset = PrintPreset::capture(name, self.selected_printer.clone(), &self.layout.page, self.print_dpi);
+                self.config_manager.save_preset(&mut self.preferences, preset);
+                self.preset_name_input.clear();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save presets: {}", e);
+                }
+            }
+            Message::DeletePreset(index) => {
+                if index < self.preferences.presets.len() {
+                    self.preferences.presets.remove(index);
+                    if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                        log::error!("Failed to save presets: {}", e);
+                    }
+                }
+            }
+            Message::ClosePresetsDialog => {
+                self.presets_dialog_open = false;
+            }
+            Message::DismissPresetWarnings => {
+                self.preset_warnings.clear();
+            }
+            Message::ImageStyleNameChanged(name) => {
+                self.image_style_name_input = name;
+            }
+            Message::SaveImageStyle => {
+                let name = self.image_style_name_input.trim().to_string();
+                let Some(img) = self.layout.selected_image() else {
+                    return Task::none();
+                };
+                if name.is_empty() {
+                    return Task::none();
+                }
+                let style = ImageStyle::capture(name, img);
+                self.config_manager.save_image_style(&mut self.preferences, style);
+                self.image_style_name_input.clear();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save image styles: {}", e);
+                }
+            }
+            Message::ApplyImageStyle(index) => {
+                let Some(style) = self.preferences.image_styles.get(index).cloned() else {
+                    return Task::none();
+                };
+                self.finish_crop_mode(false);
+                if let Some(img) = self.layout.selected_image_mut() {
+                    style.apply_to(img);
+                    self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+                    self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+                    self.image_opacity_input = format!("{:.0}", img.opacity * 100.0);
+                    self.image_rotation_input = format!("{}", img.rotation_degrees);
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                    log::info!("Applied image style '{}'", style.name);
+                }
+            }
+            Message::DeleteImageStyle(index) => {
+                if index < self.preferences.image_styles.len() {
+                    self.preferences.image_styles.remove(index);
+                    if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                        log::error!("Failed to save image styles: {}", e);
+                    }
+                }
+            }
+            Message::ShowChangesClicked => {
+                if let Some(path) = self.current_file.clone() {
+                    let config_manager = self.config_manager.clone();
+                    return Task::perform(
+                        async move { config_manager.load_layout(&path).map_err(|e| e.to_string()) },
+                        Message::SavedLayoutLoadedForDiff,
+                    );
+                }
+            }
+            Message::SavedLayoutLoadedForDiff(result) => match result {
+                Ok(saved) => {
+                    let saved = saved.document();
+                    let page = saved.pages().get(self.document.current_page()).unwrap_or(saved.current());
+                    // On a spread this compares its left page, which lies where it is on screen
+                    self.layout_changes = Some(self.document().current().diff(page));
+                }
+                Err(error) => {
+                    log::error!("Failed to load saved layout for comparison: {}", error);
+                }
+            },
+            Message::OutlineChangesOnCanvas => {
+                if let Some(changes) = self.layout_changes.take() {
+                    self.canvas
+                        .set_ghosts(changes.iter().filter_map(|c| c.previous_bounds()).collect());
+                    self.showing_change_outlines = true;
+                }
+            }
+            Message::HideChangeOutlines => {
+                self.canvas.set_ghosts(Vec::new());
+                self.showing_change_outlines = false;
+            }
+            Message::CloseChanges => {
+                self.layout_changes = None;
+            }
+            Message::ExportClicked => {
+                // Exports write one sheet, the page on screen
+                self.export_dialog_open = true;
+                return self.leave_spread_view();
+            }
+            Message::ExportFormatSelected(format) => {
+                self.export_format = format;
+            }
+            Message::ExportIncludeIndexToggled(include) => {
+                self.export_include_index = include;
+            }
+            Message::CancelExport => {
+                self.export_dialog_open = false;
+            }
+            Message::ConfirmExport => {
+                self.export_dialog_open = false;
+                let format = self.export_format;
+                let default_dir = self.preferences.last_open_directory.clone();
+                let file_name = format!("layout.{}", format.extension());
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter(format.to_string(), &[format.extension()])
+                            .set_title("Export Layout")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ExportPathSelected,
+                );
+            }
+            Message::ExportPathSelected(path) => {
+                if let Some(path) = path {
+                    let layout = self.document().current().clone();
+                    let dpi = self.export_dpi;
+                    let filter = self.preferences.resample_filter;
+                    let format = self.export_format;
+                    let include_index = self.export_include_index;
+                    let token = self.start_render_progress();
+                    self.exporting = true;
+                    let export = Task::perform(
+                        async move {
+                            printing::export_layout(&layout, dpi, filter, format, &path, Some(&token))
+                                .map_err(|e| e.to_string())?;
+                            if include_index {
+                                printing::write_layout_index(&layout, &printing::index_path_for(&path))
+                                    .map_err(|e| e.to_string())?;
+                            }
+                            Ok::<PathBuf, String>(path)
+                        },
+                        Message::ExportCompleted,
+                    );
+                    return Task::batch([export, render_progress_tick()]);
+                }
+            }
+            Message::ExportCompleted(result) => {
+                self.exporting = false;
+                if self.render_cancel.take().is_some_and(|token| token.is_cancelled()) {
+                    log::info!("Export cancelled");
+                    return Task::none();
+                }
+                if let Err(error) = &result {
+                    log::error!("Export failed: {}", error);
+                }
+                self.export_result = Some(result);
+            }
+            Message::DismissExportResult => {
+                self.export_result = None;
+            }
+            Message::ExportProcessedClicked => {
+                self.processed_dialog_open = true;
+            }
+            Message::ProcessedFormatSelected(format) => {
+                self.processed_format = format;
+            }
+            Message::ProcessedSelectedOnlyToggled(selected_only) => {
+                self.processed_selected_only = selected_only;
+            }
+            Message::CancelExportProcessed => {
+                self.processed_dialog_open = false;
+            }
+            Message::ConfirmExportProcessed => {
+                self.processed_dialog_open = false;
+                let default_dir = self.preferences.last_open_directory.clone();
+                return Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .set_title("Export Processed Images To")
+                            .set_directory(default_dir.unwrap_or_else(|| PathBuf::from(".")))
+                            .pick_folder()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ProcessedFolderSelected,
+                );
+            }
+            Message::ProcessedFolderSelected(folder) => {
+                if let Some(folder) = folder {
+                    return self.export_processed_images(folder);
+                }
+            }
+            Message::ProcessedImageExported(result) => {
+                if let Some(export) = self.processed_export.as_mut() {
+                    match result {
+                        Ok(path) => {
+                            log::info!("Exported processed image {}", path.display());
+                            export.written += 1;
+                        }
+                        Err(e) => {
+                            log::error!("Failed to export processed image: {}", e);
+                            export.skipped.push(e);
+                        }
+                    }
+                }
+            }
+            Message::DismissProcessedExport => {
+                self.processed_export = None;
+            }
+            Message::FirstFrameShown => {
+                if let Some(started) = self.startup.take() {
+                    log::info!("Startup: first frame shown after {:?}", started.elapsed());
+                }
+                // Work that touches the disk waits until the window is up
+                let recent_files = self.preferences.recent_files.clone();
+                return Task::batch([
+                    Task::done(Message::CheckAutoSave),
+                    Task::perform(
+                        blocking_with_timeout("Recent files check", STARTUP_CHECK_TIMEOUT, move || {
+                            recent_files.into_iter().filter(|path| !path.exists()).collect::<Vec<_>>()
+                        }),
+                        |missing| Message::MissingRecentFilesFound(missing.unwrap_or_default()),
+                    ),
+                ]);
+            }
+            Message::CheckAutoSave => {
+                let config_manager = self.config_manager.clone();
+                return Task::perform(
+                    blocking_with_timeout("Auto-save check", STARTUP_CHECK_TIMEOUT, move || config_manager.has_auto_save()),
+                    |found| Message::AutoSaveChecked(found.unwrap_or(false)),
+                );
+            }
+            Message::AutoSaveChecked(found) => {
+                if found {
+                    log::info!("Auto-save file detected");
+                    // Show recovery dialog to user
+                    self.show_recovery_dialog = true;
+                }
+            }
+            Message::MissingRecentFilesFound(missing) => {
+                if !missing.is_empty() {
+                    log::info!("{} recent file(s) not found: {:?}", missing.len(), missing);
+                }
+                self.missing_recent_files = missing;
+            }
+            Message::RecoverAutoSave => {
+                self.show_recovery_dialog = false;
+                match self.config_manager.load_auto_save() {
+                    Ok(project) => {
+                        self.document = project.document();
+                        self.set_spread(None);
+                        self.layout = self.document.current().clone();
+                        self.canvas.set_layout(self.layout.clone());
                         self.project = Some(project);
-                        self.is_modified = true;
+                        self.mark_modified();
                         
                         // Pre-populate thumbnail cache for recovered images
                         for item in &self.layout.images {
@@ -1017,6 +3325,7 @@ impl PrintLayout {
                         
                         let _ = self.config_manager.delete_auto_save();
                         log::info!("Recovered from auto-save");
+                        return self.load_images_in_background();
                     }
                     Err(e) => {
                         log::error!("Failed to recover auto-save: {}", e);
@@ -1033,7 +3342,7 @@ impl PrintLayout {
                     self.auto_save_counter += 1;
                     // Auto-save every N ticks (this would be time-based in real impl)
                     if self.auto_save_counter >= 10 {
-                        let _ = self.config_manager.auto_save(&self.layout);
+                        let _ = self.config_manager.auto_save(&self.document());
                         self.auto_save_counter = 0;
                     }
                 }
@@ -1049,21 +3358,7 @@ impl PrintLayout {
                 self.show_recent_files_menu = false;
                 // Check if file exists
                 if path.exists() {
-                    let path_clone = path.clone();
-                    return Task::perform(
-                        async move {
-                            match std::fs::read_to_string(&path_clone) {
-                                Ok(contents) => {
-                                    match serde_json::from_str::<ProjectLayout>(&contents) {
-                                        Ok(project) => Ok(project),
-                                        Err(e) => Err(format!("Failed to parse layout: {}", e)),
-                                    }
-                                }
-                                Err(e) => Err(format!("Failed to read file: {}", e)),
-                            }
-                        },
-                        Message::LayoutLoaded,
-                    );
+                    return self.load_layout_file(path);
                 } else {
                     // Remove from recent files if it no longer exists
                     self.preferences.recent_files.retain(|p| p != &path);
@@ -1074,854 +3369,5266 @@ impl PrintLayout {
             Message::ToggleRecentFilesMenu => {
                 self.show_recent_files_menu = !self.show_recent_files_menu;
             }
+            Message::ToggleViewMenu => {
+                self.show_view_menu = !self.show_view_menu;
+            }
+            Message::DraftPreviewToggled => {
+                let quality = self.preferences.view_options.render_quality.toggled();
+                self.preferences.view_options.render_quality = quality;
+                self.sync_canvas_view();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save view options: {}", e);
+                }
+                return self.show_toast(match quality {
+                    CanvasQuality::Draft => "Draft preview - press Q for full quality".to_string(),
+                    CanvasQuality::Full => "Full quality preview".to_string(),
+                });
+            }
+            Message::Undo => match self.history.undo() {
+                Some(document) => return self.restore_document(document),
+                None => return self.show_toast("Nothing to undo".to_string()),
+            },
+            Message::Redo => match self.history.redo() {
+                Some(document) => return self.restore_document(document),
+                None => return self.show_toast("Nothing to redo".to_string()),
+            },
+            Message::DuplicateSelection => {
+                if !self.layout.duplicate_selection(DUPLICATE_OFFSET_MM).is_empty() {
+                    self.sync_tool_fields();
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                }
+            }
+            Message::ToggleShortcutHelp => {
+                self.show_shortcut_help = !self.show_shortcut_help;
+            }
+            Message::ToggleJournal => {
+                self.show_journal = !self.show_journal;
+            }
+            Message::ViewOptionToggled(option) => {
+                let enabled = self.preferences.view_options.get_mut(option);
+                *enabled = !*enabled;
+                self.sync_canvas_view();
+                if let Err(e) = self.config_manager.save_config(&self.preferences) {
+                    log::error!("Failed to save view options: {}", e);
+                }
+            }
         }
         Task::none()
     }
 
-    fn save_layout_to_file(&mut self, path: PathBuf) -> Task<Message> {
-        // Create or update project
-        let project = match &mut self.project {
-            Some(proj) => {
-                proj.layout = self.layout.clone();
-                proj.update_modified();
-                proj.clone()
+    /// Apply the typed margins, or flag every field that is rejected. A
+    /// colliding pair flags both of its fields and leaves the last valid
+    /// margins on the page.
+    fn margin_input_changed(&mut self, side: MarginSide, value: String) {
+        match side {
+            MarginSide::Top => self.margin_top_input = value,
+            MarginSide::Bottom => self.margin_bottom_input = value,
+            MarginSide::Left => self.margin_left_input = value,
+            MarginSide::Right => self.margin_right_input = value,
+        }
+        let unit = self.preferences.measurement_unit;
+        let parsed = [
+            &self.margin_top_input,
+            &self.margin_bottom_input,
+            &self.margin_left_input,
+            &self.margin_right_input,
+        ]
+        .map(|input| input.trim().parse::<f32>().ok().map(|value| unit.to_mm(value)));
+
+        self.margin_errors = Default::default();
+        let [Some(top), Some(bottom), Some(left), Some(right)] = parsed else {
+            for (error, value) in self.margin_errors.iter_mut().zip(parsed) {
+                if value.is_none() {
+                    *error = Some(format!("Enter a margin in {}", unit.name()));
+                }
             }
-            None => {
-                let name = path.file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Unnamed")
-                    .to_string();
-                ProjectLayout::new(self.layout.clone(), name)
+            return;
+        };
+        match self.layout.page.try_set_margins(top, bottom, left, right) {
+            Ok(()) => {
+                self.margins_user_edited = true;
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
             }
+            Err(e) => {
+                for side in e.sides() {
+                    self.margin_errors[*side as usize] = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Write each image to export (the selected one, or all of them) into
+    /// `folder` as it would print, one background task per image. Images
+    /// whose source file is gone are skipped and reported.
+    fn export_processed_images(&mut self, folder: PathBuf) -> Task<Message> {
+        let images: Vec<&PlacedImage> = match self.layout.selected_image() {
+            Some(selected) if self.processed_selected_only => vec![selected],
+            _ => self.layout.images.iter().collect(),
         };
+        let names = printing::processed_file_names(&images, self.processed_format);
+        let mut export = ProcessedExport { total: images.len(), ..ProcessedExport::default() };
+        let mut tasks = Vec::new();
+        for (img, name) in images.into_iter().zip(names) {
+            if !img.path.exists() {
+                export.skipped.push(format!("{}: source file is missing", img.display_label()));
+                continue;
+            }
+            let img = img.clone();
+            let page = self.layout.page.clone();
+            let (dpi, format) = (self.export_dpi, self.processed_format);
+            let filter = self.preferences.resample_filter;
+            let path = folder.join(name);
+            tasks.push(Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        printing::export_processed_image(&img, &page, dpi, filter, format, &path)
+                            .map(|()| path)
+                            .map_err(|e| format!("{}: {}", img.display_label(), e))
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+                },
+                Message::ProcessedImageExported,
+            ));
+        }
+        self.processed_export = Some(export);
+        Task::batch(tasks)
+    }
 
-        // Save to file
-        match self.config_manager.save_layout(&project, &path) {
-            Ok(_) => {
-                // Update recent files before setting current_file
-                self.config_manager.add_recent_file(&mut self.preferences, path.clone());
-                
-                // Update last open directory
-                if let Some(parent) = path.parent() {
-                    self.preferences.last_open_directory = Some(parent.to_path_buf());
+    /// Replace the layout with a contact sheet, listing files it had to skip
+    fn apply_contact_sheet(&mut self, sheet: ContactSheet) -> Task<Message> {
+        self.layout = sheet.layout;
+        self.document = Document::new(self.layout.clone());
+        self.set_spread(None);
+        self.canvas.set_layout(self.layout.clone());
+        // A contact sheet is not a project file; saving asks for a new path
+        self.current_file = None;
+        self.project = None;
+        self.mark_modified();
+        for item in &self.layout.images {
+            self.thumbnail_cache.entry(item.path.clone())
+                .or_insert_with(|| iced::widget::image::Handle::from_path(&item.path));
+        }
+        self.import_errors = sheet.skipped.iter().map(|(path, e)| format!("{}: {}", path.display(), e)).collect();
+        let load = self.load_images_in_background();
+        if sheet.left_out > 0 {
+            let note = format!("{} more image(s) didn't fit on the sheet", sheet.left_out);
+            return Task::batch([load, self.show_toast(note)]);
+        }
+        load
+    }
+
+    /// Read a saved layout off the UI thread. Loading checks the margins and
+    /// stacking order the same way for every way of opening a file, and the
+    /// file becomes the current one only once it has loaded.
+    fn load_layout_file(&self, path: PathBuf) -> Task<Message> {
+        let config_manager = self.config_manager.clone();
+        Task::perform(
+            async move {
+                let result = config_manager.load_layout(&path).map_err(|e| e.to_string());
+                (path, result)
+            },
+            |(path, result)| Message::LayoutLoaded(path, result),
+        )
+    }
+
+    /// Decode (and downscale) the layout's new images off the UI thread. The
+    /// canvas draws placeholders until each `CanvasAssetReady` arrives.
+    fn load_images_in_background(&mut self) -> Task<Message> {
+        // Files that already failed aren't decoded again, so images opened
+        // or added since then pick up the earlier reason here
+        for img in self.layout.images.iter_mut().filter(|img| img.load_error.is_none()) {
+            img.load_error = self.canvas.source_failure(&img.path);
+        }
+        let paths = self.canvas.mark_uncached_pending();
+        if paths.is_empty() {
+            return Task::none();
+        }
+        // Images queued while others are still loading join the same progress count
+        let (done, total) = self.image_load_progress.unwrap_or((0, 0));
+        self.image_load_progress = Some((done, total + paths.len()));
+        Task::batch(paths.into_iter().map(|path| {
+            Task::perform(
+                async move {
+                    let decode_path = path.clone();
+                    let result = tokio::task::spawn_blocking(move || prepare_canvas_source(&decode_path))
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|decoded| decoded.map(Arc::new));
+                    (path, result)
+                },
+                |(path, result)| Message::CanvasAssetReady(path, result),
+            )
+        }))
+    }
+
+    /// Measure the transparent padding of newly added graphics off the UI
+    /// thread; each result crops its image to the visible artwork
+    fn trim_new_transparent_images(&mut self) -> Task<Message> {
+        Task::batch(
+            self.pending_transparency_trims
+                .drain(..)
+                .map(|(id, path)| detect_transparency_task(id, path)),
+        )
+    }
+
+    /// Add images from the file dialog, a drop or a paste. When the user wants
+    /// to be asked for a print size, they wait in the placement dialog.
+    fn add_images(&mut self, placement: PendingPlacement) {
+        if !self.preferences.ask_size_when_adding {
+            self.place_images(placement, None);
+            return;
+        }
+        match &mut self.pending_placement {
+            // More files dropped while the dialog is open join the same batch
+            Some(pending) => pending.paths.extend(placement.paths),
+            None => self.pending_placement = Some(placement),
+        }
+    }
+
+    /// Load and place images, at `size` if one was chosen
+    fn place_images(&mut self, placement: PendingPlacement, size: Option<PrintSize>) {
+        let was_empty = self.layout.images.is_empty();
+        let page = self.layout.page.clone();
+        let mut index = placement.first_index;
+        for path in placement.paths {
+            let Some(id) = self.add_image_file(path) else {
+                continue;
+            };
+            if let Some(img) = self.layout.get_image_mut(&id) {
+                place_new_image(img, &page, size, placement.anchor, index);
+            }
+            index += 1;
+        }
+        // An explicit print size wins over matching the paper to the photo
+        if was_empty && size.is_none() && self.preferences.auto_paper_from_first_image && self.spread.is_none() {
+            if let Some(first_id) = self.layout.images.first().map(|img| img.id.clone()) {
+                self.layout.fit_paper_to_image(&first_id, self.margins_user_edited);
+                self.sync_margin_inputs();
+            }
+        }
+        self.canvas.set_layout(self.layout.clone());
+        self.mark_modified();
+    }
+
+    /// Print size entered in the placement dialog, if it is valid
+    fn placement_size(&self) -> Option<PrintSize> {
+        Some(PrintSize {
+            width_mm: self.placement_unit.parse_mm(&self.placement_width_input)?,
+            height_mm: self.placement_unit.parse_mm(&self.placement_height_input)?,
+            fit: self.placement_fit,
+        })
+    }
+
+    /// Open an image file and add it to the layout at the default position.
+    /// Returns the new image's id, or None if the file couldn't be read.
+    fn add_image_file(&mut self, path: PathBuf) -> Option<String> {
+        match ::image::open(&path) {
+            Ok(img) => {
+                let (width, height) = img.dimensions();
+                let mut placed_image = PlacedImage::new(path.clone(), width, height);
+                placed_image.lock_aspect = self.preferences.lock_aspect_default;
+                let id = placed_image.id.clone();
+                self.layout.add_image(placed_image);
+                if self.preferences.trim_transparent_on_add && img.color().has_alpha() {
+                    self.pending_transparency_trims.push((id.clone(), path.clone()));
                 }
-                
-                self.current_file = Some(path);
-                self.project = Some(project);
-                self.is_modified = false;
-                
-                let _ = self.config_manager.save_config(&self.preferences);
-                log::info!("Layout saved successfully");
+                // Cache the thumbnail handle
+                let handle = iced::widget::image::Handle::from_path(&path);
+                self.thumbnail_cache.insert(path.clone(), handle);
+                log::info!("Added image: {} ({}x{})", path.display(), width, height);
+                Some(id)
             }
             Err(e) => {
-                log::error!("Failed to save layout: {}", e);
+                log::error!("Failed to load image {}: {}", path.display(), e);
+                self.import_errors.push(format!("{}: {}", path.display(), e));
+                None
             }
         }
-        
-        Task::none()
     }
 
-    fn view(&self) -> Element<'_, Message> {
-        // ====================================================================
-        // A: STORED SETTINGS AREA (Top bar with printer and file operations)
-        // ====================================================================
-        let printer_picker = if !self.printers.is_empty() {
-            let printer_names: Vec<String> = self.printers.iter().map(|p| p.name.clone()).collect();
-            pick_list(printer_names, self.selected_printer.clone(), Message::PrinterSelected)
-                .width(Length::Fixed(200.0))
-        } else {
-            pick_list(vec!["No printers found".to_string()], Some("No printers found".to_string()), |_| Message::PrinterSelected("".to_string()))
-                .width(Length::Fixed(200.0))
+    /// Start interactive cropping of the selected image
+    fn enter_crop_mode(&mut self) {
+        if let Some(img) = self.layout.selected_image() {
+            let edit = CropEdit::new(img);
+            self.canvas.set_crop_edit(Some(edit.clone()));
+            self.crop_edit = Some(edit);
+        }
+    }
+
+    /// Leave crop mode, keeping the edited crop or restoring the original
+    fn finish_crop_mode(&mut self, commit: bool) {
+        let Some(edit) = self.crop_edit.take() else {
+            return;
         };
+        self.drag_mode = DragMode::None;
+        self.canvas.set_crop_edit(None);
+        if commit {
+            let crop = (!edit.crop.is_full()).then_some(edit.crop);
+            if crop != edit.original {
+                if let Some(img) = self.layout.get_image_mut(&edit.image_id) {
+                    img.crop = crop;
+                }
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+            }
+        }
+    }
 
-        // Build recent files button with indicator
-        let recent_btn_text = if self.preferences.recent_files.is_empty() {
-            "Recent".to_string()
+    /// Image Tools content while a color block is selected
+    fn color_block_tools(&self, rect: &PlacedRect) -> Element<'_, Message> {
+        let swatches = COLOR_BLOCK_SWATCHES.iter().fold(row![].spacing(4), |row, &color| {
+            let [r, g, b, a] = color;
+            let fill = Color::from_rgba8(r, g, b, a as f32 / 255.0);
+            let selected = rect.color == color;
+            row.push(
+                button(Space::new(Length::Fixed(16.0), Length::Fixed(16.0)))
+                    .on_press(Message::ColorBlockColorPicked(color))
+                    .padding(0)
+                    .style(move |_theme: &Theme, _status| button::Style {
+                        background: Some(fill.into()),
+                        border: iced::Border {
+                            color: if selected { Color::from_rgb(0.0, 0.5, 1.0) } else { Color::from_rgb(0.6, 0.6, 0.6) },
+                            width: if selected { 2.0 } else { 1.0 },
+                            radius: 2.0.into(),
+                        },
+                        ..button::Style::default()
+                    }),
+            )
+        });
+        let max_radius = (rect.width_mm.min(rect.height_mm) / 2.0).max(1.0);
+
+        column![
+            text("Color Block").size(12),
+            text(format!("{:.1} × {:.1} mm", rect.width_mm, rect.height_mm)).size(10),
+            Space::with_height(Length::Fixed(10.0)),
+            text("Color").size(12),
+            swatches,
+            row![
+                text("Hex").size(10),
+                text_input("#RRGGBB", &self.color_block_hex_input)
+                    .on_input(Message::ColorBlockHexChanged)
+                    .size(11)
+                    .width(Length::Fixed(100.0)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            Space::with_height(Length::Fixed(10.0)),
+            text(format!("Corner radius: {:.1} mm", rect.effective_corner_radius())).size(12),
+            slider(0.0..=max_radius, rect.effective_corner_radius(), Message::ColorBlockCornerRadiusChanged)
+                .step(0.5),
+            Space::with_height(Length::Fixed(10.0)),
+            text("Drag the block on the canvas to move it;\ndrag its handles to resize it.").size(10),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// Record that the document differs from its saved copy. Only edits to
+    /// persisted layout state call this; selection, zoom and tabs do not.
+    fn mark_modified(&mut self) {
+        self.is_modified = true;
+        self.ink_estimate = None;
+        self.estimating_ink = false;
+        if self.store_layout() {
+            // Show the parts of whatever was split at the fold
+            if let Some(joined) = self.document.spread_layout() {
+                self.layout = joined;
+                self.canvas.set_layout(self.layout.clone());
+            }
+        }
+        // Margins move the spread's printable areas
+        if self.spread.is_some() {
+            self.set_spread(self.document.current_spread());
+        }
+        // Adding or removing a page notes itself in the journal
+        let previous = self.history.current();
+        if previous.page_count() == self.document.page_count() {
+            let first = self.document.current_page();
+            for index in first..first + self.pages_on_screen() {
+                self.journal.record(&previous.pages()[index], &self.document.pages()[index], chrono::Local::now().time());
+            }
+        }
+        self.history.record(&self.document);
+    }
+
+    /// Collapsible list of this session's changes, below the settings
+    fn journal_panel(&self) -> Element<'_, Message> {
+        let arrow = if self.show_journal { "▾" } else { "▸" };
+        let header = button(text(format!("{} Session changes ({})", arrow, self.journal.len())).size(12))
+            .on_press(Message::ToggleJournal)
+            .style(button::text)
+            .padding(Padding::from([2, 0]));
+        if !self.show_journal {
+            return header.into();
+        }
+        let entries: Element<'_, Message> = if self.journal.is_empty() {
+            text("No changes since the layout was opened or saved")
+                .size(11)
+                .color(Color::from_rgb(0.45, 0.45, 0.45))
+                .into()
         } else {
-            format!("Recent ({})", self.preferences.recent_files.len())
+            let rows = self.journal.newest_first().fold(column![].spacing(2), |col, entry| {
+                col.push(
+                    row![
+                        text(entry.time.format("%H:%M:%S").to_string())
+                            .size(11)
+                            .color(Color::from_rgb(0.45, 0.45, 0.45)),
+                        text(entry.text.as_str()).size(11),
+                    ]
+                    .spacing(6),
+                )
+            });
+            scrollable(rows).height(Length::Shrink).into()
         };
-        let recent_button = if self.preferences.recent_files.is_empty() {
-            button(text(recent_btn_text).size(12))
+        column![horizontal_rule(1), header, container(entries).max_height(180.0)]
+            .spacing(4)
+            .into()
+    }
+
+    /// Show a layout from the undo history without recording it again
+    fn restore_document(&mut self, document: Document) -> Task<Message> {
+        self.store_layout();
+        let previous = std::mem::replace(&mut self.document, document);
+        let now = chrono::Local::now().time();
+        if previous.page_count() == self.document.page_count() {
+            self.journal.record(&previous.pages()[self.document.current_page()], self.document.current(), now);
         } else {
-            button(text(recent_btn_text).size(12)).on_press(Message::ToggleRecentFilesMenu)
-        };
+            self.journal.note(format!("Pages: {} → {}", previous.page_count(), self.document.page_count()), now);
+        }
+        self.is_modified = true;
+        self.ink_estimate = None;
+        self.estimating_ink = false;
+        self.show_current_page()
+    }
 
-        let stored_settings_area = row![
-            text("Printer:").size(14),
-            printer_picker,
-            Space::with_width(Length::Fixed(20.0)),
-            button("New").on_press(Message::NewLayout),
-            button("Open").on_press(Message::OpenLayoutClicked),
-            recent_button,
-            button("Save").on_press(Message::SaveLayoutClicked),
-            button("Save As").on_press(Message::SaveLayoutAs),
-        ]
-        .spacing(10)
-        .padding(10)
-        .align_y(Alignment::Center);
+    /// The whole project, with the page or spread on screen as edited so far
+    fn document(&self) -> Document {
+        let mut document = self.document.clone();
+        if self.spread.is_some() {
+            document.set_spread(&self.layout);
+        } else {
+            document.set_current(self.layout.clone());
+        }
+        document
+    }
 
-        // ====================================================================
-        // D: TOOLS AREA (Toolbar with zoom, orientation, add/delete)
-        // ====================================================================
-        let delete_button = if self.layout.selected_image_id.is_some() {
-            button(row![text("X").size(14), text(" Delete").size(12)].align_y(Alignment::Center))
-                .on_press(Message::DeleteImageClicked)
+    /// Write the layout on screen back into the document: the current page,
+    /// or both pages of the spread. True when something reaching across the
+    /// fold was split into one part per page.
+    fn store_layout(&mut self) -> bool {
+        if self.spread.is_some() {
+            self.document.set_spread(&self.layout)
         } else {
-            button(row![text("X").size(14), text(" Delete").size(12)].align_y(Alignment::Center))
+            self.document.set_current(self.layout.clone());
+            false
+        }
+    }
+
+    /// How many pages the canvas shows: two in the spread view
+    fn pages_on_screen(&self) -> usize {
+        if self.spread.is_some() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Show two facing pages side by side, or with `None` a single page
+    fn set_spread(&mut self, spread: Option<Spread>) {
+        self.canvas.set_spread(spread.clone());
+        self.spread = spread;
+    }
+
+    /// Show the left page of the spread on its own again, keeping the edits
+    fn leave_spread_view(&mut self) -> Task<Message> {
+        if self.spread.is_none() {
+            return Task::none();
+        }
+        self.store_layout();
+        self.set_spread(None);
+        self.show_current_page()
+    }
+
+    /// Switch to another page, keeping the edits made to this one
+    fn show_page(&mut self, index: usize) -> Task<Message> {
+        self.store_layout();
+        if !self.document.select(index) {
+            return Task::none();
+        }
+        self.show_current_page()
+    }
+
+    /// Put the document's current page, or the spread holding it, on the
+    /// canvas and in the settings. A spread whose pages no longer face each
+    /// other falls back to the single page.
+    fn show_current_page(&mut self) -> Task<Message> {
+        if self.spread.is_some() {
+            let spread = self.document.current_spread();
+            if let Some(spread) = &spread {
+                self.document.select(spread.first_page);
+            }
+            self.set_spread(spread);
+        }
+        self.layout = match self.document.spread_layout().filter(|_| self.spread.is_some()) {
+            Some(joined) => joined,
+            None => self.document.current().clone(),
         };
+        self.drag_mode = DragMode::None;
+        self.canvas.set_layout(self.layout.clone());
+        self.canvas.set_ghosts(Vec::new());
+        self.canvas.set_unplaced(Vec::new());
+        self.showing_change_outlines = false;
+        self.sync_margin_inputs();
+        self.sync_tool_fields();
+        for item in &self.layout.images {
+            self.thumbnail_cache.entry(item.path.clone())
+                .or_insert_with(|| iced::widget::image::Handle::from_path(&item.path));
+        }
+        self.load_images_in_background()
+    }
 
-        let orientation_btn = match self.layout.page.orientation {
-            LayoutOrientation::Portrait => button(
-                row![text("|").size(16), text(" Portrait").size(12)].align_y(Alignment::Center)
-            ).on_press(Message::OrientationToggled),
-            LayoutOrientation::Landscape => button(
-                row![text("—").size(16), text(" Landscape").size(12)].align_y(Alignment::Center)
-            ).on_press(Message::OrientationToggled),
+    /// Apply a stacking order change to the selected image
+    fn restack_selected(&mut self, change: fn(&mut Layout, &str) -> bool) {
+        if let Some(id) = self.layout.selected_image_id.clone() {
+            if change(&mut self.layout, &id) {
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+            }
+        }
+    }
+
+    /// Placeholders instead of previews when the preference or `--low-gpu` asks
+    fn sync_low_gpu(&mut self) {
+        self.canvas.set_low_gpu(self.preferences.low_gpu || self.low_gpu_flag);
+    }
+
+    /// Pass the View menu choices (and the grid, while snapping) to the canvas
+    fn sync_canvas_view(&mut self) {
+        self.canvas.set_view_options(canvas_view_options(&self.preferences), self.preferences.grid_size_mm);
+    }
+
+    /// Round the selected element to the grid after a drag: its position
+    /// after a move, its size after a resize. Locked images keep their
+    /// aspect ratio by snapping the width only.
+    fn snap_selection_to_grid(&mut self, mode: DragMode) {
+        let grid = self.preferences.grid_size_mm;
+        let Some(id) = self.layout.selected_image_id.clone() else {
+            return;
         };
+        // A size that would round down to nothing stays as dragged
+        let snap_size = |value: f32| {
+            let snapped = snap_to_grid(value, grid);
+            if snapped < MIN_IMAGE_SIZE_MM { value } else { snapped }
+        };
+        match mode {
+            DragMode::Move => {
+                // The rest of a dragged group keeps its offset from the snapped element
+                let Some((x, y, _, _)) = self.layout.elements().iter().find(|e| e.id() == id).map(|e| e.bounds())
+                else {
+                    return;
+                };
+                let (snapped_x, snapped_y) = (snap_to_grid(x, grid), snap_to_grid(y, grid));
+                self.move_element_to(&id, snapped_x, snapped_y);
+                for (other, _) in self.drag_group_initial.clone() {
+                    if let Some((ox, oy, _, _)) =
+                        self.layout.elements().iter().find(|e| e.id() == other).map(|e| e.bounds())
+                    {
+                        self.move_element_to(&other, ox + snapped_x - x, oy + snapped_y - y);
+                    }
+                }
+            }
+            DragMode::Resize(_) => {
+                if let Some(image) = self.layout.get_image_mut(&id) {
+                    let aspect = image.width_mm / image.height_mm;
+                    image.width_mm = snap_size(image.width_mm);
+                    image.height_mm = if image.lock_aspect {
+                        image.width_mm / aspect
+                    } else {
+                        snap_size(image.height_mm)
+                    };
+                    self.image_width_input = self.preferences.measurement_unit.format(image.width_mm);
+                    self.image_height_input = self.preferences.measurement_unit.format(image.height_mm);
+                } else if let Some(rect) = self.layout.get_rect_mut(&id) {
+                    rect.width_mm = snap_size(rect.width_mm);
+                    rect.height_mm = snap_size(rect.height_mm);
+                }
+            }
+            DragMode::Rotate | DragMode::Crop(_) | DragMode::None => return,
+        }
+        self.canvas.set_layout(self.layout.clone());
+    }
 
-        let tools_area = row![
-            button(row![text("+").size(16), text(" Add Image").size(12)].align_y(Alignment::Center))
-                .on_press(Message::AddImageClicked),
-            delete_button,
-            Space::with_width(Length::Fixed(20.0)),
-            button(text("−").size(18)).on_press(Message::ZoomOut),
-            text(&self.zoom_text).size(14),
-            button(text("+").size(18)).on_press(Message::ZoomIn),
-            button(text("Fit").size(12)).on_press(Message::ZoomToFit),
-            button(text("100%").size(12)).on_press(Message::ZoomReset),
-            Space::with_width(Length::Fixed(20.0)),
-            orientation_btn,
-        ]
-        .spacing(5)
-        .padding(Padding::from([5, 10]))
-        .align_y(Alignment::Center);
+    /// Turn the selected image to any angle (degrees clockwise). Crossing
+    /// into another quarter turn swaps the frame about its center; returns
+    /// false when nothing unlocked is selected
+    fn set_selected_rotation(&mut self, degrees: f32) -> bool {
+        let Some(img) = self.layout.selected_image_mut().filter(|img| !img.locked) else {
+            return false;
+        };
+        let quarter_turns = img.quarter_turns();
+        img.rotation_degrees = degrees.rem_euclid(360.0);
+        if (img.quarter_turns() - quarter_turns).rem_euclid(2) == 1 {
+            img.x_mm += (img.width_mm - img.height_mm) / 2.0;
+            img.y_mm += (img.height_mm - img.width_mm) / 2.0;
+            std::mem::swap(&mut img.width_mm, &mut img.height_mm);
+            self.image_width_input = self.preferences.measurement_unit.format(img.width_mm);
+            self.image_height_input = self.preferences.measurement_unit.format(img.height_mm);
+        }
+        self.canvas.set_layout(self.layout.clone());
+        true
+    }
 
-        // ====================================================================
-        // C: SETTINGS AREA (Right sidebar with tabs)
-        // ====================================================================
-        let tab_buttons = row![
-            button(text("Print").size(10))
-                .on_press(Message::SettingsTabChanged(SettingsTab::PrintSettings))
-                .style(if self.settings_tab == SettingsTab::PrintSettings { 
-                    button::primary 
-                } else { 
-                    button::secondary 
-                }),
-            button(text("Layout").size(10))
-                .on_press(Message::SettingsTabChanged(SettingsTab::Layout))
-                .style(if self.settings_tab == SettingsTab::Layout { 
-                    button::primary 
-                } else { 
-                    button::secondary 
-                }),
-            button(text("Image").size(10))
-                .on_press(Message::SettingsTabChanged(SettingsTab::ImageTools))
-                .style(if self.settings_tab == SettingsTab::ImageTools { 
-                    button::primary 
-                } else { 
-                    button::secondary 
-                }),
-        ]
-        .spacing(2);
+    /// Show a note next to the toolbar for a few seconds
+    fn show_toast(&mut self, message: String) -> Task<Message> {
+        self.toast = Some(message);
+        // The timer is made inside the task, where the runtime is running
+        Task::perform(async { tokio::time::sleep(TOAST_DURATION).await }, |_| Message::ToastExpired)
+    }
 
-        let settings_content: Element<'_, Message> = match self.settings_tab {
-            SettingsTab::PrintSettings => {
-                // Print Settings Tab - use CUPS options when available
-                let mut content = column![].spacing(5);
-                
-                // Paper Size (always show our built-in sizes for layout)
-                let paper_sizes = vec![
-                    PaperSize::Photo3_5x5, PaperSize::Photo4x6, PaperSize::Photo5x5,
-                    PaperSize::Photo5x7, PaperSize::Photo7x10, PaperSize::Photo8x10,
-                    PaperSize::Letter, PaperSize::Legal, PaperSize::Photo10x12,
-                    PaperSize::Photo11x17, PaperSize::Photo12x12, PaperSize::Photo13x19,
-                    PaperSize::Panorama, PaperSize::A3, PaperSize::A4, PaperSize::A5,
-                    PaperSize::Tabloid, PaperSize::Ledger,
-                ];
-                content = content
-                    .push(text("Paper Size").size(12))
-                    .push(pick_list(paper_sizes, Some(self.layout.page.paper_size), Message::PaperSizeSelected)
-                        .width(Length::Fill))
-                    .push(Space::with_height(Length::Fixed(8.0)));
-                
-                // Borderless option
-                content = content
-                    .push(checkbox("Borderless Printing", self.layout.page.borderless)
-                        .on_toggle(Message::BorderlessToggled))
-                    .push(Space::with_height(Length::Fixed(8.0)));
+    /// Act on a canvas key; with nothing selected, say so instead of doing nothing
+    fn run_shortcut(&mut self, shortcut: Shortcut) -> Task<Message> {
+        if !shortcut.needs_selection() {
+            return match shortcut {
+                Shortcut::ToggleDraftPreview => self.handle_message(Message::DraftPreviewToggled),
+                Shortcut::ToggleHelp => self.handle_message(Message::ToggleShortcutHelp),
+                // Escape closes the shortcut list before touching the selection
+                Shortcut::Deselect if self.show_shortcut_help => self.handle_message(Message::ToggleShortcutHelp),
+                // Escape in the middle of a turn puts the angle back and keeps the selection
+                Shortcut::Deselect if self.drag_mode == DragMode::Rotate => {
+                    self.drag_mode = DragMode::None;
+                    self.set_selected_rotation(self.drag_initial_rotation);
+                    self.sync_tool_fields();
+                    Task::none()
+                }
+                Shortcut::Deselect => {
+                    self.layout.select(None);
+                    self.canvas.set_layout(self.layout.clone());
+                    Task::none()
+                }
+                Shortcut::Undo => self.handle_message(Message::Undo),
+                Shortcut::Redo => self.handle_message(Message::Redo),
+                Shortcut::Zoom { zoom_in: true } => self.handle_message(Message::ZoomIn),
+                Shortcut::Zoom { zoom_in: false } => self.handle_message(Message::ZoomOut),
+                _ => Task::none(),
+            };
+        }
+        if self.layout.selected_image_id.is_none() {
+            return self.show_toast("Nothing selected - click an image first".to_string());
+        }
+        self.toast = None;
+        match shortcut {
+            Shortcut::Delete => self.handle_message(Message::DeleteImageClicked),
+            Shortcut::Rotate { clockwise: true } => self.handle_message(Message::RotateImageCW),
+            Shortcut::Rotate { clockwise: false } => self.handle_message(Message::RotateImageCCW),
+            Shortcut::Nudge(dx, dy) => {
+                let moved: Vec<(String, (f32, f32, f32, f32))> = self
+                    .layout
+                    .elements()
+                    .into_iter()
+                    .filter(|e| !e.locked() && self.layout.is_selected(e.id()))
+                    .map(|e| (e.id().to_string(), e.bounds()))
+                    .collect();
+                for (id, (x, y, _, _)) in &moved {
+                    self.move_element_to(id, x + dx, y + dy);
+                }
+                if !moved.is_empty() {
+                    // Lay the automatic copies out around the new position
+                    self.canvas.set_layout(self.layout.clone());
+                    self.mark_modified();
+                    self.sync_tool_fields();
+                }
+                Task::none()
+            }
+            Shortcut::Duplicate => self.handle_message(Message::DuplicateSelection),
+            // Handled before the selection check
+            _ => Task::none(),
+        }
+    }
+
+    /// Move an image or color block and show it on the canvas without a full refresh
+    fn move_element_to(&mut self, id: &str, x_mm: f32, y_mm: f32) {
+        if let Some(image) = self.layout.get_image_mut(id) {
+            image.x_mm = x_mm;
+            image.y_mm = y_mm;
+        } else if let Some(rect) = self.layout.get_rect_mut(id) {
+            rect.x_mm = x_mm;
+            rect.y_mm = y_mm;
+        } else {
+            return;
+        }
+        self.canvas.update_image_position(id, x_mm, y_mm);
+    }
+
+    /// Select just this image or color block and fill the tool fields from it
+    fn select_element(&mut self, id: String) {
+        log::info!("Selected element: {}", id);
+        self.layout.select(Some(id));
+        self.sync_tool_fields();
+    }
+
+    /// Fill the tool fields from the primary selection
+    fn sync_tool_fields(&mut self) {
+        let Some(id) = self.layout.selected_image_id.clone() else {
+            return;
+        };
+        if let Some(image) = self.layout.get_image(&id) {
+            self.image_width_input = self.preferences.measurement_unit.format(image.width_mm);
+            self.image_height_input = self.preferences.measurement_unit.format(image.height_mm);
+            let (_, x, y) = self.page_position(image.bounds());
+            self.image_x_input = self.preferences.measurement_unit.format(x);
+            self.image_y_input = self.preferences.measurement_unit.format(y);
+            self.image_position_error = None;
+            self.image_opacity_input = format!("{:.0}", image.opacity * 100.0);
+            self.image_rotation_input = format!("{}", image.rotation_degrees);
+            self.image_name_input = image.display_name.clone().unwrap_or_default();
+        } else if let Some(rect) = self.layout.get_rect(&id) {
+            self.color_block_hex_input = format_hex_color(rect.color);
+        }
+    }
+
+    /// Show a footer or sequence number setting change on the canvas preview
+    fn footer_changed(&mut self) {
+        self.canvas.set_layout(self.layout.clone());
+        self.mark_modified();
+    }
+
+    /// Refresh the margin and custom paper text fields from the page
+    fn sync_margin_inputs(&mut self) {
+        self.margin_errors = Default::default();
+        self.custom_size_error = None;
+        let page = &self.layout.page;
+        let unit = self.preferences.measurement_unit;
+        self.margin_top_input = unit.format_compact(page.margin_top_mm);
+        self.margin_bottom_input = unit.format_compact(page.margin_bottom_mm);
+        self.margin_left_input = unit.format_compact(page.margin_left_mm);
+        self.margin_right_input = unit.format_compact(page.margin_right_mm);
+        let (width, height) = page.paper_size.to_dimensions();
+        self.custom_width_input = unit.format_compact(width);
+        self.custom_height_input = unit.format_compact(height);
+    }
+
+    /// Use the typed custom paper once both fields hold a size that can be printed
+    fn custom_size_changed(&mut self) {
+        let unit = self.preferences.measurement_unit;
+        let (Some(width), Some(height)) = (unit.parse_mm(&self.custom_width_input), unit.parse_mm(&self.custom_height_input))
+        else {
+            self.custom_size_error = Some(format!("Enter the paper size in {}", unit.name()));
+            return;
+        };
+        let largest = self.printer_capabilities.as_ref().and_then(largest_paper_mm);
+        match custom_paper_size(width, height, largest) {
+            Ok(paper_size) => {
+                self.layout.page.set_paper_size(paper_size, self.margins_user_edited);
+                // Refresh the margins, keeping what is being typed
+                let typed = (self.custom_width_input.clone(), self.custom_height_input.clone());
+                self.sync_margin_inputs();
+                (self.custom_width_input, self.custom_height_input) = typed;
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+            }
+            Err(e) => self.custom_size_error = Some(e),
+        }
+    }
+
+    /// Move the selected image to the typed X and Y, if both are valid and
+    /// keep it on the page
+    fn image_position_changed(&mut self) {
+        let unit = self.preferences.measurement_unit;
+        let (Some(x), Some(y)) = (unit.parse_mm(&self.image_x_input), unit.parse_mm(&self.image_y_input)) else {
+            self.image_position_error = Some(format!("Enter the position in {}", unit.name()));
+            return;
+        };
+        let Some(image) = self.layout.selected_image().filter(|img| !img.locked) else {
+            return;
+        };
+        let id = image.id.clone();
+        let size = (image.width_mm, image.height_mm);
+        // On a spread the position is on the page the image prints on
+        let (page, side) = match &self.spread {
+            Some(spread) => {
+                let side = spread.side_of(image.bounds());
+                (spread.page_sizes[side], Some((spread.clone(), side)))
+            }
+            None => ((self.layout.page.width_mm, self.layout.page.height_mm), None),
+        };
+        match position_on_page(x, y, size, page) {
+            Ok((x, y)) => {
+                let (x, y) = match side {
+                    Some((spread, side)) => spread.on_canvas(side, x, y),
+                    None => (x, y),
+                };
+                self.image_position_error = None;
+                self.move_element_to(&id, x, y);
+                // Lay the automatic copies out around the new position
+                self.canvas.set_layout(self.layout.clone());
+                self.mark_modified();
+            }
+            Err((max_x, max_y)) => {
+                self.image_position_error = Some(format!(
+                    "Keep the image on the page: X up to {}, Y up to {} {}",
+                    unit.format(max_x),
+                    unit.format(max_y),
+                    unit
+                ));
+            }
+        }
+    }
+
+    /// Live position and size of the element being dragged, for the
+    /// toolbar; the angle being turned to shows on the canvas instead
+    fn drag_readout(&self) -> Option<String> {
+        if !matches!(self.drag_mode, DragMode::Move | DragMode::Resize(_)) {
+            return None;
+        }
+        let bounds = match (self.layout.selected_image(), self.layout.selected_rect()) {
+            (Some(image), _) => image.bounds(),
+            (None, Some(rect)) => rect.bounds(),
+            (None, None) => return None,
+        };
+        let (page, x, y) = self.page_position(bounds);
+        let (_, _, width, height) = bounds;
+        let unit = self.preferences.measurement_unit;
+        Some(format!(
+            "{}X {}  Y {}  ·  {} × {} {}",
+            page.map(|number| format!("Page {}  ·  ", number)).unwrap_or_default(),
+            unit.format(x),
+            unit.format(y),
+            unit.format(width),
+            unit.format(height),
+            unit
+        ))
+    }
+
+    /// Where an element's top-left corner lies on the page it prints on,
+    /// with that page's number when a spread is on screen
+    fn page_position(&self, bounds: (f32, f32, f32, f32)) -> (Option<usize>, f32, f32) {
+        let (x, y, _, _) = bounds;
+        match &self.spread {
+            Some(spread) => {
+                let side = spread.side_of(bounds);
+                let (x, y) = spread.to_page(side, x, y);
+                (Some(spread.page_number(side)), x, y)
+            }
+            None => (None, x, y),
+        }
+    }
+
+    /// Pre-flight sheet check: ask before printing a layout whose copies
+    /// spill onto more than one sheet, otherwise go on to the memory check
+    fn check_sheet_count(&mut self) -> Task<Message> {
+        let sheets = 1 + self.document().current().overflow_pages().len();
+        if sheets > 1 {
+            self.confirm_sheet_count = Some(sheets);
+            return Task::none();
+        }
+        self.check_render_memory()
+    }
+
+    /// Pre-flight memory check: ask before a render expected to need more
+    /// than the configured budget, otherwise start printing
+    fn check_render_memory(&mut self) -> Task<Message> {
+        let estimate = estimate_render_bytes(self.document().current(), self.print_dpi);
+        let budget = self.preferences.render_memory_budget_mb as u64 * 1024 * 1024;
+        if estimate > budget {
+            log::warn!("Print render needs about {}, over the {} budget", format_bytes(estimate), format_bytes(budget));
+            self.confirm_memory_print = Some(estimate);
+            return Task::none();
+        }
+        self.print_document()
+    }
+
+    /// Pages picked in the Pages field (all of them when it's empty), leaving
+    /// out pages without images
+    fn pages_to_print(&self) -> Result<Vec<(usize, Layout)>, String> {
+        let document = self.document();
+        let indexes = if self.print_pages_input.trim().is_empty() {
+            (0..document.page_count()).collect()
+        } else {
+            parse_page_range(&self.print_pages_input, document.page_count())?
+        };
+        Ok(indexes
+            .into_iter()
+            .map(|index| (index, document.pages()[index].clone()))
+            .filter(|(_, layout)| !layout.images.is_empty())
+            .collect())
+    }
+
+    /// Send the chosen pages as one print job each, in order
+    fn print_document(&mut self) -> Task<Message> {
+        match self.pages_to_print() {
+            Ok(pages) => {
+                self.print_queue = pages.into();
+                self.print_next_page()
+            }
+            Err(e) => self.show_toast(e),
+        }
+    }
+
+    /// Start the job for the next queued page, if any
+    fn print_next_page(&mut self) -> Task<Message> {
+        match self.print_queue.pop_front() {
+            Some((index, layout)) => self.start_print_job(layout, index + 1),
+            None => Task::none(),
+        }
+    }
+
+    /// Render and submit a layout using the current printer and CUPS selections
+    fn start_print_job(&mut self, layout: Layout, page_number: usize) -> Task<Message> {
+        let printer_name = match &self.selected_printer {
+            Some(name) => name.clone(),
+            None => return Task::none(),
+        };
+        
+        // Set status to rendering
+        self.print_status = PrintStatus::Rendering;
+        let token = self.start_render_progress();
+        
+        // Build extra options from CUPS selections (printer defaults are skipped)
+        let mut extra_options = build_extra_options(&[
+            ("InputSlot", self.selected_input_slot.as_deref()),
+            ("MediaType", self.selected_cups_media_type.as_deref()),
+            ("ColorModel", self.selected_cups_color_model.as_deref()),
+            ("cupsPrintQuality", self.selected_cups_print_quality.as_deref()),
+            ("Resolution", self.selected_cups_resolution.as_deref()),
+            ("Duplex", self.selected_cups_duplex.as_deref()),
+        ]);
+        // Paper type, quality and color mode from the page, unless picked above
+        let page_options = page_setting_options(&layout.page, self.printer_capabilities.as_ref(), &extra_options);
+        extra_options.extend(page_options);
+        
+        let title = job_title(self.project_name().as_deref(), page_number, &layout.page);
+        self.pending_usage = Some(UsageEntry {
+            time: chrono::Utc::now(),
+            printer: printer_name.clone(),
+            paper_size: layout.page.paper_size,
+            paper_type: layout.page.paper_type,
+            sheets: self.print_copies * layout.sheets().len() as u32,
+            title: title.clone(),
+        });
+
+        let job = PrintJob {
+            layout,
+            printer_name,
+            title,
+            resample_filter: self.preferences.resample_filter,
+            copies: self.print_copies,
+            dpi: self.print_dpi,
+            extra_options,
+            cancel: Some(token),
+        };
+        let print = Task::perform(
+            async move {
+                // Simulate brief delay to show the status
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                match render_print_files(&job) {
+                    Ok(files) => Ok((job, files)),
+                    Err(e) => Err(e.to_string()),
+                }
+            },
+            Message::PrintRenderingDone,
+        );
+        Task::batch([print, render_progress_tick()])
+    }
+
+    /// Fresh cancel token for a print or export render, shown by the
+    /// progress dialog until the render finishes
+    fn start_render_progress(&mut self) -> CancelToken {
+        let token = CancelToken::new();
+        self.render_cancel = Some(token.clone());
+        self.render_progress = 0.0;
+        token
+    }
+
+    /// Progress dialog for a print or export render, with a Cancel button
+    fn render_progress_modal<'a>(&self, base: Element<'a, Message>, title: &'a str) -> Element<'a, Message> {
+        let dark_text = Color::from_rgb(0.1, 0.1, 0.1);
+        let cancelling = self.render_cancel.as_ref().is_some_and(|token| token.is_cancelled());
+        let modal_content = container(
+            column![
+                text(title).size(24).color(dark_text),
+                Space::with_height(Length::Fixed(15.0)),
+                text(format!("[  ]  Rendering... {:.0}%", self.render_progress * 100.0)).size(16).color(dark_text),
+                Space::with_height(Length::Fixed(20.0)),
+                progress_bar(0.0..=1.0, self.render_progress)
+                    .width(Length::Fixed(250.0))
+                    .height(Length::Fixed(12.0)),
+                Space::with_height(Length::Fixed(15.0)),
+                button(text(if cancelling { "Cancelling…" } else { "Cancel" }).size(14))
+                    .on_press_maybe((!cancelling).then_some(Message::CancelRender))
+                    .style(button::secondary)
+                    .padding(Padding::from([8, 24])),
+            ]
+            .align_x(Alignment::Center)
+            .spacing(5)
+        )
+        .padding(40)
+        .style(|_theme| container::Style {
+            background: Some(iced::Background::Color(Color::WHITE)),
+            border: iced::Border {
+                color: Color::from_rgb(0.3, 0.5, 0.8),
+                width: 3.0,
+                radius: 12.0.into(),
+            },
+            ..Default::default()
+        });
+
+        iced::widget::stack![
+            base,
+            opaque(
+                mouse_area(
+                    center(modal_content)
+                        .style(|_theme| container::Style {
+                            background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                            ..Default::default()
+                        })
+                )
+            )
+        ]
+        .into()
+    }
+
+    /// Everything the quick-open palette can offer: recent files, presets,
+    /// and image styles when an image is selected to apply them to
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let files = self.preferences.recent_files.iter().map(|path| PaletteEntry {
+            item: PaletteItem::RecentFile(path.clone()),
+            label: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            detail: path.parent().map(|p| p.display().to_string()).unwrap_or_default(),
+        });
+        let presets = self.preferences.presets.iter().enumerate().map(|(index, preset)| PaletteEntry {
+            item: PaletteItem::Preset(index),
+            label: preset.name.clone(),
+            detail: "Preset".to_string(),
+        });
+        let styles = self
+            .preferences
+            .image_styles
+            .iter()
+            .enumerate()
+            .filter(|_| self.layout.selected_image().is_some())
+            .map(|(index, style)| PaletteEntry {
+                item: PaletteItem::ImageStyle(index),
+                label: style.name.clone(),
+                detail: "Image style".to_string(),
+            });
+        files.chain(presets).chain(styles).collect()
+    }
+
+    /// Palette entries matching the current query, best first
+    fn palette_matches(&self) -> Vec<PaletteEntry> {
+        quick_open::filter_entries(&self.palette_entries(), &self.palette_query)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Name of the open project, or the file name if it has none
+    fn project_name(&self) -> Option<String> {
+        self.project
+            .as_ref()
+            .map(|p| p.name.clone())
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| {
+                self.current_file
+                    .as_ref()
+                    .and_then(|path| path.file_stem())
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+    }
+
+    fn save_layout_to_file(&mut self, path: PathBuf) -> Task<Message> {
+        // Create or update project
+        let document = self.document();
+        let project = match &mut self.project {
+            Some(proj) => {
+                proj.set_document(&document);
+                proj.update_modified();
+                proj.clone()
+            }
+            None => {
+                let name = path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Unnamed")
+                    .to_string();
+                ProjectLayout::from_document(&document, name)
+            }
+        };
+
+        // Save to file
+        match self.config_manager.save_layout(&project, &path) {
+            Ok(_) => {
+                // Update recent files before setting current_file
+                self.config_manager.add_recent_file(&mut self.preferences, path.clone());
                 
-                // CUPS-specific options (if available)
-                if let Some(ref caps) = self.printer_capabilities {
-                    content = content
-                        .push(horizontal_rule(1))
-                        .push(text("Printer Options").size(12))
-                        .push(Space::with_height(Length::Fixed(5.0)));
-                    
-                    // Media Source (InputSlot)
-                    if let Some(input_slot) = caps.input_slot() {
-                        let values: Vec<String> = input_slot.values.iter().map(|v| v.value.clone()).collect();
-                        if !values.is_empty() {
-                            content = content
-                                .push(text(&input_slot.display_name).size(11))
-                                .push(pick_list(values, self.selected_input_slot.clone(), Message::InputSlotSelected)
-                                    .width(Length::Fill))
-                                .push(Space::with_height(Length::Fixed(5.0)));
-                        }
-                    }
-                    
-                    // Media Type from CUPS
-                    if let Some(media_type) = caps.media_type() {
-                        let values: Vec<String> = media_type.values.iter().map(|v| v.value.clone()).collect();
-                        if !values.is_empty() {
-                            content = content
-                                .push(text(&media_type.display_name).size(11))
-                                .push(pick_list(values, self.selected_cups_media_type.clone(), Message::CupsMediaTypeSelected)
-                                    .width(Length::Fill))
-                                .push(Space::with_height(Length::Fixed(5.0)));
-                        }
-                    }
-                    
-                    // Print Quality from CUPS
-                    if let Some(print_quality) = caps.print_quality() {
-                        let values: Vec<String> = print_quality.values.iter().map(|v| v.value.clone()).collect();
-                        if !values.is_empty() {
-                            content = content
-                                .push(text(&print_quality.display_name).size(11))
-                                .push(pick_list(values, self.selected_cups_print_quality.clone(), Message::CupsPrintQualitySelected)
-                                    .width(Length::Fill))
-                                .push(Space::with_height(Length::Fixed(5.0)));
-                        }
-                    }
-                    
-                    // Color Model from CUPS
-                    if let Some(color_model) = caps.color_model() {
-                        let values: Vec<String> = color_model.values.iter().map(|v| v.value.clone()).collect();
-                        if !values.is_empty() {
-                            content = content
-                                .push(text(&color_model.display_name).size(11))
-                                .push(pick_list(values, self.selected_cups_color_model.clone(), Message::CupsColorModelSelected)
-                                    .width(Length::Fill));
-                        }
-                    }
-                } else {
-                    // Fallback to built-in options when no CUPS data
-                    let paper_types = vec![
-                        PaperType::Plain, PaperType::SuperHighGloss, PaperType::Glossy,
-                        PaperType::SemiGloss, PaperType::Matte, PaperType::FineArt,
-                    ];
-                    let print_qualities = vec![
-                        PrintQuality::Highest, PrintQuality::High,
-                        PrintQuality::Standard, PrintQuality::Draft,
-                    ];
-                    
-                    content = content
-                        .push(text("Media Type").size(12))
-                        .push(pick_list(paper_types, Some(self.layout.page.paper_type), Message::PaperTypeSelected)
-                            .width(Length::Fill))
-                        .push(Space::with_height(Length::Fixed(10.0)))
-                        .push(text("Print Quality").size(12))
-                        .push(pick_list(print_qualities, Some(self.layout.page.print_quality), Message::PrintQualitySelected)
-                            .width(Length::Fill));
+                // Update last open directory
+                if let Some(parent) = path.parent() {
+                    self.preferences.last_open_directory = Some(parent.to_path_buf());
                 }
                 
-                content.into()
+                self.current_file = Some(path);
+                self.project = Some(project);
+                self.is_modified = false;
+                self.journal.clear();
+                
+                let _ = self.config_manager.save_config(&self.preferences);
+                log::info!("Layout saved successfully");
             }
-            SettingsTab::Layout => {
-                // Layout Tab - Margins
+            Err(e) => {
+                log::error!("Failed to save layout: {}", e);
+            }
+        }
+        
+        Task::none()
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        // ====================================================================
+        // A: STORED SETTINGS AREA (Top bar with printer and file operations)
+        // ====================================================================
+        let printer_picker: Element<'_, Message> = if !self.printers.is_empty() {
+            let printer_names = self
+                .preferences
+                .printers_recent_first(self.printers.iter().map(|p| p.name.clone()).collect());
+            pick_list(printer_names, self.selected_printer.clone(), Message::PrinterSelected)
+                .width(Length::Fixed(200.0))
+                .into()
+        } else {
+            // Nothing to pick: say so instead of offering a fake entry
+            row![
+                text(if self.discovering_printers { "Looking for printers…" } else { "No printers found" })
+                    .size(12)
+                    .color(Color::from_rgb(0.45, 0.45, 0.45)),
+                button(text("Retry discovery").size(12))
+                    .on_press_maybe((!self.discovering_printers).then_some(Message::RetryPrinterDiscovery))
+                    .style(button::secondary),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into()
+        };
+
+        // Build recent files button with indicator
+        let recent_btn_text = if self.preferences.recent_files.is_empty() {
+            "Recent".to_string()
+        } else {
+            format!("Recent ({})", self.preferences.recent_files.len())
+        };
+        let recent_button = if self.preferences.recent_files.is_empty() {
+            button(text(recent_btn_text).size(12))
+        } else {
+            button(text(recent_btn_text).size(12)).on_press(Message::ToggleRecentFilesMenu)
+        };
+
+        let changes_button = if self.showing_change_outlines {
+            button(text("Hide Changes").size(12)).on_press(Message::HideChangeOutlines)
+        } else if self.current_file.is_some() {
+            button(text("Show Changes").size(12)).on_press(Message::ShowChangesClicked)
+        } else {
+            button(text("Show Changes").size(12))
+        };
+
+        let preset_buttons = self.preferences.presets.iter().enumerate().fold(
+            row![].spacing(5),
+            |row, (index, preset)| {
+                row.push(
+                    button(text(preset.name.as_str()).size(12))
+                        .on_press(Message::ApplyPreset(index))
+                        .style(button::secondary),
+                )
+            },
+        );
+
+        let stored_settings_area = row![
+            text("Printer:").size(14),
+            printer_picker,
+            preset_buttons,
+            button(text("Presets…").size(12)).on_press(Message::OpenPresetsDialog),
+            button(text("Usage…").size(12)).on_press(Message::OpenUsageDialog),
+            button(text(if self.diagnosing { "Checking…" } else { "Diagnose printing…" }).size(12))
+                .on_press_maybe((!self.diagnosing).then_some(Message::DiagnosePrinting)),
+            Space::with_width(Length::Fixed(20.0)),
+            button("New").on_press(Message::NewLayout),
+            button("Open").on_press(Message::OpenLayoutClicked),
+            recent_button,
+            button("Import Manifest").on_press(Message::ImportManifestClicked),
+            button("Import XML").on_press(Message::ImportInterchangeClicked),
+            button("Contact Sheet…").on_press(Message::CreateContactSheet),
+            changes_button,
+            button("Save").on_press(Message::SaveLayoutClicked),
+            button("Save As").on_press(Message::SaveLayoutAs),
+            button("Export").on_press(Message::ExportClicked),
+            button("Export XML").on_press(Message::ExportInterchangeClicked),
+            button("Export Images…").on_press_maybe(
+                (!self.layout.images.is_empty() && self.processed_export.is_none())
+                    .then_some(Message::ExportProcessedClicked)
+            ),
+        ]
+        .push_maybe(self.processed_export.as_ref().filter(|e| !e.finished()).map(|export| {
+            row![
+                progress_bar(0.0..=export.total as f32, export.done() as f32)
+                    .width(Length::Fixed(100.0))
+                    .height(Length::Fixed(8.0)),
+                text(format!("Exporting images {}/{}", export.done(), export.total)).size(12),
+            ]
+            .spacing(6)
+            .align_y(Alignment::Center)
+        }))
+        .push_maybe(self.image_load_progress.map(|(done, total)| {
+            row![
+                progress_bar(0.0..=total as f32, done as f32)
+                    .width(Length::Fixed(100.0))
+                    .height(Length::Fixed(8.0)),
+                text(format!("Loading images {}/{}", done, total)).size(12),
+            ]
+            .spacing(6)
+            .align_y(Alignment::Center)
+        }))
+        .spacing(10)
+        .padding(10)
+        .align_y(Alignment::Center);
+
+        // ====================================================================
+        // D: TOOLS AREA (Toolbar with zoom, orientation, add/delete)
+        // ====================================================================
+        let delete_button = if self.layout.selected_image().is_some_and(|img| img.locked) {
+            // Locked images must be unlocked before they can be deleted
+            button(row![text("X").size(14), text(" Locked").size(12)].align_y(Alignment::Center))
+        } else if self.layout.selected_image_id.is_some() {
+            button(row![text("X").size(14), text(" Delete").size(12)].align_y(Alignment::Center))
+                .on_press(Message::DeleteImageClicked)
+        } else {
+            button(row![text("X").size(14), text(" Delete").size(12)].align_y(Alignment::Center))
+        };
+
+        let orientation_btn = match self.layout.page.orientation {
+            LayoutOrientation::Portrait => button(
+                row![text("|").size(16), text(" Portrait").size(12)].align_y(Alignment::Center)
+            ).on_press(Message::OrientationToggled),
+            LayoutOrientation::Landscape => button(
+                row![text("—").size(16), text(" Landscape").size(12)].align_y(Alignment::Center)
+            ).on_press(Message::OrientationToggled),
+        };
+
+        let tools_area = row![
+            button(row![text("+").size(16), text(" Add Image").size(12)].align_y(Alignment::Center))
+                .on_press(Message::AddImageClicked),
+            button(row![text("▬").size(14), text(" Add Color Block").size(12)].align_y(Alignment::Center))
+                .on_press(Message::AddColorBlock),
+            delete_button,
+            button(text("Auto Arrange").size(12))
+                .on_press_maybe((!self.layout.images.is_empty()).then_some(Message::AutoArrange)),
+            Space::with_width(Length::Fixed(20.0)),
+            button(text("−").size(18)).on_press(Message::ZoomOut),
+            text(&self.zoom_text).size(14),
+            button(text("+").size(18)).on_press(Message::ZoomIn),
+            button(text("Fit").size(12)).on_press(Message::ZoomToFit),
+            button(text("Fit Width").size(12)).on_press(Message::ZoomToFitWidth),
+            button(text("Fit Height").size(12)).on_press(Message::ZoomToFitHeight),
+            button(text("100%").size(12)).on_press(Message::ZoomReset),
+            Space::with_width(Length::Fixed(20.0)),
+            orientation_btn,
+            button(text("Snap").size(12))
+                .on_press(Message::SnapToGridToggled)
+                .style(if self.preferences.snap_to_grid { button::primary } else { button::secondary }),
+            button(text("View ▾").size(12))
+                .on_press(Message::ToggleViewMenu)
+                .style(if self.show_view_menu { button::primary } else { button::secondary }),
+            Space::with_width(Length::Fill),
+            text(self.drag_readout().or_else(|| self.toast.clone()).unwrap_or_default())
+                .size(12)
+                .color(Color::from_rgb(0.45, 0.45, 0.45)),
+            button(text("View Only").size(12))
+                .on_press(Message::ToggleViewOnly)
+                .style(button::secondary),
+        ]
+        .spacing(5)
+        .padding(Padding::from([5, 10]))
+        .align_y(Alignment::Center);
+
+        // ====================================================================
+        // C: SETTINGS AREA (Right sidebar with tabs)
+        // ====================================================================
+        let tab_buttons = row![
+            button(text("Print").size(10))
+                .on_press(Message::SettingsTabChanged(SettingsTab::PrintSettings))
+                .style(if self.settings_tab == SettingsTab::PrintSettings { 
+                    button::primary 
+                } else { 
+                    button::secondary 
+                }),
+            button(text("Layout").size(10))
+                .on_press(Message::SettingsTabChanged(SettingsTab::Layout))
+                .style(if self.settings_tab == SettingsTab::Layout { 
+                    button::primary 
+                } else { 
+                    button::secondary 
+                }),
+            button(text("Image").size(10))
+                .on_press(Message::SettingsTabChanged(SettingsTab::ImageTools))
+                .style(if self.settings_tab == SettingsTab::ImageTools { 
+                    button::primary 
+                } else { 
+                    button::secondary 
+                }),
+        ]
+        .spacing(2);
+
+        let settings_content: Element<'_, Message> = match self.settings_tab {
+            SettingsTab::PrintSettings => {
+                // Print Settings Tab - use CUPS options when available
+                let mut content = column![].spacing(5);
+                
+                // Paper Size: our built-in sizes, narrowed to those the printer lists
+                // plus a custom entry that reveals width and height fields
+                let mut paper_sizes = paper_sizes_for_printer(self.printer_capabilities.as_ref(), self.layout.page.paper_size);
+                paper_sizes.push(match self.layout.page.paper_size {
+                    custom @ PaperSize::Custom(..) => custom,
+                    other => {
+                        let (width, height) = other.to_dimensions();
+                        PaperSize::Custom(width, height)
+                    }
+                });
+                content = content
+                    .push(text("Paper Size").size(12))
+                    .push(pick_list(paper_sizes, Some(self.layout.page.paper_size), Message::PaperSizeSelected)
+                        .width(Length::Fill));
+                if matches!(self.layout.page.paper_size, PaperSize::Custom(..)) {
+                    let error = self.custom_size_error.as_deref();
+                    content = content
+                        .push(margin_row("Width:", &self.custom_width_input, error, Message::CustomWidthChanged))
+                        .push(margin_row("Height:", &self.custom_height_input, error, Message::CustomHeightChanged))
+                        .push_maybe(error.map(|e| text(e).size(11).color(Color::from_rgb(0.85, 0.2, 0.2))));
+                }
+                content = content
+                    .push(checkbox("Match paper to first photo", self.preferences.auto_paper_from_first_image)
+                        .on_toggle(Message::AutoPaperFromFirstImageToggled))
+                    .push(checkbox("Ask print size when adding images", self.preferences.ask_size_when_adding)
+                        .on_toggle(Message::AskSizeWhenAddingToggled))
+                    .push(Space::with_height(Length::Fixed(8.0)));
+                
+                // Borderless option
+                content = content
+                    .push(checkbox("Borderless Printing", self.layout.page.borderless)
+                        .on_toggle(Message::BorderlessToggled))
+                    .push(checkbox("Keep images inside margins when toggling", self.preferences.keep_images_in_printable_area)
+                        .on_toggle(Message::KeepImagesInPrintableAreaToggled))
+                    .push(Space::with_height(Length::Fixed(8.0)));
+                
+                // Print-time scaling
+                let scaling_modes = vec![
+                    PrintScaling::ActualSize, PrintScaling::FitToPage, PrintScaling::FillPage,
+                ];
+                content = content
+                    .push(text("Scaling").size(12))
+                    .push(pick_list(scaling_modes, Some(self.layout.page.print_scaling), Message::PrintScalingSelected)
+                        .width(Length::Fill))
+                    .push(Space::with_height(Length::Fixed(8.0)));
+                
+                // Color management: toning presets apply to Black and White output
+                let color_modes = vec![
+                    ColorMode::UseICCProfile, ColorMode::DriverMatching,
+                    ColorMode::NoColorCorrection, ColorMode::BlackAndWhite,
+                ];
+                content = content
+                    .push(text("Color Mode").size(12))
+                    .push(pick_list(color_modes, Some(self.layout.page.color_mode), Message::ColorModeSelected)
+                        .width(Length::Fill));
+                if self.layout.page.color_mode == ColorMode::BlackAndWhite {
+                    let tonings = vec![
+                        Toning::Neutral, Toning::Sepia, Toning::Selenium, Toning::Cyanotype,
+                    ];
+                    content = content
+                        .push(text("Toning").size(11))
+                        .push(pick_list(tonings, Some(self.layout.page.toning), Message::ToningSelected)
+                            .width(Length::Fill));
+                }
+                content = content
+                    .push(text(format!("Print Sharpening: {}", format_sharpen(self.layout.page.output_sharpen))).size(12))
+                    .push(slider(-1.0..=1.0, self.layout.page.output_sharpen, Message::PageOutputSharpenChanged).step(0.1))
+                    .push(Space::with_height(Length::Fixed(8.0)))
+                    .push(text("Print Resolution (DPI)").size(11))
+                    .push(pick_list(DPI_CHOICES.to_vec(), Some(self.print_dpi), Message::PrintDpiSelected)
+                        .width(Length::Fill))
+                    .push(text("Resampling").size(11))
+                    .push(pick_list(ResampleFilter::ALL, Some(self.preferences.resample_filter), Message::ResampleFilterSelected)
+                        .width(Length::Fill))
+                    .push(Space::with_height(Length::Fixed(8.0)));
+                
+                // Footer line: page number, date and project name
+                let footer = &self.layout.page.footer;
+                content = content
+                    .push(checkbox("Print footer line", footer.enabled)
+                        .on_toggle(Message::FooterToggled));
+                if footer.enabled {
+                    let (min_size, max_size) = Footer::SIZE_RANGE_MM;
+                    content = content
+                        .push(checkbox("Page number", footer.page_number)
+                            .on_toggle(Message::FooterPageNumberToggled))
+                        .push(checkbox("Date", footer.date)
+                            .on_toggle(Message::FooterDateToggled))
+                        .push(text_input("Project name", &footer.project_name)
+                            .on_input(Message::FooterProjectNameChanged)
+                            .size(12))
+                        .push(pick_list(FooterPosition::ALL.to_vec(), Some(footer.position), Message::FooterPositionSelected)
+                            .width(Length::Fill))
+                        .push(text(format!("Footer Size: {:.1} mm", footer.size_mm)).size(11))
+                        .push(slider(min_size..=max_size, footer.size_mm, Message::FooterSizeChanged).step(0.5));
+                }
+
+                // Circled numbers on each image for sorting cut prints
+                let numbers = &self.layout.page.sequence_numbers;
+                content = content
+                    .push(checkbox("Number images", numbers.enabled)
+                        .on_toggle(Message::SequenceNumbersToggled));
+                if numbers.enabled {
+                    let (min_size, max_size) = SequenceNumbers::SIZE_RANGE_MM;
+                    content = content
+                        .push(pick_list(SequenceOrder::ALL, Some(numbers.order), Message::SequenceOrderSelected)
+                            .width(Length::Fill))
+                        .push(pick_list(SequenceCorner::ALL, Some(numbers.corner), Message::SequenceCornerSelected)
+                            .width(Length::Fill))
+                        .push(text(format!("Number Size: {:.1} mm", numbers.size_mm)).size(11))
+                        .push(slider(min_size..=max_size, numbers.size_mm, Message::SequenceSizeChanged).step(0.5));
+                }
+
+                content = content
+                    .push(checkbox("Print file names under images", self.layout.page.captions)
+                        .on_toggle(Message::CaptionsToggled));
+
+                // Registration marks for print-and-cut machines
+                content = content
+                    .push(text("Cut Registration Marks").size(11))
+                    .push(pick_list(
+                        RegistrationMarks::ALL,
+                        Some(self.layout.page.registration_marks),
+                        Message::RegistrationMarksSelected,
+                    )
+                    .width(Length::Fill));
+                content = content.push(Space::with_height(Length::Fixed(8.0)));
+                
+                // Paper type and quality, narrowed to what the printer can be told;
+                // sent as the closest CUPS value unless overridden below
+                let (paper_types, print_qualities) = match &self.printer_capabilities {
+                    Some(caps) => (supported_paper_types(caps), supported_print_qualities(caps)),
+                    None => (PaperType::ALL.to_vec(), PrintQuality::ALL.to_vec()),
+                };
+                content = content
+                    .push(text("Paper Type").size(12))
+                    .push(pick_list(paper_types, Some(self.layout.page.paper_type), Message::PaperTypeSelected)
+                        .width(Length::Fill))
+                    .push(Space::with_height(Length::Fixed(10.0)))
+                    .push(text("Print Quality").size(12))
+                    .push(pick_list(print_qualities, Some(self.layout.page.print_quality), Message::PrintQualitySelected)
+                        .width(Length::Fill))
+                    .push(Space::with_height(Length::Fixed(8.0)));
+                
+                // CUPS-specific options (if available)
+                if let Some(ref caps) = self.printer_capabilities {
+                    content = content
+                        .push(horizontal_rule(1))
+                        .push(text("Printer Options").size(12))
+                        .push(Space::with_height(Length::Fixed(5.0)));
+                    
+                    // Each option offers a "(printer default)" entry first
+                    let options = [
+                        (caps.input_slot(), &self.selected_input_slot, Message::InputSlotSelected as fn(OptionChoice) -> Message),
+                        (caps.media_type(), &self.selected_cups_media_type, Message::CupsMediaTypeSelected),
+                        (caps.print_quality(), &self.selected_cups_print_quality, Message::CupsPrintQualitySelected),
+                        (caps.resolution(), &self.selected_cups_resolution, Message::CupsResolutionSelected),
+                        (caps.duplex(), &self.selected_cups_duplex, Message::CupsDuplexSelected),
+                        (caps.color_model(), &self.selected_cups_color_model, Message::CupsColorModelSelected),
+                    ];
+                    for (option, selected, on_select) in options {
+                        if let Some(option) = option {
+                            if let Some(picker) = capability_picker(option, selected.as_deref(), on_select) {
+                                content = content
+                                    .push(picker)
+                                    .push(Space::with_height(Length::Fixed(5.0)));
+                            }
+                        }
+                    }
+                }
+                
+                content.into()
+            }
+            SettingsTab::Layout => {
+                // Layout Tab - Margins
+                let unit = self.preferences.measurement_unit;
+                column![
+                    row![
+                        text(format!("Margins ({})", unit)).size(12),
+                        Space::with_width(Length::Fill),
+                        button(text(format!("Show in {}", unit.toggled().name())).size(10))
+                            .on_press(Message::UnitToggled)
+                            .style(button::secondary)
+                            .padding(4),
+                    ]
+                    .align_y(Alignment::Center),
+                    horizontal_rule(1),
+                    margin_row("Top:", &self.margin_top_input, self.margin_errors[0].as_deref(), Message::MarginTopChanged),
+                    margin_row("Bottom:", &self.margin_bottom_input, self.margin_errors[1].as_deref(), Message::MarginBottomChanged),
+                    margin_row("Left:", &self.margin_left_input, self.margin_errors[2].as_deref(), Message::MarginLeftChanged),
+                    margin_row("Right:", &self.margin_right_input, self.margin_errors[3].as_deref(), Message::MarginRightChanged),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Arrange in Grid").size(12),
+                    horizontal_rule(1),
+                    row![
+                        pick_list((1..=ARRANGE_MAX_CELLS).collect::<Vec<_>>(), Some(self.arrange_grid.0), Message::ArrangeRowsSelected)
+                            .width(Length::Fixed(55.0)),
+                        text("rows ×").size(11),
+                        pick_list((1..=ARRANGE_MAX_CELLS).collect::<Vec<_>>(), Some(self.arrange_grid.1), Message::ArrangeColsSelected)
+                            .width(Length::Fixed(55.0)),
+                        text("columns").size(11),
+                        Space::with_width(Length::Fill),
+                        button(text("Arrange").size(11))
+                            .on_press_maybe((!self.layout.images.is_empty())
+                                .then_some(Message::ArrangeGrid(self.arrange_grid.0, self.arrange_grid.1))),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    text("One image is tiled into every cell").size(10),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Auto Arrange").size(12),
+                    horizontal_rule(1),
+                    row![
+                        text("Gap between images").size(11),
+                        text_input("5", &self.auto_arrange_gutter_input)
+                            .on_input(Message::AutoArrangeGutterChanged)
+                            .width(Length::Fixed(60.0)),
+                        text("mm").size(11),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Templates").size(12),
+                    horizontal_rule(1),
+                    row![
+                        pick_list(
+                            LayoutTemplate::built_ins()
+                                .into_iter()
+                                .chain(self.preferences.layout_templates.iter().cloned())
+                                .collect::<Vec<_>>(),
+                            self.layout_template.clone(),
+                            Message::LayoutTemplateSelected,
+                        )
+                        .placeholder("Choose a template")
+                        .text_size(11)
+                        .width(Length::Fill),
+                        button(text("Apply").size(11)).on_press_maybe(
+                            (self.layout_template.is_some() && !self.layout.images.is_empty())
+                                .then_some(Message::ApplyLayoutTemplate),
+                        ),
+                        button(text("✕").size(11))
+                            .on_press_maybe(self.layout_template.as_ref()
+                                .filter(|t| self.preferences.layout_templates.contains(t))
+                                .map(|_| Message::DeleteLayoutTemplate))
+                            .style(button::danger),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    row![
+                        text_input("Template name", &self.layout_template_name_input)
+                            .on_input(Message::LayoutTemplateNameChanged)
+                            .on_submit(Message::SaveLayoutTemplate)
+                            .size(11),
+                        button(text("Save").size(11)).on_press_maybe(
+                            (!self.layout_template_name_input.trim().is_empty()).then_some(Message::SaveLayoutTemplate),
+                        ),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    text("Saves the grid above with these margins").size(10),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Page Info").size(12),
+                    horizontal_rule(1),
+                    text(format!("Size: {} × {} {}",
+                        unit.format(self.layout.page.width_mm),
+                        unit.format(self.layout.page.height_mm),
+                        unit)).size(11),
+                    text(format!("Orientation: {}", self.layout.page.orientation)).size(11),
+                    button(text("Suggest Paper Size").size(11))
+                        .on_press_maybe((!self.layout.images.is_empty() || !self.layout.rects.is_empty())
+                            .then_some(Message::SuggestPaperSize))
+                        .style(button::secondary),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Workspace").size(12),
+                    horizontal_rule(1),
+                    pick_list(
+                        vec![
+                            WorkspaceBackground::White, WorkspaceBackground::LightGray,
+                            WorkspaceBackground::NeutralGray, WorkspaceBackground::DarkGray,
+                            WorkspaceBackground::Black,
+                        ],
+                        Some(self.preferences.workspace_background),
+                        Message::WorkspaceBackgroundSelected,
+                    )
+                    .width(Length::Fill),
+                    checkbox("Touch mode (large handles)", self.preferences.handle_size_px > DEFAULT_HANDLE_SIZE_PX)
+                        .on_toggle(Message::TouchModeToggled)
+                        .size(14),
+                    checkbox("Low GPU mode (placeholders instead of previews)", self.preferences.low_gpu || self.low_gpu_flag)
+                        .on_toggle_maybe((!self.low_gpu_flag).then_some(Message::LowGpuToggled))
+                        .size(14),
+                    checkbox("Keep fit zoom when resizing the window", self.preferences.fit_on_resize)
+                        .on_toggle(Message::FitOnResizeToggled)
+                        .size(14),
+                    checkbox("Lock aspect ratio of new images", self.preferences.lock_aspect_default)
+                        .on_toggle(Message::LockAspectDefaultToggled)
+                        .size(14),
+                    checkbox("Turn images to fit their cells or pack tighter", self.preferences.auto_rotate_to_fit)
+                        .on_toggle(Message::AutoRotateToFitToggled)
+                        .size(14),
+                    checkbox("Trim transparent borders of new graphics", self.preferences.trim_transparent_on_add)
+                        .on_toggle(Message::TrimTransparentOnAddToggled)
+                        .size(14),
+                    row![
+                        text("Snap grid").size(11),
+                        text_input("10", &self.grid_size_input)
+                            .on_input(Message::GridSizeChanged)
+                            .width(Length::Fixed(60.0)),
+                        text("mm").size(11),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    row![
+                        text("Shift-rotate snaps to").size(11),
+                        text_input("15", &self.rotation_snap_input)
+                            .on_input(Message::RotationSnapChanged)
+                            .width(Length::Fixed(60.0)),
+                        text("°").size(11),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    row![
+                        text("Print memory budget").size(11),
+                        text_input("2048", &self.memory_budget_input)
+                            .on_input(Message::MemoryBudgetChanged)
+                            .width(Length::Fixed(60.0)),
+                        text("MB").size(11),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                    text("Save As file name").size(11),
+                    text_input("layout_%Y%m%d.pxl", &self.preferences.save_name_template)
+                        .on_input(Message::SaveNameTemplateChanged)
+                        .width(Length::Fill),
+                    text(format!(
+                        "Suggests {}",
+                        self.preferences.suggested_save_name(chrono::Local::now().naive_local())
+                    ))
+                    .size(10),
+                ]
+                .spacing(8)
+                .into()
+            }
+            SettingsTab::ImageTools => {
+                // Image Tools Tab
+                if let Some(rect) = self.layout.selected_rect() {
+                    self.color_block_tools(rect)
+                } else if self.layout.selected_image_id.is_some() {
+                    let selected_img = self.layout.selected_image();
+                    let (flip_h, flip_v) = if let Some(img) = selected_img {
+                        (img.flip_horizontal, img.flip_vertical)
+                    } else {
+                        (false, false)
+                    };
+                    let filename = selected_img
+                        .and_then(|img| img.path.file_name())
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("");
+                    let crop_info = match selected_img.and_then(|img| img.crop_source_pixels()) {
+                        Some((x, y, w, h)) => format!("Crop: {}×{} px at ({}, {})", w, h, x, y),
+                        None => "Not cropped".to_string(),
+                    };
+                    let memory_info = selected_img
+                        .map(|img| {
+                            let megapixels = img.original_width_px as f32 * img.original_height_px as f32 / 1_000_000.0;
+                            let mut info = format!(
+                                "{}×{} px ({:.1} MP)",
+                                img.original_width_px, img.original_height_px, megapixels
+                            );
+                            match self.canvas.source_info(&img.path) {
+                                Some(source) => {
+                                    info.push_str(&format!("\n{} in memory", format_bytes(source.decoded_bytes)));
+                                    if let Some(bytes) = source.file_bytes {
+                                        info.push_str(&format!(", {} on disk", format_bytes(bytes)));
+                                    }
+                                    if let Some(format) = source.format {
+                                        info.push_str(&format!("\nFormat: {}", format));
+                                    }
+                                }
+                                None => info.push_str("\nNot decoded yet"),
+                            }
+                            info
+                        })
+                        .unwrap_or_default();
+                    let lock_aspect = selected_img.map_or(self.preferences.lock_aspect_default, |img| img.lock_aspect);
+                    let locked = selected_img.is_some_and(|img| img.locked);
+                    let frame_fit = selected_img.map(|img| img.frame_fit());
+                    // A file that failed to load: say why and offer a replacement
+                    // Warnings are an empty column when they don't apply, so the
+                    // widgets below keep their place (and state) between images
+                    let load_problem = match selected_img.and_then(|img| img.load_error.as_deref()) {
+                        Some(reason) => column![
+                            text("⚠ This image can't be loaded").size(11).color(Color::from_rgb(0.8, 0.1, 0.1)),
+                            text(reason).size(10).color(Color::from_rgb(0.4, 0.2, 0.2)),
+                            button(text("Replace Image…").size(11)).on_press(Message::ReplaceImageClicked),
+                        ]
+                        .spacing(3),
+                        None => column![],
+                    };
+                    let oversize_warning = if selected_img.is_some_and(|img| img.exceeds_page(&self.layout.page)) {
+                        column![
+                            text("⚠ Larger than the paper; it will be clipped")
+                                .size(11)
+                                .color(Color::from_rgb(0.8, 0.1, 0.1)),
+                            button(text("Scale to Fit Media").size(11))
+                                .on_press_maybe((!locked).then_some(Message::ScaleImageToFitMedia)),
+                        ]
+                        .spacing(3)
+                    } else {
+                        column![]
+                    };
+                    let style_list = self.preferences.image_styles.iter().enumerate().fold(
+                        column![].spacing(3),
+                        |col, (index, style)| {
+                            col.push(
+                                row![
+                                    button(text(style.name.as_str()).size(11))
+                                        .on_press(Message::ApplyImageStyle(index))
+                                        .style(button::secondary)
+                                        .width(Length::Fill),
+                                    button(text("✕").size(11))
+                                        .on_press(Message::DeleteImageStyle(index))
+                                        .style(button::danger),
+                                ]
+                                .spacing(3),
+                            )
+                        },
+                    );
+
+                    column![
+                        text("Name").size(12),
+                        text_input(filename, &self.image_name_input)
+                            .on_input(Message::ImageNameChanged)
+                            .width(Length::Fill),
+                        text(memory_info).size(10),
+                        load_problem,
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Rotation").size(12),
+                        row![
+                            button(text("↺ 90°").size(10))
+                                .on_press(Message::RotateImageCCW)
+                                .padding(5),
+                            button(text("↻ 90°").size(10))
+                                .on_press(Message::RotateImageCW)
+                                .padding(5),
+                            text_input("0", &self.image_rotation_input)
+                                .on_input(Message::ImageRotationChanged)
+                                .width(Length::Fixed(55.0)),
+                            text("°").size(10),
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Flip").size(12),
+                        row![
+                            button(text(if flip_h { "↔ H ✓" } else { "↔ H" }).size(10))
+                                .on_press_maybe((!locked).then_some(Message::FlipImageHorizontal))
+                                .style(if flip_h { button::primary } else { button::secondary })
+                                .padding(5),
+                            button(text(if flip_v { "↕ V ✓" } else { "↕ V" }).size(10))
+                                .on_press_maybe((!locked).then_some(Message::FlipImageVertical))
+                                .style(if flip_v { button::primary } else { button::secondary })
+                                .padding(5),
+                        ]
+                        .spacing(5),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Arrange").size(12),
+                        row![
+                            button(text("To Back").size(10))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::SendImageToBack))
+                                .padding(5),
+                            button(text("Backward").size(10))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::SendImageBackward))
+                                .padding(5),
+                            button(text("Forward").size(10))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::BringImageForward))
+                                .padding(5),
+                            button(text("To Front").size(10))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::BringImageToFront))
+                                .padding(5),
+                        ]
+                        .spacing(5),
+                        button(text(if locked { "Locked (click to unlock)" } else { "Lock Position" }).size(10))
+                            .on_press_maybe(selected_img.is_some().then_some(Message::ToggleImageLock))
+                            .style(if locked { button::primary } else { button::secondary })
+                            .padding(5),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text(format!("Size ({})", self.preferences.measurement_unit)).size(12),
+                        row![
+                            text("W:").size(10).width(Length::Fixed(20.0)),
+                            text_input("0", &self.image_width_input)
+                                .on_input(Message::ImageWidthChanged)
+                                .width(Length::Fixed(55.0)),
+                            text("H:").size(10).width(Length::Fixed(20.0)),
+                            text_input("0", &self.image_height_input)
+                                .on_input(Message::ImageHeightChanged)
+                                .width(Length::Fixed(55.0)),
+                        ]
+                        .spacing(3)
+                        .align_y(Alignment::Center),
+                        row![
+                            button(text("Fit to Page").size(11))
+                                .on_press_maybe((selected_img.is_some() && !locked).then_some(Message::FitImageToPage))
+                                .style(button::secondary),
+                            button(text("Center on Page").size(11))
+                                .on_press_maybe((selected_img.is_some() && !locked).then_some(Message::CenterImage))
+                                .style(button::secondary),
+                        ]
+                        .spacing(5),
+                        text(format!("Position from top-left ({})", self.preferences.measurement_unit)).size(12),
+                        row![
+                            text("X:").size(10).width(Length::Fixed(20.0)),
+                            text_input("0", &self.image_x_input)
+                                .on_input(Message::ImageXChanged)
+                                .width(Length::Fixed(55.0)),
+                            text("Y:").size(10).width(Length::Fixed(20.0)),
+                            text_input("0", &self.image_y_input)
+                                .on_input(Message::ImageYChanged)
+                                .width(Length::Fixed(55.0)),
+                        ]
+                        .spacing(3)
+                        .align_y(Alignment::Center),
+                        text(self.image_position_error.as_deref().unwrap_or_default())
+                            .size(10)
+                            .color(Color::from_rgb(0.85, 0.2, 0.2)),
+                        checkbox("Maintain aspect ratio", lock_aspect)
+                            .on_toggle_maybe(selected_img.is_some().then_some(Message::MaintainAspectRatio))
+                            .size(14),
+                        checkbox("Crop while resizing", self.crop_while_resizing)
+                            .on_toggle_maybe((!lock_aspect).then_some(Message::CropWhileResizing))
+                            .size(14),
+                        row![
+                            button(text("Fill Frame").size(11))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::ImageFrameFitSelected(SizeFit::Fill)))
+                                .style(if frame_fit == Some(SizeFit::Fill) { button::primary } else { button::secondary }),
+                            button(text("Fit Frame").size(11))
+                                .on_press_maybe(selected_img.is_some().then_some(Message::ImageFrameFitSelected(SizeFit::Fit)))
+                                .style(if frame_fit == Some(SizeFit::Fit) { button::primary } else { button::secondary }),
+                        ]
+                        .spacing(5),
+                        row![
+                            button(text(if self.crop_edit.is_some() { "Done Cropping" } else { "Crop" }).size(11))
+                                .on_press(Message::ToggleCropMode)
+                                .style(if self.crop_edit.is_some() { button::primary } else { button::secondary }),
+                            button(text("Reset Crop").size(11))
+                                .on_press_maybe(
+                                    selected_img
+                                        .and_then(|img| img.crop)
+                                        .map(|_| Message::ResetImageCrop),
+                                ),
+                        ]
+                        .spacing(5),
+                        text(crop_info).size(10),
+                        row![
+                            button(text(if self.trimming { "Analyzing…" } else { "Auto-Trim Borders" }).size(11))
+                                .on_press_maybe((!self.trimming).then_some(Message::AutoTrimClicked)),
+                            button(text("Trim Transparency").size(11))
+                                .on_press_maybe((!self.trimming).then_some(Message::TrimTransparencyClicked)),
+                        ]
+                        .spacing(5),
+                        row![
+                            text(format!("White ≥ {}", self.preferences.trim_threshold)).size(10),
+                            slider(200..=255, self.preferences.trim_threshold, Message::TrimThresholdChanged)
+                                .on_release(Message::TrimThresholdReleased),
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                        oversize_warning,
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Opacity").size(12),
+                        row![
+                            text_input("100", &self.image_opacity_input)
+                                .on_input(Message::ImageOpacityChanged)
+                                .width(Length::Fixed(50.0)),
+                            text("%").size(10),
+                        ]
+                        .spacing(3)
+                        .align_y(Alignment::Center),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Print Output").size(12),
+                        text(match selected_img.and_then(|img| img.output_sharpen) {
+                            Some(amount) => format!("Sharpening: {}", format_sharpen(amount)),
+                            None => format!("Sharpening: page setting ({})", format_sharpen(self.layout.page.output_sharpen)),
+                        })
+                        .size(10),
+                        row![
+                            slider(
+                                -1.0..=1.0,
+                                selected_img
+                                    .map(|img| img.effective_output_sharpen(&self.layout.page))
+                                    .unwrap_or(0.0),
+                                Message::ImageOutputSharpenChanged,
+                            )
+                            .step(0.1),
+                            button(text("Use Page").size(10))
+                                .on_press_maybe(
+                                    selected_img
+                                        .and_then(|img| img.output_sharpen)
+                                        .map(|_| Message::ResetImageOutputSharpen),
+                                )
+                                .style(button::secondary)
+                                .padding(Padding::from([2, 6])),
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                        row![
+                            pick_list(
+                                ResampleFilter::ALL,
+                                selected_img.and_then(|img| img.resample_filter),
+                                Message::ImageResampleFilterSelected,
+                            )
+                            .placeholder(format!("Resampling: global ({})", self.preferences.resample_filter))
+                            .text_size(10)
+                            .width(Length::Fill),
+                            button(text("Use Global").size(10))
+                                .on_press_maybe(
+                                    selected_img
+                                        .and_then(|img| img.resample_filter)
+                                        .map(|_| Message::ResetImageResampleFilter),
+                                )
+                                .style(button::secondary)
+                                .padding(Padding::from([2, 6])),
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Styles").size(12),
+                        style_list,
+                        row![
+                            text_input("Style name", &self.image_style_name_input)
+                                .on_input(Message::ImageStyleNameChanged)
+                                .on_submit(Message::SaveImageStyle)
+                                .size(11),
+                            button(text("Save").size(11)).on_press_maybe(
+                                (!self.image_style_name_input.trim().is_empty()).then_some(Message::SaveImageStyle),
+                            ),
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                    ]
+                    .spacing(5)
+                    .into()
+                } else {
+                    column![
+                        text("No Image Selected").size(12),
+                        Space::with_height(Length::Fixed(10.0)),
+                        text("Select an image from the\nthumbnails below to edit\nits properties.").size(10),
+                    ]
+                    .spacing(5)
+                    .into()
+                }
+            }
+        };
+
+        let settings_panel: Element<'_, Message> = if self.preferences.settings_collapsed {
+            self.collapsed_settings_strip()
+        } else {
+            column![
+                row![
+                    text("Settings").size(14),
+                    Space::with_width(Length::Fill),
+                    tooltip(
+                        button(text("»").size(12))
+                            .on_press(Message::ToggleSettingsPanel)
+                            .style(button::secondary)
+                            .padding(Padding::from([0, 6])),
+                        container(text("Hide settings (Ctrl+B)").size(12)).padding(6).style(container::rounded_box),
+                        tooltip::Position::Left,
+                    ),
+                ]
+                .align_y(Alignment::Center),
+                horizontal_rule(1),
+                tab_buttons,
+                Space::with_height(Length::Fixed(10.0)),
+                scrollable(settings_content)
+                    .id(scrollable::Id::new(SETTINGS_SCROLL_ID))
+                    .on_scroll(Message::SettingsScrolled)
+                    .height(Length::Fill),
+                self.journal_panel(),
+            ]
+            .spacing(5)
+            .padding(10)
+            .width(Length::Fixed(self.preferences.settings_panel_width))
+            .into()
+        };
+
+        // ====================================================================
+        // A: PREVIEW AREA (Center - Canvas with scrollbars)
+        // ====================================================================
+        // Calculate canvas size based on page dimensions and zoom
+        let canvas_width = self.canvas.mm_to_pixels(self.layout.page.width_mm) + 40.0;
+        let canvas_height = self.canvas.mm_to_pixels(self.layout.page.height_mm) + 40.0;
+        
+        let canvas_elem: Element<'_, CanvasMessage> = canvas(&self.canvas)
+            .width(Length::Fixed(canvas_width))
+            .height(Length::Fixed(canvas_height))
+            .into();
+        let canvas_widget = canvas_elem.map(Message::CanvasMessage);
+        
+        // Wrap canvas in a container with padding for visual margin
+        let workspace_color = self.canvas.background();
+        let canvas_container = container(canvas_widget)
+            .padding(20)
+            .style(move |theme| container::Style {
+                background: Some(iced::Background::Color(workspace_color)),
+                ..container::bordered_box(theme)
+            });
+
+        // Wrap in scrollable for both directions
+        let preview_area = scrollable(
+            scrollable(canvas_container)
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::default()
+                ))
+        )
+        .direction(scrollable::Direction::Vertical(
+            scrollable::Scrollbar::default()
+        ))
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        // Page navigation above the canvas
+        let (page, page_count) = (self.document.current_page(), self.document.page_count());
+        let last_on_screen = page + self.pages_on_screen();
+        let page_label = match &self.spread {
+            Some(_) => format!("Pages {}–{} of {}", page + 1, last_on_screen, page_count),
+            None => format!("Page {} of {}", page + 1, page_count),
+        };
+        let single_page = self.spread.is_none();
+        let page_bar = row![
+            button(text("◀").size(12))
+                .on_press_maybe((page > 0).then_some(Message::PreviousPage))
+                .style(button::secondary),
+            text(page_label).size(12),
+            button(text("▶").size(12))
+                .on_press_maybe((last_on_screen < page_count).then_some(Message::NextPage))
+                .style(button::secondary),
+            Space::with_width(Length::Fixed(10.0)),
+            button(text("Add Page").size(12))
+                .on_press_maybe(single_page.then_some(Message::AddPage))
+                .style(button::secondary),
+            button(text("Remove Page").size(12))
+                .on_press_maybe((single_page && page_count > 1).then_some(Message::RemovePage))
+                .style(button::secondary),
+            button(text(if single_page { "Spread View" } else { "Single Page" }).size(12))
+                .on_press_maybe((page_count > 1).then_some(Message::ToggleSpreadView))
+                .style(button::secondary),
+        ]
+        .spacing(5)
+        .padding(Padding::from([5, 10]))
+        .align_y(Alignment::Center);
+
+        // ====================================================================
+        // E: THUMBNAILS AREA (Bottom with image thumbnails)
+        // ====================================================================
+        let thumbnails: Vec<Element<'_, Message>> = self.layout.images.iter().map(|img| {
+            let label = img.display_label();
+            
+            // Truncate name if too long
+            let display_name = if label.chars().count() > 12 {
+                format!("{}...", label.chars().take(9).collect::<String>())
+            } else {
+                label.to_string()
+            };
+            
+            let is_selected = self.layout.selected_image_id.as_ref() == Some(&img.id);
+            let style = if is_selected { button::primary } else { button::secondary };
+            
+            // Use cached thumbnail handle or create from path
+            let img_handle = self.thumbnail_cache
+                .get(&img.path)
+                .cloned()
+                .unwrap_or_else(|| iced::widget::image::Handle::from_path(&img.path));
+            
+            let thumb_image = iced_image(img_handle)
+                .width(Length::Fixed(60.0))
+                .height(Length::Fixed(60.0));
+
+            // "×N" badge in the corner when the image prints more than once
+            let copies_badge = (img.copies > 1).then(|| {
+                container(
+                    container(text(format!("×{}", img.copies)).size(9).color(Color::WHITE))
+                        .padding(Padding::from([1, 4]))
+                        .style(|_theme| container::Style {
+                            background: Some(iced::Background::Color(Color::from_rgb(0.85, 0.35, 0.1))),
+                            border: iced::Border { radius: 6.0.into(), ..Default::default() },
+                            ..Default::default()
+                        }),
+                )
+                .width(Length::Fixed(60.0))
+                .align_x(Alignment::End)
+            });
+
+            let copies_input = self
+                .copies_inputs
+                .get(&img.id)
+                .cloned()
+                .unwrap_or_else(|| img.copies.to_string());
+            let id = img.id.clone();
+
+            let thumb_btn = button(
+                column![
+                    match copies_badge {
+                        Some(badge) => iced::widget::stack![thumb_image, badge].into(),
+                        None => Element::from(thumb_image),
+                    },
+                    text(display_name).size(9),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(2)
+            )
+            .on_press(Message::ThumbnailClicked(img.id.clone()))
+            .style(style)
+            .padding(5);
+
+            column![
+                thumb_btn,
+                tooltip(
+                    text_input("1", &copies_input)
+                        .on_input(move |value| Message::ImageCopiesChanged(id.clone(), value))
+                        .size(10)
+                        .width(Length::Fixed(70.0)),
+                    container(text("Copies to print").size(11)).padding(4).style(container::rounded_box),
+                    tooltip::Position::Bottom,
+                ),
+            ]
+            .align_x(Alignment::Center)
+            .spacing(2)
+            .into()
+        }).collect();
+
+        let thumbnails_row = if thumbnails.is_empty() {
+            row![text("No images. Click 'Add Image' to add photos.").size(12)]
+                .spacing(10)
+                .padding(10)
+        } else {
+            let mut r = row![].spacing(10).padding(10);
+            for thumb in thumbnails {
+                r = r.push(thumb);
+            }
+            r
+        };
+
+        let thumbnails_area = column![
+            row![
+                text("Thumbnails").size(12),
+                Space::with_width(Length::Fill),
+                button(text(if self.preferences.show_image_list { "Hide List" } else { "Show List" }).size(11))
+                    .on_press(Message::ToggleImageList)
+                    .style(button::secondary)
+                    .padding(Padding::from([2, 8])),
+                text(format!(
+                    "{} image(s) · {} in memory",
+                    self.layout.images.len(),
+                    format_bytes(self.canvas.source_memory_bytes())
+                ))
+                .size(11),
+                match (self.canvas.copies_left_out(), self.canvas.overflow_sheets()) {
+                    (0, _) => Element::from(Space::with_width(Length::Shrink)),
+                    (left_out, sheets) => text(format!(
+                        "  {} cop{} continue on {} more sheet{}",
+                        left_out,
+                        if left_out == 1 { "y" } else { "ies" },
+                        sheets,
+                        if sheets == 1 { "" } else { "s" }
+                    ))
+                    .size(11)
+                    .color(Color::from_rgb(0.75, 0.4, 0.0))
+                    .into(),
+                },
+                Space::with_width(Length::Fixed(15.0)),
+                match self.ink_estimate {
+                    Some(ink) => Element::from(text(format_ink(&ink)).size(11)),
+                    None => button(text(if self.estimating_ink { "Estimating ink…" } else { "Estimate Ink" }).size(11))
+                        .on_press_maybe((!self.estimating_ink && !self.layout.images.is_empty()).then_some(Message::EstimateInkClicked))
+                        .style(button::secondary)
+                        .padding(Padding::from([2, 8]))
+                        .into(),
+                },
+            ]
+            .align_y(Alignment::Center)
+            .padding(Padding::from([5, 10])),
+            scrollable(thumbnails_row).direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+            )),
+        ]
+        .height(Length::Fill);
+
+        // ====================================================================
+        // F: PRINT BUTTON AREA (Bottom right)
+        // ====================================================================
+        let has_images = !self.layout.images.is_empty() || self.document.pages().iter().any(|p| !p.images.is_empty());
+        let print_button: Element<'_, Message> = if self.selected_printer.is_some() && has_images {
+            button(text("Print").size(16))
+                .on_press(Message::PrintClicked)
+                .padding(Padding::from([10, 30]))
+                .into()
+        } else if self.selected_printer.is_none() {
+            tooltip(
+                button(text("Print").size(16)).padding(Padding::from([10, 30])),
+                container(text("No printer available. Connect one and click \"Retry discovery\".").size(12))
+                    .padding(6)
+                    .style(container::rounded_box),
+                tooltip::Position::Top,
+            )
+            .into()
+        } else {
+            button(text("Print").size(16))
+                .padding(Padding::from([10, 30]))
+                .into()
+        };
+
+        let print_selected_button = if self.selected_printer.is_some() && self.layout.selected_image_id.is_some() {
+            button(text("Print Selected").size(12))
+                .on_press(Message::PrintSelectedClicked)
+                .style(button::secondary)
+                .padding(Padding::from([10, 12]))
+        } else {
+            button(text("Print Selected").size(12))
+                .style(button::secondary)
+                .padding(Padding::from([10, 12]))
+        };
+
+        let oversized_count = self.layout.oversized_image_ids().len();
+        let over_marks_count = self.layout.images_over_marks().len();
+        let print_area = column![
+            row![
+                text("Copies:").size(12),
+                text_input("1", &self.copies_input)
+                    .on_input(Message::CopiesChanged)
+                    .width(Length::Fixed(50.0)),
+                Space::with_width(Length::Fixed(20.0)),
+                print_selected_button,
+                print_button,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .push_maybe((page_count > 1).then(|| {
+            row![
+                text("Pages:").size(12),
+                text_input("All", &self.print_pages_input)
+                    .on_input(Message::PrintPagesChanged)
+                    .width(Length::Fixed(80.0)),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        }))
+        .push_maybe((oversized_count > 0).then(|| {
+            text(format!("⚠ {} image(s) larger than the paper", oversized_count))
+                .size(11)
+                .color(Color::from_rgb(0.8, 0.1, 0.1))
+        }))
+        .push_maybe((over_marks_count > 0).then(|| {
+            text(format!("⚠ {} image(s) cover the cut registration marks", over_marks_count))
+                .size(11)
+                .color(Color::from_rgb(0.8, 0.1, 0.1))
+        }))
+        .spacing(5)
+        .padding(10);
+
+        // ====================================================================
+        // ASSEMBLE THE LAYOUT
+        // ====================================================================
+        // Top section: Stored settings
+        // Middle section: Tools + Preview + Settings
+        // Bottom section: Thumbnails + Print button
+
+        // View-only mode shows just the page, with zoom and a way back
+        let main_content = if self.view_only {
+            let view_only_bar = row![
+                container(text("View only").size(11).color(Color::from_rgb(0.35, 0.35, 0.35)))
+                    .padding(Padding::from([2, 8]))
+                    .style(container::rounded_box),
+                Space::with_width(Length::Fill),
+                button(text("−").size(18)).on_press(Message::ZoomOut),
+                text(&self.zoom_text).size(14),
+                button(text("+").size(18)).on_press(Message::ZoomIn),
+                button(text("Fit").size(12)).on_press(Message::ZoomToFit),
+                button(text("Fit Width").size(12)).on_press(Message::ZoomToFitWidth),
+                button(text("Fit Height").size(12)).on_press(Message::ZoomToFitHeight),
+                Space::with_width(Length::Fixed(20.0)),
+                button(text("Exit View Only").size(12)).on_press(Message::ToggleViewOnly),
+            ]
+            .spacing(5)
+            .padding(Padding::from([5, 10]))
+            .align_y(Alignment::Center);
+            column![
+                view_only_bar,
+                horizontal_rule(1),
+                container(preview_area).width(Length::Fill).height(Length::Fill),
+            ]
+        } else {
+            let middle_section = row![]
+                .push_maybe(self.preferences.show_image_list.then(|| self.image_list_panel()))
+                .push_maybe(self.preferences.show_image_list.then(|| vertical_rule(1)))
+                .push(
+                    column![
+                        page_bar,
+                        preview_area,
+                    ]
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+                )
+                .push(if self.preferences.settings_collapsed {
+                    vertical_rule(1).into()
+                } else {
+                    splitter(Splitter::Settings)
+                })
+                .push(settings_panel);
+
+            let bottom_section = row![
+                container(thumbnails_area).width(Length::Fill),
+                vertical_rule(1),
+                print_area,
+            ]
+            .height(Length::Fixed(self.preferences.thumbnail_strip_height));
+
+            // Settings can't be saved on this machine; say so for the whole session
+            let read_only_banner = self.config_manager.read_only_reason().map(|reason| {
+                container(
+                    text(format!(
+                        "Settings can't be saved ({}). Defaults are in use and auto-save and \
+                         backups are off; projects can still be saved with Save As.",
+                        reason
+                    ))
+                    .size(12)
+                    .color(Color::from_rgb(0.45, 0.3, 0.0)),
+                )
+                .width(Length::Fill)
+                .padding(Padding::from([6, 10]))
+                .style(|_theme| container::Style {
+                    background: Some(iced::Background::Color(Color::from_rgb(1.0, 0.95, 0.8))),
+                    ..Default::default()
+                })
+            });
+
+            column![]
+                .push_maybe(read_only_banner)
+                .push(stored_settings_area)
+                .push(horizontal_rule(1))
+                .push(tools_area)
+                .push(horizontal_rule(1))
+                .push(middle_section)
+                .push(splitter(Splitter::Thumbnails))
+                .push(bottom_section)
+        };
+
+        let base = container(main_content)
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        // Create the base with optional overlays
+        let dark_text = Color::from_rgb(0.1, 0.1, 0.1);
+        
+        // Key bindings, toggled with F1
+        if self.show_shortcut_help {
+            let rows = shortcuts::SHORTCUT_HELP.iter().fold(column![].spacing(4), |col, (keys, action)| {
+                col.push(
+                    row![
+                        text(*keys).size(13).color(dark_text).width(Length::Fixed(220.0)),
+                        text(*action).size(13).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    ]
+                    .spacing(10),
+                )
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Keyboard Shortcuts").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    rows,
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("Close").size(14))
+                        .on_press(Message::ToggleShortcutHelp)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Quick-open palette
+        if self.palette_open {
+            let matches = self.palette_matches();
+            let rows = matches.iter().enumerate().fold(column![].spacing(2), |col, (index, entry)| {
+                col.push(
+                    button(
+                        row![
+                            text(entry.label.clone()).size(13).width(Length::Fill),
+                            text(entry.detail.clone()).size(11).color(Color::from_rgb(0.45, 0.45, 0.45)),
+                        ]
+                        .spacing(10),
+                    )
+                    .on_press(Message::PalettePick(index))
+                    .style(if index == self.palette_selected { button::primary } else { button::text })
+                    .width(Length::Fill),
+                )
+            });
+            let list: Element<'_, Message> = if matches.is_empty() {
+                text("No matching files, presets or styles").size(12).color(Color::from_rgb(0.45, 0.45, 0.45)).into()
+            } else {
+                scrollable(rows).height(Length::Shrink).into()
+            };
+            return modal(
+                base.into(),
+                column![
+                    text_input("Open a recent file, apply a preset or style…", &self.palette_query)
+                        .id(text_input::Id::new(PALETTE_INPUT_ID))
+                        .on_input(Message::PaletteQueryChanged)
+                        .on_submit(Message::PaletteActivate)
+                        .size(14),
+                    Space::with_height(Length::Fixed(8.0)),
+                    container(list).max_height(360.0),
+                ]
+                .width(Length::Fixed(480.0))
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // First, check if we need to show the recovery dialog
+        if self.show_recovery_dialog {
+            let modal_content = container(
+                column![
+                    text("Recover Unsaved Work?").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("An auto-save file was found from a previous session.").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    text("Would you like to recover it?").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Recover").size(14))
+                            .on_press(Message::RecoverAutoSave)
+                            .padding(Padding::from([10, 30])),
+                        Space::with_width(Length::Fixed(20.0)),
+                        button(text("Discard").size(14))
+                            .on_press(Message::DiscardAutoSave)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+            )
+            .padding(40)
+            .style(|_theme| container::Style {
+                background: Some(iced::Background::Color(Color::WHITE)),
+                border: iced::Border {
+                    color: Color::from_rgb(0.3, 0.5, 0.8),
+                    width: 3.0,
+                    radius: 12.0.into(),
+                },
+                ..Default::default()
+            });
+
+            return iced::widget::stack![
+                base,
+                opaque(
+                    mouse_area(
+                        center(modal_content)
+                            .style(|_theme| container::Style {
+                                background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                                ..Default::default()
+                            })
+                    )
+                )
+            ]
+            .into();
+        }
+        
+        // Export options
+        if self.export_dialog_open {
+            return modal(
+                base.into(),
                 column![
-                    text("Margins (mm)").size(12),
-                    horizontal_rule(1),
+                    text("Export Layout").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Format:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    pick_list(ExportFormat::ALL.to_vec(), Some(self.export_format), Message::ExportFormatSelected)
+                        .width(Length::Fixed(220.0)),
+                    text("Resolution (DPI):").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    pick_list(DPI_CHOICES.to_vec(), Some(self.export_dpi), Message::ExportDpiSelected)
+                        .width(Length::Fixed(220.0)),
+                    checkbox("Include index file", self.export_include_index)
+                        .on_toggle(Message::ExportIncludeIndexToggled)
+                        .size(14),
+                    text("Writes a .index.json next to the export listing each image's source, position and DPI.")
+                        .size(12)
+                        .color(Color::from_rgb(0.4, 0.4, 0.4)),
+                    Space::with_height(Length::Fixed(20.0)),
                     row![
-                        text("Top:").width(Length::Fixed(60.0)),
-                        text_input("0", &self.margin_top_input)
-                            .on_input(Message::MarginTopChanged)
-                            .width(Length::Fixed(70.0)),
+                        button(text("Export").size(14))
+                            .on_press(Message::ConfirmExport)
+                            .padding(Padding::from([10, 30])),
+                        Space::with_width(Length::Fixed(20.0)),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelExport)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
                     ]
-                    .spacing(5)
-                    .align_y(Alignment::Center),
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Export processed images options
+        if self.processed_dialog_open {
+            let has_selection = self.layout.selected_image().is_some();
+            return modal(
+                base.into(),
+                column![
+                    text("Export Processed Images").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Writes each image as it would print: cropped, flipped, rotated, sharpened and toned.")
+                        .size(12)
+                        .color(Color::from_rgb(0.4, 0.4, 0.4)),
+                    text("Format:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    pick_list(ExportFormat::IMAGES.to_vec(), Some(self.processed_format), Message::ProcessedFormatSelected)
+                        .width(Length::Fixed(220.0)),
+                    text("Resolution (DPI):").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    pick_list(DPI_CHOICES.to_vec(), Some(self.export_dpi), Message::ExportDpiSelected)
+                        .width(Length::Fixed(220.0)),
+                    checkbox("Only the selected image", self.processed_selected_only && has_selection)
+                        .on_toggle_maybe(has_selection.then_some(Message::ProcessedSelectedOnlyToggled))
+                        .size(14),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Choose Folder…").size(14))
+                            .on_press(Message::ConfirmExportProcessed)
+                            .padding(Padding::from([10, 30])),
+                        Space::with_width(Length::Fixed(20.0)),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelExportProcessed)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Processed images written
+        if let Some(export) = self.processed_export.as_ref().filter(|e| e.finished()) {
+            let skipped = export.skipped.iter().fold(column![].spacing(2), |col, reason| {
+                col.push(text(reason.as_str()).size(12).color(Color::from_rgb(0.5, 0.3, 0.3)))
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Images Exported").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!("Wrote {} of {} image(s).", export.written, export.total)).size(13).color(dark_text),
+                ]
+                .push_maybe((!export.skipped.is_empty()).then(|| {
+                    column![
+                        text("Skipped:").size(13).color(dark_text),
+                        scrollable(skipped).height(Length::Shrink),
+                    ]
+                    .spacing(4)
+                }))
+                .push(Space::with_height(Length::Fixed(20.0)))
+                .push(
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissProcessedExport)
+                        .padding(Padding::from([10, 40])),
+                )
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Export finished
+        if let Some(result) = &self.export_result {
+            let message = match result {
+                Ok(path) => text(format!("Saved {}", path.display())).size(13).color(dark_text),
+                Err(error) => text(error.as_str()).size(13).color(Color::from_rgb(0.5, 0.3, 0.3)),
+            };
+            let title = if result.is_ok() { "Export Complete" } else { "Export Failed" };
+            return modal(
+                base.into(),
+                column![
+                    text(title).size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    message,
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissExportResult)
+                        .padding(Padding::from([10, 40])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Changes since the last save
+        if let Some(changes) = &self.layout_changes {
+            let list: Element<'_, Message> = if changes.is_empty() {
+                text("No changes since the last save.").size(13).color(Color::from_rgb(0.3, 0.3, 0.3)).into()
+            } else {
+                scrollable(changes.iter().fold(column![].spacing(4), |col, change| {
+                    col.push(text(change.to_string()).size(12).color(dark_text))
+                }))
+                .height(Length::Fixed(240.0))
+                .into()
+            };
+            let has_outlines = changes.iter().any(|c| c.previous_bounds().is_some());
+            return modal(
+                base.into(),
+                column![
+                    text("Changes Since Last Save").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    list,
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Outline Old Positions").size(14))
+                            .on_press_maybe(has_outlines.then_some(Message::OutlineChangesOnCanvas))
+                            .padding(Padding::from([10, 20])),
+                        Space::with_width(Length::Fixed(20.0)),
+                        button(text("Close").size(14))
+                            .on_press(Message::CloseChanges)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Settings file was broken at startup
+        if let Some(repair) = &self.config_repair {
+            let kept = match &repair.backup_path {
+                Some(path) => format!("The broken file was kept as {}.", path.display()),
+                None => "The broken file could not be moved aside and was left in place.".to_string(),
+            };
+            let recovered = if repair.recovered.is_empty() {
+                "No settings could be recovered; defaults are in use.".to_string()
+            } else {
+                format!("Recovered: {}. Everything else was reset to defaults.", repair.recovered.join(", "))
+            };
+            return modal(
+                base.into(),
+                column![
+                    text("Settings File Repaired").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Your settings file could not be read:").size(14).color(dark_text),
+                    text(repair.error.as_str()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1)),
+                    text(kept).size(12).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    text(recovered).size(12).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Open Config Folder").size(14))
+                            .on_press(Message::OpenConfigDirectory)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                        button(text("OK").size(14))
+                            .on_press(Message::DismissConfigRepair)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .max_width(450)
+                .into(),
+            );
+        }
+
+        // Print size for images being added
+        if let Some(pending) = &self.pending_placement {
+            let size_buttons = COMMON_PRINT_SIZES.iter().fold(
+                row![].spacing(5),
+                |row, &(label, short_mm, long_mm)| {
+                    row.push(
+                        button(text(label).size(11))
+                            .on_press(Message::PlacementSizeChosen(short_mm, long_mm))
+                            .style(button::secondary),
+                    )
+                },
+            );
+            let fit_button = |label, fit| {
+                button(text(label).size(12))
+                    .on_press(Message::PlacementFitSelected(fit))
+                    .style(if self.placement_fit == fit { button::primary } else { button::secondary })
+            };
+            return modal(
+                base.into(),
+                column![
+                    text("Print Size").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(10.0)),
+                    text(format!("Size for {} new image(s):", pending.paths.len())).size(14).color(dark_text),
+                    size_buttons,
                     row![
-                        text("Bottom:").width(Length::Fixed(60.0)),
-                        text_input("0", &self.margin_bottom_input)
-                            .on_input(Message::MarginBottomChanged)
+                        text_input("Width", &self.placement_width_input)
+                            .on_input(Message::PlacementWidthChanged)
+                            .width(Length::Fixed(70.0)),
+                        text("×").size(14).color(dark_text),
+                        text_input("Height", &self.placement_height_input)
+                            .on_input(Message::PlacementHeightChanged)
+                            .on_submit(Message::ConfirmPlacement)
+                            .width(Length::Fixed(70.0)),
+                        pick_list(MeasurementUnit::ALL, Some(self.placement_unit), Message::PlacementUnitSelected)
                             .width(Length::Fixed(70.0)),
                     ]
                     .spacing(5)
                     .align_y(Alignment::Center),
+                    text("The size turns to match each photo's orientation.")
+                        .size(11)
+                        .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    row![
+                        fit_button("Fill (crop to size)", SizeFit::Fill),
+                        fit_button("Fit (whole photo)", SizeFit::Fit),
+                    ]
+                    .spacing(5),
+                    checkbox("Ask every time", self.preferences.ask_size_when_adding)
+                        .on_toggle(Message::AskSizeWhenAddingToggled)
+                        .size(14),
+                    Space::with_height(Length::Fixed(15.0)),
+                    row![
+                        button(text("Add").size(14))
+                            .on_press_maybe(self.placement_size().map(|_| Message::ConfirmPlacement))
+                            .padding(Padding::from([10, 30])),
+                        button(text("Original Size").size(14))
+                            .on_press(Message::PlaceAtOriginalSize)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelPlacement)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(8)
+                .into(),
+            );
+        }
+
+        // Manifest import problems
+        if !self.import_errors.is_empty() {
+            let rows = self.import_errors.iter().fold(column![].spacing(4), |col, error| {
+                col.push(text(error.as_str()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1)))
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Images Not Added").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("These files couldn't be opened as images:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    scrollable(rows).height(Length::Fixed(200.0)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissImportErrors)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        if !self.manifest_errors.is_empty() {
+            let rows = self.manifest_errors.iter().fold(column![].spacing(4), |col, error| {
+                col.push(text(error.as_str()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1)))
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Manifest Import").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("These rows were skipped:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    scrollable(rows).height(Length::Fixed(200.0)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissManifestErrors)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        if !self.interchange_report.is_empty() {
+            let rows = self.interchange_report.iter().fold(column![].spacing(4), |col, line| {
+                col.push(text(line.as_str()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1)))
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("XML Import").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("These parts of the file were not carried over:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    scrollable(rows).height(Length::Fixed(200.0)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissInterchangeReport)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Print setting presets
+        if self.presets_dialog_open {
+            let list = self.preferences.presets.iter().enumerate().fold(
+                column![].spacing(4),
+                |col, (index, preset)| {
+                    let summary = format!(
+                        "{}: {}, {}, {}{}",
+                        preset.name,
+                        preset.printer_name.as_deref().unwrap_or("any printer"),
+                        preset.paper_size,
+                        preset.paper_type,
+                        if preset.borderless { ", borderless" } else { "" },
+                    );
+                    col.push(
+                        row![
+                            text(summary).size(12).color(dark_text).width(Length::Fill),
+                            button(text("Delete").size(11))
+                                .on_press(Message::DeletePreset(index))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                },
+            );
+            let name = self.preset_name_input.trim();
+            return modal(
+                base.into(),
+                column![
+                    text("Presets").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    scrollable(list).height(Length::Fixed(160.0)),
+                    Space::with_height(Length::Fixed(10.0)),
+                    text("Save the current printer, paper, quality, color, margins and DPI as:")
+                        .size(12)
+                        .color(Color::from_rgb(0.4, 0.4, 0.4)),
+                    row![
+                        text_input("Preset name", &self.preset_name_input)
+                            .on_input(Message::PresetNameChanged)
+                            .on_submit(Message::SaveCurrentAsPreset)
+                            .width(Length::Fixed(220.0)),
+                        button(text("Save Current").size(14))
+                            .on_press_maybe((!name.is_empty()).then_some(Message::SaveCurrentAsPreset)),
+                    ]
+                    .spacing(10),
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePresetsDialog)
+                        .style(button::secondary)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .width(Length::Fixed(420.0))
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Printing health check
+        if let Some(results) = &self.diagnostics {
+            let rows = results.iter().fold(column![].spacing(10), |col, result| {
+                let (mark, color) = match result.status {
+                    DiagnosticStatus::Ok => ("✓", Color::from_rgb(0.1, 0.5, 0.2)),
+                    DiagnosticStatus::Warning => ("!", Color::from_rgb(0.7, 0.45, 0.0)),
+                    DiagnosticStatus::Failed => ("✗", Color::from_rgb(0.6, 0.1, 0.1)),
+                    DiagnosticStatus::Skipped => ("–", Color::from_rgb(0.5, 0.5, 0.5)),
+                };
+                let mut entry = column![
+                    row![
+                        text(mark).size(14).color(color),
+                        text(result.check.to_string()).size(14).color(dark_text).width(Length::Fill),
+                    ]
+                    .spacing(8),
+                    text(result.detail.as_str()).size(12).color(color),
+                ]
+                .spacing(2);
+                if let Some(guidance) = &result.guidance {
+                    entry = entry.push(text(guidance.as_str()).size(12).color(dark_text));
+                }
+                col.push(entry)
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Printing Check").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    scrollable(rows).height(Length::Shrink),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Check Again").size(14))
+                            .on_press_maybe((!self.diagnosing).then_some(Message::DiagnosePrinting))
+                            .padding(Padding::from([10, 20])),
+                        button(text("Close").size(14))
+                            .on_press(Message::CloseDiagnostics)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                ]
+                .width(Length::Fixed(460.0))
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Print usage statistics
+        if self.usage_dialog_open {
+            let summary = usage::summarize(&self.usage_entries, self.usage_range.start(chrono::Utc::now()));
+            let label = Color::from_rgb(0.4, 0.4, 0.4);
+            let breakdown = |title: &'static str, rows: Vec<(String, u32)>| {
+                rows.into_iter().fold(
+                    column![text(title).size(14).color(dark_text)].spacing(2),
+                    |col, (name, sheets)| {
+                        col.push(
+                            row![
+                                text(name).size(12).color(dark_text).width(Length::Fill),
+                                text(format!("{} sheet(s)", sheets)).size(12).color(label),
+                            ]
+                            .spacing(10),
+                        )
+                    },
+                )
+            };
+            let mut details = column![
+                text(format!("{} job(s), {} sheet(s)", summary.jobs, summary.sheets))
+                    .size(14)
+                    .color(dark_text),
+            ]
+            .spacing(10);
+            if summary.jobs > 0 {
+                details = details
+                    .push(breakdown("By printer", summary.by_printer))
+                    .push(breakdown(
+                        "By paper type",
+                        summary.by_paper_type.into_iter().map(|(t, n)| (t.to_string(), n)).collect(),
+                    ))
+                    .push(breakdown(
+                        "By paper size",
+                        summary.by_paper_size.into_iter().map(|(p, n)| (p.to_string(), n)).collect(),
+                    ));
+                let start = self.usage_range.start(chrono::Utc::now());
+                let recent = usage::recent_entries(&self.usage_entries, start, 5).into_iter().fold(
+                    column![text("Recent jobs").size(14).color(dark_text)].spacing(2),
+                    |col, entry| {
+                        let title = if entry.title.is_empty() { entry.paper_size.to_string() } else { entry.title.clone() };
+                        col.push(
+                            row![
+                                text(title).size(12).color(dark_text).width(Length::Fill),
+                                text(entry.time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                                    .size(12)
+                                    .color(label),
+                            ]
+                            .spacing(10),
+                        )
+                    },
+                );
+                details = details.push(recent);
+            }
+            return modal(
+                base.into(),
+                column![
+                    text("Print Usage").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    pick_list(UsageRange::ALL, Some(self.usage_range), Message::UsageRangeSelected),
+                    Space::with_height(Length::Fixed(10.0)),
+                    scrollable(details).height(Length::Fixed(240.0)),
+                ]
+                .push_maybe(
+                    self.usage_error
+                        .as_deref()
+                        .map(|e| text(e.to_string()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1))),
+                )
+                .push(Space::with_height(Length::Fixed(20.0)))
+                .push(
+                    row![
+                        button(text("Export CSV…").size(14))
+                            .on_press_maybe((summary.jobs > 0).then_some(Message::ExportUsageCsv))
+                            .padding(Padding::from([10, 20])),
+                        button(text("Close").size(14))
+                            .on_press(Message::CloseUsageDialog)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
+                    ]
+                    .spacing(10),
+                )
+                .width(Length::Fixed(420.0))
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Parts of a preset that couldn't be applied
+        if !self.preset_warnings.is_empty() {
+            let rows = self.preset_warnings.iter().fold(column![].spacing(4), |col, warning| {
+                col.push(text(warning.as_str()).size(12).color(Color::from_rgb(0.6, 0.1, 0.1)))
+            });
+            return modal(
+                base.into(),
+                column![
+                    text("Preset Partly Applied").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    rows,
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text("OK").size(14))
+                        .on_press(Message::DismissPresetWarnings)
+                        .padding(Padding::from([10, 30])),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Printing would clip images larger than the paper
+        if self.confirm_oversized_print {
+            return modal(
+                base.into(),
+                column![
+                    text("Images Larger Than Paper").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!(
+                        "{} image(s) are bigger than the {} paper and will be clipped.",
+                        self.layout.oversized_image_ids().len(),
+                        self.layout.page.paper_size
+                    ))
+                    .size(14)
+                    .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Scale to Fit and Print").size(14))
+                            .on_press(Message::ScaleOversizedAndPrint)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Print Anyway").size(14))
+                            .on_press(Message::PrintOversizedAnyway)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelOversizedPrint)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Pre-flight: copies spill onto more sheets
+        if let Some(sheet) = &self.pending_contact_sheet {
+            return modal(
+                base.into(),
+                column![
+                    text("Replace Layout?").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!(
+                        "The contact sheet of {} image(s) replaces the current layout.",
+                        sheet.layout.images.len()
+                    ))
+                    .size(14)
+                    .color(dark_text),
+                    text(if self.is_modified { "Unsaved changes to the current layout will be lost." } else { "" })
+                        .size(12)
+                        .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Replace").size(14))
+                            .on_press(Message::ConfirmContactSheet)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelContactSheet)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        if let Some((paper_size, orientation)) = self.paper_suggestion {
+            let (width, height) = paper_size.to_dimensions();
+            return modal(
+                base.into(),
+                column![
+                    text("Switch Paper?").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!(
+                        "Everything placed fits on {} ({}, {:.0} × {:.0} mm) instead of {}.",
+                        paper_size, orientation.to_string().to_lowercase(), width, height, self.layout.page.paper_size
+                    ))
+                    .size(14)
+                    .color(dark_text),
+                    text("The content is moved to the middle of the new page; its arrangement is kept.")
+                        .size(12)
+                        .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text(format!("Switch to {}", paper_size)).size(14))
+                            .on_press(Message::ApplyPaperSuggestion)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Keep Current Paper").size(14))
+                            .on_press(Message::DismissPaperSuggestion)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        if let Some(sheets) = self.confirm_sheet_count {
+            return modal(
+                base.into(),
+                column![
+                    text("Print on Several Sheets").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!(
+                        "Not all copies fit on one page, so this layout prints on {} sheets of {}.",
+                        sheets, self.layout.page.paper_size
+                    ))
+                    .size(14)
+                    .color(dark_text),
+                    text(format!(
+                        "With {} cop{} of the layout that is {} sheets in total.",
+                        self.print_copies,
+                        if self.print_copies == 1 { "y" } else { "ies" },
+                        sheets as u32 * self.print_copies
+                    ))
+                    .size(12)
+                    .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text(format!("Print {} Sheets", sheets as u32 * self.print_copies)).size(14))
+                            .on_press(Message::PrintAllSheets)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelSheetsPrint)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // Pre-flight: the render would exceed the memory budget
+        if let Some(estimate) = self.confirm_memory_print {
+            return modal(
+                base.into(),
+                column![
+                    text("Large Print Render").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text(format!(
+                        "Rendering this page at {} DPI needs about {}, more than the {} MB budget.",
+                        self.print_dpi,
+                        format_bytes(estimate),
+                        self.preferences.render_memory_budget_mb
+                    ))
+                    .size(14)
+                    .color(dark_text),
+                    text("Lower the DPI, or print anyway if this computer has the memory to spare.")
+                        .size(12)
+                        .color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text("Print Anyway").size(14))
+                            .on_press(Message::PrintOverBudgetAnyway)
+                            .padding(Padding::from([10, 20])),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelOverBudgetPrint)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 20])),
+                    ]
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        // "Print selected image" paper chooser
+        if let Some(paper_size) = self.print_selected_paper {
+            let modal_content = container(
+                column![
+                    text("Print Selected Image").size(20).color(dark_text),
+                    Space::with_height(Length::Fixed(15.0)),
+                    text("Paper size for this print:").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
+                    pick_list(paper_sizes_for_printer(self.printer_capabilities.as_ref(), paper_size), Some(paper_size), Message::PrintSelectedPaperChosen)
+                        .width(Length::Fixed(220.0)),
+                    text("The image is centered on its own sheet; the layout is not changed.")
+                        .size(12)
+                        .color(Color::from_rgb(0.4, 0.4, 0.4)),
+                    Space::with_height(Length::Fixed(20.0)),
                     row![
-                        text("Left:").width(Length::Fixed(60.0)),
-                        text_input("0", &self.margin_left_input)
-                            .on_input(Message::MarginLeftChanged)
-                            .width(Length::Fixed(70.0)),
+                        button(text("Print").size(14))
+                            .on_press(Message::ConfirmPrintSelected)
+                            .padding(Padding::from([10, 30])),
+                        Space::with_width(Length::Fixed(20.0)),
+                        button(text("Cancel").size(14))
+                            .on_press(Message::CancelPrintSelected)
+                            .style(button::secondary)
+                            .padding(Padding::from([10, 30])),
                     ]
-                    .spacing(5)
-                    .align_y(Alignment::Center),
+                    .spacing(10),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+            )
+            .padding(40)
+            .style(|_theme| container::Style {
+                background: Some(iced::Background::Color(Color::WHITE)),
+                border: iced::Border {
+                    color: Color::from_rgb(0.3, 0.5, 0.8),
+                    width: 3.0,
+                    radius: 12.0.into(),
+                },
+                ..Default::default()
+            });
+
+            return iced::widget::stack![
+                base,
+                opaque(
+                    mouse_area(
+                        center(modal_content)
+                            .style(|_theme| container::Style {
+                                background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                                ..Default::default()
+                            })
+                    )
+                )
+            ]
+            .into();
+        }
+        
+        // Show recent files popup if toggled
+        if self.show_recent_files_menu && !self.preferences.recent_files.is_empty() {
+            let recent_items: Vec<Element<'_, Message>> = self.preferences.recent_files
+                .iter()
+                .take(10)
+                .map(|path| {
+                    let display_name = path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("Unknown");
+                    let path_clone = path.clone();
+                    // Files found missing after startup are dimmed, not removed:
+                    // they may be on a drive that isn't mounted yet
+                    let label = if self.missing_recent_files.contains(path) {
+                        text(format!("{} (not found)", display_name)).size(12).color(Color::from_rgb(0.55, 0.55, 0.55))
+                    } else {
+                        text(display_name).size(12)
+                    };
+                    button(label)
+                        .width(Length::Fill)
+                        .on_press(Message::OpenRecentFile(path_clone))
+                        .style(button::text)
+                        .into()
+                })
+                .collect();
+            
+            let popup_content = container(
+                column(recent_items)
+                    .spacing(2)
+                    .width(Length::Fixed(250.0))
+            )
+            .padding(10)
+            .style(|_theme| container::Style {
+                background: Some(iced::Background::Color(Color::WHITE)),
+                border: iced::Border {
+                    color: Color::from_rgb(0.7, 0.7, 0.7),
+                    width: 1.0,
+                    radius: 4.0.into(),
+                },
+                ..Default::default()
+            });
+
+            // Position the popup near the top-left where the buttons are
+            let popup_positioned = container(
+                column![
+                    Space::with_height(Length::Fixed(50.0)), // Offset from top
                     row![
-                        text("Right:").width(Length::Fixed(60.0)),
-                        text_input("0", &self.margin_right_input)
-                            .on_input(Message::MarginRightChanged)
-                            .width(Length::Fixed(70.0)),
+                        Space::with_width(Length::Fixed(400.0)), // Offset from left to align with Recent button
+                        popup_content,
+                    ],
+                ]
+            )
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+            return iced::widget::stack![
+                base,
+                mouse_area(popup_positioned)
+                    .on_press(Message::ToggleRecentFilesMenu)
+            ]
+            .into();
+        }
+
+        // View menu: one checkbox per canvas overlay; clicking outside closes it
+        if self.show_view_menu {
+            let mut items: Vec<Element<'_, Message>> = ViewOption::ALL
+                .iter()
+                .map(|&option| {
+                    let checked = self.preferences.view_options.get(option)
+                        || (option == ViewOption::Grid && self.preferences.snap_to_grid);
+                    checkbox(option.label(), checked)
+                        .on_toggle(move |_| Message::ViewOptionToggled(option))
+                        .size(14)
+                        .text_size(12)
+                        .into()
+                })
+                .collect();
+            let draft = self.preferences.view_options.render_quality == CanvasQuality::Draft;
+            items.push(
+                checkbox("Draft preview (Q)", draft)
+                    .on_toggle(|_| Message::DraftPreviewToggled)
+                    .size(14)
+                    .text_size(12)
+                    .into(),
+            );
+            let menu = container(column(items).spacing(6).width(Length::Fixed(180.0)))
+                .padding(10)
+                .style(|_theme| container::Style {
+                    background: Some(iced::Background::Color(Color::WHITE)),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.7, 0.7, 0.7),
+                        width: 1.0,
+                        radius: 4.0.into(),
+                    },
+                    ..Default::default()
+                });
+            // Below the View button in the tools row
+            let positioned = container(column![
+                Space::with_height(Length::Fixed(90.0)),
+                row![Space::with_width(Length::Fixed(820.0)), menu],
+            ])
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+            return iced::widget::stack![base, mouse_area(positioned).on_press(Message::ToggleViewMenu)].into();
+        }
+
+        // Show modal overlay when printing
+        match &self.print_status {
+            PrintStatus::Idle if self.exporting => self.render_progress_modal(base.into(), "EXPORTING"),
+            PrintStatus::Idle => base.into(),
+            PrintStatus::Rendering => self.render_progress_modal(base.into(), "PRINTING"),
+            PrintStatus::Sending => {
+                let modal_content = container(
+                    column![
+                        text("PRINTING").size(24).color(dark_text),
+                        Space::with_height(Length::Fixed(15.0)),
+                        text("[>>]  Sending to printer...").size(16).color(dark_text),
+                        Space::with_height(Length::Fixed(20.0)),
+                        progress_bar(0.0..=100.0, 70.0)
+                            .width(Length::Fixed(250.0))
+                            .height(Length::Fixed(12.0)),
+                        Space::with_height(Length::Fixed(15.0)),
+                        text("Please wait...").size(14).color(Color::from_rgb(0.4, 0.4, 0.4)),
                     ]
+                    .align_x(Alignment::Center)
                     .spacing(5)
-                    .align_y(Alignment::Center),
-                    Space::with_height(Length::Fixed(15.0)),
-                    text("Page Info").size(12),
-                    horizontal_rule(1),
-                    text(format!("Size: {:.1} × {:.1} mm", 
-                        self.layout.page.width_mm, 
-                        self.layout.page.height_mm)).size(11),
-                    text(format!("Orientation: {}", self.layout.page.orientation)).size(11),
+                )
+                .padding(40)
+                .style(|_theme| container::Style {
+                    background: Some(iced::Background::Color(Color::WHITE)),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.3, 0.5, 0.8),
+                        width: 3.0,
+                        radius: 12.0.into(),
+                    },
+                    ..Default::default()
+                });
+
+                iced::widget::stack![
+                    base,
+                    opaque(
+                        mouse_area(
+                            center(modal_content)
+                                .style(|_theme| container::Style {
+                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                                    ..Default::default()
+                                })
+                        )
+                    )
                 ]
-                .spacing(8)
                 .into()
             }
-            SettingsTab::ImageTools => {
-                // Image Tools Tab
-                if self.layout.selected_image_id.is_some() {
-                    let selected_img = self.layout.selected_image();
-                    let (rotation_text, flip_h, flip_v) = if let Some(img) = selected_img {
-                        (format!("{}°", img.rotation_degrees), img.flip_horizontal, img.flip_vertical)
-                    } else {
-                        ("0°".to_string(), false, false)
-                    };
-
+            PrintStatus::Completed(job_id) => {
+                let modal_content = container(
                     column![
-                        text("Rotation").size(12),
-                        row![
-                            text(format!("Current: {}", rotation_text)).size(10),
-                        ],
-                        row![
-                            button(text("↺ 90°").size(10))
-                                .on_press(Message::RotateImageCCW)
-                                .padding(5),
-                            button(text("↻ 90°").size(10))
-                                .on_press(Message::RotateImageCW)
-                                .padding(5),
-                        ]
-                        .spacing(5),
-                        Space::with_height(Length::Fixed(10.0)),
-                        text("Flip").size(12),
-                        row![
-                            button(text(if flip_h { "↔ H ✓" } else { "↔ H" }).size(10))
-                                .on_press(Message::FlipImageHorizontal)
-                                .style(if flip_h { button::primary } else { button::secondary })
-                                .padding(5),
-                            button(text(if flip_v { "↕ V ✓" } else { "↕ V" }).size(10))
-                                .on_press(Message::FlipImageVertical)
-                                .style(if flip_v { button::primary } else { button::secondary })
-                                .padding(5),
-                        ]
-                        .spacing(5),
-                        Space::with_height(Length::Fixed(10.0)),
-                        text("Size (mm)").size(12),
-                        row![
-                            text("W:").size(10).width(Length::Fixed(20.0)),
-                            text_input("0", &self.image_width_input)
-                                .on_input(Message::ImageWidthChanged)
-                                .width(Length::Fixed(55.0)),
-                            text("H:").size(10).width(Length::Fixed(20.0)),
-                            text_input("0", &self.image_height_input)
-                                .on_input(Message::ImageHeightChanged)
-                                .width(Length::Fixed(55.0)),
-                        ]
-                        .spacing(3)
-                        .align_y(Alignment::Center),
-                        checkbox("Maintain aspect ratio", self.maintain_aspect_ratio)
-                            .on_toggle(Message::MaintainAspectRatio)
-                            .size(14),
+                        text("[OK]").size(36).color(Color::from_rgb(0.2, 0.7, 0.3)),
+                        Space::with_height(Length::Fixed(15.0)),
+                        text("Print Job Sent Successfully!").size(18).color(dark_text),
                         Space::with_height(Length::Fixed(10.0)),
-                        text("Opacity").size(12),
-                        row![
-                            text_input("100", &self.image_opacity_input)
-                                .on_input(Message::ImageOpacityChanged)
-                                .width(Length::Fixed(50.0)),
-                            text("%").size(10),
-                        ]
-                        .spacing(3)
-                        .align_y(Alignment::Center),
+                        text(format!("Job ID: {}", job_id)).size(13).color(Color::from_rgb(0.4, 0.4, 0.4)),
+                        Space::with_height(Length::Fixed(20.0)),
+                        button(text("OK").size(14))
+                            .on_press(Message::DismissPrintStatus)
+                            .padding(Padding::from([10, 40])),
                     ]
+                    .align_x(Alignment::Center)
                     .spacing(5)
-                    .into()
-                } else {
+                )
+                .padding(40)
+                .style(|_theme| container::Style {
+                    background: Some(iced::Background::Color(Color::WHITE)),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.2, 0.7, 0.3),
+                        width: 3.0,
+                        radius: 12.0.into(),
+                    },
+                    ..Default::default()
+                });
+
+                iced::widget::stack![
+                    base,
+                    opaque(
+                        mouse_area(
+                            center(modal_content)
+                                .style(|_theme| container::Style {
+                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                                    ..Default::default()
+                                })
+                        )
+                    )
+                ]
+                .into()
+            }
+            PrintStatus::Failed(error) => {
+                let modal_content = container(
                     column![
-                        text("No Image Selected").size(12),
+                        text("[!!]").size(36).color(Color::from_rgb(0.9, 0.3, 0.3)),
+                        Space::with_height(Length::Fixed(15.0)),
+                        text("Print Job Failed").size(18).color(dark_text),
                         Space::with_height(Length::Fixed(10.0)),
-                        text("Select an image from the\nthumbnails below to edit\nits properties.").size(10),
+                        text(error).size(13).color(Color::from_rgb(0.5, 0.3, 0.3)),
+                        Space::with_height(Length::Fixed(20.0)),
+                        button(text("OK").size(14))
+                            .on_press(Message::DismissPrintStatus)
+                            .padding(Padding::from([10, 40])),
                     ]
+                    .align_x(Alignment::Center)
                     .spacing(5)
-                    .into()
+                )
+                .padding(40)
+                .style(|_theme| container::Style {
+                    background: Some(iced::Background::Color(Color::WHITE)),
+                    border: iced::Border {
+                        color: Color::from_rgb(0.9, 0.3, 0.3),
+                        width: 3.0,
+                        radius: 12.0.into(),
+                    },
+                    ..Default::default()
+                });
+
+                iced::widget::stack![
+                    base,
+                    opaque(
+                        mouse_area(
+                            center(modal_content)
+                                .style(|_theme| container::Style {
+                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                                    ..Default::default()
+                                })
+                        )
+                    )
+                ]
+                .into()
+            }
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let base_title = match &self.current_file {
+            Some(path) => {
+                let filename = path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Unnamed");
+                format!("Print Layout - {}", filename)
+            }
+            None => "Print Layout".to_string(),
+        };
+        
+        if self.is_modified {
+            format!("{}*", base_title)
+        } else {
+            base_title
+        }
+    }
+
+    /// Vertical list of the layout's images in stacking order (bottom first)
+    /// with size and resolution. Click selects; drag a row to reorder.
+    fn image_list_panel(&self) -> Element<'_, Message> {
+        let rows = self.layout.images.iter().enumerate().fold(column![].spacing(2), |col, (index, img)| {
+            let is_selected = self.layout.selected_image_id.as_deref() == Some(img.id.as_str());
+            let is_drop_target = matches!(self.list_drag, Some((from, to)) if to == index && from != index);
+            let (dpi_x, dpi_y) = img.effective_dpi();
+            let dpi_text = if self.preferences.show_dpi_warnings && img.is_low_dpi() {
+                text(format!("⚠ {:.0} DPI", dpi_x.min(dpi_y))).size(10).color(Color::from_rgb(0.8, 0.1, 0.1))
+            } else {
+                text(format!("{:.0} DPI", dpi_x.min(dpi_y))).size(10)
+            };
+            let row_content = container(
+                column![
+                    text(format!("{}. {}", index + 1, img.display_label())).size(12),
+                    row![
+                        text(format!("{:.0}×{:.0} mm", img.width_mm, img.height_mm)).size(10),
+                        Space::with_width(Length::Fill),
+                        dpi_text,
+                    ],
+                ]
+                .spacing(2),
+            )
+            .padding(Padding::from([4, 6]))
+            .width(Length::Fill)
+            .style(move |theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style {
+                    background: is_selected.then_some(iced::Background::Color(palette.primary.weak.color)),
+                    border: iced::Border {
+                        color: palette.primary.strong.color,
+                        width: if is_drop_target { 2.0 } else { 0.0 },
+                        radius: 4.0.into(),
+                    },
+                    ..Default::default()
+                }
+            });
+            col.push(
+                mouse_area(row_content)
+                    .on_press(Message::ImageListPressed(index))
+                    .on_enter(Message::ImageListHovered(index))
+                    .on_release(Message::ImageListReleased),
+            )
+        });
+
+        let list: Element<'_, Message> = if self.layout.images.is_empty() {
+            text("No images").size(11).into()
+        } else {
+            mouse_area(scrollable(rows).height(Length::Fill))
+                .on_exit(Message::ImageListDragCancelled)
+                .into()
+        };
+
+        column![
+            text("Images").size(14),
+            horizontal_rule(1),
+            list,
+        ]
+        .spacing(5)
+        .padding(10)
+        .width(Length::Fixed(200.0))
+        .into()
+    }
+
+    fn theme(&self) -> Theme {
+        Theme::default()
+    }
+
+    /// Set the zoom, clamped to the allowed range, remembering which fit
+    /// mode produced it (None for manual zooms)
+    fn set_zoom(&mut self, zoom: f32, fit: Option<ZoomFit>) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.zoom_fit = fit;
+        self.zoom_text = zoom_label(self.zoom, fit);
+        self.canvas.set_zoom(self.zoom);
+    }
+
+    /// Zoom so the page fills the preview along the chosen dimension(s)
+    fn zoom_to_fit(&mut self, fit: ZoomFit) {
+        let page = (self.layout.page.width_mm, self.layout.page.height_mm);
+        self.set_zoom(fit_zoom(fit, self.preview_viewport(), page), Some(fit));
+    }
+
+    /// Estimated size (pixels) of the preview area: the window minus the
+    /// sidebars and the bars above and below the canvas
+    fn preview_viewport(&self) -> (f32, f32) {
+        let Size { width, height } = self.window_size;
+        if self.view_only {
+            return (width, height - VIEW_ONLY_CHROME_HEIGHT);
+        }
+        let settings_width = if self.preferences.settings_collapsed { 36.0 } else { self.preferences.settings_panel_width };
+        let list_width = if self.preferences.show_image_list { 201.0 } else { 0.0 };
+        let bars_height = EDITOR_BARS_HEIGHT + self.preferences.thumbnail_strip_height;
+        (width - settings_width - 5.0 - list_width, height - bars_height)
+    }
+
+    /// Thin strip shown in place of the collapsed settings sidebar: one
+    /// button to expand it and one per tab, which expands it on that tab
+    fn collapsed_settings_strip(&self) -> Element<'_, Message> {
+        let tabs = [
+            ("P", "Print", SettingsTab::PrintSettings),
+            ("L", "Layout", SettingsTab::Layout),
+            ("I", "Image", SettingsTab::ImageTools),
+        ];
+        let mut strip = column![tooltip(
+            button(text("«").size(12))
+                .on_press(Message::ToggleSettingsPanel)
+                .style(button::secondary)
+                .width(Length::Fixed(26.0)),
+            container(text("Show settings (Ctrl+B)").size(12)).padding(6).style(container::rounded_box),
+            tooltip::Position::Left,
+        )]
+        .push(horizontal_rule(1));
+        for (icon, name, tab) in tabs {
+            strip = strip.push(tooltip(
+                button(text(icon).size(11))
+                    .on_press(Message::SettingsTabChanged(tab))
+                    .style(if self.settings_tab == tab { button::primary } else { button::secondary })
+                    .width(Length::Fixed(26.0)),
+                container(text(name).size(12)).padding(6).style(container::rounded_box),
+                tooltip::Position::Left,
+            ));
+        }
+        strip
+            .spacing(5)
+            .padding(5)
+            .width(Length::Fixed(36.0))
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // While a splitter is dragged, follow the pointer anywhere in the window
+        let splitter_drag = if self.splitter_drag.is_some() {
+            iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
+                    Some(Message::SplitterDragged(position))
+                }
+                iced::Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => {
+                    Some(Message::SplitterReleased)
+                }
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+
+        // Files dragged in from a file manager. winit doesn't say where they
+        // were dropped, so placement uses the last pointer position.
+        // Ctrl+V pastes image files copied in a file manager, Ctrl+B
+        // collapses or expands the settings sidebar, Ctrl+Shift+L toggles
+        // view-only mode, Ctrl+1/2/3 fit the page, its width or its height
+        // and Ctrl+K opens the quick-open palette, Ctrl+S saves and Ctrl+O
+        // opens, unless a text field took the key first. The other keys in
+        // shortcuts.rs (Delete, R, arrows, Ctrl+D/Z/Y…) go through
+        // ShortcutPressed. Modifier changes are always tracked for grid snapping.
+        let events = iced::event::listen_with(|event, status, _window| match event {
+            iced::Event::Window(iced::window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
+            iced::Event::Window(iced::window::Event::Resized(size)) => Some(Message::WindowResized(size)),
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == iced::event::Status::Ignored
+                    && modifiers.command()
+                    && key.as_ref() == iced::keyboard::Key::Character("v") =>
+            {
+                Some(Message::PasteRequested)
+            }
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == iced::event::Status::Ignored
+                    && modifiers.command()
+                    && key.as_ref() == iced::keyboard::Key::Character("b") =>
+            {
+                Some(Message::ToggleSettingsPanel)
+            }
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == iced::event::Status::Ignored
+                    && modifiers.command()
+                    && modifiers.shift()
+                    && matches!(key.as_ref(), iced::keyboard::Key::Character(c) if c.eq_ignore_ascii_case("l")) =>
+            {
+                Some(Message::ToggleViewOnly)
+            }
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == iced::event::Status::Ignored && modifiers.command() =>
+            {
+                match key.as_ref() {
+                    iced::keyboard::Key::Character("k") => Some(Message::OpenPalette),
+                    iced::keyboard::Key::Character("1") => Some(Message::ZoomToFit),
+                    iced::keyboard::Key::Character("2") => Some(Message::ZoomToFitWidth),
+                    iced::keyboard::Key::Character("3") => Some(Message::ZoomToFitHeight),
+                    iced::keyboard::Key::Character("s") => Some(Message::SaveLayoutClicked),
+                    iced::keyboard::Key::Character("o") => Some(Message::OpenLayoutClicked),
+                    _ => shortcuts::shortcut_for(&key, modifiers, false).map(Message::ShortcutPressed),
                 }
             }
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                shortcuts::shortcut_for(&key, modifiers, status == iced::event::Status::Captured)
+                    .map(Message::ShortcutPressed)
+            }
+            _ => None,
+        });
+        // The palette's search field keeps focus, so its keys are taken
+        // whatever the field did with them
+        let palette_keys = if self.palette_open {
+            iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, .. }) => match key.as_ref() {
+                    iced::keyboard::Key::Named(iced::keyboard::key::Named::ArrowUp) => Some(Message::PaletteMove(-1)),
+                    iced::keyboard::Key::Named(iced::keyboard::key::Named::ArrowDown) => Some(Message::PaletteMove(1)),
+                    iced::keyboard::Key::Named(iced::keyboard::key::Named::Escape) => Some(Message::ClosePalette),
+                    _ => None,
+                },
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+
+        // The first redraw ends startup and starts the deferred checks
+        let first_frame = if self.startup.is_some() {
+            iced::window::frames().map(|_| Message::FirstFrameShown)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([events, splitter_drag, palette_keys, first_frame])
+    }
+}
+
+/// A panel edge that can be dragged to resize the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Splitter {
+    /// Between the preview and the settings sidebar
+    Settings,
+    /// Between the preview and the thumbnail strip
+    Thumbnails,
+}
+
+/// Drag handle for a splitter: a rule with a wider grab area and a resize cursor
+fn splitter<'a>(which: Splitter) -> Element<'a, Message> {
+    let (handle, interaction): (Element<'a, Message>, _) = match which {
+        Splitter::Settings => (
+            container(vertical_rule(1))
+                .width(Length::Fixed(5.0))
+                .height(Length::Fill)
+                .align_x(Alignment::Center)
+                .into(),
+            iced::mouse::Interaction::ResizingHorizontally,
+        ),
+        Splitter::Thumbnails => (
+            container(horizontal_rule(1))
+                .width(Length::Fill)
+                .height(Length::Fixed(5.0))
+                .align_y(Alignment::Center)
+                .into(),
+            iced::mouse::Interaction::ResizingVertically,
+        ),
+    };
+    mouse_area(handle)
+        .on_press(Message::SplitterPressed(which))
+        .interaction(interaction)
+        .into()
+}
+
+/// Labeled margin field; shows a red border and the reason as a tooltip
+/// when the typed value was rejected
+fn margin_row<'a>(
+    label: &'a str,
+    value: &'a str,
+    error: Option<&'a str>,
+    on_input: fn(String) -> Message,
+) -> Element<'a, Message> {
+    let invalid = error.is_some();
+    let input = text_input("0", value)
+        .on_input(on_input)
+        .width(Length::Fixed(70.0))
+        .style(move |theme, status| {
+            let mut style = text_input::default(theme, status);
+            if invalid {
+                style.border.color = Color::from_rgb(0.85, 0.2, 0.2);
+                style.border.width = 2.0;
+            }
+            style
+        });
+    let field: Element<'a, Message> = match error {
+        Some(reason) => tooltip(
+            input,
+            container(text(reason).size(12)).padding(6).style(container::rounded_box),
+            tooltip::Position::Bottom,
+        )
+        .into(),
+        None => input.into(),
+    };
+    row![text(label).width(Length::Fixed(60.0)), field]
+        .spacing(5)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+/// Progress of an "Export processed images" run
+#[derive(Debug, Clone, Default)]
+struct ProcessedExport {
+    total: usize,
+    written: usize,
+    /// Why each skipped image wasn't written
+    skipped: Vec<String>,
+}
+
+impl ProcessedExport {
+    fn done(&self) -> usize {
+        self.written + self.skipped.len()
+    }
+
+    fn finished(&self) -> bool {
+        self.done() >= self.total
+    }
+}
+
+/// Images waiting to be placed, and where
+#[derive(Debug, Clone)]
+struct PendingPlacement {
+    paths: Vec<PathBuf>,
+    /// Top-left corner for the first image in mm; None uses the default spot
+    anchor: Option<(f32, f32)>,
+    /// Cascade step of the first image
+    first_index: usize,
+}
+
+/// Image files named in pasted text: one path or `file://` URI per line, as
+/// file managers put on the clipboard. Other lines are ignored.
+fn paths_from_clipboard(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix("file://") {
+            Some(uri) => PathBuf::from(percent_decode(uri)),
+            None => PathBuf::from(line),
+        })
+        .filter(|path| path.is_absolute() && has_image_extension(path))
+        .collect()
+}
+
+/// What to do with the files given on the command line: a layout opens (the
+/// first, if several), otherwise the images are added to the new layout
+fn open_at_launch(files: Vec<PathBuf>) -> Option<Message> {
+    let (layouts, others): (Vec<PathBuf>, Vec<PathBuf>) = files
+        .into_iter()
+        .partition(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pxl")));
+    if let Some(layout) = layouts.into_iter().next() {
+        return Some(Message::LayoutOpenPathSelected(Some(layout)));
+    }
+    let (images, skipped): (Vec<PathBuf>, Vec<PathBuf>) = others.into_iter().partition(|path| has_image_extension(path));
+    for path in skipped {
+        log::warn!("Not a layout or image file, skipped: {}", path.display());
+    }
+    (!images.is_empty()).then_some(Message::ImageFilesSelected(images))
+}
+
+fn has_image_extension(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Image files directly inside `dir`, sorted by name
+fn image_files_in(dir: &std::path::Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Could not read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && has_image_extension(path))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Undo %XX escapes in a file URI path
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Length for an input field, without trailing zeros ("150", "5.9")
+fn format_length(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Sharpening amount for labels: "Off", "Sharpen 0.3" or "Soften 0.5"
+fn format_sharpen(amount: f32) -> String {
+    if amount.abs() < 0.05 {
+        "Off".to_string()
+    } else if amount > 0.0 {
+        format!("Sharpen {:.1}", amount)
+    } else {
+        format!("Soften {:.1}", -amount)
+    }
+}
+
+/// Find the transparent padding around a graphic in the background
+fn detect_transparency_task(id: String, path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let image = ::image::open(&path).map_err(|e| e.to_string())?;
+                Ok(trim::detect_transparent_border(&image.to_rgba8()))
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result)
+        },
+        move |result| Message::TransparencyTrimDetected(id.clone(), result),
+    )
+}
+
+/// Rotation (degrees clockwise, 0-360) that points an image's top edge from
+/// `center` toward `pointer`, both in mm; rounded to 0.1° or to `snap`
+fn rotation_from_drag(center: (f32, f32), pointer: (f32, f32), snap: Option<f32>) -> f32 {
+    let degrees = (pointer.0 - center.0).atan2(center.1 - pointer.1).to_degrees();
+    match snap.filter(|step| *step > 0.0) {
+        Some(step) => snap_angle(degrees, step),
+        None => snap_angle((degrees * 10.0).round() / 10.0, 0.0),
+    }
+}
+
+/// The overlays the canvas draws: the View menu choices, plus the grid
+/// whenever positions snap to it
+fn canvas_view_options(preferences: &UserPreferences) -> ViewOptions {
+    ViewOptions {
+        show_grid: preferences.view_options.show_grid || preferences.snap_to_grid,
+        ..preferences.view_options
+    }
+}
+
+/// Zoom for the toolbar: "85%", or "85% (Fit Width)" after a fit zoom
+fn zoom_label(zoom: f32, fit: Option<ZoomFit>) -> String {
+    match fit {
+        Some(fit) => format!("{:.0}% ({})", zoom * 100.0, fit),
+        None => format!("{:.0}%", zoom * 100.0),
+    }
+}
+
+/// Ink estimate as whole percentages, e.g. "Ink ≈ C 12% · M 9% · Y 8% · K 30%"
+fn format_ink(ink: &InkCoverage) -> String {
+    let pct = |v: f32| (v * 100.0).round() as u32;
+    format!(
+        "Ink ≈ C {}% · M {}% · Y {}% · K {}%",
+        pct(ink.cyan),
+        pct(ink.magenta),
+        pct(ink.yellow),
+        pct(ink.black)
+    )
+}
+
+/// Human-readable byte count, e.g. "512 KB" or "1.4 GB"
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= KB * KB * KB {
+        format!("{:.1} GB", bytes / (KB * KB * KB))
+    } else if bytes >= KB * KB {
+        format!("{:.0} MB", bytes / (KB * KB))
+    } else {
+        format!("{:.0} KB", bytes / KB)
+    }
+}
+
+/// Show `content` in the standard dialog box, centered over a dimmed `base`
+fn modal<'a>(base: Element<'a, Message>, content: Element<'a, Message>) -> Element<'a, Message> {
+    let dialog = container(content)
+        .padding(40)
+        .max_width(600)
+        .style(|_theme| container::Style {
+            background: Some(iced::Background::Color(Color::WHITE)),
+            border: iced::Border {
+                color: Color::from_rgb(0.3, 0.5, 0.8),
+                width: 3.0,
+                radius: 12.0.into(),
+            },
+            ..Default::default()
+        });
+
+    iced::widget::stack![
+        base,
+        opaque(
+            mouse_area(
+                center(dialog)
+                    .style(|_theme| container::Style {
+                        background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                        ..Default::default()
+                    })
+            )
+        )
+    ]
+    .into()
+}
+
+/// Built-in paper sizes offered in the paper pick lists
+fn paper_size_choices() -> Vec<PaperSize> {
+    vec![
+        PaperSize::Photo3_5x5, PaperSize::Photo4x6, PaperSize::Photo5x5,
+        PaperSize::Photo5x7, PaperSize::Photo7x10, PaperSize::Photo8x10,
+        PaperSize::Letter, PaperSize::Legal, PaperSize::Photo10x12,
+        PaperSize::Photo11x17, PaperSize::Photo12x12, PaperSize::Photo13x19,
+        PaperSize::Panorama, PaperSize::A3, PaperSize::A4, PaperSize::A5,
+        PaperSize::Tabloid, PaperSize::Ledger,
+    ]
+}
+
+/// Smallest side accepted for a custom paper, in mm
+const MIN_CUSTOM_PAPER_MM: f32 = 25.4;
+
+/// A custom paper of the typed size, or why it can't be printed. `largest`
+/// is the printer's biggest paper (short side, long side), when known.
+fn custom_paper_size(width_mm: f32, height_mm: f32, largest: Option<(f32, f32)>) -> Result<PaperSize, String> {
+    const TOLERANCE_MM: f32 = 0.5;
+    if width_mm < MIN_CUSTOM_PAPER_MM || height_mm < MIN_CUSTOM_PAPER_MM {
+        return Err(format!("Paper must be at least {} mm on each side", MIN_CUSTOM_PAPER_MM));
+    }
+    if let Some((short, long)) = largest {
+        if width_mm.min(height_mm) > short + TOLERANCE_MM || width_mm.max(height_mm) > long + TOLERANCE_MM {
+            return Err(format!("Larger than the printer's biggest paper ({:.0} × {:.0} mm)", short, long));
+        }
+    }
+    Ok(PaperSize::Custom(width_mm, height_mm))
+}
+
+/// The top-left corner `(x, y)` if an element of `size` placed there stays
+/// on a page of `page` size, else the largest X and Y that would
+fn position_on_page(x: f32, y: f32, size: (f32, f32), page: (f32, f32)) -> Result<(f32, f32), (f32, f32)> {
+    const TOLERANCE_MM: f32 = 0.05;
+    let max_x = (page.0 - size.0).max(0.0);
+    let max_y = (page.1 - size.1).max(0.0);
+    let fits = |v: f32, max: f32| (-TOLERANCE_MM..=max + TOLERANCE_MM).contains(&v);
+    if fits(x, max_x) && fits(y, max_y) {
+        Ok((x.clamp(0.0, max_x), y.clamp(0.0, max_y)))
+    } else {
+        Err((max_x, max_y))
+    }
+}
+
+/// Built-in paper sizes the printer lists, keeping `current` so the pick
+/// list still shows it. Every size when the printer reports none we know.
+fn paper_sizes_for_printer(caps: Option<&PrinterCapabilities>, current: PaperSize) -> Vec<PaperSize> {
+    let all = paper_size_choices();
+    let Some(caps) = caps else {
+        return all;
+    };
+    let supported: Vec<PaperSize> = all
+        .iter()
+        .copied()
+        .filter(|size| *size == current || printer_supports_paper_size(caps, *size))
+        .collect();
+    if supported.iter().any(|size| *size != current) {
+        supported
+    } else {
+        all
+    }
+}
+
+/// Label and pick_list for a CUPS option, with the printer default as the first entry
+fn capability_picker<'a>(
+    option: &'a PrinterOption,
+    selected: Option<&str>,
+    on_select: fn(OptionChoice) -> Message,
+) -> Option<Element<'a, Message>> {
+    if option.values.is_empty() {
+        return None;
+    }
+    Some(
+        column![
+            text(&option.display_name).size(11),
+            pick_list(option.choices(), Some(option.choice_for(selected)), on_select)
+                .width(Length::Fill),
+        ]
+        .into()
+    )
+}
+
+/// Next poll of a running render's progress
+fn render_progress_tick() -> Task<Message> {
+    Task::perform(
+        async {
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        },
+        |_| Message::RenderProgressTick,
+    )
+}
+
+/// Look for printers in the background
+fn discover_printers_task() -> Task<Message> {
+    Task::perform(
+        blocking_with_timeout("Printer discovery", PRINTER_DISCOVERY_TIMEOUT, || {
+            discover_printers().unwrap_or_else(|e| {
+                log::error!("Failed to discover printers: {}", e);
+                Vec::new()
+            })
+        }),
+        |printers| Message::PrintersDiscovered(printers.unwrap_or_default()),
+    )
+}
+
+/// Ask CUPS for a printer's options in the background; `lpoptions` can hang
+/// on an unreachable network queue just like `lpstat`
+fn printer_capabilities_task(printer_name: String) -> Task<Message> {
+    Task::perform(
+        blocking_with_timeout("Printer capabilities", PRINTER_DISCOVERY_TIMEOUT, move || {
+            get_printer_capabilities(&printer_name).unwrap_or_default()
+        }),
+        |caps| Message::PrinterCapabilitiesLoaded(caps.unwrap_or_default()),
+    )
+}
+
+/// Run blocking `work` on tokio's blocking pool, logging how long it took.
+/// None if it panicked or took longer than `timeout`; a timed-out call is
+/// left to finish in the background and its result is dropped.
+async fn blocking_with_timeout<T: Send + 'static>(
+    what: &'static str,
+    timeout: std::time::Duration,
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let started = std::time::Instant::now();
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(work)).await {
+        Ok(Ok(value)) => {
+            log::info!("{} finished in {:?}", what, started.elapsed());
+            Some(value)
+        }
+        Ok(Err(e)) => {
+            log::error!("{} failed to run: {}", what, e);
+            None
+        }
+        Err(_) => {
+            log::warn!("{} gave up after {:?}", what, timeout);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config in a fresh temporary directory, so tests never touch the
+    /// developer's own settings. The directory is removed when the returned
+    /// guard is dropped.
+    fn test_config_manager() -> (ConfigManager, tempfile::TempDir) {
+        let dir = tempfile::Builder::new().prefix("print_layout_app_").tempdir().unwrap();
+        let manager = ConfigManager::with_dirs(dir.path().join("config"), dir.path().join("cache")).unwrap();
+        (manager, dir)
+    }
+
+    /// A window whose config directory lives as long as it does
+    struct TestApp {
+        app: PrintLayout,
+        _config_dir: tempfile::TempDir,
+    }
+
+    impl std::ops::Deref for TestApp {
+        type Target = PrintLayout;
+
+        fn deref(&self) -> &PrintLayout {
+            &self.app
+        }
+    }
+
+    impl std::ops::DerefMut for TestApp {
+        fn deref_mut(&mut self) -> &mut PrintLayout {
+            &mut self.app
+        }
+    }
+
+    /// A fresh window with default settings
+    fn test_app() -> TestApp {
+        let (config_manager, config_dir) = test_config_manager();
+        TestApp { app: PrintLayout::with_config_manager(config_manager).0, _config_dir: config_dir }
+    }
+
+    /// A fresh window with one selected image and a clean modified flag
+    fn app_with_selected_image() -> (TestApp, String) {
+        let mut app = test_app();
+        let image = PlacedImage::new(PathBuf::from("/tmp/photo.jpg"), 1200, 800);
+        let id = image.id.clone();
+        app.layout.add_image(image);
+        app.layout.selected_image_id = Some(id.clone());
+        app.is_modified = false;
+        (app, id)
+    }
+
+    #[test]
+    fn test_current_file_changes_only_when_a_layout_loads() {
+        let mut app = test_app();
+        let open = PathBuf::from("/tmp/open.pxl");
+        app.current_file = Some(open.clone());
+
+        let _ = app.update(Message::LayoutLoaded(PathBuf::from("/tmp/broken.pxl"), Err("bad file".to_string())));
+        assert_eq!(app.current_file, Some(open));
+
+        let other = PathBuf::from("/tmp/other.pxl");
+        let project = ProjectLayout::new(Layout::new(), "Other".to_string());
+        let _ = app.update(Message::LayoutLoaded(other.clone(), Ok(project)));
+        assert_eq!(app.current_file, Some(other.clone()));
+        assert_eq!(app.preferences.recent_files.first(), Some(&other));
+    }
+
+    /// A named run of messages given the selected image's id, and whether
+    /// it should leave the layout modified
+    type EditCase = (&'static str, fn(&str) -> Vec<Message>, bool);
+
+    #[test]
+    fn test_only_document_edits_mark_modified() {
+        let cases: Vec<EditCase> = vec![
+            ("select thumbnail", |id| vec![Message::ThumbnailClicked(id.to_string())], false),
+            ("select on canvas", |_| vec![Message::CanvasMessage(CanvasMessage::CanvasClicked(60.0, 60.0))], false),
+            ("click with jitter", |_| vec![
+                Message::CanvasMessage(CanvasMessage::MouseMoved(60.0, 60.0)),
+                Message::CanvasMessage(CanvasMessage::MouseMoved(60.3, 60.1)),
+                Message::CanvasMessage(CanvasMessage::CanvasClicked(60.0, 60.0)),
+            ], false),
+            ("deselect", |_| vec![Message::CanvasMessage(CanvasMessage::CanvasClicked(5.0, 5.0))], false),
+            ("zoom", |_| vec![Message::ZoomIn, Message::ZoomOut], false),
+            ("switch tab", |_| vec![Message::SettingsTabChanged(SettingsTab::Layout)], false),
+            ("aspect lock", |_| vec![Message::MaintainAspectRatio(false)], true),
+            ("invalid margin", |_| vec![Message::MarginTopChanged("abc".to_string())], false),
+            ("margin", |_| vec![Message::MarginTopChanged("10".to_string())], true),
+            ("paper size", |_| vec![Message::PaperSizeSelected(PaperSize::A3)], true),
+            ("paper type", |_| vec![Message::PaperTypeSelected(PaperType::Matte)], true),
+            ("borderless", |_| vec![Message::BorderlessToggled(true)], true),
+            ("rotate", |_| vec![Message::RotateImageCW], true),
+            ("opacity", |_| vec![Message::ImageOpacityChanged("50".to_string())], true),
+            ("drag", |id| vec![
+                Message::CanvasMessage(CanvasMessage::StartMove(id.to_string(), 10.0, 10.0)),
+                Message::CanvasMessage(CanvasMessage::MouseMoved(10.0, 10.0)),
+                Message::CanvasMessage(CanvasMessage::MouseMoved(30.0, 25.0)),
+                Message::CanvasMessage(CanvasMessage::MouseReleased),
+            ], true),
+        ];
+
+        for (name, messages, expected) in cases {
+            let (mut app, id) = app_with_selected_image();
+            for message in messages(&id) {
+                let _ = app.update(message);
+            }
+            assert_eq!(app.is_modified, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_view_only_selects_but_does_not_edit() {
+        let (mut app, id) = app_with_selected_image();
+        let before = app.layout.get_image(&id).unwrap().bounds();
+        let _ = app.update(Message::ToggleViewOnly);
+        for message in [
+            CanvasMessage::CanvasClicked(5.0, 5.0),
+            CanvasMessage::CanvasClicked(60.0, 60.0),
+            CanvasMessage::StartMove(id.clone(), 60.0, 60.0),
+            CanvasMessage::MouseMoved(10.0, 10.0),
+            CanvasMessage::MouseMoved(30.0, 25.0),
+            CanvasMessage::MouseReleased,
+            CanvasMessage::StartResize(id.clone(), ResizeHandle::BottomRight),
+            CanvasMessage::MouseMoved(60.0, 60.0),
+            CanvasMessage::ImageResized(id.clone(), 10.0, 10.0),
+            CanvasMessage::EnterCropMode,
+        ] {
+            let _ = app.update(Message::CanvasMessage(message));
+        }
+        assert_eq!(app.layout.selected_image_id.as_deref(), Some(id.as_str()));
+        assert_eq!(app.layout.get_image(&id).unwrap().bounds(), before);
+        assert!(app.crop_edit.is_none());
+        assert!(!app.is_modified);
+    }
+
+    #[test]
+    fn test_drag_snaps_to_grid_on_release_unless_ctrl_is_held() {
+        let drag = |app: &mut PrintLayout, id: &str| {
+            for message in [
+                CanvasMessage::StartMove(id.to_string(), 60.0, 60.0),
+                CanvasMessage::MouseMoved(60.0, 60.0),
+                CanvasMessage::MouseMoved(73.0, 64.0),
+                CanvasMessage::MouseReleased,
+            ] {
+                let _ = app.update(Message::CanvasMessage(message));
+            }
+            let image = app.layout.get_image(id).unwrap();
+            (image.x_mm, image.y_mm)
         };
 
-        let settings_panel = column![
-            text("Settings").size(14),
-            horizontal_rule(1),
-            tab_buttons,
-            Space::with_height(Length::Fixed(10.0)),
-            scrollable(settings_content).height(Length::Fill),
-        ]
-        .spacing(5)
-        .padding(10)
-        .width(Length::Fixed(220.0));
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.snap_to_grid = true;
+        app.preferences.grid_size_mm = 10.0;
+        assert_eq!(drag(&mut app, &id), (60.0, 50.0));
+
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.snap_to_grid = true;
+        app.preferences.grid_size_mm = 10.0;
+        let _ = app.update(Message::ModifiersChanged(iced::keyboard::Modifiers::CTRL));
+        assert_eq!(drag(&mut app, &id), (63.0, 54.0));
+        assert!(app.is_modified);
+    }
+
+    #[test]
+    fn test_starts_read_only_when_config_dir_is_unwritable() {
+        let dir = std::env::temp_dir().join(format!("print_layout_app_read_only_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let blocker = dir.join("not_a_dir");
+        std::fs::write(&blocker, b"").unwrap();
+        let manager = ConfigManager::with_dirs_or_read_only(blocker.join("config"), blocker.join("cache"));
+
+        let (mut app, _) = PrintLayout::with_config_manager(manager);
+        assert!(app.config_manager.read_only_reason().is_some());
+        assert!(app.config_repair.is_none());
+        // Preference changes still apply for the session
+        let _ = app.update(Message::LockAspectDefaultToggled(false));
+        assert!(!app.preferences.lock_aspect_default);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_low_gpu_mode_follows_the_preference_or_the_flag() {
+        let (mut app, _) = app_with_selected_image();
+        app.preferences.low_gpu = false;
+        app.sync_low_gpu();
+        assert!(!app.canvas.low_gpu());
+        app.preferences.low_gpu = true;
+        app.sync_low_gpu();
+        assert!(app.canvas.low_gpu());
+
+        // The flag wins over a preference that's off
+        let (config_manager, _config_dir) = test_config_manager();
+        let (mut app, _) = PrintLayout::launch(config_manager, true, Vec::new());
+        app.preferences.low_gpu = false;
+        app.sync_low_gpu();
+        assert!(app.canvas.low_gpu());
+    }
+
+    #[test]
+    fn test_toggle_image_lock() {
+        let (mut app, id) = app_with_selected_image();
+        let _ = app.update(Message::ToggleImageLock);
+        assert!(app.layout.get_image(&id).unwrap().locked);
+        assert!(app.is_modified);
+        let _ = app.update(Message::ToggleImageLock);
+        assert!(!app.layout.get_image(&id).unwrap().locked);
+    }
+
+    #[test]
+    fn test_locked_image_refuses_edits() {
+        let (mut app, id) = app_with_selected_image();
+        let _ = app.update(Message::ToggleImageLock);
+        app.is_modified = false;
+        let before = app.layout.get_image(&id).unwrap().clone();
 
-        // ====================================================================
-        // A: PREVIEW AREA (Center - Canvas with scrollbars)
-        // ====================================================================
-        // Calculate canvas size based on page dimensions and zoom
-        let canvas_width = self.canvas.mm_to_pixels(self.layout.page.width_mm) + 40.0;
-        let canvas_height = self.canvas.mm_to_pixels(self.layout.page.height_mm) + 40.0;
-        
-        let canvas_elem: Element<'_, CanvasMessage> = canvas(&self.canvas)
-            .width(Length::Fixed(canvas_width))
-            .height(Length::Fixed(canvas_height))
-            .into();
-        let canvas_widget = canvas_elem.map(Message::CanvasMessage);
-        
-        // Wrap canvas in a container with padding for visual margin
-        let canvas_container = container(canvas_widget)
-            .padding(20)
-            .style(container::bordered_box);
+        for msg in [
+            CanvasMessage::StartMove(id.clone(), 60.0, 60.0),
+            CanvasMessage::MouseMoved(80.0, 90.0),
+            CanvasMessage::MouseReleased,
+        ] {
+            let _ = app.update(Message::CanvasMessage(msg));
+        }
+        assert_eq!(app.drag_mode, DragMode::None);
+        let _ = app.update(Message::RotateImageCW);
+        let _ = app.update(Message::ImageWidthChanged("20".to_string()));
+        let _ = app.update(Message::ImageXChanged("20".to_string()));
+        let _ = app.update(Message::FlipImageHorizontal);
+        let _ = app.update(Message::FlipImageVertical);
+        app.layout.get_image_mut(&id).unwrap().width_mm = 1000.0;
+        let _ = app.update(Message::ScaleImageToFitMedia);
+        assert_eq!(app.layout.get_image(&id).unwrap().width_mm, 1000.0);
+        app.layout.get_image_mut(&id).unwrap().width_mm = before.width_mm;
+        let _ = app.update(Message::DeleteImageClicked);
 
-        // Wrap in scrollable for both directions
-        let preview_area = scrollable(
-            scrollable(canvas_container)
-                .direction(scrollable::Direction::Horizontal(
-                    scrollable::Scrollbar::default()
-                ))
-        )
-        .direction(scrollable::Direction::Vertical(
-            scrollable::Scrollbar::default()
-        ))
-        .width(Length::Fill)
-        .height(Length::Fill);
+        let after = app.layout.get_image(&id).expect("locked image kept");
+        assert_eq!(after.bounds(), before.bounds());
+        assert_eq!(after.rotation_degrees, before.rotation_degrees);
+        assert_eq!((after.flip_horizontal, after.flip_vertical), (before.flip_horizontal, before.flip_vertical));
+        assert!(!app.is_modified);
+        assert!(app.toast.as_deref().is_some_and(|t| t.contains("unlock")));
+    }
 
-        // ====================================================================
-        // E: THUMBNAILS AREA (Bottom with image thumbnails)
-        // ====================================================================
-        let thumbnails: Vec<Element<'_, Message>> = self.layout.images.iter().map(|img| {
-            let filename = img.path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("?");
-            
-            // Truncate filename if too long
-            let display_name = if filename.len() > 12 {
-                format!("{}...", &filename[..9])
-            } else {
-                filename.to_string()
-            };
-            
-            let is_selected = self.layout.selected_image_id.as_ref() == Some(&img.id);
-            let style = if is_selected { button::primary } else { button::secondary };
-            
-            // Use cached thumbnail handle or create from path
-            let img_handle = self.thumbnail_cache
-                .get(&img.path)
-                .cloned()
-                .unwrap_or_else(|| iced::widget::image::Handle::from_path(&img.path));
-            
-            let thumb_image = iced_image(img_handle)
-                .width(Length::Fixed(60.0))
-                .height(Length::Fixed(60.0));
-            
-            let thumb_btn = button(
-                column![
-                    thumb_image,
-                    text(display_name).size(9),
-                ]
-                .align_x(Alignment::Center)
-                .spacing(2)
-            )
-            .on_press(Message::ThumbnailClicked(img.id.clone()))
-            .style(style)
-            .padding(5);
+    #[test]
+    fn test_position_fields_move_the_image_within_the_page() {
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.measurement_unit = MeasurementUnit::Millimeters;
+        app.sync_tool_fields();
+        let (page_w, width) = (app.layout.page.width_mm, app.layout.get_image(&id).unwrap().width_mm);
 
-            thumb_btn.into()
-        }).collect();
+        let _ = app.update(Message::ImageXChanged("12.5".to_string()));
+        let _ = app.update(Message::ImageYChanged("30".to_string()));
+        let image = app.layout.get_image(&id).unwrap();
+        assert_eq!((image.x_mm, image.y_mm), (12.5, 30.0));
+        assert!(app.is_modified);
 
-        let thumbnails_row = if thumbnails.is_empty() {
-            row![text("No images. Click 'Add Image' to add photos.").size(12)]
-                .spacing(10)
-                .padding(10)
-        } else {
-            let mut r = row![].spacing(10).padding(10);
-            for thumb in thumbnails {
-                r = r.push(thumb);
-            }
-            r
+        // Off the page is refused with the largest X that fits
+        let _ = app.update(Message::ImageXChanged(format!("{}", page_w)));
+        assert_eq!(app.layout.get_image(&id).unwrap().x_mm, 12.5);
+        assert!(app
+            .image_position_error
+            .as_deref()
+            .is_some_and(|e| e.contains(&MeasurementUnit::Millimeters.format(page_w - width))));
+        let _ = app.update(Message::ImageXChanged("-1".to_string()));
+        assert_eq!(app.layout.get_image(&id).unwrap().x_mm, 12.5);
+
+        // Dragging shows the live position, and letting go refreshes the fields
+        for msg in [
+            CanvasMessage::StartMove(id.clone(), 20.0, 40.0),
+            CanvasMessage::MouseMoved(20.0, 40.0),
+            CanvasMessage::MouseMoved(30.0, 45.0),
+        ] {
+            let _ = app.update(Message::CanvasMessage(msg));
+        }
+        assert!(app.drag_readout().is_some_and(|r| r.starts_with("X 22.5  Y 35.0")));
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::MouseReleased));
+        assert_eq!(app.drag_readout(), None);
+        let image = app.layout.get_image(&id).unwrap();
+        assert_eq!(app.image_x_input, MeasurementUnit::Millimeters.format(image.x_mm));
+        assert_eq!(app.image_y_input, MeasurementUnit::Millimeters.format(image.y_mm));
+        assert_eq!(app.image_position_error, None);
+    }
+
+    #[test]
+    fn test_fit_to_page_and_center_buttons_edit_the_selected_image() {
+        let (mut app, id) = app_with_selected_image();
+        let _ = app.update(Message::CenterImage);
+        assert!(app.is_modified);
+        let (area_x, _, area_w, _) = app.layout.page.printable_area();
+        let (x, _, w, _) = app.layout.get_image(&id).unwrap().bounds();
+        assert!((x + w / 2.0 - (area_x + area_w / 2.0)).abs() < 1e-3);
+
+        let _ = app.update(Message::FitImageToPage);
+        let (_, _, w, h) = app.layout.get_image(&id).unwrap().bounds();
+        assert!((w - area_w).abs() < 1e-3 && (w / h - 1.5).abs() < 1e-3);
+        assert_eq!(app.image_width_input, app.preferences.measurement_unit.format(w));
+    }
+
+    #[test]
+    fn test_suggested_paper_is_offered_then_applied_around_the_content() {
+        let (mut app, id) = app_with_selected_image();
+        let _ = app.update(Message::SuggestPaperSize);
+        assert_eq!(app.paper_suggestion, Some((PaperSize::Photo3_5x5, LayoutOrientation::Landscape)));
+        let _ = app.update(Message::ApplyPaperSuggestion);
+        assert_eq!(app.paper_suggestion, None);
+        assert_eq!(app.layout.page.paper_size, PaperSize::Photo3_5x5);
+        assert_eq!(app.layout.page.orientation, LayoutOrientation::Landscape);
+        assert!(app.is_modified);
+        let (x, _, w, _) = app.layout.get_image(&id).unwrap().bounds();
+        assert!((x + w / 2.0 - app.layout.page.width_mm / 2.0).abs() < 1e-3);
+
+        let _ = app.update(Message::SuggestPaperSize);
+        assert_eq!(app.paper_suggestion, None);
+        assert!(app.toast.as_deref().is_some_and(|t| t.contains("already the smallest")));
+    }
+
+    #[test]
+    fn test_arrange_grid_tiles_the_image_and_reports_problems() {
+        let (mut app, _) = app_with_selected_image();
+        let _ = app.update(Message::ArrangeGrid(2, 3));
+        assert_eq!(app.layout.images.len(), 6);
+        assert!(app.is_modified);
+        assert_eq!(app.toast, None);
+
+        let _ = app.update(Message::ArrangeGrid(20, 20));
+        assert!(app.toast.as_deref().is_some_and(|t| t.contains("don't fit")));
+        assert_eq!(app.layout.images.len(), 6);
+    }
+
+    #[test]
+    fn test_layout_template_is_applied_from_the_picker() {
+        let (mut app, _) = app_with_selected_image();
+        let _ = app.update(Message::ApplyLayoutTemplate);
+        assert!(!app.is_modified);
+
+        let four_up = LayoutTemplate::built_ins().into_iter().find(|t| t.name == "4-up").unwrap();
+        let _ = app.update(Message::LayoutTemplateSelected(four_up));
+        let _ = app.update(Message::ApplyLayoutTemplate);
+        assert_eq!(app.layout.images.len(), 4);
+        assert!(app.layout.images.iter().all(|img| img.crop.is_some()));
+        assert!(app.is_modified);
+        assert_eq!(app.toast, None);
+    }
+
+    #[test]
+    fn test_auto_arrange_flags_images_that_do_not_fit() {
+        let (mut app, id) = app_with_selected_image();
+        let mut huge = PlacedImage::new(PathBuf::from("/tmp/poster.jpg"), 4000, 4000);
+        (huge.x_mm, huge.y_mm, huge.width_mm, huge.height_mm) = (0.0, 0.0, 400.0, 400.0);
+        let huge_id = huge.id.clone();
+        app.layout.add_image(huge);
+
+        let _ = app.update(Message::AutoArrange);
+        let (area_x, area_y, _, _) = app.layout.page.printable_area();
+        let photo = app.layout.get_image(&id).unwrap();
+        assert_eq!((photo.x_mm, photo.y_mm), (area_x, area_y));
+        assert_eq!(app.layout.get_image(&huge_id).unwrap().bounds(), (0.0, 0.0, 400.0, 400.0));
+        assert!(app.is_modified);
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with("1 image(s) didn't fit")));
+
+        let _ = app.update(Message::AutoArrangeGutterChanged("-3".to_string()));
+        assert_eq!(app.auto_arrange_gutter_input, "-3");
+        assert!(app.preferences.auto_arrange_gutter_mm >= 0.0);
+    }
+
+    #[test]
+    fn test_contact_sheet_asks_before_replacing_the_layout() {
+        let (mut app, _) = app_with_selected_image();
+        let mut sheet_layout = Layout::new();
+        sheet_layout.page.captions = true;
+        sheet_layout.add_image(PlacedImage::new(PathBuf::from("/shoot/a.jpg"), 1200, 800));
+        let sheet = ContactSheet {
+            layout: sheet_layout,
+            skipped: vec![(PathBuf::from("/shoot/broken.jpg"), "not an image".to_string())],
+            left_out: 0,
         };
 
-        let thumbnails_area = column![
-            row![
-                text("Thumbnails").size(12),
-                Space::with_width(Length::Fill),
-                text(format!("{} image(s)", self.layout.images.len())).size(11),
-            ]
-            .padding(Padding::from([5, 10])),
-            scrollable(thumbnails_row).direction(scrollable::Direction::Horizontal(
-                scrollable::Scrollbar::default()
-            )),
-        ]
-        .height(Length::Fixed(120.0));
+        let _ = app.update(Message::ContactSheetBuilt(Ok(sheet.clone())));
+        assert!(app.pending_contact_sheet.is_some());
+        let _ = app.update(Message::CancelContactSheet);
+        assert_eq!(app.layout.images[0].path, PathBuf::from("/tmp/photo.jpg"));
 
-        // ====================================================================
-        // F: PRINT BUTTON AREA (Bottom right)
-        // ====================================================================
-        let print_button = if self.selected_printer.is_some() && !self.layout.images.is_empty() {
-            button(text("Print").size(16))
-                .on_press(Message::PrintClicked)
-                .padding(Padding::from([10, 30]))
-        } else {
-            button(text("Print").size(16))
-                .padding(Padding::from([10, 30]))
+        let _ = app.update(Message::ContactSheetBuilt(Ok(sheet)));
+        let _ = app.update(Message::ConfirmContactSheet);
+        assert_eq!(app.layout.images[0].path, PathBuf::from("/shoot/a.jpg"));
+        assert!(app.layout.page.captions);
+        assert!(app.is_modified);
+        assert_eq!(app.current_file, None);
+        assert_eq!(app.import_errors.len(), 1);
+    }
+
+    #[test]
+    fn test_image_files_in_lists_images_by_name() {
+        let dir = std::env::temp_dir().join(format!("print_layout_contact_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.jpg")).unwrap();
+        for name in ["b.JPG", "a.png", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        assert_eq!(image_files_in(&dir).unwrap(), vec![dir.join("a.png"), dir.join("b.JPG")]);
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(image_files_in(&dir).is_err());
+    }
+
+    #[test]
+    fn test_print_status_goes_through_sending() {
+        let (mut app, _) = app_with_selected_image();
+        app.print_status = PrintStatus::Rendering;
+        let job = PrintJob {
+            layout: app.layout.clone(),
+            printer_name: "Office".to_string(),
+            title: "Test".to_string(),
+            resample_filter: app.preferences.resample_filter,
+            copies: 1,
+            dpi: 300,
+            extra_options: Vec::new(),
+            cancel: None,
         };
+        let _ = app.update(Message::PrintRenderingDone(Ok((job, Vec::new()))));
+        assert_eq!(app.print_status, PrintStatus::Sending);
+        let _ = app.update(Message::PrintJobCompleted(Err("printer offline".to_string())));
+        assert_eq!(app.print_status, PrintStatus::Failed("printer offline".to_string()));
 
-        let print_area = row![
-            text("Copies:").size(12),
-            text_input("1", &self.copies_input)
-                .on_input(Message::CopiesChanged)
-                .width(Length::Fixed(50.0)),
-            Space::with_width(Length::Fixed(20.0)),
-            print_button,
-        ]
-        .spacing(10)
-        .padding(10)
-        .align_y(Alignment::Center);
+        // A failed render never reaches the printer
+        app.print_status = PrintStatus::Rendering;
+        let _ = app.update(Message::PrintRenderingDone(Err("out of memory".to_string())));
+        assert_eq!(app.print_status, PrintStatus::Failed("out of memory".to_string()));
+    }
 
-        // ====================================================================
-        // ASSEMBLE THE LAYOUT
-        // ====================================================================
-        // Top section: Stored settings
-        // Middle section: Tools + Preview + Settings
-        // Bottom section: Thumbnails + Print button
+    #[test]
+    fn test_rotation_handle_drag_turns_to_any_angle() {
+        // Straight up is no turn; to the right is a quarter turn clockwise
+        assert_eq!(rotation_from_drag((50.0, 50.0), (50.0, 10.0), None), 0.0);
+        assert_eq!(rotation_from_drag((50.0, 50.0), (90.0, 50.0), None), 90.0);
+        assert_eq!(rotation_from_drag((50.0, 50.0), (10.0, 50.0), None), 270.0);
+        let small = rotation_from_drag((50.0, 50.0), (52.45, 10.0), None);
+        assert_eq!(small, 3.5);
+        assert_eq!(rotation_from_drag((50.0, 50.0), (52.45, 10.0), Some(15.0)), 0.0);
 
-        let middle_section = row![
-            column![
-                preview_area,
-            ]
-            .width(Length::Fill)
-            .height(Length::Fill),
-            vertical_rule(1),
-            settings_panel,
-        ];
+        let (mut app, id) = app_with_selected_image();
+        let center = app.layout.get_image(&id).unwrap().frame_point(0.5, 0.5);
+        for msg in [
+            CanvasMessage::StartRotate(id.clone()),
+            CanvasMessage::MouseMoved(center.0 + 2.45, center.1 - 40.0),
+            CanvasMessage::MouseReleased,
+        ] {
+            let _ = app.update(Message::CanvasMessage(msg));
+        }
+        let image = app.layout.get_image(&id).unwrap();
+        assert_eq!(image.rotation_degrees, 3.5);
+        assert_eq!(app.image_rotation_input, "3.5");
+        assert_eq!(app.drag_mode, DragMode::None);
+        assert!(app.is_modified);
 
-        let bottom_section = row![
-            container(thumbnails_area).width(Length::Fill),
-            vertical_rule(1),
-            print_area,
-        ]
-        .height(Length::Fixed(120.0));
+        // The canvas shows the angle next to the handle mid-turn, and
+        // Escape puts it back
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::StartRotate(id.clone())));
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::MouseMoved(center.0 + 40.0, center.1)));
+        assert_eq!(app.canvas.rotate_readout().map(|(_, label)| label).as_deref(), Some("90°"));
+        assert_eq!(app.drag_readout(), None);
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Deselect, false));
+        assert_eq!(app.drag_mode, DragMode::None);
+        assert_eq!(app.canvas.rotate_readout(), None);
+        assert_eq!(app.layout.get_image(&id).unwrap().rotation_degrees, 3.5);
+        assert_eq!(app.layout.selected_image_id.as_deref(), Some(id.as_str()));
+        assert_eq!(app.image_rotation_input, "3.5");
 
-        let main_content = column![
-            stored_settings_area,
-            horizontal_rule(1),
-            tools_area,
-            horizontal_rule(1),
-            middle_section,
-            horizontal_rule(1),
-            bottom_section,
-        ];
+        // Past 45° the frame swaps sides but stays centered
+        let _ = app.update(Message::ImageRotationChanged("60".to_string()));
+        let image = app.layout.get_image(&id).unwrap();
+        let moved = image.frame_point(0.5, 0.5);
+        assert!((moved.0 - center.0).abs() < 1e-3 && (moved.1 - center.1).abs() < 1e-3);
+    }
 
-        let base = container(main_content)
-            .width(Length::Fill)
-            .height(Length::Fill);
+    #[test]
+    fn test_new_graphics_are_trimmed_to_their_artwork() {
+        let dir = std::env::temp_dir().join(format!("print_layout_app_trim_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let logo = dir.join("logo.png");
+        let photo = dir.join("photo.jpg");
+        let alpha = |x: u32| if (40..60).contains(&x) { 255 } else { 0 };
+        ::image::RgbaImage::from_fn(100, 50, |x, _| ::image::Rgba([0, 0, 0, alpha(x)])).save(&logo).unwrap();
+        ::image::RgbImage::new(100, 50).save(&photo).unwrap();
 
-        // Create the base with optional overlays
-        let dark_text = Color::from_rgb(0.1, 0.1, 0.1);
-        
-        // First, check if we need to show the recovery dialog
-        if self.show_recovery_dialog {
-            let modal_content = container(
-                column![
-                    text("Recover Unsaved Work?").size(20).color(dark_text),
-                    Space::with_height(Length::Fixed(15.0)),
-                    text("An auto-save file was found from a previous session.").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
-                    text("Would you like to recover it?").size(14).color(Color::from_rgb(0.3, 0.3, 0.3)),
-                    Space::with_height(Length::Fixed(20.0)),
-                    row![
-                        button(text("Recover").size(14))
-                            .on_press(Message::RecoverAutoSave)
-                            .padding(Padding::from([10, 30])),
-                        Space::with_width(Length::Fixed(20.0)),
-                        button(text("Discard").size(14))
-                            .on_press(Message::DiscardAutoSave)
-                            .style(button::secondary)
-                            .padding(Padding::from([10, 30])),
-                    ]
-                    .spacing(10),
-                ]
-                .align_x(Alignment::Center)
-                .spacing(5)
-            )
-            .padding(40)
-            .style(|_theme| container::Style {
-                background: Some(iced::Background::Color(Color::WHITE)),
-                border: iced::Border {
-                    color: Color::from_rgb(0.3, 0.5, 0.8),
-                    width: 3.0,
-                    radius: 12.0.into(),
-                },
-                ..Default::default()
-            });
+        // Only graphics with an alpha channel are queued for measuring
+        let mut app = test_app();
+        app.preferences.trim_transparent_on_add = true;
+        let id = app.add_image_file(logo.clone()).unwrap();
+        app.add_image_file(photo).unwrap();
+        assert_eq!(app.pending_transparency_trims, vec![(id.clone(), logo)]);
+        let _ = app.trim_new_transparent_images();
+        assert!(app.pending_transparency_trims.is_empty());
 
-            return iced::widget::stack![
-                base,
-                opaque(
-                    mouse_area(
-                        center(modal_content)
-                            .style(|_theme| container::Style {
-                                background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
-                                ..Default::default()
-                            })
-                    )
-                )
-            ]
-            .into();
+        let before = app.layout.get_image(&id).unwrap().bounds();
+        let _ = app.update(Message::TransparencyTrimDetected(id.clone(), Ok(Some((40, 0, 20, 50)))));
+        let (x, _, width, height) = app.layout.get_image(&id).unwrap().bounds();
+        assert!((x - (before.0 + before.2 * 0.4)).abs() < 0.01);
+        assert!((width - before.2 * 0.2).abs() < 0.01 && (height - before.3).abs() < 0.01);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_group_move_and_delete() {
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.snap_to_grid = false;
+        let mut second = PlacedImage::new(PathBuf::from("/tmp/other.jpg"), 1200, 800);
+        second.x_mm = 10.0;
+        second.y_mm = 150.0;
+        let other = second.id.clone();
+        app.layout.add_image(second);
+
+        // Ctrl+click adds the second image; dragging the first moves both
+        app.modifiers = iced::keyboard::Modifiers::CTRL;
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::CanvasClicked(20.0, 160.0)));
+        app.modifiers = iced::keyboard::Modifiers::default();
+        assert_eq!(app.layout.selected_ids(), vec![other.clone(), id.clone()]);
+        for message in [
+            CanvasMessage::StartMove(id.clone(), 60.0, 60.0),
+            CanvasMessage::MouseMoved(65.0, 70.0),
+            CanvasMessage::MouseReleased,
+        ] {
+            let _ = app.update(Message::CanvasMessage(message));
         }
-        
-        // Show recent files popup if toggled
-        if self.show_recent_files_menu && !self.preferences.recent_files.is_empty() {
-            let recent_items: Vec<Element<'_, Message>> = self.preferences.recent_files
-                .iter()
-                .take(10)
-                .map(|path| {
-                    let display_name = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("Unknown");
-                    let path_clone = path.clone();
-                    button(text(display_name).size(12))
-                        .width(Length::Fill)
-                        .on_press(Message::OpenRecentFile(path_clone))
-                        .style(button::text)
-                        .into()
-                })
-                .collect();
-            
-            let popup_content = container(
-                column(recent_items)
-                    .spacing(2)
-                    .width(Length::Fixed(250.0))
-            )
-            .padding(10)
-            .style(|_theme| container::Style {
-                background: Some(iced::Background::Color(Color::WHITE)),
-                border: iced::Border {
-                    color: Color::from_rgb(0.7, 0.7, 0.7),
-                    width: 1.0,
-                    radius: 4.0.into(),
-                },
-                ..Default::default()
-            });
+        assert_eq!(app.layout.get_image(&id).map(|i| (i.x_mm, i.y_mm)), Some((55.0, 60.0)));
+        assert_eq!(app.layout.get_image(&other).map(|i| (i.x_mm, i.y_mm)), Some((15.0, 160.0)));
+        assert_eq!(app.layout.selected_image_id.as_deref(), Some(id.as_str()));
+
+        // A plain click narrows the selection again
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::CanvasClicked(60.0, 70.0)));
+        assert_eq!(app.layout.selected_ids(), vec![id.clone()]);
+
+        // Band selection picks up both, and Delete removes them together
+        let _ = app.update(Message::CanvasMessage(CanvasMessage::SelectArea(0.0, 0.0, 200.0, 250.0)));
+        assert_eq!(app.layout.selected_ids().len(), 2);
+        let _ = app.update(Message::DeleteImageClicked);
+        assert!(app.layout.images.is_empty());
+        assert_eq!(app.layout.selected_image_id, None);
+        assert!(app.is_modified);
+    }
+
+    #[test]
+    fn test_canvas_keys_respect_text_focus_and_selection() {
+        let (mut app, id) = app_with_selected_image();
+        let start = app.layout.get_image(&id).unwrap().bounds();
+
+        // Typing in a text field leaves the image alone
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Delete, true));
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Nudge(0.0, -1.0), true));
+        assert_eq!(app.layout.get_image(&id).map(|i| i.bounds()), Some(start));
+        assert!(!app.is_modified);
+
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Nudge(10.0, -1.0), false));
+        let moved = app.layout.get_image(&id).unwrap().bounds();
+        assert_eq!((moved.0, moved.1), (start.0 + 10.0, start.1 - 1.0));
+        assert!(app.is_modified);
+
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Delete, false));
+        assert!(app.layout.images.is_empty());
+        assert_eq!(app.toast, None);
+
+        // With nothing selected a key says so
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Rotate { clockwise: true }, false));
+        assert!(app.toast.is_some());
+        let _ = app.update(Message::ToastExpired);
+        assert_eq!(app.toast, None);
+    }
+
+    #[test]
+    fn test_duplicate_undo_redo_and_escape_shortcuts() {
+        let (mut app, id) = app_with_selected_image();
+        let document = app.document();
+        app.history.reset(&document);
+        let start = app.layout.get_image(&id).unwrap().bounds();
+
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Nudge(0.1, 0.0), false));
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Duplicate, false));
+        assert_eq!(app.layout.images.len(), 2);
+        let copy = app.layout.selected_image_id.clone().unwrap();
+        assert_ne!(copy, id);
+        let (x, y, _, _) = app.layout.get_image(&copy).unwrap().bounds();
+        assert!((x - (start.0 + 0.1 + DUPLICATE_OFFSET_MM)).abs() < 1e-4);
+        assert_eq!(y, start.1 + DUPLICATE_OFFSET_MM);
+
+        // Undo takes back the copy, then the nudge; redo brings the copy back
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Undo, false));
+        assert_eq!(app.layout.images.len(), 1);
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Undo, false));
+        assert_eq!(app.layout.get_image(&id).unwrap().bounds(), start);
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Undo, false));
+        assert_eq!(app.toast.as_deref(), Some("Nothing to undo"));
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Redo, false));
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Redo, false));
+        assert_eq!(app.layout.images.len(), 2);
+
+        // Escape closes the shortcut list first, then clears the selection
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::ToggleHelp, false));
+        assert!(app.show_shortcut_help);
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Deselect, false));
+        assert!(!app.show_shortcut_help);
+        assert!(app.layout.selected_image_id.is_some());
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::Deselect, false));
+        assert_eq!(app.layout.selected_image_id, None);
+    }
+
+    #[test]
+    fn test_pages_are_edited_apart_undone_and_printed_in_turn() {
+        let (mut app, id) = app_with_selected_image();
+        let document = app.document();
+        app.history.reset(&document);
+        let _ = app.update(Message::AddPage);
+        assert_eq!((app.document.current_page(), app.document.page_count()), (1, 2));
+        assert!(app.layout.images.is_empty());
+        assert_eq!(app.layout.page.paper_size, app.document.pages()[0].page.paper_size);
+        app.layout.add_image(PlacedImage::new(PathBuf::from("/tmp/second.jpg"), 800, 1200));
+        app.mark_modified();
+
+        let _ = app.update(Message::PreviousPage);
+        assert_eq!(app.layout.images.len(), 1);
+        assert!(app.layout.get_image(&id).is_some());
+        assert_eq!(app.document().pages()[1].images.len(), 1);
+
+        // Each page with images is its own job, sent in turn
+        app.selected_printer = Some("Office".to_string());
+        let _ = app.update(Message::PrintClicked);
+        assert_eq!(app.print_status, PrintStatus::Rendering);
+        assert_eq!(app.print_queue.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![1]);
+        let _ = app.update(Message::PrintJobCompleted(Err("printer offline".to_string())));
+        assert!(app.print_queue.is_empty());
+        app.print_status = PrintStatus::Idle;
+        let _ = app.update(Message::PrintPagesChanged("3".to_string()));
+        let _ = app.update(Message::PrintClicked);
+        assert_eq!(app.print_status, PrintStatus::Idle);
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with("Pages run from 1 to 2")));
+
+        // Undo takes back the second page's image, then the page itself
+        let _ = app.update(Message::Undo);
+        assert_eq!(app.document.current_page(), 1);
+        assert!(app.layout.images.is_empty());
+        let _ = app.update(Message::Undo);
+        assert_eq!(app.document.page_count(), 1);
+        assert!(app.layout.get_image(&id).is_some());
+    }
+
+    #[test]
+    fn test_spread_view_splits_images_dragged_across_the_fold() {
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.measurement_unit = MeasurementUnit::Millimeters;
+        let fold = app.layout.page.width_mm;
+        let _ = app.update(Message::AddPage);
+        app.layout.add_image(PlacedImage::new(PathBuf::from("/tmp/second.jpg"), 800, 1200));
+        app.mark_modified();
+
+        let _ = app.update(Message::ToggleSpreadView);
+        assert_eq!(app.document.current_page(), 0);
+        assert_eq!(app.layout.page.width_mm, fold * 2.0);
+        assert_eq!(app.layout.images.len(), 2);
+        let second = app.layout.images[1].id.clone();
+        app.select_element(second);
+        assert_eq!(app.image_x_input, MeasurementUnit::Millimeters.format(50.0));
+
+        // The paper can only change one page at a time
+        let _ = app.update(Message::PaperSizeSelected(PaperSize::A3));
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with("Leave the spread view")));
+        assert_eq!(app.layout.page.width_mm, fold * 2.0);
+        let margin = app.layout.page.margin_top_mm;
+        let _ = app.update(Message::MarginTopChanged("30".to_string()));
+        let _ = app.update(Message::CenterImage);
+        assert_eq!(app.layout.page.margin_top_mm, margin);
+        assert_eq!(app.layout.images[1].x_mm, fold + 50.0);
 
-            // Position the popup near the top-left where the buttons are
-            let popup_positioned = container(
-                column![
-                    Space::with_height(Length::Fixed(50.0)), // Offset from top
-                    row![
-                        Space::with_width(Length::Fixed(400.0)), // Offset from left to align with Recent button
-                        popup_content,
-                    ],
-                ]
-            )
-            .width(Length::Fill)
-            .height(Length::Fill);
+        // Centered on the fold, the first image becomes one half per page
+        app.select_element(id.clone());
+        for message in [
+            CanvasMessage::StartMove(id.clone(), 60.0, 60.0),
+            CanvasMessage::MouseMoved(60.0 + fold - 100.0, 60.0),
+            CanvasMessage::MouseReleased,
+        ] {
+            let _ = app.update(Message::CanvasMessage(message));
+        }
+        assert_eq!(app.layout.images.len(), 3);
+        assert_eq!(app.drag_readout(), None);
+        let document = app.document();
+        let [left, right] = [&document.pages()[0], &document.pages()[1]];
+        assert_eq!(left.images[0].bounds().2, 50.0);
+        assert_eq!(right.images.len(), 2);
+        assert!(right.images.iter().any(|img| img.x_mm == 0.0 && img.width_mm == 50.0));
+        assert_eq!((left.page.width_mm, right.page.width_mm), (fold, fold));
 
-            return iced::widget::stack![
-                base,
-                mouse_area(popup_positioned)
-                    .on_press(Message::ToggleRecentFilesMenu)
-            ]
-            .into();
+        // Exporting goes back to one page at a time, with the left half on it
+        let _ = app.update(Message::ExportClicked);
+        assert!(app.spread.is_none());
+        assert_eq!(app.layout.page.width_mm, fold);
+        assert_eq!(app.layout.images.len(), 1);
+        let _ = app.update(Message::NextPage);
+        assert_eq!(app.layout.images.len(), 2);
+        let _ = app.update(Message::ToggleSpreadView);
+        let _ = app.update(Message::ToggleSpreadView);
+        assert!(app.spread.is_none());
+    }
+
+    #[test]
+    fn test_session_journal_describes_edits_until_a_new_layout() {
+        let (mut app, _) = app_with_selected_image();
+        let document = app.document();
+        app.history.reset(&document);
+        app.preferences.measurement_unit = MeasurementUnit::Millimeters;
+        let _ = app.update(Message::ImageWidthChanged("120".to_string()));
+        let _ = app.update(Message::PaperSizeSelected(PaperSize::A3));
+        let texts: Vec<String> = app.journal.newest_first().map(|e| e.text.clone()).collect();
+        assert!(texts.iter().any(|t| t.starts_with("Paper size") && t.ends_with("→ A3")));
+        assert!(texts.last().unwrap().starts_with("Resized"));
+
+        // Undoing is part of the story too
+        let _ = app.update(Message::Undo);
+        let added = app.journal.len() - texts.len();
+        assert!(app.journal.newest_first().take(added).any(|e| e.text.starts_with("Paper size: A3 →")));
+
+        let _ = app.update(Message::NewLayout);
+        assert!(app.journal.is_empty());
+    }
+
+    #[test]
+    fn test_inches_are_converted_to_millimeters() {
+        let (mut app, id) = app_with_selected_image();
+        app.layout.page.try_set_margins(25.4, 25.4, 12.7, 12.7).unwrap();
+        app.preferences.measurement_unit = MeasurementUnit::Millimeters;
+        let _ = app.update(Message::UnitToggled);
+        assert_eq!(app.preferences.measurement_unit, MeasurementUnit::Inches);
+        assert_eq!((app.margin_top_input.as_str(), app.margin_left_input.as_str()), ("1", "0.5"));
+        let width = app.layout.get_image(&id).unwrap().width_mm;
+        assert_eq!(app.image_width_input, format!("{:.2}", width / 25.4));
+
+        // Typed values are inches, stored as millimeters
+        let _ = app.update(Message::ImageWidthChanged("2".to_string()));
+        assert!((app.layout.get_image(&id).unwrap().width_mm - 50.8).abs() < 1e-4);
+        let _ = app.update(Message::MarginTopChanged("0.75".to_string()));
+        assert!((app.layout.page.margin_top_mm - 19.05).abs() < 1e-4);
+        let _ = app.update(Message::MarginBottomChanged("x".to_string()));
+        assert_eq!(app.margin_errors[1].as_deref(), Some("Enter a margin in inches"));
+
+        let _ = app.update(Message::UnitToggled);
+        assert_eq!(app.margin_top_input, "19.05");
+        assert_eq!(app.image_width_input, "50.8");
+    }
+
+    #[test]
+    fn test_view_menu_toggles_saved_options() {
+        let (mut app, _) = app_with_selected_image();
+        app.preferences.view_options = ViewOptions::default();
+        app.preferences.snap_to_grid = false;
+        let _ = app.update(Message::ToggleViewMenu);
+        assert!(app.show_view_menu);
+        let _ = app.update(Message::ViewOptionToggled(ViewOption::Overlaps));
+        let _ = app.update(Message::ViewOptionToggled(ViewOption::Margins));
+        assert!(app.preferences.view_options.highlight_overlaps);
+        assert!(!app.preferences.view_options.show_margins);
+        // View options aren't part of the document
+        assert!(!app.is_modified);
+
+        // Snapping shows the grid without changing the saved choice
+        app.preferences.snap_to_grid = true;
+        assert!(canvas_view_options(&app.preferences).show_grid);
+        assert!(!app.preferences.view_options.show_grid);
+    }
+
+    #[test]
+    fn test_q_toggles_draft_preview_without_a_selection() {
+        let (mut app, _) = app_with_selected_image();
+        app.preferences.view_options = ViewOptions::default();
+        app.layout.select(None);
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::ToggleDraftPreview, false));
+        assert_eq!(app.preferences.view_options.render_quality, CanvasQuality::Draft);
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with("Draft")));
+        assert!(!app.is_modified);
+
+        // Typing a Q into a text field leaves the preview alone
+        let _ = app.update(Message::ShortcutFocusChecked(Shortcut::ToggleDraftPreview, true));
+        assert_eq!(app.preferences.view_options.render_quality, CanvasQuality::Draft);
+        let _ = app.update(Message::DraftPreviewToggled);
+        assert_eq!(app.preferences.view_options.render_quality, CanvasQuality::Full);
+    }
+
+    #[test]
+    fn test_rotation_input_tilts_and_swaps_frame_across_quarter_turns() {
+        let (mut app, id) = app_with_selected_image();
+        let (width, height) = (app.layout.get_image(&id).unwrap().width_mm, app.layout.get_image(&id).unwrap().height_mm);
+
+        let _ = app.update(Message::ImageRotationChanged("15".to_string()));
+        let image = app.layout.get_image(&id).unwrap();
+        assert_eq!((image.rotation_degrees, image.width_mm, image.height_mm), (15.0, width, height));
+        assert!(app.is_modified);
+
+        let _ = app.update(Message::ImageRotationChanged("-80".to_string()));
+        let image = app.layout.get_image(&id).unwrap();
+        assert_eq!((image.rotation_degrees, image.width_mm, image.height_mm), (280.0, height, width));
+
+        // Unparseable text is kept in the field but changes nothing
+        let _ = app.update(Message::ImageRotationChanged("-".to_string()));
+        assert_eq!(app.layout.get_image(&id).unwrap().rotation_degrees, 280.0);
+        assert_eq!(app.image_rotation_input, "-");
+    }
+
+    #[test]
+    fn test_typed_sizes_keep_the_shown_aspect_after_rotating() {
+        let (mut app, id) = app_with_selected_image();
+        let size = |app: &PrintLayout| {
+            let img = app.layout.get_image(&id).unwrap();
+            ((img.width_mm * 10.0).round() / 10.0, (img.height_mm * 10.0).round() / 10.0)
+        };
+        let (width, height) = size(&app);
+
+        // A quarter turn makes the 3:2 photo 2:3; typed widths follow that
+        let _ = app.update(Message::RotateImageCW);
+        assert_eq!(size(&app), (height, width));
+        let _ = app.update(Message::ImageWidthChanged("50".to_string()));
+        assert_eq!(size(&app), (50.0, 75.0));
+
+        // Turned back it is 3:2 again, at the new size
+        let _ = app.update(Message::RotateImageCCW);
+        assert_eq!(size(&app), (75.0, 50.0));
+        let _ = app.update(Message::ImageHeightChanged("20".to_string()));
+        assert_eq!(size(&app), (30.0, 20.0));
+
+        // A stretched frame keeps its own shape once the aspect is locked again
+        let _ = app.update(Message::MaintainAspectRatio(false));
+        let _ = app.update(Message::ImageWidthChanged("40".to_string()));
+        let _ = app.update(Message::MaintainAspectRatio(true));
+        let _ = app.update(Message::ImageWidthChanged("80".to_string()));
+        assert_eq!(size(&app), (80.0, 40.0));
+        assert_eq!(app.image_height_input, app.preferences.measurement_unit.format(40.0));
+    }
+
+    #[test]
+    fn test_copies_input_applies_valid_counts_only() {
+        let (mut app, id) = app_with_selected_image();
+
+        let _ = app.update(Message::ImageCopiesChanged(id.clone(), "3".to_string()));
+        assert_eq!(app.layout.get_image(&id).unwrap().copies, 3);
+        assert!(app.is_modified);
+        assert_eq!(app.canvas.copies_left_out(), 0);
+
+        // Empty, zero and out-of-range text stays in the field only
+        for value in ["", "0", "100", "x"] {
+            let _ = app.update(Message::ImageCopiesChanged(id.clone(), value.to_string()));
+            assert_eq!(app.layout.get_image(&id).unwrap().copies, 3);
+            assert_eq!(app.copies_inputs.get(&id).map(String::as_str), Some(value));
         }
 
-        // Show modal overlay when printing
-        match &self.print_status {
-            PrintStatus::Idle => base.into(),
-            PrintStatus::Rendering => {
-                let modal_content = container(
-                    column![
-                        text("PRINTING").size(24).color(dark_text),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("[  ]  Rendering...").size(16).color(dark_text),
-                        Space::with_height(Length::Fixed(20.0)),
-                        progress_bar(0.0..=100.0, 30.0)
-                            .width(Length::Fixed(250.0))
-                            .height(Length::Fixed(12.0)),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("Please wait...").size(14).color(Color::from_rgb(0.4, 0.4, 0.4)),
-                    ]
-                    .align_x(Alignment::Center)
-                    .spacing(5)
-                )
-                .padding(40)
-                .style(|_theme| container::Style {
-                    background: Some(iced::Background::Color(Color::WHITE)),
-                    border: iced::Border {
-                        color: Color::from_rgb(0.3, 0.5, 0.8),
-                        width: 3.0,
-                        radius: 12.0.into(),
-                    },
-                    ..Default::default()
-                });
+        // More copies than the page holds continue on extra sheets
+        let _ = app.update(Message::ImageCopiesChanged(id.clone(), "20".to_string()));
+        assert!(app.copies_inputs.is_empty());
+        assert!(app.canvas.copies_left_out() > 0);
+        assert!(app.canvas.overflow_sheets() > 0);
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with("Created")));
 
-                iced::widget::stack![
-                    base,
-                    opaque(
-                        mouse_area(
-                            center(modal_content)
-                                .style(|_theme| container::Style {
-                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
-                                    ..Default::default()
-                                })
-                        )
-                    )
-                ]
-                .into()
-            }
-            PrintStatus::Sending => {
-                let modal_content = container(
-                    column![
-                        text("PRINTING").size(24).color(dark_text),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("[>>]  Sending to printer...").size(16).color(dark_text),
-                        Space::with_height(Length::Fixed(20.0)),
-                        progress_bar(0.0..=100.0, 70.0)
-                            .width(Length::Fixed(250.0))
-                            .height(Length::Fixed(12.0)),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("Please wait...").size(14).color(Color::from_rgb(0.4, 0.4, 0.4)),
-                    ]
-                    .align_x(Alignment::Center)
-                    .spacing(5)
-                )
-                .padding(40)
-                .style(|_theme| container::Style {
-                    background: Some(iced::Background::Color(Color::WHITE)),
-                    border: iced::Border {
-                        color: Color::from_rgb(0.3, 0.5, 0.8),
-                        width: 3.0,
-                        radius: 12.0.into(),
-                    },
-                    ..Default::default()
-                });
+        // The note counts only the pages just added
+        let sheets_before = app.canvas.overflow_sheets();
+        let _ = app.update(Message::ImageCopiesChanged(id.clone(), "60".to_string()));
+        let added = app.canvas.overflow_sheets() - sheets_before;
+        assert!(added > 0 && added < app.canvas.overflow_sheets());
+        assert!(app.toast.as_deref().is_some_and(|t| t.starts_with(&format!("Created {} more", added))));
 
-                iced::widget::stack![
-                    base,
-                    opaque(
-                        mouse_area(
-                            center(modal_content)
-                                .style(|_theme| container::Style {
-                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
-                                    ..Default::default()
-                                })
-                        )
-                    )
-                ]
-                .into()
-            }
-            PrintStatus::Completed(job_id) => {
-                let modal_content = container(
-                    column![
-                        text("[OK]").size(36).color(Color::from_rgb(0.2, 0.7, 0.3)),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("Print Job Sent Successfully!").size(18).color(dark_text),
-                        Space::with_height(Length::Fixed(10.0)),
-                        text(format!("Job ID: {}", job_id)).size(13).color(Color::from_rgb(0.4, 0.4, 0.4)),
-                        Space::with_height(Length::Fixed(20.0)),
-                        button(text("OK").size(14))
-                            .on_press(Message::DismissPrintStatus)
-                            .padding(Padding::from([10, 40])),
-                    ]
-                    .align_x(Alignment::Center)
-                    .spacing(5)
-                )
-                .padding(40)
-                .style(|_theme| container::Style {
-                    background: Some(iced::Background::Color(Color::WHITE)),
-                    border: iced::Border {
-                        color: Color::from_rgb(0.2, 0.7, 0.3),
-                        width: 3.0,
-                        radius: 12.0.into(),
-                    },
-                    ..Default::default()
-                });
+        // Printing asks before using more than one sheet
+        app.selected_printer = Some("Office".to_string());
+        let _ = app.update(Message::PrintClicked);
+        assert_eq!(app.confirm_sheet_count, Some(1 + app.canvas.overflow_sheets()));
+        let _ = app.update(Message::CancelSheetsPrint);
+        assert_eq!(app.confirm_sheet_count, None);
+        assert_eq!(app.print_status, PrintStatus::Idle);
+    }
 
-                iced::widget::stack![
-                    base,
-                    opaque(
-                        mouse_area(
-                            center(modal_content)
-                                .style(|_theme| container::Style {
-                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
-                                    ..Default::default()
-                                })
-                        )
-                    )
-                ]
-                .into()
-            }
-            PrintStatus::Failed(error) => {
-                let modal_content = container(
-                    column![
-                        text("[!!]").size(36).color(Color::from_rgb(0.9, 0.3, 0.3)),
-                        Space::with_height(Length::Fixed(15.0)),
-                        text("Print Job Failed").size(18).color(dark_text),
-                        Space::with_height(Length::Fixed(10.0)),
-                        text(error).size(13).color(Color::from_rgb(0.5, 0.3, 0.3)),
-                        Space::with_height(Length::Fixed(20.0)),
-                        button(text("OK").size(14))
-                            .on_press(Message::DismissPrintStatus)
-                            .padding(Padding::from([10, 40])),
-                    ]
-                    .align_x(Alignment::Center)
-                    .spacing(5)
-                )
-                .padding(40)
-                .style(|_theme| container::Style {
-                    background: Some(iced::Background::Color(Color::WHITE)),
-                    border: iced::Border {
-                        color: Color::from_rgb(0.9, 0.3, 0.3),
-                        width: 3.0,
-                        radius: 12.0.into(),
-                    },
-                    ..Default::default()
-                });
+    #[test]
+    fn test_settings_scroll_survives_selection_but_not_tab_switch() {
+        let (mut app, id) = app_with_selected_image();
+        let other = PlacedImage::new(PathBuf::from("/tmp/other.jpg"), 800, 1200);
+        let other_id = other.id.clone();
+        app.layout.add_image(other);
+        app.settings_tab = SettingsTab::ImageTools;
+        app.settings_scroll = scrollable::AbsoluteOffset { x: 0.0, y: 240.0 };
 
-                iced::widget::stack![
-                    base,
-                    opaque(
-                        mouse_area(
-                            center(modal_content)
-                                .style(|_theme| container::Style {
-                                    background: Some(iced::Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
-                                    ..Default::default()
-                                })
-                        )
-                    )
-                ]
-                .into()
-            }
+        let _ = app.update(Message::ThumbnailClicked(other_id));
+        let _ = app.update(Message::ThumbnailClicked(id));
+        assert_eq!(app.settings_scroll.y, 240.0);
+
+        let _ = app.update(Message::SettingsTabChanged(SettingsTab::ImageTools));
+        assert_eq!(app.settings_scroll.y, 240.0);
+        let _ = app.update(Message::SettingsTabChanged(SettingsTab::Layout));
+        assert_eq!(app.settings_scroll.y, 0.0);
+    }
+
+    #[test]
+    fn test_fit_zoom_is_remembered_until_a_manual_zoom() {
+        let (mut app, _) = app_with_selected_image();
+        app.preferences.fit_on_resize = true;
+        let _ = app.update(Message::ZoomToFitWidth);
+        assert_eq!(app.zoom_fit, Some(ZoomFit::Width));
+        assert!(app.zoom_text.ends_with("(Fit Width)"));
+
+        // A wider window re-fits the width
+        let before = app.zoom;
+        let wider = Size::new(app.window_size.width + 400.0, app.window_size.height);
+        let _ = app.update(Message::WindowResized(wider));
+        assert!(app.zoom > before);
+
+        // Zooming by hand stops following the window
+        let _ = app.update(Message::ZoomIn);
+        assert_eq!(app.zoom_fit, None);
+        let zoom = app.zoom;
+        let _ = app.update(Message::WindowResized(Size::new(800.0, 600.0)));
+        assert_eq!(app.zoom, zoom);
+
+        for _ in 0..30 {
+            let _ = app.update(Message::ZoomIn);
         }
+        assert_eq!(app.zoom, MAX_ZOOM);
+        assert_eq!(zoom_label(0.5, None), "50%");
     }
 
-    pub fn title(&self) -> String {
-        let base_title = match &self.current_file {
-            Some(path) => {
-                let filename = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unnamed");
-                format!("Print Layout - {}", filename)
-            }
-            None => "Print Layout".to_string(),
+    #[test]
+    fn test_palette_filters_and_applies_a_style() {
+        let (mut app, id) = app_with_selected_image();
+        app.preferences.recent_files = vec![PathBuf::from("/home/me/Holiday.pxl")];
+        app.preferences.image_styles = ["Faded", "Mirror"]
+            .iter()
+            .map(|name| ImageStyle {
+                name: name.to_string(),
+                rotation_degrees: 0.0,
+                flip_horizontal: *name == "Mirror",
+                flip_vertical: false,
+                opacity: 0.5,
+                crop_aspect: None,
+            })
+            .collect();
+
+        let _ = app.update(Message::OpenPalette);
+        assert_eq!(app.palette_matches().len(), 3);
+        let _ = app.update(Message::PaletteQueryChanged("mir".to_string()));
+        let matches = app.palette_matches();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].item, PaletteItem::ImageStyle(1));
+        // Moving wraps around the filtered list
+        let _ = app.update(Message::PaletteMove(1));
+        assert_eq!(app.palette_selected, 0);
+
+        let _ = app.update(Message::PaletteActivate);
+        assert!(!app.palette_open);
+        assert!(app.layout.get_image(&id).unwrap().flip_horizontal);
+        assert!(app.is_modified);
+    }
+
+    #[test]
+    fn test_aspect_lock_is_per_image() {
+        let (mut app, squashed) = app_with_selected_image();
+        let other = PlacedImage::new(PathBuf::from("/tmp/other.jpg"), 1200, 800);
+        let other_id = other.id.clone();
+        app.layout.add_image(other);
+
+        let _ = app.update(Message::MaintainAspectRatio(false));
+        let _ = app.update(Message::ImageHeightChanged("20".to_string()));
+        let img = app.layout.get_image(&squashed).unwrap();
+        assert!(!img.lock_aspect);
+        assert_eq!((img.width_mm, img.height_mm), (100.0, 20.0));
+
+        // The other image is still locked and keeps its proportions
+        app.layout.selected_image_id = Some(other_id.clone());
+        let _ = app.update(Message::ImageHeightChanged("40".to_string()));
+        let img = app.layout.get_image(&other_id).unwrap();
+        assert!(img.lock_aspect);
+        assert!((img.width_mm - 60.0).abs() < 1e-3);
+        assert!(app.preferences.lock_aspect_default);
+    }
+
+    #[test]
+    fn test_startup_checks_wait_for_the_first_frame() {
+        let mut app = test_app();
+        assert!(app.startup.is_some());
+        assert!(app.discovering_printers);
+        assert!(!app.show_recovery_dialog);
+
+        let _ = app.update(Message::FirstFrameShown);
+        assert!(app.startup.is_none());
+
+        // Results arrive as messages once the background checks finish
+        let missing = PathBuf::from("/mnt/usb/Old.pxl");
+        let _ = app.update(Message::MissingRecentFilesFound(vec![missing.clone()]));
+        assert_eq!(app.missing_recent_files, vec![missing]);
+        let _ = app.update(Message::AutoSaveChecked(true));
+        assert!(app.show_recovery_dialog);
+    }
+
+    #[test]
+    fn test_no_printers_leaves_nothing_selected() {
+        let (mut app, _) = app_with_selected_image();
+        app.selected_printer = Some("Office".to_string());
+        let _ = app.update(Message::PrintersDiscovered(Vec::new()));
+        assert_eq!(app.selected_printer, None);
+        assert!(!app.discovering_printers);
+
+        // An empty name is never stored
+        let _ = app.update(Message::PrinterSelected(String::new()));
+        assert_eq!(app.selected_printer, None);
+
+        let _ = app.update(Message::RetryPrinterDiscovery);
+        assert!(app.discovering_printers);
+    }
+
+    #[test]
+    fn test_paper_size_list_follows_the_printer() {
+        assert_eq!(paper_sizes_for_printer(None, PaperSize::A4), paper_size_choices());
+        let caps = PrinterCapabilities {
+            printer_name: "Photo".to_string(),
+            options: vec![PrinterOption {
+                name: "PageSize".to_string(),
+                display_name: "Page Size".to_string(),
+                values: ["4x6", "5x7.Borderless"]
+                    .iter()
+                    .map(|v| printing::PrinterOptionValue { value: v.to_string(), is_default: false })
+                    .collect(),
+                default_index: None,
+            }],
         };
-        
-        if self.is_modified {
-            format!("{}*", base_title)
-        } else {
-            base_title
-        }
+        // The current paper stays listed even if the printer lacks it
+        assert_eq!(
+            paper_sizes_for_printer(Some(&caps), PaperSize::A4),
+            vec![PaperSize::Photo4x6, PaperSize::Photo5x7, PaperSize::A4]
+        );
     }
 
-    fn theme(&self) -> Theme {
-        Theme::default()
+    #[test]
+    fn test_custom_paper_size_from_typed_fields() {
+        let (mut app, _) = app_with_selected_image();
+        app.preferences.measurement_unit = MeasurementUnit::Millimeters;
+        let _ = app.update(Message::PaperSizeSelected(PaperSize::Custom(210.0, 297.0)));
+        assert_eq!(app.custom_width_input, "210");
+
+        let _ = app.update(Message::CustomWidthChanged("150".to_string()));
+        let _ = app.update(Message::CustomHeightChanged("".to_string()));
+        assert!(app.custom_size_error.is_some());
+        let _ = app.update(Message::CustomHeightChanged("400".to_string()));
+        assert_eq!(app.custom_size_error, None);
+        assert_eq!(app.layout.page.paper_size, PaperSize::Custom(150.0, 400.0));
+        assert_eq!((app.layout.page.width_mm, app.layout.page.height_mm), (150.0, 400.0));
+
+        // Too big for a printer whose largest paper is A4
+        assert_eq!(custom_paper_size(150.0, 280.0, Some((210.0, 297.0))), Ok(PaperSize::Custom(150.0, 280.0)));
+        assert!(custom_paper_size(150.0, 400.0, Some((210.0, 297.0))).is_err());
+        assert!(custom_paper_size(10.0, 100.0, None).is_err());
+    }
+
+    #[test]
+    fn test_paths_from_clipboard() {
+        let text = "copy\nfile:///home/me/My%20Photos/a.JPG\n/home/me/b.png\nnotes.txt\nrelative.jpg\n";
+        assert_eq!(
+            paths_from_clipboard(text),
+            vec![PathBuf::from("/home/me/My Photos/a.JPG"), PathBuf::from("/home/me/b.png")]
+        );
+        assert!(paths_from_clipboard("just some text").is_empty());
+    }
+
+    #[test]
+    fn test_open_at_launch_prefers_a_layout_over_images() {
+        let files = |list: &[&str]| list.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert!(matches!(
+            open_at_launch(files(&["a.png", "b.PXL", "c.pxl"])),
+            Some(Message::LayoutOpenPathSelected(Some(path))) if path == std::path::Path::new("b.PXL")
+        ));
+        assert!(matches!(
+            open_at_launch(files(&["a.png", "notes.txt", "b.jpg"])),
+            Some(Message::ImageFilesSelected(paths)) if paths == files(&["a.png", "b.jpg"])
+        ));
+        assert!(open_at_launch(files(&["notes.txt"])).is_none());
+        assert!(open_at_launch(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_format_length_trims_zeros() {
+        assert_eq!(format_length(150.0), "150");
+        assert_eq!(format_length(5.9055), "5.91");
+        assert_eq!(format_length(4.5), "4.5");
     }
 }
@@ -0,0 +1,267 @@
+// manifest.rs - Script-authored placement manifests
+// A manifest lists one image per row (path, x_mm, y_mm, width_mm, height_mm, rotation)
+// as CSV or as a JSON array of objects, and is turned into a Layout.
+
+use crate::layout::{Layout, Page, PlacedImage};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// One placement row from a manifest
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ManifestEntry {
+    pub path: PathBuf,
+    pub x_mm: f32,
+    pub y_mm: f32,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    /// Degrees clockwise, multiple of 90; defaults to 0
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+/// A manifest row that could not be used
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    /// CSV line number or 1-based JSON array index
+    pub row: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Row {}: {}", self.row, self.message)
+    }
+}
+
+/// Rows read from a manifest, before image files are checked
+#[derive(Debug, Default)]
+pub struct ParsedManifest {
+    pub entries: Vec<(usize, ManifestEntry)>,
+    pub errors: Vec<RowError>,
+}
+
+/// Result of importing a manifest: the layout built from the good rows
+/// and the rows that were skipped
+#[derive(Debug, Clone)]
+pub struct ManifestImport {
+    pub layout: Layout,
+    pub errors: Vec<RowError>,
+}
+
+const CSV_COLUMNS: [&str; 6] = ["path", "x_mm", "y_mm", "width_mm", "height_mm", "rotation"];
+
+/// Parse manifest text. Input starting with `[` is read as JSON, anything
+/// else as CSV. Fails only if JSON input is not an array at all.
+pub fn parse_manifest(contents: &str) -> Result<ParsedManifest, String> {
+    if contents.trim_start().starts_with('[') {
+        parse_json(contents)
+    } else {
+        Ok(parse_csv(contents))
+    }
+}
+
+fn parse_json(contents: &str) -> Result<ParsedManifest, String> {
+    let rows: Vec<serde_json::Value> =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid JSON manifest: {}", e))?;
+    let mut parsed = ParsedManifest::default();
+    for (index, value) in rows.into_iter().enumerate() {
+        let row = index + 1;
+        match serde_json::from_value::<ManifestEntry>(value) {
+            Ok(entry) => match validate_entry(&entry) {
+                Ok(()) => parsed.entries.push((row, entry)),
+                Err(message) => parsed.errors.push(RowError { row, message }),
+            },
+            Err(e) => parsed.errors.push(RowError { row, message: e.to_string() }),
+        }
+    }
+    Ok(parsed)
+}
+
+/// CSV rows are `path,x_mm,y_mm,width_mm,height_mm[,rotation]`. Blank lines,
+/// `#` comments and a header row starting with `path` are skipped. Paths
+/// may be wrapped in double quotes but cannot contain commas.
+fn parse_csv(contents: &str) -> ParsedManifest {
+    let mut parsed = ParsedManifest::default();
+    for (index, line) in contents.lines().enumerate() {
+        let row = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if fields[0].eq_ignore_ascii_case(CSV_COLUMNS[0]) {
+            continue;
+        }
+        match parse_csv_fields(&fields).and_then(|entry| validate_entry(&entry).map(|()| entry)) {
+            Ok(entry) => parsed.entries.push((row, entry)),
+            Err(message) => parsed.errors.push(RowError { row, message }),
+        }
+    }
+    parsed
+}
+
+fn parse_csv_fields(fields: &[&str]) -> Result<ManifestEntry, String> {
+    if fields.len() < 5 || fields.len() > CSV_COLUMNS.len() {
+        return Err(format!("expected 5 or 6 columns, found {}", fields.len()));
+    }
+    let path = fields[0].trim_matches('"');
+    if path.is_empty() {
+        return Err("missing path".to_string());
+    }
+    let number = |i: usize| -> Result<f32, String> {
+        fields[i]
+            .parse::<f32>()
+            .map_err(|_| format!("{} is not a number: '{}'", CSV_COLUMNS[i], fields[i]))
+    };
+    Ok(ManifestEntry {
+        path: PathBuf::from(path),
+        x_mm: number(1)?,
+        y_mm: number(2)?,
+        width_mm: number(3)?,
+        height_mm: number(4)?,
+        rotation: if fields.len() > 5 { number(5)? } else { 0.0 },
+    })
+}
+
+fn validate_entry(entry: &ManifestEntry) -> Result<(), String> {
+    let values = [entry.x_mm, entry.y_mm, entry.width_mm, entry.height_mm, entry.rotation];
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err("values must be finite numbers".to_string());
+    }
+    if entry.width_mm <= 0.0 || entry.height_mm <= 0.0 {
+        return Err("width_mm and height_mm must be positive".to_string());
+    }
+    if entry.rotation % 90.0 != 0.0 {
+        return Err(format!("rotation must be a multiple of 90, found {}", entry.rotation));
+    }
+    Ok(())
+}
+
+/// Build a layout on `page` from parsed rows. Relative paths are resolved
+/// against `base_dir`; `probe` returns an image's pixel size or an error,
+/// and rows whose image can't be read are reported instead of placed.
+pub fn build_layout<F>(page: Page, manifest: ParsedManifest, base_dir: &Path, probe: F) -> ManifestImport
+where
+    F: Fn(&Path) -> Result<(u32, u32), String>,
+{
+    let mut layout = Layout::new();
+    layout.page = page;
+    let mut errors = manifest.errors;
+
+    for (row, entry) in manifest.entries {
+        let path = if entry.path.is_absolute() {
+            entry.path
+        } else {
+            base_dir.join(entry.path)
+        };
+        match probe(&path) {
+            Ok((width_px, height_px)) => {
+                let mut image = PlacedImage::new(path, width_px, height_px);
+                image.x_mm = entry.x_mm;
+                image.y_mm = entry.y_mm;
+                image.width_mm = entry.width_mm;
+                image.height_mm = entry.height_mm;
+                image.rotation_degrees = entry.rotation.rem_euclid(360.0);
+                layout.add_image(image);
+            }
+            Err(message) => errors.push(RowError {
+                row,
+                message: format!("{}: {}", path.display(), message),
+            }),
+        }
+    }
+
+    errors.sort_by_key(|e| e.row);
+    ManifestImport { layout, errors }
+}
+
+/// Read a manifest file and build a layout on `page` from it
+pub fn import_manifest(path: &Path, page: Page) -> Result<ManifestImport, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let parsed = parse_manifest(&contents)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    Ok(build_layout(page, parsed, base_dir, |p| {
+        image::image_dimensions(p).map_err(|e| e.to_string())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe_ok(_: &Path) -> Result<(u32, u32), String> {
+        Ok((1200, 800))
+    }
+
+    #[test]
+    fn test_parse_csv_with_header_comments_and_bad_rows() {
+        let csv = "\
+path,x_mm,y_mm,width_mm,height_mm,rotation
+# product shots
+a.jpg, 10, 20, 60, 40, 90
+\"b.png\",0,0,50,50
+
+c.jpg,5,5,abc,10
+d.jpg,5,5,10,10,45
+e.jpg,5,5
+";
+        let parsed = parse_manifest(csv).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].0, 3);
+        assert_eq!(parsed.entries[0].1.path, PathBuf::from("a.jpg"));
+        assert_eq!(parsed.entries[0].1.rotation, 90.0);
+        assert_eq!(parsed.entries[1].1.path, PathBuf::from("b.png"));
+        assert_eq!(parsed.entries[1].1.rotation, 0.0);
+
+        let bad_rows: Vec<usize> = parsed.errors.iter().map(|e| e.row).collect();
+        assert_eq!(bad_rows, vec![6, 7, 8]);
+        assert!(parsed.errors[0].message.contains("width_mm"));
+    }
+
+    #[test]
+    fn test_parse_json_reports_bad_elements_by_index() {
+        let json = r#"[
+            {"path": "/abs/a.jpg", "x_mm": 1, "y_mm": 2, "width_mm": 30, "height_mm": 20},
+            {"path": "b.jpg", "x_mm": 1},
+            {"path": "c.jpg", "x_mm": 1, "y_mm": 2, "width_mm": -5, "height_mm": 20, "rotation": 180}
+        ]"#;
+        let parsed = parse_manifest(json).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].1.width_mm, 30.0);
+        let bad_rows: Vec<usize> = parsed.errors.iter().map(|e| e.row).collect();
+        assert_eq!(bad_rows, vec![2, 3]);
+
+        assert!(parse_manifest("[not json").is_err());
+    }
+
+    #[test]
+    fn test_build_layout_resolves_paths_and_reports_unreadable_images() {
+        let parsed = parse_manifest("a.jpg,10,20,60,40,270\n/abs/missing.jpg,0,0,10,10\n").unwrap();
+        let import = build_layout(Page::default(), parsed, Path::new("/sheets"), |p| {
+            if p.ends_with("missing.jpg") {
+                Err("not found".to_string())
+            } else {
+                probe_ok(p)
+            }
+        });
+
+        assert_eq!(import.layout.images.len(), 1);
+        let img = &import.layout.images[0];
+        assert_eq!(img.path, PathBuf::from("/sheets/a.jpg"));
+        assert_eq!((img.x_mm, img.y_mm, img.width_mm, img.height_mm), (10.0, 20.0, 60.0, 40.0));
+        assert_eq!(img.rotation_degrees, 270.0);
+        assert_eq!((img.original_width_px, img.original_height_px), (1200, 800));
+
+        assert_eq!(import.errors.len(), 1);
+        assert_eq!(import.errors[0].row, 2);
+        assert!(import.errors[0].to_string().contains("missing.jpg"));
+    }
+
+    #[test]
+    fn test_negative_rotation_is_normalized() {
+        let parsed = parse_manifest("a.jpg,0,0,10,10,-90").unwrap();
+        let import = build_layout(Page::default(), parsed, Path::new("."), probe_ok);
+        assert_eq!(import.layout.images[0].rotation_degrees, 270.0);
+    }
+}
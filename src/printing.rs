@@ -1,11 +1,15 @@
 // printing.rs - CUPS integration
 // Phase 4: Printing Integration
 
-use crate::layout::{Layout, PaperSize};
-use image::{ImageBuffer, Rgba, RgbaImage};
-use std::io;
+use crate::layout::{ColorMode, Crop, Layout, LayoutElement, Page, PaperSize, PaperType, PlacedImage, PlacedRect, PrintQuality, PrintScaling, ResampleFilter, SequenceNumbers, Toning};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+use serde::Serialize;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 /// Represents a printer available on the system
@@ -55,6 +59,7 @@ impl PrinterOption {
     }
     
     /// Get the currently selected or default value
+    #[allow(dead_code)]
     pub fn current_value(&self) -> Option<&str> {
         self.values.iter()
             .find(|v| v.is_default)
@@ -99,6 +104,339 @@ impl PrinterCapabilities {
     pub fn page_sizes(&self) -> Option<&PrinterOption> {
         self.get_option("PageSize")
     }
+    
+    /// Get the Resolution option
+    pub fn resolution(&self) -> Option<&PrinterOption> {
+        self.get_option("Resolution")
+    }
+    
+    /// Get the Duplex option
+    pub fn duplex(&self) -> Option<&PrinterOption> {
+        self.get_option("Duplex")
+    }
+    
+    /// Whether the PPD lists any borderless page sizes (e.g. "4x6.Borderless", "A4.FB")
+    pub fn supports_borderless(&self) -> bool {
+        self.page_sizes()
+            .map(|o| o.values.iter().any(|v| {
+                let value = v.value.to_ascii_lowercase();
+                value.contains("borderless") || value.ends_with(".fb")
+            }))
+            .unwrap_or(false)
+    }
+}
+
+/// An entry in a capability-driven pick_list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionChoice {
+    /// Leave the option out of the lp command so the printer's PPD default applies.
+    /// Carries the default value for display, if the printer reports one.
+    PrinterDefault(Option<String>),
+    /// An explicitly chosen value
+    Value(String),
+}
+
+impl OptionChoice {
+    /// The value to pass to lp, or None when the printer default should apply
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            OptionChoice::PrinterDefault(_) => None,
+            OptionChoice::Value(v) => Some(v.as_str()),
+        }
+    }
+}
+
+impl std::fmt::Display for OptionChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionChoice::PrinterDefault(Some(v)) => write!(f, "(printer default: {})", v),
+            OptionChoice::PrinterDefault(None) => write!(f, "(printer default)"),
+            OptionChoice::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl PrinterOption {
+    /// Pick list entries: the synthetic printer-default entry first, then every value
+    pub fn choices(&self) -> Vec<OptionChoice> {
+        let mut choices = vec![self.default_choice()];
+        choices.extend(self.values.iter().map(|v| OptionChoice::Value(v.value.clone())));
+        choices
+    }
+    
+    /// The synthetic entry meaning "don't send this option"
+    pub fn default_choice(&self) -> OptionChoice {
+        OptionChoice::PrinterDefault(self.default_value().map(String::from))
+    }
+    
+    /// Map a stored selection (None = printer default) to its pick list entry
+    pub fn choice_for(&self, selected: Option<&str>) -> OptionChoice {
+        match selected {
+            Some(v) => OptionChoice::Value(v.to_string()),
+            None => self.default_choice(),
+        }
+    }
+}
+
+/// Build the extra lp options from per-option selections.
+/// Options left at the printer default (None) are omitted entirely.
+pub fn build_extra_options(selections: &[(&str, Option<&str>)]) -> Vec<(String, String)> {
+    selections
+        .iter()
+        .filter_map(|(name, value)| value.map(|v| (name.to_string(), v.to_string())))
+        .collect()
+}
+
+/// CUPS scaling options for the given mode.
+/// Only `FitToPage` passes `fit-to-page`; actual size pins the image resolution
+/// to the render DPI so the physical size matches the layout exactly.
+pub fn scaling_options(scaling: PrintScaling, dpi: u32) -> Vec<String> {
+    match scaling {
+        PrintScaling::ActualSize => vec![
+            "print-scaling=none".to_string(),
+            format!("ppi={}", dpi),
+        ],
+        PrintScaling::FitToPage => vec![
+            "print-scaling=fit".to_string(),
+            "fit-to-page".to_string(),
+        ],
+        PrintScaling::FillPage => vec!["print-scaling=fill".to_string()],
+    }
+}
+
+/// One page setting and the CUPS spellings it can take
+struct PageSetting {
+    /// PPD option name, as listed by `get_printer_capabilities`
+    ppd_name: &'static str,
+    /// PPD values to look for, best match first
+    ppd_values: &'static [&'static str],
+    /// Standard IPP attribute sent when the PPD doesn't list the option
+    ipp: (&'static str, &'static str),
+}
+
+fn paper_type_setting(paper_type: PaperType) -> PageSetting {
+    let (ppd_values, ipp_value): (&'static [&'static str], _) = match paper_type {
+        PaperType::Plain => (&["Plain", "PlainPaper", "Stationery"], "stationery"),
+        PaperType::SuperHighGloss => (
+            &["PhotoProPlatinum", "PhotoPlatinum", "PhotographicHighGloss", "HighGloss"],
+            "photographic-high-gloss",
+        ),
+        PaperType::Glossy => (
+            &["PhotoPlusGloss2", "GlossyPaper", "Glossy", "PhotographicGlossy", "PhotoGloss"],
+            "photographic-glossy",
+        ),
+        PaperType::SemiGloss => (
+            &["PhotoPlusSemiGloss", "PhotoProLuster", "SemiGloss", "PhotographicSemiGloss", "Luster"],
+            "photographic-semi-gloss",
+        ),
+        PaperType::Matte => (
+            &["MattePhotoPaper", "PhotoPremiumMatte", "Matte", "PhotographicMatte"],
+            "photographic-matte",
+        ),
+        PaperType::FineArt => (
+            &[
+                "PremiumFineArtRough",
+                "FineArtRough",
+                "FineArt",
+                "MattePhotoPaper",
+                "PhotoPremiumMatte",
+                "PhotographicMatte",
+                "Matte",
+            ],
+            "photographic-matte",
+        ),
+    };
+    PageSetting { ppd_name: "MediaType", ppd_values, ipp: ("media-type", ipp_value) }
+}
+
+fn print_quality_setting(quality: PrintQuality) -> PageSetting {
+    // IPP print-quality: 3 = draft, 4 = normal, 5 = high
+    let (ppd_values, ipp_value): (&'static [&'static str], _) = match quality {
+        PrintQuality::Highest => (&["Highest", "Best", "Photo", "High"], "5"),
+        PrintQuality::High => (&["High", "Fine", "Best"], "5"),
+        PrintQuality::Standard => (&["Normal", "Standard"], "4"),
+        PrintQuality::Draft => (&["Draft", "Fast", "Economy"], "3"),
+    };
+    PageSetting { ppd_name: "cupsPrintQuality", ppd_values, ipp: ("print-quality", ipp_value) }
+}
+
+fn color_mode_setting(color_mode: ColorMode) -> PageSetting {
+    let (ppd_values, ipp_value): (&'static [&'static str], _) = match color_mode {
+        ColorMode::BlackAndWhite => (&["Gray", "Grayscale", "KGray", "Black"], "monochrome"),
+        _ => (&["RGB", "Color", "CMYK"], "color"),
+    };
+    PageSetting { ppd_name: "ColorModel", ppd_values, ipp: ("print-color-mode", ipp_value) }
+}
+
+/// PPD values differ only in case and separators between drivers ("SemiGloss", "semi-gloss")
+fn same_option_value(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| -> String {
+        s.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_lowercase()).collect()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// The value the printer offers for a setting, the best match first
+fn ppd_value_for<'a>(setting: &PageSetting, option: &'a PrinterOption) -> Option<&'a str> {
+    setting
+        .ppd_values
+        .iter()
+        .find_map(|wanted| option.values.iter().find(|v| same_option_value(&v.value, wanted)))
+        .map(|v| v.value.as_str())
+}
+
+/// Paper types the printer has a Media Type for; all of them when it lists none
+pub fn supported_paper_types(caps: &PrinterCapabilities) -> Vec<PaperType> {
+    match caps.media_type().filter(|option| !option.values.is_empty()) {
+        Some(option) => PaperType::ALL
+            .into_iter()
+            .filter(|t| ppd_value_for(&paper_type_setting(*t), option).is_some())
+            .collect(),
+        None => PaperType::ALL.to_vec(),
+    }
+}
+
+/// Print qualities the printer has a setting for; all of them when it lists none
+pub fn supported_print_qualities(caps: &PrinterCapabilities) -> Vec<PrintQuality> {
+    match caps.print_quality().filter(|option| !option.values.is_empty()) {
+        Some(option) => PrintQuality::ALL
+            .into_iter()
+            .filter(|q| ppd_value_for(&print_quality_setting(*q), option).is_some())
+            .collect(),
+        None => PrintQuality::ALL.to_vec(),
+    }
+}
+
+/// PPD PageSize keywords for a paper size, as CUPS drivers commonly spell them
+fn ppd_page_size_names(size: PaperSize) -> &'static [&'static str] {
+    match size {
+        PaperSize::A3 => &["A3"],
+        PaperSize::A4 => &["A4"],
+        PaperSize::A5 => &["A5"],
+        PaperSize::Letter => &["Letter"],
+        PaperSize::Legal => &["Legal"],
+        PaperSize::Tabloid => &["Tabloid", "11x17"],
+        PaperSize::Ledger => &["Ledger"],
+        PaperSize::Photo3_5x5 => &["3.5x5", "L"],
+        PaperSize::Photo4x6 => &["4x6", "Photo4x6", "KG"],
+        PaperSize::Photo5x5 => &["5x5"],
+        PaperSize::Photo5x7 => &["5x7", "Photo5x7", "2L"],
+        PaperSize::Photo7x10 => &["7x10"],
+        PaperSize::Photo8x10 => &["8x10"],
+        PaperSize::Photo10x12 => &["10x12"],
+        PaperSize::Photo11x17 => &["11x17", "Tabloid"],
+        PaperSize::Photo12x12 => &["12x12"],
+        PaperSize::Photo13x19 => &["13x19", "A3Plus", "SuperB"],
+        _ => &[],
+    }
+}
+
+/// Size in mm of a PPD size written in points, such as "w288h432"
+fn ppd_points_size(name: &str) -> Option<(f32, f32)> {
+    let (width, height) = name.strip_prefix('w')?.split_once('h')?;
+    let to_mm = |points: &str| points.parse::<f32>().ok().map(|p| p * 25.4 / 72.0);
+    Some((to_mm(width)?, to_mm(height)?))
+}
+
+/// Whether the printer lists `size` among its page sizes, by name or by
+/// dimensions. Borderless variants ("4x6.Borderless", "A4.FB") count.
+/// Printers that report no page sizes are assumed to take anything.
+pub fn printer_supports_paper_size(caps: &PrinterCapabilities, size: PaperSize) -> bool {
+    const TOLERANCE_MM: f32 = 1.0;
+    let Some(option) = caps.page_sizes().filter(|option| !option.values.is_empty()) else {
+        return true;
+    };
+    let (width, height) = size.to_dimensions();
+    option.values.iter().any(|v| {
+        let base = ppd_size_base(&v.value);
+        ppd_page_size_names(size).iter().any(|name| base.eq_ignore_ascii_case(name))
+            || ppd_points_size(base).is_some_and(|(w, h)| {
+                ((w - width).abs() < TOLERANCE_MM && (h - height).abs() < TOLERANCE_MM)
+                    || ((w - height).abs() < TOLERANCE_MM && (h - width).abs() < TOLERANCE_MM)
+            })
+    })
+}
+
+/// A PPD size without its ".Borderless"-style suffix (but keeping the point in "3.5x5")
+fn ppd_size_base(value: &str) -> &str {
+    match value.rsplit_once('.') {
+        Some((base, suffix)) if suffix.chars().all(|c| c.is_ascii_alphabetic()) => base,
+        _ => value,
+    }
+}
+
+/// Short and long side in mm of the largest paper the printer lists, when
+/// any of its page sizes can be recognized
+pub fn largest_paper_mm(caps: &PrinterCapabilities) -> Option<(f32, f32)> {
+    let named = [
+        PaperSize::A3, PaperSize::A4, PaperSize::A5, PaperSize::Letter, PaperSize::Legal,
+        PaperSize::Tabloid, PaperSize::Ledger, PaperSize::Photo3_5x5, PaperSize::Photo4x6,
+        PaperSize::Photo5x5, PaperSize::Photo5x7, PaperSize::Photo7x10, PaperSize::Photo8x10,
+        PaperSize::Photo10x12, PaperSize::Photo11x17, PaperSize::Photo12x12, PaperSize::Photo13x19,
+    ];
+    caps.page_sizes()?
+        .values
+        .iter()
+        .filter_map(|v| {
+            let base = ppd_size_base(&v.value);
+            ppd_points_size(base).or_else(|| {
+                named
+                    .iter()
+                    .find(|size| ppd_page_size_names(**size).iter().any(|name| base.eq_ignore_ascii_case(name)))
+                    .map(|size| size.to_dimensions())
+            })
+        })
+        .map(|(w, h)| (w.min(h), w.max(h)))
+        .reduce(|(short, long), (s, l)| (short.max(s), long.max(l)))
+}
+
+/// The `media=` option for the page's paper. Sizes without a CUPS name are
+/// sent as a custom size in mm, portrait side first.
+pub fn media_option(page: &Page) -> String {
+    let name = match page.paper_size {
+        PaperSize::A4 => "A4",
+        PaperSize::A3 => "A3",
+        PaperSize::A5 => "A5",
+        PaperSize::Letter => "Letter",
+        PaperSize::Legal => "Legal",
+        PaperSize::Tabloid => "Tabloid",
+        PaperSize::Ledger => "Ledger",
+        PaperSize::Photo4x6 => "4x6",
+        PaperSize::Photo5x7 => "5x7",
+        PaperSize::Photo8x10 => "8x10",
+        PaperSize::Photo11x17 => "11x17",
+        PaperSize::Photo13x19 => "13x19",
+        _ => {
+            let (w, h) = (page.width_mm.min(page.height_mm), page.width_mm.max(page.height_mm));
+            let mm = |v: f32| format!("{:.1}", v).trim_end_matches(".0").to_string();
+            return format!("media=Custom.{}x{}mm", mm(w), mm(h));
+        }
+    };
+    format!("media={}", name)
+}
+
+/// CUPS options for the page's paper type, print quality and color mode.
+/// When the printer's PPD lists the option, the closest value it offers is
+/// used (or nothing, if none fits); otherwise the standard IPP attribute is
+/// sent, which CUPS maps for driverless printers and ignores elsewhere.
+/// Options the user picked explicitly in `explicit` are left alone.
+pub fn page_setting_options(
+    page: &Page,
+    caps: Option<&PrinterCapabilities>,
+    explicit: &[(String, String)],
+) -> Vec<(String, String)> {
+    [
+        paper_type_setting(page.paper_type),
+        print_quality_setting(page.print_quality),
+        color_mode_setting(page.color_mode),
+    ]
+    .into_iter()
+    .filter(|setting| !explicit.iter().any(|(name, _)| name == setting.ppd_name))
+    .filter_map(|setting| match caps.and_then(|c| c.get_option(setting.ppd_name)) {
+        Some(option) => ppd_value_for(&setting, option).map(|value| (setting.ppd_name.to_string(), value.to_string())),
+        None => Some((setting.ipp.0.to_string(), setting.ipp.1.to_string())),
+    })
+    .collect()
 }
 
 /// Print job configuration
@@ -106,10 +444,26 @@ impl PrinterCapabilities {
 pub struct PrintJob {
     pub layout: Layout,
     pub printer_name: String,
+    /// Job name shown in the CUPS queue and on the printer's panel
+    pub title: String,
+    /// Resampling for images without their own filter
+    pub resample_filter: ResampleFilter,
     pub copies: u32,
     pub dpi: u32,
     /// Additional CUPS options (e.g., "InputSlot=ByPassTray")
     pub extra_options: Vec<(String, String)>,
+    /// Lets the UI follow and stop the render; None when it can't be cancelled
+    pub cancel: Option<CancelToken>,
+}
+
+/// Name used in job titles for layouts that were never saved
+pub const UNTITLED_LAYOUT: &str = "Untitled layout";
+
+/// Job title such as "Holiday — page 1 (A4 High)". `project_name` falls
+/// back to UNTITLED_LAYOUT when missing or blank.
+pub fn job_title(project_name: Option<&str>, page_number: usize, page: &Page) -> String {
+    let name = project_name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(UNTITLED_LAYOUT);
+    format!("{} — page {} ({} {})", name, page_number, page.paper_size, page.print_quality)
 }
 
 /// Page orientation (kept for backwards compatibility, but layout.page.orientation is preferred)
@@ -131,6 +485,8 @@ pub enum PrintError {
     RenderError(String),
     IoError(io::Error),
     CommandFailed(String),
+    /// Stopped through a CancelToken
+    Cancelled,
 }
 
 impl std::fmt::Display for PrintError {
@@ -145,6 +501,7 @@ impl std::fmt::Display for PrintError {
             PrintError::RenderError(msg) => write!(f, "Failed to render layout: {}", msg),
             PrintError::IoError(e) => write!(f, "I/O error: {}", e),
             PrintError::CommandFailed(msg) => write!(f, "Print command failed: {}", msg),
+            PrintError::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -304,14 +661,274 @@ pub fn get_default_printer() -> Result<Option<PrinterInfo>, PrintError> {
     Ok(printers.into_iter().find(|p| p.is_default))
 }
 
-/// Render layout to image buffer at specified DPI
-pub fn render_layout_to_image(layout: &Layout, dpi: u32) -> Result<RgbaImage, PrintError> {
-    log::info!("Rendering layout at {} DPI", dpi);
+/// Output of an external command
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs external programs; tests substitute canned output
+pub trait CommandRunner {
+    /// None when the program couldn't be started, usually because it isn't installed
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput>;
+}
+
+/// Runs programs on this system
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput> {
+        let output = Command::new(program).args(args).output().ok()?;
+        Some(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// One step of the printing health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticCheck {
+    /// lpstat and lp are installed
+    ClientTools,
+    /// The CUPS scheduler answers
+    Scheduler,
+    /// At least one printer queue exists
+    Queues,
+    /// The default queue exists and accepts jobs
+    DefaultQueue,
+}
+
+impl DiagnosticCheck {
+    /// Every check, in the order they run
+    pub const ALL: [DiagnosticCheck; 4] = [
+        DiagnosticCheck::ClientTools,
+        DiagnosticCheck::Scheduler,
+        DiagnosticCheck::Queues,
+        DiagnosticCheck::DefaultQueue,
+    ];
+}
+
+impl std::fmt::Display for DiagnosticCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticCheck::ClientTools => write!(f, "CUPS client tools"),
+            DiagnosticCheck::Scheduler => write!(f, "CUPS scheduler"),
+            DiagnosticCheck::Queues => write!(f, "Printer queues"),
+            DiagnosticCheck::DefaultQueue => write!(f, "Default printer"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticStatus {
+    Ok,
+    /// Printing can work, but something needs attention
+    Warning,
+    Failed,
+    /// Not checked because an earlier check failed
+    Skipped,
+}
+
+/// Outcome of one check, with what to do about it when it isn't Ok
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticResult {
+    pub check: DiagnosticCheck,
+    pub status: DiagnosticStatus,
+    pub detail: String,
+    pub guidance: Option<String>,
+}
+
+impl DiagnosticResult {
+    fn ok(check: DiagnosticCheck, detail: impl Into<String>) -> Self {
+        Self { check, status: DiagnosticStatus::Ok, detail: detail.into(), guidance: None }
+    }
+
+    fn problem(check: DiagnosticCheck, status: DiagnosticStatus, detail: impl Into<String>, guidance: impl Into<String>) -> Self {
+        Self { check, status, detail: detail.into(), guidance: Some(guidance.into()) }
+    }
+
+    fn skipped(check: DiagnosticCheck) -> Self {
+        Self { check, status: DiagnosticStatus::Skipped, detail: "Not checked".to_string(), guidance: None }
+    }
+}
+
+/// True if any check failed outright
+pub fn diagnosis_failed(results: &[DiagnosticResult]) -> bool {
+    results.iter().any(|r| r.status == DiagnosticStatus::Failed)
+}
+
+/// Check the printing stack on this system
+pub fn diagnose() -> Vec<DiagnosticResult> {
+    diagnose_with(&SystemRunner)
+}
+
+/// Check, in order, that the CUPS tools are installed, the scheduler is
+/// running, printers are configured and the default one accepts jobs.
+/// Checks after a failure are reported as skipped.
+pub fn diagnose_with(runner: &dyn CommandRunner) -> Vec<DiagnosticResult> {
+    let mut results = Vec::new();
+    let skip_rest = |results: &mut Vec<DiagnosticResult>| {
+        let done = results.len();
+        results.extend(DiagnosticCheck::ALL[done..].iter().map(|&c| DiagnosticResult::skipped(c)));
+    };
+
+    let Some(scheduler) = runner.run("lpstat", &["-r"]) else {
+        results.push(DiagnosticResult::problem(
+            DiagnosticCheck::ClientTools,
+            DiagnosticStatus::Failed,
+            "CUPS client tools not installed",
+            "Install the CUPS client package (cups-client on Debian, Ubuntu and Fedora, cups on Arch), then restart Print Layout.",
+        ));
+        skip_rest(&mut results);
+        return results;
+    };
+    results.push(DiagnosticResult::ok(DiagnosticCheck::ClientTools, "lpstat is available"));
+
+    if !scheduler.success || scheduler.stdout.contains("not running") {
+        results.push(DiagnosticResult::problem(
+            DiagnosticCheck::Scheduler,
+            DiagnosticStatus::Failed,
+            "CUPS is not running",
+            "Start the CUPS service, e.g. `sudo systemctl enable --now cups`.",
+        ));
+        skip_rest(&mut results);
+        return results;
+    }
+    results.push(DiagnosticResult::ok(DiagnosticCheck::Scheduler, "CUPS is running"));
+
+    let queues: Vec<String> = runner
+        .run("lpstat", &["-p"])
+        .map(|out| {
+            out.stdout
+                .lines()
+                .filter_map(|line| line.strip_prefix("printer "))
+                .filter_map(|rest| rest.split_whitespace().next())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    if queues.is_empty() {
+        results.push(DiagnosticResult::problem(
+            DiagnosticCheck::Queues,
+            DiagnosticStatus::Failed,
+            "CUPS is running but no printers are configured",
+            "Add a printer in your system's printer settings or open http://localhost:631 in a browser.",
+        ));
+        skip_rest(&mut results);
+        return results;
+    }
+    results.push(DiagnosticResult::ok(DiagnosticCheck::Queues, format!("{} printer(s) configured", queues.len())));
+
+    let default = runner.run("lpstat", &["-d"]).and_then(|out| {
+        out.stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("system default destination:"))
+            .map(|name| name.trim().to_string())
+    });
+    let Some(default) = default else {
+        results.push(DiagnosticResult::problem(
+            DiagnosticCheck::DefaultQueue,
+            DiagnosticStatus::Warning,
+            "No default printer is set",
+            format!("Choose a printer in the top bar, or set a default with `lpoptions -d {}`.", queues[0]),
+        ));
+        return results;
+    };
+    let accepting = runner
+        .run("lpstat", &["-a", &default])
+        .map(|out| out.success && out.stdout.contains("accepting requests") && !out.stdout.contains("not accepting"))
+        .unwrap_or(false);
+    if accepting {
+        results.push(DiagnosticResult::ok(DiagnosticCheck::DefaultQueue, format!("{} is accepting jobs", default)));
+    } else {
+        results.push(DiagnosticResult::problem(
+            DiagnosticCheck::DefaultQueue,
+            DiagnosticStatus::Failed,
+            format!("{} is not accepting jobs", default),
+            format!("Re-enable the queue with `cupsaccept {0}` and `cupsenable {0}`, or at http://localhost:631.", default),
+        ));
+    }
+    results
+}
+
+/// Shared between the UI and a render running on another thread: the UI
+/// reads how far the render got and can ask it to stop. The render checks
+/// it between images, so it stops within one image of being cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    /// Progress in thousandths
+    progress: Arc<AtomicU32>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fraction of the render done, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        self.progress.load(Ordering::Relaxed) as f32 / 1000.0
+    }
+
+    fn set_progress(&self, fraction: f32) {
+        self.progress.store((fraction.clamp(0.0, 1.0) * 1000.0).round() as u32, Ordering::Relaxed);
+    }
+}
+
+/// Err(Cancelled) once `cancel` has been cancelled
+fn check_cancelled(cancel: Option<&CancelToken>) -> Result<(), PrintError> {
+    match cancel {
+        Some(token) if token.is_cancelled() => Err(PrintError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Render layout to image buffer at specified DPI. `filter` is used for
+/// images that don't choose their own. With a `cancel` token, progress is
+/// reported after each image and the render stops with
+/// `PrintError::Cancelled` once the token is cancelled. Copies that spill
+/// onto further sheets are left to [`render_sheet`].
+pub fn render_layout_to_image(
+    layout: &Layout,
+    dpi: u32,
+    filter: ResampleFilter,
+    cancel: Option<&CancelToken>,
+) -> Result<RgbaImage, PrintError> {
+    // Extra copies of an image print as images of their own
+    let sheets = layout.sheets();
+    render_sheet(&sheets[0], dpi, filter, cancel, 1, sheets.len())
+}
+
+/// Render one sheet from [`Layout::sheets`], numbered `page_number` of
+/// `page_count` in the footer
+pub fn render_sheet(
+    layout: &Layout,
+    dpi: u32,
+    filter: ResampleFilter,
+    cancel: Option<&CancelToken>,
+    page_number: usize,
+    page_count: usize,
+) -> Result<RgbaImage, PrintError> {
+    log::info!("Rendering sheet {} of {} at {} DPI", page_number, page_count, dpi);
 
     // Calculate page dimensions in pixels
     let page = &layout.page;
-    let width_px = ((page.width_mm / 25.4) * dpi as f32) as u32;
-    let height_px = ((page.height_mm / 25.4) * dpi as f32) as u32;
+    // Round rather than truncate so the physical size matches the media at this DPI
+    let width_px = ((page.width_mm / 25.4) * dpi as f32).round() as u32;
+    let height_px = ((page.height_mm / 25.4) * dpi as f32).round() as u32;
 
     log::debug!(
         "Page dimensions: {}x{} mm -> {}x{} px at {} DPI (Orientation: {:?})",
@@ -326,8 +943,21 @@ pub fn render_layout_to_image(layout: &Layout, dpi: u32) -> Result<RgbaImage, Pr
     // Create white canvas
     let mut img: RgbaImage = ImageBuffer::from_pixel(width_px, height_px, Rgba([255, 255, 255, 255]));
 
-    // Render each image
-    for placed_image in &layout.images {
+    // Render each image and color block, bottom first
+    let elements = layout.elements();
+    let total = elements.len();
+    for (index, element) in elements.into_iter().enumerate() {
+        check_cancelled(cancel)?;
+        if let Some(token) = cancel {
+            token.set_progress(index as f32 / total as f32);
+        }
+        let placed_image = match element {
+            LayoutElement::Rect(rect) => {
+                fill_rect(&mut img, rect, dpi);
+                continue;
+            }
+            LayoutElement::Image(placed_image) => placed_image,
+        };
         // Load the source image - use ImageReader to ensure proper format handling
         let source_img = match load_image_for_print(&placed_image.path) {
             Ok(img) => img,
@@ -337,49 +967,30 @@ pub fn render_layout_to_image(layout: &Layout, dpi: u32) -> Result<RgbaImage, Pr
             }
         };
 
-        // Apply rotation (rotation_degrees is in 90° increments)
-        let rotation_normalized = ((placed_image.rotation_degrees % 360.0) + 360.0) % 360.0;
-        let rotated = if rotation_normalized >= 85.0 && rotation_normalized <= 95.0 {
-            source_img.rotate90()
-        } else if rotation_normalized >= 175.0 && rotation_normalized <= 185.0 {
-            source_img.rotate180()
-        } else if rotation_normalized >= 265.0 && rotation_normalized <= 275.0 {
-            source_img.rotate270()
-        } else {
-            source_img // 0 or other values = no rotation
-        };
+        // Calculate position in pixels
+        let x_px = ((placed_image.x_mm / 25.4) * dpi as f32) as u32;
+        let y_px = ((placed_image.y_mm / 25.4) * dpi as f32) as u32;
 
-        // Apply flip transforms
-        let flipped = if placed_image.flip_horizontal && placed_image.flip_vertical {
-            rotated.fliph().flipv()
-        } else if placed_image.flip_horizontal {
-            rotated.fliph()
-        } else if placed_image.flip_vertical {
-            rotated.flipv()
+        let rgba_img = apply_transforms(source_img, placed_image, page, dpi, filter);
+        let (w_px, h_px) = rgba_img.dimensions();
+
+        // A tilted frame is turned about its center onto a transparent box,
+        // which is then centered where the frame's center is
+        let tilt = placed_image.frame_angle_degrees();
+        let (rgba_img, x_px, y_px) = if tilt != 0.0 {
+            let tilted = rotate_about_center(&rgba_img, tilt);
+            let px_per_mm = dpi as f32 / 25.4;
+            let center_x = (placed_image.x_mm + placed_image.width_mm / 2.0) * px_per_mm;
+            let center_y = (placed_image.y_mm + placed_image.height_mm / 2.0) * px_per_mm;
+            let x = (center_x - tilted.width() as f32 / 2.0).round() as i64;
+            let y = (center_y - tilted.height() as f32 / 2.0).round() as i64;
+            (tilted, x, y)
         } else {
-            rotated
+            (rgba_img, i64::from(x_px), i64::from(y_px))
         };
 
-        // Calculate position and size in pixels
-        let x_px = ((placed_image.x_mm / 25.4) * dpi as f32) as u32;
-        let y_px = ((placed_image.y_mm / 25.4) * dpi as f32) as u32;
-        let w_px = ((placed_image.width_mm / 25.4) * dpi as f32) as u32;
-        let h_px = ((placed_image.height_mm / 25.4) * dpi as f32) as u32;
-
-        // Resize source image to target dimensions
-        let resized = flipped.resize_exact(w_px, h_px, image::imageops::FilterType::Lanczos3);
-
-        // Convert to RGBA and apply opacity
-        let mut rgba_img = resized.to_rgba8();
-        if placed_image.opacity < 1.0 {
-            let opacity_factor = placed_image.opacity.clamp(0.0, 1.0);
-            for pixel in rgba_img.pixels_mut() {
-                pixel[3] = (pixel[3] as f32 * opacity_factor) as u8;
-            }
-        }
-
         // Composite onto canvas
-        image::imageops::overlay(&mut img, &rgba_img, x_px.into(), y_px.into());
+        image::imageops::overlay(&mut img, &rgba_img, x_px, y_px);
 
         log::debug!(
             "Rendered image {} at ({}, {}) with size {}x{} px, rotation={}°, flip_h={}, flip_v={}, opacity={}",
@@ -395,6 +1006,20 @@ pub fn render_layout_to_image(layout: &Layout, dpi: u32) -> Result<RgbaImage, Pr
         );
     }
 
+    check_cancelled(cancel)?;
+
+    // Marks, numbers and footer go on top of everything
+    draw_registration_marks(&mut img, page, dpi);
+    draw_sequence_numbers(&mut img, layout, dpi);
+    draw_captions(&mut img, layout, dpi);
+    draw_footer(&mut img, page, dpi, page_number, page_count);
+
+    // Page color mode last, over the composited page (see `page_toning`)
+    apply_page_color(&mut img, page);
+    if let Some(token) = cancel {
+        token.set_progress(1.0);
+    }
+
     // NOTE: We do NOT rotate the image here for landscape mode.
     // The page dimensions (width_mm, height_mm) are already swapped when the user
     // selects landscape orientation, so the canvas is already rendered correctly.
@@ -403,6 +1028,373 @@ pub fn render_layout_to_image(layout: &Layout, dpi: u32) -> Result<RgbaImage, Pr
     Ok(img)
 }
 
+/// Everything printing does to one image before compositing it: rotate,
+/// flip and crop the source, resize it to the placed size at `dpi`, then
+/// apply output sharpening and opacity
+pub fn apply_transforms(
+    source_img: DynamicImage,
+    placed_image: &PlacedImage,
+    page: &Page,
+    dpi: u32,
+    filter: ResampleFilter,
+) -> RgbaImage {
+    // Apply the quarter turns; any tilt is applied when compositing
+    let rotated = match placed_image.quarter_turns() {
+        1 => source_img.rotate90(),
+        2 => source_img.rotate180(),
+        3 => source_img.rotate270(),
+        _ => source_img,
+    };
+
+    // Apply flip transforms
+    let flipped = if placed_image.flip_horizontal && placed_image.flip_vertical {
+        rotated.fliph().flipv()
+    } else if placed_image.flip_horizontal {
+        rotated.fliph()
+    } else if placed_image.flip_vertical {
+        rotated.flipv()
+    } else {
+        rotated
+    };
+
+    // Apply crop
+    let cropped = match placed_image.crop {
+        Some(crop) => {
+            let (x, y, w, h) = crop.pixel_rect(flipped.width(), flipped.height());
+            flipped.crop_imm(x, y, w, h)
+        }
+        None => flipped,
+    };
+
+    // Calculate size in pixels
+    let w_px = ((placed_image.width_mm / 25.4) * dpi as f32) as u32;
+    let h_px = ((placed_image.height_mm / 25.4) * dpi as f32) as u32;
+
+    // Resize source image to target dimensions, with a filter suited to the scale
+    let scale = (w_px as f32 / cropped.width().max(1) as f32).min(h_px as f32 / cropped.height().max(1) as f32);
+    let filter = placed_image.resample_filter.unwrap_or(filter).for_scale(scale);
+    let resized = cropped.resize_exact(w_px, h_px, filter_type(filter));
+
+    // Convert to RGBA, sharpen or soften for output, then apply opacity
+    let mut rgba_img = resized.to_rgba8();
+    let sharpen = placed_image.effective_output_sharpen(page);
+    if sharpen != 0.0 {
+        rgba_img = apply_output_sharpen(&rgba_img, sharpen, dpi);
+    }
+    if placed_image.opacity < 1.0 {
+        let opacity_factor = placed_image.opacity.clamp(0.0, 1.0);
+        for pixel in rgba_img.pixels_mut() {
+            pixel[3] = (pixel[3] as f32 * opacity_factor) as u8;
+        }
+    }
+
+    rgba_img
+}
+
+/// The image crate's filter for a resolved (non-Auto) choice
+fn filter_type(filter: ResampleFilter) -> FilterType {
+    match filter {
+        ResampleFilter::Triangle => FilterType::Triangle,
+        ResampleFilter::CatmullRom => FilterType::CatmullRom,
+        ResampleFilter::Lanczos3 | ResampleFilter::Auto => FilterType::Lanczos3,
+    }
+}
+
+/// Dark gray used for footer text
+const FOOTER_COLOR: Rgba<u8> = Rgba([90, 90, 90, 255]);
+
+/// Rasterize the page footer, if enabled, using today's date
+fn draw_footer(img: &mut RgbaImage, page: &Page, dpi: u32, page_number: usize, page_count: usize) {
+    let today = chrono::Local::now().date_naive();
+    let Some(text) = page.footer.text(page_number, page_count, today) else {
+        return;
+    };
+    let px_per_mm = dpi as f32 / 25.4;
+    let (x, y, _, height) = page.footer.rect(page, &text);
+    crate::bitmap_font::draw_text(img, &text, x * px_per_mm, y * px_per_mm, height * px_per_mm, FOOTER_COLOR);
+}
+
+/// Registration marks are solid black so cutters' optical sensors find them
+const MARK_INK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// Rasterize the page's cut registration marks, if any. Edges are rounded to
+/// the nearest pixel and every line is at least one pixel thick.
+fn draw_registration_marks(img: &mut RgbaImage, page: &Page, dpi: u32) {
+    let px_per_mm = dpi as f32 / 25.4;
+    let (width, height) = img.dimensions();
+    for (x, y, w, h) in page.registration_mark_shapes() {
+        let span = |start: f32, size: f32, limit: u32| {
+            let from = (start * px_per_mm).round().max(0.0) as u32;
+            let to = ((start + size) * px_per_mm).round().max(from as f32 + 1.0) as u32;
+            from.min(limit)..to.min(limit)
+        };
+        for py in span(y, h, height) {
+            for px in span(x, w, width) {
+                img.put_pixel(px, py, MARK_INK);
+            }
+        }
+    }
+}
+
+/// Ring and digits of the sequence number badges
+const SEQUENCE_INK: Rgba<u8> = Rgba([40, 40, 40, 255]);
+
+/// Rasterize a circled number on each image, if enabled
+fn draw_sequence_numbers(img: &mut RgbaImage, layout: &Layout, dpi: u32) {
+    let numbers = &layout.page.sequence_numbers;
+    if !numbers.enabled {
+        return;
+    }
+    let px_per_mm = dpi as f32 / 25.4;
+    for (placed, number) in layout.numbered_images() {
+        let badge = numbers.badge(placed.x_mm, placed.y_mm, placed.width_mm, placed.height_mm);
+        let (cx, cy, radius) = (badge.0 * px_per_mm, badge.1 * px_per_mm, badge.2 * px_per_mm);
+        // White disc with a dark ring, so the number reads on any photo
+        let ring = (radius * 0.12).max(1.0);
+        let x0 = (cx - radius).floor().max(0.0) as u32;
+        let y0 = (cy - radius).floor().max(0.0) as u32;
+        let x1 = ((cx + radius).ceil().max(0.0) as u32).min(img.width());
+        let y1 = ((cy + radius).ceil().max(0.0) as u32).min(img.height());
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+                if distance <= radius - ring {
+                    img.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                } else if distance <= radius {
+                    img.put_pixel(x, y, SEQUENCE_INK);
+                }
+            }
+        }
+        let label = number.to_string();
+        let (x, y, _, height) = SequenceNumbers::label_rect(&label, badge);
+        crate::bitmap_font::draw_text(img, &label, x * px_per_mm, y * px_per_mm, height * px_per_mm, SEQUENCE_INK);
+    }
+}
+
+/// Rasterize each image's name under it, if captions are on
+fn draw_captions(img: &mut RgbaImage, layout: &Layout, dpi: u32) {
+    if !layout.page.captions {
+        return;
+    }
+    let px_per_mm = dpi as f32 / 25.4;
+    for placed in &layout.images {
+        let caption = placed.display_label();
+        let (x, y, _, height) = placed.caption_rect(caption);
+        crate::bitmap_font::draw_text(img, caption, x * px_per_mm, y * px_per_mm, height * px_per_mm, SEQUENCE_INK);
+    }
+}
+
+/// Blend a color block into the page, sampling each pixel at its center so
+/// rounded corners are cut cleanly
+fn fill_rect(img: &mut RgbaImage, rect: &PlacedRect, dpi: u32) {
+    let px_per_mm = dpi as f32 / 25.4;
+    let x0 = (rect.x_mm * px_per_mm).floor().max(0.0) as u32;
+    let y0 = (rect.y_mm * px_per_mm).floor().max(0.0) as u32;
+    let x1 = (((rect.x_mm + rect.width_mm) * px_per_mm).ceil().max(0.0) as u32).min(img.width());
+    let y1 = (((rect.y_mm + rect.height_mm) * px_per_mm).ceil().max(0.0) as u32).min(img.height());
+    let [r, g, b, a] = rect.color;
+    let alpha = a as f32 / 255.0;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (x_mm, y_mm) = ((x as f32 + 0.5) / px_per_mm, (y as f32 + 0.5) / px_per_mm);
+            if !rect.covers_point(x_mm, y_mm) {
+                continue;
+            }
+            let pixel = img.get_pixel_mut(x, y);
+            for (channel, value) in [r, g, b].into_iter().enumerate() {
+                let blended = value as f32 * alpha + pixel[channel] as f32 * (1.0 - alpha);
+                pixel[channel] = blended.round() as u8;
+            }
+        }
+    }
+}
+
+/// Blur radius (sigma, pixels) used for sharpening and softening at 300 DPI;
+/// scaled with the render DPI so the effect looks the same at any resolution
+const SHARPEN_SIGMA_PX_AT_300_DPI: f32 = 1.0;
+
+/// Rotate `img` clockwise by `degrees` about its center onto an image just
+/// big enough to hold the result; the uncovered corners are transparent.
+/// Pixels are sampled bilinearly so tilted edges stay smooth.
+pub fn rotate_about_center(img: &RgbaImage, degrees: f32) -> RgbaImage {
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // The small allowance keeps float noise at quarter turns from adding a pixel
+    let out_w = (w * cos.abs() + h * sin.abs() - 1e-3).ceil().max(1.0) as u32;
+    let out_h = (w * sin.abs() + h * cos.abs() - 1e-3).ceil().max(1.0) as u32;
+    let mut out = RgbaImage::new(out_w, out_h);
+    let (out_cx, out_cy) = (out_w as f32 / 2.0, out_h as f32 / 2.0);
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        // Turn each output pixel center back into the source
+        let (dx, dy) = (x as f32 + 0.5 - out_cx, y as f32 + 0.5 - out_cy);
+        let source_x = dx * cos + dy * sin + w / 2.0 - 0.5;
+        let source_y = -dx * sin + dy * cos + h / 2.0 - 0.5;
+        *pixel = sample_bilinear(img, source_x, source_y);
+    }
+    out
+}
+
+/// Color at a fractional pixel position, blending the four nearest pixels
+/// weighted by alpha; outside the image counts as transparent
+fn sample_bilinear(img: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let neighbours = [
+        (0, 0, (1.0 - fx) * (1.0 - fy)),
+        (1, 0, fx * (1.0 - fy)),
+        (0, 1, (1.0 - fx) * fy),
+        (1, 1, fx * fy),
+    ];
+    let mut sum = [0.0f32; 4];
+    for (ox, oy, weight) in neighbours {
+        let (px, py) = (x0 as i64 + ox, y0 as i64 + oy);
+        if weight <= 0.0 || px < 0 || py < 0 || px >= img.width() as i64 || py >= img.height() as i64 {
+            continue;
+        }
+        let p = img.get_pixel(px as u32, py as u32);
+        let alpha = p[3] as f32 * weight;
+        for c in 0..3 {
+            sum[c] += p[c] as f32 * alpha;
+        }
+        sum[3] += alpha;
+    }
+    if sum[3] <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let color = |c: usize| (sum[c] / sum[3]).round().clamp(0.0, 255.0) as u8;
+    Rgba([color(0), color(1), color(2), sum[3].round().min(255.0) as u8])
+}
+
+/// Sharpen (`amount` > 0) or soften (`amount` < 0) an image by blending it
+/// toward an unsharp-masked or blurred copy; `amount` runs from -1.0 to 1.0.
+/// Alpha is left untouched.
+pub fn apply_output_sharpen(img: &RgbaImage, amount: f32, dpi: u32) -> RgbaImage {
+    let amount = amount.clamp(-1.0, 1.0);
+    if amount == 0.0 {
+        return img.clone();
+    }
+    // Below about half a pixel the 3-tap kernel barely reaches the
+    // neighbours and rounds back to the original, so keep at least that
+    let sigma = (SHARPEN_SIGMA_PX_AT_300_DPI * dpi as f32 / 300.0).max(0.5);
+    let target = if amount > 0.0 {
+        image::imageops::unsharpen(img, sigma, 0)
+    } else {
+        image::imageops::blur(img, sigma)
+    };
+    let strength = amount.abs();
+    let mut out = img.clone();
+    for (pixel, target) in out.pixels_mut().zip(target.pixels()) {
+        for channel in 0..3 {
+            let original = pixel[channel] as f32;
+            let blended = original + (target[channel] as f32 - original) * strength;
+            pixel[channel] = blended.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    out
+}
+
+/// Resolution of the quick render used for the ink estimate
+pub const INK_ESTIMATE_DPI: u32 = 30;
+
+/// Rough ink use of a page: the average coverage (0.0-1.0) of each ink over
+/// the whole sheet. Only a relative guide; printer drivers mix inks their own way.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InkCoverage {
+    pub cyan: f32,
+    pub magenta: f32,
+    pub yellow: f32,
+    pub black: f32,
+}
+
+/// Average CMYK coverage of a rendered page, using a naive RGB to CMYK
+/// split (black takes the darkness shared by all channels)
+pub fn ink_coverage(img: &RgbaImage) -> InkCoverage {
+    let pixels = (img.width() as u64 * img.height() as u64).max(1) as f32;
+    let mut sum = [0.0f64; 4];
+    for pixel in img.pixels() {
+        let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|v| v as f32 / 255.0);
+        let k = 1.0 - r.max(g).max(b);
+        if k >= 1.0 {
+            sum[3] += 1.0;
+            continue;
+        }
+        let ink = |v: f32| ((1.0 - v - k) / (1.0 - k)) as f64;
+        sum[0] += ink(r);
+        sum[1] += ink(g);
+        sum[2] += ink(b);
+        sum[3] += k as f64;
+    }
+    let [cyan, magenta, yellow, black] = sum.map(|s| (s / pixels as f64) as f32);
+    InkCoverage { cyan, magenta, yellow, black }
+}
+
+/// Render the layout at `INK_ESTIMATE_DPI` and measure its ink coverage
+pub fn estimate_ink_coverage(layout: &Layout) -> Result<InkCoverage, PrintError> {
+    render_layout_to_image(layout, INK_ESTIMATE_DPI, ResampleFilter::Triangle, None).map(|img| ink_coverage(&img))
+}
+
+/// Rough peak memory in bytes of rendering `layout` at `dpi`: the page
+/// buffer plus the largest image being transformed (its decoded source and a
+/// rotated copy, the resized result and its RGBA conversion). Sources are
+/// counted as 8-bit RGBA.
+pub fn estimate_render_bytes(layout: &Layout, dpi: u32) -> u64 {
+    let to_px = |mm: f32| ((mm / 25.4) * dpi as f32).round() as u64;
+    let page_bytes = to_px(layout.page.width_mm) * to_px(layout.page.height_mm) * 4;
+    let largest_image = layout
+        .images
+        .iter()
+        .map(|img| {
+            let source = img.original_width_px as u64 * img.original_height_px as u64 * 4;
+            let target = to_px(img.width_mm) * to_px(img.height_mm) * 4;
+            source * 2 + target * 2
+        })
+        .max()
+        .unwrap_or(0);
+    page_bytes + largest_image
+}
+
+/// Toning the page's color mode applies to everything on it, or `None` when
+/// it prints in color.
+///
+/// Colors are processed in one order, on the canvas and in print:
+/// 1. each image's own transforms and adjustments (turns, flips, crop,
+///    opacity, sharpening)
+/// 2. the page color mode: Black and White converts to gray and maps
+///    through this toning
+/// 3. printer color management (ICC profile or driver matching), which
+///    CUPS applies after the page leaves the app
+pub fn page_toning(page: &Page) -> Option<Toning> {
+    (page.color_mode == ColorMode::BlackAndWhite).then_some(page.toning)
+}
+
+/// Step 2 of the color order in [`page_toning`]
+pub fn apply_page_color(img: &mut RgbaImage, page: &Page) {
+    if let Some(toning) = page_toning(page) {
+        apply_toning(img, toning);
+    }
+}
+
+/// Convert to grayscale and map luminance through the toning preset's duotone.
+/// Black maps to the shadow color, mid-gray to the midtone color and white stays white.
+pub fn apply_toning(img: &mut RgbaImage, toning: Toning) {
+    let (shadow, mid) = toning.duotone();
+    for pixel in img.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let luma = (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0;
+        let out: [u8; 3] = std::array::from_fn(|c| {
+            let value = if luma < 0.5 {
+                let t = luma * 2.0;
+                shadow[c] as f32 + (mid[c] as f32 - shadow[c] as f32) * t
+            } else {
+                let t = (luma - 0.5) * 2.0;
+                mid[c] as f32 + (255.0 - mid[c] as f32) * t
+            };
+            value.round().clamp(0.0, 255.0) as u8
+        });
+        pixel.0 = [out[0], out[1], out[2], a];
+    }
+}
+
 /// Load an image for printing with proper format handling
 /// This handles all supported formats including GIF (first frame only)
 fn load_image_for_print(path: &PathBuf) -> Result<image::DynamicImage, PrintError> {
@@ -420,8 +1412,21 @@ fn load_image_for_print(path: &PathBuf) -> Result<image::DynamicImage, PrintErro
     Ok(img)
 }
 
+/// First stage of a print job: render each sheet and save it to a temporary
+/// file, one at a time to keep only one page in memory. The files are passed
+/// to `send_to_printer`.
+pub fn render_print_files(job: &PrintJob) -> Result<Vec<PathBuf>, PrintError> {
+    let sheets = job.layout.sheets();
+    let mut temp_files = Vec::with_capacity(sheets.len());
+    for (index, sheet) in sheets.iter().enumerate() {
+        let img = render_sheet(sheet, job.dpi, job.resample_filter, job.cancel.as_ref(), index + 1, sheets.len())?;
+        temp_files.push(create_temp_print_file(&img, index + 1)?);
+    }
+    Ok(temp_files)
+}
+
 /// Send a print job to the specified printer
-pub fn send_to_printer(job: &PrintJob, temp_file: &Path) -> Result<String, PrintError> {
+pub fn send_to_printer(job: &PrintJob, temp_files: &[PathBuf]) -> Result<String, PrintError> {
     log::info!(
         "Sending print job to printer '{}' with {} copies",
         job.printer_name,
@@ -438,40 +1443,23 @@ pub fn send_to_printer(job: &PrintJob, temp_file: &Path) -> Result<String, Print
     let mut cmd = Command::new("lp");
     cmd.arg("-d").arg(&job.printer_name);
     cmd.arg("-n").arg(job.copies.to_string());
+    cmd.arg("-t").arg(&job.title);
 
     // NOTE: We do NOT set orientation-requested or landscape options here.
     // Our rendered image already has the correct dimensions (width/height swapped for landscape).
     // The image is ready to print as-is. Setting CUPS orientation would cause double-rotation.
     // We just need to tell CUPS the correct media size.
 
-    // Add paper size option - use the actual dimensions we rendered
-    // For landscape, width > height, so we specify the media accordingly
-    let paper_option = match job.layout.page.paper_size {
-        PaperSize::A4 => "media=A4",
-        PaperSize::A3 => "media=A3",
-        PaperSize::A5 => "media=A5",
-        PaperSize::Letter => "media=Letter",
-        PaperSize::Legal => "media=Legal",
-        PaperSize::Tabloid => "media=Tabloid",
-        PaperSize::Ledger => "media=Ledger",
-        PaperSize::Photo4x6 => "media=4x6",
-        PaperSize::Photo5x7 => "media=5x7",
-        PaperSize::Photo8x10 => "media=8x10",
-        PaperSize::Photo11x17 => "media=11x17",
-        PaperSize::Photo13x19 => "media=13x19",
-        // For custom sizes, try to use closest standard or specify dimensions
-        _ => {
-            // Use custom size in mm
-            let w = job.layout.page.width_mm;
-            let h = job.layout.page.height_mm;
-            log::debug!("Using custom media size: {}x{}mm", w, h);
-            "media=A4" // Fallback to A4, most printers support it
-        }
-    };
+    // Add paper size option (portrait media; the render already has the
+    // page's orientation)
+    let paper_option = media_option(&job.layout.page);
+    log::debug!("Using {}", paper_option);
     cmd.arg("-o").arg(paper_option);
     
-    // For proper scaling, tell CUPS to fit the image to the page
-    cmd.arg("-o").arg("fit-to-page");
+    // Scaling: only rescale when the user asked for it
+    for option in scaling_options(job.layout.page.print_scaling, job.dpi) {
+        cmd.arg("-o").arg(option);
+    }
     
     // Add any extra options (InputSlot, MediaType, ColorModel, etc.)
     for (opt_name, opt_value) in &job.extra_options {
@@ -480,8 +1468,8 @@ pub fn send_to_printer(job: &PrintJob, temp_file: &Path) -> Result<String, Print
         cmd.arg("-o").arg(option_str);
     }
 
-    // Add the file to print
-    cmd.arg(temp_file);
+    // Add the files to print, one per sheet
+    cmd.args(temp_files);
 
     log::debug!("Executing: {:?}", cmd);
 
@@ -507,14 +1495,14 @@ pub fn send_to_printer(job: &PrintJob, temp_file: &Path) -> Result<String, Print
     Ok(job_id)
 }
 
-/// Create a temporary file for printing
-pub fn create_temp_print_file(img: &RgbaImage) -> Result<PathBuf, PrintError> {
+/// Create a temporary file for printing sheet `sheet` (from 1) of a job
+pub fn create_temp_print_file(img: &RgbaImage, sheet: usize) -> Result<PathBuf, PrintError> {
     let temp_dir = std::env::temp_dir();
     let timestamp = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
         .as_secs();
-    let temp_path = temp_dir.join(format!("print_layout_{}.png", timestamp));
+    let temp_path = temp_dir.join(format!("print_layout_{}_{}.png", timestamp, sheet));
 
     log::debug!("Creating temporary print file: {:?}", temp_path);
 
@@ -524,18 +1512,345 @@ pub fn create_temp_print_file(img: &RgbaImage) -> Result<PathBuf, PrintError> {
     Ok(temp_path)
 }
 
+/// File format for exporting a rendered layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Png, ExportFormat::Jpeg, ExportFormat::Pdf];
+    /// Formats for single processed images
+    pub const IMAGES: [ExportFormat; 2] = [ExportFormat::Png, ExportFormat::Jpeg];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg => "jpg",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Png => write!(f, "PNG Image"),
+            ExportFormat::Jpeg => write!(f, "JPEG Image"),
+            ExportFormat::Pdf => write!(f, "PDF Document"),
+        }
+    }
+}
+
+/// Render the layout at `dpi` and write it to `path` in the given format
+pub fn export_layout(
+    layout: &Layout,
+    dpi: u32,
+    filter: ResampleFilter,
+    format: ExportFormat,
+    path: &Path,
+    cancel: Option<&CancelToken>,
+) -> Result<(), PrintError> {
+    if format == ExportFormat::Pdf {
+        return export_to_pdf(layout, dpi, filter, path, cancel);
+    }
+    log::info!("Exporting layout to {:?}", path);
+    let img = render_layout_to_image(layout, dpi, filter, cancel)?;
+    check_cancelled(cancel)?;
+    let result = match format {
+        ExportFormat::Jpeg => image::DynamicImage::ImageRgba8(img).to_rgb8().save(path),
+        _ => img.save(path),
+    };
+    let result = result.map_err(|e| PrintError::RenderError(format!("Failed to save {}: {}", path.display(), e)));
+    discard_unfinished(path, result, cancel)
+}
+
+/// Once an export has started writing `path`, a failed write or a late
+/// cancel removes the file so no partial output is left behind
+fn discard_unfinished(path: &Path, result: Result<(), PrintError>, cancel: Option<&CancelToken>) -> Result<(), PrintError> {
+    let result = result.and_then(|()| check_cancelled(cancel));
+    if result.is_err() {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Could not remove unfinished export {}: {}", path.display(), e);
+        }
+    }
+    result
+}
+
+/// File names for exporting each image processed: `<name>_processed.<ext>`,
+/// where the name is the image's label without its extension. Names that
+/// would repeat get `_2`, `_3`, ... so no export overwrites another.
+pub fn processed_file_names(images: &[&PlacedImage], format: ExportFormat) -> Vec<String> {
+    let mut used: Vec<String> = Vec::new();
+    images
+        .iter()
+        .map(|img| {
+            let label: String = img
+                .display_label()
+                .chars()
+                .map(|c| if matches!(c, '/' | '\\' | ':') { '_' } else { c })
+                .collect();
+            let stem = Path::new(&label).file_stem().and_then(|s| s.to_str()).unwrap_or(&label);
+            let mut name = format!("{}_processed.{}", stem, format.extension());
+            let mut n = 2;
+            while used.iter().any(|u| u.eq_ignore_ascii_case(&name)) {
+                name = format!("{}_processed_{}.{}", stem, n, format.extension());
+                n += 1;
+            }
+            used.push(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// Write one placed image as it would print, at its placed size and `dpi`:
+/// the same transforms as [`render_layout_to_image`], plus the page's toning
+/// in black and white. JPEG has no transparency, so it is flattened on white
+/// like the paper.
+pub fn export_processed_image(
+    placed_image: &PlacedImage,
+    page: &Page,
+    dpi: u32,
+    filter: ResampleFilter,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<(), PrintError> {
+    let source = load_image_for_print(&placed_image.path)?;
+    let mut img = apply_transforms(source, placed_image, page, dpi, filter);
+    apply_page_color(&mut img, page);
+    let result = match format {
+        ExportFormat::Jpeg => {
+            let mut paper = RgbaImage::from_pixel(img.width(), img.height(), Rgba([255, 255, 255, 255]));
+            image::imageops::overlay(&mut paper, &img, 0, 0);
+            DynamicImage::ImageRgba8(paper).to_rgb8().save(path)
+        }
+        _ => img.save(path),
+    };
+    result.map_err(|e| PrintError::RenderError(format!("Failed to save {}: {}", path.display(), e)))
+}
+
+/// Machine-readable description of a rendered sheet, written next to exports
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutIndex {
+    pub page: PageIndex,
+    pub images: Vec<ImageIndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageIndex {
+    pub paper_size: String,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    pub orientation: String,
+    /// top, bottom, left, right
+    pub margins_mm: [f32; 4],
+    pub borderless: bool,
+    pub paper_type: String,
+    pub color_mode: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageIndexEntry {
+    pub id: String,
+    pub name: String,
+    pub source_path: PathBuf,
+    pub x_mm: f32,
+    pub y_mm: f32,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    pub rotation_degrees: f32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub crop: Option<Crop>,
+    /// Horizontal and vertical DPI at the printed size
+    pub effective_dpi: (f32, f32),
+}
+
+/// Describe the layout's page and images, in drawing order
+pub fn layout_index(layout: &Layout) -> LayoutIndex {
+    let page = &layout.page;
+    LayoutIndex {
+        page: PageIndex {
+            paper_size: page.paper_size.to_string(),
+            width_mm: page.width_mm,
+            height_mm: page.height_mm,
+            orientation: page.orientation.to_string(),
+            margins_mm: [
+                page.margin_top_mm,
+                page.margin_bottom_mm,
+                page.margin_left_mm,
+                page.margin_right_mm,
+            ],
+            borderless: page.borderless,
+            paper_type: page.paper_type.to_string(),
+            color_mode: page.color_mode.to_string(),
+        },
+        images: layout
+            .images
+            .iter()
+            .map(|img| ImageIndexEntry {
+                id: img.id.clone(),
+                name: img.display_label().to_string(),
+                source_path: img.path.clone(),
+                x_mm: img.x_mm,
+                y_mm: img.y_mm,
+                width_mm: img.width_mm,
+                height_mm: img.height_mm,
+                rotation_degrees: img.rotation_degrees,
+                flip_horizontal: img.flip_horizontal,
+                flip_vertical: img.flip_vertical,
+                crop: img.crop,
+                effective_dpi: img.effective_dpi(),
+            })
+            .collect(),
+    }
+}
+
+/// Sidecar index path for an export: `sheet.png` -> `sheet.index.json`
+pub fn index_path_for(output: &Path) -> PathBuf {
+    output.with_extension("index.json")
+}
+
+/// Write the layout index as pretty-printed JSON
+pub fn write_layout_index(layout: &Layout, path: &Path) -> Result<(), PrintError> {
+    let json = serde_json::to_string_pretty(&layout_index(layout))
+        .map_err(|e| PrintError::RenderError(format!("Failed to build index: {}", e)))?;
+    std::fs::write(path, json)?;
+    log::info!("Wrote layout index {:?}", path);
+    Ok(())
+}
+
+/// Render the layout at `dpi` and write it as a single-page PDF whose page
+/// size matches the layout's paper
+pub fn export_to_pdf(
+    layout: &Layout,
+    dpi: u32,
+    filter: ResampleFilter,
+    path: &Path,
+    cancel: Option<&CancelToken>,
+) -> Result<(), PrintError> {
+    export_pages_to_pdf(std::slice::from_ref(layout), dpi, filter, path, cancel)
+}
+
+/// Render each page at `dpi` and write them as one PDF, one PDF page per
+/// sheet in order, each the size of its own paper
+pub fn export_pages_to_pdf(
+    pages: &[Layout],
+    dpi: u32,
+    filter: ResampleFilter,
+    path: &Path,
+    cancel: Option<&CancelToken>,
+) -> Result<(), PrintError> {
+    log::info!("Exporting {} page(s) to PDF: {:?}", pages.len(), path);
+
+    let points = |mm: f32| mm / 25.4 * 72.0;
+    let mut sheets = Vec::with_capacity(pages.len());
+    for layout in pages {
+        let img = render_layout_to_image(layout, dpi, filter, cancel)?;
+        let rgb = image::DynamicImage::ImageRgba8(img).to_rgb8();
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 95)
+            .encode_image(&rgb)
+            .map_err(|e| PrintError::RenderError(format!("Failed to encode page: {}", e)))?;
+        sheets.push(PdfSheet {
+            jpeg,
+            pixels: rgb.dimensions(),
+            page_size: (points(layout.page.width_mm), points(layout.page.height_mm)),
+        });
+    }
+    let pdf = image_pages_pdf(&sheets);
+    check_cancelled(cancel)?;
+    let written = std::fs::File::create(path).and_then(|mut file| file.write_all(&pdf));
+    discard_unfinished(path, written.map_err(PrintError::from), cancel)
+}
+
+/// One PDF page: a JPEG stretched over a page of `page_size` PDF points (1/72 inch)
+struct PdfSheet {
+    jpeg: Vec<u8>,
+    pixels: (u32, u32),
+    page_size: (f32, f32),
+}
+
+/// Build a PDF with one page per sheet, each showing its JPEG over the whole page
+fn image_pages_pdf(sheets: &[PdfSheet]) -> Vec<u8> {
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+
+    // Objects 1 and 2 are the catalog and page tree; each sheet then takes
+    // three: its page, its image and its content stream
+    let page_object = |index: usize| 3 + index * 3;
+    let kids: Vec<String> = (0..sheets.len()).map(|i| format!("{} 0 R", page_object(i))).collect();
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    object(
+        &mut pdf,
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), sheets.len()).as_bytes(),
+    );
+    for (index, sheet) in sheets.iter().enumerate() {
+        let (page_w, page_h) = sheet.page_size;
+        let page = page_object(index);
+        object(
+            &mut pdf,
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                page_w,
+                page_h,
+                page + 1,
+                page + 2
+            )
+            .as_bytes(),
+        );
+        let mut image = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+            sheet.pixels.0,
+            sheet.pixels.1,
+            sheet.jpeg.len()
+        )
+        .into_bytes();
+        image.extend_from_slice(&sheet.jpeg);
+        image.extend_from_slice(b"\nendstream");
+        object(&mut pdf, &image);
+        let content = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", page_w, page_h);
+        object(
+            &mut pdf,
+            format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content).as_bytes(),
+        );
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).as_bytes());
+    for offset in &offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
 /// Execute a complete print job
 pub fn execute_print_job(job: PrintJob) -> Result<String, PrintError> {
     log::info!("Executing print job");
 
-    // Render layout to image
-    let img = render_layout_to_image(&job.layout, job.dpi)?;
-
-    // Save to temporary file
-    let temp_file = create_temp_print_file(&img)?;
+    let temp_files = render_print_files(&job)?;
 
     // Send to printer
-    let job_id = send_to_printer(&job, &temp_file)?;
+    let job_id = send_to_printer(&job, &temp_files)?;
 
     // Note: Temporary file cleanup should be handled by caller
     // after confirming successful print submission
@@ -546,6 +1861,722 @@ pub fn execute_print_job(job: PrintJob) -> Result<String, PrintError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layout::RegistrationMarks;
+
+    #[test]
+    fn test_estimate_render_bytes_counts_page_and_largest_image() {
+        let mut layout = Layout::new();
+        layout.page.width_mm = 254.0; // 10 x 5 inches
+        layout.page.height_mm = 127.0;
+        assert_eq!(estimate_render_bytes(&layout, 100), 1000 * 500 * 4);
+
+        let mut small = PlacedImage::new(PathBuf::from("a.jpg"), 100, 100);
+        small.width_mm = 25.4;
+        small.height_mm = 25.4;
+        let mut big = PlacedImage::new(PathBuf::from("b.jpg"), 2000, 1000);
+        big.width_mm = 50.8;
+        big.height_mm = 25.4;
+        layout.add_image(small);
+        layout.add_image(big);
+        let big_bytes = 2000 * 1000 * 4 * 2 + 200 * 100 * 4 * 2;
+        assert_eq!(estimate_render_bytes(&layout, 100), 1000 * 500 * 4 + big_bytes);
+    }
+
+    #[test]
+    fn test_rotate_about_center_grows_and_leaves_corners_transparent() {
+        let red = Rgba([200, 0, 0, 255]);
+        let img = RgbaImage::from_pixel(20, 10, red);
+
+        // No tilt copies the pixels exactly
+        assert_eq!(rotate_about_center(&img, 0.0), img);
+        // A quarter turn swaps the sides without growing
+        assert_eq!(rotate_about_center(&img, 90.0).dimensions(), (10, 20));
+
+        let tilted = rotate_about_center(&img, 45.0);
+        assert_eq!(tilted.dimensions(), (22, 22));
+        assert_eq!(tilted.get_pixel(0, 0)[3], 0);
+        assert_eq!(tilted.get_pixel(21, 21)[3], 0);
+        assert_eq!(*tilted.get_pixel(11, 11), red);
+        // Edge pixels blend toward transparent without darkening
+        assert!(tilted.pixels().filter(|p| p[3] > 0).all(|p| p[0] == 200));
+    }
+
+    #[test]
+    fn test_color_blocks_render_under_images_with_rounded_corners() {
+        let mut layout = Layout::new();
+        let mut rect = PlacedRect::new(25.4, 25.4, 25.4, 25.4, [255, 0, 0, 255]);
+        rect.corner_radius_mm = 5.0;
+        layout.add_rect(rect);
+        let img = render_layout_to_image(&layout, 10, ResampleFilter::Auto, None).unwrap();
+
+        // 10 DPI: the block covers pixels 10..20; its corner pixel is cut off
+        assert_eq!(img.get_pixel(15, 15).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(10, 10).0, [255, 255, 255, 255]);
+        assert_eq!(img.get_pixel(5, 15).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_page_toning_comes_after_image_adjustments() {
+        let mut layout = Layout::new();
+        layout.page.toning = Toning::Sepia;
+        layout.add_rect(PlacedRect::new(25.4, 25.4, 25.4, 25.4, [255, 0, 0, 255]));
+        layout.add_rect(PlacedRect::new(76.2, 25.4, 25.4, 25.4, [255, 0, 0, 128]));
+        assert_eq!(page_toning(&layout.page), None);
+        let color = render_layout_to_image(&layout, 10, ResampleFilter::Auto, None).unwrap();
+
+        layout.page.color_mode = ColorMode::BlackAndWhite;
+        assert_eq!(page_toning(&layout.page), Some(Toning::Sepia));
+        let toned = render_layout_to_image(&layout, 10, ResampleFilter::Auto, None).unwrap();
+
+        // Opaque red has luma 0.2126: 42.5% of the way from the sepia shadow to the midtone
+        assert_eq!(toned.get_pixel(15, 15).0, [75, 56, 36, 255]);
+
+        // The translucent block is blended onto the paper first, then toned
+        let mut expected = RgbaImage::from_pixel(1, 1, *color.get_pixel(35, 15));
+        apply_toning(&mut expected, Toning::Sepia);
+        assert_eq!(toned.get_pixel(35, 15), expected.get_pixel(0, 0));
+        assert_ne!(toned.get_pixel(35, 15), toned.get_pixel(15, 15));
+    }
+
+    /// Canned lpstat output keyed by its arguments; None means lpstat is missing
+    struct FakeRunner(Option<Vec<(&'static str, bool, &'static str)>>);
+
+    impl CommandRunner for FakeRunner {
+        fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput> {
+            assert_eq!(program, "lpstat");
+            let responses = self.0.as_ref()?;
+            let key = args.join(" ");
+            let (_, success, stdout) = responses.iter().find(|(k, _, _)| *k == key)?;
+            Some(CommandOutput { success: *success, stdout: stdout.to_string(), stderr: String::new() })
+        }
+    }
+
+    fn statuses(results: &[DiagnosticResult]) -> Vec<DiagnosticStatus> {
+        results.iter().map(|r| r.status).collect()
+    }
+
+    #[test]
+    fn test_diagnose_reports_first_failure_and_skips_the_rest() {
+        use DiagnosticStatus::{Failed, Skipped};
+        let missing = diagnose_with(&FakeRunner(None));
+        assert_eq!(statuses(&missing), vec![Failed, Skipped, Skipped, Skipped]);
+        assert_eq!(missing[0].detail, "CUPS client tools not installed");
+        assert!(diagnosis_failed(&missing));
+
+        let stopped = diagnose_with(&FakeRunner(Some(vec![("-r", true, "scheduler is not running\n")])));
+        assert_eq!(stopped[1].status, Failed);
+        assert!(stopped[1].guidance.as_deref().unwrap().contains("systemctl"));
+
+        let empty = diagnose_with(&FakeRunner(Some(vec![
+            ("-r", true, "scheduler is running\n"),
+            ("-p", true, ""),
+        ])));
+        assert_eq!(statuses(&empty)[2..], [Failed, Skipped]);
+        assert!(empty[2].guidance.as_deref().unwrap().contains("http://localhost:631"));
+    }
+
+    #[test]
+    fn test_diagnose_checks_default_queue() {
+        use DiagnosticStatus::{Failed, Ok as Good, Warning};
+        let running = [
+            ("-r", true, "scheduler is running\n"),
+            ("-p", true, "printer Canon is idle.  enabled since Mon\nprinter Epson disabled since Tue -\n"),
+        ];
+        let with = |extra: &[(&'static str, bool, &'static str)]| {
+            let mut responses = running.to_vec();
+            responses.extend_from_slice(extra);
+            diagnose_with(&FakeRunner(Some(responses)))
+        };
+
+        let healthy = with(&[
+            ("-d", true, "system default destination: Canon\n"),
+            ("-a Canon", true, "Canon accepting requests since Mon\n"),
+        ]);
+        assert_eq!(statuses(&healthy), vec![Good; 4]);
+        assert_eq!(healthy[2].detail, "2 printer(s) configured");
+        assert!(!diagnosis_failed(&healthy));
+
+        let no_default = with(&[("-d", true, "no system default destination\n")]);
+        assert_eq!(no_default[3].status, Warning);
+        assert!(no_default[3].guidance.as_deref().unwrap().contains("lpoptions -d Canon"));
+
+        let rejecting = with(&[
+            ("-d", true, "system default destination: Canon\n"),
+            ("-a Canon", true, "Canon not accepting requests since Mon -\n"),
+        ]);
+        assert_eq!(rejecting[3].status, Failed);
+        assert!(rejecting[3].guidance.as_deref().unwrap().contains("cupsaccept Canon"));
+    }
+
+    #[test]
+    fn test_job_title_names_project_page_and_media() {
+        let mut page = Page::new(PaperSize::A4);
+        page.print_quality = crate::layout::PrintQuality::High;
+        assert_eq!(job_title(Some("Holiday"), 1, &page), "Holiday — page 1 (A4 High)");
+        assert_eq!(job_title(Some("  "), 2, &page), "Untitled layout — page 2 (A4 High)");
+        assert_eq!(job_title(None, 1, &page), "Untitled layout — page 1 (A4 High)");
+    }
+
+    #[test]
+    fn test_footer_is_printed_inside_its_rect() {
+        let mut layout = Layout::new();
+        let dpi = 100;
+        let blank = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        assert!(blank.pixels().all(|p| p.0 == [255, 255, 255, 255]));
+
+        layout.page.footer.enabled = true;
+        layout.page.footer.size_mm = 5.0;
+        let img = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        let text = layout.page.footer.text(1, 1, chrono::Local::now().date_naive()).unwrap();
+        let (x, y, width, height) = layout.page.footer.rect(&layout.page, &text);
+        let px = |mm: f32| mm * dpi as f32 / 25.4;
+        let inked: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == FOOTER_COLOR.0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!inked.is_empty());
+        assert!(inked.iter().all(|&(ix, iy)| {
+            (ix as f32) >= px(x) - 1.0
+                && (ix as f32) <= px(x + width) + 1.0
+                && (iy as f32) >= px(y) - 1.0
+                && (iy as f32) <= px(y + height) + 1.0
+        }));
+    }
+
+    #[test]
+    fn test_sequence_numbers_are_printed_in_each_badge() {
+        let mut layout = Layout::new();
+        for x in [20.0, 80.0] {
+            // Missing files are skipped, but their numbers still print
+            let mut img = PlacedImage::new(PathBuf::from("/nonexistent/a.jpg"), 10, 10);
+            (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (x, 20.0, 40.0, 30.0);
+            layout.add_image(img);
+        }
+        let dpi = 100;
+        let blank = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        assert!(blank.pixels().all(|p| p.0 == [255, 255, 255, 255]));
+
+        layout.page.sequence_numbers.enabled = true;
+        layout.page.sequence_numbers.size_mm = 8.0;
+        let img = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        let px = |mm: f32| mm * dpi as f32 / 25.4;
+        let inked: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == SEQUENCE_INK.0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        for placed in &layout.images {
+            let (cx, cy, radius) = layout.page.sequence_numbers.badge(placed.x_mm, placed.y_mm, 40.0, 30.0);
+            let inside = |&&(x, y): &&(u32, u32)| (x as f32 + 0.5 - px(cx)).hypot(y as f32 + 0.5 - px(cy)) <= px(radius) + 1.0;
+            assert!(inked.iter().filter(inside).count() > 0);
+        }
+        assert!(inked.iter().all(|&(x, y)| (y as f32) < px(20.0 + 8.0 + 2.0 + 1.0) && (x as f32) >= px(20.0)));
+    }
+
+    #[test]
+    fn test_captions_are_printed_under_each_image() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("/nonexistent/img_7.jpg"), 10, 10);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (20.0, 20.0, 40.0, 30.0);
+        layout.add_image(img);
+        layout.page.captions = true;
+        let dpi = 254;
+        let rendered = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        let inked: Vec<(u32, u32)> = rendered
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == SEQUENCE_INK.0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!inked.is_empty());
+        // 10 px per mm: everything sits in the band under the 20..60 mm wide image
+        let (_, y, _, height) = layout.images[0].caption_rect("img_7.jpg");
+        assert!(inked.iter().all(|&(x, py)| {
+            (200..600).contains(&x) && py as f32 >= y * 10.0 - 1.0 && (py as f32) < (y + height) * 10.0 + 1.0
+        }));
+    }
+
+    #[test]
+    fn test_registration_marks_are_printed_to_the_pixel() {
+        let mut layout = Layout::new();
+        layout.page.registration_marks = RegistrationMarks::Silhouette;
+        // 254 DPI is exactly 10 px per mm
+        let img = render_layout_to_image(&layout, 254, ResampleFilter::Auto, None).unwrap();
+        let black = |x: u32, y: u32| img.get_pixel(x, y).0 == MARK_INK.0;
+
+        // The 5 mm square at (10, 10) mm covers pixels 100..150
+        assert!(black(100, 100) && black(149, 149));
+        assert!(!black(99, 100) && !black(150, 149) && !black(100, 150));
+        // The top-right bracket's vertical arm is 0.5 mm wide at x = 199.5 mm
+        assert!(black(1995, 100) && black(1999, 299));
+        assert!(!black(1994, 150) && !black(2000, 150) && !black(1999, 300));
+        // Nothing in the unmarked bottom-right corner
+        let (width, height) = img.dimensions();
+        assert!(!black(width - 110, height - 110));
+    }
+
+    #[test]
+    fn test_cancelled_export_leaves_no_file() {
+        let dir = std::env::temp_dir().join(format!("print_layout_cancel_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut layout = Layout::new();
+        layout.page.width_mm = 50.0;
+        layout.page.height_mm = 50.0;
+        layout.add_rect(PlacedRect::new(5.0, 5.0, 10.0, 10.0, [0, 0, 0, 255]));
+
+        let token = CancelToken::new();
+        let png = dir.join("page.png");
+        export_layout(&layout, 50, ResampleFilter::Auto, ExportFormat::Png, &png, Some(&token)).unwrap();
+        assert!(png.exists());
+        assert_eq!(token.progress(), 1.0);
+
+        // A cancelled export stops before writing and removes nothing else
+        token.cancel();
+        let pdf = dir.join("page.pdf");
+        for (format, path) in [(ExportFormat::Png, &png), (ExportFormat::Pdf, &pdf)] {
+            let _ = std::fs::remove_file(path);
+            let result = export_layout(&layout, 50, ResampleFilter::Auto, format, path, Some(&token));
+            assert!(matches!(result, Err(PrintError::Cancelled)));
+            assert!(!path.exists());
+        }
+        assert!(matches!(
+            render_layout_to_image(&layout, 50, ResampleFilter::Auto, Some(&token)),
+            Err(PrintError::Cancelled)
+        ));
+
+        // Cancelling after the file is written deletes it
+        std::fs::write(&png, b"partial").unwrap();
+        assert!(matches!(discard_unfinished(&png, Ok(()), Some(&token)), Err(PrintError::Cancelled)));
+        assert!(!png.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Layout with one image file of vertical stripes, the page's sharpening
+    /// set to `page_sharpen` and the image's override to `image_sharpen`
+    fn striped_layout(dir: &Path, page_sharpen: f32, image_sharpen: Option<f32>) -> Layout {
+        let path = dir.join("stripes.png");
+        if !path.exists() {
+            let stripes = RgbaImage::from_fn(40, 40, |x, _| {
+                if (x / 4) % 2 == 0 { Rgba([30, 30, 30, 255]) } else { Rgba([220, 220, 220, 255]) }
+            });
+            stripes.save(&path).unwrap();
+        }
+        let mut layout = Layout::new();
+        layout.page.output_sharpen = page_sharpen;
+        let mut img = PlacedImage::new(path, 40, 40);
+        (img.x_mm, img.y_mm, img.width_mm, img.height_mm) = (10.0, 10.0, 40.0, 40.0);
+        img.output_sharpen = image_sharpen;
+        layout.add_image(img);
+        layout
+    }
+
+    #[test]
+    fn test_processed_image_matches_page_render() {
+        let dir = std::env::temp_dir().join(format!("print_layout_processed_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut layout = striped_layout(&dir, 0.5, None);
+        let img = &mut layout.images[0];
+        img.flip_horizontal = true;
+        img.crop = Some(Crop { x: 0.1, y: 0.0, width: 0.5, height: 1.0 });
+        (img.width_mm, img.height_mm) = (20.0, 40.0);
+        let img = layout.images[0].clone();
+
+        let dpi = 50;
+        let path = dir.join("out.png");
+        export_processed_image(&img, &layout.page, dpi, ResampleFilter::Auto, ExportFormat::Png, &path).unwrap();
+        let exported = image::open(&path).unwrap().to_rgba8();
+
+        let page = render_layout_to_image(&layout, dpi, ResampleFilter::Auto, None).unwrap();
+        let x = ((img.x_mm / 25.4) * dpi as f32) as u32;
+        let y = ((img.y_mm / 25.4) * dpi as f32) as u32;
+        let composited = image::imageops::crop_imm(&page, x, y, exported.width(), exported.height()).to_image();
+        assert_eq!(exported.dimensions(), (39, 78));
+        assert_eq!(exported, composited);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_processed_file_names_are_unique() {
+        let mut named = PlacedImage::new(PathBuf::from("/a/beach.jpg"), 10, 10);
+        named.display_name = Some("Day 1/Beach".to_string());
+        let images = [
+            PlacedImage::new(PathBuf::from("/a/beach.jpg"), 10, 10),
+            PlacedImage::new(PathBuf::from("/b/Beach.png"), 10, 10),
+            PlacedImage::new(PathBuf::from("/a/beach.jpg"), 10, 10),
+            named,
+        ];
+        let refs: Vec<&PlacedImage> = images.iter().collect();
+        assert_eq!(
+            processed_file_names(&refs, ExportFormat::Jpeg),
+            vec![
+                "beach_processed.jpg",
+                "Beach_processed_2.jpg",
+                "beach_processed_3.jpg",
+                "Day 1_Beach_processed.jpg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_sharpen_override_and_zero() {
+        let dir = std::env::temp_dir().join(format!("print_layout_sharpen_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let render = |page: f32, image: Option<f32>| {
+            render_layout_to_image(&striped_layout(&dir, page, image), 50, ResampleFilter::Auto, None).unwrap().into_raw()
+        };
+
+        let plain = render(0.0, None);
+        // 0.0 is no processing at all, even when it overrides a sharpening page
+        assert_eq!(render(0.8, Some(0.0)), plain);
+        // The override wins over the page setting
+        assert_eq!(render(0.0, Some(-0.6)), render(0.9, Some(-0.6)));
+        assert_ne!(render(0.8, None), plain);
+        assert_ne!(render(0.0, Some(-0.6)), plain);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let page = Page::default();
+        let mut img = PlacedImage::new(PathBuf::from("a.jpg"), 10, 10);
+        assert_eq!(img.effective_output_sharpen(&page), 0.0);
+        img.output_sharpen = Some(2.0);
+        assert_eq!(img.effective_output_sharpen(&page), 1.0);
+    }
+
+    #[test]
+    fn test_ink_coverage_of_plain_colors() {
+        let white = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        assert_eq!(ink_coverage(&white), InkCoverage::default());
+
+        // Half black, half pure red (magenta + yellow)
+        let mut page = RgbaImage::from_pixel(4, 2, Rgba([0, 0, 0, 255]));
+        for x in 0..4 {
+            page.put_pixel(x, 1, Rgba([255, 0, 0, 255]));
+        }
+        let coverage = ink_coverage(&page);
+        assert_eq!(coverage, InkCoverage { cyan: 0.0, magenta: 0.5, yellow: 0.5, black: 0.5 });
+
+        // A light page uses less ink than a dark one
+        let light = RgbaImage::from_pixel(2, 2, Rgba([220, 220, 220, 255]));
+        let dark = RgbaImage::from_pixel(2, 2, Rgba([60, 60, 60, 255]));
+        assert!(ink_coverage(&light).black < ink_coverage(&dark).black);
+    }
+
+    #[test]
+    fn test_layout_index_describes_page_and_images() {
+        let mut layout = Layout::new();
+        let mut img = PlacedImage::new(PathBuf::from("/photos/a.jpg"), 3000, 2000);
+        img.width_mm = 127.0;
+        img.height_mm = 254.0;
+        img.rotation_degrees = 90.0;
+        img.crop = Some(Crop { x: 0.1, y: 0.0, width: 0.8, height: 1.0 });
+        layout.add_image(img);
+
+        let index = layout_index(&layout);
+        assert_eq!(index.page.paper_size, layout.page.paper_size.to_string());
+        assert_eq!(index.page.margins_mm, [25.4; 4]);
+        assert_eq!(index.images.len(), 1);
+        let entry = &index.images[0];
+        assert_eq!(entry.source_path, PathBuf::from("/photos/a.jpg"));
+        assert_eq!(entry.name, "a.jpg");
+        assert_eq!(entry.rotation_degrees, 90.0);
+        // Turned a quarter, the 2000px side runs across: 80% of it over 5
+        // inches, and the 3000px side over 10 inches down
+        assert!((entry.effective_dpi.0 - 320.0).abs() < 0.5);
+        assert!((entry.effective_dpi.1 - 300.0).abs() < 0.5);
+
+        let json: serde_json::Value = serde_json::to_value(&index).unwrap();
+        assert!((json["images"][0]["crop"]["width"].as_f64().unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(json["page"]["borderless"], false);
+    }
+
+    #[test]
+    fn test_write_layout_index_sidecar() {
+        let dir = std::env::temp_dir().join(format!("print_layout_index_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("sheet.png");
+        let index_path = index_path_for(&output);
+        assert_eq!(index_path, dir.join("sheet.index.json"));
+
+        write_layout_index(&Layout::new(), &index_path).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&index_path).unwrap()).unwrap();
+        assert!(written["images"].as_array().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_image_pages_pdf_structure() {
+        // Stand-in bytes; kept ASCII so offsets line up in the text view
+        let sheet = |page_size| PdfSheet { jpeg: b"JPEGDATA".to_vec(), pixels: (2, 3), page_size };
+        let pdf = image_pages_pdf(&[sheet((288.0, 432.0)), sheet((432.0, 288.0))]);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Kids [3 0 R 6 0 R] /Count 2"));
+        assert!(text.contains("/MediaBox [0 0 288.00 432.00]"));
+        assert!(text.contains("/MediaBox [0 0 432.00 288.00] /Resources << /XObject << /Im0 7 0 R >> >> /Contents 8 0 R"));
+        assert!(text.contains("/Width 2 /Height 3"));
+
+        // Every xref entry points at the start of its object
+        let xref_start: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        let entries: Vec<&str> = text[xref_start..].lines().skip(3).take(8).collect();
+        assert_eq!(entries.len(), 8);
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    fn sample_option() -> PrinterOption {
+        PrinterOption {
+            name: "MediaType".to_string(),
+            display_name: "Media Type".to_string(),
+            values: vec![
+                PrinterOptionValue { value: "Plain".to_string(), is_default: false },
+                PrinterOptionValue { value: "Glossy".to_string(), is_default: true },
+            ],
+            default_index: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_printer_default_choice_is_first() {
+        let option = sample_option();
+        let choices = option.choices();
+        assert_eq!(choices.len(), 3);
+        assert_eq!(choices[0], OptionChoice::PrinterDefault(Some("Glossy".to_string())));
+        assert_eq!(choices[0].to_string(), "(printer default: Glossy)");
+        assert_eq!(choices[0].value(), None);
+        assert_eq!(option.choice_for(None), choices[0]);
+        assert_eq!(option.choice_for(Some("Plain")), OptionChoice::Value("Plain".to_string()));
+    }
+
+    #[test]
+    fn test_no_options_emitted_for_defaults() {
+        let options = build_extra_options(&[
+            ("InputSlot", None),
+            ("MediaType", None),
+            ("Resolution", None),
+            ("Duplex", None),
+        ]);
+        assert!(options.is_empty());
+    }
+
+    #[test]
+    fn test_explicit_options_emitted() {
+        let options = build_extra_options(&[
+            ("InputSlot", Some("ByPassTray")),
+            ("MediaType", None),
+            ("Duplex", Some("DuplexNoTumble")),
+        ]);
+        assert_eq!(
+            options,
+            vec![
+                ("InputSlot".to_string(), "ByPassTray".to_string()),
+                ("Duplex".to_string(), "DuplexNoTumble".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fit_to_page_only_for_fit_mode() {
+        assert!(!scaling_options(PrintScaling::ActualSize, 300).contains(&"fit-to-page".to_string()));
+        assert!(!scaling_options(PrintScaling::FillPage, 300).contains(&"fit-to-page".to_string()));
+        assert!(scaling_options(PrintScaling::FitToPage, 300).contains(&"fit-to-page".to_string()));
+    }
+
+    #[test]
+    fn test_actual_size_pins_resolution() {
+        let options = scaling_options(PrintScaling::ActualSize, 600);
+        assert!(options.contains(&"print-scaling=none".to_string()));
+        assert!(options.contains(&"ppi=600".to_string()));
+    }
+
+    fn lp_strings(options: Vec<(String, String)>) -> Vec<String> {
+        options.into_iter().map(|(name, value)| format!("{}={}", name, value)).collect()
+    }
+
+    #[test]
+    fn test_page_settings_fall_back_to_ipp_attributes() {
+        let mut page = Page::default();
+        let media = [
+            (PaperType::Plain, "media-type=stationery"),
+            (PaperType::SuperHighGloss, "media-type=photographic-high-gloss"),
+            (PaperType::Glossy, "media-type=photographic-glossy"),
+            (PaperType::SemiGloss, "media-type=photographic-semi-gloss"),
+            (PaperType::Matte, "media-type=photographic-matte"),
+            (PaperType::FineArt, "media-type=photographic-matte"),
+        ];
+        for (paper_type, expected) in media {
+            page.paper_type = paper_type;
+            assert_eq!(lp_strings(page_setting_options(&page, None, &[]))[0], expected);
+        }
+        let quality = [
+            (PrintQuality::Highest, "print-quality=5"),
+            (PrintQuality::High, "print-quality=5"),
+            (PrintQuality::Standard, "print-quality=4"),
+            (PrintQuality::Draft, "print-quality=3"),
+        ];
+        for (print_quality, expected) in quality {
+            page.print_quality = print_quality;
+            assert_eq!(lp_strings(page_setting_options(&page, None, &[]))[1], expected);
+        }
+        let color = [
+            (ColorMode::UseICCProfile, "print-color-mode=color"),
+            (ColorMode::DriverMatching, "print-color-mode=color"),
+            (ColorMode::NoColorCorrection, "print-color-mode=color"),
+            (ColorMode::BlackAndWhite, "print-color-mode=monochrome"),
+        ];
+        for (color_mode, expected) in color {
+            page.color_mode = color_mode;
+            assert_eq!(lp_strings(page_setting_options(&page, None, &[]))[2], expected);
+        }
+    }
+
+    #[test]
+    fn test_page_settings_use_the_printers_own_values() {
+        let option = |name: &str, values: &[&str]| PrinterOption {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            values: values
+                .iter()
+                .map(|v| PrinterOptionValue { value: v.to_string(), is_default: false })
+                .collect(),
+            default_index: None,
+        };
+        let caps = PrinterCapabilities {
+            printer_name: "Canon".to_string(),
+            options: vec![
+                option("MediaType", &["PlainPaper", "Photo-Plus-Gloss2", "MattePhotoPaper"]),
+                option("cupsPrintQuality", &["Draft", "Normal", "High"]),
+                option("ColorModel", &["RGB", "Gray"]),
+            ],
+        };
+        let mut page = Page {
+            paper_type: PaperType::Glossy,
+            print_quality: PrintQuality::Highest,
+            color_mode: ColorMode::BlackAndWhite,
+            ..Page::default()
+        };
+        assert_eq!(
+            lp_strings(page_setting_options(&page, Some(&caps), &[])),
+            vec!["MediaType=Photo-Plus-Gloss2", "cupsPrintQuality=High", "ColorModel=Gray"]
+        );
+
+        // Fine art falls back to the closest matte value the driver has
+        page.paper_type = PaperType::FineArt;
+        page.print_quality = PrintQuality::Draft;
+        page.color_mode = ColorMode::UseICCProfile;
+        assert_eq!(
+            lp_strings(page_setting_options(&page, Some(&caps), &[])),
+            vec!["MediaType=MattePhotoPaper", "cupsPrintQuality=Draft", "ColorModel=RGB"]
+        );
+
+        // An explicit CUPS selection wins over the mapped value
+        let explicit = vec![("MediaType".to_string(), "PlainPaper".to_string())];
+        let options = lp_strings(page_setting_options(&page, Some(&caps), &explicit));
+        assert!(!options.iter().any(|o| o.starts_with("MediaType=")));
+
+        // Nothing fitting is offered: leave the printer default alone
+        let caps = PrinterCapabilities {
+            printer_name: "Office".to_string(),
+            options: vec![option("cupsPrintQuality", &["Normal"])],
+        };
+        page.print_quality = PrintQuality::Highest;
+        let options = lp_strings(page_setting_options(&page, Some(&caps), &[]));
+        assert!(!options.iter().any(|o| o.contains("Quality") || o.contains("quality")));
+    }
+
+    #[test]
+    fn test_pick_lists_follow_the_printers_options() {
+        let option = |name: &str, values: &[&str]| PrinterOption {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            values: values
+                .iter()
+                .map(|v| PrinterOptionValue { value: v.to_string(), is_default: false })
+                .collect(),
+            default_index: None,
+        };
+        // Nothing reported: everything is offered
+        let unknown = PrinterCapabilities::default();
+        assert!(printer_supports_paper_size(&unknown, PaperSize::Photo13x19));
+        assert_eq!(supported_paper_types(&unknown), PaperType::ALL.to_vec());
+
+        let caps = PrinterCapabilities {
+            printer_name: "Photo".to_string(),
+            options: vec![
+                option("PageSize", &["A4", "Letter.Borderless", "3.5x5", "w360h504"]),
+                option("MediaType", &["Plain", "GlossyPaper"]),
+                option("cupsPrintQuality", &["Normal", "High"]),
+            ],
+        };
+        assert!(printer_supports_paper_size(&caps, PaperSize::A4));
+        assert!(printer_supports_paper_size(&caps, PaperSize::Letter));
+        assert!(printer_supports_paper_size(&caps, PaperSize::Photo3_5x5));
+        // 360 × 504 points is 5 × 7 inches
+        assert!(printer_supports_paper_size(&caps, PaperSize::Photo5x7));
+        assert!(!printer_supports_paper_size(&caps, PaperSize::A3));
+        assert!(!printer_supports_paper_size(&caps, PaperSize::Photo4x6));
+
+        assert_eq!(supported_paper_types(&caps), vec![PaperType::Plain, PaperType::Glossy]);
+        assert_eq!(
+            supported_print_qualities(&caps),
+            vec![PrintQuality::Highest, PrintQuality::High, PrintQuality::Standard]
+        );
+        // Letter is the widest listed and A4 the longest
+        let (short, long) = largest_paper_mm(&caps).unwrap();
+        assert!((short - 215.9).abs() < 0.01 && (long - 297.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_custom_paper_is_sent_as_custom_media() {
+        let mut page = Page::default();
+        page.set_paper_size(PaperSize::A4, false);
+        assert_eq!(media_option(&page), "media=A4");
+        page.set_paper_size(PaperSize::Custom(101.6, 250.0), false);
+        assert_eq!(media_option(&page), "media=Custom.101.6x250mm");
+        // Landscape pages still name the paper portrait side first
+        page.set_orientation(crate::layout::Orientation::Landscape);
+        assert_eq!(media_option(&page), "media=Custom.101.6x250mm");
+        // Sizes CUPS has no name for aren't sent as A4
+        page.set_orientation(crate::layout::Orientation::Portrait);
+        page.set_paper_size(PaperSize::Photo5x5, false);
+        assert_eq!(media_option(&page), "media=Custom.127x127mm");
+    }
+
+    #[test]
+    fn test_neutral_toning_is_plain_grayscale() {
+        let mut img: RgbaImage = ImageBuffer::from_pixel(2, 1, Rgba([200, 40, 90, 255]));
+        img.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+        apply_toning(&mut img, Toning::Neutral);
+        let p = img.get_pixel(0, 0);
+        assert_eq!(p[0], p[1]);
+        assert_eq!(p[1], p[2]);
+        assert_eq!(img.get_pixel(1, 0), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_sepia_toning_warms_midtones() {
+        let mut img: RgbaImage = ImageBuffer::from_pixel(1, 1, Rgba([128, 128, 128, 255]));
+        apply_toning(&mut img, Toning::Sepia);
+        let p = img.get_pixel(0, 0);
+        assert!(p[0] > p[2], "sepia midtone should be warmer than neutral: {:?}", p);
+        assert_eq!(p[3], 255);
+    }
+
+    #[test]
+    fn test_cyanotype_toning_cools_shadows() {
+        let mut img: RgbaImage = ImageBuffer::from_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        apply_toning(&mut img, Toning::Cyanotype);
+        let p = img.get_pixel(0, 0);
+        assert!(p[2] > p[0]);
+    }
 
     #[test]
     fn test_printer_discovery() {
@@ -0,0 +1,92 @@
+// quick_open.rs - Ctrl+K palette of recent files, presets and image styles
+// Entries are filtered with a fuzzy subsequence match as the user types.
+
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+/// What choosing a palette entry does
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteItem {
+    /// Open a recently used layout file
+    RecentFile(PathBuf),
+    /// Apply a print preset (index into the saved presets)
+    Preset(usize),
+    /// Apply an image style to the selected image (index into the saved styles)
+    ImageStyle(usize),
+}
+
+/// One row of the palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    pub item: PaletteItem,
+    /// Text matched against the query
+    pub label: String,
+    /// Shown dimmed next to the label: the folder or the kind of entry
+    pub detail: String,
+}
+
+/// Score of `query` as a case-insensitive subsequence of `text`, higher is
+/// better; None if it doesn't match. Consecutive letters and letters at the
+/// start of a word score extra, so "bw" ranks "Black White" above "bowl".
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Entries matching `query`, best first; ties keep their original order.
+/// An empty query matches everything.
+pub fn filter_entries<'a>(entries: &'a [PaletteEntry], query: &str) -> Vec<&'a PaletteEntry> {
+    let mut matches: Vec<(u32, &PaletteEntry)> = entries
+        .iter()
+        .filter_map(|entry| fuzzy_score(query, &entry.label).map(|score| (score, entry)))
+        .collect();
+    matches.sort_by_key(|&(score, _)| Reverse(score));
+    matches.into_iter().map(|(_, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str) -> PaletteEntry {
+        PaletteEntry { item: PaletteItem::Preset(0), label: label.to_string(), detail: String::new() }
+    }
+
+    #[test]
+    fn test_fuzzy_score_matches_subsequences() {
+        assert!(fuzzy_score("hol", "Holiday 2026.pxl").is_some());
+        assert!(fuzzy_score("HD", "holiday").is_some());
+        assert!(fuzzy_score("hdx", "holiday").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        // Word starts and runs beat scattered letters
+        assert!(fuzzy_score("bw", "Black White").unwrap() > fuzzy_score("bw", "bowl").unwrap());
+        assert!(fuzzy_score("glo", "Glossy A4").unwrap() > fuzzy_score("glo", "Big Color").unwrap());
+    }
+
+    #[test]
+    fn test_filter_entries_ranks_and_keeps_order_for_ties() {
+        let entries = vec![entry("Matte Letter"), entry("Glossy 4x6"), entry("Glossy A4")];
+        let labels = |query: &str| -> Vec<String> {
+            filter_entries(&entries, query).into_iter().map(|e| e.label.clone()).collect()
+        };
+        assert_eq!(labels(""), vec!["Matte Letter", "Glossy 4x6", "Glossy A4"]);
+        assert_eq!(labels("glossy"), vec!["Glossy 4x6", "Glossy A4"]);
+        assert_eq!(labels("ga4"), vec!["Glossy A4"]);
+        assert!(labels("zzz").is_empty());
+    }
+}
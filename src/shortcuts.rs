@@ -0,0 +1,188 @@
+// shortcuts.rs - Keys that act on the canvas and its selection
+// A key press becomes a Shortcut here; the app still checks that no text
+// field has keyboard focus before acting on it, so typing a width never
+// deletes or moves an image. SHORTCUT_HELP lists every binding for the F1
+// overlay, including the Ctrl shortcuts handled directly by the app.
+
+use iced::keyboard::{key::Named, Key, Modifiers};
+
+/// Distance an arrow key moves the selection, in mm
+pub const NUDGE_STEP_MM: f32 = 1.0;
+
+/// Distance an arrow key moves the selection with Shift held, in mm
+pub const NUDGE_STEP_LARGE_MM: f32 = 10.0;
+
+/// Distance an arrow key moves the selection with Ctrl held, in mm
+pub const NUDGE_STEP_FINE_MM: f32 = 0.1;
+
+/// Every key binding as (keys, what they do), shown by the F1 overlay
+pub const SHORTCUT_HELP: &[(&str, &str)] = &[
+    ("Arrow keys", "Move the selection 1 mm"),
+    ("Shift + Arrow keys", "Move the selection 10 mm"),
+    ("Ctrl + Arrow keys", "Move the selection 0.1 mm"),
+    ("Delete / Backspace", "Delete the selection"),
+    ("Escape", "Clear the selection"),
+    ("R / Shift + R", "Rotate 90° clockwise / counter-clockwise"),
+    ("Ctrl + D", "Duplicate the selection"),
+    ("Ctrl + Z", "Undo"),
+    ("Ctrl + Y / Ctrl + Shift + Z", "Redo"),
+    ("+ / -", "Zoom in / out"),
+    ("Ctrl + 1 / 2 / 3", "Fit the page / its width / its height"),
+    ("Q", "Draft preview on / off"),
+    ("Ctrl + S", "Save"),
+    ("Ctrl + O", "Open"),
+    ("Ctrl + V", "Paste copied image files"),
+    ("Ctrl + K", "Quick open"),
+    ("Ctrl + B", "Show / hide the settings panel"),
+    ("Ctrl + Shift + L", "View-only mode"),
+    ("F1", "Show / hide this list"),
+];
+
+/// Canvas action bound to a key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shortcut {
+    /// Delete or Backspace: remove the selected images
+    Delete,
+    /// R rotates 90° clockwise, Shift+R counter-clockwise
+    Rotate { clockwise: bool },
+    /// Arrow keys: move the selection by (dx, dy) mm
+    Nudge(f32, f32),
+    /// Q switches the canvas between draft and full quality
+    ToggleDraftPreview,
+    /// Escape: select nothing
+    Deselect,
+    /// Ctrl+D: copy the selection
+    Duplicate,
+    /// Ctrl+Z
+    Undo,
+    /// Ctrl+Y or Ctrl+Shift+Z
+    Redo,
+    /// + zooms in, - zooms out
+    Zoom { zoom_in: bool },
+    /// F1 shows or hides the list of shortcuts
+    ToggleHelp,
+}
+
+impl Shortcut {
+    /// Whether the shortcut acts on the selection, so needs one
+    pub fn needs_selection(&self) -> bool {
+        matches!(self, Shortcut::Delete | Shortcut::Rotate { .. } | Shortcut::Nudge(..) | Shortcut::Duplicate)
+    }
+
+    /// Whether the shortcut changes the layout, so is ignored in view-only mode
+    pub fn edits_layout(&self) -> bool {
+        self.needs_selection() || matches!(self, Shortcut::Undo | Shortcut::Redo)
+    }
+}
+
+/// The shortcut for a key press, if any. `captured` is true when a widget
+/// already handled the key: a focused text field takes Delete, Backspace,
+/// letters and Left/Right itself. Keys held with Alt, and Ctrl (Cmd on
+/// macOS) keys other than those below, belong to other bindings.
+pub fn shortcut_for(key: &Key, modifiers: Modifiers, captured: bool) -> Option<Shortcut> {
+    if captured || modifiers.alt() {
+        return None;
+    }
+    if modifiers.command() {
+        return command_shortcut_for(key, modifiers);
+    }
+    if modifiers.control() || modifiers.logo() {
+        return None;
+    }
+    let step = if modifiers.shift() { NUDGE_STEP_LARGE_MM } else { NUDGE_STEP_MM };
+    match key.as_ref() {
+        Key::Named(Named::Delete | Named::Backspace) => Some(Shortcut::Delete),
+        Key::Named(Named::Escape) => Some(Shortcut::Deselect),
+        Key::Named(Named::F1) => Some(Shortcut::ToggleHelp),
+        Key::Named(Named::ArrowLeft) => Some(Shortcut::Nudge(-step, 0.0)),
+        Key::Named(Named::ArrowRight) => Some(Shortcut::Nudge(step, 0.0)),
+        Key::Named(Named::ArrowUp) => Some(Shortcut::Nudge(0.0, -step)),
+        Key::Named(Named::ArrowDown) => Some(Shortcut::Nudge(0.0, step)),
+        Key::Character(c) if c.eq_ignore_ascii_case("r") => {
+            Some(Shortcut::Rotate { clockwise: !modifiers.shift() })
+        }
+        Key::Character(c) if c.eq_ignore_ascii_case("q") => Some(Shortcut::ToggleDraftPreview),
+        // "=" is + without Shift on most layouts
+        Key::Character("+" | "=") => Some(Shortcut::Zoom { zoom_in: true }),
+        Key::Character("-") => Some(Shortcut::Zoom { zoom_in: false }),
+        _ => None,
+    }
+}
+
+/// Shortcuts held with Ctrl (Cmd on macOS)
+fn command_shortcut_for(key: &Key, modifiers: Modifiers) -> Option<Shortcut> {
+    let step = NUDGE_STEP_FINE_MM;
+    match key.as_ref() {
+        Key::Named(Named::ArrowLeft) => Some(Shortcut::Nudge(-step, 0.0)),
+        Key::Named(Named::ArrowRight) => Some(Shortcut::Nudge(step, 0.0)),
+        Key::Named(Named::ArrowUp) => Some(Shortcut::Nudge(0.0, -step)),
+        Key::Named(Named::ArrowDown) => Some(Shortcut::Nudge(0.0, step)),
+        Key::Character(c) if c.eq_ignore_ascii_case("d") => Some(Shortcut::Duplicate),
+        Key::Character(c) if c.eq_ignore_ascii_case("z") => {
+            Some(if modifiers.shift() { Shortcut::Redo } else { Shortcut::Undo })
+        }
+        Key::Character(c) if c.eq_ignore_ascii_case("y") => Some(Shortcut::Redo),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(key: Named) -> Key {
+        Key::Named(key)
+    }
+
+    #[test]
+    fn test_keys_map_to_shortcuts() {
+        let none = Modifiers::default();
+        assert_eq!(shortcut_for(&named(Named::Delete), none, false), Some(Shortcut::Delete));
+        assert_eq!(shortcut_for(&named(Named::Backspace), none, false), Some(Shortcut::Delete));
+        assert_eq!(shortcut_for(&named(Named::ArrowUp), none, false), Some(Shortcut::Nudge(0.0, -1.0)));
+        assert_eq!(
+            shortcut_for(&named(Named::ArrowRight), Modifiers::SHIFT, false),
+            Some(Shortcut::Nudge(10.0, 0.0))
+        );
+        assert_eq!(
+            shortcut_for(&Key::Character("r".into()), none, false),
+            Some(Shortcut::Rotate { clockwise: true })
+        );
+        assert_eq!(
+            shortcut_for(&Key::Character("R".into()), Modifiers::SHIFT, false),
+            Some(Shortcut::Rotate { clockwise: false })
+        );
+        assert_eq!(shortcut_for(&Key::Character("q".into()), none, false), Some(Shortcut::ToggleDraftPreview));
+        assert_eq!(shortcut_for(&named(Named::Escape), none, false), Some(Shortcut::Deselect));
+        assert_eq!(shortcut_for(&named(Named::F1), none, false), Some(Shortcut::ToggleHelp));
+        assert_eq!(shortcut_for(&Key::Character("=".into()), none, false), Some(Shortcut::Zoom { zoom_in: true }));
+        assert_eq!(shortcut_for(&Key::Character("-".into()), none, false), Some(Shortcut::Zoom { zoom_in: false }));
+        assert_eq!(shortcut_for(&Key::Character("x".into()), none, false), None);
+    }
+
+    #[test]
+    fn test_ctrl_keys_map_to_shortcuts() {
+        let ctrl = Modifiers::COMMAND;
+        assert_eq!(
+            shortcut_for(&named(Named::ArrowLeft), ctrl, false),
+            Some(Shortcut::Nudge(-NUDGE_STEP_FINE_MM, 0.0))
+        );
+        assert_eq!(shortcut_for(&Key::Character("d".into()), ctrl, false), Some(Shortcut::Duplicate));
+        assert_eq!(shortcut_for(&Key::Character("z".into()), ctrl, false), Some(Shortcut::Undo));
+        assert_eq!(shortcut_for(&Key::Character("Z".into()), ctrl | Modifiers::SHIFT, false), Some(Shortcut::Redo));
+        assert_eq!(shortcut_for(&Key::Character("y".into()), ctrl, false), Some(Shortcut::Redo));
+        // Save, open and the rest are bound by the app itself
+        assert_eq!(shortcut_for(&Key::Character("s".into()), ctrl, false), None);
+    }
+
+    #[test]
+    fn test_keys_taken_by_a_widget_or_held_with_ctrl_are_ignored() {
+        // A focused text field deletes a character instead
+        assert_eq!(shortcut_for(&named(Named::Delete), Modifiers::default(), true), None);
+        assert_eq!(shortcut_for(&Key::Character("r".into()), Modifiers::default(), true), None);
+        // Ctrl+R and Alt+Arrow are left for other bindings
+        assert_eq!(shortcut_for(&Key::Character("r".into()), Modifiers::CTRL, false), None);
+        assert_eq!(shortcut_for(&named(Named::ArrowLeft), Modifiers::ALT, false), None);
+        assert_eq!(shortcut_for(&Key::Character("z".into()), Modifiers::COMMAND, true), None);
+    }
+}
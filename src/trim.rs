@@ -0,0 +1,156 @@
+// trim.rs - Border detection for scanned photos and graphics
+// Finds the near-white margins a scanner leaves around a photo, or the
+// transparent padding around an exported logo, so they can be cropped away.
+
+use image::RgbaImage;
+
+/// Default brightness (0-255) at or above which a pixel counts as white
+pub const DEFAULT_WHITE_THRESHOLD: u8 = 240;
+
+/// Alpha (0-255) at or below which a pixel counts as transparent padding
+const TRANSPARENT_ALPHA: u8 = 8;
+
+/// Share of pixels in a row or column allowed to be darker than the
+/// threshold while still counting as border (dust and scanner noise)
+const NOISE_TOLERANCE: f32 = 0.01;
+
+/// Pixel rectangle (x, y, width, height) left after removing near-white
+/// borders from each edge. A row or column is border when nearly all of its
+/// pixels have every color channel at or above `threshold`. Returns `None`
+/// when there is no border to remove or the image is entirely white.
+pub fn detect_white_border(image: &RgbaImage, threshold: u8) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let is_white = |x: u32, y: u32| {
+        let p = image.get_pixel(x, y);
+        p[0] >= threshold && p[1] >= threshold && p[2] >= threshold
+    };
+    let row_is_border = |y: u32| {
+        let dark = (0..width).filter(|&x| !is_white(x, y)).count();
+        dark as f32 <= width as f32 * NOISE_TOLERANCE
+    };
+    let column_is_border = |x: u32, top: u32, bottom: u32| {
+        let dark = (top..bottom).filter(|&y| !is_white(x, y)).count();
+        dark as f32 <= (bottom - top) as f32 * NOISE_TOLERANCE
+    };
+
+    let top = (0..height).find(|&y| !row_is_border(y))?;
+    let bottom = (0..height).rev().find(|&y| !row_is_border(y))? + 1;
+    let left = (0..width).find(|&x| !column_is_border(x, top, bottom))?;
+    let right = (0..width).rev().find(|&x| !column_is_border(x, top, bottom))? + 1;
+
+    let trimmed = (left, top, right - left, bottom - top);
+    if trimmed == (0, 0, width, height) {
+        None
+    } else {
+        Some(trimmed)
+    }
+}
+
+/// Pixel rectangle (x, y, width, height) around the visible pixels of a
+/// graphic, without its transparent padding. Returns `None` when no edge is
+/// padded or the image is entirely transparent.
+pub fn detect_transparent_border(image: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = image.dimensions();
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, p) in image.enumerate_pixels() {
+        if p[3] > TRANSPARENT_ALPHA {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+    }
+    if left == u32::MAX {
+        return None;
+    }
+    let trimmed = (left, top, right - left, bottom - top);
+    (trimmed != (0, 0, width, height)).then_some(trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// White image with a dark photo area at (x, y, width, height)
+    fn scan(size: (u32, u32), photo: (u32, u32, u32, u32)) -> RgbaImage {
+        let (px, py, pw, ph) = photo;
+        RgbaImage::from_fn(size.0, size.1, |x, y| {
+            if x >= px && x < px + pw && y >= py && y < py + ph {
+                Rgba([90, 60, 40, 255])
+            } else {
+                Rgba([250, 252, 248, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn test_uneven_borders_are_found() {
+        let image = scan((200, 100), (12, 5, 170, 80));
+        assert_eq!(detect_white_border(&image, DEFAULT_WHITE_THRESHOLD), Some((12, 5, 170, 80)));
+    }
+
+    #[test]
+    fn test_no_border_or_all_white() {
+        let full = scan((50, 40), (0, 0, 50, 40));
+        assert_eq!(detect_white_border(&full, DEFAULT_WHITE_THRESHOLD), None);
+        let blank = scan((50, 40), (0, 0, 0, 0));
+        assert_eq!(detect_white_border(&blank, DEFAULT_WHITE_THRESHOLD), None);
+    }
+
+    #[test]
+    fn test_specks_in_border_are_ignored() {
+        let mut image = scan((200, 200), (20, 20, 160, 160));
+        // A single dust speck in the top border
+        image.put_pixel(100, 3, Rgba([0, 0, 0, 255]));
+        assert_eq!(detect_white_border(&image, DEFAULT_WHITE_THRESHOLD), Some((20, 20, 160, 160)));
+    }
+
+    #[test]
+    fn test_threshold_decides_what_counts_as_white() {
+        // Off-white (230) border is kept at the default threshold, trimmed at a lower one
+        let image = RgbaImage::from_fn(60, 60, |x, y| {
+            if (10..50).contains(&x) && (10..50).contains(&y) {
+                Rgba([20, 20, 20, 255])
+            } else {
+                Rgba([230, 230, 230, 255])
+            }
+        });
+        assert_eq!(detect_white_border(&image, DEFAULT_WHITE_THRESHOLD), None);
+        assert_eq!(detect_white_border(&image, 220), Some((10, 10, 40, 40)));
+    }
+
+    /// Transparent image with an opaque logo at (x, y, width, height)
+    fn padded_logo(size: (u32, u32), logo: (u32, u32, u32, u32)) -> RgbaImage {
+        let (lx, ly, lw, lh) = logo;
+        RgbaImage::from_fn(size.0, size.1, |x, y| {
+            if x >= lx && x < lx + lw && y >= ly && y < ly + lh {
+                Rgba([200, 30, 30, 255])
+            } else {
+                Rgba([255, 255, 255, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn test_transparent_padding_is_found() {
+        let logo = padded_logo((400, 300), (150, 40, 60, 25));
+        assert_eq!(detect_transparent_border(&logo), Some((150, 40, 60, 25)));
+
+        // Nearly invisible anti-aliasing haze counts as padding, faint edges don't
+        let mut hazy = logo.clone();
+        hazy.put_pixel(5, 5, Rgba([0, 0, 0, 3]));
+        hazy.put_pixel(149, 40, Rgba([200, 30, 30, 60]));
+        assert_eq!(detect_transparent_border(&hazy), Some((149, 40, 61, 25)));
+    }
+
+    #[test]
+    fn test_opaque_or_empty_graphics_are_left_alone() {
+        assert_eq!(detect_transparent_border(&padded_logo((50, 40), (0, 0, 50, 40))), None);
+        assert_eq!(detect_transparent_border(&padded_logo((50, 40), (0, 0, 0, 0))), None);
+        assert_eq!(detect_transparent_border(&RgbaImage::new(0, 0)), None);
+    }
+}
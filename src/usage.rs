@@ -0,0 +1,225 @@
+// usage.rs - Paper usage statistics
+// Every completed print job is recorded as a UsageEntry; the Usage dialog
+// summarizes them per printer and per paper type over a time range.
+
+use crate::layout::{PaperSize, PaperType};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// One completed print job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub time: DateTime<Utc>,
+    pub printer: String,
+    pub paper_size: PaperSize,
+    pub paper_type: PaperType,
+    /// Sheets of paper used (pages × copies)
+    pub sheets: u32,
+    /// Job title sent to CUPS; empty for jobs recorded before titles existed
+    #[serde(default)]
+    pub title: String,
+}
+
+/// Time range shown in the Usage dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageRange {
+    Last7Days,
+    #[default]
+    Last30Days,
+    ThisYear,
+    AllTime,
+}
+
+impl UsageRange {
+    pub const ALL: [UsageRange; 4] = [
+        UsageRange::Last7Days,
+        UsageRange::Last30Days,
+        UsageRange::ThisYear,
+        UsageRange::AllTime,
+    ];
+
+    /// Earliest time included in the range, or None for all time
+    pub fn start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            UsageRange::Last7Days => Some(now - Duration::days(7)),
+            UsageRange::Last30Days => Some(now - Duration::days(30)),
+            UsageRange::ThisYear => Utc.with_ymd_and_hms(now.year(), 1, 1, 0, 0, 0).single(),
+            UsageRange::AllTime => None,
+        }
+    }
+}
+
+impl std::fmt::Display for UsageRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsageRange::Last7Days => write!(f, "Last 7 days"),
+            UsageRange::Last30Days => write!(f, "Last 30 days"),
+            UsageRange::ThisYear => write!(f, "This year"),
+            UsageRange::AllTime => write!(f, "All time"),
+        }
+    }
+}
+
+/// Sheet and job totals for a range
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UsageSummary {
+    pub jobs: usize,
+    pub sheets: u32,
+    /// (printer, sheets), most used first
+    pub by_printer: Vec<(String, u32)>,
+    /// (paper type, sheets), most used first
+    pub by_paper_type: Vec<(PaperType, u32)>,
+    /// (paper size, sheets), most used first
+    pub by_paper_size: Vec<(PaperSize, u32)>,
+}
+
+/// Entries at or after `start` (all of them when `start` is None)
+pub fn entries_in_range(entries: &[UsageEntry], start: Option<DateTime<Utc>>) -> Vec<&UsageEntry> {
+    entries
+        .iter()
+        .filter(|e| start.map_or(true, |start| e.time >= start))
+        .collect()
+}
+
+/// Entries at or after `start`, most recent first, at most `limit` of them
+pub fn recent_entries(entries: &[UsageEntry], start: Option<DateTime<Utc>>, limit: usize) -> Vec<&UsageEntry> {
+    let mut recent = entries_in_range(entries, start);
+    recent.sort_by_key(|e| Reverse(e.time));
+    recent.truncate(limit);
+    recent
+}
+
+/// Total the entries at or after `start`
+pub fn summarize(entries: &[UsageEntry], start: Option<DateTime<Utc>>) -> UsageSummary {
+    let selected = entries_in_range(entries, start);
+    UsageSummary {
+        jobs: selected.len(),
+        sheets: selected.iter().map(|e| e.sheets).sum(),
+        by_printer: totals(&selected, |e| e.printer.clone()),
+        by_paper_type: totals(&selected, |e| e.paper_type),
+        by_paper_size: totals(&selected, |e| e.paper_size),
+    }
+}
+
+/// Sheets per key, most sheets first; ties keep first-seen order
+fn totals<K: PartialEq>(entries: &[&UsageEntry], key: impl Fn(&UsageEntry) -> K) -> Vec<(K, u32)> {
+    let mut totals: Vec<(K, u32)> = Vec::new();
+    for entry in entries {
+        let k = key(entry);
+        match totals.iter_mut().find(|(existing, _)| *existing == k) {
+            Some((_, sheets)) => *sheets += entry.sheets,
+            None => totals.push((k, entry.sheets)),
+        }
+    }
+    totals.sort_by_key(|(_, sheets)| Reverse(*sheets));
+    totals
+}
+
+/// CSV with a header row, one line per job
+pub fn to_csv(entries: &[&UsageEntry]) -> String {
+    let mut csv = String::from("time,printer,paper_size,paper_type,sheets\n");
+    for e in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            e.time.to_rfc3339(),
+            csv_field(&e.printer),
+            csv_field(&e.paper_size.to_string()),
+            csv_field(&e.paper_type.to_string()),
+            e.sheets
+        ));
+    }
+    csv
+}
+
+/// Quote a field if it contains a comma, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(days_ago: i64, printer: &str, paper_type: PaperType, sheets: u32) -> UsageEntry {
+        UsageEntry {
+            time: now() - Duration::days(days_ago),
+            printer: printer.to_string(),
+            paper_size: PaperSize::Photo4x6,
+            paper_type,
+            sheets,
+            title: String::new(),
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_summarize_totals_per_printer_and_paper() {
+        let entries = vec![
+            entry(1, "Canon", PaperType::Glossy, 2),
+            entry(3, "Epson", PaperType::Matte, 5),
+            entry(5, "Canon", PaperType::Matte, 4),
+            entry(40, "Canon", PaperType::Glossy, 10),
+        ];
+
+        let week = summarize(&entries, UsageRange::Last7Days.start(now()));
+        assert_eq!(week.jobs, 3);
+        assert_eq!(week.sheets, 11);
+        assert_eq!(week.by_printer, vec![("Canon".to_string(), 6), ("Epson".to_string(), 5)]);
+        assert_eq!(week.by_paper_type, vec![(PaperType::Matte, 9), (PaperType::Glossy, 2)]);
+        assert_eq!(week.by_paper_size, vec![(PaperSize::Photo4x6, 11)]);
+
+        let all = summarize(&entries, UsageRange::AllTime.start(now()));
+        assert_eq!((all.jobs, all.sheets), (4, 21));
+        assert_eq!(all.by_printer[0], ("Canon".to_string(), 16));
+    }
+
+    #[test]
+    fn test_range_starts() {
+        assert_eq!(
+            UsageRange::ThisYear.start(now()),
+            Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(UsageRange::Last30Days.start(now()), Some(now() - Duration::days(30)));
+        assert_eq!(UsageRange::AllTime.start(now()), None);
+        assert_eq!(summarize(&[], None), UsageSummary::default());
+    }
+
+    #[test]
+    fn test_recent_entries_newest_first() {
+        let entries = vec![
+            entry(5, "Canon", PaperType::Glossy, 1),
+            entry(1, "Epson", PaperType::Glossy, 1),
+            entry(3, "Canon", PaperType::Glossy, 1),
+        ];
+        let recent = recent_entries(&entries, None, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].time, now() - Duration::days(1));
+        assert_eq!(recent[1].time, now() - Duration::days(3));
+        assert_eq!(recent_entries(&entries, Some(now() - Duration::days(2)), 5).len(), 1);
+
+        // Entries saved before titles existed still load
+        let old = r#"{"time":"2026-06-15T12:00:00Z","printer":"Canon","paper_size":"A4","paper_type":"Plain","sheets":1}"#;
+        let parsed: UsageEntry = serde_json::from_str(old).unwrap();
+        assert_eq!(parsed.title, "");
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let entries = [entry(0, "Office, 2nd floor", PaperType::Plain, 3)];
+        let refs: Vec<&UsageEntry> = entries.iter().collect();
+        let csv = to_csv(&refs);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("time,printer,paper_size,paper_type,sheets"));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("2026-06-15T12:00:00+00:00,\"Office, 2nd floor\","));
+        assert!(row.ends_with(",Plain Paper,3"));
+    }
+}